@@ -0,0 +1,90 @@
+use crate::check::error::Error;
+use crate::cli::CheckArgs;
+use libcnb_package::buildpack_dependency_graph::build_libcnb_buildpacks_dependency_graph;
+use libcnb_package::check::check_buildpack;
+use libcnb_package::dependency_graph::get_dependencies;
+use libcnb_package::find_cargo_workspace_root_dir;
+use libcnb_package::metadata::sync_buildpack_metadata;
+
+pub(crate) fn execute(args: &CheckArgs) -> Result<(), Error> {
+    let current_dir = std::env::current_dir().map_err(Error::CannotGetCurrentDir)?;
+
+    let workspace_root_path =
+        find_cargo_workspace_root_dir(&current_dir).map_err(Error::CannotFindCargoWorkspaceRoot)?;
+
+    eprintln!("🏗️ Building buildpack dependency graph...");
+    let buildpack_dependency_graph = build_libcnb_buildpacks_dependency_graph(&workspace_root_path)
+        .map_err(Error::CannotBuildBuildpackDependencyGraph)?;
+
+    let root_nodes = buildpack_dependency_graph
+        .node_weights()
+        .find(|node| node.path == current_dir)
+        .map(|node| vec![node])
+        .or_else(|| {
+            current_dir.eq(&workspace_root_path).then(|| {
+                buildpack_dependency_graph
+                    .node_weights()
+                    .collect::<Vec<_>>()
+            })
+        })
+        .unwrap_or_default();
+
+    let buildpacks = get_dependencies(&buildpack_dependency_graph, &root_nodes)
+        .map_err(Error::CannotGetDependencies)?;
+
+    if buildpacks.is_empty() {
+        return Err(Error::NoBuildpacksFound);
+    }
+
+    let mut reports = Vec::new();
+    for node in buildpacks {
+        eprintln!("🔍 Checking {}...", node.buildpack_id);
+
+        if let Some(mut report) = check_buildpack(&node.path, true)
+            .map_err(|error| Error::CannotCheckBuildpack(node.buildpack_id.clone(), error))?
+        {
+            if args.fix && !report.metadata_drifts.is_empty() {
+                sync_buildpack_metadata(&node.path.join("buildpack.toml"), &report.metadata_drifts)
+                    .map_err(|error| {
+                        Error::CannotSyncBuildpackMetadata(node.buildpack_id.clone(), error)
+                    })?;
+
+                eprintln!("🔧 Synced buildpack.toml metadata with Cargo.toml");
+                report.metadata_drifts.clear();
+            }
+
+            reports.push(report);
+        }
+    }
+
+    eprintln!();
+    for report in &reports {
+        print!("{report}");
+    }
+
+    let reports_with_findings = reports
+        .iter()
+        .filter(|report| report.has_findings())
+        .count();
+
+    eprintln!();
+    if reports_with_findings == 0 {
+        eprintln!("✨ No findings in {} buildpack(s)!", reports.len());
+    } else {
+        eprintln!(
+            "⚠️ {reports_with_findings} of {} buildpack(s) use deprecated libcnb.rs APIs, have a different MSRV than libcnb.rs or buildpack.toml metadata that differs from Cargo.toml.",
+            reports.len()
+        );
+
+        if reports
+            .iter()
+            .any(|report| !report.metadata_drifts.is_empty())
+        {
+            eprintln!(
+                "Run `cargo libcnb check --fix` to sync buildpack.toml metadata with Cargo.toml."
+            );
+        }
+    }
+
+    Ok(())
+}
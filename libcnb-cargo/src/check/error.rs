@@ -0,0 +1,23 @@
+use libcnb_data::buildpack::BuildpackId;
+use libcnb_package::buildpack_dependency_graph::BuildBuildpackDependencyGraphError;
+use libcnb_package::check::CheckError;
+use libcnb_package::dependency_graph::GetDependenciesError;
+use libcnb_package::metadata::SyncMetadataError;
+
+#[derive(thiserror::Error, Debug)]
+pub(crate) enum Error {
+    #[error("Failed to get current dir: {0}")]
+    CannotGetCurrentDir(#[source] std::io::Error),
+    #[error("Failed to find Cargo workspace root: {0}")]
+    CannotFindCargoWorkspaceRoot(#[source] libcnb_package::FindCargoWorkspaceRootError),
+    #[error("Failed to create buildpack dependency graph: {0}")]
+    CannotBuildBuildpackDependencyGraph(#[source] BuildBuildpackDependencyGraphError),
+    #[error("Failed to get dependencies: {0}")]
+    CannotGetDependencies(#[source] GetDependenciesError<BuildpackId>),
+    #[error("Failed to check buildpack {0}: {1}")]
+    CannotCheckBuildpack(BuildpackId, #[source] CheckError),
+    #[error("Failed to sync buildpack.toml metadata of buildpack {0}: {1}")]
+    CannotSyncBuildpackMetadata(BuildpackId, #[source] SyncMetadataError),
+    #[error("No buildpacks found!")]
+    NoBuildpacksFound,
+}
@@ -0,0 +1,4 @@
+mod command;
+mod error;
+
+pub(crate) use command::execute;
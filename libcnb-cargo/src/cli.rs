@@ -1,4 +1,5 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -13,6 +14,9 @@ pub(crate) enum Cli {
 pub(crate) enum LibcnbSubcommand {
     /// Packages a libcnb.rs Cargo project as a Cloud Native Buildpack
     Package(PackageArgs),
+    /// Reports usages of deprecated libcnb.rs APIs, MSRV differences to libcnb.rs and
+    /// buildpack.toml metadata that differs from Cargo.toml
+    Check(CheckArgs),
 }
 
 #[derive(Parser)]
@@ -29,6 +33,44 @@ pub(crate) struct PackageArgs {
     /// Directory for packaged buildpacks, defaults to 'packaged' in Cargo workspace root
     #[arg(long)]
     pub(crate) package_dir: Option<PathBuf>,
+    /// Package all buildpacks of the Cargo workspace, regardless of the current directory
+    #[arg(long)]
+    pub(crate) workspace: bool,
+    /// Number of parallel jobs for compiling and packaging buildpacks, defaults to the number of CPUs
+    #[arg(long, short)]
+    pub(crate) jobs: Option<NonZeroUsize>,
+    /// Output format of the packaged buildpacks
+    #[arg(long, value_enum, default_value_t = PackageFormat::Directory)]
+    pub(crate) format: PackageFormat,
+    /// Format of the report printed to stdout
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub(crate) output_format: OutputFormat,
+}
+
+#[derive(Parser)]
+pub(crate) struct CheckArgs {
+    /// Sync the description, homepage, licenses and keywords in buildpack.toml with Cargo.toml
+    #[arg(long)]
+    pub(crate) fix: bool,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub(crate) enum PackageFormat {
+    /// A buildpack directory, as used by `pack build --buildpack`
+    Directory,
+    /// An OCI image layout directory, i.e. for `crane push` or `skopeo copy oci:`
+    OciLayout,
+    /// A tarball of an OCI image layout, i.e. for `skopeo copy oci-archive:`
+    OciLayoutTar,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub(crate) enum OutputFormat {
+    /// The paths of the packaged buildpacks, one per line
+    Text,
+    /// A JSON report with the id, version, paths, size and dependencies of all packaged
+    /// buildpacks
+    Json,
 }
 
 #[cfg(test)]
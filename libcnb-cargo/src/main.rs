@@ -6,6 +6,7 @@ use libcnb_common as _;
 #[cfg(test)]
 use tempfile as _;
 
+mod check;
 mod cli;
 mod package;
 
@@ -22,5 +23,11 @@ fn main() {
                 std::process::exit(UNSPECIFIED_ERROR);
             }
         }
+        Cli::Libcnb(LibcnbSubcommand::Check(args)) => {
+            if let Err(error) = check::execute(&args) {
+                eprintln!("❌ {error}");
+                std::process::exit(UNSPECIFIED_ERROR);
+            }
+        }
     }
 }
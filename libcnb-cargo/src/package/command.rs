@@ -1,14 +1,27 @@
-use crate::cli::PackageArgs;
+use crate::cli::{OutputFormat, PackageArgs, PackageFormat};
 use crate::package::error::Error;
+use crate::package::report::{calculate_dir_size, PackageReport};
 use libcnb_data::buildpack::BuildpackId;
-use libcnb_package::buildpack_dependency_graph::build_libcnb_buildpacks_dependency_graph;
+use libcnb_package::buildpack_dependency_graph::{
+    build_libcnb_buildpacks_dependency_graph, BuildpackDependencyGraphNode,
+};
+use libcnb_package::buildpack_kind::{determine_buildpack_kind, BuildpackKind};
+use libcnb_package::check::check_buildpack;
+use libcnb_package::checksums::write_checksums_file;
 use libcnb_package::cross_compile::{cross_compile_assistance, CrossCompileAssistance};
 use libcnb_package::dependency_graph::get_dependencies;
+use libcnb_package::oci_layout::{oci_architecture, write_buildpack_oci_layout, OciLayoutFormat};
 use libcnb_package::output::create_packaged_buildpack_dir_resolver;
+use libcnb_package::package::{
+    assemble_libcnb_buildpack, compile_libcnb_buildpacks, package_composite_buildpack,
+    CompiledLibcnbBuildpack, PackageBuildpackError,
+};
 use libcnb_package::util::absolutize_path;
 use libcnb_package::{find_cargo_workspace_root_dir, CargoProfile};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
+use std::ffi::OsString;
 use std::fs;
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 
 pub(crate) fn execute(args: &PackageArgs) -> Result<(), Error> {
@@ -39,11 +52,87 @@ pub(crate) fn execute(args: &PackageArgs) -> Result<(), Error> {
         create_packaged_buildpack_dir_resolver(&package_dir, cargo_profile, &args.target);
 
     eprintln!("🖥️ Gathering Cargo configuration (for {})", args.target);
-    let cargo_build_env = if args.no_cross_compile_assistance {
-        Vec::new()
+    let cargo_build_env = cargo_build_env(args)?;
+
+    eprintln!("🏗️ Building buildpack dependency graph...");
+    let buildpack_dependency_graph = build_libcnb_buildpacks_dependency_graph(&workspace_root_path)
+        .map_err(Error::CannotBuildBuildpackDependencyGraph)?;
+
+    eprintln!("🔀 Determining build order...");
+    let root_nodes = root_nodes(
+        &buildpack_dependency_graph
+            .node_weights()
+            .collect::<Vec<_>>(),
+        args.workspace,
+        &current_dir,
+        &workspace_root_path,
+    );
+
+    let build_order = get_dependencies(&buildpack_dependency_graph, &root_nodes)
+        .map_err(Error::CannotGetDependencies)?;
+
+    if build_order.is_empty() {
+        return Err(Error::NoBuildpacksFound);
+    }
+
+    let jobs = args
+        .jobs
+        .or_else(|| std::thread::available_parallelism().ok())
+        .unwrap_or(NonZeroUsize::MIN);
+
+    let compiled_buildpacks = compile_buildpacks(
+        &build_order,
+        cargo_profile,
+        &args.target,
+        &cargo_build_env,
+        jobs,
+    )?;
+
+    eprintln!("🚚 Building {} buildpacks...", build_order.len());
+    let mut packaged_buildpack_dirs = BTreeMap::new();
+    let mut node_index = 0;
+    for batch in packaging_batches(&build_order, jobs) {
+        let batch = batch
+            .into_iter()
+            .map(|node| {
+                node_index += 1;
+                eprint_building_buildpack(node_index, build_order.len(), node, &current_dir);
+                (node, buildpack_dir_resolver(&node.buildpack_id))
+            })
+            .collect::<Vec<_>>();
+
+        for batch_result in
+            package_buildpacks_in_parallel(batch, &compiled_buildpacks, &packaged_buildpack_dirs)
+        {
+            let (buildpack_id, buildpack_destination_dir) = batch_result?;
+            eprint_compiled_buildpack_success(&current_dir, &buildpack_destination_dir);
+            packaged_buildpack_dirs.insert(buildpack_id, buildpack_destination_dir);
+        }
+    }
+
+    let output_paths = write_package_output(
+        args.format,
+        &args.target,
+        &packaged_buildpack_dirs,
+        &root_nodes,
+        &current_dir,
+    )?;
+
+    print_package_output(args, &build_order, &packaged_buildpack_dirs, &output_paths)?;
+
+    eprint_msrv_differences(&build_order);
+
+    Ok(())
+}
+
+/// Determines the environment for Cargo, containing the cross-compile configuration unless
+/// cross-compile assistance is disabled.
+fn cargo_build_env(args: &PackageArgs) -> Result<Vec<(OsString, OsString)>, Error> {
+    if args.no_cross_compile_assistance {
+        Ok(Vec::new())
     } else {
         match cross_compile_assistance(&args.target) {
-            CrossCompileAssistance::Configuration { cargo_env } => cargo_env,
+            CrossCompileAssistance::Configuration { cargo_env } => Ok(cargo_env),
             CrossCompileAssistance::NoAssistance => {
                 eprintln!(
                     "Couldn't determine automatic cross-compile settings for target triple {}.",
@@ -51,84 +140,303 @@ pub(crate) fn execute(args: &PackageArgs) -> Result<(), Error> {
                 );
                 eprintln!("This is not an error, but without proper cross-compile settings in your Cargo manifest and locally installed toolchains, compilation might fail.");
                 eprintln!("To disable this warning, pass --no-cross-compile-assistance.");
-                Vec::new()
+                Ok(Vec::new())
             }
             CrossCompileAssistance::HelpText(help_text) => {
                 eprintln!("{help_text}");
-                return Err(Error::CannotConfigureCrossCompilation);
+                Err(Error::CannotConfigureCrossCompilation)
             }
         }
-    };
+    }
+}
 
-    eprintln!("🏗️ Building buildpack dependency graph...");
-    let buildpack_dependency_graph = build_libcnb_buildpacks_dependency_graph(&workspace_root_path)
-        .map_err(Error::CannotBuildBuildpackDependencyGraph)?;
+/// Determines the buildpacks to package: all buildpacks of the workspace if requested, the
+/// buildpack in the current directory, or all buildpacks if run in the workspace root.
+fn root_nodes<'a>(
+    nodes: &[&'a BuildpackDependencyGraphNode],
+    workspace: bool,
+    current_dir: &Path,
+    workspace_root_path: &Path,
+) -> Vec<&'a BuildpackDependencyGraphNode> {
+    if workspace {
+        return nodes.to_vec();
+    }
 
-    eprintln!("🔀 Determining build order...");
-    let root_nodes = buildpack_dependency_graph
-        .node_weights()
+    nodes
+        .iter()
         .find(|node| node.path == current_dir)
-        .map(|node| vec![node])
-        .or_else(|| {
-            current_dir.eq(&workspace_root_path).then(|| {
-                buildpack_dependency_graph
-                    .node_weights()
-                    .collect::<Vec<_>>()
-            })
-        })
-        .unwrap_or_default();
+        .map(|node| vec![*node])
+        .or_else(|| (current_dir == workspace_root_path).then(|| nodes.to_vec()))
+        .unwrap_or_default()
+}
 
-    let build_order = get_dependencies(&buildpack_dependency_graph, &root_nodes)
-        .map_err(Error::CannotGetDependencies)?;
+/// Writes the packaged root buildpacks in the requested format and returns the resulting path
+/// of each root buildpack.
+fn write_package_output(
+    package_format: PackageFormat,
+    target_triple: &str,
+    packaged_buildpack_dirs: &BTreeMap<BuildpackId, PathBuf>,
+    root_nodes: &[&BuildpackDependencyGraphNode],
+    current_dir: &Path,
+) -> Result<BTreeMap<BuildpackId, PathBuf>, Error> {
+    let root_buildpack_dirs = packaged_buildpack_dirs
+        .iter()
+        .filter(|(id, _)| root_nodes.iter().any(|node| node.buildpack_id == **id))
+        .map(|(id, packaged_buildpack_dir)| (id.clone(), packaged_buildpack_dir.clone()))
+        .collect::<BTreeMap<_, _>>();
 
-    if build_order.is_empty() {
-        return Err(Error::NoBuildpacksFound);
+    match oci_layout_format(package_format) {
+        None => {
+            eprint_pack_command_hint(packaged_buildpack_dirs, current_dir);
+            Ok(root_buildpack_dirs)
+        }
+        Some(oci_layout_format) => {
+            eprintln!("🗃️ Writing OCI image layouts...");
+            let oci_layout_paths = root_buildpack_dirs
+                .into_iter()
+                .map(|(id, packaged_buildpack_dir)| {
+                    let oci_layout_path =
+                        oci_layout_path(&packaged_buildpack_dir, oci_layout_format);
+                    remove_previous_oci_layout(&oci_layout_path).map_err(|error| {
+                        Error::CannotRemovePreviousOciLayout(oci_layout_path.clone(), error)
+                    })?;
+                    write_buildpack_oci_layout(
+                        &packaged_buildpack_dir,
+                        &oci_layout_path,
+                        oci_layout_format,
+                        oci_architecture(target_triple),
+                    )
+                    .map_err(Error::CannotWriteOciLayout)
+                    .map(|()| (id, oci_layout_path))
+                })
+                .collect::<Result<BTreeMap<_, _>, _>>()?;
+
+            eprint_oci_layout_hint(
+                &oci_layout_paths.values().cloned().collect::<Vec<_>>(),
+                oci_layout_format,
+                current_dir,
+            );
+            Ok(oci_layout_paths)
+        }
     }
+}
 
-    eprintln!("🚚 Building {} buildpacks...", build_order.len());
-    let mut packaged_buildpack_dirs = BTreeMap::new();
-    for (node_index, node) in build_order.iter().enumerate() {
-        eprintln!(
-            "📦 [{}/{}] Building {} (./{})",
-            node_index + 1,
-            build_order.len(),
-            node.buildpack_id,
-            pathdiff::diff_paths(&node.path, &current_dir)
-                .unwrap_or_else(|| node.path.clone())
-                .to_string_lossy()
-        );
+/// Prints the output paths of the root buildpacks or, with `--output-format json`, a
+/// [`PackageReport`] of all packaged buildpacks to stdout.
+fn print_package_output(
+    args: &PackageArgs,
+    build_order: &[&BuildpackDependencyGraphNode],
+    packaged_buildpack_dirs: &BTreeMap<BuildpackId, PathBuf>,
+    output_paths: &BTreeMap<BuildpackId, PathBuf>,
+) -> Result<(), Error> {
+    match args.output_format {
+        OutputFormat::Text => {
+            for output_path in output_paths.values() {
+                println!("{}", output_path.to_string_lossy());
+            }
+        }
+        OutputFormat::Json => {
+            let package_report = PackageReport::new(
+                &args.target,
+                build_order,
+                packaged_buildpack_dirs,
+                output_paths,
+            );
 
-        let buildpack_destination_dir = buildpack_dir_resolver(&node.buildpack_id);
-        let _ = fs::remove_dir_all(&buildpack_destination_dir);
-        fs::create_dir_all(&buildpack_destination_dir).map_err(|error| {
-            Error::CannotCreateBuildpackDestinationDir(buildpack_destination_dir.clone(), error)
-        })?;
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&package_report).map_err(Error::CannotWriteReport)?
+            );
+        }
+    }
 
-        libcnb_package::package::package_buildpack(
-            &node.path,
-            cargo_profile,
-            &args.target,
-            &cargo_build_env,
-            &buildpack_destination_dir,
-            &packaged_buildpack_dirs,
-        )
-        .map_err(Error::CannotPackageBuildpack)?;
+    Ok(())
+}
+
+fn oci_layout_format(package_format: PackageFormat) -> Option<OciLayoutFormat> {
+    match package_format {
+        PackageFormat::Directory => None,
+        PackageFormat::OciLayout => Some(OciLayoutFormat::Directory),
+        PackageFormat::OciLayoutTar => Some(OciLayoutFormat::Tarball),
+    }
+}
 
-        eprint_compiled_buildpack_success(&current_dir, &buildpack_destination_dir);
+/// The OCI image layout is written next to the packaged buildpack directory.
+/// Removes the OCI image layout directory or tarball written by a previous invocation, if any.
+fn remove_previous_oci_layout(oci_layout_path: &Path) -> std::io::Result<()> {
+    let result = if oci_layout_path.is_dir() {
+        fs::remove_dir_all(oci_layout_path)
+    } else {
+        fs::remove_file(oci_layout_path)
+    };
 
-        packaged_buildpack_dirs.insert(node.buildpack_id.clone(), buildpack_destination_dir);
+    match result {
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        result => result,
     }
+}
+
+fn oci_layout_path(packaged_buildpack_dir: &Path, format: OciLayoutFormat) -> PathBuf {
+    let mut file_name = packaged_buildpack_dir
+        .file_name()
+        .unwrap_or_default()
+        .to_os_string();
+
+    file_name.push(match format {
+        OciLayoutFormat::Directory => ".oci",
+        OciLayoutFormat::Tarball => ".oci.tar",
+    });
 
-    eprint_pack_command_hint(&packaged_buildpack_dirs, &current_dir);
+    packaged_buildpack_dir.with_file_name(file_name)
+}
 
-    packaged_buildpack_dirs
+/// Compiles all libcnb.rs buildpacks of the given build order, running at most `jobs` Cargo
+/// invocations in parallel.
+fn compile_buildpacks(
+    build_order: &[&BuildpackDependencyGraphNode],
+    cargo_profile: CargoProfile,
+    target_triple: &str,
+    cargo_build_env: &[(OsString, OsString)],
+    jobs: NonZeroUsize,
+) -> Result<BTreeMap<BuildpackId, CompiledLibcnbBuildpack>, Error> {
+    let libcnb_buildpack_nodes = build_order
         .iter()
-        .filter(|(id, _)| root_nodes.iter().any(|node| node.buildpack_id == **id))
-        .for_each(|(_, packaged_buildpack_dir)| {
-            println!("{}", packaged_buildpack_dir.to_string_lossy());
-        });
+        .copied()
+        .filter(|node| determine_buildpack_kind(&node.path) == Some(BuildpackKind::LibCnbRs))
+        .collect::<Vec<_>>();
 
-    Ok(())
+    eprintln!(
+        "⚙️ Compiling {} libcnb.rs buildpacks...",
+        libcnb_buildpack_nodes.len()
+    );
+    compile_libcnb_buildpacks(
+        &libcnb_buildpack_nodes
+            .iter()
+            .map(|node| node.path.as_path())
+            .collect::<Vec<_>>(),
+        cargo_profile,
+        target_triple,
+        cargo_build_env,
+        jobs,
+    )
+    .map_err(Error::CannotCompileBuildpacks)
+    .map(|compiled_buildpacks| {
+        compiled_buildpacks
+            .into_iter()
+            .zip(&libcnb_buildpack_nodes)
+            .map(|(compiled_buildpack, node)| (node.buildpack_id.clone(), compiled_buildpack))
+            .collect()
+    })
+}
+
+/// Packages the given buildpacks in parallel, one thread per buildpack.
+///
+/// libcnb.rs buildpacks must have been compiled beforehand, packaging only assembles their
+/// buildpack directories and doesn't invoke Cargo. Results contain the id and destination
+/// directory of each packaged buildpack and are returned in the order of the given buildpacks.
+fn package_buildpacks_in_parallel(
+    buildpacks: Vec<(&BuildpackDependencyGraphNode, PathBuf)>,
+    compiled_buildpacks: &BTreeMap<BuildpackId, CompiledLibcnbBuildpack>,
+    packaged_buildpack_dirs: &BTreeMap<BuildpackId, PathBuf>,
+) -> Vec<Result<(BuildpackId, PathBuf), Error>> {
+    std::thread::scope(|scope| {
+        let handles = buildpacks
+            .into_iter()
+            .map(|(node, buildpack_destination_dir)| {
+                scope.spawn(move || {
+                    package_buildpack(
+                        node,
+                        compiled_buildpacks.get(&node.buildpack_id),
+                        &buildpack_destination_dir,
+                        packaged_buildpack_dirs,
+                    )
+                    .map(|()| (node.buildpack_id.clone(), buildpack_destination_dir))
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    })
+}
+
+/// Packages a compiled libcnb.rs buildpack or, if no compiled buildpack is given, a composite
+/// buildpack.
+fn package_buildpack(
+    node: &BuildpackDependencyGraphNode,
+    compiled_buildpack: Option<&CompiledLibcnbBuildpack>,
+    buildpack_destination_dir: &Path,
+    packaged_buildpack_dirs: &BTreeMap<BuildpackId, PathBuf>,
+) -> Result<(), Error> {
+    let _ = fs::remove_dir_all(buildpack_destination_dir);
+    fs::create_dir_all(buildpack_destination_dir).map_err(|error| {
+        Error::CannotCreateBuildpackDestinationDir(buildpack_destination_dir.to_path_buf(), error)
+    })?;
+
+    match compiled_buildpack {
+        Some(compiled_buildpack) => {
+            assemble_libcnb_buildpack(&node.path, compiled_buildpack, buildpack_destination_dir)
+                .map_err(PackageBuildpackError::PackageLibcnbBuildpackError)
+        }
+        None => package_composite_buildpack(
+            &node.path,
+            buildpack_destination_dir,
+            packaged_buildpack_dirs,
+        )
+        .map_err(PackageBuildpackError::PackageCompositeBuildpackError),
+    }
+    .map_err(Error::CannotPackageBuildpack)?;
+
+    write_checksums_file(buildpack_destination_dir).map_err(|error| {
+        Error::CannotWriteChecksums(buildpack_destination_dir.to_path_buf(), error)
+    })
+}
+
+/// Splits the given build order into batches of at most `jobs` buildpacks that can be packaged in
+/// parallel, i.e. every buildpack only depends on buildpacks of previous batches.
+///
+/// Buildpacks keep their relative order from the given build order.
+fn packaging_batches<'a>(
+    build_order: &[&'a BuildpackDependencyGraphNode],
+    jobs: NonZeroUsize,
+) -> Vec<Vec<&'a BuildpackDependencyGraphNode>> {
+    let mut batches = Vec::new();
+    let mut batched_ids = HashSet::new();
+    let mut remaining = build_order.to_vec();
+
+    while !remaining.is_empty() {
+        let mut batch = remaining
+            .iter()
+            .copied()
+            .filter(|node| {
+                node.dependencies.iter().all(|dependency| {
+                    batched_ids.contains(dependency)
+                        || !remaining
+                            .iter()
+                            .any(|node| node.buildpack_id == *dependency)
+                })
+            })
+            .take(jobs.get())
+            .collect::<Vec<_>>();
+
+        // The build order is topologically sorted, so a batch can only be empty if the dependency
+        // graph contains a cycle. Fall back to packaging in build order in that case.
+        if batch.is_empty() {
+            batch.push(remaining[0]);
+        }
+
+        remaining.retain(|node| !batch.iter().any(|batched| std::ptr::eq(*batched, *node)));
+        batched_ids.extend(batch.iter().map(|node| node.buildpack_id.clone()));
+        batches.push(batch);
+    }
+
+    batches
 }
 
 fn eprint_pack_command_hint(
@@ -152,6 +460,67 @@ fn eprint_pack_command_hint(
     eprintln!();
 }
 
+fn eprint_oci_layout_hint(
+    oci_layout_paths: &[PathBuf],
+    format: OciLayoutFormat,
+    current_dir: &Path,
+) {
+    eprintln!("✨ Packaging successfully finished!");
+    eprintln!();
+    eprintln!("💡 To push your buildpack to a registry, run:");
+    for path in oci_layout_paths {
+        let relative_path = pathdiff::diff_paths(path, current_dir).unwrap_or_else(|| path.clone());
+        match format {
+            OciLayoutFormat::Directory => eprintln!(
+                "skopeo copy oci:{} docker://registry.example.com/my-buildpack:latest",
+                relative_path.to_string_lossy()
+            ),
+            OciLayoutFormat::Tarball => eprintln!(
+                "skopeo copy oci-archive:{} docker://registry.example.com/my-buildpack:latest",
+                relative_path.to_string_lossy()
+            ),
+        }
+    }
+    eprintln!();
+}
+
+/// Warns about buildpacks with a different MSRV than libcnb.rs.
+///
+/// Checking the MSRV only requires the Cargo metadata, so unlike `cargo libcnb check`, it doesn't
+/// slow down packaging. Failing to check is not an error.
+fn eprint_msrv_differences(build_order: &[&BuildpackDependencyGraphNode]) {
+    let msrv_differences = build_order
+        .iter()
+        .filter_map(|node| check_buildpack(&node.path, false).ok().flatten())
+        .filter(|report| !report.msrv.matches())
+        .collect::<Vec<_>>();
+
+    if !msrv_differences.is_empty() {
+        for report in &msrv_differences {
+            eprintln!("⚠️ {}: {}", report.package_name, report.msrv);
+        }
+        eprintln!(
+            "💡 Run `cargo libcnb check` to also report usages of deprecated libcnb.rs APIs."
+        );
+        eprintln!();
+    }
+}
+
+fn eprint_building_buildpack(
+    node_index: usize,
+    node_count: usize,
+    node: &BuildpackDependencyGraphNode,
+    current_dir: &Path,
+) {
+    eprintln!(
+        "📦 [{node_index}/{node_count}] Building {} (./{})",
+        node.buildpack_id,
+        pathdiff::diff_paths(&node.path, current_dir)
+            .unwrap_or_else(|| node.path.clone())
+            .to_string_lossy()
+    );
+}
+
 fn eprint_compiled_buildpack_success(current_dir: &Path, target_dir: &Path) {
     let size_string = calculate_dir_size(target_dir)
         .map(|size_in_bytes| {
@@ -172,23 +541,95 @@ fn eprint_compiled_buildpack_success(current_dir: &Path, target_dir: &Path) {
     );
 }
 
-/// Recursively calculate the size of a directory and its contents in bytes.
-fn calculate_dir_size(path: impl AsRef<Path>) -> std::io::Result<u64> {
-    let mut size_in_bytes = 0;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libcnb_data::buildpack_id;
 
-    // The size of the directory entry (ie: its metadata only, not the directory contents).
-    size_in_bytes += path.as_ref().metadata()?.len();
+    fn node(id: BuildpackId, dependencies: &[BuildpackId]) -> BuildpackDependencyGraphNode {
+        BuildpackDependencyGraphNode {
+            buildpack_id: id,
+            path: PathBuf::new(),
+            dependencies: dependencies.to_vec(),
+        }
+    }
 
-    for entry in std::fs::read_dir(&path)? {
-        let entry = entry?;
-        let metadata = entry.metadata()?;
+    fn batch_ids(batches: &[Vec<&BuildpackDependencyGraphNode>]) -> Vec<Vec<String>> {
+        batches
+            .iter()
+            .map(|batch| {
+                batch
+                    .iter()
+                    .map(|node| node.buildpack_id.to_string())
+                    .collect()
+            })
+            .collect()
+    }
 
-        if metadata.is_dir() {
-            size_in_bytes += calculate_dir_size(entry.path())?;
-        } else {
-            size_in_bytes += metadata.len();
-        }
+    #[test]
+    fn packaging_batches_respects_dependencies() {
+        let a = node(buildpack_id!("a"), &[]);
+        let b = node(buildpack_id!("b"), &[]);
+        let c = node(buildpack_id!("c"), &[]);
+        let composite = node(
+            buildpack_id!("composite"),
+            &[buildpack_id!("a"), buildpack_id!("b"), buildpack_id!("c")],
+        );
+        let build_order = [&a, &b, &c, &composite];
+
+        assert_eq!(
+            batch_ids(&packaging_batches(
+                &build_order,
+                NonZeroUsize::new(8).unwrap()
+            )),
+            [vec!["a", "b", "c"], vec!["composite"]]
+        );
+
+        assert_eq!(
+            batch_ids(&packaging_batches(
+                &build_order,
+                NonZeroUsize::new(2).unwrap()
+            )),
+            [vec!["a", "b"], vec!["c"], vec!["composite"]]
+        );
     }
 
-    Ok(size_in_bytes)
+    #[test]
+    fn root_nodes_for_current_dir() {
+        let workspace_root = PathBuf::from("/workspace");
+        let one = BuildpackDependencyGraphNode {
+            path: workspace_root.join("buildpacks/one"),
+            ..node(buildpack_id!("one"), &[])
+        };
+        let two = BuildpackDependencyGraphNode {
+            path: workspace_root.join("buildpacks/two"),
+            ..node(buildpack_id!("two"), &[])
+        };
+        let nodes = [&one, &two];
+
+        let root_node_ids = |workspace: bool, current_dir: &Path| {
+            root_nodes(&nodes, workspace, current_dir, &workspace_root)
+                .iter()
+                .map(|node| node.buildpack_id.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(root_node_ids(false, &one.path), ["one"]);
+        assert_eq!(root_node_ids(false, &workspace_root), ["one", "two"]);
+        assert_eq!(root_node_ids(true, &one.path), ["one", "two"]);
+        assert!(root_node_ids(false, &workspace_root.join("other")).is_empty());
+    }
+
+    #[test]
+    fn packaging_batches_sequential() {
+        let a = node(buildpack_id!("a"), &[]);
+        let b = node(buildpack_id!("b"), &[buildpack_id!("a")]);
+        let c = node(buildpack_id!("c"), &[]);
+        let build_order = [&a, &b, &c];
+
+        assert_eq!(
+            batch_ids(&packaging_batches(&build_order, NonZeroUsize::MIN)),
+            [vec!["a"], vec!["b"], vec!["c"]]
+        );
+    }
 }
@@ -1,7 +1,8 @@
 use libcnb_data::buildpack::BuildpackId;
 use libcnb_package::buildpack_dependency_graph::BuildBuildpackDependencyGraphError;
 use libcnb_package::dependency_graph::GetDependenciesError;
-use libcnb_package::package::PackageBuildpackError;
+use libcnb_package::oci_layout::WriteOciLayoutError;
+use libcnb_package::package::{PackageBuildpackError, PackageLibcnbBuildpackError};
 use std::path::PathBuf;
 
 #[derive(thiserror::Error, Debug)]
@@ -18,8 +19,18 @@ pub(crate) enum Error {
     CannotGetDependencies(#[source] GetDependenciesError<BuildpackId>),
     #[error("Failed to create buildpack package directory {0}: {1}")]
     CannotCreateBuildpackDestinationDir(PathBuf, #[source] std::io::Error),
+    #[error("Failed to compile buildpacks: {0}")]
+    CannotCompileBuildpacks(#[source] PackageLibcnbBuildpackError),
     #[error("Failed to package buildpack: {0}")]
     CannotPackageBuildpack(#[source] PackageBuildpackError),
+    #[error("Failed to write checksums of packaged buildpack {0}: {1}")]
+    CannotWriteChecksums(PathBuf, #[source] std::io::Error),
+    #[error("Failed to remove previous OCI image layout {0}: {1}")]
+    CannotRemovePreviousOciLayout(PathBuf, #[source] std::io::Error),
+    #[error("Failed to write OCI image layout: {0}")]
+    CannotWriteOciLayout(#[source] WriteOciLayoutError),
+    #[error("Failed to write package report: {0}")]
+    CannotWriteReport(#[source] serde_json::Error),
     #[error("Failed to configure Cargo for cross-compilation")]
     CannotConfigureCrossCompilation,
     #[error("No buildpacks found!")]
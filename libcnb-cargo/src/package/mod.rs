@@ -1,4 +1,5 @@
 mod command;
 mod error;
+mod report;
 
 pub(crate) use command::execute;
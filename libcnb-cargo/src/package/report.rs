@@ -0,0 +1,158 @@
+use libcnb_common::toml_file::read_toml_file;
+use libcnb_data::buildpack::{BuildpackDescriptor, BuildpackId};
+use libcnb_package::buildpack_dependency_graph::BuildpackDependencyGraphNode;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Machine-readable report of packaged buildpacks, printed to stdout with `--output-format json`.
+#[derive(Debug, Serialize)]
+pub(crate) struct PackageReport {
+    /// The target triple the buildpacks were compiled for.
+    pub(crate) target: String,
+    /// All packaged buildpacks, including dependencies, in build order.
+    pub(crate) buildpacks: Vec<PackagedBuildpack>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct PackagedBuildpack {
+    pub(crate) id: BuildpackId,
+    /// The version from the packaged `buildpack.toml`, if it could be read.
+    pub(crate) version: Option<String>,
+    /// The path of the buildpack's Cargo project or `buildpack.toml` directory.
+    pub(crate) source_path: PathBuf,
+    /// The path of the packaged buildpack directory.
+    pub(crate) path: PathBuf,
+    /// The path printed in the text output format, only set for the buildpacks that were
+    /// requested to be packaged. Differs from `path` when packaging as an OCI image layout.
+    pub(crate) output_path: Option<PathBuf>,
+    /// The size of the packaged buildpack directory, if it could be determined.
+    pub(crate) size_in_bytes: Option<u64>,
+    /// The ids of the buildpacks this buildpack directly depends on.
+    pub(crate) dependencies: Vec<BuildpackId>,
+}
+
+impl PackageReport {
+    pub(crate) fn new(
+        target: &str,
+        build_order: &[&BuildpackDependencyGraphNode],
+        packaged_buildpack_dirs: &BTreeMap<BuildpackId, PathBuf>,
+        output_paths: &BTreeMap<BuildpackId, PathBuf>,
+    ) -> Self {
+        let buildpacks = build_order
+            .iter()
+            .filter_map(|node| {
+                let path = packaged_buildpack_dirs.get(&node.buildpack_id)?;
+
+                Some(PackagedBuildpack {
+                    id: node.buildpack_id.clone(),
+                    version: read_toml_file::<BuildpackDescriptor>(path.join("buildpack.toml"))
+                        .ok()
+                        .map(|descriptor| descriptor.buildpack().version.to_string()),
+                    source_path: node.path.clone(),
+                    path: path.clone(),
+                    output_path: output_paths.get(&node.buildpack_id).cloned(),
+                    size_in_bytes: calculate_dir_size(path).ok(),
+                    dependencies: node.dependencies.clone(),
+                })
+            })
+            .collect();
+
+        Self {
+            target: target.to_string(),
+            buildpacks,
+        }
+    }
+}
+
+/// Recursively calculate the size of a directory and its contents in bytes.
+pub(crate) fn calculate_dir_size(path: impl AsRef<Path>) -> std::io::Result<u64> {
+    let mut size_in_bytes = 0;
+
+    // The size of the directory entry (ie: its metadata only, not the directory contents).
+    size_in_bytes += path.as_ref().metadata()?.len();
+
+    for entry in std::fs::read_dir(&path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+
+        if metadata.is_dir() {
+            size_in_bytes += calculate_dir_size(entry.path())?;
+        } else {
+            size_in_bytes += metadata.len();
+        }
+    }
+
+    Ok(size_in_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libcnb_data::buildpack_id;
+    use std::fs;
+
+    #[test]
+    fn package_report() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let packaged_dir = temp_dir.path().join("packaged");
+        let composite_dir = packaged_dir.join("composite");
+        let component_dir = packaged_dir.join("component");
+        fs::create_dir_all(&composite_dir).unwrap();
+        fs::create_dir_all(&component_dir).unwrap();
+        fs::write(
+            component_dir.join("buildpack.toml"),
+            r#"
+api = "0.10"
+
+[buildpack]
+id = "component"
+version = "1.2.3"
+"#,
+        )
+        .unwrap();
+
+        let component = BuildpackDependencyGraphNode {
+            buildpack_id: buildpack_id!("component"),
+            path: PathBuf::from("/workspace/component"),
+            dependencies: Vec::new(),
+        };
+        let composite = BuildpackDependencyGraphNode {
+            buildpack_id: buildpack_id!("composite"),
+            path: PathBuf::from("/workspace/composite"),
+            dependencies: vec![buildpack_id!("component")],
+        };
+
+        let report = PackageReport::new(
+            "x86_64-unknown-linux-musl",
+            &[&component, &composite],
+            &BTreeMap::from([
+                (buildpack_id!("component"), component_dir.clone()),
+                (buildpack_id!("composite"), composite_dir.clone()),
+            ]),
+            &BTreeMap::from([(buildpack_id!("composite"), composite_dir.clone())]),
+        );
+
+        assert_eq!(report.target, "x86_64-unknown-linux-musl");
+        assert_eq!(report.buildpacks.len(), 2);
+
+        let component_report = &report.buildpacks[0];
+        assert_eq!(component_report.id, buildpack_id!("component"));
+        assert_eq!(component_report.version.as_deref(), Some("1.2.3"));
+        assert_eq!(component_report.path, component_dir);
+        assert_eq!(component_report.output_path, None);
+        assert!(component_report.size_in_bytes.is_some());
+
+        let composite_report = &report.buildpacks[1];
+        assert_eq!(composite_report.version, None);
+        assert_eq!(composite_report.output_path, Some(composite_dir));
+        assert_eq!(
+            composite_report.dependencies,
+            vec![buildpack_id!("component")]
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["buildpacks"][0]["id"], "component");
+        assert_eq!(json["buildpacks"][1]["dependencies"][0], "component");
+    }
+}
@@ -42,6 +42,70 @@ fn package_buildpack_in_single_buildpack_project() {
     validate_packaged_buildpack(&packaged_buildpack_dir, &buildpack_id);
 }
 
+#[test]
+#[ignore = "integration test"]
+fn package_buildpack_as_oci_layout() {
+    let fixture_dir = copy_fixture_to_temp_dir("single_buildpack").unwrap();
+    let buildpack_id = buildpack_id!("single-buildpack");
+
+    let output = Command::new(CARGO_LIBCNB_BINARY_UNDER_TEST)
+        .args(["libcnb", "package", "--release", "--format", "oci-layout"])
+        .current_dir(&fixture_dir)
+        .output()
+        .unwrap();
+
+    let packaged_buildpack_dir = create_packaged_buildpack_dir_resolver(
+        &fixture_dir.path().join(DEFAULT_PACKAGE_DIR_NAME),
+        CargoProfile::Release,
+        X86_64_UNKNOWN_LINUX_MUSL,
+    )(&buildpack_id);
+    let oci_layout_dir = packaged_buildpack_dir.with_file_name("single-buildpack.oci");
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("{}\n", oci_layout_dir.to_string_lossy())
+    );
+
+    validate_packaged_buildpack(&packaged_buildpack_dir, &buildpack_id);
+    assert!(oci_layout_dir.join("oci-layout").is_file());
+    assert!(oci_layout_dir.join("index.json").is_file());
+}
+
+#[test]
+#[ignore = "integration test"]
+fn package_buildpack_with_json_output() {
+    let fixture_dir = copy_fixture_to_temp_dir("single_buildpack").unwrap();
+    let buildpack_id = buildpack_id!("single-buildpack");
+
+    let output = Command::new(CARGO_LIBCNB_BINARY_UNDER_TEST)
+        .args(["libcnb", "package", "--release", "--output-format", "json"])
+        .current_dir(&fixture_dir)
+        .output()
+        .unwrap();
+
+    let packaged_buildpack_dir = create_packaged_buildpack_dir_resolver(
+        &fixture_dir.path().join(DEFAULT_PACKAGE_DIR_NAME),
+        CargoProfile::Release,
+        X86_64_UNKNOWN_LINUX_MUSL,
+    )(&buildpack_id);
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["target"], X86_64_UNKNOWN_LINUX_MUSL);
+
+    let buildpack_report = &report["buildpacks"][0];
+    assert_eq!(buildpack_report["id"], buildpack_id.as_str());
+    assert_eq!(buildpack_report["version"], "0.0.0");
+    assert_eq!(
+        buildpack_report["path"],
+        packaged_buildpack_dir.to_string_lossy().as_ref()
+    );
+    assert_eq!(buildpack_report["output_path"], buildpack_report["path"]);
+    assert!(buildpack_report["size_in_bytes"].as_u64().unwrap() > 0);
+    assert_eq!(buildpack_report["dependencies"], serde_json::json!([]));
+
+    validate_packaged_buildpack(&packaged_buildpack_dir, &buildpack_id);
+}
+
 #[test]
 #[ignore = "integration test"]
 fn package_single_composite_buildpack_in_monorepo_buildpack_project() {
@@ -184,12 +248,62 @@ fn package_all_buildpacks_in_monorepo_buildpack_project() {
         .unwrap(),
     );
 
-    for buildpack_id in dependent_buildpack_ids {
-        validate_packaged_buildpack(
-            &packaged_buildpack_dir_resolver(&buildpack_id),
-            &buildpack_id,
-        );
+    for buildpack_id in &dependent_buildpack_ids {
+        validate_packaged_buildpack(&packaged_buildpack_dir_resolver(buildpack_id), buildpack_id);
     }
+
+    // Both buildpacks have an additional `helper` binary. Each packaged buildpack must contain
+    // its own one and not the one of the other buildpack.
+    let [helper_one, helper_two] = dependent_buildpack_ids.map(|buildpack_id| {
+        fs::read(
+            packaged_buildpack_dir_resolver(&buildpack_id)
+                .join(".libcnb-cargo/additional-bin/helper"),
+        )
+        .unwrap()
+    });
+
+    assert_ne!(helper_one, helper_two);
+}
+
+#[test]
+#[ignore = "integration test"]
+fn package_workspace_from_buildpack_directory() {
+    let fixture_dir = copy_fixture_to_temp_dir("multiple_buildpacks").unwrap();
+
+    let output = Command::new(CARGO_LIBCNB_BINARY_UNDER_TEST)
+        .args([
+            "libcnb",
+            "package",
+            "--release",
+            "--workspace",
+            "--jobs",
+            "2",
+        ])
+        .current_dir(fixture_dir.path().join("buildpacks/one"))
+        .output()
+        .unwrap();
+
+    let packaged_buildpack_dir_resolver = create_packaged_buildpack_dir_resolver(
+        &fixture_dir.path().join(DEFAULT_PACKAGE_DIR_NAME),
+        CargoProfile::Release,
+        X86_64_UNKNOWN_LINUX_MUSL,
+    );
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "{}\n",
+            [
+                packaged_buildpack_dir_resolver(&buildpack_id!(
+                    "multiple-buildpacks/composite-one"
+                )),
+                packaged_buildpack_dir_resolver(&buildpack_id!("multiple-buildpacks/one")),
+                packaged_buildpack_dir_resolver(&buildpack_id!("multiple-buildpacks/two")),
+            ]
+            .map(|path| path.to_string_lossy().into_owned())
+            .join("\n")
+        )
+    );
 }
 
 #[test]
@@ -228,6 +342,24 @@ fn package_command_error_when_run_in_project_with_no_buildpacks() {
     );
 }
 
+#[test]
+#[ignore = "integration test"]
+fn check_buildpack_in_single_buildpack_project() {
+    let fixture_dir = copy_fixture_to_temp_dir("single_buildpack").unwrap();
+
+    let output = Command::new(CARGO_LIBCNB_BINARY_UNDER_TEST)
+        .args(["libcnb", "check"])
+        .current_dir(&fixture_dir)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "single_buildpack:\n  MSRV not specified, libcnb.rs MSRV is unknown\n  No usages of deprecated libcnb.rs APIs\n"
+    );
+}
+
 #[test]
 #[ignore = "integration test"]
 fn package_command_respects_ignore_files() {
@@ -282,6 +414,7 @@ fn validate_packaged_buildpack(packaged_buildpack_dir: &Path, buildpack_id: &Bui
     assert!(packaged_buildpack_dir.join("package.toml").exists());
     assert!(packaged_buildpack_dir.join("bin").join("build").exists());
     assert!(packaged_buildpack_dir.join("bin").join("detect").exists());
+    assert!(packaged_buildpack_dir.join("CHECKSUMS.sha256").exists());
 
     assert_eq!(
         &read_toml_file::<BuildpackDescriptor>(packaged_buildpack_dir.join("buildpack.toml"))
@@ -0,0 +1,122 @@
+//! A global switch for colored output (ANSI escape codes) of libcnb.rs crates.
+//!
+//! Colors are enabled by default since platforms usually render ANSI escape codes, even though
+//! buildpack output is not written to a TTY. Platforms that embed buildpack or tool output in
+//! plain text logs can disable colors for all libcnb.rs crates at once, either by setting
+//! `NO_COLOR` (<https://no-color.org/>) or via [`set_color_output`]. `CLICOLOR_FORCE`
+//! (<https://bixense.com/clicolors/>) force-enables colors regardless of `NO_COLOR`.
+//!
+//! This switch is honored by:
+//! - the `log` module of `libherokubuildpack`,
+//! - the libcnb runtime, which also reads `NO_COLOR` and `CLICOLOR_FORCE` from the platform
+//!   environment (`/platform/env`),
+//! - `cargo libcnb`, which passes `--color never` to Cargo if colors are disabled.
+
+use std::env;
+use std::ffi::OsStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// The name of the environment variable that disables colored output if set to a non-empty value.
+pub const NO_COLOR_ENV_VAR_NAME: &str = "NO_COLOR";
+
+/// The name of the environment variable that force-enables colored output if set to a non-empty
+/// value other than `0`.
+pub const CLICOLOR_FORCE_ENV_VAR_NAME: &str = "CLICOLOR_FORCE";
+
+const NO_OVERRIDE: u8 = 0;
+const OVERRIDE_ENABLED: u8 = 1;
+const OVERRIDE_DISABLED: u8 = 2;
+
+static COLOR_OUTPUT_OVERRIDE: AtomicU8 = AtomicU8::new(NO_OVERRIDE);
+
+/// Returns whether colored output is enabled.
+///
+/// A value set with [`set_color_output`] takes precedence over the `NO_COLOR` and
+/// `CLICOLOR_FORCE` environment variables of the current process. Defaults to `true`.
+#[must_use]
+pub fn color_output_enabled() -> bool {
+    match COLOR_OUTPUT_OVERRIDE.load(Ordering::Relaxed) {
+        OVERRIDE_ENABLED => true,
+        OVERRIDE_DISABLED => false,
+        _ => color_output_from_env_vars(
+            env::var_os(NO_COLOR_ENV_VAR_NAME).as_deref(),
+            env::var_os(CLICOLOR_FORCE_ENV_VAR_NAME).as_deref(),
+        )
+        .unwrap_or(true),
+    }
+}
+
+/// Enables or disables colored output for the current process, regardless of the environment.
+pub fn set_color_output(enabled: bool) {
+    COLOR_OUTPUT_OVERRIDE.store(
+        if enabled {
+            OVERRIDE_ENABLED
+        } else {
+            OVERRIDE_DISABLED
+        },
+        Ordering::Relaxed,
+    );
+}
+
+/// Determines whether colored output is enabled from the values of the `NO_COLOR` and
+/// `CLICOLOR_FORCE` environment variables.
+///
+/// Returns `None` if neither variable has an effect.
+///
+/// # Example
+/// ```
+/// use libcnb_common::color::color_output_from_env_vars;
+/// use std::ffi::OsStr;
+///
+/// assert_eq!(color_output_from_env_vars(Some(OsStr::new("1")), None), Some(false));
+/// assert_eq!(color_output_from_env_vars(None, Some(OsStr::new("1"))), Some(true));
+/// assert_eq!(color_output_from_env_vars(None, None), None);
+/// ```
+#[must_use]
+pub fn color_output_from_env_vars(
+    no_color: Option<&OsStr>,
+    clicolor_force: Option<&OsStr>,
+) -> Option<bool> {
+    if clicolor_force.is_some_and(|value| !value.is_empty() && value != "0") {
+        Some(true)
+    } else if no_color.is_some_and(|value| !value.is_empty()) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_output_from_env_vars() {
+        assert_eq!(super::color_output_from_env_vars(None, None), None);
+        assert_eq!(
+            super::color_output_from_env_vars(Some(OsStr::new("1")), None),
+            Some(false)
+        );
+        assert_eq!(
+            super::color_output_from_env_vars(Some(OsStr::new("")), None),
+            None
+        );
+        assert_eq!(
+            super::color_output_from_env_vars(Some(OsStr::new("1")), Some(OsStr::new("1"))),
+            Some(true)
+        );
+        assert_eq!(
+            super::color_output_from_env_vars(Some(OsStr::new("1")), Some(OsStr::new("0"))),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn set_color_output() {
+        super::set_color_output(false);
+        assert!(!color_output_enabled());
+
+        super::set_color_output(true);
+        assert!(color_output_enabled());
+    }
+}
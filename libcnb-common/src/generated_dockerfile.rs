@@ -0,0 +1,102 @@
+//! Printing of the Dockerfiles generated by image extensions, so that integration tests can assert
+//! on them.
+//!
+//! The Dockerfiles an extension generates are only written to a directory of the lifecycle
+//! container that is discarded after the build. If enabled via
+//! [`PRINT_GENERATED_DOCKERFILES_ENV_VAR_NAME`], the libcnb extension runtime therefore also
+//! prints them, delimited by markers, to stdout. `libcnb-test` enables this for its builds and
+//! parses the Dockerfiles from the `pack` output with [`parse_generated_dockerfiles`].
+
+/// Enables printing of generated Dockerfiles when set to `true`, either in the platform or process
+/// environment.
+pub const PRINT_GENERATED_DOCKERFILES_ENV_VAR_NAME: &str = "LIBCNB_PRINT_GENERATED_DOCKERFILES";
+
+const BEGIN_MARKER: &str = "[libcnb generated Dockerfile begin]";
+const END_MARKER: &str = "[libcnb generated Dockerfile end]";
+
+/// Formats a generated Dockerfile for printing, delimited by markers.
+///
+/// The `file_name` is the name of the Dockerfile in the output directory of the extension, i.e.
+/// `build.Dockerfile` or `run.Dockerfile`.
+#[must_use]
+pub fn format_generated_dockerfile(extension_id: &str, file_name: &str, contents: &str) -> String {
+    let newline = if contents.ends_with('\n') { "" } else { "\n" };
+    format!("{BEGIN_MARKER} {extension_id} {file_name}\n{contents}{newline}{END_MARKER}\n")
+}
+
+/// Parses all generated Dockerfiles printed with [`format_generated_dockerfile`] from the given
+/// output, returning the extension ID, file name and contents of each in order of appearance.
+///
+/// Text preceding the begin marker on its line, i.e. the `[builder]` prefix of verbose `pack`
+/// output, is removed from all lines of the Dockerfile. Dockerfiles without an end marker are
+/// ignored.
+#[must_use]
+pub fn parse_generated_dockerfiles(output: &str) -> Vec<(String, String, String)> {
+    let mut dockerfiles = Vec::new();
+    let mut lines = output.lines();
+
+    while let Some(line) = lines.next() {
+        let Some((prefix, header)) = line.split_once(BEGIN_MARKER) else {
+            continue;
+        };
+
+        let Some((extension_id, file_name)) = header.trim().split_once(' ') else {
+            continue;
+        };
+
+        let mut contents = String::new();
+        for line in lines.by_ref() {
+            let line = line.strip_prefix(prefix).unwrap_or(line);
+
+            if line == END_MARKER {
+                dockerfiles.push((extension_id.to_string(), file_name.to_string(), contents));
+                break;
+            }
+
+            contents.push_str(line);
+            contents.push('\n');
+        }
+    }
+
+    dockerfiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_and_parse_generated_dockerfiles() {
+        let output = format!(
+            "===> GENERATING\n{}[builder] {}[builder] Done\n",
+            format_generated_dockerfile("foo/bar", "build.Dockerfile", "FROM base\nUSER root\n"),
+            format_generated_dockerfile("foo/baz", "run.Dockerfile", "FROM run-base")
+                .replace('\n', "\n[builder] ")
+                .trim_end_matches("[builder] ")
+        );
+
+        assert_eq!(
+            parse_generated_dockerfiles(&output),
+            vec![
+                (
+                    String::from("foo/bar"),
+                    String::from("build.Dockerfile"),
+                    String::from("FROM base\nUSER root\n")
+                ),
+                (
+                    String::from("foo/baz"),
+                    String::from("run.Dockerfile"),
+                    String::from("FROM run-base\n")
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_generated_dockerfiles_without_end_marker() {
+        let output = format_generated_dockerfile("foo/bar", "build.Dockerfile", "FROM base")
+            .replace(END_MARKER, "");
+
+        assert_eq!(parse_generated_dockerfiles(&output), Vec::new());
+    }
+}
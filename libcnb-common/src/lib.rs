@@ -1,3 +1,5 @@
 #![doc = include_str!("../README.md")]
 
+pub mod color;
+pub mod generated_dockerfile;
 pub mod toml_file;
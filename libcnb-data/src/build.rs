@@ -1,6 +1,57 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Debug)]
+/// Data Structure for the build.toml file.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
 pub struct Build {
-    pub unmet: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unmet: Vec<Unmet>,
+}
+
+/// A buildpack plan entry the buildpack did not provide.
+///
+/// Unmet entries are not removed from the buildpack plan and are passed on to subsequent
+/// buildpacks that require them.
+#[derive(Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Unmet {
+    /// The name of the buildpack plan entry.
+    pub name: String,
+}
+
+impl Unmet {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_unmet_entries() {
+        let build = Build {
+            unmet: vec![Unmet::new("ruby"), Unmet::new("node")],
+        };
+
+        assert_eq!(
+            toml::to_string(&build).unwrap(),
+            "[[unmet]]\nname = \"ruby\"\n\n[[unmet]]\nname = \"node\"\n"
+        );
+        assert_eq!(toml::to_string(&Build::default()).unwrap(), "");
+    }
+
+    #[test]
+    fn deserialize_unmet_entries() {
+        let build: Build = toml::from_str(
+            r#"
+[[unmet]]
+name = "ruby"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(build.unmet, [Unmet::new("ruby")]);
+    }
 }
@@ -43,6 +43,18 @@ impl BuildPlanBuilder {
         self
     }
 
+    /// Adds a requirement with the given metadata to the current alternative of the build plan.
+    ///
+    /// # Errors
+    /// Returns an error if the metadata can't be serialized as a TOML table.
+    pub fn requires_with_metadata<T: Serialize>(
+        self,
+        name: impl Into<String>,
+        metadata: T,
+    ) -> Result<Self, toml::ser::Error> {
+        Require::with_metadata(name, metadata).map(|require| self.requires(require))
+    }
+
     pub fn or(mut self) -> Self {
         self.acc
             .push_back((self.current_provides, self.current_requires));
@@ -107,6 +119,20 @@ impl Require {
         }
     }
 
+    /// Creates a requirement with the given metadata.
+    ///
+    /// # Errors
+    /// This will return error for any normal TOML serialization error as well if it's not
+    /// possible to serialize as a TOML Table.
+    pub fn with_metadata<T: Serialize>(
+        name: impl Into<String>,
+        metadata: T,
+    ) -> Result<Self, toml::ser::Error> {
+        let mut require = Self::new(name);
+        require.metadata(metadata)?;
+        Ok(require)
+    }
+
     /// Convert a Serializable struct and store it as a toml Table for metadata
     ///
     /// # Errors
@@ -162,4 +188,50 @@ mod tests {
             Some(&toml::Value::String(String::from("bar")))
         );
     }
+
+    #[test]
+    fn it_builds_build_plan_with_metadata() {
+        #[derive(Serialize)]
+        struct Metadata {
+            version: String,
+        }
+
+        let build_plan = BuildPlanBuilder::new()
+            .provides("jdk")
+            .requires_with_metadata(
+                "jdk",
+                Metadata {
+                    version: String::from("21"),
+                },
+            )
+            .unwrap()
+            .or()
+            .requires("jdk")
+            .build();
+
+        assert_eq!(
+            toml::to_string(&build_plan).unwrap(),
+            r#"[[provides]]
+name = "jdk"
+
+[[requires]]
+name = "jdk"
+
+[requires.metadata]
+version = "21"
+
+[[or]]
+
+[[or.requires]]
+name = "jdk"
+
+[or.requires.metadata]
+"#
+        );
+    }
+
+    #[test]
+    fn it_rejects_non_table_metadata() {
+        assert!(Require::with_metadata("foo", "bar").is_err());
+    }
 }
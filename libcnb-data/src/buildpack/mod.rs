@@ -4,7 +4,7 @@ mod stack;
 mod target;
 mod version;
 
-use crate::generic::GenericMetadata;
+use crate::generic::{optional_metadata_section, GenericMetadata, MetadataSectionError};
 use crate::sbom::SbomFormat;
 pub use api::*;
 pub use id::*;
@@ -71,6 +71,53 @@ impl<BM> BuildpackDescriptor<BM> {
     }
 }
 
+impl BuildpackDescriptor<GenericMetadata> {
+    /// Returns the libcnb.rs specific metadata of the buildpack, declared in the
+    /// `[metadata.libcnb]` table. Returns the defaults if the table doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `[metadata.libcnb]` table is invalid.
+    pub fn libcnb_metadata(&self) -> Result<LibcnbBuildpackMetadata, MetadataSectionError> {
+        let metadata = match self {
+            BuildpackDescriptor::Component(descriptor) => &descriptor.metadata,
+            BuildpackDescriptor::Composite(descriptor) => &descriptor.metadata,
+        };
+
+        optional_metadata_section(metadata, "libcnb").map(Option::unwrap_or_default)
+    }
+}
+
+/// Capabilities of a buildpack that libcnb.rs tooling can verify, declared in the
+/// `[metadata.libcnb]` table of `buildpack.toml`.
+///
+/// # Example:
+/// ```
+/// use libcnb_data::buildpack::BuildpackDescriptor;
+///
+/// let toml_str = r#"
+/// api = "0.10"
+///
+/// [buildpack]
+/// id = "foo/bar"
+/// version = "0.0.1"
+///
+/// [metadata.libcnb]
+/// network-isolation = true
+/// "#;
+///
+/// let buildpack_descriptor = toml::from_str::<BuildpackDescriptor>(toml_str).unwrap();
+/// assert!(buildpack_descriptor.libcnb_metadata().unwrap().network_isolation);
+/// ```
+#[derive(Deserialize, Debug, Default, Clone, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct LibcnbBuildpackMetadata {
+    /// Whether the buildpack supports builds without network access. `libcnb-test` verifies this
+    /// by building with `pack build --network none` if all buildpacks of a build declare it.
+    #[serde(default)]
+    pub network_isolation: bool,
+}
+
 /// Data structure for the Buildpack descriptor (buildpack.toml) of a component buildpack.
 ///
 /// Representation of [buildpack.toml](https://github.com/buildpacks/spec/blob/main/buildpack.md#buildpacktoml-toml)
@@ -233,6 +280,32 @@ pub struct Group {
 mod tests {
     use super::*;
 
+    #[test]
+    fn libcnb_metadata() {
+        let descriptor = |metadata: &str| {
+            toml::from_str::<BuildpackDescriptor>(&format!(
+                "api = \"0.10\"\n[buildpack]\nid = \"foo/bar\"\nversion = \"0.0.1\"\n{metadata}"
+            ))
+            .unwrap()
+        };
+
+        assert_eq!(
+            descriptor("").libcnb_metadata().unwrap(),
+            LibcnbBuildpackMetadata::default()
+        );
+        assert_eq!(
+            descriptor("[metadata.libcnb]\nnetwork-isolation = true")
+                .libcnb_metadata()
+                .unwrap(),
+            LibcnbBuildpackMetadata {
+                network_isolation: true
+            }
+        );
+        assert!(descriptor("[metadata.libcnb]\nnetwork-isolation = \"yes\"")
+            .libcnb_metadata()
+            .is_err());
+    }
+
     #[test]
     #[allow(clippy::too_many_lines)]
     fn deserialize_component_buildpack() {
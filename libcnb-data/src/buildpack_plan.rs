@@ -1,3 +1,4 @@
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use toml::value::Table;
 
@@ -8,6 +9,53 @@ pub struct BuildpackPlan {
     pub entries: Vec<Entry>,
 }
 
+impl BuildpackPlan {
+    /// Deserializes the metadata of all entries with the given name.
+    ///
+    /// The result contains one element per matching entry, in plan order.
+    ///
+    /// # Errors
+    /// This will return an error if the metadata of any matching entry can't be deserialized into
+    /// a `T`.
+    pub fn entries_metadata<T: DeserializeOwned>(
+        &self,
+        name: &str,
+    ) -> Result<Vec<T>, toml::de::Error> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.name == name)
+            .map(Entry::metadata)
+            .collect()
+    }
+
+    /// Merges the metadata of all entries with the given name and deserializes the result.
+    ///
+    /// Entries are merged in plan order, top-level keys of later entries override the keys of
+    /// earlier ones. Returns `None` if there is no entry with the given name.
+    ///
+    /// # Errors
+    /// This will return an error if the merged metadata can't be deserialized into a `T`.
+    pub fn merged_entry_metadata<T: DeserializeOwned>(
+        &self,
+        name: &str,
+    ) -> Result<Option<T>, toml::de::Error> {
+        let mut matching_entries = self.entries.iter().filter(|entry| entry.name == name);
+
+        matching_entries
+            .next()
+            .map(|first_entry| {
+                let merged_metadata =
+                    matching_entries.fold(first_entry.metadata.clone(), |mut merged, entry| {
+                        merged.extend(entry.metadata.clone());
+                        merged
+                    });
+
+                toml::Value::Table(merged_metadata).try_into()
+            })
+            .transpose()
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Entry {
@@ -93,4 +141,79 @@ name = "rust"
             })
         );
     }
+
+    #[test]
+    fn it_deserializes_entries_metadata() {
+        #[derive(Deserialize, Eq, PartialEq, Debug)]
+        struct Metadata {
+            version: String,
+        }
+
+        let buildpack_plan = toml::from_str::<BuildpackPlan>(
+            r#"
+[[entries]]
+name = "jdk"
+metadata = { version = "21" }
+
+[[entries]]
+name = "maven"
+metadata = { version = "3.9.9" }
+
+[[entries]]
+name = "jdk"
+metadata = { version = "17" }
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            buildpack_plan.entries_metadata("jdk"),
+            Ok(vec![
+                Metadata {
+                    version: String::from("21")
+                },
+                Metadata {
+                    version: String::from("17")
+                }
+            ])
+        );
+        assert_eq!(
+            buildpack_plan.entries_metadata::<Metadata>("node"),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn it_merges_entry_metadata() {
+        #[derive(Deserialize, Eq, PartialEq, Debug)]
+        struct Metadata {
+            version: String,
+            distribution: Option<String>,
+        }
+
+        let buildpack_plan = toml::from_str::<BuildpackPlan>(
+            r#"
+[[entries]]
+name = "jdk"
+metadata = { version = "21", distribution = "temurin" }
+
+[[entries]]
+name = "jdk"
+metadata = { version = "17" }
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            buildpack_plan.merged_entry_metadata("jdk"),
+            Ok(Some(Metadata {
+                version: String::from("17"),
+                distribution: Some(String::from("temurin"))
+            }))
+        );
+        assert_eq!(
+            buildpack_plan.merged_entry_metadata::<Metadata>("node"),
+            Ok(None)
+        );
+    }
 }
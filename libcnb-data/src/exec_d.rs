@@ -1,17 +1,25 @@
 use crate::newtypes::libcnb_newtype;
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Output of a CNB exec.d program.
 ///
+/// Keys are serialized in sorted order, which keeps the output stable.
+///
 /// See [Cloud Native Buildpack specification](https://github.com/buildpacks/spec/blob/main/buildpack.md#execd)
-#[derive(Serialize, Clone)]
-pub struct ExecDProgramOutput(HashMap<ExecDProgramOutputKey, String>);
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct ExecDProgramOutput(BTreeMap<ExecDProgramOutputKey, String>);
 
 impl ExecDProgramOutput {
     #[must_use]
     pub fn new(map: HashMap<ExecDProgramOutputKey, String>) -> Self {
-        Self(map)
+        Self(map.into_iter().collect())
+    }
+
+    /// Returns the value for the given key, if present.
+    #[must_use]
+    pub fn get(&self, key: &ExecDProgramOutputKey) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
     }
 }
 
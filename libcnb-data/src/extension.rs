@@ -0,0 +1,215 @@
+use crate::buildpack::{BuildpackApi, BuildpackId, BuildpackTarget, BuildpackVersion, License};
+use crate::generic::GenericMetadata;
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the image extension descriptor (extension.toml).
+///
+/// Representation of [extension.toml](https://github.com/buildpacks/spec/blob/main/image_extension.md#extensiontoml-toml).
+///
+/// # Example:
+/// ```
+/// use libcnb_data::buildpack_id;
+/// use libcnb_data::extension::ExtensionDescriptor;
+///
+/// let toml_str = r#"
+/// api = "0.10"
+///
+/// [extension]
+/// id = "foo/bar"
+/// name = "Bar Extension"
+/// version = "0.0.1"
+/// homepage = "https://www.foo.com/bar"
+/// description = "An extension for Foo Bar"
+/// keywords = ["foo"]
+///
+/// [[extension.licenses]]
+/// type = "BSD-3-Clause"
+/// "#;
+///
+/// let extension_descriptor = toml::from_str::<ExtensionDescriptor>(toml_str).unwrap();
+/// assert_eq!(extension_descriptor.extension.id, buildpack_id!("foo/bar"));
+/// ```
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ExtensionDescriptor<EM = GenericMetadata> {
+    pub api: BuildpackApi,
+    pub extension: Extension,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<BuildpackTarget>,
+    pub metadata: EM,
+}
+
+/// The `[extension]` table of an image extension descriptor (extension.toml).
+///
+/// Extension IDs follow the same rules as buildpack IDs and are therefore represented as
+/// [`BuildpackId`] values.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Extension {
+    pub id: BuildpackId,
+    pub name: Option<String>,
+    pub version: BuildpackVersion,
+    pub homepage: Option<String>,
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub licenses: Vec<License>,
+}
+
+/// Data structure for the `extend-config.toml` file written by an image extension.
+///
+/// Contains the build arguments that will be passed to the `build.Dockerfile` and `run.Dockerfile`
+/// generated by the extension.
+///
+/// See the [CNB image extension specification](https://github.com/buildpacks/spec/blob/main/image_extension.md#extend-configtoml-toml)
+/// for details.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ExtendConfig {
+    #[serde(default, skip_serializing_if = "DockerfileArgs::is_empty")]
+    pub build: DockerfileArgs,
+    #[serde(default, skip_serializing_if = "DockerfileArgs::is_empty")]
+    pub run: DockerfileArgs,
+}
+
+impl ExtendConfig {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.build.is_empty() && self.run.is_empty()
+    }
+}
+
+/// Build arguments for a Dockerfile generated by an image extension.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DockerfileArgs {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<DockerfileArg>,
+}
+
+impl DockerfileArgs {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.args.is_empty()
+    }
+}
+
+/// A single build argument for a Dockerfile generated by an image extension.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DockerfileArg {
+    pub name: String,
+    pub value: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_extension_descriptor() {
+        let toml_str = r#"
+api = "0.10"
+
+[extension]
+id = "foo/bar"
+name = "Bar Extension"
+version = "0.0.1"
+homepage = "https://example.tld"
+description = "An extension for Foo Bar"
+keywords = ["foo", "bar"]
+
+[[extension.licenses]]
+type = "BSD-3-Clause"
+
+[[targets]]
+os = "linux"
+arch = "amd64"
+
+[metadata]
+checksum = "abc123"
+"#;
+
+        let extension_descriptor = toml::from_str::<ExtensionDescriptor>(toml_str).unwrap();
+
+        assert_eq!(
+            extension_descriptor.api,
+            BuildpackApi {
+                major: 0,
+                minor: 10
+            }
+        );
+        assert_eq!(
+            extension_descriptor.extension.id,
+            "foo/bar".parse::<BuildpackId>().unwrap()
+        );
+        assert_eq!(
+            extension_descriptor.extension.name,
+            Some(String::from("Bar Extension"))
+        );
+        assert_eq!(
+            extension_descriptor.extension.version,
+            BuildpackVersion::new(0, 0, 1)
+        );
+        assert_eq!(
+            extension_descriptor.extension.licenses,
+            [License {
+                r#type: Some(String::from("BSD-3-Clause")),
+                uri: None
+            }]
+        );
+        assert_eq!(extension_descriptor.targets.len(), 1);
+        assert_eq!(
+            extension_descriptor.metadata.unwrap().get("checksum"),
+            Some(&toml::value::Value::try_from("abc123").unwrap())
+        );
+    }
+
+    #[test]
+    fn deserialize_minimal_extension_descriptor() {
+        let toml_str = r#"
+api = "0.10"
+
+[extension]
+id = "foo/bar"
+version = "0.0.1"
+"#;
+
+        let extension_descriptor = toml::from_str::<ExtensionDescriptor>(toml_str).unwrap();
+
+        assert_eq!(
+            extension_descriptor.extension.id,
+            "foo/bar".parse::<BuildpackId>().unwrap()
+        );
+        assert_eq!(extension_descriptor.extension.name, None);
+        assert_eq!(extension_descriptor.targets, Vec::new());
+        assert_eq!(extension_descriptor.metadata, None);
+    }
+
+    #[test]
+    fn serialize_extend_config() {
+        let extend_config = ExtendConfig {
+            build: DockerfileArgs {
+                args: vec![DockerfileArg {
+                    name: String::from("NODE_VERSION"),
+                    value: String::from("22.12.0"),
+                }],
+            },
+            run: DockerfileArgs::default(),
+        };
+
+        assert_eq!(
+            toml::to_string(&extend_config).unwrap(),
+            r#"[[build.args]]
+name = "NODE_VERSION"
+value = "22.12.0"
+"#
+        );
+    }
+
+    #[test]
+    fn serialize_empty_extend_config() {
+        assert_eq!(toml::to_string(&ExtendConfig::default()).unwrap(), "");
+    }
+}
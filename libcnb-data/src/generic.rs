@@ -1,2 +1,173 @@
+use serde::de::DeserializeOwned;
+
 /// Generic TOML metadata.
 pub type GenericMetadata = Option<toml::value::Table>;
+
+/// Deserializes a namespaced section of generic metadata into a typed value.
+///
+/// Buildpacks commonly store configuration in dedicated sections of their `buildpack.toml`
+/// metadata, i.e. `[metadata.dependencies]` or `[metadata.configure]`. The section is specified as
+/// a dot-separated path of keys, relative to the metadata table.
+///
+/// # Example
+/// ```
+/// use libcnb_data::generic::{metadata_section, GenericMetadata};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Dependency {
+///     name: String,
+///     version: String,
+/// }
+///
+/// let metadata: GenericMetadata = toml::from_str(
+///     r#"
+/// [[dependencies.java]]
+/// name = "openjdk"
+/// version = "21.0.2"
+/// "#,
+/// )
+/// .unwrap();
+///
+/// let dependencies = metadata_section::<Vec<Dependency>>(&metadata, "dependencies.java").unwrap();
+/// assert_eq!(dependencies[0].name, "openjdk");
+/// assert_eq!(dependencies[0].version, "21.0.2");
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the section doesn't exist or can't be deserialized into the given type.
+pub fn metadata_section<T: DeserializeOwned>(
+    metadata: &GenericMetadata,
+    key_path: &str,
+) -> Result<T, MetadataSectionError> {
+    optional_metadata_section(metadata, key_path)?
+        .ok_or_else(|| MetadataSectionError::MissingSection(metadata_key_path(key_path)))
+}
+
+/// Deserializes a namespaced section of generic metadata into a typed value, if it exists.
+///
+/// Like [`metadata_section`], but returns `Ok(None)` if the section doesn't exist.
+///
+/// # Errors
+///
+/// Returns an error if the section can't be deserialized into the given type, or one of the keys
+/// in the path to the section isn't a table.
+pub fn optional_metadata_section<T: DeserializeOwned>(
+    metadata: &GenericMetadata,
+    key_path: &str,
+) -> Result<Option<T>, MetadataSectionError> {
+    let Some(table) = metadata else {
+        return Ok(None);
+    };
+
+    let mut keys = key_path.split('.');
+    let Some(mut value) = keys.next().and_then(|key| table.get(key)) else {
+        return Ok(None);
+    };
+
+    for (index, key) in keys.enumerate() {
+        let Some(table) = value.as_table() else {
+            let parent_key_path = key_path.split('.').take(index + 1).collect::<Vec<_>>();
+            return Err(MetadataSectionError::NotATable(metadata_key_path(
+                &parent_key_path.join("."),
+            )));
+        };
+
+        match table.get(key) {
+            Some(child_value) => value = child_value,
+            None => return Ok(None),
+        }
+    }
+
+    value
+        .clone()
+        .try_into()
+        .map(Some)
+        .map_err(|error| MetadataSectionError::InvalidSection(metadata_key_path(key_path), error))
+}
+
+fn metadata_key_path(key_path: &str) -> String {
+    format!("metadata.{key_path}")
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum MetadataSectionError {
+    #[error("Metadata section {0} is missing")]
+    MissingSection(String),
+
+    #[error("Metadata key {0} is not a table")]
+    NotATable(String),
+
+    #[error("Metadata section {0} is invalid: {1}")]
+    InvalidSection(String, toml::de::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug, Eq, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    struct Configure {
+        default_version: String,
+    }
+
+    fn metadata() -> GenericMetadata {
+        toml::from_str(
+            r#"
+release-notes = "https://example.com"
+
+[configure]
+default_version = "3.3"
+
+[configure.invalid]
+default_version = 3
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn existing_section() {
+        assert_eq!(
+            metadata_section::<toml::Table>(&metadata(), "configure.invalid")
+                .unwrap()
+                .get("default_version"),
+            Some(&toml::Value::Integer(3))
+        );
+        assert_eq!(
+            optional_metadata_section::<String>(&metadata(), "release-notes").unwrap(),
+            Some(String::from("https://example.com"))
+        );
+    }
+
+    #[test]
+    fn missing_section() {
+        assert!(matches!(
+            metadata_section::<Configure>(&metadata(), "dependencies"),
+            Err(MetadataSectionError::MissingSection(ref key_path)) if key_path == "metadata.dependencies"
+        ));
+        assert!(matches!(
+            metadata_section::<Configure>(&None, "configure"),
+            Err(MetadataSectionError::MissingSection(_))
+        ));
+        assert_eq!(
+            optional_metadata_section::<Configure>(&metadata(), "configure.missing").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn invalid_section() {
+        assert!(matches!(
+            metadata_section::<Configure>(&metadata(), "configure.invalid"),
+            Err(MetadataSectionError::InvalidSection(ref key_path, _)) if key_path == "metadata.configure.invalid"
+        ));
+        assert!(matches!(
+            metadata_section::<Configure>(&metadata(), "release-notes.foo"),
+            Err(MetadataSectionError::NotATable(ref key_path)) if key_path == "metadata.release-notes"
+        ));
+    }
+}
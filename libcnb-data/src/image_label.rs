@@ -0,0 +1,267 @@
+//! Well-known labels of images built by the CNB lifecycle.
+//!
+//! The lifecycle adds several labels with JSON payloads to every app image it exports. This module
+//! contains their names and types to parse their payloads. See the
+//! [CNB platform specification](https://github.com/buildpacks/spec/blob/main/platform.md#labels)
+//! for details.
+//!
+//! The types in this module only contain a subset of the data in the labels and ignore any unknown
+//! fields, to remain compatible with future lifecycle versions.
+//!
+//! # Example:
+//! ```
+//! use libcnb_data::image_label::BuildMetadata;
+//!
+//! let label_value = r#"{
+//!   "processes": [{ "type": "web", "command": ["bundle"], "args": ["exec", "rails", "s"], "direct": true, "buildpackID": "heroku/ruby" }],
+//!   "buildpacks": [{ "id": "heroku/ruby", "version": "1.0.0" }]
+//! }"#;
+//!
+//! let build_metadata = label_value.parse::<BuildMetadata>().unwrap();
+//! assert_eq!(build_metadata.processes[0].r#type, "web");
+//! ```
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// The name of the label containing the [`BuildMetadata`] of an app image.
+pub const BUILD_METADATA_LABEL: &str = "io.buildpacks.build.metadata";
+
+/// The name of the label containing the [`LifecycleMetadata`] of an app image.
+pub const LIFECYCLE_METADATA_LABEL: &str = "io.buildpacks.lifecycle.metadata";
+
+/// The name of the label containing the [`ProjectMetadata`] of an app image.
+pub const PROJECT_METADATA_LABEL: &str = "io.buildpacks.project.metadata";
+
+/// Payload of the `io.buildpacks.build.metadata` label.
+#[derive(Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct BuildMetadata {
+    #[serde(default)]
+    pub processes: Vec<BuildMetadataProcess>,
+    #[serde(default)]
+    pub buildpacks: Vec<BuildMetadataBuildpack>,
+    #[serde(default)]
+    pub extensions: Vec<BuildMetadataBuildpack>,
+    pub launcher: Option<BuildMetadataLauncher>,
+}
+
+/// A process in the [`BuildMetadata`] of an app image.
+#[derive(Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct BuildMetadataProcess {
+    pub r#type: String,
+    #[serde(default)]
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub direct: bool,
+    #[serde(rename = "working-dir")]
+    pub working_dir: Option<String>,
+    #[serde(rename = "buildpackID")]
+    pub buildpack_id: String,
+}
+
+/// A buildpack or extension in the [`BuildMetadata`] of an app image.
+#[derive(Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct BuildMetadataBuildpack {
+    pub id: String,
+    pub version: String,
+    pub homepage: Option<String>,
+}
+
+/// The launcher in the [`BuildMetadata`] of an app image.
+#[derive(Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct BuildMetadataLauncher {
+    pub version: String,
+}
+
+/// Payload of the `io.buildpacks.lifecycle.metadata` label.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct LifecycleMetadata {
+    #[serde(default)]
+    pub buildpacks: Vec<LifecycleMetadataBuildpack>,
+    #[serde(rename = "runImage")]
+    pub run_image: Option<LifecycleMetadataRunImage>,
+}
+
+/// A buildpack and its exported layers in the [`LifecycleMetadata`] of an app image.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct LifecycleMetadataBuildpack {
+    pub key: String,
+    pub version: String,
+    #[serde(default)]
+    pub layers: BTreeMap<String, LifecycleMetadataLayer>,
+}
+
+/// An exported layer in the [`LifecycleMetadata`] of an app image.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct LifecycleMetadataLayer {
+    pub sha: String,
+    #[serde(default)]
+    pub build: bool,
+    #[serde(default)]
+    pub launch: bool,
+    #[serde(default)]
+    pub cache: bool,
+    /// The layer metadata, as written by the buildpack to the `[metadata]` table of the layer
+    /// content metadata.
+    pub data: Option<serde_json::Value>,
+}
+
+/// The run image in the [`LifecycleMetadata`] of an app image.
+#[derive(Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct LifecycleMetadataRunImage {
+    #[serde(rename = "topLayer")]
+    pub top_layer: Option<String>,
+    pub reference: Option<String>,
+    pub image: Option<String>,
+}
+
+/// Payload of the `io.buildpacks.project.metadata` label.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ProjectMetadata {
+    pub source: Option<ProjectMetadataSource>,
+}
+
+/// The source of the app in the [`ProjectMetadata`] of an app image.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ProjectMetadataSource {
+    pub r#type: String,
+    #[serde(default)]
+    pub version: BTreeMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub metadata: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("Couldn't parse image label value: {0}")]
+pub struct ParseImageLabelError(#[from] serde_json::Error);
+
+macro_rules! impl_from_str_json {
+    ($($type:ty),+) => {
+        $(
+            impl FromStr for $type {
+                type Err = ParseImageLabelError;
+
+                fn from_str(value: &str) -> Result<Self, Self::Err> {
+                    serde_json::from_str(value).map_err(ParseImageLabelError)
+                }
+            }
+        )+
+    };
+}
+
+impl_from_str_json!(BuildMetadata, LifecycleMetadata, ProjectMetadata);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_build_metadata() {
+        let build_metadata = r#"{
+          "bom": null,
+          "buildpacks": [
+            { "id": "heroku/procfile", "version": "3.1.2", "homepage": "https://github.com/heroku/buildpacks-procfile" }
+          ],
+          "launcher": { "version": "0.20.5", "source": { "git": { "repository": "github.com/buildpacks/lifecycle", "commit": "abc" } } },
+          "processes": [
+            { "type": "web", "command": ["bash", "-c"], "args": ["echo hello"], "direct": true, "buildpackID": "heroku/procfile" },
+            { "type": "worker", "command": ["worker"], "direct": false, "working-dir": "/workspace/worker", "buildpackID": "heroku/procfile" }
+          ]
+        }"#
+        .parse::<BuildMetadata>()
+        .unwrap();
+
+        assert_eq!(
+            build_metadata.buildpacks,
+            [BuildMetadataBuildpack {
+                id: String::from("heroku/procfile"),
+                version: String::from("3.1.2"),
+                homepage: Some(String::from(
+                    "https://github.com/heroku/buildpacks-procfile"
+                )),
+            }]
+        );
+        assert_eq!(build_metadata.extensions, []);
+        assert_eq!(
+            build_metadata.launcher,
+            Some(BuildMetadataLauncher {
+                version: String::from("0.20.5")
+            })
+        );
+        assert_eq!(
+            build_metadata.processes[1],
+            BuildMetadataProcess {
+                r#type: String::from("worker"),
+                command: vec![String::from("worker")],
+                args: Vec::new(),
+                direct: false,
+                working_dir: Some(String::from("/workspace/worker")),
+                buildpack_id: String::from("heroku/procfile"),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_lifecycle_metadata() {
+        let lifecycle_metadata = r#"{
+          "app": [{ "sha": "sha256:1111" }],
+          "buildpacks": [
+            {
+              "key": "heroku/ruby",
+              "version": "1.0.0",
+              "layers": {
+                "gems": { "sha": "sha256:2222", "data": { "ruby_version": "3.3.0" }, "launch": true, "cache": true }
+              }
+            }
+          ],
+          "runImage": { "topLayer": "sha256:3333", "reference": "sha256:4444", "image": "heroku/heroku:24" }
+        }"#
+        .parse::<LifecycleMetadata>()
+        .unwrap();
+
+        let gems_layer = &lifecycle_metadata.buildpacks[0].layers["gems"];
+        assert_eq!(gems_layer.sha, "sha256:2222");
+        assert!(gems_layer.launch);
+        assert!(gems_layer.cache);
+        assert!(!gems_layer.build);
+        assert_eq!(
+            gems_layer.data,
+            Some(serde_json::json!({ "ruby_version": "3.3.0" }))
+        );
+        assert_eq!(
+            lifecycle_metadata.run_image,
+            Some(LifecycleMetadataRunImage {
+                top_layer: Some(String::from("sha256:3333")),
+                reference: Some(String::from("sha256:4444")),
+                image: Some(String::from("heroku/heroku:24")),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_project_metadata() {
+        let project_metadata = r#"{
+          "source": { "type": "git", "version": { "commit": "abc123" }, "metadata": { "refs": ["main"] } }
+        }"#
+        .parse::<ProjectMetadata>()
+        .unwrap();
+
+        let source = project_metadata.source.unwrap();
+        assert_eq!(source.r#type, "git");
+        assert_eq!(source.version["commit"], "abc123");
+        assert_eq!(source.metadata["refs"], serde_json::json!(["main"]));
+
+        assert_eq!(
+            "{}".parse::<ProjectMetadata>().unwrap(),
+            ProjectMetadata { source: None }
+        );
+    }
+
+    #[test]
+    fn parse_invalid_label_value() {
+        assert!("not json".parse::<BuildMetadata>().is_err());
+    }
+}
@@ -9,8 +9,9 @@ use std::str::FromStr;
 /// An inventory is made up of multiple artifacts that have a version that
 /// can be compared to each other and a URL where the artifact can be downloaded.
 ///
-/// Artifacts are OS and architectures specific. The checksum value can
-/// be used to validate an artifact once it has been downloaded.
+/// Artifacts are OS and architectures specific and can optionally be specific to a Linux
+/// distribution as well. The checksum value can be used to validate an artifact once it has been
+/// downloaded, regardless of whether it was downloaded from the primary URL or one of the mirrors.
 ///
 /// Metadata can be used to store additional information about the artifact.
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -19,13 +20,26 @@ pub struct Artifact<V, D, M> {
     pub version: V,
     pub os: Os,
     pub arch: Arch,
+    /// The distribution the artifact was built for, or `None` if it works on any distribution.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distro: Option<Distro>,
     pub url: String,
+    /// Alternative URLs the same artifact can be downloaded from, in order of preference.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<String>,
     #[serde(bound = "D: Digest")]
     pub checksum: Checksum<D>,
     #[serde(bound = "M: Serialize + DeserializeOwned")]
     pub metadata: M,
 }
 
+impl<V, D, M> Artifact<V, D, M> {
+    /// Returns the primary URL of the artifact, followed by its mirrors.
+    pub fn urls(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.url.as_str()).chain(self.mirrors.iter().map(String::as_str))
+    }
+}
+
 impl<V, D, M> PartialEq for Artifact<V, D, M>
 where
     V: Eq,
@@ -35,7 +49,9 @@ where
         self.version == other.version
             && self.os == other.os
             && self.arch == other.arch
+            && self.distro == other.distro
             && self.url == other.url
+            && self.mirrors == other.mirrors
             && self.checksum == other.checksum
             && self.metadata == other.metadata
     }
@@ -48,6 +64,25 @@ where
 {
 }
 
+/// A Linux distribution, i.e. Ubuntu 24.04.
+///
+/// The name and version use the same format as the `ID` and `VERSION_ID` fields of
+/// `/etc/os-release`, which is also what the CNB platform provides as the target distribution.
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct Distro {
+    pub name: String,
+    pub version: String,
+}
+
+impl Distro {
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: version.into(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum Os {
@@ -115,6 +150,7 @@ impl FromStr for Arch {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::inventory::checksum::tests::BogusDigest;
     use crate::inventory::version::VersionRequirement;
 
     #[test]
@@ -149,6 +185,28 @@ mod tests {
         assert_eq!("linux", Os::Linux.to_string());
     }
 
+    #[test]
+    fn test_artifact_urls() {
+        let artifact = Artifact {
+            version: String::from("1.0.0"),
+            os: Os::Linux,
+            arch: Arch::Amd64,
+            distro: None,
+            url: String::from("https://example.com/foo.tgz"),
+            mirrors: vec![String::from("https://mirror.example.com/foo.tgz")],
+            checksum: BogusDigest::checksum("cafebabe"),
+            metadata: (),
+        };
+
+        assert_eq!(
+            artifact.urls().collect::<Vec<_>>(),
+            [
+                "https://example.com/foo.tgz",
+                "https://mirror.example.com/foo.tgz"
+            ]
+        );
+    }
+
     #[test]
     fn test_os_parsing() {
         assert_eq!(Os::Linux, "linux".parse::<Os>().unwrap());
@@ -1,6 +1,7 @@
 use crate::newtypes::libcnb_newtype;
-use serde::{Deserialize, Serialize, Serializer};
-use std::path::PathBuf;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 /// Data Structure for the launch.toml file.
 #[derive(Deserialize, Serialize, Clone, Debug, Default)]
@@ -14,6 +15,172 @@ pub struct Launch {
     pub slices: Vec<Slice>,
 }
 
+impl Launch {
+    /// Validates the processes of this launch configuration.
+    ///
+    /// Process types have to be unique, at most one process can be the default process and every
+    /// process needs a non-empty command. The CNB lifecycle rejects launch configurations that
+    /// violate these constraints, but only after the build with less helpful error messages.
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb_data::launch::{LaunchBuilder, LaunchValidationError, ProcessBuilder};
+    /// use libcnb_data::process_type;
+    ///
+    /// let launch = LaunchBuilder::new()
+    ///     .process(ProcessBuilder::new(process_type!("web"), ["web"]).build())
+    ///     .process(ProcessBuilder::new(process_type!("web"), ["web2"]).build())
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     launch.validate(),
+    ///     Err(LaunchValidationError::DuplicateProcessType(process_type!("web")))
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for the first constraint that is violated.
+    pub fn validate(&self) -> Result<(), LaunchValidationError> {
+        let mut process_types = Vec::new();
+
+        for process in &self.processes {
+            if process_types.contains(&&process.r#type) {
+                return Err(LaunchValidationError::DuplicateProcessType(
+                    process.r#type.clone(),
+                ));
+            }
+
+            if process.command.first().map_or(true, String::is_empty) {
+                return Err(LaunchValidationError::EmptyCommand(process.r#type.clone()));
+            }
+
+            process_types.push(&process.r#type);
+        }
+
+        let default_process_types = self
+            .processes
+            .iter()
+            .filter(|process| process.default)
+            .map(|process| process.r#type.clone())
+            .collect::<Vec<_>>();
+
+        if default_process_types.len() > 1 {
+            return Err(LaunchValidationError::MultipleDefaultProcesses(
+                default_process_types,
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// A violated constraint of a [`Launch`] value, as returned by [`Launch::validate`].
+#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+pub enum LaunchValidationError {
+    #[error("Process type {0} is declared more than once")]
+    DuplicateProcessType(ProcessType),
+
+    #[error("Process {0} has an empty command")]
+    EmptyCommand(ProcessType),
+
+    #[error("Multiple processes are marked as default: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    MultipleDefaultProcesses(Vec<ProcessType>),
+}
+
+/// An error while splitting a shell-like command string, as returned by
+/// [`ProcessBuilder::from_shell_words`].
+#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+pub enum ShellWordsError {
+    #[error("Command is empty")]
+    EmptyCommand,
+
+    #[error("Command has an unterminated quote or escape")]
+    Unterminated,
+
+    #[error("Command uses unsupported shell syntax '{0}', run the command with an explicit shell such as 'bash -c' instead")]
+    UnsupportedSyntax(char),
+}
+
+/// Splits a shell-like string into words, see [`ProcessBuilder::from_shell_words`] for the rules.
+fn split_shell_words(input: &str) -> Result<Vec<String>, ShellWordsError> {
+    #[derive(Clone, Copy, Eq, PartialEq)]
+    enum State {
+        Unquoted,
+        SingleQuoted,
+        DoubleQuoted,
+    }
+
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut state = State::Unquoted;
+    let mut chars = input.chars();
+    // Whether the input so far only consists of an unquoted variable name, in which case an
+    // unquoted `=` would make the first word a variable assignment.
+    let mut leading_name = true;
+
+    while let Some(char) = chars.next() {
+        if leading_name && state == State::Unquoted {
+            match char {
+                '=' if word.is_some() => return Err(ShellWordsError::UnsupportedSyntax(char)),
+                '_' | 'a'..='z' | 'A'..='Z' => {}
+                '0'..='9' if word.is_some() => {}
+                char if char.is_whitespace() && word.is_none() => {}
+                _ => leading_name = false,
+            }
+        }
+
+        match (state, char) {
+            (State::Unquoted, char) if char.is_whitespace() => {
+                words.extend(word.take());
+            }
+            (State::Unquoted, '\\') => match chars.next() {
+                // A backslash-newline pair is a line continuation and removed entirely.
+                Some('\n') => {}
+                Some(char) => word.get_or_insert_with(String::new).push(char),
+                None => return Err(ShellWordsError::Unterminated),
+            },
+            (State::Unquoted, '\'') => {
+                word.get_or_insert_with(String::new);
+                state = State::SingleQuoted;
+            }
+            (State::Unquoted, '"') => {
+                word.get_or_insert_with(String::new);
+                state = State::DoubleQuoted;
+            }
+            (State::Unquoted, '#' | '~') if word.is_none() => {
+                return Err(ShellWordsError::UnsupportedSyntax(char));
+            }
+            (
+                State::Unquoted,
+                '$' | '`' | '|' | '&' | ';' | '<' | '>' | '(' | ')' | '*' | '?' | '[' | '{' | '}',
+            )
+            | (State::DoubleQuoted, '$' | '`') => {
+                return Err(ShellWordsError::UnsupportedSyntax(char));
+            }
+            (State::SingleQuoted, '\'') | (State::DoubleQuoted, '"') => {
+                state = State::Unquoted;
+            }
+            (State::DoubleQuoted, '\\') => match chars.next() {
+                Some('\n') => {}
+                Some(char @ ('$' | '`' | '"' | '\\')) => {
+                    word.get_or_insert_with(String::new).push(char);
+                }
+                Some(char) => word.get_or_insert_with(String::new).extend(['\\', char]),
+                None => return Err(ShellWordsError::Unterminated),
+            },
+            (_, char) => word.get_or_insert_with(String::new).push(char),
+        }
+    }
+
+    if state != State::Unquoted {
+        return Err(ShellWordsError::Unterminated);
+    }
+
+    words.extend(word);
+    Ok(words)
+}
+
 /// A non-consuming builder for [`Launch`] values.
 ///
 /// # Examples
@@ -27,6 +194,8 @@ pub struct Launch {
 ///             .args(["exec", "ruby", "app.rb"])
 ///             .build(),
 ///     )
+///     .label(("org.opencontainers.image.vendor", "Heroku"))
+///     .slice(["public/assets/**"])
 ///     .build();
 ///
 /// assert!(toml::to_string(&launch_toml).is_ok());
@@ -61,6 +230,8 @@ impl LaunchBuilder {
     }
 
     /// Adds a label to the launch configuration.
+    ///
+    /// Accepts a [`Label`] or a `(key, value)` tuple.
     pub fn label<L: Into<Label>>(&mut self, label: L) -> &mut Self {
         self.launch.labels.push(label.into());
         self
@@ -76,6 +247,8 @@ impl LaunchBuilder {
     }
 
     /// Adds a slice to the launch configuration.
+    ///
+    /// Accepts a [`Slice`] or an array or `Vec` of path globs.
     pub fn slice<S: Into<Slice>>(&mut self, slice: S) -> &mut Self {
         self.launch.slices.push(slice.into());
         self
@@ -97,13 +270,29 @@ impl LaunchBuilder {
     }
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+/// An OCI label that is added to the app image.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
 pub struct Label {
     pub key: String,
     pub value: String,
 }
 
+impl Label {
+    pub fn new(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            value: value.into(),
+        }
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> From<(K, V)> for Label {
+    fn from((key, value): (K, V)) -> Self {
+        Self::new(key, value)
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
 pub struct Process {
@@ -119,10 +308,16 @@ pub struct Process {
         skip_serializing_if = "WorkingDirectory::is_app"
     )]
     pub working_directory: WorkingDirectory,
+    /// Default environment variables for this process.
+    ///
+    /// These are not part of `launch.toml` as the CNB spec has no field for them. Instead, libcnb
+    /// writes them as process-specific environment variables to a launch layer it manages when
+    /// the build finishes.
+    #[serde(skip)]
+    pub env: BTreeMap<String, String>,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
-#[serde(untagged)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum WorkingDirectory {
     // There is no explicitly defined value in the CNB spec that denotes the app directory. Since
     // we cannot enforce skipping serialization (which indicates the app directory) from this type
@@ -157,6 +352,39 @@ impl Serialize for WorkingDirectory {
     }
 }
 
+// Custom Deserialize implementation to ensure that "." (the serialized form of App) deserializes
+// back to App instead of a directory.
+impl<'de> Deserialize<'de> for WorkingDirectory {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        PathBuf::deserialize(deserializer).map(Self::from)
+    }
+}
+
+impl From<PathBuf> for WorkingDirectory {
+    fn from(path: PathBuf) -> Self {
+        if path == Path::new(".") {
+            Self::App
+        } else {
+            Self::Directory(path)
+        }
+    }
+}
+
+impl From<&Path> for WorkingDirectory {
+    fn from(path: &Path) -> Self {
+        Self::from(path.to_path_buf())
+    }
+}
+
+impl From<&str> for WorkingDirectory {
+    fn from(path: &str) -> Self {
+        Self::from(PathBuf::from(path))
+    }
+}
+
 impl Default for WorkingDirectory {
     fn default() -> Self {
         Self::App
@@ -185,6 +413,7 @@ impl ProcessBuilder {
     /// * No additional, user-overridable, arguments to the command
     /// * `default` is `false`
     /// * `working_directory` will be `WorkingDirectory::App`.
+    /// * No default environment variables
     pub fn new(r#type: ProcessType, command: impl IntoIterator<Item = impl Into<String>>) -> Self {
         Self {
             process: Process {
@@ -193,10 +422,59 @@ impl ProcessBuilder {
                 args: Vec::new(),
                 default: false,
                 working_directory: WorkingDirectory::App,
+                env: BTreeMap::new(),
             },
         }
     }
 
+    /// Constructs a new `ProcessBuilder` from a shell-like command string, such as a `Procfile`
+    /// entry.
+    ///
+    /// The string is split into words following the quoting rules of POSIX shells: words are
+    /// separated by whitespace, single quotes preserve all characters literally, and backslashes
+    /// escape characters outside and, for `$`, `` ` ``, `"` and `\`, inside double quotes. The
+    /// first word is the command, all remaining words are user-overridable arguments. The same
+    /// defaults as with [`new`](Self::new) apply otherwise.
+    ///
+    /// Since no shell is involved when the process is launched, unquoted shell syntax such as
+    /// variable assignments, variable expansion, command substitution, redirection, pipes, command
+    /// lists, globs and brace expansion is rejected. Processes that need these features should explicitly run a shell, i.e.
+    /// `ProcessBuilder::new(process_type, ["bash", "-c", "puma -p $PORT"])`.
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb_data::launch::{ProcessBuilder, ShellWordsError};
+    /// use libcnb_data::process_type;
+    ///
+    /// let process = ProcessBuilder::from_shell_words(
+    ///     process_type!("web"),
+    ///     "bundle exec puma -C 'config/puma.rb'",
+    /// )
+    /// .unwrap()
+    /// .build();
+    ///
+    /// assert_eq!(process.command, ["bundle"]);
+    /// assert_eq!(process.args, ["exec", "puma", "-C", "config/puma.rb"]);
+    ///
+    /// assert_eq!(
+    ///     ProcessBuilder::from_shell_words(process_type!("web"), "puma -p $PORT").err(),
+    ///     Some(ShellWordsError::UnsupportedSyntax('$'))
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string contains no words, has unterminated quotes or escapes, or
+    /// uses unsupported shell syntax.
+    pub fn from_shell_words(r#type: ProcessType, command: &str) -> Result<Self, ShellWordsError> {
+        let mut words = split_shell_words(command)?.into_iter();
+        let command = words.next().ok_or(ShellWordsError::EmptyCommand)?;
+
+        let mut process_builder = Self::new(r#type, [command]);
+        process_builder.args(words);
+        Ok(process_builder)
+    }
+
     /// Adds a user-overridable argument to the process.
     ///
     /// Only one argument can be passed per use. So instead of:
@@ -244,8 +522,36 @@ impl ProcessBuilder {
     }
 
     /// Set the working directory for the process.
-    pub fn working_directory(&mut self, value: WorkingDirectory) -> &mut Self {
-        self.process.working_directory = value;
+    ///
+    /// Relative paths are resolved relative to the app directory. Accepts [`WorkingDirectory`]
+    /// values as well as paths, i.e. `.working_directory("/workspace/backend")`.
+    pub fn working_directory(&mut self, value: impl Into<WorkingDirectory>) -> &mut Self {
+        self.process.working_directory = value.into();
+        self
+    }
+
+    /// Sets a default environment variable for the process.
+    ///
+    /// The variable is only set if it isn't already defined in the environment the process is
+    /// launched in. It does not affect any other process types.
+    ///
+    /// To set multiple environment variables see [`envs`](Self::envs).
+    pub fn env(&mut self, key: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.process.env.insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets multiple default environment variables for the process.
+    ///
+    /// To set a single environment variable see [`env`](Self::env).
+    pub fn envs(
+        &mut self,
+        envs: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) -> &mut Self {
+        for (key, value) in envs {
+            self.env(key, value);
+        }
+
         self
     }
 
@@ -256,7 +562,11 @@ impl ProcessBuilder {
     }
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+/// A slice of the application directory that is exported as a separate layer of the app image.
+///
+/// Files that change less often than the rest of the application, such as compiled assets, can be
+/// put into a slice so that the layer can be reused across builds.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
 pub struct Slice {
     /// Path globs for this slice.
@@ -267,6 +577,26 @@ pub struct Slice {
     pub path_globs: Vec<String>,
 }
 
+impl Slice {
+    pub fn new(path_globs: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            path_globs: path_globs.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl<S: Into<String>, const N: usize> From<[S; N]> for Slice {
+    fn from(path_globs: [S; N]) -> Self {
+        Self::new(path_globs)
+    }
+}
+
+impl<S: Into<String>> From<Vec<S>> for Slice {
+    fn from(path_globs: Vec<S>) -> Self {
+        Self::new(path_globs)
+    }
+}
+
 libcnb_newtype!(
     launch,
     /// Construct a [`ProcessType`] value at compile time.
@@ -314,6 +644,137 @@ mod tests {
     use super::*;
     use serde_test::{assert_ser_tokens, Token};
 
+    #[test]
+    fn process_builder_from_shell_words() {
+        let process = ProcessBuilder::from_shell_words(
+            process_type!("web"),
+            "  bundle exec puma -C config/puma.rb\n",
+        )
+        .unwrap()
+        .default(true)
+        .build();
+
+        assert_eq!(process.command, ["bundle"]);
+        assert_eq!(process.args, ["exec", "puma", "-C", "config/puma.rb"]);
+        assert!(process.default);
+
+        assert_eq!(
+            split_shell_words(
+                r#"echo 'a  b' "c \"d\" \$e \f" g\ h '' i\
+j"#
+            ),
+            Ok(vec![
+                String::from("echo"),
+                String::from("a  b"),
+                String::from(r#"c "d" $e \f"#),
+                String::from("g h"),
+                String::new(),
+                String::from("ij"),
+            ])
+        );
+
+        assert_eq!(
+            ProcessBuilder::from_shell_words(process_type!("web"), " ").err(),
+            Some(ShellWordsError::EmptyCommand)
+        );
+
+        for (command, error) in [
+            ("echo 'foo", ShellWordsError::Unterminated),
+            ("echo \"foo", ShellWordsError::Unterminated),
+            ("echo foo\\", ShellWordsError::Unterminated),
+            ("puma -p $PORT", ShellWordsError::UnsupportedSyntax('$')),
+            ("echo \"$(date)\"", ShellWordsError::UnsupportedSyntax('$')),
+            (
+                "rake db:migrate && puma",
+                ShellWordsError::UnsupportedSyntax('&'),
+            ),
+            ("worker > log.txt", ShellWordsError::UnsupportedSyntax('>')),
+            ("ls *.rb", ShellWordsError::UnsupportedSyntax('*')),
+            ("ls ~/app", ShellWordsError::UnsupportedSyntax('~')),
+            ("puma # comment", ShellWordsError::UnsupportedSyntax('#')),
+            (
+                "RACK_ENV=production puma",
+                ShellWordsError::UnsupportedSyntax('='),
+            ),
+            ("  _FOO1= puma", ShellWordsError::UnsupportedSyntax('=')),
+            ("ls app/[a-z]*", ShellWordsError::UnsupportedSyntax('[')),
+            (
+                "cp config.{yml,bak}",
+                ShellWordsError::UnsupportedSyntax('{'),
+            ),
+            ("puma }", ShellWordsError::UnsupportedSyntax('}')),
+        ] {
+            assert_eq!(split_shell_words(command), Err(error), "{command}");
+        }
+
+        assert_eq!(
+            split_shell_words("echo 'a $b' foo#bar"),
+            Ok(vec![
+                String::from("echo"),
+                String::from("a $b"),
+                String::from("foo#bar")
+            ])
+        );
+
+        for command in [
+            "puma --port=3000",
+            "'FOO=bar' puma",
+            "FOO\\=bar puma",
+            "./bin/FOO=bar",
+            "1FOO=bar",
+            "echo '[a]' \"{b}\"",
+        ] {
+            assert!(split_shell_words(command).is_ok(), "{command}");
+        }
+    }
+
+    #[test]
+    fn launch_validate() {
+        let process = |r#type: ProcessType, command: &str, default: bool| {
+            ProcessBuilder::new(r#type, [command])
+                .default(default)
+                .build()
+        };
+
+        assert_eq!(Launch::default().validate(), Ok(()));
+        assert_eq!(
+            LaunchBuilder::new()
+                .process(process(process_type!("web"), "web", true))
+                .process(process(process_type!("worker"), "worker", false))
+                .build()
+                .validate(),
+            Ok(())
+        );
+        assert_eq!(
+            LaunchBuilder::new()
+                .process(process(process_type!("web"), "web", false))
+                .process(process(process_type!("web"), "web", false))
+                .build()
+                .validate(),
+            Err(LaunchValidationError::DuplicateProcessType(process_type!(
+                "web"
+            )))
+        );
+        assert_eq!(
+            LaunchBuilder::new()
+                .process(process(process_type!("web"), "", false))
+                .build()
+                .validate(),
+            Err(LaunchValidationError::EmptyCommand(process_type!("web")))
+        );
+        assert_eq!(
+            LaunchBuilder::new()
+                .process(process(process_type!("web"), "web", true))
+                .process(process(process_type!("worker"), "worker", true))
+                .build()
+                .validate(),
+            Err(LaunchValidationError::MultipleDefaultProcesses(vec![
+                process_type!("web"),
+                process_type!("worker")
+            ]))
+        );
+    }
+
     #[test]
     fn launch_builder_add_processes() {
         let launch = LaunchBuilder::new()
@@ -374,7 +835,8 @@ command = ["foo"]
                 command: vec![String::from("foo")],
                 args: Vec::new(),
                 default: false,
-                working_directory: WorkingDirectory::App
+                working_directory: WorkingDirectory::App,
+                env: BTreeMap::new(),
             })
         );
     }
@@ -421,7 +883,8 @@ working-dir = "dist"
                 command: vec![String::from("java")],
                 args: Vec::new(),
                 default: false,
-                working_directory: WorkingDirectory::App
+                working_directory: WorkingDirectory::App,
+                env: BTreeMap::new(),
             }
         );
 
@@ -434,7 +897,8 @@ working-dir = "dist"
                 command: vec![String::from("java")],
                 args: Vec::new(),
                 default: true,
-                working_directory: WorkingDirectory::App
+                working_directory: WorkingDirectory::App,
+                env: BTreeMap::new(),
             }
         );
 
@@ -447,7 +911,8 @@ working-dir = "dist"
                 command: vec![String::from("java")],
                 args: Vec::new(),
                 default: true,
-                working_directory: WorkingDirectory::Directory(PathBuf::from("dist"))
+                working_directory: WorkingDirectory::Directory(PathBuf::from("dist")),
+                env: BTreeMap::new(),
             }
         );
     }
@@ -470,11 +935,43 @@ working-dir = "dist"
                     String::from("bar"),
                 ],
                 default: false,
-                working_directory: WorkingDirectory::App
+                working_directory: WorkingDirectory::App,
+                env: BTreeMap::new(),
             }
         );
     }
 
+    #[test]
+    fn process_builder_env() {
+        let process = ProcessBuilder::new(process_type!("web"), ["java"])
+            .env("FOO", "foo")
+            .envs([("BAR", "bar"), ("FOO", "baz")])
+            .build();
+
+        assert_eq!(
+            process.env,
+            BTreeMap::from([
+                (String::from("BAR"), String::from("bar")),
+                (String::from("FOO"), String::from("baz"))
+            ])
+        );
+    }
+
+    #[test]
+    fn process_with_env_serialization() {
+        let process = ProcessBuilder::new(process_type!("web"), ["foo"])
+            .env("FOO", "bar")
+            .build();
+
+        let string = toml::to_string(&process).unwrap();
+        assert_eq!(
+            string,
+            r#"type = "web"
+command = ["foo"]
+"#
+        );
+    }
+
     #[test]
     fn process_working_directory_serialization() {
         assert_ser_tokens(&WorkingDirectory::App, &[Token::BorrowedStr(".")]);
@@ -492,4 +989,108 @@ working-dir = "dist"
             &[Token::BorrowedStr("relative/foo/bar")],
         );
     }
+
+    #[test]
+    fn process_working_directory_deserialization() {
+        let toml_str = r#"
+type = "web"
+command = ["foo"]
+working-dir = "backend"
+"#;
+
+        assert_eq!(
+            toml::from_str::<Process>(toml_str).map(|process| process.working_directory),
+            Ok(WorkingDirectory::Directory(PathBuf::from("backend")))
+        );
+
+        let toml_str = r#"
+type = "web"
+command = ["foo"]
+working-dir = "."
+"#;
+
+        assert_eq!(
+            toml::from_str::<Process>(toml_str).map(|process| process.working_directory),
+            Ok(WorkingDirectory::App)
+        );
+    }
+
+    #[test]
+    fn process_working_directory_roundtrip() {
+        let launch = LaunchBuilder::new()
+            .processes([
+                ProcessBuilder::new(process_type!("web"), ["foo"])
+                    .working_directory("/workspace/backend")
+                    .build(),
+                ProcessBuilder::new(process_type!("worker"), ["bar"])
+                    .working_directory(WorkingDirectory::App)
+                    .build(),
+            ])
+            .build();
+
+        let launch_toml = toml::to_string(&launch).unwrap();
+        assert_eq!(
+            launch_toml,
+            r#"[[processes]]
+type = "web"
+command = ["foo"]
+working-dir = "/workspace/backend"
+
+[[processes]]
+type = "worker"
+command = ["bar"]
+"#
+        );
+
+        assert_eq!(
+            toml::from_str::<Launch>(&launch_toml).unwrap().processes,
+            launch.processes
+        );
+    }
+
+    #[test]
+    fn launch_builder_labels_and_slices_serialization() {
+        let launch = LaunchBuilder::new()
+            .label(("com.example.foo", "foo"))
+            .labels([Label::new("com.example.bar", "bar")])
+            .slice(["public/assets/**", "public/packs/**"])
+            .slices([Slice::new(["vendor/**"])])
+            .slice(vec![String::from("static/*")])
+            .build();
+
+        let launch_toml = toml::to_string(&launch).unwrap();
+        assert_eq!(
+            launch_toml,
+            r#"[[labels]]
+key = "com.example.foo"
+value = "foo"
+
+[[labels]]
+key = "com.example.bar"
+value = "bar"
+
+[[slices]]
+paths = ["public/assets/**", "public/packs/**"]
+
+[[slices]]
+paths = ["vendor/**"]
+
+[[slices]]
+paths = ["static/*"]
+"#
+        );
+
+        let deserialized = toml::from_str::<Launch>(&launch_toml).unwrap();
+        assert_eq!(deserialized.labels, launch.labels);
+        assert_eq!(deserialized.slices, launch.slices);
+    }
+
+    #[test]
+    fn working_directory_from_path() {
+        assert_eq!(WorkingDirectory::from("."), WorkingDirectory::App);
+        assert_eq!(
+            WorkingDirectory::from(Path::new("/foo")),
+            WorkingDirectory::Directory(PathBuf::from("/foo"))
+        );
+    }
 }
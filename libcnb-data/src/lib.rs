@@ -5,7 +5,11 @@ pub mod build_plan;
 pub mod buildpack;
 pub mod buildpack_plan;
 pub mod exec_d;
+pub mod extension;
 pub mod generic;
+pub mod image_label;
+#[cfg(feature = "inventory")]
+pub mod inventory;
 pub mod launch;
 pub mod layer;
 pub mod layer_content_metadata;
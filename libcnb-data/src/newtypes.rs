@@ -188,6 +188,62 @@ macro_rules! libcnb_newtype {
 
 pub(crate) use libcnb_newtype;
 
+/// Constructs a value of a newtype of this crate from a literal string.
+///
+/// Expands to the literal macro of the given newtype, i.e. `cnb_literal!(LayerName, "foo")` to
+/// `layer_name!("foo")`. The value is validated at compile time with the regular expression of the
+/// newtype. Passing an invalid value yields a compilation error. Supported are [`LayerName`],
+/// [`BuildpackId`], [`ProcessType`] and [`ExecDProgramOutputKey`], which can be referred to by
+/// their name or their full path. Since macros are expanded before types are resolved, type
+/// aliases and newtypes of other crates are not supported.
+///
+/// [`LayerName`]: crate::layer::LayerName
+/// [`BuildpackId`]: crate::buildpack::BuildpackId
+/// [`ProcessType`]: crate::launch::ProcessType
+/// [`ExecDProgramOutputKey`]: crate::exec_d::ExecDProgramOutputKey
+///
+/// # Examples
+/// ```
+/// use libcnb_data::buildpack::BuildpackId;
+/// use libcnb_data::cnb_literal;
+///
+/// let buildpack_id: BuildpackId = cnb_literal!(BuildpackId, "heroku/java");
+/// let process_type = cnb_literal!(libcnb_data::launch::ProcessType, "web");
+/// ```
+///
+/// ```compile_fail
+/// use libcnb_data::cnb_literal;
+/// use libcnb_data::layer::LayerName;
+///
+/// let layer_name = cnb_literal!(LayerName, "build");
+/// ```
+#[macro_export]
+macro_rules! cnb_literal {
+    (LayerName, $value:literal $(,)?) => {
+        $crate::layer_name!($value)
+    };
+    (BuildpackId, $value:literal $(,)?) => {
+        $crate::buildpack_id!($value)
+    };
+    (ProcessType, $value:literal $(,)?) => {
+        $crate::process_type!($value)
+    };
+    (ExecDProgramOutputKey, $value:literal $(,)?) => {
+        $crate::exec_d_program_output_key!($value)
+    };
+    // Strips the path of the newtype, so that the arms above match its name.
+    ($(::)? $module:ident :: $($rest:tt)+) => {
+        $crate::cnb_literal!($($rest)+)
+    };
+    ($newtype:ident, $value:literal $(,)?) => {
+        ::std::compile_error!(::std::concat!(
+            "cnb_literal! doesn't support ",
+            ::std::stringify!($newtype),
+            ", only the newtypes of libcnb-data!"
+        ))
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use serde_test::{assert_de_tokens, assert_de_tokens_error, Token};
@@ -0,0 +1,147 @@
+//! Minimal typed model of [CycloneDX](https://cyclonedx.org/) 1.4, 1.5 and 1.6 JSON documents.
+//!
+//! Only the fields required by the spec and the most commonly used optional fields are modeled,
+//! all other fields are ignored when deserializing.
+
+use super::SbomValidationError;
+use serde::{Deserialize, Serialize};
+
+/// The `CycloneDX` spec versions supported by [`CycloneDxBom::validate`].
+pub const SUPPORTED_SPEC_VERSIONS: &[&str] = &["1.4", "1.5", "1.6"];
+
+/// A `CycloneDX` bill of materials.
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CycloneDxBom {
+    /// Always `CycloneDX`.
+    pub bom_format: String,
+    pub spec_version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial_number: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    #[serde(
+        default,
+        deserialize_with = "super::null_as_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub components: Vec<Component>,
+    #[serde(
+        default,
+        deserialize_with = "super::null_as_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub dependencies: Vec<Dependency>,
+}
+
+impl CycloneDxBom {
+    /// Validates the `bomFormat` and `specVersion` of the document.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `bomFormat` isn't `CycloneDX` or `specVersion` isn't one of
+    /// [`SUPPORTED_SPEC_VERSIONS`].
+    pub fn validate(&self) -> Result<(), SbomValidationError> {
+        if self.bom_format != "CycloneDX" {
+            return Err(SbomValidationError::UnexpectedBomFormat(
+                self.bom_format.clone(),
+            ));
+        }
+
+        if !SUPPORTED_SPEC_VERSIONS.contains(&self.spec_version.as_str()) {
+            return Err(SbomValidationError::UnsupportedSpecVersion(
+                self.spec_version.clone(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// A software or hardware component, i.e. a library or application.
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+pub struct Component {
+    /// The component type, i.e. `application` or `library`.
+    pub r#type: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(rename = "bom-ref", default, skip_serializing_if = "Option::is_none")]
+    pub bom_ref: Option<String>,
+    /// The [package URL](https://github.com/package-url/purl-spec) of the component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purl: Option<String>,
+    /// Components included in this component.
+    #[serde(
+        default,
+        deserialize_with = "super::null_as_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub components: Vec<Component>,
+}
+
+/// The dependencies of a component, referenced by their `bom-ref`.
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Dependency {
+    pub r#ref: String,
+    #[serde(
+        default,
+        deserialize_with = "super::null_as_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub depends_on: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_and_validate() {
+        let bom: CycloneDxBom = serde_json::from_str(
+            r#"
+{
+  "bomFormat": "CycloneDX",
+  "specVersion": "1.5",
+  "version": 1,
+  "metadata": {"timestamp": "2024-01-01T00:00:00Z"},
+  "components": [
+    {"type": "application", "name": "ruby", "version": "3.3.0", "bom-ref": "ruby", "purl": "pkg:generic/ruby@3.3.0"}
+  ],
+  "dependencies": [{"ref": "ruby", "dependsOn": []}]
+}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(bom.components[0].bom_ref.as_deref(), Some("ruby"));
+        assert_eq!(bom.dependencies[0].r#ref, "ruby");
+        assert!(bom.validate().is_ok());
+
+        assert!(CycloneDxBom {
+            spec_version: String::from("1.6"),
+            ..bom.clone()
+        }
+        .validate()
+        .is_ok());
+
+        assert!(matches!(
+            CycloneDxBom {
+                spec_version: String::from("1.2"),
+                ..bom.clone()
+            }
+            .validate(),
+            Err(SbomValidationError::UnsupportedSpecVersion(version)) if version == "1.2"
+        ));
+
+        assert!(matches!(
+            CycloneDxBom {
+                bom_format: String::from("SPDX"),
+                ..bom
+            }
+            .validate(),
+            Err(SbomValidationError::UnexpectedBomFormat(format)) if format == "SPDX"
+        ));
+    }
+}
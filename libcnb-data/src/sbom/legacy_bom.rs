@@ -0,0 +1,124 @@
+//! Detection of legacy Bill of Materials entries (`[[bom]]`) in `launch.toml` and `build.toml`.
+//!
+//! Buildpack API 0.7 replaced these entries with SBOM files in the [`SbomFormat`](super::SbomFormat)
+//! formats. Lifecycles reject buildpacks with a newer Buildpack API that still write them, which
+//! usually happens when metadata handling is carried over from older buildpacks.
+
+use crate::buildpack::BuildpackApi;
+use toml::Table;
+
+/// The first Buildpack API version that doesn't support legacy BOM entries.
+pub const LEGACY_BOM_REMOVED_IN_API: BuildpackApi = BuildpackApi { major: 0, minor: 7 };
+
+/// A legacy BOM entry, as found by [`find_legacy_bom_entries`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegacyBomEntry {
+    pub name: Option<String>,
+    pub metadata: Table,
+}
+
+/// Returns whether the given Buildpack API version supports legacy BOM entries.
+#[must_use]
+pub fn legacy_bom_supported(api: &BuildpackApi) -> bool {
+    (api.major, api.minor)
+        < (
+            LEGACY_BOM_REMOVED_IN_API.major,
+            LEGACY_BOM_REMOVED_IN_API.minor,
+        )
+}
+
+/// Returns the legacy BOM entries of the given `launch.toml` or `build.toml` document.
+///
+/// Entries are migrated by moving their data into an SBOM document, i.e. the `components` of a
+/// `CycloneDX` SBOM, and removing the `[[bom]]` table.
+///
+/// # Examples
+/// ```
+/// use libcnb_data::sbom::legacy_bom::find_legacy_bom_entries;
+///
+/// let launch_toml = toml::from_str(
+///     r#"
+/// [[bom]]
+/// name = "ruby"
+///
+/// [bom.metadata]
+/// version = "3.3.0"
+/// "#,
+/// )
+/// .unwrap();
+///
+/// let entries = find_legacy_bom_entries(&launch_toml);
+/// assert_eq!(entries[0].name.as_deref(), Some("ruby"));
+/// ```
+#[must_use]
+pub fn find_legacy_bom_entries(document: &Table) -> Vec<LegacyBomEntry> {
+    document
+        .get("bom")
+        .and_then(toml::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(toml::Value::as_table)
+        .map(|entry| LegacyBomEntry {
+            name: entry
+                .get("name")
+                .and_then(toml::Value::as_str)
+                .map(String::from),
+            metadata: entry
+                .get("metadata")
+                .and_then(toml::Value::as_table)
+                .cloned()
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_bom_supported_by_api() {
+        assert!(legacy_bom_supported(&BuildpackApi { major: 0, minor: 6 }));
+        assert!(!legacy_bom_supported(&BuildpackApi { major: 0, minor: 7 }));
+        assert!(!legacy_bom_supported(&BuildpackApi {
+            major: 0,
+            minor: 10
+        }));
+    }
+
+    #[test]
+    fn find_entries() {
+        let document = toml::from_str(
+            r#"
+[[processes]]
+type = "web"
+command = ["ruby"]
+
+[[bom]]
+name = "ruby"
+
+[bom.metadata]
+version = "3.3.0"
+
+[[bom]]
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            find_legacy_bom_entries(&document),
+            vec![
+                LegacyBomEntry {
+                    name: Some(String::from("ruby")),
+                    metadata: toml::toml! { version = "3.3.0" },
+                },
+                LegacyBomEntry {
+                    name: None,
+                    metadata: Table::new(),
+                }
+            ]
+        );
+
+        assert_eq!(find_legacy_bom_entries(&Table::new()), Vec::new());
+    }
+}
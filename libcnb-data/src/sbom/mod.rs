@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub enum SbomFormat {
+    /// Cyclone DX (JSON)
+    ///
+    /// See: <https://cyclonedx.org/>
+    #[serde(rename = "application/vnd.cyclonedx+json")]
+    CycloneDxJson,
+
+    /// SPDX (JSON)
+    ///
+    /// See: <https://spdx.dev/>
+    #[serde(rename = "application/spdx+json")]
+    SpdxJson,
+
+    /// Syft (JSON)
+    ///
+    /// See: <https://github.com/anchore/syft>
+    #[serde(rename = "application/vnd.syft+json")]
+    SyftJson,
+}
+
+/// All currently supported SBOM formats.
+pub const SBOM_FORMATS: &[SbomFormat] = &[
+    SbomFormat::CycloneDxJson,
+    SbomFormat::SpdxJson,
+    SbomFormat::SyftJson,
+];
+
+pub mod legacy_bom;
+
+#[cfg(feature = "sbom-validate")]
+pub mod cyclonedx;
+#[cfg(feature = "sbom-validate")]
+pub mod spdx;
+
+#[cfg(feature = "sbom-validate")]
+impl SbomFormat {
+    /// Validates that the given bytes are an SBOM document of this format.
+    ///
+    /// `CycloneDX` documents are parsed as [`cyclonedx::CycloneDxBom`] and have to use spec version
+    /// 1.4, 1.5 or 1.6, SPDX documents are parsed as [`spdx::SpdxDocument`] and have to use SPDX 2.2 or
+    /// 2.3. Syft documents are only checked to be JSON objects.
+    ///
+    /// Only the fields of the minimal typed models are checked, other fields are ignored.
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb_data::sbom::SbomFormat;
+    ///
+    /// assert!(SbomFormat::CycloneDxJson
+    ///     .validate(br#"{"bomFormat": "CycloneDX", "specVersion": "1.5"}"#)
+    ///     .is_ok());
+    ///
+    /// assert!(SbomFormat::SpdxJson
+    ///     .validate(br#"{"bomFormat": "CycloneDX", "specVersion": "1.5"}"#)
+    ///     .is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes can't be parsed as a document of this format or the document
+    /// uses an unsupported spec version.
+    pub fn validate(&self, data: &[u8]) -> Result<(), SbomValidationError> {
+        match self {
+            Self::CycloneDxJson => serde_json::from_slice::<cyclonedx::CycloneDxBom>(data)
+                .map_err(SbomValidationError::InvalidDocument)?
+                .validate(),
+            Self::SpdxJson => serde_json::from_slice::<spdx::SpdxDocument>(data)
+                .map_err(SbomValidationError::InvalidDocument)?
+                .validate(),
+            Self::SyftJson => {
+                serde_json::from_slice::<serde_json::Map<String, serde_json::Value>>(data)
+                    .map(|_| ())
+                    .map_err(SbomValidationError::InvalidDocument)
+            }
+        }
+    }
+}
+
+/// Deserializes `null` as the default value, since some SBOM tools write `null` instead of
+/// omitting empty arrays.
+#[cfg(feature = "sbom-validate")]
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Option::unwrap_or_default)
+}
+
+/// An error that occurred while validating an SBOM with `SbomFormat::validate`, which requires
+/// the `sbom-validate` feature.
+#[derive(thiserror::Error, Debug)]
+pub enum SbomValidationError {
+    #[error("SBOM document is invalid: {0}")]
+    InvalidDocument(serde_json::Error),
+
+    #[error("Unexpected CycloneDX bomFormat {0}, expected CycloneDX")]
+    UnexpectedBomFormat(String),
+
+    #[error("Unsupported SBOM spec version {0}")]
+    UnsupportedSpecVersion(String),
+}
@@ -0,0 +1,166 @@
+//! Minimal typed model of [SPDX](https://spdx.dev/) 2.3 JSON documents. SPDX 2.2 documents, which
+//! many SBOM tools still generate, are supported as well.
+//!
+//! Only the fields required by the spec and the most commonly used optional fields are modeled,
+//! all other fields are ignored when deserializing.
+
+use super::SbomValidationError;
+use serde::{Deserialize, Serialize};
+
+/// The SPDX versions supported by [`SpdxDocument::validate`].
+pub const SUPPORTED_SPDX_VERSIONS: &[&str] = &["SPDX-2.2", "SPDX-2.3"];
+
+/// An SPDX document.
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxDocument {
+    /// Always `SPDXRef-DOCUMENT`.
+    #[serde(rename = "SPDXID")]
+    pub spdx_id: String,
+    pub spdx_version: String,
+    pub data_license: String,
+    pub name: String,
+    pub document_namespace: String,
+    pub creation_info: CreationInfo,
+    #[serde(
+        default,
+        deserialize_with = "super::null_as_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub packages: Vec<Package>,
+    #[serde(
+        default,
+        deserialize_with = "super::null_as_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub relationships: Vec<Relationship>,
+}
+
+impl SpdxDocument {
+    /// Validates the `spdxVersion` of the document.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `spdxVersion` isn't one of [`SUPPORTED_SPDX_VERSIONS`].
+    pub fn validate(&self) -> Result<(), SbomValidationError> {
+        if SUPPORTED_SPDX_VERSIONS.contains(&self.spdx_version.as_str()) {
+            Ok(())
+        } else {
+            Err(SbomValidationError::UnsupportedSpecVersion(
+                self.spdx_version.clone(),
+            ))
+        }
+    }
+}
+
+/// Information about the creation of an SPDX document.
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+pub struct CreationInfo {
+    /// The creation time in the `YYYY-MM-DDThh:mm:ssZ` format.
+    pub created: String,
+    /// The creators of the document, i.e. `Tool: my-buildpack-1.0.0`.
+    pub creators: Vec<String>,
+}
+
+/// A package described by an SPDX document.
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Package {
+    #[serde(rename = "SPDXID")]
+    pub spdx_id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_info: Option<String>,
+    /// The download location of the package, or `NOASSERTION`.
+    pub download_location: String,
+    /// References to external information about the package, i.e. its package URL.
+    #[serde(
+        default,
+        deserialize_with = "super::null_as_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub external_refs: Vec<ExternalRef>,
+}
+
+impl Package {
+    /// Returns the [package URL](https://github.com/package-url/purl-spec) of the package, if it
+    /// has one.
+    #[must_use]
+    pub fn purl(&self) -> Option<&str> {
+        self.external_refs
+            .iter()
+            .find(|external_ref| external_ref.reference_type == "purl")
+            .map(|external_ref| external_ref.reference_locator.as_str())
+    }
+}
+
+/// A reference to external information about a package, i.e. a package URL or CPE.
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalRef {
+    /// The category of the reference, i.e. `PACKAGE-MANAGER` or `SECURITY`.
+    pub reference_category: String,
+    /// The type of the reference, i.e. `purl` or `cpe23Type`.
+    pub reference_type: String,
+    pub reference_locator: String,
+}
+
+/// A relationship between two SPDX elements, i.e. `DESCRIBES` or `DEPENDS_ON`.
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Relationship {
+    pub spdx_element_id: String,
+    pub relationship_type: String,
+    pub related_spdx_element: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_and_validate() {
+        let document: SpdxDocument = serde_json::from_str(
+            r#"
+{
+  "SPDXID": "SPDXRef-DOCUMENT",
+  "spdxVersion": "SPDX-2.3",
+  "dataLicense": "CC0-1.0",
+  "name": "ruby",
+  "documentNamespace": "https://example.com/ruby",
+  "creationInfo": {"created": "2024-01-01T00:00:00Z", "creators": ["Tool: heroku/ruby"]},
+  "packages": [
+    {
+      "SPDXID": "SPDXRef-ruby",
+      "name": "ruby",
+      "versionInfo": "3.3.0",
+      "downloadLocation": "NOASSERTION",
+      "externalRefs": [
+        {"referenceCategory": "SECURITY", "referenceType": "cpe23Type", "referenceLocator": "cpe:2.3:a:ruby:ruby"},
+        {"referenceCategory": "PACKAGE-MANAGER", "referenceType": "purl", "referenceLocator": "pkg:generic/ruby@3.3.0"}
+      ]
+    }
+  ],
+  "relationships": [
+    {"spdxElementId": "SPDXRef-DOCUMENT", "relationshipType": "DESCRIBES", "relatedSpdxElement": "SPDXRef-ruby"}
+  ]
+}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(document.packages[0].version_info.as_deref(), Some("3.3.0"));
+        assert_eq!(document.packages[0].purl(), Some("pkg:generic/ruby@3.3.0"));
+        assert_eq!(document.relationships[0].relationship_type, "DESCRIBES");
+        assert!(document.validate().is_ok());
+
+        assert!(matches!(
+            SpdxDocument {
+                spdx_version: String::from("SPDX-2.1"),
+                ..document
+            }
+            .validate(),
+            Err(SbomValidationError::UnsupportedSpecVersion(version)) if version == "SPDX-2.1"
+        ));
+    }
+}
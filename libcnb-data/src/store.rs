@@ -1,3 +1,4 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use toml::value::Table;
 
@@ -6,3 +7,89 @@ use toml::value::Table;
 pub struct Store {
     pub metadata: Table,
 }
+
+impl Store {
+    /// Deserializes the metadata value with the given key.
+    ///
+    /// Returns `None` if there is no value with the given key.
+    ///
+    /// # Errors
+    /// This will return an error if the value can't be deserialized into a `T`.
+    ///
+    /// # Example
+    /// ```
+    /// use libcnb_data::store::Store;
+    ///
+    /// let mut store = Store::default();
+    /// store.set("ruby_versions", vec!["3.3.6", "3.4.1"]).unwrap();
+    ///
+    /// assert_eq!(
+    ///     store.get::<Vec<String>>("ruby_versions").unwrap(),
+    ///     Some(vec![String::from("3.3.6"), String::from("3.4.1")])
+    /// );
+    /// assert_eq!(store.get::<String>("node_version").unwrap(), None);
+    /// ```
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, toml::de::Error> {
+        self.metadata
+            .get(key)
+            .cloned()
+            .map(toml::Value::try_into)
+            .transpose()
+    }
+
+    /// Serializes the given value and stores it under the given key, replacing an existing value.
+    ///
+    /// # Errors
+    /// This will return an error if the value can't be serialized to TOML, i.e. because it is a
+    /// unit value or a map with non-string keys.
+    pub fn set(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Serialize,
+    ) -> Result<(), toml::ser::Error> {
+        self.metadata
+            .insert(key.into(), toml::Value::try_from(value)?);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    struct RuntimeMetadata {
+        version: String,
+        checksum: Option<String>,
+    }
+
+    #[test]
+    fn store_set_and_get() {
+        let mut store = Store::default();
+        let metadata = RuntimeMetadata {
+            version: String::from("1.2.3"),
+            checksum: None,
+        };
+
+        store.set("runtime", &metadata).unwrap();
+        store.set("build_count", 3).unwrap();
+
+        assert_eq!(
+            toml::to_string(&store).unwrap(),
+            "[metadata]\nbuild_count = 3\n\n[metadata.runtime]\nversion = \"1.2.3\"\n"
+        );
+        assert_eq!(store.get("runtime").unwrap(), Some(metadata));
+        assert_eq!(store.get::<u32>("build_count").unwrap(), Some(3));
+        assert_eq!(store.get::<u32>("missing").unwrap(), None);
+        assert!(store.get::<String>("build_count").is_err());
+    }
+
+    #[test]
+    fn store_set_unsupported_value() {
+        let mut store = Store::default();
+
+        assert!(store.set("unit", ()).is_err());
+        assert!(store.metadata.is_empty());
+    }
+}
@@ -1,80 +1,216 @@
 use crate::cargo::{
-    cargo_binary_target_names, determine_buildpack_cargo_target_name,
+    cargo_binary_target_names, determine_buildpack_cargo_target_name, CargoBuildConfiguration,
     DetermineBuildpackCargoTargetNameError,
 };
 use crate::CargoProfile;
 use cargo_metadata::Metadata;
+use libcnb_common::color::color_output_enabled;
 use std::collections::HashMap;
 use std::ffi::OsString;
+use std::fs;
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 use std::process::{Command, ExitStatus};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
 
-/// Builds all buildpack binary targets using Cargo.
+/// Builds all binary targets of the given buildpacks using Cargo.
 ///
 /// It uses libcnb configuration metadata in the Crate's `Cargo.toml` to determine which binary is
 /// the main buildpack binary and which are additional ones.
 ///
-/// See [`build_binary`] for details around the build process.
+/// Every buildpack is built with its own Cargo invocation, so Cargo resolves the features of its
+/// dependencies exactly as for a standalone build of that buildpack. At most `jobs` Cargo
+/// invocations run in parallel. See [`build_package`] for details around the build process.
+///
+/// Buildpacks that share a Cargo target directory are built one after another, since Cargo would
+/// wait on the lock of the target directory anyway. Right after each build, the binaries of the
+/// buildpack are copied out of Cargo's output directory. This way, binaries of different
+/// buildpacks that have the same name, such as a `helper` binary in multiple buildpacks, don't
+/// overwrite each other.
+///
+/// The binaries are returned in the order of the given buildpacks.
 ///
 /// # Errors
 ///
 /// Will return `Err` if any build did not finish successfully, the configuration can't be
 /// read or the configured main buildpack binary does not exist.
-pub(crate) fn build_buildpack_binaries(
-    project_path: impl AsRef<Path>,
-    cargo_metadata: &Metadata,
+pub(crate) fn build_buildpacks_binaries(
+    buildpacks: &[(&Metadata, &CargoBuildConfiguration)],
     cargo_profile: CargoProfile,
     cargo_env: &[(OsString, OsString)],
-    target_triple: impl AsRef<str>,
-) -> Result<BuildpackBinaries, BuildBinariesError> {
-    let binary_target_names = cargo_binary_target_names(cargo_metadata);
-    let buildpack_cargo_target = determine_buildpack_cargo_target_name(cargo_metadata)
-        .map_err(BuildBinariesError::CannotDetermineBuildpackCargoTargetName)?;
-
-    let buildpack_target_binary_path = if binary_target_names.contains(&buildpack_cargo_target) {
-        build_binary(
-            project_path.as_ref(),
-            cargo_metadata,
-            cargo_profile,
-            cargo_env.to_owned(),
-            target_triple.as_ref(),
-            &buildpack_cargo_target,
-        )
-        .map_err(|error| BuildBinariesError::BuildError(buildpack_cargo_target.clone(), error))
-    } else {
-        Err(BuildBinariesError::MissingBuildpackTarget(
-            buildpack_cargo_target.clone(),
-        ))
-    }?;
+    target_triple: &str,
+    jobs: NonZeroUsize,
+) -> Result<Vec<BuildpackBinaries>, BuildBinariesError> {
+    let mut cargo_builds = Vec::new();
 
-    let mut additional_target_binary_paths = HashMap::new();
-    for additional_binary_target_name in binary_target_names
+    for (cargo_metadata, cargo_build_configuration) in buildpacks {
+        let package_name = cargo_metadata
+            .root_package()
+            .map(|root_package| root_package.name.clone())
+            .ok_or(BuildBinariesError::CannotDetermineBuildpackCargoTargetName(
+                DetermineBuildpackCargoTargetNameError::NoRootPackage,
+            ))?;
+
+        let binary_target_names = cargo_binary_target_names(cargo_metadata);
+        let buildpack_cargo_target = determine_buildpack_cargo_target_name(cargo_metadata)
+            .map_err(BuildBinariesError::CannotDetermineBuildpackCargoTargetName)?;
+
+        if !binary_target_names.contains(&buildpack_cargo_target) {
+            return Err(BuildBinariesError::MissingBuildpackTarget(
+                buildpack_cargo_target,
+            ));
+        }
+
+        cargo_builds.push(CargoBuild {
+            workspace_root: cargo_metadata.workspace_root.clone().into_std_path_buf(),
+            target_directory: cargo_metadata.target_directory.clone().into_std_path_buf(),
+            package_name,
+            configuration: cargo_build_configuration,
+            buildpack_cargo_target,
+            binary_target_names,
+        });
+    }
+
+    let target_directory_locks = cargo_builds
         .iter()
-        .filter(|name| *name != &buildpack_cargo_target)
-    {
-        additional_target_binary_paths.insert(
-            additional_binary_target_name.clone(),
-            build_binary(
-                project_path.as_ref(),
-                cargo_metadata,
-                cargo_profile,
-                cargo_env.to_owned(),
-                target_triple.as_ref(),
-                additional_binary_target_name,
-            )
-            .map_err(|error| {
-                BuildBinariesError::BuildError(additional_binary_target_name.clone(), error)
-            })?,
-        );
+        .map(|cargo_build| (cargo_build.target_directory.clone(), Mutex::new(())))
+        .collect::<HashMap<_, _>>();
+
+    let next_cargo_build = AtomicUsize::new(0);
+    let results = Mutex::new(
+        cargo_builds
+            .iter()
+            .map(|_| None)
+            .collect::<Vec<Option<Result<BuildpackBinaries, BuildBinariesError>>>>(),
+    );
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.get().min(cargo_builds.len()) {
+            scope.spawn(|| loop {
+                let index = next_cargo_build.fetch_add(1, Ordering::SeqCst);
+                let Some(cargo_build) = cargo_builds.get(index) else {
+                    break;
+                };
+
+                // Holding the lock until the binaries have been copied ensures that no other
+                // build in the same target directory overwrites binaries with the same name.
+                let _target_directory_guard = target_directory_locks
+                    .get(&cargo_build.target_directory)
+                    .map(lock);
+
+                let result = build_package(cargo_build, cargo_profile, cargo_env, target_triple)
+                    .map_err(|error| {
+                        BuildBinariesError::BuildError(cargo_build.package_name.clone(), error)
+                    })
+                    .and_then(|()| {
+                        copy_buildpack_binaries(cargo_build, cargo_profile, target_triple).map_err(
+                            |error| {
+                                BuildBinariesError::CannotCopyBinaries(
+                                    cargo_build.package_name.clone(),
+                                    error,
+                                )
+                            },
+                        )
+                    });
+
+                let failed = result.is_err();
+                lock(&results)[index] = Some(result);
+
+                // Don't start any further builds if one of them failed.
+                if failed {
+                    next_cargo_build.fetch_add(cargo_builds.len(), Ordering::SeqCst);
+                }
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap_or_else(PoisonError::into_inner)
+        .into_iter()
+        .flatten()
+        .collect::<Result<Vec<_>, _>>()
+}
+
+/// A Cargo invocation that builds the binaries of a single buildpack.
+struct CargoBuild<'a> {
+    workspace_root: PathBuf,
+    target_directory: PathBuf,
+    package_name: String,
+    configuration: &'a CargoBuildConfiguration,
+    buildpack_cargo_target: String,
+    binary_target_names: Vec<String>,
+}
+
+/// Copies the binaries of the given build from Cargo's output directory to a directory that is
+/// specific to the built package.
+///
+/// Cargo writes the binaries of all packages of a target directory to the same output directory.
+/// Copying them right after the build keeps the binaries of buildpacks apart, even if they have the
+/// same name.
+fn copy_buildpack_binaries(
+    cargo_build: &CargoBuild,
+    cargo_profile: CargoProfile,
+    target_triple: &str,
+) -> std::io::Result<BuildpackBinaries> {
+    let profile_dir_name = match cargo_profile {
+        CargoProfile::Dev => "debug",
+        CargoProfile::Release => "release",
+    };
+
+    let cargo_output_dir = cargo_build
+        .target_directory
+        .join(target_triple)
+        .join(profile_dir_name);
+
+    let buildpack_binaries_dir = buildpack_binaries_dir(
+        &cargo_build.target_directory,
+        target_triple,
+        profile_dir_name,
+        &cargo_build.package_name,
+    );
+
+    let _ = fs::remove_dir_all(&buildpack_binaries_dir);
+    fs::create_dir_all(&buildpack_binaries_dir)?;
+
+    let mut additional_target_binary_paths = HashMap::new();
+    for binary_target_name in &cargo_build.binary_target_names {
+        let binary_path = buildpack_binaries_dir.join(binary_target_name);
+        fs::copy(cargo_output_dir.join(binary_target_name), &binary_path)?;
+
+        if *binary_target_name != cargo_build.buildpack_cargo_target {
+            additional_target_binary_paths.insert(binary_target_name.clone(), binary_path);
+        }
     }
 
     Ok(BuildpackBinaries {
-        buildpack_target_binary_path,
+        buildpack_target_binary_path: buildpack_binaries_dir
+            .join(&cargo_build.buildpack_cargo_target),
         additional_target_binary_paths,
     })
 }
 
-/// Builds a binary using Cargo.
+/// The directory the binaries of a package are copied to after building it, see
+/// [`copy_buildpack_binaries`].
+fn buildpack_binaries_dir(
+    target_directory: &Path,
+    target_triple: &str,
+    profile_dir_name: &str,
+    package_name: &str,
+) -> PathBuf {
+    target_directory
+        .join("libcnb-package")
+        .join(target_triple)
+        .join(profile_dir_name)
+        .join(package_name)
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Builds all binaries of the given package using Cargo.
 ///
 /// It is designed to handle cross-compilation without requiring custom configuration in the Cargo
 /// manifest of the user's buildpack. The triple for the target platform is a mandatory
@@ -90,20 +226,30 @@ pub(crate) fn build_buildpack_binaries(
 /// returned which provides additional information. Use the `cross_compile::cross_compile_help`
 /// function to obtain human-readable instructions on how to setup the required tools.
 ///
-/// This function will write Cargo's output to stdout and stderr.
+/// Cargo is run in the workspace root. This function will write Cargo's output to stdout and
+/// stderr.
 ///
 /// # Errors
 ///
 /// Will return `Err` if the build did not finish successfully.
-fn build_binary(
-    project_path: impl AsRef<Path>,
-    cargo_metadata: &Metadata,
+fn build_package(
+    cargo_build: &CargoBuild,
     cargo_profile: CargoProfile,
-    mut cargo_env: Vec<(OsString, OsString)>,
-    target_triple: impl AsRef<str>,
-    target_name: impl AsRef<str>,
-) -> Result<PathBuf, BuildError> {
-    let mut cargo_args = vec!["build", "--target", target_triple.as_ref()];
+    cargo_env: &[(OsString, OsString)],
+    target_triple: &str,
+) -> Result<(), BuildError> {
+    let mut cargo_env = cargo_env.to_vec();
+    let mut cargo_args = vec!["build", "--target", target_triple];
+    if !color_output_enabled() {
+        cargo_args.extend(["--color", "never"]);
+    }
+
+    cargo_args.extend(["--package", &cargo_build.package_name]);
+
+    let features = cargo_build.configuration.features.join(",");
+    if !features.is_empty() {
+        cargo_args.extend(["--features", &features]);
+    }
     match cargo_profile {
         CargoProfile::Dev => {
             // We enable stripping for dev builds too, since debug builds are extremely
@@ -134,24 +280,22 @@ fn build_binary(
 
     let exit_status = Command::new("cargo")
         .args(cargo_args)
+        .envs(&cargo_build.configuration.env)
+        .envs(
+            cargo_build
+                .configuration
+                .rustflags
+                .as_ref()
+                .map(|rustflags| ("RUSTFLAGS", rustflags)),
+        )
         .envs(cargo_env)
-        .current_dir(&project_path)
+        .current_dir(&cargo_build.workspace_root)
         .spawn()
         .and_then(|mut child| child.wait())
         .map_err(BuildError::CargoProcessIoError)?;
 
     if exit_status.success() {
-        let binary_path = cargo_metadata
-            .target_directory
-            .join(target_triple.as_ref())
-            .join(match cargo_profile {
-                CargoProfile::Dev => "debug",
-                CargoProfile::Release => "release",
-            })
-            .join(target_name.as_ref())
-            .into_std_path_buf();
-
-        Ok(binary_path)
+        Ok(())
     } else {
         Err(BuildError::UnexpectedCargoExitStatus(exit_status))
     }
@@ -177,8 +321,68 @@ pub enum BuildError {
 pub enum BuildBinariesError {
     #[error("Failed to determine Cargo target name for buildpack: {0}")]
     CannotDetermineBuildpackCargoTargetName(#[source] DetermineBuildpackCargoTargetNameError),
-    #[error("Failed to build binary target {0}: {1}")]
+    #[error("Failed to build Cargo package {0}: {1}")]
     BuildError(String, #[source] BuildError),
+    #[error("Failed to copy binaries of Cargo package {0}: {1}")]
+    CannotCopyBinaries(String, #[source] std::io::Error),
     #[error("Binary target {0} couldn't be found")]
     MissingBuildpackTarget(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copy_buildpack_binaries_keeps_binaries_with_the_same_name_apart() {
+        let target_directory = tempfile::tempdir().unwrap();
+        let cargo_output_dir = target_directory
+            .path()
+            .join("x86_64-unknown-linux-musl")
+            .join("release");
+        fs::create_dir_all(&cargo_output_dir).unwrap();
+
+        let cargo_build_configuration = CargoBuildConfiguration::default();
+        let cargo_build = |package_name: &str| CargoBuild {
+            workspace_root: target_directory.path().to_path_buf(),
+            target_directory: target_directory.path().to_path_buf(),
+            package_name: package_name.to_string(),
+            configuration: &cargo_build_configuration,
+            buildpack_cargo_target: package_name.to_string(),
+            binary_target_names: vec![package_name.to_string(), String::from("helper")],
+        };
+
+        let mut buildpack_binaries = Vec::new();
+        for package_name in ["one", "two"] {
+            // Simulates Cargo writing the binaries of each package to the shared output directory.
+            fs::write(cargo_output_dir.join(package_name), package_name).unwrap();
+            fs::write(
+                cargo_output_dir.join("helper"),
+                format!("{package_name}-helper"),
+            )
+            .unwrap();
+
+            buildpack_binaries.push(
+                copy_buildpack_binaries(
+                    &cargo_build(package_name),
+                    CargoProfile::Release,
+                    "x86_64-unknown-linux-musl",
+                )
+                .unwrap(),
+            );
+        }
+
+        for (package_name, buildpack_binaries) in ["one", "two"].iter().zip(&buildpack_binaries) {
+            assert_eq!(
+                fs::read_to_string(&buildpack_binaries.buildpack_target_binary_path).unwrap(),
+                *package_name
+            );
+            assert_eq!(
+                fs::read_to_string(&buildpack_binaries.additional_target_binary_paths["helper"])
+                    .unwrap(),
+                format!("{package_name}-helper")
+            );
+            assert_eq!(buildpack_binaries.additional_target_binary_paths.len(), 1);
+        }
+    }
+}
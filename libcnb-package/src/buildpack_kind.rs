@@ -2,8 +2,11 @@ use libcnb_common::toml_file::read_toml_file;
 use libcnb_data::buildpack::BuildpackDescriptor;
 use std::path::Path;
 
+/// Determines the kind of the buildpack in the given directory.
+///
+/// Returns `None` if the directory doesn't contain a valid `buildpack.toml`.
 #[must_use]
-pub(crate) fn determine_buildpack_kind(buildpack_dir: &Path) -> Option<BuildpackKind> {
+pub fn determine_buildpack_kind(buildpack_dir: &Path) -> Option<BuildpackKind> {
     read_toml_file::<BuildpackDescriptor>(buildpack_dir.join("buildpack.toml"))
         .ok()
         .map(|buildpack_descriptor| match buildpack_descriptor {
@@ -18,7 +21,8 @@ pub(crate) fn determine_buildpack_kind(buildpack_dir: &Path) -> Option<Buildpack
         })
 }
 
-pub(crate) enum BuildpackKind {
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BuildpackKind {
     Composite,
     LibCnbRs,
     Other,
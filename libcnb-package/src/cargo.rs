@@ -1,3 +1,6 @@
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
 pub(crate) fn determine_buildpack_cargo_target_name(
     cargo_metadata: &cargo_metadata::Metadata,
 ) -> Result<String, DetermineBuildpackCargoTargetNameError> {
@@ -46,3 +49,229 @@ fn cargo_binary_target_names_from_root_package(
         .filter_map(|target| target.is_bin().then_some(target.name.clone()))
         .collect()
 }
+
+/// The directory, relative to the packaged buildpack, that additional binary targets are copied to
+/// when the buildpack doesn't configure a different directory.
+pub const DEFAULT_ADDITIONAL_BINARIES_DIR: &str = ".libcnb-cargo/additional-bin";
+
+/// Determines the directory, relative to the packaged buildpack, that additional binary targets
+/// are copied to.
+///
+/// Buildpacks can configure the directory in their `Cargo.toml`:
+///
+/// ```toml
+/// [package.metadata.libcnb]
+/// additional-binaries-dir = "bin/helpers"
+/// ```
+///
+/// If not configured, [`DEFAULT_ADDITIONAL_BINARIES_DIR`] is used.
+///
+/// # Errors
+///
+/// Returns `Err` if the configured value is not a string or not a relative path that stays within
+/// the buildpack directory.
+pub fn additional_binaries_dir(
+    cargo_metadata: &cargo_metadata::Metadata,
+) -> Result<PathBuf, AdditionalBinariesDirError> {
+    let root_package = cargo_metadata
+        .root_package()
+        .ok_or(AdditionalBinariesDirError::NoRootPackage)?;
+
+    match &root_package.metadata["libcnb"]["additional-binaries-dir"] {
+        value if value.is_null() => Ok(PathBuf::from(DEFAULT_ADDITIONAL_BINARIES_DIR)),
+        value => {
+            let path = value
+                .as_str()
+                .map(PathBuf::from)
+                .ok_or(AdditionalBinariesDirError::NotAString)?;
+
+            if is_valid_additional_binaries_dir(&path) {
+                Ok(path)
+            } else {
+                Err(AdditionalBinariesDirError::InvalidPath(path))
+            }
+        }
+    }
+}
+
+fn is_valid_additional_binaries_dir(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum AdditionalBinariesDirError {
+    #[error("Cargo metadata is missing the required root package")]
+    NoRootPackage,
+    #[error("The configured additional binaries directory must be a string")]
+    NotAString,
+    #[error("The configured additional binaries directory must be a relative path within the buildpack directory, got: {}", .0.display())]
+    InvalidPath(PathBuf),
+}
+
+/// Configuration for building the binaries of a buildpack with Cargo.
+///
+/// Buildpacks can configure it in their `Cargo.toml`, so that the same settings are used whenever
+/// the buildpack is packaged, without passing them on the command line:
+///
+/// ```toml
+/// [package.metadata.libcnb.build]
+/// features = ["telemetry"]
+/// rustflags = "-C target-cpu=x86-64-v2"
+/// env = { OPENSSL_STATIC = "1" }
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct CargoBuildConfiguration {
+    /// Cargo features to enable, passed to Cargo via `--features`.
+    pub features: Vec<String>,
+    /// Flags for the compiler, passed to Cargo via the `RUSTFLAGS` environment variable.
+    pub rustflags: Option<String>,
+    /// Additional environment variables for the Cargo process.
+    ///
+    /// Environment variables libcnb.rs sets for cross-compilation and stripping of binaries take
+    /// precedence. `rustflags` takes precedence over a `RUSTFLAGS` variable.
+    pub env: BTreeMap<String, String>,
+}
+
+/// Reads the [`CargoBuildConfiguration`] from the `[package.metadata.libcnb.build]` table of
+/// the buildpack's `Cargo.toml`.
+///
+/// If the table doesn't exist, the default (empty) configuration is returned.
+///
+/// # Errors
+///
+/// Returns `Err` if the configuration has values of unexpected types.
+pub fn cargo_build_configuration(
+    cargo_metadata: &cargo_metadata::Metadata,
+) -> Result<CargoBuildConfiguration, CargoBuildConfigurationError> {
+    let root_package = cargo_metadata
+        .root_package()
+        .ok_or(CargoBuildConfigurationError::NoRootPackage)?;
+
+    parse_cargo_build_configuration(&root_package.metadata["libcnb"]["build"])
+}
+
+fn parse_cargo_build_configuration(
+    value: &serde_json::Value,
+) -> Result<CargoBuildConfiguration, CargoBuildConfigurationError> {
+    let features = match &value["features"] {
+        serde_json::Value::Null => Vec::new(),
+        serde_json::Value::Array(features) => features
+            .iter()
+            .map(|feature| feature.as_str().map(String::from))
+            .collect::<Option<_>>()
+            .ok_or(CargoBuildConfigurationError::InvalidFeatures)?,
+        _ => Err(CargoBuildConfigurationError::InvalidFeatures)?,
+    };
+
+    let rustflags = match &value["rustflags"] {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(rustflags) => Some(rustflags.clone()),
+        _ => Err(CargoBuildConfigurationError::InvalidRustflags)?,
+    };
+
+    let env = match &value["env"] {
+        serde_json::Value::Null => BTreeMap::new(),
+        serde_json::Value::Object(env) => env
+            .iter()
+            .map(|(key, value)| {
+                value
+                    .as_str()
+                    .map(|value| (key.clone(), String::from(value)))
+                    .ok_or_else(|| CargoBuildConfigurationError::InvalidEnvValue(key.clone()))
+            })
+            .collect::<Result<_, _>>()?,
+        _ => Err(CargoBuildConfigurationError::InvalidEnv)?,
+    };
+
+    Ok(CargoBuildConfiguration {
+        features,
+        rustflags,
+        env,
+    })
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CargoBuildConfigurationError {
+    #[error("Cargo metadata is missing the required root package")]
+    NoRootPackage,
+    #[error("The configured build features must be an array of strings")]
+    InvalidFeatures,
+    #[error("The configured build rustflags must be a string")]
+    InvalidRustflags,
+    #[error("The configured build env must be a table")]
+    InvalidEnv,
+    #[error("The value of the configured build env variable {0} must be a string")]
+    InvalidEnvValue(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cargo_build_configurations() {
+        assert_eq!(
+            parse_cargo_build_configuration(&serde_json::Value::Null).unwrap(),
+            CargoBuildConfiguration::default()
+        );
+
+        assert_eq!(
+            parse_cargo_build_configuration(&serde_json::json!({
+                "features": ["foo", "bar"],
+                "rustflags": "-C target-cpu=x86-64-v2",
+                "env": { "OPENSSL_STATIC": "1" }
+            }))
+            .unwrap(),
+            CargoBuildConfiguration {
+                features: vec![String::from("foo"), String::from("bar")],
+                rustflags: Some(String::from("-C target-cpu=x86-64-v2")),
+                env: BTreeMap::from([(String::from("OPENSSL_STATIC"), String::from("1"))]),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_invalid_cargo_build_configurations() {
+        assert!(matches!(
+            parse_cargo_build_configuration(&serde_json::json!({ "features": "foo" })),
+            Err(CargoBuildConfigurationError::InvalidFeatures)
+        ));
+        assert!(matches!(
+            parse_cargo_build_configuration(&serde_json::json!({ "features": [1] })),
+            Err(CargoBuildConfigurationError::InvalidFeatures)
+        ));
+        assert!(matches!(
+            parse_cargo_build_configuration(&serde_json::json!({ "rustflags": ["-g"] })),
+            Err(CargoBuildConfigurationError::InvalidRustflags)
+        ));
+        assert!(matches!(
+            parse_cargo_build_configuration(&serde_json::json!({ "env": ["FOO=bar"] })),
+            Err(CargoBuildConfigurationError::InvalidEnv)
+        ));
+        assert!(matches!(
+            parse_cargo_build_configuration(&serde_json::json!({ "env": { "FOO": 1 } })),
+            Err(CargoBuildConfigurationError::InvalidEnvValue(key)) if key == "FOO"
+        ));
+    }
+
+    #[test]
+    fn valid_additional_binaries_dirs() {
+        assert!(is_valid_additional_binaries_dir(Path::new(
+            DEFAULT_ADDITIONAL_BINARIES_DIR
+        )));
+        assert!(is_valid_additional_binaries_dir(Path::new("bin/helpers")));
+        assert!(is_valid_additional_binaries_dir(Path::new("helpers")));
+    }
+
+    #[test]
+    fn invalid_additional_binaries_dirs() {
+        assert!(!is_valid_additional_binaries_dir(Path::new("")));
+        assert!(!is_valid_additional_binaries_dir(Path::new("/opt/helpers")));
+        assert!(!is_valid_additional_binaries_dir(Path::new("../helpers")));
+        assert!(!is_valid_additional_binaries_dir(Path::new("bin/../..")));
+        assert!(!is_valid_additional_binaries_dir(Path::new("./helpers")));
+    }
+}
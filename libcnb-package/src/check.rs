@@ -0,0 +1,569 @@
+use crate::buildpack_kind::{determine_buildpack_kind, BuildpackKind};
+use crate::metadata::{find_metadata_drifts, MetadataDrift};
+use cargo_metadata::diagnostic::DiagnosticLevel;
+use cargo_metadata::semver::Version;
+use cargo_metadata::{Message, Metadata, MetadataCommand, PackageId};
+use libcnb_common::toml_file::{read_toml_file, TomlFileError};
+use libcnb_data::buildpack::BuildpackDescriptor;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::thread;
+
+/// Names of the crates whose deprecated APIs are reported by [`check_buildpack`].
+const LIBCNB_CRATE_NAMES: [&str; 5] = [
+    "libcnb",
+    "libcnb_data",
+    "libcnb_package",
+    "libcnb_test",
+    "libherokubuildpack",
+];
+
+/// Checks a libcnb.rs buildpack for findings that are relevant when upgrading libcnb.rs.
+///
+/// The report contains the buildpack's usages of deprecated libcnb.rs APIs and compares the
+/// buildpack's minimum supported Rust version (MSRV) with the one of libcnb.rs. It also lists
+/// differences between the metadata in `buildpack.toml` and `Cargo.toml`, see
+/// [`find_metadata_drifts`]. Deprecated API
+/// usages are collected from the compiler diagnostics of `cargo check`, which compiles all targets
+/// of the buildpack crate, including tests. Cargo's output is not forwarded.
+///
+/// When `include_deprecated_api_usages` is `false`, `cargo check` is not run and the report only
+/// contains the MSRV comparison, which is cheap to determine.
+///
+/// Returns `Ok(None)` if the given directory doesn't contain a libcnb.rs buildpack.
+///
+/// # Errors
+///
+/// Returns an error if the Cargo metadata or buildpack descriptor couldn't be read or
+/// `cargo check` failed.
+pub fn check_buildpack(
+    buildpack_directory: &Path,
+    include_deprecated_api_usages: bool,
+) -> Result<Option<CheckReport>, CheckError> {
+    let Some(BuildpackKind::LibCnbRs) = determine_buildpack_kind(buildpack_directory) else {
+        return Ok(None);
+    };
+
+    let cargo_metadata = MetadataCommand::new()
+        .manifest_path(buildpack_directory.join("Cargo.toml"))
+        .exec()
+        .map_err(CheckError::CargoMetadataError)?;
+
+    let root_package = cargo_metadata
+        .root_package()
+        .ok_or(CheckError::MissingRootPackage)?;
+
+    let buildpack_descriptor =
+        read_toml_file::<BuildpackDescriptor>(buildpack_directory.join("buildpack.toml"))
+            .map_err(CheckError::CouldNotReadBuildpackDescriptor)?;
+
+    let deprecated_api_usages = if include_deprecated_api_usages {
+        Some(collect_deprecated_api_usages(
+            buildpack_directory,
+            &root_package.id,
+        )?)
+    } else {
+        None
+    };
+
+    Ok(Some(CheckReport {
+        package_name: root_package.name.clone(),
+        msrv: check_msrv(&cargo_metadata),
+        metadata_drifts: find_metadata_drifts(buildpack_descriptor.buildpack(), root_package),
+        deprecated_api_usages,
+    }))
+}
+
+/// Compares the MSRV of the root package with the one of its `libcnb` dependency.
+#[must_use]
+pub fn check_msrv(cargo_metadata: &Metadata) -> MsrvCheck {
+    MsrvCheck {
+        buildpack_msrv: cargo_metadata
+            .root_package()
+            .and_then(|package| package.rust_version.clone()),
+        libcnb_msrv: cargo_metadata
+            .packages
+            .iter()
+            .find(|package| package.name == "libcnb")
+            .and_then(|package| package.rust_version.clone()),
+    }
+}
+
+fn collect_deprecated_api_usages(
+    buildpack_directory: &Path,
+    package_id: &PackageId,
+) -> Result<Vec<DeprecatedApiUsage>, CheckError> {
+    let mut child = Command::new("cargo")
+        .args(["check", "--all-targets", "--message-format", "json"])
+        .current_dir(buildpack_directory)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(CheckError::CargoProcessIoError)?;
+
+    // Read in a separate thread, so that Cargo doesn't block on a full stderr pipe while stdout
+    // is being read.
+    let stderr_thread = child.stderr.take().map(|mut stderr| {
+        thread::spawn(move || {
+            let mut output = String::new();
+            stderr.read_to_string(&mut output).map(|_| output)
+        })
+    });
+
+    let messages = child
+        .stdout
+        .take()
+        .map(|stdout| {
+            Message::parse_stream(BufReader::new(stdout))
+                .filter_map(Result::ok)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    let exit_status = child.wait().map_err(CheckError::CargoProcessIoError)?;
+
+    let stderr = stderr_thread
+        .map(|stderr_thread| {
+            stderr_thread
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        })
+        .transpose()
+        .map_err(CheckError::CargoProcessIoError)?
+        .unwrap_or_default();
+
+    if !exit_status.success() {
+        return Err(CheckError::UnexpectedCargoExitStatus(
+            exit_status,
+            cargo_error_output(&messages, &stderr),
+        ));
+    }
+
+    Ok(deprecated_api_usages(messages, package_id))
+}
+
+/// Combines the rendered compiler errors from Cargo's JSON messages with Cargo's stderr.
+///
+/// With `--message-format json`, compiler diagnostics are only part of the JSON messages on
+/// stdout, while stderr only contains Cargo's own messages, such as manifest errors.
+fn cargo_error_output(messages: &[Message], stderr: &str) -> String {
+    messages
+        .iter()
+        .filter_map(|message| match message {
+            Message::CompilerMessage(compiler_message)
+                if matches!(
+                    compiler_message.message.level,
+                    DiagnosticLevel::Error | DiagnosticLevel::Ice
+                ) =>
+            {
+                compiler_message.message.rendered.as_deref()
+            }
+            _ => None,
+        })
+        .chain(std::iter::once(stderr))
+        .map(str::trim_end)
+        .filter(|output| !output.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Extracts the usages of deprecated libcnb.rs APIs in the given package from Cargo's JSON
+/// messages.
+///
+/// Cargo reports the diagnostics of all targets of a package separately, so the same usage can be
+/// reported multiple times. Duplicates are removed.
+fn deprecated_api_usages(
+    messages: impl IntoIterator<Item = Message>,
+    package_id: &PackageId,
+) -> Vec<DeprecatedApiUsage> {
+    let mut usages = messages
+        .into_iter()
+        .filter_map(|message| match message {
+            Message::CompilerMessage(compiler_message)
+                if compiler_message.package_id == *package_id =>
+            {
+                Some(compiler_message.message)
+            }
+            _ => None,
+        })
+        .filter(|diagnostic| {
+            diagnostic
+                .code
+                .as_ref()
+                .is_some_and(|code| code.code == "deprecated")
+        })
+        .filter_map(|diagnostic| {
+            let item = deprecated_libcnb_item(&diagnostic.message)?;
+            let primary_span = diagnostic.spans.iter().find(|span| span.is_primary);
+
+            Some(DeprecatedApiUsage {
+                item: String::from(item),
+                message: diagnostic.message.clone(),
+                file: primary_span.map(|span| PathBuf::from(&span.file_name)),
+                line: primary_span.map(|span| span.line_start),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    usages.sort();
+    usages.dedup();
+    usages
+}
+
+/// Returns the path of the deprecated item if it belongs to libcnb.rs.
+///
+/// rustc reports deprecations with messages like ``use of deprecated function `libcnb::foo`: note``.
+fn deprecated_libcnb_item(message: &str) -> Option<&str> {
+    let (_, rest) = message.split_once('`')?;
+    let (item, _) = rest.split_once('`')?;
+
+    let crate_name = item.split("::").next().unwrap_or_default();
+    LIBCNB_CRATE_NAMES.contains(&crate_name).then_some(item)
+}
+
+/// The findings of [`check_buildpack`] for a single buildpack.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CheckReport {
+    /// The name of the buildpack's Cargo package.
+    pub package_name: String,
+    pub msrv: MsrvCheck,
+    /// The differences between the metadata in `buildpack.toml` and `Cargo.toml`.
+    pub metadata_drifts: Vec<MetadataDrift>,
+    /// The usages of deprecated libcnb.rs APIs, `None` if they weren't checked.
+    pub deprecated_api_usages: Option<Vec<DeprecatedApiUsage>>,
+}
+
+impl CheckReport {
+    /// Returns whether the report contains findings that need attention.
+    #[must_use]
+    pub fn has_findings(&self) -> bool {
+        !self.msrv.matches()
+            || !self.metadata_drifts.is_empty()
+            || self
+                .deprecated_api_usages
+                .as_ref()
+                .is_some_and(|usages| !usages.is_empty())
+    }
+
+    /// Counts the usages per deprecated item, sorted by item path.
+    #[must_use]
+    pub fn deprecated_item_counts(&self) -> BTreeMap<&str, usize> {
+        self.deprecated_api_usages
+            .iter()
+            .flatten()
+            .fold(BTreeMap::new(), |mut counts, usage| {
+                *counts.entry(usage.item.as_str()).or_default() += 1;
+                counts
+            })
+    }
+}
+
+impl Display for CheckReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}:", self.package_name)?;
+        writeln!(f, "  {}", self.msrv)?;
+
+        for metadata_drift in &self.metadata_drifts {
+            writeln!(f, "  {metadata_drift}")?;
+        }
+
+        match &self.deprecated_api_usages {
+            None => Ok(()),
+            Some(usages) if usages.is_empty() => {
+                writeln!(f, "  No usages of deprecated libcnb.rs APIs")
+            }
+            Some(usages) => {
+                writeln!(
+                    f,
+                    "  {} usage(s) of deprecated libcnb.rs APIs:",
+                    usages.len()
+                )?;
+
+                for (item, count) in self.deprecated_item_counts() {
+                    writeln!(f, "    {item} ({count}x)")?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The usage of a deprecated libcnb.rs API.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct DeprecatedApiUsage {
+    /// The path of the deprecated item, i.e. `libcnb::layer::Layer`.
+    pub item: String,
+    /// The compiler's deprecation message, including the deprecation note.
+    pub message: String,
+    pub file: Option<PathBuf>,
+    pub line: Option<usize>,
+}
+
+/// The comparison of a buildpack's MSRV with the MSRV of libcnb.rs.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MsrvCheck {
+    /// The `rust-version` of the buildpack's Cargo package.
+    pub buildpack_msrv: Option<Version>,
+    /// The `rust-version` of the `libcnb` dependency.
+    pub libcnb_msrv: Option<Version>,
+}
+
+impl MsrvCheck {
+    /// Returns whether both MSRVs are the same Rust version.
+    ///
+    /// Only the major, minor and patch versions are compared, any pre-release or build metadata
+    /// is ignored. Cargo already normalizes two-component versions such as `1.76` to `1.76.0`.
+    #[must_use]
+    pub fn matches(&self) -> bool {
+        self.buildpack_msrv.as_ref().map(normalized_rust_version)
+            == self.libcnb_msrv.as_ref().map(normalized_rust_version)
+    }
+}
+
+fn normalized_rust_version(version: &Version) -> Version {
+    Version::new(version.major, version.minor, version.patch)
+}
+
+impl Display for MsrvCheck {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.buildpack_msrv, &self.libcnb_msrv) {
+            (Some(buildpack_msrv), Some(_)) if self.matches() => {
+                write!(f, "MSRV {buildpack_msrv} matches libcnb.rs")
+            }
+            (Some(buildpack_msrv), Some(libcnb_msrv)) => write!(
+                f,
+                "MSRV {buildpack_msrv} differs from libcnb.rs MSRV {libcnb_msrv}"
+            ),
+            (None, Some(libcnb_msrv)) => write!(
+                f,
+                "MSRV not specified (rust-version), libcnb.rs MSRV is {libcnb_msrv}"
+            ),
+            (Some(buildpack_msrv), None) => {
+                write!(f, "MSRV {buildpack_msrv}, libcnb.rs MSRV is unknown")
+            }
+            (None, None) => write!(f, "MSRV not specified, libcnb.rs MSRV is unknown"),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CheckError {
+    #[error("Failed to read Cargo metadata: {0}")]
+    CargoMetadataError(#[source] cargo_metadata::Error),
+    #[error("Cargo metadata doesn't contain a root package")]
+    MissingRootPackage,
+    #[error("Couldn't read buildpack descriptor: {0}")]
+    CouldNotReadBuildpackDescriptor(#[source] TomlFileError),
+    #[error("I/O error while running Cargo check process: {0}")]
+    CargoProcessIoError(#[source] std::io::Error),
+    #[error("Cargo unexpectedly exited with status {0}:\n\n{1}")]
+    UnexpectedCargoExitStatus(ExitStatus, String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACKAGE_ID: &str = "path+file:///workspace/buildpacks/java#0.1.0";
+
+    fn compiler_message(package_id: &str, code: &str, message: &str, line: usize) -> String {
+        compiler_message_with_level(package_id, code, message, line, "warning")
+    }
+
+    fn compiler_message_with_level(
+        package_id: &str,
+        code: &str,
+        message: &str,
+        line: usize,
+        level: &str,
+    ) -> String {
+        format!(
+            r#"{{"reason":"compiler-message","package_id":"{package_id}","manifest_path":"/workspace/buildpacks/java/Cargo.toml","target":{{"kind":["bin"],"crate_types":["bin"],"name":"java","src_path":"/workspace/buildpacks/java/src/main.rs","edition":"2021","doc":true,"doctest":false,"test":true}},"message":{{"message":"{message}","code":{{"code":"{code}","explanation":null}},"level":"{level}","spans":[{{"file_name":"src/main.rs","byte_start":0,"byte_end":1,"line_start":{line},"line_end":{line},"column_start":1,"column_end":2,"is_primary":true,"text":[],"label":null,"suggested_replacement":null,"suggestion_applicability":null,"expansion":null}}],"children":[],"rendered":"{level}: {message}\n"}}}}"#
+        )
+    }
+
+    #[test]
+    fn deprecated_api_usages_from_messages() {
+        let stream = [
+            compiler_message(
+                PACKAGE_ID,
+                "deprecated",
+                "use of deprecated trait `libcnb::layer::Layer`: Use the struct layer API instead",
+                10,
+            ),
+            // Reported again for the test target.
+            compiler_message(
+                PACKAGE_ID,
+                "deprecated",
+                "use of deprecated trait `libcnb::layer::Layer`: Use the struct layer API instead",
+                10,
+            ),
+            compiler_message(
+                PACKAGE_ID,
+                "deprecated",
+                "use of deprecated function `libherokubuildpack::log::log_info`: Use bullet_stream",
+                20,
+            ),
+            compiler_message(
+                PACKAGE_ID,
+                "deprecated",
+                "use of deprecated function `other_crate::foo`",
+                30,
+            ),
+            compiler_message(
+                PACKAGE_ID,
+                "unused_imports",
+                "unused import: `libcnb::Env`",
+                40,
+            ),
+            compiler_message(
+                "path+file:///workspace/buildpacks/other#0.1.0",
+                "deprecated",
+                "use of deprecated trait `libcnb::layer::Layer`",
+                50,
+            ),
+        ]
+        .join("\n");
+
+        let usages = deprecated_api_usages(
+            Message::parse_stream(stream.as_bytes()).map(Result::unwrap),
+            &PackageId {
+                repr: String::from(PACKAGE_ID),
+            },
+        );
+
+        assert_eq!(
+            usages
+                .iter()
+                .map(|usage| (usage.item.as_str(), usage.line))
+                .collect::<Vec<_>>(),
+            [
+                ("libcnb::layer::Layer", Some(10)),
+                ("libherokubuildpack::log::log_info", Some(20))
+            ]
+        );
+        assert_eq!(usages[0].file, Some(PathBuf::from("src/main.rs")));
+    }
+
+    #[test]
+    fn cargo_error_output_from_messages_and_stderr() {
+        let stream = [
+            compiler_message_with_level(
+                PACKAGE_ID,
+                "E0425",
+                "cannot find value `x` in this scope",
+                10,
+                "error",
+            ),
+            compiler_message(
+                PACKAGE_ID,
+                "unused_imports",
+                "unused import: `libcnb::Env`",
+                20,
+            ),
+        ]
+        .join("\n");
+
+        let messages = Message::parse_stream(stream.as_bytes())
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            cargo_error_output(
+                &messages,
+                "error: could not compile `java` (bin \"java\") due to 1 previous error\n"
+            ),
+            "error: cannot find value `x` in this scope\nerror: could not compile `java` (bin \"java\") due to 1 previous error"
+        );
+        assert_eq!(cargo_error_output(&[], ""), "");
+    }
+
+    #[test]
+    fn msrv_matches_normalized_versions() {
+        let msrv_check = |buildpack_msrv: Option<&str>, libcnb_msrv: Option<&str>| MsrvCheck {
+            buildpack_msrv: buildpack_msrv.map(|version| Version::parse(version).unwrap()),
+            libcnb_msrv: libcnb_msrv.map(|version| Version::parse(version).unwrap()),
+        };
+
+        assert!(msrv_check(Some("1.76.0"), Some("1.76.0")).matches());
+        assert!(msrv_check(Some("1.76.0-nightly"), Some("1.76.0")).matches());
+        assert!(msrv_check(Some("1.76.0+build.1"), Some("1.76.0")).matches());
+        assert!(!msrv_check(Some("1.76.1"), Some("1.76.0")).matches());
+        assert!(!msrv_check(None, Some("1.76.0")).matches());
+        assert_eq!(
+            msrv_check(Some("1.76.0+build.1"), Some("1.76.0")).to_string(),
+            "MSRV 1.76.0+build.1 matches libcnb.rs"
+        );
+    }
+
+    #[test]
+    fn report_display() {
+        let usage = DeprecatedApiUsage {
+            item: String::from("libcnb::layer::Layer"),
+            message: String::from("use of deprecated trait `libcnb::layer::Layer`"),
+            file: None,
+            line: None,
+        };
+
+        let report = CheckReport {
+            package_name: String::from("java-buildpack"),
+            msrv: MsrvCheck {
+                buildpack_msrv: Some(Version::new(1, 74, 0)),
+                libcnb_msrv: Some(Version::new(1, 76, 0)),
+            },
+            metadata_drifts: vec![MetadataDrift::Homepage {
+                buildpack_toml: None,
+                cargo_toml: String::from("https://example.com"),
+            }],
+            deprecated_api_usages: Some(vec![usage.clone(), usage]),
+        };
+
+        assert!(report.has_findings());
+        assert_eq!(
+            report.to_string(),
+            "java-buildpack:\n  MSRV 1.74.0 differs from libcnb.rs MSRV 1.76.0\n  buildpack.toml homepage missing, Cargo.toml specifies \"https://example.com\"\n  2 usage(s) of deprecated libcnb.rs APIs:\n    libcnb::layer::Layer (2x)\n"
+        );
+
+        let report = CheckReport {
+            package_name: String::from("java-buildpack"),
+            msrv: MsrvCheck {
+                buildpack_msrv: Some(Version::new(1, 76, 0)),
+                libcnb_msrv: Some(Version::new(1, 76, 0)),
+            },
+            metadata_drifts: Vec::new(),
+            deprecated_api_usages: Some(Vec::new()),
+        };
+
+        assert!(!report.has_findings());
+        assert_eq!(
+            report.to_string(),
+            "java-buildpack:\n  MSRV 1.76.0 matches libcnb.rs\n  No usages of deprecated libcnb.rs APIs\n"
+        );
+
+        let report = CheckReport {
+            deprecated_api_usages: None,
+            ..report
+        };
+        assert_eq!(
+            report.to_string(),
+            "java-buildpack:\n  MSRV 1.76.0 matches libcnb.rs\n"
+        );
+    }
+
+    #[test]
+    fn check_msrv_of_workspace_crate() {
+        let cargo_metadata = MetadataCommand::new()
+            .manifest_path(Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml"))
+            .exec()
+            .unwrap();
+
+        // All crates of the libcnb.rs workspace share the same MSRV.
+        let msrv_check = check_msrv(&cargo_metadata);
+        assert!(msrv_check.buildpack_msrv.is_some());
+        assert!(msrv_check.matches());
+    }
+}
@@ -0,0 +1,109 @@
+//! Writes a checksum manifest of the contents of a packaged buildpack directory.
+//!
+//! The manifest uses the format of `sha256sum` and can be verified by running
+//! `sha256sum --check CHECKSUMS.sha256` in the packaged buildpack directory. This allows signing
+//! and attestation pipelines to verify the integrity of packaged buildpacks without hashing the
+//! files in bespoke scripts.
+
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The name of the checksum manifest in the packaged buildpack directory.
+pub const CHECKSUMS_FILE_NAME: &str = "CHECKSUMS.sha256";
+
+/// Calculates the SHA-256 digests of all files in the given directory, recursively.
+///
+/// Paths are relative to the given directory, use `/` as separator and are sorted. Symlinks to
+/// files are hashed with the contents of the file they point to, like `sha256sum` does. Symlinks
+/// to directories and an existing checksum manifest in the root of the directory are skipped.
+///
+/// # Errors
+///
+/// Returns `Err` if the directory or one of its files couldn't be read.
+pub fn calculate_checksums(dir: &Path) -> io::Result<Vec<(String, String)>> {
+    let mut checksums = Vec::new();
+    collect_checksums(dir, &PathBuf::new(), &mut checksums)?;
+    checksums.retain(|(path, _)| path != CHECKSUMS_FILE_NAME);
+    checksums.sort();
+    Ok(checksums)
+}
+
+/// Writes a `CHECKSUMS.sha256` manifest covering all files in the given packaged buildpack
+/// directory to that directory, replacing an existing manifest.
+///
+/// # Errors
+///
+/// Returns `Err` if the checksums couldn't be calculated or the manifest couldn't be written.
+pub fn write_checksums_file(packaged_buildpack_dir: &Path) -> io::Result<()> {
+    let contents = calculate_checksums(packaged_buildpack_dir)?
+        .into_iter()
+        .fold(String::new(), |mut contents, (path, digest)| {
+            let _ = writeln!(contents, "{digest}  {path}");
+            contents
+        });
+
+    fs::write(packaged_buildpack_dir.join(CHECKSUMS_FILE_NAME), contents)
+}
+
+fn collect_checksums(
+    dir: &Path,
+    relative_dir: &Path,
+    checksums: &mut Vec<(String, String)>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let relative_path = relative_dir.join(entry.file_name());
+        // Follows symlinks, so that symlinked executables are hashed with their contents.
+        let metadata = fs::metadata(&path)?;
+
+        if metadata.is_dir() {
+            if !entry.file_type()?.is_symlink() {
+                collect_checksums(&path, &relative_path, checksums)?;
+            }
+        } else {
+            checksums.push((
+                relative_path
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                format!("{:x}", Sha256::digest(fs::read(&path)?)),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_checksums_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        fs::create_dir_all(dir.join("bin")).unwrap();
+        fs::create_dir_all(dir.join("empty")).unwrap();
+        fs::write(dir.join("buildpack.toml"), "").unwrap();
+        fs::write(dir.join("bin/build"), "build").unwrap();
+        std::os::unix::fs::symlink("build", dir.join("bin/detect")).unwrap();
+        std::os::unix::fs::symlink("bin", dir.join("bin-link")).unwrap();
+        fs::write(dir.join(CHECKSUMS_FILE_NAME), "outdated").unwrap();
+
+        super::write_checksums_file(dir).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.join(CHECKSUMS_FILE_NAME)).unwrap(),
+            "\
+44575cf5b28512d75644bf54a517dcef304ff809fd511747621b4d64f19aac66  bin/build
+44575cf5b28512d75644bf54a517dcef304ff809fd511747621b4d64f19aac66  bin/detect
+e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  buildpack.toml
+"
+        );
+    }
+}
@@ -1,6 +1,9 @@
+use cargo_metadata::MetadataCommand;
 use indoc::{formatdoc, indoc};
 use std::env::consts;
 use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
 use which::which;
 
 /// Provides assistance for cross-compiling from the user's host platform to the desired target platform.
@@ -8,6 +11,12 @@ use which::which;
 /// This function will not install required toolchains, linkers or compilers automatically. It will
 /// look for the required tools and returns a human-readable help text if they can't be found or
 /// any other issue has been detected.
+///
+/// The platform specific GCC cross-compiler is preferred. If it can't be found, [Zig](https://ziglang.org)
+/// is used as a fallback linker and C compiler, either via [`cargo-zigbuild`](https://github.com/rust-cross/cargo-zigbuild)
+/// or the `zig` binary. To do so, a small wrapper script that invokes `zig cc` for the target
+/// platform is written to the Cargo target directory of the project in the current directory.
+/// Unlike a shared temporary directory, only the user owning the project can replace the script.
 pub fn cross_compile_assistance(target_triple: impl AsRef<str>) -> CrossCompileAssistance {
     let target_triple = target_triple.as_ref();
     let (gcc_binary_name, help_text) = match (target_triple, consts::OS, consts::ARCH) {
@@ -50,49 +59,51 @@ pub fn cross_compile_assistance(target_triple: impl AsRef<str>) -> CrossCompileA
         _ => return CrossCompileAssistance::NoAssistance,
     };
 
-    match which(gcc_binary_name) {
-        Ok(_) => {
-            // When the gcc binary name is `musl-gcc`, Cargo will automatically select the appropriate default linker,
-            // and set the required environment variables.
-            if gcc_binary_name == "musl-gcc" {
-                CrossCompileAssistance::Configuration {
-                    cargo_env: Vec::new(),
-                }
-            } else {
-                CrossCompileAssistance::Configuration {
-                    cargo_env: vec![
-                        (
-                            // Required until Cargo can auto-detect the musl-cross gcc/linker itself,
-                            // since otherwise it checks for a binary named 'musl-gcc' (which is handled above):
-                            // https://github.com/rust-lang/cargo/issues/4133
-                            OsString::from(format!(
-                                "CARGO_TARGET_{}_LINKER",
-                                target_triple.to_uppercase().replace('-', "_")
-                            )),
-                            OsString::from(gcc_binary_name),
-                        ),
-                        (
-                            // Required so that any crates that call out to gcc are also cross-compiled:
-                            // https://github.com/alexcrichton/cc-rs/issues/82
-                            OsString::from(format!("CC_{}", target_triple.replace('-', "_"))),
-                            OsString::from(gcc_binary_name),
-                        ),
-                    ],
-                }
+    if which(gcc_binary_name).is_ok() {
+        // When the gcc binary name is `musl-gcc`, Cargo will automatically select the appropriate default linker,
+        // and set the required environment variables.
+        if gcc_binary_name == "musl-gcc" {
+            return CrossCompileAssistance::Configuration {
+                cargo_env: Vec::new(),
+            };
+        }
+
+        return CrossCompileAssistance::Configuration {
+            cargo_env: linker_cargo_env(target_triple, gcc_binary_name),
+        };
+    }
+
+    // Only use Zig when actually cross-compiling, native builds should use the system's musl-gcc.
+    if gcc_binary_name != "musl-gcc" {
+        if let Some(zig_command) = find_zig_command() {
+            if let Ok(wrapper_path) = write_zig_cc_wrapper(target_triple, &zig_command) {
+                return CrossCompileAssistance::Configuration {
+                    cargo_env: linker_cargo_env(target_triple, wrapper_path),
+                };
             }
         }
-        Err(_) => CrossCompileAssistance::HelpText(formatdoc! {"
-            For cross-compilation from {0} {1} to {target_triple},
-            a C compiler and linker for the target platform must be installed:
-
-            {help_text}
-            You will also need to install the Rust target:
-            rustup target add {target_triple}
-            ",
-            consts::ARCH,
-            consts::OS
-        }),
     }
+
+    CrossCompileAssistance::HelpText(formatdoc! {"
+        For cross-compilation from {0} {1} to {target_triple},
+        a C compiler and linker for the target platform must be installed:
+
+        {help_text}{zig_help_text}
+        You will also need to install the Rust target:
+        rustup target add {target_triple}
+        ",
+        consts::ARCH,
+        consts::OS,
+        zig_help_text = if gcc_binary_name == "musl-gcc" {
+            ""
+        } else {
+            indoc! {"
+
+                Alternatively, install Zig (https://ziglang.org/download/) or cargo-zigbuild
+                (cargo install --locked cargo-zigbuild) to use Zig as the cross-compiler and linker.
+            "}
+        }
+    })
 }
 
 pub enum CrossCompileAssistance {
@@ -107,6 +118,89 @@ pub enum CrossCompileAssistance {
     },
 }
 
+fn linker_cargo_env(target_triple: &str, linker: impl Into<OsString>) -> Vec<(OsString, OsString)> {
+    let linker = linker.into();
+
+    vec![
+        (
+            // Required until Cargo can auto-detect the musl-cross gcc/linker itself,
+            // since otherwise it checks for a binary named 'musl-gcc' (which is handled above):
+            // https://github.com/rust-lang/cargo/issues/4133
+            OsString::from(format!(
+                "CARGO_TARGET_{}_LINKER",
+                target_triple.to_uppercase().replace('-', "_")
+            )),
+            linker.clone(),
+        ),
+        (
+            // Required so that any crates that call out to gcc are also cross-compiled:
+            // https://github.com/alexcrichton/cc-rs/issues/82
+            OsString::from(format!("CC_{}", target_triple.replace('-', "_"))),
+            linker,
+        ),
+    ]
+}
+
+/// Returns the command prefix that runs `zig cc`, preferring `cargo-zigbuild`.
+///
+/// `cargo-zigbuild` is preferred since it works around incompatibilities between the linker
+/// arguments rustc passes and the ones `zig cc` accepts.
+fn find_zig_command() -> Option<Vec<PathBuf>> {
+    which("cargo-zigbuild")
+        .map(|cargo_zigbuild| {
+            vec![
+                cargo_zigbuild,
+                PathBuf::from("zig"),
+                PathBuf::from("cc"),
+                PathBuf::from("--"),
+            ]
+        })
+        .or_else(|_| which("zig").map(|zig| vec![zig, PathBuf::from("cc")]))
+        .ok()
+}
+
+fn write_zig_cc_wrapper(target_triple: &str, zig_command: &[PathBuf]) -> std::io::Result<PathBuf> {
+    let wrapper_dir = MetadataCommand::new()
+        .no_deps()
+        .exec()
+        .map_err(std::io::Error::other)?
+        .target_directory
+        .join("libcnb-cross-compile")
+        .into_std_path_buf();
+    let wrapper_path = wrapper_dir.join(format!("zig-cc-{target_triple}"));
+
+    fs::create_dir_all(&wrapper_dir)?;
+    fs::write(
+        &wrapper_path,
+        zig_cc_wrapper_script(target_triple, zig_command),
+    )?;
+
+    #[cfg(target_family = "unix")]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&wrapper_path, fs::Permissions::from_mode(0o755))?;
+    }
+
+    Ok(wrapper_path)
+}
+
+fn zig_cc_wrapper_script(target_triple: &str, zig_command: &[PathBuf]) -> String {
+    // Zig uses target triples without the vendor component, i.e. `aarch64-linux-musl`.
+    let zig_target = target_triple.replacen("-unknown", "", 1);
+
+    let command = zig_command
+        .iter()
+        .map(|word| shell_quote(word))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!("#!/bin/sh\nexec {command} -target {zig_target} \"$@\"\n")
+}
+
+fn shell_quote(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', r"'\''"))
+}
+
 // Constants for supported target triples
 const AARCH64_UNKNOWN_LINUX_MUSL: &str = "aarch64-unknown-linux-musl";
 const X86_64_UNKNOWN_LINUX_MUSL: &str = "x86_64-unknown-linux-musl";
@@ -116,3 +210,32 @@ const OS_LINUX: &str = "linux";
 const OS_MACOS: &str = "macos";
 const ARCH_X86_64: &str = "x86_64";
 const ARCH_AARCH64: &str = "aarch64";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zig_cc_wrapper_scripts() {
+        assert_eq!(
+            zig_cc_wrapper_script(
+                AARCH64_UNKNOWN_LINUX_MUSL,
+                &[PathBuf::from("/opt/zig's/zig"), PathBuf::from("cc")]
+            ),
+            "#!/bin/sh\nexec '/opt/zig'\\''s/zig' 'cc' -target aarch64-linux-musl \"$@\"\n"
+        );
+
+        assert_eq!(
+            zig_cc_wrapper_script(
+                X86_64_UNKNOWN_LINUX_MUSL,
+                &[
+                    PathBuf::from("/usr/bin/cargo-zigbuild"),
+                    PathBuf::from("zig"),
+                    PathBuf::from("cc"),
+                    PathBuf::from("--")
+                ]
+            ),
+            "#!/bin/sh\nexec '/usr/bin/cargo-zigbuild' 'zig' 'cc' '--' -target x86_64-linux-musl \"$@\"\n"
+        );
+    }
+}
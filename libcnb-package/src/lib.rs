@@ -4,8 +4,12 @@ pub mod build;
 pub mod buildpack_dependency_graph;
 pub mod buildpack_kind;
 pub mod cargo;
+pub mod check;
+pub mod checksums;
 pub mod cross_compile;
 pub mod dependency_graph;
+pub mod metadata;
+pub mod oci_layout;
 pub mod output;
 pub mod package;
 pub mod package_descriptor;
@@ -33,6 +37,9 @@ pub enum CargoProfile {
 /// the buildpack binary is only copied once and symlinks are used to refer to it when the CNB
 /// spec requires different file(name)s.
 ///
+/// Additional binaries are copied to the given directory, relative to the destination path. See
+/// [`cargo::additional_binaries_dir`] for details.
+///
 /// This function will not validate if the buildpack descriptor at the given path is valid and will
 /// use it as-is.
 ///
@@ -43,6 +50,7 @@ fn assemble_buildpack_directory(
     destination_path: impl AsRef<Path>,
     buildpack_descriptor_path: impl AsRef<Path>,
     buildpack_binaries: &BuildpackBinaries,
+    additional_binaries_dir: &Path,
 ) -> std::io::Result<()> {
     fs::create_dir_all(destination_path.as_ref())?;
 
@@ -62,10 +70,7 @@ fn assemble_buildpack_directory(
     create_file_symlink("build", bin_path.join("detect"))?;
 
     if !buildpack_binaries.additional_target_binary_paths.is_empty() {
-        let additional_binaries_dir = destination_path
-            .as_ref()
-            .join(".libcnb-cargo")
-            .join("additional-bin");
+        let additional_binaries_dir = destination_path.as_ref().join(additional_binaries_dir);
 
         fs::create_dir_all(&additional_binaries_dir)?;
 
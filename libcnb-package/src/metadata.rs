@@ -0,0 +1,413 @@
+use cargo_metadata::Package;
+use libcnb_data::buildpack::Buildpack;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+use toml_edit::{value, Array, ArrayOfTables, DocumentMut, Item, Table};
+
+/// A difference between the metadata in `buildpack.toml` and the metadata of the buildpack's
+/// Cargo package.
+///
+/// The Cargo package is considered the source of truth. Fields that aren't set in `Cargo.toml`
+/// are not compared, since buildpacks might only specify them in `buildpack.toml`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum MetadataDrift {
+    Description {
+        buildpack_toml: Option<String>,
+        cargo_toml: String,
+    },
+    Homepage {
+        buildpack_toml: Option<String>,
+        cargo_toml: String,
+    },
+    /// The license types in `buildpack.toml` differ from the SPDX license identifiers in the
+    /// `license` expression of `Cargo.toml`.
+    Licenses {
+        buildpack_toml: Vec<String>,
+        cargo_toml: Vec<String>,
+    },
+    Keywords {
+        buildpack_toml: Vec<String>,
+        cargo_toml: Vec<String>,
+    },
+}
+
+impl Display for MetadataDrift {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (field, buildpack_toml, cargo_toml) = match self {
+            MetadataDrift::Description {
+                buildpack_toml,
+                cargo_toml,
+            } => ("description", buildpack_toml.clone(), cargo_toml.clone()),
+            MetadataDrift::Homepage {
+                buildpack_toml,
+                cargo_toml,
+            } => ("homepage", buildpack_toml.clone(), cargo_toml.clone()),
+            MetadataDrift::Licenses {
+                buildpack_toml,
+                cargo_toml,
+            } => (
+                "licenses",
+                Some(buildpack_toml.join(", ")).filter(|value| !value.is_empty()),
+                cargo_toml.join(", "),
+            ),
+            MetadataDrift::Keywords {
+                buildpack_toml,
+                cargo_toml,
+            } => (
+                "keywords",
+                Some(buildpack_toml.join(", ")).filter(|value| !value.is_empty()),
+                cargo_toml.join(", "),
+            ),
+        };
+
+        match buildpack_toml {
+            Some(buildpack_toml) => write!(
+                f,
+                "buildpack.toml {field} \"{buildpack_toml}\" differs from Cargo.toml \"{cargo_toml}\""
+            ),
+            None => write!(
+                f,
+                "buildpack.toml {field} missing, Cargo.toml specifies \"{cargo_toml}\""
+            ),
+        }
+    }
+}
+
+/// Compares the description, homepage, licenses and keywords of the given buildpack with the ones
+/// of its Cargo package.
+///
+/// Licenses and keywords are compared regardless of their order.
+#[must_use]
+pub fn find_metadata_drifts(buildpack: &Buildpack, package: &Package) -> Vec<MetadataDrift> {
+    let mut drifts = Vec::new();
+
+    if let Some(description) = package
+        .description
+        .as_ref()
+        .filter(|description| buildpack.description.as_ref() != Some(*description))
+    {
+        drifts.push(MetadataDrift::Description {
+            buildpack_toml: buildpack.description.clone(),
+            cargo_toml: description.clone(),
+        });
+    }
+
+    if let Some(homepage) = package
+        .homepage
+        .as_ref()
+        .filter(|homepage| buildpack.homepage.as_ref() != Some(*homepage))
+    {
+        drifts.push(MetadataDrift::Homepage {
+            buildpack_toml: buildpack.homepage.clone(),
+            cargo_toml: homepage.clone(),
+        });
+    }
+
+    if let Some(license) = &package.license {
+        let buildpack_licenses = buildpack
+            .licenses
+            .iter()
+            .filter_map(|license| license.r#type.clone())
+            .collect::<Vec<_>>();
+        let cargo_licenses = spdx_license_ids(license);
+
+        if sorted_unique(&buildpack_licenses) != sorted_unique(&cargo_licenses) {
+            drifts.push(MetadataDrift::Licenses {
+                buildpack_toml: buildpack_licenses,
+                cargo_toml: cargo_licenses,
+            });
+        }
+    }
+
+    if !package.keywords.is_empty()
+        && sorted_unique(&buildpack.keywords) != sorted_unique(&package.keywords)
+    {
+        drifts.push(MetadataDrift::Keywords {
+            buildpack_toml: buildpack.keywords.clone(),
+            cargo_toml: package.keywords.clone(),
+        });
+    }
+
+    drifts
+}
+
+/// Updates the `buildpack.toml` at the given path to match the Cargo package metadata of the
+/// given drifts.
+///
+/// Formatting and comments of the file are preserved. License URIs are kept for license types
+/// that are still present.
+///
+/// # Errors
+///
+/// Returns an error if the file couldn't be read, parsed or written.
+pub fn sync_buildpack_metadata(
+    buildpack_descriptor_path: &Path,
+    drifts: &[MetadataDrift],
+) -> Result<(), SyncMetadataError> {
+    let mut document = fs::read_to_string(buildpack_descriptor_path)
+        .map_err(SyncMetadataError::IoError)?
+        .parse::<DocumentMut>()
+        .map_err(SyncMetadataError::TomlParseError)?;
+
+    let buildpack_table = document
+        .get_mut("buildpack")
+        .and_then(Item::as_table_mut)
+        .ok_or(SyncMetadataError::MissingBuildpackTable)?;
+
+    for drift in drifts {
+        match drift {
+            MetadataDrift::Description { cargo_toml, .. } => {
+                buildpack_table["description"] = value(cargo_toml);
+            }
+            MetadataDrift::Homepage { cargo_toml, .. } => {
+                buildpack_table["homepage"] = value(cargo_toml);
+            }
+            MetadataDrift::Licenses { cargo_toml, .. } => {
+                let mut licenses = ArrayOfTables::new();
+                for license_type in cargo_toml {
+                    let mut license =
+                        existing_license_table(buildpack_table, license_type).unwrap_or_default();
+                    license["type"] = value(license_type);
+                    licenses.push(license);
+                }
+                buildpack_table["licenses"] = Item::ArrayOfTables(licenses);
+            }
+            MetadataDrift::Keywords { cargo_toml, .. } => {
+                buildpack_table["keywords"] = value(cargo_toml.iter().collect::<Array>());
+            }
+        }
+    }
+
+    fs::write(buildpack_descriptor_path, document.to_string()).map_err(SyncMetadataError::IoError)
+}
+
+fn existing_license_table(buildpack_table: &Table, license_type: &str) -> Option<Table> {
+    buildpack_table
+        .get("licenses")?
+        .as_array_of_tables()?
+        .iter()
+        .find(|license| {
+            license
+                .get("type")
+                .and_then(Item::as_str)
+                .is_some_and(|existing_type| existing_type == license_type)
+        })
+        .cloned()
+}
+
+/// Extracts the license identifiers of an SPDX license expression, i.e. `MIT OR Apache-2.0`.
+///
+/// License exceptions, i.e. `LLVM-exception` in `Apache-2.0 WITH LLVM-exception`, aren't licenses
+/// and are skipped. The deprecated `/` separator that Cargo still accepts is supported as well.
+fn spdx_license_ids(expression: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    let mut tokens = expression
+        .split(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | '/'))
+        .filter(|token| !token.is_empty());
+
+    while let Some(token) = tokens.next() {
+        match token {
+            "OR" | "AND" => {}
+            "WITH" => {
+                tokens.next();
+            }
+            id => {
+                if !ids.iter().any(|existing| existing == id) {
+                    ids.push(String::from(id));
+                }
+            }
+        }
+    }
+
+    ids
+}
+
+fn sorted_unique(values: &[String]) -> BTreeSet<&str> {
+    values.iter().map(String::as_str).collect()
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SyncMetadataError {
+    #[error("I/O error while syncing buildpack metadata: {0}")]
+    IoError(#[source] std::io::Error),
+    #[error("Couldn't parse buildpack descriptor: {0}")]
+    TomlParseError(#[source] toml_edit::TomlError),
+    #[error("Buildpack descriptor doesn't contain a [buildpack] table")]
+    MissingBuildpackTable,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use libcnb_common::toml_file::read_toml_file;
+    use libcnb_data::buildpack::BuildpackDescriptor;
+    use std::path::PathBuf;
+    use tempfile::tempdir;
+
+    const BUILDPACK_TOML: &str = indoc! {r#"
+        api = "0.10"
+
+        [buildpack]
+        id = "heroku/java"
+        version = "0.1.0"
+        # Shown on the buildpack registry.
+        description = "Outdated description"
+        keywords = ["java", "jvm"]
+
+        [[buildpack.licenses]]
+        type = "BSD-3-Clause"
+        uri = "https://example.com/bsd-3-clause"
+    "#};
+
+    fn package(
+        description: Option<&str>,
+        homepage: Option<&str>,
+        license: Option<&str>,
+        keywords: &[&str],
+    ) -> Package {
+        serde_json::from_value(serde_json::json!({
+            "name": "java",
+            "version": "0.1.0",
+            "id": "path+file:///workspace/buildpacks/java#0.1.0",
+            "description": description,
+            "homepage": homepage,
+            "license": license,
+            "keywords": keywords,
+            "dependencies": [],
+            "targets": [],
+            "features": {},
+            "manifest_path": "/workspace/buildpacks/java/Cargo.toml",
+        }))
+        .unwrap()
+    }
+
+    fn buildpack_descriptor(contents: &str) -> BuildpackDescriptor {
+        toml::from_str(contents).unwrap()
+    }
+
+    #[test]
+    fn find_drifts() {
+        let descriptor = buildpack_descriptor(BUILDPACK_TOML);
+
+        assert_eq!(
+            find_metadata_drifts(
+                descriptor.buildpack(),
+                &package(
+                    Some("Java buildpack"),
+                    Some("https://example.com"),
+                    Some("MIT OR Apache-2.0"),
+                    &["jvm", "java"],
+                )
+            ),
+            [
+                MetadataDrift::Description {
+                    buildpack_toml: Some(String::from("Outdated description")),
+                    cargo_toml: String::from("Java buildpack"),
+                },
+                MetadataDrift::Homepage {
+                    buildpack_toml: None,
+                    cargo_toml: String::from("https://example.com"),
+                },
+                MetadataDrift::Licenses {
+                    buildpack_toml: vec![String::from("BSD-3-Clause")],
+                    cargo_toml: vec![String::from("MIT"), String::from("Apache-2.0")],
+                },
+            ]
+        );
+
+        // Fields that aren't set in Cargo.toml aren't compared.
+        assert_eq!(
+            find_metadata_drifts(
+                descriptor.buildpack(),
+                &package(None, None, Some("BSD-3-Clause"), &[])
+            ),
+            []
+        );
+    }
+
+    #[test]
+    fn spdx_license_expressions() {
+        assert_eq!(spdx_license_ids("MIT"), ["MIT"]);
+        assert_eq!(spdx_license_ids("MIT/Apache-2.0"), ["MIT", "Apache-2.0"]);
+        assert_eq!(
+            spdx_license_ids("(MIT OR Apache-2.0) AND Apache-2.0 WITH LLVM-exception"),
+            ["MIT", "Apache-2.0"]
+        );
+        assert_eq!(
+            spdx_license_ids("GPL-2.0-or-later WITH Classpath-exception-2.0 OR MIT"),
+            ["GPL-2.0-or-later", "MIT"]
+        );
+    }
+
+    #[test]
+    fn drift_display() {
+        assert_eq!(
+            MetadataDrift::Description {
+                buildpack_toml: Some(String::from("Old")),
+                cargo_toml: String::from("New"),
+            }
+            .to_string(),
+            "buildpack.toml description \"Old\" differs from Cargo.toml \"New\""
+        );
+        assert_eq!(
+            MetadataDrift::Keywords {
+                buildpack_toml: Vec::new(),
+                cargo_toml: vec![String::from("java"), String::from("jvm")],
+            }
+            .to_string(),
+            "buildpack.toml keywords missing, Cargo.toml specifies \"java, jvm\""
+        );
+    }
+
+    #[test]
+    fn sync_drifts() {
+        let temp_dir = tempdir().unwrap();
+        let buildpack_descriptor_path = temp_dir.path().join("buildpack.toml");
+        fs::write(&buildpack_descriptor_path, BUILDPACK_TOML).unwrap();
+
+        let cargo_package = package(
+            Some("Java buildpack"),
+            Some("https://example.com"),
+            Some("MIT OR BSD-3-Clause"),
+            &["java"],
+        );
+        let drifts = find_metadata_drifts(
+            buildpack_descriptor(BUILDPACK_TOML).buildpack(),
+            &cargo_package,
+        );
+        sync_buildpack_metadata(&buildpack_descriptor_path, &drifts).unwrap();
+
+        let synced_descriptor =
+            read_toml_file::<BuildpackDescriptor>(PathBuf::from(&buildpack_descriptor_path))
+                .unwrap();
+        assert_eq!(
+            find_metadata_drifts(synced_descriptor.buildpack(), &cargo_package),
+            []
+        );
+
+        assert_eq!(
+            fs::read_to_string(&buildpack_descriptor_path).unwrap(),
+            indoc! {r#"
+                api = "0.10"
+
+                [buildpack]
+                id = "heroku/java"
+                version = "0.1.0"
+                # Shown on the buildpack registry.
+                description = "Java buildpack"
+                keywords = ["java"]
+                homepage = "https://example.com"
+
+                [[buildpack.licenses]]
+                type = "MIT"
+
+                [[buildpack.licenses]]
+                type = "BSD-3-Clause"
+                uri = "https://example.com/bsd-3-clause"
+            "#}
+        );
+    }
+}
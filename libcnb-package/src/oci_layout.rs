@@ -0,0 +1,809 @@
+//! Writes packaged buildpacks as [OCI image layouts](https://github.com/opencontainers/image-spec/blob/main/image-layout.md).
+//!
+//! The resulting image follows the same conventions as the buildpackages created by
+//! `pack buildpack package`: every buildpack is stored in a separate layer at
+//! `/cnb/buildpacks/<id>/<version>` and described by the `io.buildpacks.buildpack.layers` and
+//! `io.buildpacks.buildpackage.metadata` labels. The layout can then be pushed to a registry with
+//! tools such as `crane` or `skopeo`, without requiring `pack` or a Docker daemon.
+
+use libcnb_common::toml_file::{read_toml_file, TomlFileError};
+use libcnb_data::buildpack::BuildpackDescriptor;
+use libcnb_data::package_descriptor::PackageDescriptor;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const OCI_LAYOUT_FILE: &str = "oci-layout";
+const OCI_INDEX_FILE: &str = "index.json";
+const BLOBS_DIR: &str = "blobs/sha256";
+const MANIFEST_MEDIA_TYPE: &str = "application/vnd.oci.image.manifest.v1+json";
+const CONFIG_MEDIA_TYPE: &str = "application/vnd.oci.image.config.v1+json";
+const LAYER_MEDIA_TYPE: &str = "application/vnd.oci.image.layer.v1.tar";
+const BUILDPACK_LAYERS_LABEL: &str = "io.buildpacks.buildpack.layers";
+const BUILDPACKAGE_METADATA_LABEL: &str = "io.buildpacks.buildpackage.metadata";
+
+/// The modification time of all files in the layers, to make the image reproducible. Matches the
+/// timestamp the CNB lifecycle and `pack` use for reproducible images (1980-01-01T00:00:01Z).
+const NORMALIZED_MTIME: u64 = 315_532_801;
+
+/// The format of an OCI image layout.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum OciLayoutFormat {
+    /// An OCI image layout directory.
+    Directory,
+    /// A tarball containing an OCI image layout, as accepted by `crane push` or
+    /// `skopeo copy oci-archive:`.
+    Tarball,
+}
+
+/// Writes the packaged buildpack in the given directory as an OCI image layout.
+///
+/// Composite buildpacks are written with all of their dependencies, which must be local buildpack
+/// directories (i.e. libcnb.rs buildpacks packaged with [`crate::package::package_buildpack`]).
+///
+/// The image is written for the given architecture, i.e. `amd64` or `arm64`, which has to match
+/// the target the buildpack binaries were compiled for. See [`oci_architecture`] to determine it
+/// from a Rust target triple. Layers are written to disk while they are created, so that large
+/// buildpacks don't have to fit into memory.
+///
+/// # Errors
+///
+/// Returns `Err` if a buildpack or package descriptor couldn't be read, a dependency isn't a local
+/// directory, a buildpack doesn't support the architecture, the destination directory isn't empty
+/// or the layout couldn't be written.
+pub fn write_buildpack_oci_layout(
+    packaged_buildpack_dir: &Path,
+    destination: &Path,
+    format: OciLayoutFormat,
+    architecture: &str,
+) -> Result<(), WriteOciLayoutError> {
+    let mut buildpacks = Vec::new();
+    collect_buildpacks(packaged_buildpack_dir, &mut buildpacks)?;
+    validate_architecture(&buildpacks, architecture)?;
+
+    match format {
+        OciLayoutFormat::Directory => {
+            if fs::read_dir(destination).is_ok_and(|mut entries| entries.next().is_some()) {
+                return Err(WriteOciLayoutError::DestinationNotEmpty(
+                    destination.to_path_buf(),
+                ));
+            }
+
+            write_oci_layout_dir(&buildpacks, destination, architecture)
+        }
+        OciLayoutFormat::Tarball => {
+            let parent_dir = destination
+                .parent()
+                .filter(|parent_dir| !parent_dir.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            fs::create_dir_all(parent_dir)?;
+
+            // The layout is staged next to the destination, since the sizes of the layer blobs
+            // have to be known before they can be added to the tarball.
+            let staging_dir = tempfile::tempdir_in(parent_dir)?;
+            write_oci_layout_dir(&buildpacks, staging_dir.path(), architecture)?;
+            write_tarball(staging_dir.path(), destination)?;
+
+            Ok(())
+        }
+    }
+}
+
+/// Returns the OCI image architecture, i.e. `amd64`, for the given Rust target triple, i.e.
+/// `x86_64-unknown-linux-musl`.
+///
+/// Architectures without a different name in OCI images are returned as-is.
+#[must_use]
+pub fn oci_architecture(target_triple: &str) -> &str {
+    match target_triple.split('-').next().unwrap_or_default() {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        architecture => architecture,
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum WriteOciLayoutError {
+    #[error("Couldn't read buildpack descriptor: {0}")]
+    CouldNotReadBuildpackDescriptor(TomlFileError),
+    #[error("Couldn't read package descriptor: {0}")]
+    CouldNotReadPackageDescriptor(TomlFileError),
+    #[error("Dependency {0} isn't a local buildpack directory, only local dependencies can be written to an OCI image layout")]
+    UnsupportedDependency(String),
+    #[error("Buildpack {0} doesn't declare a target with the {1} architecture")]
+    UnsupportedArchitecture(String, String),
+    #[error("Destination directory {0} isn't empty")]
+    DestinationNotEmpty(PathBuf),
+    #[error("I/O error while writing OCI image layout: {0}")]
+    IoError(#[from] io::Error),
+}
+
+struct PackagedBuildpack {
+    dir: PathBuf,
+    descriptor: BuildpackDescriptor,
+}
+
+/// Checks that all component buildpacks that declare targets support the given architecture.
+/// Targets without an architecture support all architectures.
+fn validate_architecture(
+    buildpacks: &[PackagedBuildpack],
+    architecture: &str,
+) -> Result<(), WriteOciLayoutError> {
+    for buildpack in buildpacks {
+        if let BuildpackDescriptor::Component(descriptor) = &buildpack.descriptor {
+            if !descriptor.targets.is_empty()
+                && !descriptor.targets.iter().any(|target| {
+                    target
+                        .arch
+                        .as_deref()
+                        .map_or(true, |arch| arch == architecture)
+                })
+            {
+                return Err(WriteOciLayoutError::UnsupportedArchitecture(
+                    descriptor.buildpack.id.to_string(),
+                    String::from(architecture),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the OCI image layout for the given buildpacks into the given directory.
+fn write_oci_layout_dir(
+    buildpacks: &[PackagedBuildpack],
+    layout_dir: &Path,
+    architecture: &str,
+) -> Result<(), WriteOciLayoutError> {
+    fs::create_dir_all(layout_dir.join(BLOBS_DIR))?;
+
+    let mut layer_descriptors = Vec::new();
+    let mut diff_ids = Vec::new();
+    let mut buildpack_layers = Map::new();
+
+    for buildpack in buildpacks {
+        let (digest, layer_size) = write_layer_blob(buildpack, layout_dir)?;
+
+        layer_descriptors.push(json!({
+            "mediaType": LAYER_MEDIA_TYPE,
+            "digest": digest,
+            "size": layer_size,
+        }));
+        diff_ids.push(Value::from(digest.clone()));
+
+        let buildpack_info = buildpack.descriptor.buildpack();
+        let mut layer_info = buildpack_metadata(&buildpack.descriptor);
+        layer_info.insert(
+            String::from("api"),
+            Value::from(match &buildpack.descriptor {
+                BuildpackDescriptor::Component(descriptor) => descriptor.api.to_string(),
+                BuildpackDescriptor::Composite(descriptor) => descriptor.api.to_string(),
+            }),
+        );
+        layer_info.insert(String::from("layerDiffID"), Value::from(digest));
+        layer_info.remove("id");
+        layer_info.remove("version");
+
+        if let Value::Object(versions) = buildpack_layers
+            .entry(buildpack_info.id.to_string())
+            .or_insert_with(|| Value::Object(Map::new()))
+        {
+            versions.insert(
+                buildpack_info.version.to_string(),
+                Value::Object(layer_info),
+            );
+        }
+    }
+
+    let config = serde_json::to_vec(&json!({
+        "architecture": architecture,
+        "os": "linux",
+        "config": {
+            "Labels": {
+                BUILDPACK_LAYERS_LABEL: Value::Object(buildpack_layers).to_string(),
+                BUILDPACKAGE_METADATA_LABEL:
+                    Value::Object(buildpack_metadata(&buildpacks[0].descriptor)).to_string(),
+            },
+        },
+        "rootfs": {
+            "type": "layers",
+            "diff_ids": diff_ids,
+        },
+    }))
+    .map_err(io::Error::from)?;
+
+    let manifest = serde_json::to_vec(&json!({
+        "schemaVersion": 2,
+        "mediaType": MANIFEST_MEDIA_TYPE,
+        "config": {
+            "mediaType": CONFIG_MEDIA_TYPE,
+            "digest": sha256_digest(&config),
+            "size": config.len(),
+        },
+        "layers": layer_descriptors,
+    }))
+    .map_err(io::Error::from)?;
+
+    let index = serde_json::to_vec(&json!({
+        "schemaVersion": 2,
+        "manifests": [{
+            "mediaType": MANIFEST_MEDIA_TYPE,
+            "digest": sha256_digest(&manifest),
+            "size": manifest.len(),
+            "annotations": {
+                "org.opencontainers.image.ref.name":
+                    buildpacks[0].descriptor.buildpack().version.to_string(),
+            },
+        }],
+    }))
+    .map_err(io::Error::from)?;
+
+    fs::write(layout_dir.join(blob_path(&sha256_digest(&config))), config)?;
+    fs::write(
+        layout_dir.join(blob_path(&sha256_digest(&manifest))),
+        manifest,
+    )?;
+    fs::write(layout_dir.join(OCI_INDEX_FILE), index)?;
+    fs::write(
+        layout_dir.join(OCI_LAYOUT_FILE),
+        br#"{"imageLayoutVersion":"1.0.0"}"#,
+    )?;
+
+    Ok(())
+}
+
+/// Writes the files of the given OCI image layout directory into a tarball, in a deterministic
+/// order.
+fn write_tarball(layout_dir: &Path, destination: &Path) -> io::Result<()> {
+    let mut blob_paths = fs::read_dir(layout_dir.join(BLOBS_DIR))?
+        .map(|entry| entry.map(|entry| Path::new(BLOBS_DIR).join(entry.file_name())))
+        .collect::<Result<Vec<_>, _>>()?;
+    blob_paths.sort();
+
+    let mut builder = tar::Builder::new(BufWriter::new(fs::File::create(destination)?));
+    for path in [
+        PathBuf::from(OCI_LAYOUT_FILE),
+        PathBuf::from(OCI_INDEX_FILE),
+    ]
+    .into_iter()
+    .chain(blob_paths)
+    {
+        let file = fs::File::open(layout_dir.join(&path))?;
+        let mut header = normalized_header(tar::EntryType::Regular, 0o644);
+        header.set_size(file.metadata()?.len());
+        builder.append_data(&mut header, &path, file)?;
+    }
+
+    builder.into_inner()?.flush()
+}
+
+/// Collects the buildpack in the given directory and, for composite buildpacks, all of its
+/// dependencies. Buildpacks that are referenced more than once are only collected once.
+fn collect_buildpacks(
+    buildpack_dir: &Path,
+    buildpacks: &mut Vec<PackagedBuildpack>,
+) -> Result<(), WriteOciLayoutError> {
+    let descriptor = read_toml_file::<BuildpackDescriptor>(buildpack_dir.join("buildpack.toml"))
+        .map_err(WriteOciLayoutError::CouldNotReadBuildpackDescriptor)?;
+
+    if buildpacks.iter().any(|buildpack| {
+        buildpack.descriptor.buildpack().id == descriptor.buildpack().id
+            && buildpack.descriptor.buildpack().version == descriptor.buildpack().version
+    }) {
+        return Ok(());
+    }
+
+    let is_composite = matches!(descriptor, BuildpackDescriptor::Composite(_));
+
+    buildpacks.push(PackagedBuildpack {
+        dir: PathBuf::from(buildpack_dir),
+        descriptor,
+    });
+
+    if is_composite {
+        let package_descriptor =
+            read_toml_file::<PackageDescriptor>(buildpack_dir.join("package.toml"))
+                .map_err(WriteOciLayoutError::CouldNotReadPackageDescriptor)?;
+
+        for dependency in package_descriptor.dependencies {
+            let uri = dependency.uri;
+            match uri
+                .scheme()
+                .map(|scheme| scheme.as_str().to_ascii_lowercase())
+            {
+                None => collect_buildpacks(&buildpack_dir.join(uri.path().to_string()), buildpacks),
+                Some(scheme) if scheme == "file" => {
+                    collect_buildpacks(Path::new(&uri.path().to_string()), buildpacks)
+                }
+                Some(_) => Err(WriteOciLayoutError::UnsupportedDependency(uri.to_string())),
+            }?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The metadata of a buildpack as used by the buildpackage labels.
+fn buildpack_metadata(descriptor: &BuildpackDescriptor) -> Map<String, Value> {
+    let buildpack = descriptor.buildpack();
+    let mut metadata = Map::new();
+
+    metadata.insert(String::from("id"), Value::from(buildpack.id.to_string()));
+    metadata.insert(
+        String::from("version"),
+        Value::from(buildpack.version.to_string()),
+    );
+    if let Some(name) = &buildpack.name {
+        metadata.insert(String::from("name"), Value::from(name.clone()));
+    }
+    if let Some(homepage) = &buildpack.homepage {
+        metadata.insert(String::from("homepage"), Value::from(homepage.clone()));
+    }
+
+    match descriptor {
+        BuildpackDescriptor::Component(descriptor) => {
+            if !descriptor.stacks.is_empty() {
+                metadata.insert(
+                    String::from("stacks"),
+                    descriptor
+                        .stacks
+                        .iter()
+                        .map(|stack| json!({ "id": stack.id, "mixins": stack.mixins }))
+                        .collect(),
+                );
+            }
+
+            if !descriptor.targets.is_empty() {
+                metadata.insert(
+                    String::from("targets"),
+                    descriptor
+                        .targets
+                        .iter()
+                        .map(|target| {
+                            let mut target_json = Map::new();
+                            for (key, value) in [
+                                ("os", &target.os),
+                                ("arch", &target.arch),
+                                ("variant", &target.variant),
+                            ] {
+                                if let Some(value) = value {
+                                    target_json.insert(String::from(key), Value::from(value.clone()));
+                                }
+                            }
+                            if !target.distros.is_empty() {
+                                target_json.insert(
+                                    String::from("distros"),
+                                    target
+                                        .distros
+                                        .iter()
+                                        .map(|distro| {
+                                            json!({ "name": distro.name, "version": distro.version })
+                                        })
+                                        .collect(),
+                                );
+                            }
+                            Value::Object(target_json)
+                        })
+                        .collect(),
+                );
+            }
+        }
+        BuildpackDescriptor::Composite(descriptor) => {
+            metadata.insert(
+                String::from("order"),
+                descriptor
+                    .order
+                    .iter()
+                    .map(|order| {
+                        json!({
+                            "group": order
+                                .group
+                                .iter()
+                                .map(|group| json!({
+                                    "id": group.id.to_string(),
+                                    "version": group.version.to_string(),
+                                    "optional": group.optional,
+                                }))
+                                .collect::<Vec<_>>(),
+                        })
+                    })
+                    .collect(),
+            );
+        }
+    }
+
+    metadata
+}
+
+/// Writes an uncompressed layer tarball that contains the given buildpack at
+/// `/cnb/buildpacks/<id>/<version>` into the blobs of the given OCI image layout directory.
+/// Returns the digest and size of the layer.
+fn write_layer_blob(buildpack: &PackagedBuildpack, layout_dir: &Path) -> io::Result<(String, u64)> {
+    let buildpack_info = buildpack.descriptor.buildpack();
+    let id_dir = PathBuf::from("cnb/buildpacks").join(buildpack_info.id.replace('/', "_"));
+    let buildpack_root = id_dir.join(buildpack_info.version.to_string());
+
+    let mut temp_file = tempfile::NamedTempFile::new_in(layout_dir.join(BLOBS_DIR))?;
+    let mut builder = tar::Builder::new(DigestWriter::new(BufWriter::new(temp_file.as_file_mut())));
+
+    for dir in [
+        Path::new("cnb"),
+        Path::new("cnb/buildpacks"),
+        &id_dir,
+        &buildpack_root,
+    ] {
+        append_dir(&mut builder, dir)?;
+    }
+
+    append_dir_contents(&mut builder, &buildpack.dir, &buildpack_root)?;
+
+    let (digest, size) = builder.into_inner()?.finish()?;
+
+    temp_file.persist(layout_dir.join(blob_path(&digest)))?;
+
+    Ok((digest, size))
+}
+
+/// A writer that calculates the SHA-256 digest and size of the data written through it.
+struct DigestWriter<W> {
+    inner: W,
+    hasher: Sha256,
+    size: u64,
+}
+
+impl<W> DigestWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+            size: 0,
+        }
+    }
+}
+
+impl<W: Write> DigestWriter<W> {
+    /// Flushes the inner writer and returns the digest and size of the written data.
+    fn finish(mut self) -> io::Result<(String, u64)> {
+        self.inner.flush()?;
+        Ok((format!("sha256:{:x}", self.hasher.finalize()), self.size))
+    }
+}
+
+impl<W: Write> Write for DigestWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Recursively appends the contents of the given directory in a deterministic order. Symlinks
+/// are preserved, since packaged libcnb.rs buildpacks use them for their executables.
+fn append_dir_contents<W: Write>(
+    builder: &mut tar::Builder<W>,
+    dir: &Path,
+    archive_dir: &Path,
+) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(fs::DirEntry::file_name);
+
+    for entry in entries {
+        let path = entry.path();
+        let archive_path = archive_dir.join(entry.file_name());
+        let metadata = fs::symlink_metadata(&path)?;
+
+        if metadata.is_symlink() {
+            let mut header = normalized_header(tar::EntryType::Symlink, 0o777);
+            builder.append_link(&mut header, &archive_path, fs::read_link(&path)?)?;
+        } else if metadata.is_dir() {
+            append_dir(builder, &archive_path)?;
+            append_dir_contents(builder, &path, &archive_path)?;
+        } else {
+            let mut header = normalized_header(tar::EntryType::Regular, file_mode(&metadata));
+            header.set_size(metadata.len());
+            builder.append_data(&mut header, &archive_path, fs::File::open(&path)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn append_dir<W: Write>(builder: &mut tar::Builder<W>, path: &Path) -> io::Result<()> {
+    let mut header = normalized_header(tar::EntryType::Directory, 0o755);
+    builder.append_data(&mut header, path, io::empty())
+}
+
+fn normalized_header(entry_type: tar::EntryType, mode: u32) -> tar::Header {
+    let mut header = tar::Header::new_ustar();
+    header.set_entry_type(entry_type);
+    header.set_mode(mode);
+    header.set_mtime(NORMALIZED_MTIME);
+    header.set_uid(0);
+    header.set_gid(0);
+    header.set_size(0);
+    header
+}
+
+#[cfg(target_family = "unix")]
+fn file_mode(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+
+    if metadata.permissions().mode() & 0o111 == 0 {
+        0o644
+    } else {
+        0o755
+    }
+}
+
+#[cfg(not(target_family = "unix"))]
+fn file_mode(_metadata: &fs::Metadata) -> u32 {
+    0o755
+}
+
+fn sha256_digest(data: &[u8]) -> String {
+    format!("sha256:{:x}", Sha256::digest(data))
+}
+
+fn blob_path(digest: &str) -> PathBuf {
+    PathBuf::from("blobs").join(digest.replacen(':', "/", 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn write_buildpack(dir: &Path, buildpack_toml: &str) {
+        fs::create_dir_all(dir.join("bin")).unwrap();
+        fs::write(dir.join("buildpack.toml"), buildpack_toml).unwrap();
+        fs::write(dir.join("bin/build"), "#!/usr/bin/env bash\n").unwrap();
+        std::os::unix::fs::symlink("build", dir.join("bin/detect")).unwrap();
+    }
+
+    fn read_json(path: &Path) -> Value {
+        serde_json::from_slice(&fs::read(path).unwrap()).unwrap()
+    }
+
+    fn read_blob(layout_dir: &Path, digest: &Value) -> Vec<u8> {
+        fs::read(layout_dir.join(blob_path(digest.as_str().unwrap()))).unwrap()
+    }
+
+    #[test]
+    fn write_component_buildpack_layout() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let buildpack_dir = temp_dir.path().join("buildpack");
+        let layout_dir = temp_dir.path().join("layout");
+        write_buildpack(
+            &buildpack_dir,
+            "api = \"0.10\"\n\n[buildpack]\nid = \"heroku/test\"\nversion = \"1.2.3\"\n\n[[targets]]\nos = \"linux\"\narch = \"arm64\"\n",
+        );
+
+        write_buildpack_oci_layout(
+            &buildpack_dir,
+            &layout_dir,
+            OciLayoutFormat::Directory,
+            "arm64",
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_json(&layout_dir.join("oci-layout")),
+            json!({ "imageLayoutVersion": "1.0.0" })
+        );
+
+        let index = read_json(&layout_dir.join("index.json"));
+        assert_eq!(
+            index["manifests"][0]["annotations"]["org.opencontainers.image.ref.name"],
+            "1.2.3"
+        );
+
+        let manifest: Value =
+            serde_json::from_slice(&read_blob(&layout_dir, &index["manifests"][0]["digest"]))
+                .unwrap();
+        let config: Value =
+            serde_json::from_slice(&read_blob(&layout_dir, &manifest["config"]["digest"])).unwrap();
+        let layer_digest = &manifest["layers"][0]["digest"];
+
+        assert_eq!(config["architecture"], "arm64");
+        assert_eq!(config["rootfs"]["diff_ids"], json!([layer_digest]));
+
+        let buildpack_layers: Value = serde_json::from_str(
+            config["config"]["Labels"][BUILDPACK_LAYERS_LABEL]
+                .as_str()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            buildpack_layers,
+            json!({
+                "heroku/test": {
+                    "1.2.3": {
+                        "api": "0.10",
+                        "layerDiffID": layer_digest,
+                        "targets": [{ "os": "linux", "arch": "arm64" }],
+                    }
+                }
+            })
+        );
+
+        let mut archive = tar::Archive::new(io::Cursor::new(read_blob(&layout_dir, layer_digest)));
+        let entries = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let path = entry.path().unwrap().to_string_lossy().to_string();
+                let link = entry
+                    .link_name()
+                    .unwrap()
+                    .map(|link| link.to_string_lossy().to_string());
+                let mut contents = String::new();
+                entry.read_to_string(&mut contents).unwrap();
+                assert_eq!(entry.header().mtime().unwrap(), NORMALIZED_MTIME);
+                (path, link)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            entries,
+            [
+                (String::from("cnb"), None),
+                (String::from("cnb/buildpacks"), None),
+                (String::from("cnb/buildpacks/heroku_test"), None),
+                (String::from("cnb/buildpacks/heroku_test/1.2.3"), None),
+                (String::from("cnb/buildpacks/heroku_test/1.2.3/bin"), None),
+                (
+                    String::from("cnb/buildpacks/heroku_test/1.2.3/bin/build"),
+                    None
+                ),
+                (
+                    String::from("cnb/buildpacks/heroku_test/1.2.3/bin/detect"),
+                    Some(String::from("build"))
+                ),
+                (
+                    String::from("cnb/buildpacks/heroku_test/1.2.3/buildpack.toml"),
+                    None
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn write_composite_buildpack_layout_tarball() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let component_dir = temp_dir.path().join("component");
+        let composite_dir = temp_dir.path().join("composite");
+        let layout_tarball = temp_dir.path().join("composite.oci.tar");
+
+        write_buildpack(
+            &component_dir,
+            "api = \"0.10\"\n\n[buildpack]\nid = \"heroku/component\"\nversion = \"1.0.0\"\n",
+        );
+        fs::create_dir_all(&composite_dir).unwrap();
+        fs::write(
+            composite_dir.join("buildpack.toml"),
+            "api = \"0.10\"\n\n[buildpack]\nid = \"heroku/composite\"\nversion = \"2.0.0\"\n\n[[order]]\n[[order.group]]\nid = \"heroku/component\"\nversion = \"1.0.0\"\n",
+        )
+        .unwrap();
+        fs::write(
+            composite_dir.join("package.toml"),
+            format!(
+                "[buildpack]\nuri = \".\"\n\n[[dependencies]]\nuri = \"{}\"\n",
+                component_dir.display()
+            ),
+        )
+        .unwrap();
+
+        write_buildpack_oci_layout(
+            &composite_dir,
+            &layout_tarball,
+            OciLayoutFormat::Tarball,
+            "amd64",
+        )
+        .unwrap();
+
+        let mut archive = tar::Archive::new(fs::File::open(&layout_tarball).unwrap());
+        let paths = archive
+            .entries()
+            .unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            paths
+                .iter()
+                .filter(|path| path.starts_with("blobs/sha256/"))
+                .count(),
+            4
+        );
+        assert!(paths.contains(&String::from("index.json")));
+        assert!(paths.contains(&String::from("oci-layout")));
+    }
+
+    #[test]
+    fn write_layout_with_remote_dependency() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let composite_dir = temp_dir.path().join("composite");
+
+        fs::create_dir_all(&composite_dir).unwrap();
+        fs::write(
+            composite_dir.join("buildpack.toml"),
+            "api = \"0.10\"\n\n[buildpack]\nid = \"heroku/composite\"\nversion = \"2.0.0\"\n\n[[order]]\n[[order.group]]\nid = \"heroku/procfile\"\nversion = \"3.0.0\"\n",
+        )
+        .unwrap();
+        fs::write(
+            composite_dir.join("package.toml"),
+            "[buildpack]\nuri = \".\"\n\n[[dependencies]]\nuri = \"docker://docker.io/heroku/procfile-cnb:3.0.0\"\n",
+        )
+        .unwrap();
+
+        assert!(matches!(
+            write_buildpack_oci_layout(
+                &composite_dir,
+                &temp_dir.path().join("layout"),
+                OciLayoutFormat::Directory,
+                "amd64"
+            ),
+            Err(WriteOciLayoutError::UnsupportedDependency(_))
+        ));
+    }
+
+    #[test]
+    fn write_layout_into_non_empty_directory() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let buildpack_dir = temp_dir.path().join("buildpack");
+        let layout_dir = temp_dir.path().join("layout");
+        write_buildpack(
+            &buildpack_dir,
+            "api = \"0.10\"\n\n[buildpack]\nid = \"heroku/test\"\nversion = \"1.2.3\"\n",
+        );
+        fs::create_dir_all(&layout_dir).unwrap();
+        fs::write(layout_dir.join("unrelated.txt"), "").unwrap();
+
+        assert!(matches!(
+            write_buildpack_oci_layout(
+                &buildpack_dir,
+                &layout_dir,
+                OciLayoutFormat::Directory,
+                "amd64"
+            ),
+            Err(WriteOciLayoutError::DestinationNotEmpty(_))
+        ));
+        assert!(layout_dir.join("unrelated.txt").exists());
+    }
+
+    #[test]
+    fn write_layout_with_unsupported_architecture() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let buildpack_dir = temp_dir.path().join("buildpack");
+        write_buildpack(
+            &buildpack_dir,
+            "api = \"0.10\"\n\n[buildpack]\nid = \"heroku/test\"\nversion = \"1.2.3\"\n\n[[targets]]\nos = \"linux\"\narch = \"amd64\"\n\n[[targets]]\nos = \"linux\"\narch = \"arm64\"\n",
+        );
+
+        assert!(matches!(
+            write_buildpack_oci_layout(
+                &buildpack_dir,
+                &temp_dir.path().join("layout"),
+                OciLayoutFormat::Directory,
+                "s390x"
+            ),
+            Err(WriteOciLayoutError::UnsupportedArchitecture(id, arch)) if id == "heroku/test" && arch == "s390x"
+        ));
+    }
+
+    #[test]
+    fn oci_architecture_for_target_triples() {
+        assert_eq!(oci_architecture("x86_64-unknown-linux-musl"), "amd64");
+        assert_eq!(oci_architecture("aarch64-unknown-linux-gnu"), "arm64");
+        assert_eq!(oci_architecture("riscv64gc-unknown-linux-gnu"), "riscv64gc");
+    }
+}
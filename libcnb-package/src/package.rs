@@ -1,5 +1,9 @@
-use crate::build::build_buildpack_binaries;
+use crate::build::{build_buildpacks_binaries, BuildpackBinaries};
 use crate::buildpack_kind::{determine_buildpack_kind, BuildpackKind};
+use crate::cargo::{
+    additional_binaries_dir, cargo_build_configuration, AdditionalBinariesDirError,
+    CargoBuildConfigurationError,
+};
 use crate::package_descriptor::{normalize_package_descriptor, NormalizePackageDescriptorError};
 use crate::{assemble_buildpack_directory, CargoProfile};
 use cargo_metadata::MetadataCommand;
@@ -9,6 +13,7 @@ use libcnb_data::package_descriptor::PackageDescriptor;
 use std::collections::BTreeMap;
 use std::ffi::OsString;
 use std::fs;
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 
 /// Packages either a libcnb.rs or a composite buildpack.
@@ -53,6 +58,8 @@ pub enum PackageBuildpackError {
 
 /// Packages a libcnb.rs buildpack after (cross-) compiling.
 ///
+/// See [`compile_libcnb_buildpacks`] and [`assemble_libcnb_buildpack`] for details.
+///
 /// # Errors
 ///
 /// Returns `Err` if compilation or packaging failed.
@@ -63,24 +70,117 @@ fn package_libcnb_buildpack(
     cargo_build_env: &[(OsString, OsString)],
     destination: &Path,
 ) -> Result<(), PackageLibcnbBuildpackError> {
-    let cargo_metadata = MetadataCommand::new()
-        .manifest_path(buildpack_directory.join("Cargo.toml"))
-        .exec()
-        .map_err(PackageLibcnbBuildpackError::CargoMetadataError)?;
-
-    let buildpack_binaries = build_buildpack_binaries(
-        buildpack_directory,
-        &cargo_metadata,
+    compile_libcnb_buildpacks(
+        &[buildpack_directory],
+        cargo_profile,
+        target_triple,
+        cargo_build_env,
+        NonZeroUsize::MIN,
+    )?
+    .iter()
+    .try_for_each(|compiled_buildpack| {
+        assemble_libcnb_buildpack(buildpack_directory, compiled_buildpack, destination)
+    })
+}
+
+/// A (cross-) compiled libcnb.rs buildpack, see [`compile_libcnb_buildpacks`].
+#[derive(Debug)]
+pub struct CompiledLibcnbBuildpack {
+    buildpack_binaries: BuildpackBinaries,
+    additional_binaries_dir: PathBuf,
+}
+
+/// (Cross-) compiles the given libcnb.rs buildpacks.
+///
+/// Every buildpack is compiled with its own Cargo invocation, running at most `jobs` Cargo
+/// invocations in parallel. Buildpacks that share a Cargo target directory are compiled one after
+/// another.
+///
+/// The compiled buildpacks are returned in the order of the given buildpack directories and can
+/// be assembled into buildpack directories with [`assemble_libcnb_buildpack`].
+///
+/// # Errors
+///
+/// Returns `Err` if the Cargo configuration of a buildpack couldn't be read or compilation failed.
+pub fn compile_libcnb_buildpacks(
+    buildpack_directories: &[&Path],
+    cargo_profile: CargoProfile,
+    target_triple: &str,
+    cargo_build_env: &[(OsString, OsString)],
+    jobs: NonZeroUsize,
+) -> Result<Vec<CompiledLibcnbBuildpack>, PackageLibcnbBuildpackError> {
+    let mut cargo_metadata = Vec::new();
+    let mut cargo_build_configurations = Vec::new();
+    let mut additional_binaries_dirs = Vec::new();
+
+    for buildpack_directory in buildpack_directories {
+        let buildpack_cargo_metadata = MetadataCommand::new()
+            .manifest_path(buildpack_directory.join("Cargo.toml"))
+            .exec()
+            .map_err(PackageLibcnbBuildpackError::CargoMetadataError)?;
+
+        additional_binaries_dirs.push(
+            additional_binaries_dir(&buildpack_cargo_metadata)
+                .map_err(PackageLibcnbBuildpackError::AdditionalBinariesDirError)?,
+        );
+
+        cargo_build_configurations.push(
+            cargo_build_configuration(&buildpack_cargo_metadata)
+                .map_err(PackageLibcnbBuildpackError::CargoBuildConfigurationError)?,
+        );
+
+        cargo_metadata.push(buildpack_cargo_metadata);
+    }
+
+    let buildpacks = cargo_metadata
+        .iter()
+        .zip(&cargo_build_configurations)
+        .collect::<Vec<_>>();
+
+    let buildpack_binaries = build_buildpacks_binaries(
+        &buildpacks,
         cargo_profile,
         cargo_build_env,
         target_triple,
+        jobs,
     )
     .map_err(PackageLibcnbBuildpackError::BuildBinariesError)?;
 
+    Ok(buildpack_binaries
+        .into_iter()
+        .zip(additional_binaries_dirs)
+        .map(
+            |(buildpack_binaries, additional_binaries_dir)| CompiledLibcnbBuildpack {
+                buildpack_binaries,
+                additional_binaries_dir,
+            },
+        )
+        .collect())
+}
+
+/// Assembles the buildpack directory of a libcnb.rs buildpack compiled with
+/// [`compile_libcnb_buildpacks`].
+///
+/// Assembling doesn't invoke Cargo, so multiple buildpacks can be assembled in parallel.
+///
+/// # Errors
+///
+/// Returns `Err` if packaging failed.
+pub fn assemble_libcnb_buildpack(
+    buildpack_directory: &Path,
+    compiled_buildpack: &CompiledLibcnbBuildpack,
+    destination: &Path,
+) -> Result<(), PackageLibcnbBuildpackError> {
+    let CompiledLibcnbBuildpack {
+        buildpack_binaries,
+        additional_binaries_dir,
+    } = compiled_buildpack;
+
     assemble_buildpack_directory(
         destination,
         buildpack_directory.join("buildpack.toml"),
-        &buildpack_binaries,
+        buildpack_binaries,
+        additional_binaries_dir,
     )
     .map_err(PackageLibcnbBuildpackError::AssembleBuildpackDirectory)?;
 
@@ -101,6 +201,10 @@ pub enum PackageLibcnbBuildpackError {
     BuildBinariesError(crate::build::BuildBinariesError),
     #[error("Obtaining Cargo metadata failed: {0}")]
     CargoMetadataError(cargo_metadata::Error),
+    #[error("Determining additional binaries directory failed: {0}")]
+    AdditionalBinariesDirError(AdditionalBinariesDirError),
+    #[error("Reading the Cargo build configuration failed: {0}")]
+    CargoBuildConfigurationError(CargoBuildConfigurationError),
 }
 
 /// Packages a composite buildpack.
@@ -110,12 +214,16 @@ pub enum PackageLibcnbBuildpackError {
 ///
 /// In addition, references to libcnb.rs buildpacks in the form of `libcnb:` URIs are resolved and
 /// local paths are absolutized so the `package.toml` stays correct after being moved to a
-/// different location.
+/// different location. The `[platform]` table is kept as-is.
+///
+/// All dependency URIs are verified to use a form supported by `pack`. For `docker://` and
+/// `urn:cnb:registry:` dependencies, the image tag and digest or buildpack id and version are
+/// validated as well.
 ///
 /// # Errors
 ///
-/// Returns `Err` if a `libcnb:` URI refers to a buildpack not in `buildpack_paths` or packaging
-/// otherwise failed (i.e. I/O errors).
+/// Returns `Err` if a `libcnb:` URI refers to a buildpack not in `buildpack_paths`, a dependency
+/// URI is invalid or packaging otherwise failed (i.e. I/O errors).
 pub fn package_composite_buildpack(
     buildpack_directory: &Path,
     destination: &Path,
@@ -17,6 +17,14 @@ pub(crate) fn normalize_package_descriptor(
             absolutize_dependency_paths(&package_descriptor, descriptor_path)
                 .map_err(NormalizePackageDescriptorError::PackageDescriptorDependencyError)
         })
+        .and_then(|package_descriptor| {
+            package_descriptor
+                .dependencies
+                .iter()
+                .try_for_each(validate_dependency_uri)
+                .map_err(NormalizePackageDescriptorError::InvalidDependencyUri)
+                .map(|()| package_descriptor)
+        })
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -25,6 +33,8 @@ pub enum NormalizePackageDescriptorError {
     ReplaceLibcnbUriError(ReplaceLibcnbUriError),
     #[error(transparent)]
     PackageDescriptorDependencyError(PackageDescriptorDependencyError),
+    #[error(transparent)]
+    InvalidDependencyUri(InvalidDependencyUriError),
 }
 
 fn replace_libcnb_uris(
@@ -105,6 +115,134 @@ fn absolutize_dependency_paths(
         })
 }
 
+/// Verifies that a dependency uses a URI form supported by `pack` and, for published buildpacks,
+/// that the image reference or registry reference is well-formed. Catching these here gives
+/// clearer errors than `pack buildpack package` does later on.
+///
+/// Only the syntax is checked, libcnb-package doesn't contact any registry. In particular, it
+/// doesn't verify that a `docker://` image exists, that its tag refers to the given
+/// `@sha256:` digest or that it supports the `[platform]` of the package descriptor.
+fn validate_dependency_uri(
+    dependency: &PackageDescriptorDependency,
+) -> Result<(), InvalidDependencyUriError> {
+    let uri = dependency.uri.to_string();
+
+    match dependency
+        .uri
+        .scheme()
+        .map(uriparse::scheme::Scheme::as_str)
+    {
+        None | Some("file" | "http" | "https") => Ok(()),
+        Some("docker") => validate_docker_image_reference(&uri),
+        Some("urn") => validate_registry_reference(&uri),
+        Some(scheme) => Err(InvalidDependencyUriError::UnsupportedScheme {
+            uri: uri.clone(),
+            scheme: String::from(scheme),
+        }),
+    }
+}
+
+fn validate_docker_image_reference(uri: &str) -> Result<(), InvalidDependencyUriError> {
+    let image_reference = uri.strip_prefix("docker://").unwrap_or_default();
+
+    let (name, digest) = match image_reference.split_once('@') {
+        Some((name, digest)) => (name, Some(digest)),
+        None => (image_reference, None),
+    };
+
+    // A colon after the last slash separates the tag, other colons belong to a registry port.
+    let (repository, tag) = match name.rsplit_once(':') {
+        Some((repository, tag)) if !tag.contains('/') => (repository, Some(tag)),
+        _ => (name, None),
+    };
+
+    if repository.is_empty() || repository.split('/').any(str::is_empty) {
+        return Err(InvalidDependencyUriError::InvalidDockerImageReference(
+            String::from(uri),
+        ));
+    }
+
+    if let Some(tag) = tag {
+        let is_valid_tag = tag.len() <= 128
+            && tag.chars().enumerate().all(|(index, c)| {
+                c.is_ascii_alphanumeric() || c == '_' || (index > 0 && (c == '.' || c == '-'))
+            });
+
+        if !is_valid_tag {
+            return Err(InvalidDependencyUriError::InvalidDockerImageTag {
+                uri: String::from(uri),
+                tag: String::from(tag),
+            });
+        }
+    }
+
+    if let Some(digest) = digest {
+        let is_valid_digest = digest.strip_prefix("sha256:").is_some_and(|hex| {
+            hex.len() == 64
+                && hex
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+        });
+
+        if !is_valid_digest {
+            return Err(InvalidDependencyUriError::InvalidDockerImageDigest {
+                uri: String::from(uri),
+                digest: String::from(digest),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_registry_reference(uri: &str) -> Result<(), InvalidDependencyUriError> {
+    let registry_reference = uri.strip_prefix("urn:cnb:registry:").ok_or_else(|| {
+        InvalidDependencyUriError::UnsupportedScheme {
+            uri: String::from(uri),
+            scheme: String::from("urn"),
+        }
+    })?;
+
+    let (id, version) = match registry_reference.split_once('@') {
+        Some((id, version)) => (id, Some(version)),
+        None => (registry_reference, None),
+    };
+
+    id.parse::<BuildpackId>().map_err(|error| {
+        InvalidDependencyUriError::InvalidRegistryBuildpackId {
+            uri: String::from(uri),
+            error,
+        }
+    })?;
+
+    if version.is_some_and(str::is_empty) {
+        return Err(InvalidDependencyUriError::MissingRegistryBuildpackVersion(
+            String::from(uri),
+        ));
+    }
+
+    Ok(())
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum InvalidDependencyUriError {
+    #[error("Dependency {uri} uses the unsupported scheme {scheme}. Supported are local paths, file://, http://, https://, docker:// and urn:cnb:registry: URIs.")]
+    UnsupportedScheme { uri: String, scheme: String },
+    #[error("Dependency {0} is not a valid Docker image reference")]
+    InvalidDockerImageReference(String),
+    #[error("Dependency {uri} has an invalid image tag: {tag}")]
+    InvalidDockerImageTag { uri: String, tag: String },
+    #[error("Dependency {uri} has an invalid image digest: {digest}. Digests must be in the form sha256:<64 lowercase hex characters>.")]
+    InvalidDockerImageDigest { uri: String, digest: String },
+    #[error("Dependency {uri} references an invalid buildpack id: {error}")]
+    InvalidRegistryBuildpackId {
+        uri: String,
+        error: BuildpackIdError,
+    },
+    #[error("Dependency {0} has an empty buildpack version after @")]
+    MissingRegistryBuildpackVersion(String),
+}
+
 pub(crate) fn buildpack_id_from_libcnb_dependency(
     dependency: &PackageDescriptorDependency,
 ) -> Result<Option<BuildpackId>, BuildpackIdError> {
@@ -116,3 +254,83 @@ pub(crate) fn buildpack_id_from_libcnb_dependency(
         .map(|uri| uri.path().to_string().parse())
         .transpose()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(uri: &str) -> Result<(), InvalidDependencyUriError> {
+        validate_dependency_uri(&PackageDescriptorDependency::try_from(uri).unwrap())
+    }
+
+    #[test]
+    fn validate_supported_dependency_uris() {
+        for uri in [
+            "/absolute/path",
+            "relative/path.cnb",
+            "file:///absolute/path",
+            "https://example.com/buildpack.tgz",
+            "docker://docker.io/heroku/procfile-cnb:3.0.0",
+            "docker://localhost:5000/heroku/procfile-cnb",
+            "docker://docker.io/heroku/procfile-cnb:3.0.0@sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824",
+            "urn:cnb:registry:heroku/procfile",
+            "urn:cnb:registry:heroku/procfile@3.0.0",
+        ] {
+            assert!(validate(uri).is_ok(), "{uri} should be valid");
+        }
+    }
+
+    #[test]
+    fn validate_invalid_dependency_uris() {
+        assert!(matches!(
+            validate("ftp://example.com/buildpack.tgz"),
+            Err(InvalidDependencyUriError::UnsupportedScheme { scheme, .. }) if scheme == "ftp"
+        ));
+        assert!(matches!(
+            validate("urn:isbn:0451450523"),
+            Err(InvalidDependencyUriError::UnsupportedScheme { scheme, .. }) if scheme == "urn"
+        ));
+        assert!(matches!(
+            validate("docker://docker.io//procfile-cnb"),
+            Err(InvalidDependencyUriError::InvalidDockerImageReference(_))
+        ));
+        assert!(matches!(
+            validate("docker://docker.io/heroku/procfile-cnb:-3.0.0"),
+            Err(InvalidDependencyUriError::InvalidDockerImageTag { tag, .. }) if tag == "-3.0.0"
+        ));
+        assert!(matches!(
+            validate("docker://docker.io/heroku/procfile-cnb@sha256:abc"),
+            Err(InvalidDependencyUriError::InvalidDockerImageDigest { digest, .. }) if digest == "sha256:abc"
+        ));
+        assert!(matches!(
+            validate("urn:cnb:registry:heroku/procfile@"),
+            Err(InvalidDependencyUriError::MissingRegistryBuildpackVersion(
+                _
+            ))
+        ));
+        assert!(matches!(
+            validate("urn:cnb:registry:config@1.0.0"),
+            Err(InvalidDependencyUriError::InvalidRegistryBuildpackId { .. })
+        ));
+    }
+
+    #[test]
+    fn normalize_keeps_platform() {
+        let package_descriptor = toml::from_str::<PackageDescriptor>(
+            "[buildpack]\nuri = \".\"\n\n[[dependencies]]\nuri = \"urn:cnb:registry:heroku/procfile@3.0.0\"\n\n[platform]\nos = \"windows\"\n",
+        )
+        .unwrap();
+
+        let normalized_package_descriptor = normalize_package_descriptor(
+            &package_descriptor,
+            Path::new("/buildpacks/composite/package.toml"),
+            &BTreeMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            normalized_package_descriptor.platform.os,
+            libcnb_data::package_descriptor::PlatformOs::Windows
+        );
+    }
+}
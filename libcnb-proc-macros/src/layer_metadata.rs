@@ -0,0 +1,153 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Member};
+
+/// Implements the `LayerMetadata` derive macro, see `libcnb::layer::LayerMetadata`.
+pub(crate) fn derive(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let Data::Struct(data_struct) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "LayerMetadata can only be derived for structs",
+        ));
+    };
+
+    let mut compared_fields = Vec::new();
+    let mut invalidate_on_change_fields = Vec::new();
+
+    for (index, field) in data_struct.fields.iter().enumerate() {
+        let member = field
+            .ident
+            .clone()
+            .map_or_else(|| Member::Unnamed(syn::Index::from(index)), Member::Named);
+
+        let mut invalidate_on_change = false;
+        let mut ignore = false;
+
+        for attr in &field.attrs {
+            if !attr.path().is_ident("layer_metadata") {
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("invalidate_on_change") {
+                    invalidate_on_change = true;
+                    Ok(())
+                } else if meta.path.is_ident("ignore") {
+                    ignore = true;
+                    Ok(())
+                } else {
+                    Err(meta.error(
+                        "Unsupported layer_metadata attribute, expected `invalidate_on_change` or `ignore`",
+                    ))
+                }
+            })?;
+        }
+
+        if invalidate_on_change && ignore {
+            return Err(syn::Error::new_spanned(
+                field,
+                "A field can't be both `invalidate_on_change` and `ignore`",
+            ));
+        }
+
+        if invalidate_on_change {
+            invalidate_on_change_fields.push(member);
+        } else if !ignore {
+            compared_fields.push(member);
+        }
+    }
+
+    // If any fields are marked, only changes to these fields invalidate the layer.
+    if !invalidate_on_change_fields.is_empty() {
+        compared_fields = invalidate_on_change_fields;
+    }
+
+    let unchanged = if compared_fields.is_empty() {
+        quote! { true }
+    } else {
+        quote! { #(self.#compared_fields == other.#compared_fields)&&* }
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::libcnb::layer::LayerMetadata for #ident #type_generics #where_clause {
+            fn eq_or_invalidate(&self, other: &Self) -> ::libcnb::layer::RestoredLayerAction {
+                if #unchanged {
+                    ::libcnb::layer::RestoredLayerAction::KeepLayer
+                } else {
+                    ::libcnb::layer::RestoredLayerAction::DeleteLayer
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::derive;
+    use syn::parse_quote;
+
+    fn derive_error(input: &syn::DeriveInput) -> String {
+        derive(input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn derive_compares_fields() {
+        let tokens = derive(&parse_quote! {
+            struct Metadata {
+                #[layer_metadata(invalidate_on_change)]
+                version: String,
+                #[layer_metadata(ignore)]
+                installed_at: String,
+                arch: String,
+            }
+        })
+        .unwrap()
+        .to_string();
+
+        assert!(tokens.contains("self . version == other . version"));
+        assert!(!tokens.contains("installed_at"));
+        assert!(!tokens.contains("arch"));
+
+        let tokens = derive(&parse_quote! {
+            struct Metadata(String, #[layer_metadata(ignore)] String);
+        })
+        .unwrap()
+        .to_string();
+
+        assert!(tokens.contains("self . 0 == other . 0"));
+        assert!(!tokens.contains("self . 1"));
+    }
+
+    #[test]
+    fn derive_errors() {
+        assert_eq!(
+            derive_error(&parse_quote! {
+                enum Metadata { A, B }
+            }),
+            "LayerMetadata can only be derived for structs"
+        );
+
+        assert_eq!(
+            derive_error(&parse_quote! {
+                struct Metadata {
+                    #[layer_metadata(invalidate_on_change, ignore)]
+                    version: String,
+                }
+            }),
+            "A field can't be both `invalidate_on_change` and `ignore`"
+        );
+
+        assert_eq!(
+            derive_error(&parse_quote! {
+                struct Metadata {
+                    #[layer_metadata(skip)]
+                    version: String,
+                }
+            }),
+            "Unsupported layer_metadata attribute, expected `invalidate_on_change` or `ignore`"
+        );
+    }
+}
@@ -1,8 +1,10 @@
 #![doc = include_str!("../README.md")]
 
+mod layer_metadata;
+
 use proc_macro::TokenStream;
 use quote::quote;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use syn::parse::{Parse, ParseStream};
 use syn::parse_macro_input;
 use syn::Token;
@@ -74,6 +76,16 @@ impl Parse for VerifyRegexInput {
     }
 }
 
+/// Derives `libcnb::layer::LayerMetadata`. See the documentation of the trait for details.
+#[proc_macro_derive(LayerMetadata, attributes(layer_metadata))]
+pub fn derive_layer_metadata(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+
+    layer_metadata::derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
 #[proc_macro]
 pub fn verify_bin_target_exists(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as VerifyBinTargetExistsInput);
@@ -139,3 +151,58 @@ impl Parse for VerifyBinTargetExistsInput {
         })
     }
 }
+
+/// Expands to the directory, relative to the packaged buildpack, that `libcnb-package` copies
+/// additional binary targets to, as a string literal.
+///
+/// The directory is read from the `additional-binaries-dir` key in the `[package.metadata.libcnb]`
+/// table of the crate's `Cargo.toml` and defaults to `.libcnb-cargo/additional-bin`. This must be
+/// kept in sync with `libcnb_package::cargo::additional_binaries_dir`.
+#[proc_macro]
+pub fn additional_binaries_dir(_input: TokenStream) -> TokenStream {
+    let cargo_metadata = std::env::var("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .ok()
+        .map(|cargo_manifest_dir| {
+            cargo_metadata::MetadataCommand::new()
+                .manifest_path(cargo_manifest_dir.join("Cargo.toml"))
+                .exec()
+        })
+        .transpose();
+
+    let token_stream = if let Ok(Some(cargo_metadata)) = cargo_metadata {
+        if let Some(root_package) = cargo_metadata.root_package() {
+            let value = &root_package.metadata["libcnb"]["additional-binaries-dir"];
+
+            if value.is_null() {
+                quote! { ".libcnb-cargo/additional-bin" }
+            } else if let Some(dir) = value
+                .as_str()
+                .filter(|dir| is_valid_additional_binaries_dir(Path::new(dir)))
+            {
+                quote! { #dir }
+            } else {
+                quote! {
+                    compile_error!("The additional-binaries-dir in [package.metadata.libcnb] must be a relative path within the buildpack directory!")
+                }
+            }
+        } else {
+            quote! {
+                compile_error!("Couldn't read root package for this crate!")
+            }
+        }
+    } else {
+        quote! {
+            compile_error!("Couldn't read Cargo metadata!")
+        }
+    };
+
+    token_stream.into()
+}
+
+fn is_valid_additional_binaries_dir(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
@@ -0,0 +1,88 @@
+use crate::TestContext;
+use std::fmt::{Display, Formatter};
+
+/// A reusable assertion about the result of an integration test build.
+///
+/// Implement this trait to share assertions between test suites, for example in a separate crate.
+/// Assertions have access to everything in [`TestContext`], such as the `pack` output
+/// ([`pack_stdout`](TestContext::pack_stdout), [`pack_stderr`](TestContext::pack_stderr)), the
+/// image labels ([`image_labels`](TestContext::image_labels)) and the metadata of the built image
+/// ([`build_metadata`](TestContext::build_metadata),
+/// [`lifecycle_metadata`](TestContext::lifecycle_metadata)).
+///
+/// Use [`TestContext::assert_that`] to check an assertion. Closures with a matching signature
+/// implement this trait as well, which is useful for one-off assertions.
+///
+/// # Example
+/// ```no_run
+/// use libcnb_test::{Assertion, AssertionFailure, BuildConfig, TestContext, TestRunner};
+///
+/// struct HasProcessType(&'static str);
+///
+/// impl Assertion for HasProcessType {
+///     fn check(&self, context: &TestContext) -> Result<(), AssertionFailure> {
+///         let build_metadata = context.build_metadata();
+///
+///         if build_metadata
+///             .processes
+///             .iter()
+///             .any(|process| process.r#type == self.0)
+///         {
+///             Ok(())
+///         } else {
+///             Err(AssertionFailure::new(format!(
+///                 "Expected process type '{}' in image, found: {:?}",
+///                 self.0, build_metadata.processes
+///             )))
+///         }
+///     }
+/// }
+///
+/// TestRunner::default().build(
+///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+///     |context| {
+///         context.assert_that(&HasProcessType("web"));
+///     },
+/// );
+/// ```
+pub trait Assertion {
+    /// Checks the assertion against the given test context.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AssertionFailure`] describing the mismatch if the assertion doesn't hold.
+    fn check(&self, context: &TestContext) -> Result<(), AssertionFailure>;
+}
+
+impl<F: Fn(&TestContext) -> Result<(), AssertionFailure>> Assertion for F {
+    fn check(&self, context: &TestContext) -> Result<(), AssertionFailure> {
+        self(context)
+    }
+}
+
+/// Describes why an [`Assertion`] didn't hold.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AssertionFailure {
+    message: String,
+}
+
+impl AssertionFailure {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for AssertionFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for AssertionFailure {}
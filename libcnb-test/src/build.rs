@@ -1,5 +1,10 @@
-use libcnb_common::toml_file::{read_toml_file, TomlFileError};
+use crate::docker::{DockerImageInspectCommand, DockerPullCommand};
+use crate::util;
+use libcnb_common::toml_file::{read_toml_file, write_toml_file, TomlFileError};
 use libcnb_data::buildpack::{BuildpackDescriptor, BuildpackId};
+use libcnb_data::package_descriptor::{
+    PackageDescriptor, PackageDescriptorDependency, PackageDescriptorDependencyError,
+};
 use libcnb_package::buildpack_dependency_graph::{
     build_libcnb_buildpacks_dependency_graph, BuildBuildpackDependencyGraphError,
 };
@@ -7,6 +12,7 @@ use libcnb_package::cross_compile::{cross_compile_assistance, CrossCompileAssist
 use libcnb_package::dependency_graph::{get_dependencies, GetDependenciesError};
 use libcnb_package::output::create_packaged_buildpack_dir_resolver;
 use libcnb_package::{find_cargo_workspace_root_dir, CargoProfile, FindCargoWorkspaceRootError};
+use serde::Deserialize;
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::{fs, io};
@@ -17,6 +23,7 @@ pub(crate) fn package_crate_buildpack(
     target_triple: impl AsRef<str>,
     cargo_manifest_dir: &Path,
     target_buildpack_dir: &Path,
+    component_overrides: &[BuildpackId],
 ) -> Result<PathBuf, PackageBuildpackError> {
     let buildpack_toml = cargo_manifest_dir.join("buildpack.toml");
 
@@ -35,6 +42,7 @@ pub(crate) fn package_crate_buildpack(
         target_triple,
         cargo_manifest_dir,
         target_buildpack_dir,
+        component_overrides,
     )
 }
 
@@ -44,6 +52,7 @@ pub(crate) fn package_buildpack(
     target_triple: impl AsRef<str>,
     cargo_manifest_dir: &Path,
     target_buildpack_dir: &Path,
+    component_overrides: &[BuildpackId],
 ) -> Result<PathBuf, PackageBuildpackError> {
     let cargo_build_env = match cross_compile_assistance(target_triple.as_ref()) {
         CrossCompileAssistance::HelpText(help_text) => {
@@ -67,14 +76,23 @@ pub(crate) fn package_buildpack(
     let buildpack_dependency_graph = build_libcnb_buildpacks_dependency_graph(&workspace_root_path)
         .map_err(PackageBuildpackError::BuildBuildpackDependencyGraph)?;
 
-    let root_node = buildpack_dependency_graph
-        .node_weights()
-        .find(|node| &node.buildpack_id == buildpack_id)
-        .ok_or_else(|| {
-            PackageBuildpackError::BuildpackIdNotFound(buildpack_id.clone(), workspace_root_path)
-        })?;
+    // Component overrides are packaged alongside the requested buildpack.
+    let root_nodes = std::iter::once(buildpack_id)
+        .chain(component_overrides)
+        .map(|root_buildpack_id| {
+            buildpack_dependency_graph
+                .node_weights()
+                .find(|node| &node.buildpack_id == root_buildpack_id)
+                .ok_or_else(|| {
+                    PackageBuildpackError::BuildpackIdNotFound(
+                        root_buildpack_id.clone(),
+                        workspace_root_path.clone(),
+                    )
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
-    let build_order = get_dependencies(&buildpack_dependency_graph, &[root_node])
+    let build_order = get_dependencies(&buildpack_dependency_graph, &root_nodes)
         .map_err(PackageBuildpackError::GetDependencies)?;
 
     let mut packaged_buildpack_dirs = BTreeMap::new();
@@ -98,9 +116,143 @@ pub(crate) fn package_buildpack(
         packaged_buildpack_dirs.insert(node.buildpack_id.clone(), buildpack_destination_dir);
     }
 
+    if !component_overrides.is_empty() {
+        override_composite_components(
+            &buildpack_dir_resolver(buildpack_id),
+            &packaged_buildpack_dirs
+                .into_iter()
+                .filter(|(id, _)| component_overrides.contains(id))
+                .collect(),
+            resolve_published_buildpack_id,
+        )?;
+    }
+
     Ok(buildpack_dir_resolver(buildpack_id))
 }
 
+/// Replaces dependencies of a packaged composite buildpack with the given packaged buildpacks.
+///
+/// Dependencies are matched by the buildpack ID returned by `resolve_buildpack_id`. The versions
+/// in the order groups of the composite's `buildpack.toml` are updated to match the versions of
+/// the replacements, so that `pack` can find them.
+fn override_composite_components(
+    packaged_composite_dir: &Path,
+    overrides: &BTreeMap<BuildpackId, PathBuf>,
+    resolve_buildpack_id: impl Fn(&str) -> Result<Option<BuildpackId>, String>,
+) -> Result<(), PackageBuildpackError> {
+    let package_descriptor_path = packaged_composite_dir.join("package.toml");
+    if !package_descriptor_path.exists() {
+        return Err(PackageBuildpackError::NotACompositeBuildpack(
+            packaged_composite_dir.to_path_buf(),
+        ));
+    }
+
+    let mut package_descriptor: PackageDescriptor = read_toml_file(&package_descriptor_path)
+        .map_err(PackageBuildpackError::CannotReadPackageDescriptor)?;
+
+    let mut overridden_versions = BTreeMap::new();
+    for dependency in &mut package_descriptor.dependencies {
+        let uri = dependency.uri.to_string();
+        let Some(buildpack_id) = resolve_buildpack_id(&uri)
+            .map_err(|error| PackageBuildpackError::CannotResolvePublishedBuildpack(uri, error))?
+        else {
+            continue;
+        };
+
+        if let Some(override_dir) = overrides.get(&buildpack_id) {
+            let override_descriptor: BuildpackDescriptor =
+                read_toml_file(override_dir.join("buildpack.toml"))
+                    .map_err(PackageBuildpackError::CannotReadBuildpackDescriptor)?;
+
+            *dependency = PackageDescriptorDependency::try_from(override_dir.clone())
+                .map_err(PackageBuildpackError::InvalidPackageDescriptorDependency)?;
+
+            overridden_versions.insert(
+                buildpack_id,
+                override_descriptor.buildpack().version.to_string(),
+            );
+        }
+    }
+
+    if let Some(unused_override) = overrides
+        .keys()
+        .find(|id| !overridden_versions.contains_key(*id))
+    {
+        return Err(PackageBuildpackError::UnusedComponentOverride(
+            unused_override.clone(),
+        ));
+    }
+
+    write_toml_file(&package_descriptor, &package_descriptor_path)
+        .map_err(PackageBuildpackError::CannotWriteCompositeBuildpack)?;
+
+    let buildpack_descriptor_path = packaged_composite_dir.join("buildpack.toml");
+    let mut buildpack_descriptor: toml::Table = read_toml_file(&buildpack_descriptor_path)
+        .map_err(PackageBuildpackError::CannotReadBuildpackDescriptor)?;
+
+    buildpack_descriptor
+        .get_mut("order")
+        .and_then(toml::Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(|order| order.get_mut("group").and_then(toml::Value::as_array_mut))
+        .flatten()
+        .filter_map(toml::Value::as_table_mut)
+        .for_each(|group_entry| {
+            let version = group_entry
+                .get("id")
+                .and_then(toml::Value::as_str)
+                .and_then(|id| id.parse::<BuildpackId>().ok())
+                .and_then(|id| overridden_versions.get(&id));
+
+            if let Some(version) = version {
+                group_entry.insert(String::from("version"), toml::Value::from(version.clone()));
+            }
+        });
+
+    write_toml_file(&buildpack_descriptor, &buildpack_descriptor_path)
+        .map_err(PackageBuildpackError::CannotWriteCompositeBuildpack)
+}
+
+/// Resolves the buildpack ID of a published buildpack referenced in `package.toml`.
+///
+/// Returns `None` for other kinds of references, such as local paths.
+fn resolve_published_buildpack_id(uri: &str) -> Result<Option<BuildpackId>, String> {
+    if let Some(registry_reference) = uri.strip_prefix("urn:cnb:registry:") {
+        let id = registry_reference
+            .split_once('@')
+            .map_or(registry_reference, |(id, _)| id);
+
+        return id.parse().map(Some).map_err(|error| format!("{error}"));
+    }
+
+    if let Some(image_name) = uri.strip_prefix("docker://") {
+        util::run_command(DockerPullCommand::new(image_name)).map_err(|error| error.to_string())?;
+
+        let mut inspect_command = DockerImageInspectCommand::new(image_name);
+        inspect_command.format(format!(
+            "{{{{ index .Config.Labels \"{BUILDPACKAGE_METADATA_LABEL}\" }}}}"
+        ));
+
+        let output = util::run_command(inspect_command).map_err(|error| error.to_string())?;
+
+        return serde_json::from_str::<BuildpackageMetadata>(&output.stdout)
+            .map(|metadata| Some(metadata.id))
+            .map_err(|error| {
+                format!("Couldn't read label {BUILDPACKAGE_METADATA_LABEL}: {error}")
+            });
+    }
+
+    Ok(None)
+}
+
+const BUILDPACKAGE_METADATA_LABEL: &str = "io.buildpacks.buildpackage.metadata";
+
+#[derive(Deserialize)]
+struct BuildpackageMetadata {
+    id: BuildpackId,
+}
+
 #[derive(thiserror::Error, Debug)]
 pub(crate) enum PackageBuildpackError {
     #[error("Couldn't find a buildpack.toml file at {0}")]
@@ -121,4 +273,155 @@ pub(crate) enum PackageBuildpackError {
     GetDependencies(GetDependenciesError<BuildpackId>),
     #[error(transparent)]
     PackageBuildpack(libcnb_package::package::PackageBuildpackError),
+    #[error("Component overrides require a composite buildpack, but {0} has no package.toml")]
+    NotACompositeBuildpack(PathBuf),
+    #[error("Couldn't read package.toml: {0}")]
+    CannotReadPackageDescriptor(TomlFileError),
+    #[error("Couldn't write composite buildpack: {0}")]
+    CannotWriteCompositeBuildpack(TomlFileError),
+    #[error("Couldn't resolve published buildpack {0}: {1}")]
+    CannotResolvePublishedBuildpack(String, String),
+    #[error("Invalid package.toml dependency: {0}")]
+    InvalidPackageDescriptorDependency(PackageDescriptorDependencyError),
+    #[error("Component override {0} doesn't match any dependency of the composite buildpack")]
+    UnusedComponentOverride(BuildpackId),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libcnb_data::buildpack_id;
+    use tempfile::tempdir;
+
+    #[test]
+    fn override_composite_components_replaces_published_dependencies() {
+        let temp_dir = tempdir().unwrap();
+        let composite_dir = temp_dir.path().join("composite");
+        let component_dir = temp_dir.path().join("component");
+        fs::create_dir_all(&composite_dir).unwrap();
+        fs::create_dir_all(&component_dir).unwrap();
+
+        fs::write(
+            composite_dir.join("buildpack.toml"),
+            r#"
+api = "0.10"
+
+[buildpack]
+id = "heroku/composite"
+version = "1.0.0"
+
+[[order]]
+[[order.group]]
+id = "heroku/component"
+version = "1.0.0"
+
+[[order.group]]
+id = "heroku/other"
+version = "2.0.0"
+"#,
+        )
+        .unwrap();
+
+        fs::write(
+            composite_dir.join("package.toml"),
+            r#"
+[buildpack]
+uri = "."
+
+[[dependencies]]
+uri = "docker://docker.io/heroku/component:1.0.0"
+
+[[dependencies]]
+uri = "urn:cnb:registry:heroku/other@2.0.0"
+"#,
+        )
+        .unwrap();
+
+        fs::write(
+            component_dir.join("buildpack.toml"),
+            r#"
+api = "0.10"
+
+[buildpack]
+id = "heroku/component"
+version = "1.1.0"
+
+[[targets]]
+os = "linux"
+"#,
+        )
+        .unwrap();
+
+        override_composite_components(
+            &composite_dir,
+            &BTreeMap::from([(buildpack_id!("heroku/component"), component_dir.clone())]),
+            |uri| match uri {
+                "docker://docker.io/heroku/component:1.0.0" => {
+                    Ok(Some(buildpack_id!("heroku/component")))
+                }
+                other => resolve_published_buildpack_id(other),
+            },
+        )
+        .unwrap();
+
+        let package_descriptor: PackageDescriptor =
+            read_toml_file(composite_dir.join("package.toml")).unwrap();
+        assert_eq!(
+            package_descriptor
+                .dependencies
+                .iter()
+                .map(|dependency| dependency.uri.to_string())
+                .collect::<Vec<_>>(),
+            [
+                component_dir.to_string_lossy().to_string(),
+                String::from("urn:cnb:registry:heroku/other@2.0.0")
+            ]
+        );
+
+        let buildpack_descriptor: BuildpackDescriptor =
+            read_toml_file(composite_dir.join("buildpack.toml")).unwrap();
+        let BuildpackDescriptor::Composite(buildpack_descriptor) = buildpack_descriptor else {
+            panic!("Expected a composite buildpack");
+        };
+        assert_eq!(
+            buildpack_descriptor.order[0]
+                .group
+                .iter()
+                .map(|group| group.version.to_string())
+                .collect::<Vec<_>>(),
+            ["1.1.0", "2.0.0"]
+        );
+    }
+
+    #[test]
+    fn override_composite_components_unused_override() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join("package.toml"),
+            "[buildpack]\nuri = \".\"\n",
+        )
+        .unwrap();
+
+        assert!(matches!(
+            override_composite_components(
+                temp_dir.path(),
+                &BTreeMap::from([(buildpack_id!("heroku/component"), PathBuf::new())]),
+                |_| Ok(None),
+            ),
+            Err(PackageBuildpackError::UnusedComponentOverride(_))
+        ));
+    }
+
+    #[test]
+    fn resolve_registry_buildpack_id() {
+        assert_eq!(
+            resolve_published_buildpack_id("urn:cnb:registry:heroku/java@6.0.0"),
+            Ok(Some(buildpack_id!("heroku/java")))
+        );
+        assert_eq!(
+            resolve_published_buildpack_id("urn:cnb:registry:heroku/java"),
+            Ok(Some(buildpack_id!("heroku/java")))
+        );
+        assert_eq!(resolve_published_buildpack_id("../java"), Ok(None));
+    }
 }
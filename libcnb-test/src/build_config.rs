@@ -1,5 +1,8 @@
+use crate::cache_modification::{CachedLayerModification, CachedLayerModificationEntry};
 use libcnb_data::buildpack::BuildpackId;
+use libcnb_data::layer::LayerName;
 use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
@@ -13,9 +16,17 @@ pub struct BuildConfig {
     pub(crate) target_triple: String,
     pub(crate) builder_name: String,
     pub(crate) buildpacks: Vec<BuildpackReference>,
+    pub(crate) component_overrides: Vec<BuildpackId>,
     pub(crate) env: HashMap<String, String>,
+    pub(crate) network: Option<String>,
+    pub(crate) registry_credentials: Option<RegistryCredentials>,
     pub(crate) app_dir_preprocessor: Option<Rc<dyn Fn(PathBuf)>>,
     pub(crate) expected_pack_result: PackResult,
+    pub(crate) cached_layer_modifications: Vec<CachedLayerModificationEntry>,
+    pub(crate) verbose_pack_output: bool,
+    pub(crate) print_generated_dockerfiles: bool,
+    pub(crate) trust_builder: bool,
+    pub(crate) pack_args: Vec<String>,
 }
 
 impl BuildConfig {
@@ -43,9 +54,17 @@ impl BuildConfig {
             target_triple: String::from("x86_64-unknown-linux-musl"),
             builder_name: builder_name.into(),
             buildpacks: vec![BuildpackReference::CurrentCrate],
+            component_overrides: Vec::new(),
             env: HashMap::new(),
+            network: None,
+            registry_credentials: None,
             app_dir_preprocessor: None,
             expected_pack_result: PackResult::Success,
+            cached_layer_modifications: Vec::new(),
+            verbose_pack_output: false,
+            print_generated_dockerfiles: false,
+            trust_builder: true,
+            pack_args: Vec::new(),
         }
     }
 
@@ -74,6 +93,37 @@ impl BuildConfig {
         self
     }
 
+    /// Sets the component buildpacks of composite buildpacks to replace with workspace buildpacks.
+    ///
+    /// Composite buildpacks commonly reference published releases of their component buildpacks
+    /// in `package.toml`, using `docker://` or `urn:cnb:registry:` URIs. When packaging a composite
+    /// buildpack from the workspace, dependencies that resolve to one of the given buildpack IDs
+    /// are replaced with the buildpack of the same ID from the workspace. This allows testing a
+    /// locally modified component buildpack inside an otherwise released composite buildpack.
+    ///
+    /// Docker image dependencies are pulled to determine their buildpack ID.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb::data::buildpack_id;
+    /// use libcnb_test::{BuildConfig, BuildpackReference, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app")
+    ///         .buildpacks([BuildpackReference::WorkspaceBuildpack(buildpack_id!(
+    ///             "my-project/composite"
+    ///         ))])
+    ///         .component_overrides([buildpack_id!("my-project/component")]),
+    ///     |context| {
+    ///         // ...
+    ///     },
+    /// );
+    /// ```
+    pub fn component_overrides(&mut self, buildpack_ids: impl Into<Vec<BuildpackId>>) -> &mut Self {
+        self.component_overrides = buildpack_ids.into();
+        self
+    }
+
     /// Sets the Cargo profile used when compiling the buildpack.
     ///
     /// Defaults to [`CargoProfile::Dev`].
@@ -169,6 +219,63 @@ impl BuildConfig {
         self
     }
 
+    /// Sets the network mode of the containers `pack` uses to build the app.
+    ///
+    /// The value is passed verbatim to `pack build --network`. Passing `"none"` disables all
+    /// network access during the build, which can be used to verify that a buildpack supports
+    /// offline builds. Buildpacks that do require network access (i.e. to download dependencies)
+    /// will fail in this mode, use [`BuildConfig::expected_pack_result`] to assert such failures.
+    ///
+    /// If all buildpacks of the build are buildpacks of the Cargo workspace that declare
+    /// `network-isolation = true` in the `[metadata.libcnb]` table of their `buildpack.toml`, the
+    /// build runs with `"none"` by default, to verify that declaration. Otherwise, defaults to the
+    /// default network mode of `pack`. Set the network mode explicitly, i.e. to `"bridge"`, to
+    /// override this.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app").network("none"),
+    ///     |context| {
+    ///         // ...
+    ///     },
+    /// );
+    /// ```
+    pub fn network(&mut self, network: impl Into<String>) -> &mut Self {
+        self.network = Some(network.into());
+        self
+    }
+
+    /// Sets the credentials `pack` uses to pull the builder and buildpack images.
+    ///
+    /// This is required when the builder or any [`BuildpackReference::Other`] image is hosted on
+    /// a private registry and the images haven't been pulled beforehand.
+    ///
+    /// Defaults to the Docker configuration of the current user.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, RegistryCredentials, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("registry.example.com/builder:24", "tests/fixtures/app")
+    ///         .registry_credentials(RegistryCredentials::Token {
+    ///             registry: String::from("registry.example.com"),
+    ///             username: String::from("ci"),
+    ///             token: std::env::var("REGISTRY_TOKEN").unwrap(),
+    ///         }),
+    ///     |context| {
+    ///         // ...
+    ///     },
+    /// );
+    /// ```
+    pub fn registry_credentials(&mut self, registry_credentials: RegistryCredentials) -> &mut Self {
+        self.registry_credentials = Some(registry_credentials);
+        self
+    }
+
     /// Sets an app directory preprocessor function.
     ///
     /// It will be run after the app directory has been copied for the current integration test run,
@@ -247,6 +354,168 @@ impl BuildConfig {
         self.expected_pack_result = pack_result;
         self
     }
+
+    /// Sets whether `pack` runs with verbose output.
+    ///
+    /// Verbose output includes debug messages of the CNB lifecycle that mark the start and end of
+    /// each buildpack's build, which [`TestContext::logs_for_buildpack`](crate::TestContext::logs_for_buildpack)
+    /// requires. Note that `pack` prefixes each line of the lifecycle output with the name of the
+    /// current phase (i.e. `[builder]`) in this mode.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app").verbose_pack_output(true),
+    ///     |context| {
+    ///         // ...
+    ///     },
+    /// );
+    /// ```
+    pub fn verbose_pack_output(&mut self, verbose_pack_output: bool) -> &mut Self {
+        self.verbose_pack_output = verbose_pack_output;
+        self
+    }
+
+    /// Sets whether image extensions implemented with libcnb.rs print the Dockerfiles they
+    /// generate to the build output.
+    ///
+    /// Required for [`TestContext::generated_dockerfiles`](crate::TestContext::generated_dockerfiles)
+    /// and [`TestContext::generated_dockerfile`](crate::TestContext::generated_dockerfile). Since
+    /// this changes the build output of extensions, it's disabled by default.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:24", "tests/fixtures/app")
+    ///         .print_generated_dockerfiles(true),
+    ///     |context| {
+    ///         // ...
+    ///     },
+    /// );
+    /// ```
+    pub fn print_generated_dockerfiles(&mut self, print_generated_dockerfiles: bool) -> &mut Self {
+        self.print_generated_dockerfiles = print_generated_dockerfiles;
+        self
+    }
+
+    /// Sets whether `pack` trusts the builder, i.e. runs all lifecycle phases in a single container
+    /// with access to registry credentials.
+    ///
+    /// Untrusted builders run each phase in a separate container, like on platforms that don't
+    /// trust third-party builders. Defaults to `true`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app").trust_builder(false),
+    ///     |context| {
+    ///         // ...
+    ///     },
+    /// );
+    /// ```
+    pub fn trust_builder(&mut self, trust_builder: bool) -> &mut Self {
+        self.trust_builder = trust_builder;
+        self
+    }
+
+    /// Adds an argument that is passed to `pack build`, i.e. `--clear-cache`.
+    ///
+    /// Additional arguments are passed after the arguments libcnb-test uses, so they take
+    /// precedence for flags that can only be set once, such as `--pull-policy`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app").pack_arg("--clear-cache"),
+    ///     |context| {
+    ///         // ...
+    ///     },
+    /// );
+    /// ```
+    pub fn pack_arg(&mut self, arg: impl Into<String>) -> &mut Self {
+        self.pack_args.push(arg.into());
+        self
+    }
+
+    /// Adds multiple arguments that are passed to `pack build`.
+    ///
+    /// See [`pack_arg`](Self::pack_arg) for details.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app")
+    ///         .pack_args(["--pull-policy", "always"]),
+    ///     |context| {
+    ///         // ...
+    ///     },
+    /// );
+    /// ```
+    pub fn pack_args<I: IntoIterator<Item = S>, S: Into<String>>(&mut self, args: I) -> &mut Self {
+        self.pack_args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Adds a modification of a cached layer, applied after the CNB lifecycle restored the layer
+    /// from the cache and before the buildpacks run.
+    ///
+    /// This allows testing how a buildpack handles corrupted, outdated or otherwise unexpected
+    /// cached layers, i.e. in the layer's `restored_layer_action` or `invalid_metadata_action`.
+    /// Modifications are applied in the order they were added.
+    ///
+    /// The modifications are applied by a buildpack that is added as the first buildpack of the
+    /// build and shows up in the build output and image metadata. The build fails if the layer
+    /// wasn't restored, so this is only useful for [rebuilds](crate::TestContext::rebuild).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb::data::{buildpack_id, layer_name};
+    /// use libcnb_test::{assert_contains, BuildConfig, CachedLayerModification, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         let mut config = context.config.clone();
+    ///         config.cached_layer_modification(
+    ///             buildpack_id!("heroku/ruby"),
+    ///             layer_name!("ruby"),
+    ///             CachedLayerModification::TruncateFile("bin/ruby".into()),
+    ///         );
+    ///
+    ///         context.rebuild(config, |context| {
+    ///             assert_contains!(context.pack_stdout, "Reinstalling corrupted Ruby");
+    ///         });
+    ///     },
+    /// );
+    /// ```
+    pub fn cached_layer_modification(
+        &mut self,
+        buildpack_id: BuildpackId,
+        layer_name: LayerName,
+        modification: CachedLayerModification,
+    ) -> &mut Self {
+        self.cached_layer_modifications
+            .push(CachedLayerModificationEntry {
+                buildpack_id,
+                layer_name,
+                modification,
+            });
+        self
+    }
 }
 
 /// References a Cloud Native Buildpack.
@@ -262,6 +531,39 @@ pub enum BuildpackReference {
     Other(String),
 }
 
+/// Credentials for pulling images from a container registry.
+#[derive(Clone)]
+pub enum RegistryCredentials {
+    /// Uses the Docker configuration directory at the given path, i.e. a directory that contains
+    /// a `config.json` file with registry credentials.
+    DockerConfig(PathBuf),
+    /// Uses the given username and token to authenticate with the given registry.
+    ///
+    /// The rest of the user's Docker config (`$DOCKER_CONFIG` or `~/.docker`), such as credential
+    /// helpers, credentials for other registries and Docker contexts, is kept.
+    Token {
+        registry: String,
+        username: String,
+        token: String,
+    },
+}
+
+impl Debug for RegistryCredentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DockerConfig(path) => f.debug_tuple("DockerConfig").field(path).finish(),
+            Self::Token {
+                registry, username, ..
+            } => f
+                .debug_struct("Token")
+                .field("registry", registry)
+                .field("username", username)
+                .field("token", &"<redacted>")
+                .finish(),
+        }
+    }
+}
+
 /// Result of a pack execution.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum PackResult {
@@ -0,0 +1,218 @@
+//! Simulated modifications of cached layers, i.e. to test cache invalidation logic.
+//!
+//! The CNB lifecycle restores cached layers before any buildpack runs. To modify them
+//! deterministically, a small shell script buildpack that applies the modifications is added as
+//! the first buildpack of the build. It runs after the layers have been restored, but before any
+//! of the buildpacks under test.
+
+use libcnb_data::buildpack::BuildpackId;
+use libcnb_data::layer::LayerName;
+use libcnb_data::layer_content_metadata::LayerContentMetadata;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A modification of a layer that was restored from the cache.
+///
+/// See [`BuildConfig::cached_layer_modification`](crate::BuildConfig::cached_layer_modification).
+#[derive(Debug, Clone, PartialEq)]
+pub enum CachedLayerModification {
+    /// Truncates the file at the given path, relative to the layer directory, to zero bytes.
+    TruncateFile(PathBuf),
+    /// Writes the given contents to the file at the given path, relative to the layer directory.
+    WriteFile(PathBuf, String),
+    /// Deletes the file or directory at the given path, relative to the layer directory.
+    DeletePath(PathBuf),
+    /// Replaces the metadata of the layer, i.e. with metadata of an older buildpack version.
+    ReplaceMetadata(toml::Table),
+    /// Moves the modification times of all files in the layer the given duration into the past.
+    Age(Duration),
+    /// Deletes the layer directory and its metadata, as if it was evicted from the cache.
+    DeleteLayer,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CachedLayerModificationEntry {
+    pub(crate) buildpack_id: BuildpackId,
+    pub(crate) layer_name: LayerName,
+    pub(crate) modification: CachedLayerModification,
+}
+
+const MODIFICATION_BUILDPACK_ID: &str = "libcnb-test/cached-layer-modifications";
+
+/// Writes a buildpack that applies the given modifications into the given directory.
+pub(crate) fn write_modification_buildpack(
+    buildpack_dir: &Path,
+    entries: &[CachedLayerModificationEntry],
+) -> std::io::Result<()> {
+    let bin_dir = buildpack_dir.join("bin");
+    fs::create_dir_all(&bin_dir)?;
+
+    fs::write(
+        buildpack_dir.join("buildpack.toml"),
+        format!(
+            "api = \"0.10\"\n\n[buildpack]\nid = \"{MODIFICATION_BUILDPACK_ID}\"\nversion = \"0.0.0\"\n\n[[targets]]\nos = \"linux\"\n"
+        ),
+    )?;
+
+    write_executable(&bin_dir.join("detect"), "#!/usr/bin/env bash\nexit 0\n")?;
+    write_executable(&bin_dir.join("build"), &build_script(entries))
+}
+
+fn write_executable(path: &Path, contents: &str) -> std::io::Result<()> {
+    fs::write(path, contents)?;
+
+    #[cfg(target_family = "unix")]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    }
+
+    Ok(())
+}
+
+fn build_script(entries: &[CachedLayerModificationEntry]) -> String {
+    let mut script = String::from(
+        "#!/usr/bin/env bash\nset -euo pipefail\n\nlayers_root=\"$(dirname \"${CNB_LAYERS_DIR:-$1}\")\"\n",
+    );
+
+    for entry in entries {
+        let layer_dir = format!(
+            "\"$layers_root\"/{}/{}",
+            shell_quote(&entry.buildpack_id.replace('/', "_")),
+            shell_quote(&entry.layer_name)
+        );
+        let layer_toml = format!(
+            "\"$layers_root\"/{}/{}",
+            shell_quote(&entry.buildpack_id.replace('/', "_")),
+            shell_quote(&format!("{}.toml", entry.layer_name))
+        );
+        let description = format!(
+            "layer '{}' of buildpack '{}'",
+            entry.layer_name, entry.buildpack_id
+        );
+
+        let _ = write!(
+            script,
+            "\nif [[ ! -f {layer_toml} ]]; then\n  echo {} >&2\n  exit 1\nfi\necho {}\n",
+            shell_quote(&format!("Cached {description} wasn't restored")),
+            shell_quote(&format!("Modifying cached {description}"))
+        );
+
+        let command = match &entry.modification {
+            CachedLayerModification::TruncateFile(path) => {
+                let path = format!("{layer_dir}/{}", shell_quote(&path.to_string_lossy()));
+                format!("test -f {path}\n: > {path}")
+            }
+            CachedLayerModification::WriteFile(path, contents) => format!(
+                "printf '%s' {} > {layer_dir}/{}",
+                shell_quote(contents),
+                shell_quote(&path.to_string_lossy())
+            ),
+            CachedLayerModification::DeletePath(path) => {
+                format!("rm -rf {layer_dir}/{}", shell_quote(&path.to_string_lossy()))
+            }
+            CachedLayerModification::ReplaceMetadata(metadata) => {
+                let layer_content_metadata = LayerContentMetadata {
+                    types: None,
+                    metadata: metadata.clone(),
+                };
+
+                format!(
+                    "printf '%s' {} > {layer_toml}",
+                    shell_quote(&toml::to_string(&layer_content_metadata).unwrap_or_default())
+                )
+            }
+            CachedLayerModification::Age(duration) => format!(
+                "if [[ -d {layer_dir} ]]; then\n  find {layer_dir} -exec touch -h -d \"@$(( $(date +%s) - {} ))\" {{}} +\nfi",
+                duration.as_secs()
+            ),
+            CachedLayerModification::DeleteLayer => format!("rm -rf {layer_dir} {layer_toml}"),
+        };
+
+        script.push_str(&command);
+        script.push('\n');
+    }
+
+    script
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libcnb_data::{buildpack_id, layer_name};
+
+    fn entry(modification: CachedLayerModification) -> CachedLayerModificationEntry {
+        CachedLayerModificationEntry {
+            buildpack_id: buildpack_id!("heroku/ruby"),
+            layer_name: layer_name!("gems"),
+            modification,
+        }
+    }
+
+    #[test]
+    fn build_script_modifications() {
+        let script = build_script(&[
+            entry(CachedLayerModification::TruncateFile(PathBuf::from(
+                "bin/ruby",
+            ))),
+            entry(CachedLayerModification::WriteFile(
+                PathBuf::from("VERSION"),
+                String::from("it's 1.0"),
+            )),
+            entry(CachedLayerModification::DeletePath(PathBuf::from("lib"))),
+            entry(CachedLayerModification::Age(Duration::from_secs(86400))),
+            entry(CachedLayerModification::DeleteLayer),
+        ]);
+
+        assert!(script.contains(
+            "if [[ ! -f \"$layers_root\"/'heroku_ruby'/'gems.toml' ]]; then\n  echo 'Cached layer '\\''gems'\\'' of buildpack '\\''heroku/ruby'\\'' wasn'\\''t restored' >&2\n  exit 1\nfi\n"
+        ));
+        assert!(script.contains(
+            "test -f \"$layers_root\"/'heroku_ruby'/'gems'/'bin/ruby'\n: > \"$layers_root\"/'heroku_ruby'/'gems'/'bin/ruby'\n"
+        ));
+        assert!(script.contains(
+            "printf '%s' 'it'\\''s 1.0' > \"$layers_root\"/'heroku_ruby'/'gems'/'VERSION'\n"
+        ));
+        assert!(script.contains("rm -rf \"$layers_root\"/'heroku_ruby'/'gems'/'lib'\n"));
+        assert!(script.contains("touch -h -d \"@$(( $(date +%s) - 86400 ))\" {} +"));
+        assert!(script.contains(
+            "rm -rf \"$layers_root\"/'heroku_ruby'/'gems' \"$layers_root\"/'heroku_ruby'/'gems.toml'\n"
+        ));
+    }
+
+    #[test]
+    fn build_script_replace_metadata() {
+        let mut metadata = toml::Table::new();
+        metadata.insert(String::from("version"), toml::Value::from("1.0"));
+
+        let script = build_script(&[entry(CachedLayerModification::ReplaceMetadata(metadata))]);
+
+        assert!(script.contains(
+            "printf '%s' '[metadata]\nversion = \"1.0\"\n' > \"$layers_root\"/'heroku_ruby'/'gems.toml'\n"
+        ));
+    }
+
+    #[test]
+    fn write_modification_buildpack_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        write_modification_buildpack(
+            temp_dir.path(),
+            &[entry(CachedLayerModification::DeleteLayer)],
+        )
+        .unwrap();
+
+        assert!(fs::read_to_string(temp_dir.path().join("buildpack.toml"))
+            .unwrap()
+            .contains("id = \"libcnb-test/cached-layer-modifications\""));
+        assert!(temp_dir.path().join("bin/detect").is_file());
+        assert!(fs::read_to_string(temp_dir.path().join("bin/build"))
+            .unwrap()
+            .starts_with("#!/usr/bin/env bash\n"));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::ServiceConfig;
+use std::borrow::Borrow;
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
@@ -33,6 +35,10 @@ pub struct ContainerConfig {
     pub(crate) env: HashMap<String, String>,
     pub(crate) exposed_ports: HashSet<u16>,
     pub(crate) bind_mounts: HashMap<PathBuf, PathBuf>,
+    pub(crate) memory_limit: Option<String>,
+    pub(crate) network: Option<String>,
+    pub(crate) docker_run_args: Vec<String>,
+    pub(crate) services: Vec<ServiceConfig>,
 }
 
 impl ContainerConfig {
@@ -202,6 +208,86 @@ impl ContainerConfig {
         self
     }
 
+    /// Limits the memory available to the container, i.e. `512m` or `1g`. Useful for testing how
+    /// processes behave on dynos or instances with little memory.
+    ///
+    /// See: [Docker Engine: Memory](https://docs.docker.com/engine/containers/resource_constraints/#memory)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, ContainerConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         // ...
+    ///         context.start_container(ContainerConfig::new().memory_limit("512m"), |container| {
+    ///             // ...
+    ///         });
+    ///     },
+    /// );
+    /// ```
+    pub fn memory_limit(&mut self, memory_limit: impl Into<String>) -> &mut Self {
+        self.memory_limit = Some(memory_limit.into());
+        self
+    }
+
+    /// Connects the container to the given Docker network, i.e. `host` or `none`, instead of the
+    /// default bridge network.
+    ///
+    /// Can't be combined with [`ContainerConfig::service`], since services are connected to the
+    /// container via a dedicated network.
+    ///
+    /// See: [Docker Engine: Networking](https://docs.docker.com/engine/network/)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, ContainerConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         // ...
+    ///         context.start_container(ContainerConfig::new().network("none"), |container| {
+    ///             // ...
+    ///         });
+    ///     },
+    /// );
+    /// ```
+    pub fn network(&mut self, network: impl Into<String>) -> &mut Self {
+        self.network = Some(network.into());
+        self
+    }
+
+    /// Adds an argument that is passed to `docker run` as-is, for options that have no dedicated
+    /// method. Arguments are passed in the order they were added, before the image name.
+    ///
+    /// See: [Docker CLI: `docker run`](https://docs.docker.com/reference/cli/docker/container/run/)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, ContainerConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         // ...
+    ///         context.start_container(
+    ///             ContainerConfig::new()
+    ///                 .docker_run_arg("--cpus=0.5")
+    ///                 .docker_run_arg("--read-only"),
+    ///             |container| {
+    ///                 // ...
+    ///             },
+    ///         );
+    ///     },
+    /// );
+    /// ```
+    pub fn docker_run_arg(&mut self, arg: impl Into<String>) -> &mut Self {
+        self.docker_run_args.push(arg.into());
+        self
+    }
+
     /// Adds or updates multiple environment variable mappings for the container.
     ///
     /// # Example
@@ -231,4 +317,33 @@ impl ContainerConfig {
 
         self
     }
+
+    /// Adds a service container, such as a database, that is started before the app container.
+    /// See [`ServiceConfig`] for details.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, ContainerConfig, ServiceConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         // ...
+    ///         context.start_container(
+    ///             ContainerConfig::new().service(
+    ///                 ServiceConfig::new("redis", "redis:7")
+    ///                     .ready_log_message("Ready to accept connections")
+    ///                     .app_env("REDIS_URL", "redis://redis:6379"),
+    ///             ),
+    ///             |container| {
+    ///                 // ...
+    ///             },
+    ///         );
+    ///     },
+    /// );
+    /// ```
+    pub fn service(&mut self, service: impl Borrow<ServiceConfig>) -> &mut Self {
+        self.services.push(service.borrow().clone());
+        self
+    }
 }
@@ -1,16 +1,25 @@
 use crate::docker::{
     DockerExecCommand, DockerLogsCommand, DockerPortCommand, DockerRemoveContainerCommand,
 };
-use crate::log::LogOutput;
+use crate::docker_host::DockerEndpoint;
+use crate::log::{ExecOutput, ExecStream, LogOutput};
+use crate::service_config::ServiceContainers;
 use crate::util::CommandError;
 use crate::{util, ContainerConfig};
-use std::net::SocketAddr;
+use std::io::{BufRead, BufReader, Read};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
 
 /// Context of a launched container.
 pub struct ContainerContext {
     /// The randomly generated name of this container.
     pub container_name: String,
     pub(crate) config: ContainerConfig,
+    // Dropped after the container has been removed in `drop`.
+    pub(crate) _services: Option<ServiceContainers>,
 }
 
 impl ContainerContext {
@@ -86,6 +95,10 @@ impl ContainerContext {
 
     /// Returns the local address of an exposed container port.
     ///
+    /// When the Docker daemon runs on a remote machine, i.e. when `DOCKER_HOST` or the current
+    /// Docker context use an `ssh://` or `tcp://` URL, the address of the remote machine is
+    /// returned instead.
+    ///
     /// # Example
     /// ```no_run
     /// use libcnb_test::{BuildConfig, ContainerConfig, TestRunner};
@@ -109,8 +122,9 @@ impl ContainerContext {
     ///
     /// # Panics
     ///
-    /// Will panic if there was an error obtaining the container port mapping, or the specified port
-    /// was not exposed using [`ContainerConfig::expose_port`](crate::ContainerConfig::expose_port).
+    /// Will panic if there was an error obtaining the container port mapping, the address of a
+    /// remote Docker host couldn't be resolved, or the specified port was not exposed using
+    /// [`ContainerConfig::expose_port`](crate::ContainerConfig::expose_port).
     #[must_use]
     pub fn address_for_port(&self, port: u16) -> SocketAddr {
         assert!(
@@ -121,11 +135,28 @@ impl ContainerContext {
         let docker_port_command = DockerPortCommand::new(&self.container_name, port);
 
         match util::run_command(docker_port_command) {
-            Ok(output) => output
-                .stdout
-                .trim()
-                .parse()
-                .unwrap_or_else(|error| panic!("Error parsing `docker port` output: {error}")),
+            Ok(output) => {
+                // Ports published on all interfaces are listed once per address family.
+                let published_address = output
+                    .stdout
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .parse::<SocketAddr>()
+                    .unwrap_or_else(|error| panic!("Error parsing `docker port` output: {error}"));
+
+                match DockerEndpoint::current().remote_host() {
+                    Some(remote_host) => (remote_host, published_address.port())
+                        .to_socket_addrs()
+                        .ok()
+                        .and_then(|mut addresses| addresses.next())
+                        .unwrap_or_else(|| {
+                            panic!("Error resolving address of remote Docker host {remote_host}")
+                        }),
+                    None => published_address,
+                }
+            }
             Err(CommandError::NonZeroExitCode { log_output, .. }) => {
                 panic!(
                     "Error obtaining container port mapping:\n{}\nThis normally means that the container crashed. Container logs:\n\n{}",
@@ -139,6 +170,122 @@ impl ContainerContext {
         }
     }
 
+    /// Waits until a TCP connection to an exposed container port can be established.
+    ///
+    /// Connection attempts are retried with exponential backoff until the timeout elapsed. The
+    /// timeout applies to all attempts combined, not to each attempt individually. Returns
+    /// the local address of the port, like [`address_for_port`](Self::address_for_port).
+    ///
+    /// Note: Depending on the Docker setup, the port might already accept connections before the
+    /// process in the container listens on it. For HTTP services, prefer
+    /// [`wait_for_http`](Self::wait_for_http).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, ContainerConfig, TestRunner};
+    /// use std::time::Duration;
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         // ...
+    ///         context.start_container(
+    ///             ContainerConfig::new()
+    ///                 .env("PORT", "12345")
+    ///                 .expose_port(12345),
+    ///             |container| {
+    ///                 let address_on_host = container.wait_for_port(12345, Duration::from_secs(10));
+    ///                 // ...
+    ///             },
+    ///         );
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the port couldn't be connected to before the timeout elapsed, or for the same
+    /// reasons as [`address_for_port`](Self::address_for_port).
+    #[must_use]
+    pub fn wait_for_port(&self, port: u16, timeout: Duration) -> SocketAddr {
+        let address = self.address_for_port(port);
+
+        util::retry_with_backoff(timeout, |remaining| {
+            TcpStream::connect_timeout(&address, remaining).map(|_| address)
+        })
+        .unwrap_or_else(|error| {
+            panic!(
+                "Timed out after {timeout:?} waiting for container port {port} ({address}) to accept connections: {error}\n\nContainer logs:\n\n{}",
+                self.logs_now()
+            )
+        })
+    }
+
+    /// Waits until an HTTP GET request to the given URL returns a response that satisfies the
+    /// given predicate.
+    ///
+    /// Requests are retried with exponential backoff until the timeout elapsed. The timeout applies
+    /// to all requests combined, not to each request individually. Connection errors
+    /// and responses that don't satisfy the predicate are retried. Responses with non-2xx status
+    /// codes are passed to the predicate as well. Returns the first response that satisfied the
+    /// predicate.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{assert_contains, BuildConfig, ContainerConfig, TestRunner};
+    /// use std::time::Duration;
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         // ...
+    ///         context.start_container(
+    ///             ContainerConfig::new()
+    ///                 .env("PORT", "12345")
+    ///                 .expose_port(12345),
+    ///             |container| {
+    ///                 let address_on_host = container.address_for_port(12345);
+    ///                 let response = container.wait_for_http(
+    ///                     &format!("http://{address_on_host}/health"),
+    ///                     |response| response.status == 200,
+    ///                     Duration::from_secs(10),
+    ///                 );
+    ///                 assert_contains!(response.body, "ok");
+    ///             },
+    ///         );
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if no response satisfied the predicate before the timeout elapsed.
+    pub fn wait_for_http(
+        &self,
+        url: &str,
+        predicate: impl Fn(&HttpResponse) -> bool,
+        timeout: Duration,
+    ) -> HttpResponse {
+        util::retry_with_backoff(timeout, |remaining| {
+            let response = http_get(url, remaining)?;
+
+            if predicate(&response) {
+                Ok(response)
+            } else {
+                Err(format!(
+                    "Response didn't satisfy the predicate. Status: {}, body:\n{}",
+                    response.status, response.body
+                ))
+            }
+        })
+        .unwrap_or_else(|error| {
+            panic!(
+                "Timed out after {timeout:?} waiting for HTTP response from {url}: {error}\n\nContainer logs:\n\n{}",
+                self.logs_now()
+            )
+        })
+    }
+
     /// Executes a shell command inside an already running container.
     ///
     /// # Example
@@ -160,7 +307,8 @@ impl ContainerContext {
     /// # Panics
     ///
     /// Panics if it was not possible to exec into the container, or if the command
-    /// exited with a non-zero exit code.
+    /// exited with a non-zero exit code. Use [`shell_exec_unchecked`](Self::shell_exec_unchecked)
+    /// to assert on failing commands.
     pub fn shell_exec(&self, command: impl AsRef<str>) -> LogOutput {
         let docker_exec_command = DockerExecCommand::new(
             &self.container_name,
@@ -169,6 +317,181 @@ impl ContainerContext {
         util::run_command(docker_exec_command)
             .unwrap_or_else(|command_err| panic!("Error performing docker exec:\n\n{command_err}"))
     }
+
+    /// Executes a shell command inside an already running container, without checking its exit
+    /// code.
+    ///
+    /// Unlike [`shell_exec`](Self::shell_exec), a failing command doesn't panic. Instead, its
+    /// output and exit code are returned, so that tests can assert on failing commands.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{assert_contains, BuildConfig, ContainerConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         // ...
+    ///         context.start_container(ContainerConfig::new(), |container| {
+    ///             let exec_output = container.shell_exec_unchecked("python3 -c 'import missing'");
+    ///             assert_eq!(exec_output.exit_code, Some(1));
+    ///             assert_contains!(exec_output.stderr, "ModuleNotFoundError");
+    ///         });
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if it was not possible to run `docker exec`.
+    pub fn shell_exec_unchecked(&self, command: impl AsRef<str>) -> ExecOutput {
+        let docker_exec_command = DockerExecCommand::new(
+            &self.container_name,
+            [util::CNB_LAUNCHER_BINARY, command.as_ref()],
+        );
+
+        match util::run_command(docker_exec_command) {
+            Ok(LogOutput { stdout, stderr }) => ExecOutput {
+                stdout,
+                stderr,
+                exit_code: Some(0),
+            },
+            Err(CommandError::NonZeroExitCode {
+                exit_code,
+                log_output: LogOutput { stdout, stderr },
+                ..
+            }) => ExecOutput {
+                stdout,
+                stderr,
+                exit_code,
+            },
+            Err(command_err) => panic!("Error performing docker exec:\n\n{command_err}"),
+        }
+    }
+
+    /// Executes a shell command inside an already running container, calling the given function
+    /// with each line of output while the command is running.
+    ///
+    /// This is useful for long-running commands, whose progress would otherwise only be visible
+    /// after they finished. Lines are passed without their line ending, in the order they were
+    /// received. Like [`shell_exec_unchecked`](Self::shell_exec_unchecked), the exit code isn't
+    /// checked.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, ContainerConfig, ExecStream, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         // ...
+    ///         context.start_container(ContainerConfig::new(), |container| {
+    ///             let exec_output = container.shell_exec_streaming(
+    ///                 "bin/rake db:migrate",
+    ///                 |stream, line| match stream {
+    ///                     ExecStream::Stdout => println!("{line}"),
+    ///                     ExecStream::Stderr => eprintln!("{line}"),
+    ///                 },
+    ///             );
+    ///             assert!(exec_output.success());
+    ///         });
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if it was not possible to run `docker exec`.
+    pub fn shell_exec_streaming(
+        &self,
+        command: impl AsRef<str>,
+        mut on_line: impl FnMut(ExecStream, &str),
+    ) -> ExecOutput {
+        let mut child = Command::from(DockerExecCommand::new(
+            &self.container_name,
+            [util::CNB_LAUNCHER_BINARY, command.as_ref()],
+        ))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap_or_else(|io_error| panic!("Error performing docker exec:\n\n{io_error}"));
+
+        let readers: [(ExecStream, Option<Box<dyn Read + Send>>); 2] = [
+            (
+                ExecStream::Stdout,
+                child.stdout.take().map(|stdout| Box::new(stdout) as _),
+            ),
+            (
+                ExecStream::Stderr,
+                child.stderr.take().map(|stderr| Box::new(stderr) as _),
+            ),
+        ];
+
+        let mut exec_output = ExecOutput::default();
+        let (sender, receiver) = mpsc::channel();
+
+        thread::scope(|scope| {
+            for (stream, reader) in readers {
+                let Some(reader) = reader else { continue };
+                let sender = sender.clone();
+
+                scope.spawn(move || {
+                    let mut reader = BufReader::new(reader);
+                    let mut line = Vec::new();
+
+                    while reader
+                        .read_until(b'\n', &mut line)
+                        .is_ok_and(|read| read > 0)
+                    {
+                        let _ = sender.send((stream, String::from_utf8_lossy(&line).into_owned()));
+                        line.clear();
+                    }
+                });
+            }
+
+            // Lines are received until all reader threads have dropped their sender.
+            drop(sender);
+
+            for (stream, line) in receiver {
+                on_line(stream, line.trim_end_matches(['\n', '\r']));
+
+                match stream {
+                    ExecStream::Stdout => exec_output.stdout.push_str(&line),
+                    ExecStream::Stderr => exec_output.stderr.push_str(&line),
+                }
+            }
+        });
+
+        exec_output.exit_code = child
+            .wait()
+            .unwrap_or_else(|io_error| panic!("Error performing docker exec:\n\n{io_error}"))
+            .code();
+
+        exec_output
+    }
+}
+
+/// An HTTP response, as returned by [`ContainerContext::wait_for_http`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HttpResponse {
+    /// The HTTP status code.
+    pub status: u16,
+    /// The response body, interpreted as an UTF-8 string.
+    pub body: String,
+}
+
+fn http_get(url: &str, timeout: Duration) -> Result<HttpResponse, String> {
+    let response = match ureq::get(url).timeout(timeout).call() {
+        Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+        Err(ureq::Error::Transport(transport_error)) => return Err(transport_error.to_string()),
+    };
+
+    let status = response.status();
+    let body = response
+        .into_string()
+        .map_err(|io_error| format!("Couldn't read response body: {io_error}"))?;
+
+    Ok(HttpResponse { status, body })
 }
 
 impl Drop for ContainerContext {
@@ -178,3 +501,62 @@ impl Drop for ContainerContext {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::time::Instant;
+
+    fn serve_once(response: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+
+        format!("http://{address}/")
+    }
+
+    #[test]
+    fn http_get_success() {
+        let url = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok");
+
+        assert_eq!(
+            http_get(&url, Duration::from_secs(5)),
+            Ok(HttpResponse {
+                status: 200,
+                body: String::from("ok")
+            })
+        );
+    }
+
+    #[test]
+    fn http_get_error_status() {
+        let url = serve_once("HTTP/1.1 503 Service Unavailable\r\nContent-Length: 4\r\n\r\nbusy");
+
+        assert_eq!(
+            http_get(&url, Duration::from_secs(5)),
+            Ok(HttpResponse {
+                status: 503,
+                body: String::from("busy")
+            })
+        );
+    }
+
+    #[test]
+    fn http_get_respects_timeout() {
+        // Accepts connections (via the listen backlog) but never responds.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let start = Instant::now();
+
+        assert!(http_get(&url, Duration::from_millis(200)).is_err());
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+}
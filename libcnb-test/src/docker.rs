@@ -5,6 +5,7 @@ use std::process::Command;
 /// Represents a `docker run` command.
 #[derive(Clone, Debug)]
 pub(crate) struct DockerRunCommand {
+    additional_args: Vec<String>,
     command: Option<Vec<String>>,
     container_name: String,
     detach: bool,
@@ -12,7 +13,11 @@ pub(crate) struct DockerRunCommand {
     env: BTreeMap<String, String>,
     exposed_ports: BTreeSet<u16>,
     image_name: String,
+    memory: Option<String>,
+    network: Option<String>,
+    network_aliases: BTreeSet<String>,
     platform: Option<String>,
+    publish_on_all_interfaces: bool,
     remove: bool,
     bind_mounts: BTreeMap<PathBuf, PathBuf>,
 }
@@ -20,6 +25,7 @@ pub(crate) struct DockerRunCommand {
 impl DockerRunCommand {
     pub(crate) fn new(image_name: impl Into<String>, container_name: impl Into<String>) -> Self {
         Self {
+            additional_args: Vec::new(),
             command: None,
             container_name: container_name.into(),
             detach: false,
@@ -27,12 +33,22 @@ impl DockerRunCommand {
             env: BTreeMap::new(),
             exposed_ports: BTreeSet::new(),
             image_name: image_name.into(),
+            memory: None,
+            network: None,
+            network_aliases: BTreeSet::new(),
             platform: None,
+            publish_on_all_interfaces: false,
             remove: false,
             bind_mounts: BTreeMap::new(),
         }
     }
 
+    /// Adds an argument that is passed to `docker run` before the image name.
+    pub(crate) fn additional_arg(&mut self, arg: impl Into<String>) -> &mut Self {
+        self.additional_args.push(arg.into());
+        self
+    }
+
     pub(crate) fn command<I: IntoIterator<Item = S>, S: Into<String>>(
         &mut self,
         command: I,
@@ -61,11 +77,38 @@ impl DockerRunCommand {
         self
     }
 
+    pub(crate) fn memory(&mut self, memory: impl Into<String>) -> &mut Self {
+        self.memory = Some(memory.into());
+        self
+    }
+
+    pub(crate) fn network(&mut self, network: impl Into<String>) -> &mut Self {
+        self.network = Some(network.into());
+        self
+    }
+
+    pub(crate) fn network_alias(&mut self, alias: impl Into<String>) -> &mut Self {
+        self.network_aliases.insert(alias.into());
+        self
+    }
+
     pub(crate) fn platform(&mut self, platform: impl Into<String>) -> &mut Self {
         self.platform = Some(platform.into());
         self
     }
 
+    /// Publishes exposed ports on all interfaces of the Docker host instead of only on `127.0.0.1`.
+    ///
+    /// Required when the Docker daemon runs on a remote machine, since ports published on its
+    /// loopback interface can't be reached from the machine running the tests.
+    pub(crate) fn publish_on_all_interfaces(
+        &mut self,
+        publish_on_all_interfaces: bool,
+    ) -> &mut Self {
+        self.publish_on_all_interfaces = publish_on_all_interfaces;
+        self
+    }
+
     pub(crate) fn remove(&mut self, remove: bool) -> &mut Self {
         self.remove = remove;
         self
@@ -94,6 +137,18 @@ impl From<DockerRunCommand> for Command {
             command.args(["--platform", &platform]);
         }
 
+        if let Some(memory) = docker_run_command.memory {
+            command.args(["--memory", &memory]);
+        }
+
+        if let Some(network) = docker_run_command.network {
+            command.args(["--network", &network]);
+        }
+
+        for alias in &docker_run_command.network_aliases {
+            command.args(["--network-alias", alias]);
+        }
+
         if let Some(entrypoint) = docker_run_command.entrypoint {
             command.args(["--entrypoint", &entrypoint]);
         }
@@ -103,7 +158,11 @@ impl From<DockerRunCommand> for Command {
         }
 
         for port in &docker_run_command.exposed_ports {
-            command.args(["--publish", &format!("127.0.0.1::{port}")]);
+            if docker_run_command.publish_on_all_interfaces {
+                command.args(["--publish", &port.to_string()]);
+            } else {
+                command.args(["--publish", &format!("127.0.0.1::{port}")]);
+            }
         }
 
         for (source, target) in &docker_run_command.bind_mounts {
@@ -117,6 +176,7 @@ impl From<DockerRunCommand> for Command {
             ]);
         }
 
+        command.args(docker_run_command.additional_args);
         command.arg(docker_run_command.image_name);
 
         if let Some(container_command) = docker_run_command.command {
@@ -190,6 +250,87 @@ impl From<DockerLogsCommand> for Command {
     }
 }
 
+/// Represents a `docker image inspect` command.
+#[derive(Clone, Debug)]
+pub(crate) struct DockerImageInspectCommand {
+    format: Option<String>,
+    image_name: String,
+}
+
+impl DockerImageInspectCommand {
+    pub(crate) fn new(image_name: impl Into<String>) -> Self {
+        Self {
+            format: None,
+            image_name: image_name.into(),
+        }
+    }
+
+    pub(crate) fn format(&mut self, format: impl Into<String>) -> &mut Self {
+        self.format = Some(format.into());
+        self
+    }
+}
+
+impl From<DockerImageInspectCommand> for Command {
+    fn from(docker_image_inspect_command: DockerImageInspectCommand) -> Self {
+        let mut command = Command::new("docker");
+        command.args(["image", "inspect"]);
+
+        if let Some(format) = docker_image_inspect_command.format {
+            command.args(["--format", &format]);
+        }
+
+        command.arg(docker_image_inspect_command.image_name);
+        command
+    }
+}
+
+/// Represents a `docker pull` command.
+#[derive(Clone, Debug)]
+pub(crate) struct DockerPullCommand {
+    image_name: String,
+}
+
+impl DockerPullCommand {
+    pub(crate) fn new(image_name: impl Into<String>) -> Self {
+        Self {
+            image_name: image_name.into(),
+        }
+    }
+}
+
+impl From<DockerPullCommand> for Command {
+    fn from(docker_pull_command: DockerPullCommand) -> Self {
+        let mut command = Command::new("docker");
+        command.args(["pull", "--quiet", &docker_pull_command.image_name]);
+        command
+    }
+}
+
+/// Represents a `docker context inspect` command that outputs the Docker daemon URL of the
+/// current context.
+#[derive(Clone, Debug)]
+pub(crate) struct DockerContextInspectCommand;
+
+impl DockerContextInspectCommand {
+    pub(crate) fn new() -> Self {
+        Self
+    }
+}
+
+impl From<DockerContextInspectCommand> for Command {
+    fn from(_docker_context_inspect_command: DockerContextInspectCommand) -> Self {
+        let mut command = Command::new("docker");
+        command.args([
+            "context",
+            "inspect",
+            "--format",
+            "{{.Endpoints.docker.Host}}",
+        ]);
+        command
+    }
+}
+
 /// Represents a `docker port` command.
 #[derive(Clone, Debug)]
 pub(crate) struct DockerPortCommand {
@@ -307,6 +448,54 @@ impl From<DockerRemoveVolumeCommand> for Command {
     }
 }
 
+/// Represents a `docker network create` command.
+#[derive(Clone, Debug)]
+pub(crate) struct DockerNetworkCreateCommand {
+    network_name: String,
+}
+
+impl DockerNetworkCreateCommand {
+    pub(crate) fn new(network_name: impl Into<String>) -> Self {
+        Self {
+            network_name: network_name.into(),
+        }
+    }
+}
+
+impl From<DockerNetworkCreateCommand> for Command {
+    fn from(docker_network_create_command: DockerNetworkCreateCommand) -> Self {
+        let mut command = Command::new("docker");
+        command.args([
+            "network",
+            "create",
+            &docker_network_create_command.network_name,
+        ]);
+        command
+    }
+}
+
+/// Represents a `docker network rm` command.
+#[derive(Clone, Debug)]
+pub(crate) struct DockerNetworkRemoveCommand {
+    network_name: String,
+}
+
+impl DockerNetworkRemoveCommand {
+    pub(crate) fn new(network_name: impl Into<String>) -> Self {
+        Self {
+            network_name: network_name.into(),
+        }
+    }
+}
+
+impl From<DockerNetworkRemoveCommand> for Command {
+    fn from(docker_network_remove_command: DockerNetworkRemoveCommand) -> Self {
+        let mut command = Command::new("docker");
+        command.args(["network", "rm", &docker_network_remove_command.network_name]);
+        command
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -332,10 +521,14 @@ mod tests {
         docker_run_command.env("FOO", "1");
         docker_run_command.expose_port(12345);
         docker_run_command.expose_port(55555);
+        docker_run_command.memory("512m");
+        docker_run_command.network("my-network");
+        docker_run_command.network_alias("postgres");
         docker_run_command.platform("linux/amd64");
         docker_run_command.remove(true);
         docker_run_command.bind_mount(PathBuf::from("./test-cache"), PathBuf::from("/cache"));
         docker_run_command.bind_mount("foo", "/bar");
+        docker_run_command.additional_arg("--cpus=1");
 
         let command: Command = docker_run_command.clone().into();
         assert_eq!(
@@ -348,6 +541,12 @@ mod tests {
                 "--rm",
                 "--platform",
                 "linux/amd64",
+                "--memory",
+                "512m",
+                "--network",
+                "my-network",
+                "--network-alias",
+                "postgres",
                 "--entrypoint",
                 "/usr/bin/bash",
                 "--env",
@@ -362,11 +561,30 @@ mod tests {
                 "type=bind,source=./test-cache,target=/cache",
                 "--mount",
                 "type=bind,source=foo,target=/bar",
+                "--cpus=1",
                 "my-image",
                 "echo",
                 "hello",
             ]
         );
+
+        // With ports published on all interfaces, for remote Docker daemons
+        let mut docker_run_command = DockerRunCommand::new("my-image", "my-container");
+        docker_run_command.expose_port(12345);
+        docker_run_command.publish_on_all_interfaces(true);
+
+        let command: Command = docker_run_command.into();
+        assert_eq!(
+            command.get_args().collect::<Vec<&OsStr>>(),
+            [
+                "run",
+                "--name",
+                "my-container",
+                "--publish",
+                "12345",
+                "my-image"
+            ]
+        );
     }
 
     #[test]
@@ -402,6 +620,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn from_docker_image_inspect_command_to_command() {
+        let mut docker_image_inspect_command = DockerImageInspectCommand::new("my-image");
+
+        // Default usage
+        let command: Command = docker_image_inspect_command.clone().into();
+        assert_eq!(command.get_program(), "docker");
+        assert_eq!(
+            command.get_args().collect::<Vec<&OsStr>>(),
+            ["image", "inspect", "my-image"]
+        );
+
+        // With optional flag/arguments set
+        docker_image_inspect_command.format("{{json .Config.Labels}}");
+
+        let command: Command = docker_image_inspect_command.clone().into();
+        assert_eq!(
+            command.get_args().collect::<Vec<&OsStr>>(),
+            [
+                "image",
+                "inspect",
+                "--format",
+                "{{json .Config.Labels}}",
+                "my-image"
+            ]
+        );
+    }
+
+    #[test]
+    fn from_docker_pull_command_to_command() {
+        let command: Command = DockerPullCommand::new("heroku/buildpack-java:6.0.0").into();
+        assert_eq!(command.get_program(), "docker");
+        assert_eq!(
+            command.get_args().collect::<Vec<&OsStr>>(),
+            ["pull", "--quiet", "heroku/buildpack-java:6.0.0"]
+        );
+    }
+
+    #[test]
+    fn from_docker_context_inspect_command_to_command() {
+        let command: Command = DockerContextInspectCommand::new().into();
+        assert_eq!(command.get_program(), "docker");
+        assert_eq!(
+            command.get_args().collect::<Vec<&OsStr>>(),
+            [
+                "context",
+                "inspect",
+                "--format",
+                "{{.Endpoints.docker.Host}}"
+            ]
+        );
+    }
+
     #[test]
     fn from_docker_port_command_to_command() {
         let docker_port_command = DockerPortCommand::new("my-container", 12345);
@@ -445,4 +716,26 @@ mod tests {
             ["volume", "remove", "volume1", "volume2", "--force"]
         );
     }
+
+    #[test]
+    fn from_docker_network_create_command_to_command() {
+        let docker_network_create_command = DockerNetworkCreateCommand::new("my-network");
+        let command: Command = docker_network_create_command.into();
+        assert_eq!(command.get_program(), "docker");
+        assert_eq!(
+            command.get_args().collect::<Vec<&OsStr>>(),
+            ["network", "create", "my-network"]
+        );
+    }
+
+    #[test]
+    fn from_docker_network_remove_command_to_command() {
+        let docker_network_remove_command = DockerNetworkRemoveCommand::new("my-network");
+        let command: Command = docker_network_remove_command.into();
+        assert_eq!(command.get_program(), "docker");
+        assert_eq!(
+            command.get_args().collect::<Vec<&OsStr>>(),
+            ["network", "rm", "my-network"]
+        );
+    }
 }
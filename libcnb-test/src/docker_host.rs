@@ -0,0 +1,130 @@
+//! Detection of the Docker daemon the tests run against.
+//!
+//! The Docker CLI and Pack connect to the daemon configured via the `DOCKER_HOST` environment
+//! variable or, if it isn't set, the current Docker context. When that daemon runs on another
+//! machine (i.e. `ssh://user@example.com`), published container ports are only reachable via the
+//! remote host and not via `127.0.0.1`.
+
+use crate::docker::DockerContextInspectCommand;
+use crate::util;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+
+/// The endpoint of the Docker daemon, as configured by the user.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct DockerEndpoint {
+    /// The daemon URL, i.e. `unix:///var/run/docker.sock` or `ssh://user@example.com`.
+    pub(crate) url: Option<String>,
+    /// Whether the URL was read from the current Docker context rather than `DOCKER_HOST`.
+    pub(crate) from_context: bool,
+}
+
+impl DockerEndpoint {
+    /// Returns the endpoint of the Docker daemon used by the Docker CLI.
+    ///
+    /// The endpoint is determined once and then cached for the lifetime of the test process.
+    pub(crate) fn current() -> &'static Self {
+        static CURRENT: OnceLock<DockerEndpoint> = OnceLock::new();
+        CURRENT.get_or_init(Self::detect)
+    }
+
+    fn detect() -> Self {
+        if let Some(url) = std::env::var("DOCKER_HOST")
+            .ok()
+            .filter(|url| !url.is_empty())
+        {
+            return Self {
+                url: Some(url),
+                from_context: false,
+            };
+        }
+
+        // The Docker CLI itself takes `DOCKER_CONTEXT` into account when inspecting the context.
+        // Older Docker versions without support for contexts always use the default socket.
+        let url = util::run_command(DockerContextInspectCommand::new())
+            .ok()
+            .map(|output| output.stdout.trim().to_string())
+            .filter(|url| !url.is_empty());
+
+        Self {
+            url,
+            from_context: true,
+        }
+    }
+
+    /// Returns the host name of the machine the Docker daemon runs on, if it isn't the local one.
+    pub(crate) fn remote_host(&self) -> Option<&str> {
+        self.url.as_deref().and_then(remote_host)
+    }
+}
+
+fn remote_host(url: &str) -> Option<&str> {
+    let (scheme, rest) = url.split_once("://")?;
+
+    // Other schemes, such as `unix://` and `npipe://`, always refer to the local machine.
+    if !matches!(scheme, "tcp" | "ssh" | "http" | "https") {
+        return None;
+    }
+
+    let authority = rest.split('/').next().unwrap_or_default();
+    let host_and_port = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_user_info, host_and_port)| host_and_port);
+
+    let host = match host_and_port.strip_prefix('[') {
+        Some(bracketed_host) => bracketed_host.split(']').next()?,
+        None => host_and_port.split(':').next()?,
+    };
+
+    let is_local = host.is_empty()
+        || host == "localhost"
+        || host
+            .parse::<IpAddr>()
+            .is_ok_and(|ip_addr| ip_addr.is_loopback() || ip_addr.is_unspecified());
+
+    (!is_local).then_some(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remote_host_from_url() {
+        assert_eq!(remote_host("ssh://example.com"), Some("example.com"));
+        assert_eq!(
+            remote_host("ssh://user@build-host.example.com:2222"),
+            Some("build-host.example.com")
+        );
+        assert_eq!(remote_host("tcp://10.0.0.5:2376"), Some("10.0.0.5"));
+        assert_eq!(remote_host("tcp://[fd00::5]:2376"), Some("fd00::5"));
+        assert_eq!(
+            remote_host("https://docker.example.com/path"),
+            Some("docker.example.com")
+        );
+    }
+
+    #[test]
+    fn remote_host_for_local_daemons() {
+        assert_eq!(remote_host("unix:///var/run/docker.sock"), None);
+        assert_eq!(remote_host("npipe:////./pipe/docker_engine"), None);
+        assert_eq!(remote_host("tcp://127.0.0.1:2375"), None);
+        assert_eq!(remote_host("tcp://localhost:2375"), None);
+        assert_eq!(remote_host("tcp://[::1]:2375"), None);
+        assert_eq!(remote_host("ssh://user@localhost"), None);
+        assert_eq!(remote_host("/var/run/docker.sock"), None);
+    }
+
+    #[test]
+    fn remote_host_of_endpoint() {
+        assert_eq!(DockerEndpoint::default().remote_host(), None);
+        assert_eq!(
+            DockerEndpoint {
+                url: Some(String::from("ssh://user@example.com")),
+                from_context: true,
+            }
+            .remote_host(),
+            Some("example.com")
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use libcnb_data::buildpack::BuildpackId;
+
+/// A Dockerfile generated by an image extension during the build, as returned by
+/// [`TestContext::generated_dockerfiles`](crate::TestContext::generated_dockerfiles).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GeneratedDockerfile {
+    /// The ID of the extension that generated the Dockerfile. Extension IDs follow the same rules
+    /// as buildpack IDs.
+    pub extension_id: BuildpackId,
+    pub kind: DockerfileKind,
+    pub contents: String,
+}
+
+/// The image a generated Dockerfile extends.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DockerfileKind {
+    /// `build.Dockerfile`, which extends the build image.
+    Build,
+    /// `run.Dockerfile`, which extends or switches the run image.
+    Run,
+}
+
+impl DockerfileKind {
+    fn from_file_name(file_name: &str) -> Option<Self> {
+        match file_name {
+            "build.Dockerfile" => Some(Self::Build),
+            "run.Dockerfile" => Some(Self::Run),
+            _ => None,
+        }
+    }
+}
+
+/// Parses the generated Dockerfiles the libcnb extension runtime printed to the given `pack`
+/// output.
+pub(crate) fn parse_generated_dockerfiles(pack_stdout: &str) -> Vec<GeneratedDockerfile> {
+    libcnb_common::generated_dockerfile::parse_generated_dockerfiles(pack_stdout)
+        .into_iter()
+        .filter_map(|(extension_id, file_name, contents)| {
+            Some(GeneratedDockerfile {
+                extension_id: extension_id.parse().ok()?,
+                kind: DockerfileKind::from_file_name(&file_name)?,
+                contents,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libcnb_common::generated_dockerfile::format_generated_dockerfile;
+    use libcnb_data::buildpack_id;
+
+    #[test]
+    fn parse_generated_dockerfiles_from_pack_output() {
+        let pack_stdout = format!(
+            "===> GENERATING\n{}{}{}===> EXTENDING (BUILD)\n",
+            format_generated_dockerfile("foo/bar", "build.Dockerfile", "FROM base\n"),
+            format_generated_dockerfile("foo/bar", "run.Dockerfile", "FROM run-base\n"),
+            format_generated_dockerfile("foo/bar", "other.Dockerfile", "FROM other\n"),
+        );
+
+        assert_eq!(
+            parse_generated_dockerfiles(&pack_stdout),
+            vec![
+                GeneratedDockerfile {
+                    extension_id: buildpack_id!("foo/bar"),
+                    kind: DockerfileKind::Build,
+                    contents: String::from("FROM base\n"),
+                },
+                GeneratedDockerfile {
+                    extension_id: buildpack_id!("foo/bar"),
+                    kind: DockerfileKind::Run,
+                    contents: String::from("FROM run-base\n"),
+                }
+            ]
+        );
+    }
+}
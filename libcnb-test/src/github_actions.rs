@@ -0,0 +1,270 @@
+//! Annotations for test failures when running on GitHub Actions.
+//!
+//! GitHub Actions parses [workflow commands](https://docs.github.com/en/actions/using-workflows/workflow-commands-for-github-actions)
+//! such as `::error file=tests/integration_test.rs,line=42::Message` from the output of a step and
+//! shows them as annotations on the workflow run summary and pull request diff. With dozens of
+//! Docker-backed tests, this makes it much easier to spot which test failed and why.
+//!
+//! Annotations are written directly to the process' stdout, since the output of tests is captured
+//! by the test harness and only printed after all tests finished.
+
+use crate::log::LogOutput;
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write;
+use std::panic::Location;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+
+/// Set by GitHub Actions to `true` for all steps of a workflow run.
+const GITHUB_ACTIONS_ENV_VAR_NAME: &str = "GITHUB_ACTIONS";
+
+/// Directory the output of failed builds is written to, i.e. to upload it as workflow artifacts.
+pub(crate) const ARTIFACTS_DIR_ENV_VAR_NAME: &str = "LIBCNB_TEST_ARTIFACTS_DIR";
+
+static INSTALL_PANIC_HOOK: Once = Once::new();
+
+thread_local! {
+    static TEST_RUNS: RefCell<Vec<TestRun>> = const { RefCell::new(Vec::new()) };
+}
+
+struct TestRun {
+    caller: &'static Location<'static>,
+    image_name: String,
+    pack_output: Option<String>,
+}
+
+pub(crate) fn enabled() -> bool {
+    std::env::var(GITHUB_ACTIONS_ENV_VAR_NAME).is_ok_and(|value| value == "true")
+}
+
+/// Tracks an integration test run on the current thread until the returned guard is dropped.
+///
+/// Panics on the current thread while the run is tracked are reported as error annotations. The
+/// given caller location is used for panics that originate in libcnb-test itself.
+pub(crate) fn start_test_run(
+    caller: &'static Location<'static>,
+    image_name: &str,
+) -> Option<TestRunGuard> {
+    if !enabled() {
+        return None;
+    }
+
+    INSTALL_PANIC_HOOK.call_once(|| {
+        let previous_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |panic_info| {
+            annotate_panic(panic_info.location(), panic_info.payload());
+            previous_hook(panic_info);
+        }));
+    });
+
+    TEST_RUNS.with_borrow_mut(|test_runs| {
+        test_runs.push(TestRun {
+            caller,
+            image_name: image_name.to_string(),
+            pack_output: None,
+        });
+    });
+
+    Some(TestRunGuard)
+}
+
+/// Records the output of the Pack build of the current test run, so that it can be written to the
+/// artifacts directory if the test fails.
+pub(crate) fn record_pack_output(log_output: &LogOutput) {
+    TEST_RUNS.with_borrow_mut(|test_runs| {
+        if let Some(test_run) = test_runs.last_mut() {
+            test_run.pack_output = Some(log_output.to_string());
+        }
+    });
+}
+
+/// Emits a warning annotation without a file location.
+pub(crate) fn warning(message: &str) {
+    if enabled() {
+        write_annotation(&Annotation {
+            level: AnnotationLevel::Warning,
+            location: None,
+            title: "libcnb-test",
+            message,
+        });
+    }
+}
+
+pub(crate) struct TestRunGuard;
+
+impl Drop for TestRunGuard {
+    fn drop(&mut self) {
+        TEST_RUNS.with_borrow_mut(Vec::pop);
+    }
+}
+
+fn annotate_panic(panic_location: Option<&Location>, payload: &(dyn Any + Send)) {
+    let Some((caller, image_name, pack_output)) = TEST_RUNS
+        .try_with(|test_runs| {
+            test_runs.try_borrow().ok().and_then(|test_runs| {
+                // Attribute the failure to the outermost test run, which is the one the test
+                // function started.
+                let first = test_runs.first()?;
+                let last = test_runs.last()?;
+                Some((
+                    first.caller,
+                    last.image_name.clone(),
+                    last.pack_output.clone(),
+                ))
+            })
+        })
+        .ok()
+        .flatten()
+    else {
+        return;
+    };
+
+    let panic_message = payload
+        .downcast_ref::<&str>()
+        .map(ToString::to_string)
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("Test panicked"));
+
+    let (file, line) = panic_location
+        .filter(|location| !is_libcnb_test_source(Path::new(location.file())))
+        .map_or((caller.file(), caller.line()), |location| {
+            (location.file(), location.line())
+        });
+
+    let mut message = panic_message.clone();
+
+    if let Some(artifacts_dir) = std::env::var_os(ARTIFACTS_DIR_ENV_VAR_NAME) {
+        let log_path = PathBuf::from(artifacts_dir).join(format!("{image_name}.log"));
+        let log_contents = format!("{panic_message}\n\n{}", pack_output.unwrap_or_default());
+
+        if log_path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&log_path, log_contents))
+            .is_ok()
+        {
+            let _ = write!(message, "\n\nBuild output: {}", log_path.display());
+        }
+    }
+
+    if let Some(run_url) = workflow_run_url() {
+        let _ = write!(message, "\n\nWorkflow run: {run_url}");
+    }
+
+    write_annotation(&Annotation {
+        level: AnnotationLevel::Error,
+        location: Some((file, line)),
+        title: "libcnb-test",
+        message: &message,
+    });
+}
+
+fn is_libcnb_test_source(path: &Path) -> bool {
+    Path::new(file!())
+        .parent()
+        .is_some_and(|source_dir| path.starts_with(source_dir))
+}
+
+fn workflow_run_url() -> Option<String> {
+    let server_url = std::env::var("GITHUB_SERVER_URL").ok()?;
+    let repository = std::env::var("GITHUB_REPOSITORY").ok()?;
+    let run_id = std::env::var("GITHUB_RUN_ID").ok()?;
+
+    Some(format!("{server_url}/{repository}/actions/runs/{run_id}"))
+}
+
+fn write_annotation(annotation: &Annotation) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", annotation.to_workflow_command());
+    let _ = stdout.flush();
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum AnnotationLevel {
+    Error,
+    Warning,
+}
+
+struct Annotation<'a> {
+    level: AnnotationLevel,
+    location: Option<(&'a str, u32)>,
+    title: &'a str,
+    message: &'a str,
+}
+
+impl Annotation<'_> {
+    fn to_workflow_command(&self) -> String {
+        let command = match self.level {
+            AnnotationLevel::Error => "error",
+            AnnotationLevel::Warning => "warning",
+        };
+
+        let mut properties = Vec::new();
+        if let Some((file, line)) = self.location {
+            properties.push(format!("file={}", escape_property(file)));
+            properties.push(format!("line={line}"));
+        }
+        properties.push(format!("title={}", escape_property(self.title)));
+
+        format!(
+            "::{command} {}::{}",
+            properties.join(","),
+            escape_data(self.message)
+        )
+    }
+}
+
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_annotation_with_location() {
+        assert_eq!(
+            Annotation {
+                level: AnnotationLevel::Error,
+                location: Some(("tests/integration_test.rs", 42)),
+                title: "libcnb-test",
+                message: "Assertion failed: 100% broken\n\n## stderr:\n",
+            }
+            .to_workflow_command(),
+            "::error file=tests/integration_test.rs,line=42,title=libcnb-test::Assertion failed: 100%25 broken%0A%0A## stderr:%0A"
+        );
+    }
+
+    #[test]
+    fn warning_annotation_without_location() {
+        assert_eq!(
+            Annotation {
+                level: AnnotationLevel::Warning,
+                location: None,
+                title: "a, b: c",
+                message: "Couldn't remove image",
+            }
+            .to_workflow_command(),
+            "::warning title=a%2C b%3A c::Couldn't remove image"
+        );
+    }
+
+    #[test]
+    fn libcnb_test_sources() {
+        assert!(is_libcnb_test_source(Path::new(file!())));
+        assert!(!is_libcnb_test_source(Path::new(
+            "tests/integration_test.rs"
+        )));
+    }
+}
@@ -0,0 +1,256 @@
+use crate::util;
+use libcnb_common::toml_file::{read_toml_file, TomlFileError};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::process::Command;
+
+/// Pinned versions of the external tools and images used by integration tests.
+///
+/// Used by [`TestRunner::hermetic`](crate::TestRunner::hermetic) to make integration test results
+/// reproducible across developer machines and CI. The configuration is usually checked into the
+/// repository as a TOML file:
+///
+/// ```toml
+/// pack-version = "0.36.0"
+///
+/// [builders."heroku/builder:24"]
+/// digest = "sha256:3f5c21a1e5bd0ea28b9d8fcec4c8c4ba12e2a0f1f1de4bc2b2ed2a3b1c0e4d5a"
+/// run-image = "heroku/heroku:24"
+/// run-image-digest = "sha256:8e07f2d1ce0e2a6a1e8d9f4c3b2a1d0e9f8c7b6a5d4e3f2a1b0c9d8e7f6a5b4c"
+/// ```
+///
+/// Builders used by a test must be pinned, pinning the run image is optional. Since the builder
+/// image contains the lifecycle, pinning the builder digest also pins the lifecycle version.
+#[derive(Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct HermeticConfig {
+    /// The required version of the `pack` CLI, i.e. `0.36.0`.
+    pub pack_version: Option<String>,
+    /// The pinned builders, keyed by the builder name used in [`BuildConfig`](crate::BuildConfig).
+    #[serde(default)]
+    pub builders: BTreeMap<String, PinnedBuilder>,
+}
+
+/// A builder image pinned to a specific digest.
+#[derive(Deserialize, Debug, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct PinnedBuilder {
+    /// The digest of the builder image, i.e. `sha256:...`.
+    pub digest: String,
+    /// The run image to use instead of the run image configured in the builder.
+    pub run_image: Option<String>,
+    /// The digest of the run image, i.e. `sha256:...`. Requires `run-image` to be set.
+    pub run_image_digest: Option<String>,
+}
+
+impl PinnedBuilder {
+    /// The image reference of the builder, pinned to its digest.
+    pub(crate) fn builder_reference(&self, builder_name: &str) -> String {
+        format!("{builder_name}@{}", self.digest)
+    }
+
+    /// The image reference of the run image, pinned to its digest if configured.
+    pub(crate) fn run_image_reference(&self) -> Option<String> {
+        self.run_image
+            .as_ref()
+            .map(|run_image| match &self.run_image_digest {
+                Some(digest) => format!("{run_image}@{digest}"),
+                None => run_image.clone(),
+            })
+    }
+}
+
+impl HermeticConfig {
+    /// Reads the configuration from the given TOML file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or isn't a valid configuration.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, HermeticConfigError> {
+        let config =
+            read_toml_file::<Self>(path.as_ref()).map_err(HermeticConfigError::CannotReadConfig)?;
+
+        match config
+            .builders
+            .iter()
+            .find(|(_, builder)| builder.run_image.is_none() && builder.run_image_digest.is_some())
+        {
+            Some((builder_name, _)) => Err(HermeticConfigError::RunImageDigestWithoutRunImage(
+                builder_name.clone(),
+            )),
+            None => Ok(config),
+        }
+    }
+
+    /// Returns the pinned builder with the given name.
+    pub(crate) fn pinned_builder(
+        &self,
+        builder_name: &str,
+    ) -> Result<&PinnedBuilder, HermeticConfigError> {
+        self.builders
+            .get(builder_name)
+            .ok_or_else(|| HermeticConfigError::UnpinnedBuilder(builder_name.to_string()))
+    }
+
+    /// Verifies that the locally installed tools match the pinned versions.
+    pub(crate) fn verify_environment(&self) -> Result<(), HermeticConfigError> {
+        if let Some(expected) = &self.pack_version {
+            let output = util::run_command(pack_version_command()).map_err(|error| {
+                HermeticConfigError::CannotDeterminePackVersion(error.to_string())
+            })?;
+
+            let actual = parse_pack_version(&output.stdout);
+            if actual != expected {
+                return Err(HermeticConfigError::PackVersionMismatch {
+                    expected: expected.clone(),
+                    actual: actual.to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for HermeticConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "pack version: {}",
+            self.pack_version.as_deref().unwrap_or("<not pinned>")
+        )?;
+
+        for (builder_name, builder) in &self.builders {
+            writeln!(
+                f,
+                "builder: {} (run image: {})",
+                builder.builder_reference(builder_name),
+                builder
+                    .run_image_reference()
+                    .as_deref()
+                    .unwrap_or("<from builder>")
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn pack_version_command() -> Command {
+    let mut command = Command::new("pack");
+    command.arg("--version");
+    command
+}
+
+// Pack reports versions with build metadata, i.e. `0.36.0+git-1f8c2e1.build-6125`.
+fn parse_pack_version(output: &str) -> &str {
+    let output = output.trim();
+    output
+        .split_once('+')
+        .map_or(output, |(version, _)| version)
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum HermeticConfigError {
+    #[error("Couldn't read hermetic test configuration: {0}")]
+    CannotReadConfig(TomlFileError),
+
+    #[error("Builder {0} has a run-image-digest but no run-image")]
+    RunImageDigestWithoutRunImage(String),
+
+    #[error("Builder {0} is not pinned in the hermetic test configuration")]
+    UnpinnedBuilder(String),
+
+    #[error("Couldn't determine pack version: {0}")]
+    CannotDeterminePackVersion(String),
+
+    #[error("Expected pack version {expected}, but found {actual}")]
+    PackVersionMismatch { expected: String, actual: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn from_path() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("libcnb-test.toml");
+        fs::write(
+            &path,
+            r#"
+pack-version = "0.36.0"
+
+[builders."heroku/builder:24"]
+digest = "sha256:aaaa"
+run-image = "heroku/heroku:24"
+run-image-digest = "sha256:bbbb"
+
+[builders."heroku/builder:22"]
+digest = "sha256:cccc"
+"#,
+        )
+        .unwrap();
+
+        let config = HermeticConfig::from_path(&path).unwrap();
+        assert_eq!(config.pack_version.as_deref(), Some("0.36.0"));
+
+        let builder = config.pinned_builder("heroku/builder:24").unwrap();
+        assert_eq!(
+            builder.builder_reference("heroku/builder:24"),
+            "heroku/builder:24@sha256:aaaa"
+        );
+        assert_eq!(
+            builder.run_image_reference().as_deref(),
+            Some("heroku/heroku:24@sha256:bbbb")
+        );
+
+        let builder = config.pinned_builder("heroku/builder:22").unwrap();
+        assert_eq!(builder.run_image_reference(), None);
+
+        assert!(matches!(
+            config.pinned_builder("heroku/builder:20"),
+            Err(HermeticConfigError::UnpinnedBuilder(_))
+        ));
+
+        assert_eq!(
+            config.to_string(),
+            "pack version: 0.36.0\nbuilder: heroku/builder:22@sha256:cccc (run image: <from builder>)\nbuilder: heroku/builder:24@sha256:aaaa (run image: heroku/heroku:24@sha256:bbbb)\n"
+        );
+    }
+
+    #[test]
+    fn from_path_invalid() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("libcnb-test.toml");
+
+        fs::write(
+            &path,
+            "[builders.\"heroku/builder:24\"]\ndigest = \"sha256:aaaa\"\nrun-image-digest = \"sha256:bbbb\"\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            HermeticConfig::from_path(&path),
+            Err(HermeticConfigError::RunImageDigestWithoutRunImage(_))
+        ));
+
+        fs::write(&path, "lifecycle = \"0.20.0\"\n").unwrap();
+        assert!(matches!(
+            HermeticConfig::from_path(&path),
+            Err(HermeticConfigError::CannotReadConfig(_))
+        ));
+    }
+
+    #[test]
+    fn parse_pack_versions() {
+        assert_eq!(
+            parse_pack_version("0.36.0+git-1f8c2e1.build-6125\n"),
+            "0.36.0"
+        );
+        assert_eq!(parse_pack_version("0.36.0\n"), "0.36.0");
+    }
+}
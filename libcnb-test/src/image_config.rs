@@ -0,0 +1,216 @@
+use libcnb_data::image_label::{
+    BuildMetadata, LifecycleMetadata, ParseImageLabelError, ProjectMetadata, BUILD_METADATA_LABEL,
+    LIFECYCLE_METADATA_LABEL, PROJECT_METADATA_LABEL,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// The configuration of a built image, as returned by [`TestContext::image_config`].
+///
+/// Use [`assert_image_env!`](crate::assert_image_env) and
+/// [`assert_image_label!`](crate::assert_image_label) to assert on environment variables and
+/// labels.
+///
+/// [`TestContext::image_config`]: crate::TestContext::image_config
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ImageConfig {
+    pub labels: BTreeMap<String, String>,
+    pub env: BTreeMap<String, String>,
+    pub entrypoint: Vec<String>,
+    pub cmd: Vec<String>,
+    pub working_dir: String,
+    pub user: String,
+}
+
+impl ImageConfig {
+    /// Parses the image configuration from the JSON output of
+    /// `docker image inspect --format '{{json .Config}}'`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the given data isn't a valid image configuration.
+    ///
+    /// # Example
+    /// ```
+    /// use libcnb_test::ImageConfig;
+    ///
+    /// let image_config = ImageConfig::from_docker_inspect_json(
+    ///     r#"{"Env": ["PORT=8080"], "Entrypoint": ["/cnb/process/web"], "WorkingDir": "/workspace"}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(image_config.env["PORT"], "8080");
+    /// assert_eq!(image_config.entrypoint, ["/cnb/process/web"]);
+    /// assert_eq!(image_config.working_dir, "/workspace");
+    /// ```
+    pub fn from_docker_inspect_json(json: &str) -> Result<Self, serde_json::Error> {
+        let docker_config = serde_json::from_str::<DockerImageConfig>(json)?;
+
+        Ok(Self {
+            labels: docker_config.labels.unwrap_or_default(),
+            env: docker_config
+                .env
+                .unwrap_or_default()
+                .into_iter()
+                .map(|entry| match entry.split_once('=') {
+                    Some((name, value)) => (String::from(name), String::from(value)),
+                    None => (entry, String::new()),
+                })
+                .collect(),
+            entrypoint: docker_config.entrypoint.unwrap_or_default(),
+            cmd: docker_config.cmd.unwrap_or_default(),
+            working_dir: docker_config.working_dir.unwrap_or_default(),
+            user: docker_config.user.unwrap_or_default(),
+        })
+    }
+
+    /// Returns the build metadata (processes, buildpacks, ...) the CNB lifecycle stored in the
+    /// `io.buildpacks.build.metadata` label.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the label is missing or invalid.
+    pub fn build_metadata(&self) -> Result<BuildMetadata, ImageConfigLabelError> {
+        self.parse_label(BUILD_METADATA_LABEL)
+    }
+
+    /// Returns the lifecycle metadata (exported layers, run image, ...) the CNB lifecycle stored
+    /// in the `io.buildpacks.lifecycle.metadata` label.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the label is missing or invalid.
+    pub fn lifecycle_metadata(&self) -> Result<LifecycleMetadata, ImageConfigLabelError> {
+        self.parse_label(LIFECYCLE_METADATA_LABEL)
+    }
+
+    /// Returns the project metadata (app source) the CNB lifecycle stored in the
+    /// `io.buildpacks.project.metadata` label.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the label is missing or invalid.
+    pub fn project_metadata(&self) -> Result<ProjectMetadata, ImageConfigLabelError> {
+        self.parse_label(PROJECT_METADATA_LABEL)
+    }
+
+    /// Returns the type of the default process of the image.
+    ///
+    /// The CNB lifecycle sets the entrypoint of images with a default process to the
+    /// `/cnb/process/<type>` launcher symlink. Returns `None` if the image has no default process.
+    ///
+    /// # Example
+    /// ```
+    /// use libcnb_test::ImageConfig;
+    ///
+    /// let image_config =
+    ///     ImageConfig::from_docker_inspect_json(r#"{"Entrypoint": ["/cnb/process/web"]}"#)
+    ///         .unwrap();
+    ///
+    /// assert_eq!(image_config.default_process_type(), Some("web"));
+    /// ```
+    #[must_use]
+    pub fn default_process_type(&self) -> Option<&str> {
+        self.entrypoint
+            .first()
+            .and_then(|entrypoint| entrypoint.strip_prefix(CNB_PROCESS_DIR))
+            .filter(|process_type| !process_type.is_empty())
+    }
+
+    fn parse_label<T: FromStr<Err = ParseImageLabelError>>(
+        &self,
+        label: &str,
+    ) -> Result<T, ImageConfigLabelError> {
+        self.labels
+            .get(label)
+            .ok_or_else(|| ImageConfigLabelError::MissingLabel(String::from(label)))?
+            .parse()
+            .map_err(|error| ImageConfigLabelError::InvalidLabel(String::from(label), error))
+    }
+}
+
+/// The directory in CNB images that contains a launcher symlink for each process type.
+pub(crate) const CNB_PROCESS_DIR: &str = "/cnb/process/";
+
+#[derive(thiserror::Error, Debug)]
+pub enum ImageConfigLabelError {
+    #[error("Image is missing label {0}")]
+    MissingLabel(String),
+    #[error("Error parsing image label {0}: {1}")]
+    InvalidLabel(String, ParseImageLabelError),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DockerImageConfig {
+    labels: Option<BTreeMap<String, String>>,
+    env: Option<Vec<String>>,
+    entrypoint: Option<Vec<String>>,
+    cmd: Option<Vec<String>>,
+    working_dir: Option<String>,
+    user: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_docker_inspect_json() {
+        let image_config = ImageConfig::from_docker_inspect_json(
+            r#"{
+              "Hostname": "",
+              "User": "1000:1000",
+              "Env": ["PATH=/cnb/process:/usr/bin", "CNB_APP_DIR=/workspace", "EMPTY="],
+              "Cmd": null,
+              "WorkingDir": "/workspace",
+              "Entrypoint": ["/cnb/process/web"],
+              "Labels": {
+                "io.buildpacks.build.metadata": "{\"processes\": [{\"type\": \"web\", \"command\": [\"bash\"], \"direct\": false, \"buildpackID\": \"heroku/procfile\"}], \"buildpacks\": []}",
+                "io.buildpacks.lifecycle.metadata": "not json"
+              }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(image_config.user, "1000:1000");
+        assert_eq!(
+            image_config.env,
+            BTreeMap::from([
+                (String::from("CNB_APP_DIR"), String::from("/workspace")),
+                (String::from("EMPTY"), String::new()),
+                (String::from("PATH"), String::from("/cnb/process:/usr/bin")),
+            ])
+        );
+        assert!(image_config.cmd.is_empty());
+        assert_eq!(image_config.working_dir, "/workspace");
+        assert_eq!(image_config.entrypoint, ["/cnb/process/web"]);
+        assert_eq!(image_config.default_process_type(), Some("web"));
+
+        assert_eq!(
+            image_config.build_metadata().unwrap().processes[0].r#type,
+            "web"
+        );
+        assert!(matches!(
+            image_config.lifecycle_metadata(),
+            Err(ImageConfigLabelError::InvalidLabel(label, _)) if label == LIFECYCLE_METADATA_LABEL
+        ));
+        assert!(matches!(
+            image_config.project_metadata(),
+            Err(ImageConfigLabelError::MissingLabel(label)) if label == PROJECT_METADATA_LABEL
+        ));
+    }
+
+    #[test]
+    fn from_docker_inspect_json_without_values() {
+        assert_eq!(
+            ImageConfig::from_docker_inspect_json(
+                r#"{"Env": null, "Labels": null, "Entrypoint": null}"#
+            )
+            .unwrap(),
+            ImageConfig::default()
+        );
+        assert_eq!(ImageConfig::default().default_process_type(), None);
+    }
+}
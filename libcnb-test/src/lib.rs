@@ -1,22 +1,45 @@
 #![doc = include_str!("../README.md")]
 
 mod app;
+mod assertion;
 mod build;
 mod build_config;
+mod cache_modification;
 mod container_config;
 mod container_context;
 mod docker;
+mod docker_host;
+mod generated_dockerfile;
+mod github_actions;
+mod hermetic;
+mod image_config;
+#[cfg(feature = "lifecycle-driver")]
+mod lifecycle_driver;
 mod log;
 mod macros;
 mod pack;
+mod package_context;
+mod sbom;
+mod service_config;
+mod snapshot;
 mod test_context;
 mod test_runner;
 mod util;
 
+pub use crate::assertion::*;
 pub use crate::build_config::*;
+pub use crate::cache_modification::CachedLayerModification;
 pub use crate::container_config::*;
 pub use crate::container_context::*;
+pub use crate::generated_dockerfile::*;
+pub use crate::hermetic::*;
+pub use crate::image_config::*;
+#[cfg(feature = "lifecycle-driver")]
+pub use crate::lifecycle_driver::*;
 pub use crate::log::*;
+pub use crate::package_context::*;
+pub use crate::sbom::*;
+pub use crate::service_config::*;
 pub use crate::test_context::*;
 pub use crate::test_runner::*;
 
@@ -25,7 +48,5 @@ pub use crate::test_runner::*;
 use indoc as _;
 #[cfg(test)]
 use libcnb as _;
-#[cfg(test)]
-use ureq as _;
 // This dependency is used by the `assert_not_contains` and `assert_not_contains_match` macros
 use regex as _;
@@ -0,0 +1,527 @@
+use crate::app::{self, AppDir};
+use crate::test_runner::cargo_manifest_dir;
+use crate::util::{self, CommandError};
+use crate::{LogOutput, PackagedBuildpack};
+use libcnb_common::toml_file::{read_toml_file, write_toml_file};
+use libcnb_data::buildpack::{BuildpackDescriptor, BuildpackId};
+use libcnb_data::launch::Launch;
+use libcnb_data::layer::LayerName;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::{tempdir, TempDir};
+
+/// The exit code of `bin/detect` for buildpacks that don't pass detection.
+const DETECT_FAIL_EXIT_CODE: i32 = 100;
+
+/// Runs the detect and build phases of packaged buildpacks directly on the host, without `pack`
+/// or Docker.
+///
+/// This covers tests that only need the layers, environment files and `launch.toml` a buildpack
+/// writes, and runs them much faster than [`TestRunner::build`](crate::TestRunner::build). Use
+/// [`TestRunner::package_only`](crate::TestRunner::package_only) to package the buildpacks.
+///
+/// The driver implements only a minimal subset of the CNB lifecycle:
+/// - All buildpacks must pass detection. Only the primary build plan of each buildpack is
+///   resolved, `or` alternatives are ignored.
+/// - The environment of layers created by earlier buildpacks isn't applied to later buildpacks.
+/// - Composite buildpacks, image extensions, caching and exporting images aren't supported.
+///
+/// Since the buildpack binaries are executed on the host, the `target_triple` of the
+/// [`BuildConfig`](crate::BuildConfig) must be runnable on the host, i.e. the default
+/// `x86_64-unknown-linux-musl` on Linux AMD64 machines.
+///
+/// Requires the `lifecycle-driver` feature.
+///
+/// # Example
+/// ```no_run
+/// use libcnb_data::{buildpack_id, layer_name};
+/// use libcnb_test::{BuildConfig, BuildpackReference, LifecycleDriver, TestRunner};
+///
+/// TestRunner::default().package_only(
+///     BuildConfig::new("heroku/builder:24", "tests/fixtures/app")
+///         .buildpacks([BuildpackReference::CurrentCrate]),
+///     |context| {
+///         LifecycleDriver::new("tests/fixtures/app")
+///             .env("BP_LOG_LEVEL", "DEBUG")
+///             .run(&context.buildpacks, |context| {
+///                 let buildpack_id = buildpack_id!("heroku/example");
+///
+///                 assert!(context
+///                     .layer_dir(&buildpack_id, &layer_name!("runtime"))
+///                     .join("bin")
+///                     .is_dir());
+///                 assert!(context.launch(&buildpack_id).is_some());
+///             });
+///     },
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct LifecycleDriver {
+    app_dir: PathBuf,
+    env: HashMap<String, String>,
+    target_distro_name: String,
+    target_distro_version: String,
+}
+
+impl LifecycleDriver {
+    /// Creates a driver for the given app directory. Relative paths are resolved against the
+    /// directory of the crate's `Cargo.toml`, like for [`BuildConfig`](crate::BuildConfig).
+    ///
+    /// The app directory is copied to a temporary location before the buildpacks run.
+    pub fn new(app_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            app_dir: app_dir.into(),
+            env: HashMap::new(),
+            target_distro_name: String::from("ubuntu"),
+            target_distro_version: String::from("24.04"),
+        }
+    }
+
+    /// Inserts or updates an environment variable of the platform directory.
+    pub fn env(&mut self, key: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
+
+    /// Adds or updates multiple environment variables of the platform directory.
+    pub fn envs<K: Into<String>, V: Into<String>, I: IntoIterator<Item = (K, V)>>(
+        &mut self,
+        envs: I,
+    ) -> &mut Self {
+        envs.into_iter().for_each(|(key, value)| {
+            self.env(key.into(), value.into());
+        });
+
+        self
+    }
+
+    /// Sets the distribution the buildpacks are told they run on via `CNB_TARGET_DISTRO_NAME`
+    /// and `CNB_TARGET_DISTRO_VERSION`. Defaults to Ubuntu 24.04.
+    pub fn target_distro(
+        &mut self,
+        name: impl Into<String>,
+        version: impl Into<String>,
+    ) -> &mut Self {
+        self.target_distro_name = name.into();
+        self.target_distro_version = version.into();
+        self
+    }
+
+    /// Runs the detect and build phases of the given buildpacks in order and passes the result to
+    /// the given function. The temporary app and layers directories are removed after the function
+    /// has returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a buildpack doesn't pass detection, the build plan can't be resolved, a buildpack
+    /// fails to build, or a buildpack isn't supported by the driver.
+    pub fn run<F: FnOnce(LifecycleDriverContext)>(&self, buildpacks: &[PackagedBuildpack], f: F) {
+        let app_dir = if self.app_dir.is_relative() {
+            cargo_manifest_dir().join(&self.app_dir)
+        } else {
+            self.app_dir.clone()
+        };
+
+        assert!(
+            app_dir.is_dir(),
+            "App dir is not a valid directory: {}",
+            app_dir.display()
+        );
+
+        let workspace =
+            app::copy_app(&app_dir).expect("Error copying app fixture to temporary location");
+
+        let temp_dir = tempdir().expect("Error creating temporary directory for lifecycle driver");
+        let layers_dir = temp_dir.path().join("layers");
+        let platform_dir = temp_dir.path().join("platform");
+        self.write_platform_dir(&platform_dir)
+            .expect("Error writing platform directory");
+
+        let buildpacks = buildpacks
+            .iter()
+            .map(|buildpack| match &buildpack.descriptor {
+                BuildpackDescriptor::Component(descriptor) => {
+                    (descriptor.buildpack.id.clone(), buildpack.path.clone())
+                }
+                BuildpackDescriptor::Composite(descriptor) => panic!(
+                    "Composite buildpack '{}' isn't supported by the lifecycle driver",
+                    descriptor.buildpack.id
+                ),
+            })
+            .collect::<Vec<_>>();
+
+        let mut context = LifecycleDriverContext {
+            detect_output: LogOutput::default(),
+            build_output: LogOutput::default(),
+            buildpack_ids: buildpacks.iter().map(|(id, _)| id.clone()).collect(),
+            layers_dir,
+            workspace,
+            temp_dir,
+        };
+
+        let mut build_plans = Vec::new();
+        for (buildpack_id, buildpack_dir) in &buildpacks {
+            let build_plan_path = context
+                .buildpack_temp_dir("plans", buildpack_id)
+                .join("plan.toml");
+
+            let mut command = self.buildpack_command(buildpack_dir, "detect", &context);
+            command
+                .args([&platform_dir, &build_plan_path])
+                .env("CNB_PLATFORM_DIR", &platform_dir)
+                .env("CNB_BUILD_PLAN_PATH", &build_plan_path);
+
+            let log_output = match util::run_command(command) {
+                Ok(log_output) => log_output,
+                Err(CommandError::NonZeroExitCode {
+                    exit_code: Some(DETECT_FAIL_EXIT_CODE),
+                    log_output,
+                    ..
+                }) => panic!("Buildpack '{buildpack_id}' didn't pass detection:\n\n{log_output}"),
+                Err(command_err) => {
+                    panic!("Error running detect of buildpack '{buildpack_id}':\n\n{command_err}")
+                }
+            };
+            context.append_output(&log_output, Phase::Detect);
+
+            let build_plan = if build_plan_path.exists() {
+                read_toml_file::<DetectBuildPlan>(&build_plan_path).unwrap_or_else(|error| {
+                    panic!("Error reading build plan of buildpack '{buildpack_id}': {error}")
+                })
+            } else {
+                DetectBuildPlan::default()
+            };
+
+            build_plans.push(build_plan);
+        }
+
+        let buildpack_plans = resolve_build_plans(&context.buildpack_ids, &build_plans)
+            .unwrap_or_else(|error| panic!("Error resolving build plan: {error}"));
+
+        for ((buildpack_id, buildpack_dir), buildpack_plan) in
+            buildpacks.iter().zip(buildpack_plans)
+        {
+            let buildpack_plan_path = context
+                .buildpack_temp_dir("plans", buildpack_id)
+                .join("buildpack-plan.toml");
+            write_toml_file(&buildpack_plan, &buildpack_plan_path).unwrap_or_else(|error| {
+                panic!("Error writing buildpack plan of buildpack '{buildpack_id}': {error}")
+            });
+
+            let buildpack_layers_dir = context.layers_dir(buildpack_id);
+            fs::create_dir_all(&buildpack_layers_dir)
+                .expect("Error creating layers directory of buildpack");
+
+            let mut command = self.buildpack_command(buildpack_dir, "build", &context);
+            command
+                .args([&buildpack_layers_dir, &platform_dir, &buildpack_plan_path])
+                .env("CNB_LAYERS_DIR", &buildpack_layers_dir)
+                .env("CNB_PLATFORM_DIR", &platform_dir)
+                .env("CNB_BP_PLAN_PATH", &buildpack_plan_path);
+
+            let log_output = util::run_command(command).unwrap_or_else(|command_err| {
+                panic!("Error running build of buildpack '{buildpack_id}':\n\n{command_err}")
+            });
+            context.append_output(&log_output, Phase::Build);
+        }
+
+        f(context);
+    }
+
+    fn write_platform_dir(&self, platform_dir: &Path) -> std::io::Result<()> {
+        let env_dir = platform_dir.join("env");
+        fs::create_dir_all(&env_dir)?;
+
+        for (key, value) in &self.env {
+            fs::write(env_dir.join(key), value)?;
+        }
+
+        Ok(())
+    }
+
+    fn buildpack_command(
+        &self,
+        buildpack_dir: &Path,
+        executable: &str,
+        context: &LifecycleDriverContext,
+    ) -> Command {
+        let mut command = Command::new(buildpack_dir.join("bin").join(executable));
+        command
+            .current_dir(context.app_dir())
+            .env("CNB_BUILDPACK_DIR", buildpack_dir)
+            .env("CNB_TARGET_OS", "linux")
+            .env("CNB_TARGET_ARCH", target_arch())
+            .env("CNB_TARGET_DISTRO_NAME", &self.target_distro_name)
+            .env("CNB_TARGET_DISTRO_VERSION", &self.target_distro_version);
+        command
+    }
+}
+
+/// Context of a [`LifecycleDriver`] run, as passed to the function of [`LifecycleDriver::run`].
+pub struct LifecycleDriverContext {
+    /// Output of the detect phase of all buildpacks, in order.
+    pub detect_output: LogOutput,
+    /// Output of the build phase of all buildpacks, in order.
+    pub build_output: LogOutput,
+    buildpack_ids: Vec<BuildpackId>,
+    layers_dir: PathBuf,
+    workspace: AppDir,
+    temp_dir: TempDir,
+}
+
+impl LifecycleDriverContext {
+    /// Returns the path of the app directory the buildpacks ran in.
+    #[must_use]
+    pub fn app_dir(&self) -> &Path {
+        self.workspace.as_path()
+    }
+
+    /// Returns the path of the layers directory of the given buildpack.
+    #[must_use]
+    pub fn layers_dir(&self, buildpack_id: &BuildpackId) -> PathBuf {
+        self.layers_dir.join(escape_buildpack_id(buildpack_id))
+    }
+
+    /// Returns the path of the given layer of the given buildpack.
+    #[must_use]
+    pub fn layer_dir(&self, buildpack_id: &BuildpackId, layer_name: &LayerName) -> PathBuf {
+        self.layers_dir(buildpack_id).join(layer_name.as_str())
+    }
+
+    /// Reads the `launch.toml` of the given buildpack, if it wrote one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buildpack isn't part of the run or the `launch.toml` can't be parsed.
+    #[must_use]
+    pub fn launch(&self, buildpack_id: &BuildpackId) -> Option<Launch> {
+        assert!(
+            self.buildpack_ids.contains(buildpack_id),
+            "Buildpack '{buildpack_id}' isn't part of the lifecycle driver run"
+        );
+
+        let launch_toml_path = self.layers_dir(buildpack_id).join("launch.toml");
+        launch_toml_path.exists().then(|| {
+            read_toml_file(&launch_toml_path).unwrap_or_else(|error| {
+                panic!("Error reading launch.toml of buildpack '{buildpack_id}': {error}")
+            })
+        })
+    }
+
+    fn buildpack_temp_dir(&self, name: &str, buildpack_id: &BuildpackId) -> PathBuf {
+        let dir = self
+            .temp_dir
+            .path()
+            .join(name)
+            .join(escape_buildpack_id(buildpack_id));
+
+        fs::create_dir_all(&dir).expect("Error creating temporary directory for lifecycle driver");
+        dir
+    }
+
+    fn append_output(&mut self, log_output: &LogOutput, phase: Phase) {
+        let output = match phase {
+            Phase::Detect => &mut self.detect_output,
+            Phase::Build => &mut self.build_output,
+        };
+
+        output.stdout.push_str(&log_output.stdout);
+        output.stderr.push_str(&log_output.stderr);
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Phase {
+    Detect,
+    Build,
+}
+
+/// The build plan a buildpack writes during detection. Only used by the lifecycle driver, since
+/// [`libcnb_data::build_plan::BuildPlan`] can't be deserialized.
+#[derive(Deserialize, Default)]
+struct DetectBuildPlan {
+    #[serde(default)]
+    provides: Vec<DetectProvide>,
+    #[serde(default)]
+    requires: Vec<BuildpackPlanEntry>,
+}
+
+#[derive(Deserialize)]
+struct DetectProvide {
+    name: String,
+}
+
+#[derive(Serialize, Default, Debug, PartialEq)]
+struct BuildpackPlan {
+    entries: Vec<BuildpackPlanEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct BuildpackPlanEntry {
+    name: String,
+    #[serde(default)]
+    metadata: toml::Table,
+}
+
+/// Resolves the build plans of all buildpacks into a buildpack plan for each buildpack.
+///
+/// Like in the lifecycle, each requirement must be provided by the same or an earlier buildpack
+/// and is passed to all of these buildpacks. Each provision must be required by the same or a
+/// later buildpack.
+fn resolve_build_plans(
+    buildpack_ids: &[BuildpackId],
+    build_plans: &[DetectBuildPlan],
+) -> Result<Vec<BuildpackPlan>, ResolveBuildPlansError> {
+    let mut buildpack_plans = build_plans
+        .iter()
+        .map(|_| BuildpackPlan::default())
+        .collect::<Vec<_>>();
+    let mut providers: HashMap<&str, Vec<usize>> = HashMap::new();
+
+    for (index, build_plan) in build_plans.iter().enumerate() {
+        for provide in &build_plan.provides {
+            providers.entry(&provide.name).or_default().push(index);
+        }
+
+        for require in &build_plan.requires {
+            let require_providers = providers
+                .get(require.name.as_str())
+                .filter(|require_providers| !require_providers.is_empty())
+                .ok_or_else(|| ResolveBuildPlansError::UnprovidedRequirement {
+                    name: require.name.clone(),
+                    buildpack_id: buildpack_ids[index].clone(),
+                })?;
+
+            for provider in require_providers {
+                buildpack_plans[*provider].entries.push(require.clone());
+            }
+        }
+    }
+
+    for (index, build_plan) in build_plans.iter().enumerate() {
+        for provide in &build_plan.provides {
+            if !buildpack_plans[index]
+                .entries
+                .iter()
+                .any(|entry| entry.name == provide.name)
+            {
+                return Err(ResolveBuildPlansError::UnrequiredProvision {
+                    name: provide.name.clone(),
+                    buildpack_id: buildpack_ids[index].clone(),
+                });
+            }
+        }
+    }
+
+    Ok(buildpack_plans)
+}
+
+#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+enum ResolveBuildPlansError {
+    #[error("Requirement '{name}' of buildpack '{buildpack_id}' isn't provided by it or an earlier buildpack")]
+    UnprovidedRequirement {
+        name: String,
+        buildpack_id: BuildpackId,
+    },
+    #[error("Provision '{name}' of buildpack '{buildpack_id}' isn't required by it or a later buildpack")]
+    UnrequiredProvision {
+        name: String,
+        buildpack_id: BuildpackId,
+    },
+}
+
+/// Escapes the buildpack ID for use as directory name, like the lifecycle does for the layers
+/// directories of buildpacks.
+fn escape_buildpack_id(buildpack_id: &BuildpackId) -> String {
+    buildpack_id.replace('/', "_")
+}
+
+/// The CNB architecture name of the host.
+fn target_arch() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        arch => arch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libcnb_data::buildpack_id;
+
+    fn build_plan(provides: &[&str], requires: &[&str]) -> DetectBuildPlan {
+        DetectBuildPlan {
+            provides: provides
+                .iter()
+                .map(|name| DetectProvide {
+                    name: (*name).to_string(),
+                })
+                .collect(),
+            requires: requires.iter().map(|name| entry(name)).collect(),
+        }
+    }
+
+    fn entry(name: &str) -> BuildpackPlanEntry {
+        BuildpackPlanEntry {
+            name: name.to_string(),
+            metadata: toml::Table::new(),
+        }
+    }
+
+    #[test]
+    fn resolve_build_plans_passes_requirements_to_providers() {
+        let buildpack_ids = [buildpack_id!("heroku/jvm"), buildpack_id!("heroku/maven")];
+
+        let buildpack_plans = resolve_build_plans(
+            &buildpack_ids,
+            &[
+                build_plan(&["jdk"], &[]),
+                build_plan(&["maven"], &["jdk", "maven"]),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            buildpack_plans,
+            [
+                BuildpackPlan {
+                    entries: vec![entry("jdk")]
+                },
+                BuildpackPlan {
+                    entries: vec![entry("maven")]
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_build_plans_with_unresolvable_plans() {
+        let buildpack_ids = [buildpack_id!("heroku/jvm"), buildpack_id!("heroku/maven")];
+
+        assert_eq!(
+            resolve_build_plans(
+                &buildpack_ids,
+                &[build_plan(&[], &["maven"]), build_plan(&["maven"], &[])],
+            ),
+            Err(ResolveBuildPlansError::UnprovidedRequirement {
+                name: String::from("maven"),
+                buildpack_id: buildpack_id!("heroku/jvm"),
+            })
+        );
+
+        assert_eq!(
+            resolve_build_plans(
+                &buildpack_ids,
+                &[build_plan(&["jdk"], &[]), build_plan(&[], &[])],
+            ),
+            Err(ResolveBuildPlansError::UnrequiredProvision {
+                name: String::from("jdk"),
+                buildpack_id: buildpack_id!("heroku/jvm"),
+            })
+        );
+    }
+}
@@ -13,3 +13,29 @@ impl Display for LogOutput {
         write!(f, "## stderr:\n\n{stderr}\n## stdout:\n\n{stdout}\n")
     }
 }
+
+/// Output and exit status of a command executed in a container, as returned by
+/// [`ContainerContext::shell_exec_unchecked`](crate::ContainerContext::shell_exec_unchecked) and
+/// [`ContainerContext::shell_exec_streaming`](crate::ContainerContext::shell_exec_streaming).
+#[derive(Debug, Default)]
+pub struct ExecOutput {
+    pub stdout: String,
+    pub stderr: String,
+    /// The exit code of the command, or `None` if it was terminated by a signal.
+    pub exit_code: Option<i32>,
+}
+
+impl ExecOutput {
+    /// Returns whether the command exited with exit code zero.
+    #[must_use]
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// The output stream a line of [`ExecOutput`] was written to.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ExecStream {
+    Stdout,
+    Stderr,
+}
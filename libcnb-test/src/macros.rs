@@ -251,6 +251,142 @@ right: `{:?}`: {}",
     }};
 }
 
+/// Asserts that an [`SbomDocument`](crate::SbomDocument) contains a package.
+///
+/// Optionally, a version prefix can be given that the version of the package must start with. The
+/// prefix is matched by whole version segments, see
+/// [`SbomDocument::contains_package`](crate::SbomDocument::contains_package). Logs all packages of the SBOM on failure.
+///
+/// # Example
+///
+/// ```
+/// use libcnb_data::sbom::SbomFormat;
+/// use libcnb_test::{assert_sbom_contains_package, SbomDocument};
+///
+/// let sbom = SbomDocument::from_slice(
+///     SbomFormat::CycloneDxJson,
+///     br#"{
+///         "bomFormat": "CycloneDX",
+///         "specVersion": "1.5",
+///         "components": [{"type": "library", "name": "openssl", "version": "3.0.13"}]
+///     }"#,
+/// )
+/// .unwrap();
+///
+/// assert_sbom_contains_package!(sbom, "openssl");
+/// assert_sbom_contains_package!(sbom, "openssl", "3.0");
+/// ```
+#[macro_export]
+macro_rules! assert_sbom_contains_package {
+    ($sbom:expr, $name:expr $(,)?) => {{
+        if !$sbom.contains_package($name, None) {
+            ::std::panic!(
+                r"assertion failed: `(SBOM contains package)`
+package: `{:?}`
+SBOM packages:
+{}",
+                $name,
+                $sbom
+            )
+        }
+    }};
+
+    ($sbom:expr, $name:expr, $version_prefix:expr $(,)?) => {{
+        if !$sbom.contains_package($name, Some($version_prefix)) {
+            ::std::panic!(
+                r"assertion failed: `(SBOM contains package)`
+package: `{:?}`
+version prefix: `{:?}`
+SBOM packages:
+{}",
+                $name,
+                $version_prefix,
+                $sbom
+            )
+        }
+    }};
+}
+
+/// Asserts that an [`ImageConfig`](crate::ImageConfig) sets an environment variable to a value.
+///
+/// Logs all environment variables of the image on failure.
+///
+/// # Example
+///
+/// ```
+/// use libcnb_test::{assert_image_env, ImageConfig};
+///
+/// let image_config =
+///     ImageConfig::from_docker_inspect_json(r#"{"Env": ["PORT=8080"]}"#).unwrap();
+///
+/// assert_image_env!(image_config, "PORT", "8080");
+/// ```
+#[macro_export]
+macro_rules! assert_image_env {
+    ($image_config:expr, $name:expr, $value:expr $(,)?) => {{
+        let image_config: &$crate::ImageConfig = &$image_config;
+        if image_config
+            .env
+            .get($name)
+            .map(::std::string::String::as_str)
+            != Some($value)
+        {
+            ::std::panic!(
+                r"assertion failed: `(image env contains variable with value)`
+name: `{:?}`
+expected value: `{:?}`
+actual value: `{:?}`
+image env: `{:?}`",
+                $name,
+                $value,
+                image_config.env.get($name),
+                image_config.env
+            )
+        }
+    }};
+}
+
+/// Asserts that an [`ImageConfig`](crate::ImageConfig) has a label with a value.
+///
+/// Logs all labels of the image on failure.
+///
+/// # Example
+///
+/// ```
+/// use libcnb_test::{assert_image_label, ImageConfig};
+///
+/// let image_config = ImageConfig::from_docker_inspect_json(
+///     r#"{"Labels": {"org.opencontainers.image.vendor": "Heroku"}}"#,
+/// )
+/// .unwrap();
+///
+/// assert_image_label!(image_config, "org.opencontainers.image.vendor", "Heroku");
+/// ```
+#[macro_export]
+macro_rules! assert_image_label {
+    ($image_config:expr, $name:expr, $value:expr $(,)?) => {{
+        let image_config: &$crate::ImageConfig = &$image_config;
+        if image_config
+            .labels
+            .get($name)
+            .map(::std::string::String::as_str)
+            != Some($value)
+        {
+            ::std::panic!(
+                r"assertion failed: `(image has label with value)`
+label: `{:?}`
+expected value: `{:?}`
+actual value: `{:?}`
+image labels: `{:?}`",
+                $name,
+                $value,
+                image_config.labels.get($name),
+                image_config.labels
+            )
+        }
+    }};
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -636,4 +772,69 @@ error: unclosed group
     fn not_contains_match_with_invalid_regex_and_args() {
         assert_not_contains_match!("Hello World!", "(unclosed group", "This will fail");
     }
+
+    fn sbom() -> crate::SbomDocument {
+        crate::SbomDocument::from_slice(
+            libcnb_data::sbom::SbomFormat::SyftJson,
+            br#"{"artifacts": [{"name": "openssl", "version": "3.0.13"}]}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn sbom_contains_package() {
+        assert_sbom_contains_package!(sbom(), "openssl");
+        assert_sbom_contains_package!(sbom(), "openssl", "3.0");
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: `(SBOM contains package)`
+package: `\"openssl\"`
+version prefix: `\"1.1\"`
+SBOM packages:
+openssl 3.0.13
+")]
+    fn sbom_contains_package_failing_version() {
+        assert_sbom_contains_package!(sbom(), "openssl", "1.1");
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: `(SBOM contains package)`
+package: `\"ruby\"`")]
+    fn sbom_contains_package_failing() {
+        assert_sbom_contains_package!(sbom(), "ruby");
+    }
+
+    fn image_config() -> crate::ImageConfig {
+        crate::ImageConfig::from_docker_inspect_json(
+            r#"{"Env": ["PORT=8080"], "Labels": {"vendor": "Heroku"}}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn image_env_and_label() {
+        assert_image_env!(image_config(), "PORT", "8080");
+        assert_image_label!(image_config(), "vendor", "Heroku");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "assertion failed: `(image env contains variable with value)`
+name: `\"PORT\"`
+expected value: `\"80\"`
+actual value: `Some(\"8080\")`"
+    )]
+    fn image_env_failing() {
+        assert_image_env!(image_config(), "PORT", "80");
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: `(image has label with value)`
+label: `\"maintainer\"`
+expected value: `\"Heroku\"`
+actual value: `None`")]
+    fn image_label_failing() {
+        assert_image_label!(image_config(), "maintainer", "Heroku");
+    }
 }
@@ -5,16 +5,22 @@ use std::process::Command;
 /// Represents a `pack build` command.
 #[derive(Clone, Debug)]
 pub(crate) struct PackBuildCommand {
+    additional_args: Vec<String>,
     build_cache_volume_name: String,
     builder: String,
     buildpacks: Vec<BuildpackReference>,
+    docker_config_dir: Option<PathBuf>,
+    docker_host: Option<String>,
     env: BTreeMap<String, String>,
     image_name: String,
     launch_cache_volume_name: String,
+    network: Option<String>,
     path: PathBuf,
     pull_policy: PullPolicy,
+    run_image: Option<String>,
     trust_builder: bool,
     trust_extra_buildpacks: bool,
+    verbose: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -56,29 +62,75 @@ impl PackBuildCommand {
         launch_cache_volume_name: impl Into<String>,
     ) -> Self {
         Self {
+            additional_args: Vec::new(),
             build_cache_volume_name: build_cache_volume_name.into(),
             builder: builder.into(),
             buildpacks: Vec::new(),
+            docker_config_dir: None,
+            docker_host: None,
             env: BTreeMap::new(),
             image_name: image_name.into(),
             launch_cache_volume_name: launch_cache_volume_name.into(),
+            network: None,
             path: path.into(),
             // Prevent redundant image-pulling, which slows tests and risks hitting registry rate limits.
             pull_policy: PullPolicy::IfNotPresent,
+            run_image: None,
             trust_builder: true,
             trust_extra_buildpacks: true,
+            verbose: false,
         }
     }
 
+    pub(crate) fn additional_arg(&mut self, arg: impl Into<String>) -> &mut Self {
+        self.additional_args.push(arg.into());
+        self
+    }
+
+    pub(crate) fn builder(&mut self, builder: impl Into<String>) -> &mut Self {
+        self.builder = builder.into();
+        self
+    }
+
     pub(crate) fn buildpack(&mut self, b: impl Into<BuildpackReference>) -> &mut Self {
         self.buildpacks.push(b.into());
         self
     }
 
+    pub(crate) fn docker_config_dir(&mut self, dir: impl Into<PathBuf>) -> &mut Self {
+        self.docker_config_dir = Some(dir.into());
+        self
+    }
+
+    pub(crate) fn docker_host(&mut self, docker_host: impl Into<String>) -> &mut Self {
+        self.docker_host = Some(docker_host.into());
+        self
+    }
+
     pub(crate) fn env(&mut self, k: impl Into<String>, v: impl Into<String>) -> &mut Self {
         self.env.insert(k.into(), v.into());
         self
     }
+
+    pub(crate) fn network(&mut self, network: impl Into<String>) -> &mut Self {
+        self.network = Some(network.into());
+        self
+    }
+
+    pub(crate) fn run_image(&mut self, run_image: impl Into<String>) -> &mut Self {
+        self.run_image = Some(run_image.into());
+        self
+    }
+
+    pub(crate) fn trust_builder(&mut self, trust_builder: bool) -> &mut Self {
+        self.trust_builder = trust_builder;
+        self
+    }
+
+    pub(crate) fn verbose(&mut self) -> &mut Self {
+        self.verbose = true;
+        self
+    }
 }
 
 impl From<PackBuildCommand> for Command {
@@ -124,6 +176,22 @@ impl From<PackBuildCommand> for Command {
             command.args(["--env", &format!("{env_key}={env_value}")]);
         }
 
+        if let Some(network) = &pack_build_command.network {
+            command.args(["--network", network]);
+        }
+
+        if let Some(run_image) = &pack_build_command.run_image {
+            command.args(["--run-image", run_image]);
+        }
+
+        if let Some(docker_config_dir) = &pack_build_command.docker_config_dir {
+            command.env("DOCKER_CONFIG", docker_config_dir);
+        }
+
+        if let Some(docker_host) = &pack_build_command.docker_host {
+            command.env("DOCKER_HOST", docker_host);
+        }
+
         if pack_build_command.trust_builder {
             command.arg("--trust-builder");
         }
@@ -132,6 +200,12 @@ impl From<PackBuildCommand> for Command {
             command.arg("--trust-extra-buildpacks");
         }
 
+        if pack_build_command.verbose {
+            command.arg("--verbose");
+        }
+
+        command.args(&pack_build_command.additional_args);
+
         command
     }
 }
@@ -179,22 +253,28 @@ mod tests {
     #[test]
     fn from_pack_build_command_to_command() {
         let mut input = PackBuildCommand {
+            additional_args: Vec::new(),
             build_cache_volume_name: String::from("build-cache-volume"),
             builder: String::from("builder:20"),
             buildpacks: vec![
                 BuildpackReference::Id(String::from("libcnb/buildpack1")),
                 BuildpackReference::Path(PathBuf::from("/tmp/buildpack2")),
             ],
+            docker_config_dir: None,
+            docker_host: None,
             env: BTreeMap::from([
                 (String::from("ENV_FOO"), String::from("FOO_VALUE")),
                 (String::from("ENV_BAR"), String::from("WHITESPACE VALUE")),
             ]),
             image_name: String::from("my-image"),
             launch_cache_volume_name: String::from("launch-cache-volume"),
+            network: None,
             path: PathBuf::from("/tmp/foo/bar"),
             pull_policy: PullPolicy::IfNotPresent,
+            run_image: None,
             trust_builder: true,
             trust_extra_buildpacks: true,
+            verbose: false,
         };
 
         let command: Command = input.clone().into();
@@ -237,6 +317,59 @@ mod tests {
         assert!(!command
             .get_args()
             .any(|arg| arg == OsStr::new("--trust-builder")));
+
+        // Assert conditional '--verbose' flag works as expected:
+        input.verbose = true;
+        let command: Command = input.clone().into();
+        assert!(command.get_args().any(|arg| arg == OsStr::new("--verbose")));
+        input.verbose = false;
+
+        // Assert conditional '--network' flag works as expected:
+        input.network = Some(String::from("none"));
+        let command: Command = input.clone().into();
+        assert!(command
+            .get_args()
+            .collect::<Vec<&OsStr>>()
+            .windows(2)
+            .any(|args| args == ["--network", "none"]));
+
+        // Assert conditional '--run-image' flag works as expected:
+        input.run_image = Some(String::from("run-image@sha256:abc"));
+        let command: Command = input.clone().into();
+        assert!(command
+            .get_args()
+            .collect::<Vec<&OsStr>>()
+            .windows(2)
+            .any(|args| args == ["--run-image", "run-image@sha256:abc"]));
+
+        // Assert additional arguments are passed last:
+        input.additional_args = vec![String::from("--clear-cache")];
+        let command: Command = input.clone().into();
+        assert_eq!(command.get_args().last(), Some(OsStr::new("--clear-cache")));
+        input.additional_args = Vec::new();
+
+        // Assert conditional 'DOCKER_CONFIG' env var works as expected:
+        input.docker_config_dir = Some(PathBuf::from("/tmp/docker-config"));
+        let command: Command = input.clone().into();
+        assert_eq!(
+            command.get_envs().collect::<Vec<_>>(),
+            [(
+                OsStr::new("DOCKER_CONFIG"),
+                Some(OsStr::new("/tmp/docker-config"))
+            )]
+        );
+
+        // Assert conditional 'DOCKER_HOST' env var works as expected:
+        input.docker_config_dir = None;
+        input.docker_host = Some(String::from("ssh://user@example.com"));
+        let command: Command = input.clone().into();
+        assert_eq!(
+            command.get_envs().collect::<Vec<_>>(),
+            [(
+                OsStr::new("DOCKER_HOST"),
+                Some(OsStr::new("ssh://user@example.com"))
+            )]
+        );
     }
 
     #[test]
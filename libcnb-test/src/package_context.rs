@@ -0,0 +1,59 @@
+use crate::BuildpackReference;
+use libcnb_data::buildpack::{BuildpackDescriptor, BuildpackId};
+use libcnb_data::package_descriptor::PackageDescriptor;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// Context of a packaging-only test, as passed to the test function of
+/// [`TestRunner::package_only`](crate::TestRunner::package_only).
+///
+/// The packaged buildpack directories are removed after the test function has returned.
+pub struct PackageContext {
+    /// The packaged buildpacks, in the order of the [`BuildConfig`](crate::BuildConfig)
+    /// buildpacks. [`BuildpackReference::Other`] references aren't packaged and therefore not
+    /// included.
+    pub buildpacks: Vec<PackagedBuildpack>,
+    pub(crate) _buildpacks_target_dir: TempDir,
+}
+
+impl PackageContext {
+    /// Returns the packaged buildpack with the given ID, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_data::buildpack_id;
+    /// use libcnb_test::{BuildConfig, BuildpackReference, TestRunner};
+    ///
+    /// TestRunner::default().package_only(
+    ///     BuildConfig::new("heroku/builder:24", "tests/fixtures/app").buildpacks([
+    ///         BuildpackReference::WorkspaceBuildpack(buildpack_id!("heroku/composite")),
+    ///     ]),
+    ///     |context| {
+    ///         let composite = context
+    ///             .buildpack(&buildpack_id!("heroku/composite"))
+    ///             .unwrap();
+    ///
+    ///         assert!(composite.package_descriptor.is_some());
+    ///     },
+    /// );
+    /// ```
+    #[must_use]
+    pub fn buildpack(&self, buildpack_id: &BuildpackId) -> Option<&PackagedBuildpack> {
+        self.buildpacks
+            .iter()
+            .find(|buildpack| &buildpack.descriptor.buildpack().id == buildpack_id)
+    }
+}
+
+/// A buildpack packaged by [`TestRunner::package_only`](crate::TestRunner::package_only).
+#[derive(Debug)]
+pub struct PackagedBuildpack {
+    /// The reference of the buildpack in the [`BuildConfig`](crate::BuildConfig).
+    pub reference: BuildpackReference,
+    /// The path to the packaged buildpack directory, as passed to `pack build --buildpack`.
+    pub path: PathBuf,
+    /// The `buildpack.toml` of the packaged buildpack.
+    pub descriptor: BuildpackDescriptor,
+    /// The `package.toml` of the packaged buildpack, if it's a composite buildpack.
+    pub package_descriptor: Option<PackageDescriptor>,
+}
@@ -0,0 +1,364 @@
+use libcnb_data::sbom::cyclonedx::{Component, CycloneDxBom};
+use libcnb_data::sbom::spdx::SpdxDocument;
+use libcnb_data::sbom::SbomFormat;
+use serde::Deserialize;
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+
+/// A parsed SBOM document, as downloaded with [`TestContext::download_sbom_files`].
+///
+/// Provides a format-independent view on the packages of Cyclone DX, SPDX and Syft SBOMs. The
+/// complete JSON document is available via [`SbomDocument::json`] for format-specific assertions.
+///
+/// Use [`assert_sbom_contains_package!`](crate::assert_sbom_contains_package) to assert that an
+/// SBOM contains a specific package.
+///
+/// [`TestContext::download_sbom_files`]: crate::TestContext::download_sbom_files
+#[derive(Debug, Clone, PartialEq)]
+pub struct SbomDocument {
+    format: SbomFormat,
+    packages: Vec<SbomPackage>,
+    json: Value,
+}
+
+/// A package listed in an SBOM.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SbomPackage {
+    pub name: String,
+    pub version: Option<String>,
+    /// The package URL (purl) of the package, if present.
+    pub purl: Option<String>,
+}
+
+impl SbomDocument {
+    /// Parses an SBOM document of the given format.
+    ///
+    /// Cyclone DX and SPDX documents are parsed as [`CycloneDxBom`] and [`SpdxDocument`] of
+    /// `libcnb_data::sbom`, so they need to have all fields required by these models.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data isn't valid JSON or doesn't have the structure of the given
+    /// SBOM format.
+    pub fn from_slice(format: SbomFormat, data: &[u8]) -> Result<Self, SbomParseError> {
+        let json: Value = serde_json::from_slice(data).map_err(SbomParseError::InvalidJson)?;
+
+        let packages = match format {
+            SbomFormat::CycloneDxJson => {
+                let mut packages = Vec::new();
+                collect_cyclonedx_components(
+                    CycloneDxBom::deserialize(&json)
+                        .map_err(SbomParseError::InvalidStructure)?
+                        .components,
+                    &mut packages,
+                );
+                packages
+            }
+            SbomFormat::SpdxJson => SpdxDocument::deserialize(&json)
+                .map_err(SbomParseError::InvalidStructure)?
+                .packages
+                .into_iter()
+                .map(|package| SbomPackage {
+                    purl: package.purl().map(String::from),
+                    name: package.name,
+                    version: package.version_info,
+                })
+                .collect(),
+            SbomFormat::SyftJson => SyftDocument::deserialize(&json)
+                .map_err(SbomParseError::InvalidStructure)?
+                .artifacts
+                .unwrap_or_default()
+                .into_iter()
+                .map(|artifact| SbomPackage {
+                    name: artifact.name,
+                    version: artifact.version,
+                    purl: artifact.purl,
+                })
+                .collect(),
+        };
+
+        Ok(Self {
+            format,
+            packages,
+            json,
+        })
+    }
+
+    #[must_use]
+    pub fn format(&self) -> &SbomFormat {
+        &self.format
+    }
+
+    /// All packages listed in the SBOM, including nested Cyclone DX components.
+    #[must_use]
+    pub fn packages(&self) -> &[SbomPackage] {
+        &self.packages
+    }
+
+    /// The complete SBOM as JSON.
+    #[must_use]
+    pub fn json(&self) -> &Value {
+        &self.json
+    }
+
+    /// Returns the first package with the given name.
+    #[must_use]
+    pub fn find_package(&self, name: &str) -> Option<&SbomPackage> {
+        self.packages.iter().find(|package| package.name == name)
+    }
+
+    /// Returns whether the SBOM contains a package with the given name and, if given, a version
+    /// that starts with the given version prefix.
+    ///
+    /// The prefix is matched by whole version segments, separated by `.`, `-` or `+`. For example,
+    /// the prefix `3.0` matches the versions `3.0`, `3.0.13` and `3.0-beta1`, but not `3.01`.
+    #[must_use]
+    pub fn contains_package(&self, name: &str, version_prefix: Option<&str>) -> bool {
+        self.packages.iter().any(|package| {
+            package.name == name
+                && version_prefix.map_or(true, |version_prefix| {
+                    package
+                        .version
+                        .as_deref()
+                        .is_some_and(|version| version_has_prefix(version, version_prefix))
+                })
+        })
+    }
+}
+
+const VERSION_SEGMENT_SEPARATORS: [char; 3] = ['.', '-', '+'];
+
+fn version_has_prefix(version: &str, version_prefix: &str) -> bool {
+    version.strip_prefix(version_prefix).is_some_and(|rest| {
+        rest.is_empty()
+            || rest.starts_with(VERSION_SEGMENT_SEPARATORS)
+            || version_prefix.ends_with(VERSION_SEGMENT_SEPARATORS)
+    })
+}
+
+impl Display for SbomDocument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for package in &self.packages {
+            writeln!(
+                f,
+                "{} {}",
+                package.name,
+                package.version.as_deref().unwrap_or("<no version>")
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn collect_cyclonedx_components(components: Vec<Component>, packages: &mut Vec<SbomPackage>) {
+    for component in components {
+        packages.push(SbomPackage {
+            name: component.name,
+            version: component.version,
+            purl: component.purl,
+        });
+
+        collect_cyclonedx_components(component.components, packages);
+    }
+}
+
+// Cyclone DX and SPDX documents are parsed with the models of `libcnb_data::sbom`. Syft documents
+// aren't modeled there, so only the parts relevant for `SbomDocument` are modeled here. Optional
+// arrays are wrapped in `Option` since some tools write `null` instead of omitting them.
+
+#[derive(Deserialize)]
+struct SyftDocument {
+    artifacts: Option<Vec<SyftArtifact>>,
+}
+
+#[derive(Deserialize)]
+struct SyftArtifact {
+    #[serde(default)]
+    name: String,
+    version: Option<String>,
+    purl: Option<String>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SbomParseError {
+    #[error("SBOM is not valid JSON: {0}")]
+    InvalidJson(serde_json::Error),
+
+    #[error("SBOM doesn't have the structure of its format: {0}")]
+    InvalidStructure(serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(format: SbomFormat, value: &Value) -> SbomDocument {
+        SbomDocument::from_slice(format, &serde_json::to_vec(value).unwrap()).unwrap()
+    }
+
+    fn spdx_document(packages: &Value) -> Value {
+        json!({
+            "SPDXID": "SPDXRef-DOCUMENT",
+            "spdxVersion": "SPDX-2.3",
+            "dataLicense": "CC0-1.0",
+            "name": "app",
+            "documentNamespace": "https://example.com/app",
+            "creationInfo": {"created": "2024-01-01T00:00:00Z", "creators": ["Tool: test"]},
+            "packages": packages
+        })
+    }
+
+    #[test]
+    fn cyclonedx() {
+        let sbom = parse(
+            SbomFormat::CycloneDxJson,
+            &json!({
+                "bomFormat": "CycloneDX",
+                "specVersion": "1.5",
+                "components": [
+                    {
+                        "type": "library",
+                        "name": "openssl",
+                        "version": "3.0.13",
+                        "purl": "pkg:deb/ubuntu/openssl@3.0.13",
+                        "components": [{"type": "library", "name": "libssl3", "version": "3.0.13"}]
+                    }
+                ]
+            }),
+        );
+
+        assert_eq!(
+            sbom.find_package("openssl"),
+            Some(&SbomPackage {
+                name: String::from("openssl"),
+                version: Some(String::from("3.0.13")),
+                purl: Some(String::from("pkg:deb/ubuntu/openssl@3.0.13")),
+            })
+        );
+        assert!(sbom.contains_package("libssl3", Some("3.0")));
+        assert!(sbom.contains_package("openssl", None));
+        assert!(!sbom.contains_package("openssl", Some("1.1")));
+        assert!(!sbom.contains_package("ruby", None));
+    }
+
+    #[test]
+    fn spdx() {
+        let sbom = parse(
+            SbomFormat::SpdxJson,
+            &spdx_document(&json!([{
+                "SPDXID": "SPDXRef-ruby",
+                "name": "ruby",
+                "versionInfo": "3.3.0",
+                "downloadLocation": "NOASSERTION",
+                "externalRefs": [
+                    {
+                        "referenceCategory": "SECURITY",
+                        "referenceType": "cpe23Type",
+                        "referenceLocator": "cpe:2.3:a:ruby:ruby"
+                    },
+                    {
+                        "referenceCategory": "PACKAGE-MANAGER",
+                        "referenceType": "purl",
+                        "referenceLocator": "pkg:generic/ruby@3.3.0"
+                    }
+                ]
+            }])),
+        );
+
+        assert_eq!(
+            sbom.packages(),
+            [SbomPackage {
+                name: String::from("ruby"),
+                version: Some(String::from("3.3.0")),
+                purl: Some(String::from("pkg:generic/ruby@3.3.0")),
+            }]
+        );
+    }
+
+    #[test]
+    fn syft() {
+        let sbom = parse(
+            SbomFormat::SyftJson,
+            &json!({"artifacts": [{"name": "rails", "version": "7.1.0", "purl": "pkg:gem/rails@7.1.0"}]}),
+        );
+
+        assert!(sbom.contains_package("rails", Some("7")));
+        assert_eq!(sbom.to_string(), "rails 7.1.0\n");
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(matches!(
+            SbomDocument::from_slice(SbomFormat::SyftJson, b"{"),
+            Err(SbomParseError::InvalidJson(_))
+        ));
+        assert!(matches!(
+            SbomDocument::from_slice(SbomFormat::SyftJson, br#"{"artifacts": {}}"#),
+            Err(SbomParseError::InvalidStructure(_))
+        ));
+        assert!(matches!(
+            SbomDocument::from_slice(
+                SbomFormat::SpdxJson,
+                &serde_json::to_vec(&spdx_document(&json!([{"versionInfo": 3}]))).unwrap()
+            ),
+            Err(SbomParseError::InvalidStructure(_))
+        ));
+        assert!(matches!(
+            SbomDocument::from_slice(SbomFormat::CycloneDxJson, br#"{"components": []}"#),
+            Err(SbomParseError::InvalidStructure(_))
+        ));
+    }
+
+    #[test]
+    fn null_arrays() {
+        assert_eq!(
+            parse(
+                SbomFormat::CycloneDxJson,
+                &json!({"bomFormat": "CycloneDX", "specVersion": "1.5", "components": null})
+            )
+            .packages(),
+            []
+        );
+        assert_eq!(
+            parse(
+                SbomFormat::SpdxJson,
+                &spdx_document(&json!([{
+                    "SPDXID": "SPDXRef-ruby",
+                    "name": "ruby",
+                    "downloadLocation": "NOASSERTION",
+                    "externalRefs": null
+                }]))
+            )
+            .packages(),
+            [SbomPackage {
+                name: String::from("ruby"),
+                version: None,
+                purl: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn contains_package_version_segments() {
+        let sbom = parse(
+            SbomFormat::SyftJson,
+            &json!({"artifacts": [
+                {"name": "openjdk", "version": "21.0.2+13"},
+                {"name": "node", "version": "20.10.0"},
+                {"name": "ruby", "version": "3.3"}
+            ]}),
+        );
+
+        assert!(sbom.contains_package("openjdk", Some("21")));
+        assert!(sbom.contains_package("openjdk", Some("21.0")));
+        assert!(sbom.contains_package("openjdk", Some("21.0.2")));
+        assert!(sbom.contains_package("openjdk", Some("21.0.2+13")));
+        assert!(!sbom.contains_package("openjdk", Some("2")));
+        assert!(sbom.contains_package("node", Some("20.")));
+        assert!(!sbom.contains_package("node", Some("20.1")));
+        assert!(sbom.contains_package("ruby", Some("3.3")));
+        assert!(!sbom.contains_package("ruby", Some("3.3.0")));
+    }
+}
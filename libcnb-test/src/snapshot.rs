@@ -0,0 +1,159 @@
+use regex::Regex;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that, when set to `1` or `true`, causes snapshot assertions to
+/// (re-)write their snapshot files instead of comparing against them.
+pub(crate) const UPDATE_SNAPSHOTS_ENV_VAR: &str = "LIBCNB_TEST_UPDATE_SNAPSHOTS";
+
+/// Asserts that the given (normalized) output matches the snapshot stored at the given path.
+///
+/// In update mode, the snapshot file is written instead. Missing snapshot files are never created
+/// outside of update mode, so that tests don't silently pass on CI.
+pub(crate) fn assert_snapshot(snapshot_path: &Path, actual: &str, update: bool) {
+    if update {
+        if let Some(parent_dir) = snapshot_path.parent() {
+            fs::create_dir_all(parent_dir).unwrap_or_else(|error| {
+                panic!(
+                    "Couldn't create snapshot directory {}: {error}",
+                    parent_dir.display()
+                )
+            });
+        }
+
+        fs::write(snapshot_path, actual).unwrap_or_else(|error| {
+            panic!(
+                "Couldn't write snapshot {}: {error}",
+                snapshot_path.display()
+            )
+        });
+
+        return;
+    }
+
+    let expected = fs::read_to_string(snapshot_path).unwrap_or_else(|error| {
+        panic!(
+            "Couldn't read snapshot {}: {error}\n\nRun the test with {UPDATE_SNAPSHOTS_ENV_VAR}=1 to create it.",
+            snapshot_path.display()
+        )
+    });
+
+    assert!(
+        expected == actual,
+        "Output doesn't match snapshot {}\n\nExpected:\n{expected}\n\nActual:\n{actual}\n\nRun the test with {UPDATE_SNAPSHOTS_ENV_VAR}=1 to update the snapshot.",
+        snapshot_path.display()
+    );
+}
+
+pub(crate) fn update_snapshots_enabled() -> bool {
+    env::var(UPDATE_SNAPSHOTS_ENV_VAR).is_ok_and(|value| value == "1" || value == "true")
+}
+
+/// Returns the path of the snapshot with the given name, relative to the manifest directory of
+/// the crate under test.
+pub(crate) fn snapshot_path(name: &str) -> PathBuf {
+    let cargo_manifest_dir = env::var("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .expect("Couldn't read CARGO_MANIFEST_DIR environment variable");
+
+    cargo_manifest_dir
+        .join("tests")
+        .join("snapshots")
+        .join(format!("{name}.snap"))
+}
+
+/// Formats `pack` output as snapshot contents, with non-deterministic values normalized.
+pub(crate) fn format_snapshot(pack_stdout: &str, pack_stderr: &str) -> String {
+    format!(
+        "--- stdout ---\n{}\n--- stderr ---\n{}",
+        normalize_output(pack_stdout),
+        normalize_output(pack_stderr)
+    )
+}
+
+/// Replaces values in `pack` output that change between builds (such as timestamps, image
+/// digests, layer hashes and the random names of temporary Docker resources) with placeholders.
+pub(crate) fn normalize_output(output: &str) -> String {
+    let replacements = [
+        (
+            r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:?\d{2})?",
+            "<timestamp>",
+        ),
+        (r"sha256:[0-9a-f]{64}", "sha256:<digest>"),
+        (r"\b[0-9a-f]{64}\b", "<hash>"),
+        (r"libcnbtest_[a-z]{12}", "libcnbtest_<random>"),
+    ];
+
+    replacements
+        .into_iter()
+        .fold(String::from(output), |output, (pattern, replacement)| {
+            Regex::new(pattern)
+                .expect("should be a valid regex")
+                .replace_all(&output, replacement)
+                .into_owned()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn normalize_output_replaces_non_deterministic_values() {
+        let output = "\
+===> EXPORTING
+Adding layer 'heroku/ruby:gems' with diffID 'sha256:4cd1b05e4c0ab6ed5bd1f8eec17b2c45b4d64b1fa1d4ec4b8ab0ae3f7e2f4b7a'
+Saving libcnbtest_abcdefghijkl...
+*** Images (b3c8e0fe1f1ea9bd3a1d7c7d0b1a2a6c5e3b4f8d6e9a0c1b2d3e4f5a6b7c8d9e):
+Build time: 2024-12-04T10:15:30.123456Z";
+
+        assert_eq!(
+            normalize_output(output),
+            "\
+===> EXPORTING
+Adding layer 'heroku/ruby:gems' with diffID 'sha256:<digest>'
+Saving libcnbtest_<random>...
+*** Images (<hash>):
+Build time: <timestamp>"
+        );
+    }
+
+    #[test]
+    fn format_snapshot_sections() {
+        assert_eq!(
+            format_snapshot("out\n", "err\n"),
+            "--- stdout ---\nout\n\n--- stderr ---\nerr\n"
+        );
+    }
+
+    #[test]
+    fn assert_snapshot_matching() {
+        let temp_dir = tempdir().unwrap();
+        let snapshot_path = temp_dir.path().join("snapshots").join("build.snap");
+
+        assert_snapshot(&snapshot_path, "output", true);
+        assert_eq!(fs::read_to_string(&snapshot_path).unwrap(), "output");
+
+        assert_snapshot(&snapshot_path, "output", false);
+    }
+
+    #[test]
+    #[should_panic(expected = "Output doesn't match snapshot")]
+    fn assert_snapshot_mismatch() {
+        let temp_dir = tempdir().unwrap();
+        let snapshot_path = temp_dir.path().join("build.snap");
+        fs::write(&snapshot_path, "expected").unwrap();
+
+        assert_snapshot(&snapshot_path, "actual", false);
+    }
+
+    #[test]
+    #[should_panic(expected = "Couldn't read snapshot")]
+    fn assert_snapshot_missing() {
+        let temp_dir = tempdir().unwrap();
+
+        assert_snapshot(&temp_dir.path().join("build.snap"), "output", false);
+    }
+}
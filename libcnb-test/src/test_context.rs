@@ -1,13 +1,23 @@
-use crate::docker::DockerRunCommand;
+use crate::docker::{DockerImageInspectCommand, DockerRunCommand};
+use crate::docker_host::DockerEndpoint;
+use crate::image_config::CNB_PROCESS_DIR;
 use crate::pack::PackSbomDownloadCommand;
+use crate::service_config::ServiceContainers;
 use crate::{
-    util, BuildConfig, ContainerConfig, ContainerContext, LogOutput, TemporaryDockerResources,
-    TestRunner,
+    generated_dockerfile, snapshot, util, Assertion, BuildConfig, ContainerConfig,
+    ContainerContext, DockerfileKind, GeneratedDockerfile, ImageConfig, LogOutput, SbomDocument,
+    TemporaryDockerResources, TestRunner,
 };
 use libcnb_data::buildpack::BuildpackId;
+use libcnb_data::image_label::{
+    BuildMetadata, BuildMetadataProcess, LifecycleMetadata, LifecycleMetadataRunImage,
+};
 use libcnb_data::layer::LayerName;
 use libcnb_data::sbom::SbomFormat;
 use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::fs;
+use std::panic::Location;
 use std::path::PathBuf;
 use tempfile::tempdir;
 
@@ -81,6 +91,7 @@ impl TestContext<'_> {
     /// # Panics
     ///
     /// Panics if there was an error starting the container, such as when the specified entrypoint/command can't be found.
+    /// Also panics if the config combines [`ContainerConfig::network`] with services.
     ///
     /// Note: Does not panic if the container exits after starting (including if it crashes and exits non-zero).
     pub fn start_container<C: Borrow<ContainerConfig>, F: FnOnce(ContainerContext)>(
@@ -112,15 +123,54 @@ impl TestContext<'_> {
             docker_run_command.expose_port(*port);
         });
 
+        if !config.exposed_ports.is_empty() {
+            docker_run_command
+                .publish_on_all_interfaces(DockerEndpoint::current().remote_host().is_some());
+        }
+
         config.bind_mounts.iter().for_each(|(source, target)| {
             docker_run_command.bind_mount(source, target);
         });
 
+        if let Some(memory_limit) = &config.memory_limit {
+            docker_run_command.memory(memory_limit);
+        }
+
+        if let Some(network) = &config.network {
+            assert!(
+                config.services.is_empty(),
+                "ContainerConfig::network can't be combined with ContainerConfig::service"
+            );
+
+            docker_run_command.network(network);
+        }
+
+        config.docker_run_args.iter().for_each(|arg| {
+            docker_run_command.additional_arg(arg);
+        });
+
+        let services = (!config.services.is_empty()).then(|| {
+            let services = ServiceContainers::start(&config.services);
+            docker_run_command.network(&services.network_name);
+
+            // Environment variables of the container config take precedence.
+            for service in &config.services {
+                for (key, value) in &service.app_env {
+                    if !config.env.contains_key(key) {
+                        docker_run_command.env(key, value);
+                    }
+                }
+            }
+
+            services
+        });
+
         // We create the ContainerContext early to ensure the cleanup in ContainerContext::drop
         // is still performed even if the Docker command panics.
         let container_context = ContainerContext {
             container_name,
             config: config.clone(),
+            _services: services,
         };
 
         util::run_command(docker_run_command)
@@ -194,6 +244,76 @@ impl TestContext<'_> {
             .unwrap_or_else(|command_err| panic!("Error running container:\n\n{command_err}"))
     }
 
+    /// Runs a process type of the built image to completion and returns its output.
+    ///
+    /// Use this to test processes that exit on their own, such as CLI tools or one-off tasks,
+    /// without starting a long-running container.
+    ///
+    /// Note: This method will block until the container stops.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         let log_output = context.run_process("worker");
+    ///         assert_eq!(log_output.stdout, "Processed all jobs\n");
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if there was an error starting the container, i.e. because the image has no such
+    /// process type, or the process exited with a non-zero exit code.
+    pub fn run_process(&self, process_type: impl Into<String>) -> LogOutput {
+        let mut docker_run_command = DockerRunCommand::new(
+            &self.docker_resources.image_name,
+            util::random_docker_identifier(),
+        );
+        docker_run_command
+            .remove(true)
+            .platform(self.determine_container_platform())
+            .entrypoint(format!("{CNB_PROCESS_DIR}{}", process_type.into()));
+
+        util::run_command(docker_run_command)
+            .unwrap_or_else(|command_err| panic!("Error running container:\n\n{command_err}"))
+    }
+
+    /// Returns the default process of the built image, if it has one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image couldn't be inspected or the build metadata label is missing or invalid.
+    #[must_use]
+    pub fn default_process(&self) -> Option<BuildMetadataProcess> {
+        let image_config = self.image_config();
+        let default_process_type = image_config.default_process_type()?;
+
+        image_config
+            .build_metadata()
+            .unwrap_or_else(|error| panic!("{error}"))
+            .processes
+            .into_iter()
+            .find(|process| process.r#type == default_process_type)
+    }
+
+    /// Returns the process types of the built image, in the order the lifecycle stored them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image couldn't be inspected or the build metadata label is missing or invalid.
+    #[must_use]
+    pub fn process_types(&self) -> Vec<String> {
+        self.build_metadata()
+            .processes
+            .into_iter()
+            .map(|process| process.r#type)
+            .collect()
+    }
+
     // We set an explicit platform when starting containers to prevent the Docker CLI's
     // "no specific platform was requested" warning from cluttering the captured logs.
     fn determine_container_platform(&self) -> &str {
@@ -252,6 +372,285 @@ impl TestContext<'_> {
         })
     }
 
+    /// Asserts that the output of `pack` matches the stored snapshot with the given name.
+    ///
+    /// Both [`pack_stdout`](Self::pack_stdout) and [`pack_stderr`](Self::pack_stderr) are
+    /// compared against the snapshot file at `tests/snapshots/<name>.snap` in the directory of the
+    /// crate under test. Before comparing, values that change between builds are replaced with
+    /// placeholders: timestamps, image digests, layer hashes and the names of the temporary Docker
+    /// resources created by `libcnb-test`.
+    ///
+    /// To create or update snapshots, run the tests with the `LIBCNB_TEST_UPDATE_SNAPSHOTS`
+    /// environment variable set to `1`, then review and commit the changed snapshot files.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         context.assert_output_snapshot("basic_build");
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the output doesn't match the snapshot, or if the snapshot doesn't exist and
+    /// update mode isn't enabled.
+    pub fn assert_output_snapshot(&self, name: &str) {
+        snapshot::assert_snapshot(
+            &snapshot::snapshot_path(name),
+            &snapshot::format_snapshot(&self.pack_stdout, &self.pack_stderr),
+            snapshot::update_snapshots_enabled(),
+        );
+    }
+
+    /// Returns the build output of the given buildpack.
+    ///
+    /// Slices [`pack_stdout`](Self::pack_stdout) into per-buildpack sections, allowing tests of
+    /// builds with multiple buildpacks to assert on the output of each buildpack in isolation. The
+    /// sections are delimited by the debug messages the CNB lifecycle writes before and after it
+    /// runs the build of a buildpack, so the build must use
+    /// [`BuildConfig::verbose_pack_output`]. The `[builder]` prefix of verbose `pack` output is
+    /// removed from the returned lines, other lifecycle debug messages of the section are kept.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb::data::buildpack_id;
+    /// use libcnb_test::{assert_contains, assert_not_contains, BuildConfig, BuildpackReference, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app")
+    ///         .buildpacks([
+    ///             BuildpackReference::Other(String::from("heroku/nodejs")),
+    ///             BuildpackReference::CurrentCrate,
+    ///         ])
+    ///         .verbose_pack_output(true),
+    ///     |context| {
+    ///         let output = context.logs_for_buildpack(buildpack_id!("my-project/buildpack"));
+    ///         assert_contains!(output, "Installing dependencies");
+    ///         assert_not_contains!(output, "Installing Node.js");
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the output doesn't contain a build section for the given buildpack, i.e.
+    /// because the buildpack didn't participate in the build or the build didn't use verbose
+    /// `pack` output.
+    #[must_use]
+    pub fn logs_for_buildpack(&self, buildpack_id: impl Borrow<BuildpackId>) -> String {
+        let buildpack_id = buildpack_id.borrow();
+
+        buildpack_build_output(&self.pack_stdout, buildpack_id).unwrap_or_else(|| {
+            panic!(
+                "Couldn't find the build output of buildpack {buildpack_id} in the pack output. Make sure the buildpack participated in the build and BuildConfig::verbose_pack_output is enabled.\n\n## stdout:\n\n{}",
+                self.pack_stdout
+            )
+        })
+    }
+
+    /// Checks the given [`Assertion`] against this context.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{AssertionFailure, BuildConfig, TestContext, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         context.assert_that(&|context: &TestContext| {
+    ///             if context.pack_stdout.contains("Installing dependencies") {
+    ///                 Ok(())
+    ///             } else {
+    ///                 Err(AssertionFailure::new("Dependencies weren't installed"))
+    ///             }
+    ///         });
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics with the failure message if the assertion doesn't hold.
+    #[track_caller]
+    pub fn assert_that(&self, assertion: &impl Assertion) {
+        if let Err(failure) = assertion.check(self) {
+            panic!(
+                "Assertion failed: {failure}\n\n## stderr:\n\n{}\n## stdout:\n\n{}",
+                self.pack_stderr, self.pack_stdout
+            );
+        }
+    }
+
+    /// Returns the configuration of the built image, such as its labels, environment variables,
+    /// entrypoint and working directory.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{assert_image_env, assert_image_label, BuildConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         let image_config = context.image_config();
+    ///
+    ///         assert_image_env!(image_config, "JAVA_HOME", "/layers/heroku_jvm/openjdk");
+    ///         assert_image_label!(image_config, "org.opencontainers.image.vendor", "Heroku");
+    ///         assert_eq!(image_config.working_dir, "/workspace");
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image couldn't be inspected, i.e. because the build failed.
+    #[must_use]
+    pub fn image_config(&self) -> ImageConfig {
+        let mut command = DockerImageInspectCommand::new(&self.docker_resources.image_name);
+        command.format("{{json .Config}}");
+
+        let output = util::run_command(command)
+            .unwrap_or_else(|command_err| panic!("Error inspecting image:\n\n{command_err}"));
+
+        ImageConfig::from_docker_inspect_json(&output.stdout)
+            .unwrap_or_else(|error| panic!("Error parsing image config: {error}"))
+    }
+
+    /// Returns the labels of the built image.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image couldn't be inspected, i.e. because the build failed.
+    #[must_use]
+    pub fn image_labels(&self) -> BTreeMap<String, String> {
+        self.image_config().labels
+    }
+
+    /// Returns the build metadata (processes, buildpacks, ...) the CNB lifecycle stored in the
+    /// labels of the built image.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image couldn't be inspected or the label is missing or invalid.
+    #[must_use]
+    pub fn build_metadata(&self) -> BuildMetadata {
+        self.image_config()
+            .build_metadata()
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Returns the lifecycle metadata (exported layers, run image, ...) the CNB lifecycle stored
+    /// in the labels of the built image.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image couldn't be inspected or the label is missing or invalid.
+    #[must_use]
+    pub fn lifecycle_metadata(&self) -> LifecycleMetadata {
+        self.image_config()
+            .lifecycle_metadata()
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Returns the Dockerfiles generated by image extensions during the build, in the order they
+    /// were generated.
+    ///
+    /// Only Dockerfiles generated by extensions implemented with libcnb.rs are returned, which
+    /// print them to the `pack` output if
+    /// [`BuildConfig::print_generated_dockerfiles`] is enabled. Which extensions participated in
+    /// the build is available via [`build_metadata`](Self::build_metadata).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_data::buildpack_id;
+    /// use libcnb_test::{assert_contains, BuildConfig, DockerfileKind, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:24", "tests/fixtures/app")
+    ///         .print_generated_dockerfiles(true),
+    ///     |context| {
+    ///         let run_dockerfile = context
+    ///             .generated_dockerfile(buildpack_id!("my-project/extension"), DockerfileKind::Run)
+    ///             .unwrap();
+    ///
+    ///         assert_contains!(run_dockerfile, "apt-get install -y curl");
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`BuildConfig::print_generated_dockerfiles`] isn't enabled.
+    #[must_use]
+    pub fn generated_dockerfiles(&self) -> Vec<GeneratedDockerfile> {
+        assert!(
+            self.config.print_generated_dockerfiles,
+            "Generated Dockerfiles are only available if BuildConfig::print_generated_dockerfiles is enabled."
+        );
+
+        generated_dockerfile::parse_generated_dockerfiles(&self.pack_stdout)
+    }
+
+    /// Returns the contents of the Dockerfile of the given kind generated by the given extension,
+    /// or `None` if it didn't generate one. See
+    /// [`generated_dockerfiles`](Self::generated_dockerfiles) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`BuildConfig::print_generated_dockerfiles`] isn't enabled.
+    #[must_use]
+    pub fn generated_dockerfile(
+        &self,
+        extension_id: impl Borrow<BuildpackId>,
+        kind: DockerfileKind,
+    ) -> Option<String> {
+        self.generated_dockerfiles()
+            .into_iter()
+            .find(|dockerfile| {
+                &dockerfile.extension_id == extension_id.borrow() && dockerfile.kind == kind
+            })
+            .map(|dockerfile| dockerfile.contents)
+    }
+
+    /// Returns the run image the built image is based on, as recorded by the CNB lifecycle.
+    ///
+    /// If image extensions switched the run image with a `run.Dockerfile` that only contains a
+    /// `FROM` instruction, this is the image they switched to. If they extended the run image
+    /// instead, the built image contains the extended run image and is based on it. Use
+    /// [`start_container`](Self::start_container) or [`image_config`](Self::image_config) to
+    /// assert on its contents. The extended build image is only used during the build and isn't
+    /// available after it, use [`generated_dockerfile`](Self::generated_dockerfile) to assert on
+    /// the `build.Dockerfile` instead.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:24", "tests/fixtures/app"),
+    ///     |context| {
+    ///         assert_eq!(
+    ///             context.run_image().image.as_deref(),
+    ///             Some("heroku/heroku:24-cnb")
+    ///         );
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image couldn't be inspected or doesn't have run image metadata.
+    #[must_use]
+    pub fn run_image(&self) -> LifecycleMetadataRunImage {
+        self.lifecycle_metadata()
+            .run_image
+            .unwrap_or_else(|| panic!("The built image doesn't have run image metadata."))
+    }
+
     /// Starts a subsequent integration test build.
     ///
     /// This function behaves exactly like [`TestRunner::build`], but it will reuse the OCI image
@@ -281,8 +680,63 @@ impl TestContext<'_> {
     ///     },
     /// );
     /// ```
+    #[track_caller]
     pub fn rebuild<C: Borrow<BuildConfig>, F: FnOnce(TestContext)>(self, config: C, f: F) {
-        self.runner.build_internal(self.docker_resources, config, f);
+        self.runner
+            .build_internal(self.docker_resources, config, f, Location::caller());
+    }
+
+    /// Starts a subsequent integration test build with a modified app.
+    ///
+    /// This function behaves like [`TestContext::rebuild`] with the configuration of the current
+    /// test, but the given function is called with the path of a temporary copy of the app
+    /// directory before the build, allowing it to add, modify or delete files of the app. The
+    /// original app fixture is left untouched. If the configuration has an
+    /// [`app_dir_preprocessor`](BuildConfig::app_dir_preprocessor), it runs before the given
+    /// function.
+    ///
+    /// This allows testing how buildpacks invalidate or reuse cached layers when the app changes,
+    /// i.e. after a dependency has been added to a lockfile.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{assert_contains, BuildConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         assert_contains!(context.pack_stdout, "---> Installing dependencies");
+    ///
+    ///         context.rebuild_with_app_edit(
+    ///             |app_dir| {
+    ///                 std::fs::write(app_dir.join("requirements.txt"), "requests==2.32.3\n")
+    ///                     .unwrap();
+    ///             },
+    ///             |context| {
+    ///                 assert_contains!(context.pack_stdout, "---> Installing dependencies");
+    ///             },
+    ///         );
+    ///     },
+    /// );
+    /// ```
+    #[track_caller]
+    pub fn rebuild_with_app_edit<E: 'static + Fn(PathBuf), F: FnOnce(TestContext)>(
+        self,
+        app_edit: E,
+        f: F,
+    ) {
+        let mut config = self.config.clone();
+        let app_dir_preprocessor = config.app_dir_preprocessor.take();
+
+        config.app_dir_preprocessor(move |app_dir| {
+            if let Some(app_dir_preprocessor) = &app_dir_preprocessor {
+                (app_dir_preprocessor)(app_dir.clone());
+            }
+
+            (app_edit)(app_dir);
+        });
+
+        self.rebuild(config, f);
     }
 }
 
@@ -324,4 +778,174 @@ impl SbomFiles {
                 SbomFormat::SyftJson => "sbom.syft.json",
             })
     }
+
+    /// Reads and parses a specific downloaded SBOM file.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_data::buildpack_id;
+    /// use libcnb_data::sbom::SbomFormat;
+    /// use libcnb_test::{assert_sbom_contains_package, BuildConfig, SbomType, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         context.download_sbom_files(|sbom_files| {
+    ///             let sbom = sbom_files.read(
+    ///                 buildpack_id!("heroku/jvm"),
+    ///                 SbomType::Launch,
+    ///                 SbomFormat::CycloneDxJson,
+    ///             );
+    ///
+    ///             assert_sbom_contains_package!(sbom, "openjdk", "21.0");
+    ///         });
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the SBOM file doesn't exist, can't be read or isn't a valid SBOM of the given
+    /// format.
+    pub fn read<I: Borrow<BuildpackId>, T: Borrow<SbomType>, F: Borrow<SbomFormat>>(
+        &self,
+        buildpack_id: I,
+        sbom_type: T,
+        format: F,
+    ) -> SbomDocument {
+        let path = self.path_for(buildpack_id, sbom_type, format.borrow());
+
+        let data = fs::read(&path).unwrap_or_else(|io_error| {
+            panic!("Couldn't read SBOM file {}: {io_error}", path.display())
+        });
+
+        SbomDocument::from_slice(format.borrow().clone(), &data).unwrap_or_else(|parse_error| {
+            panic!("Couldn't parse SBOM file {}: {parse_error}", path.display())
+        })
+    }
+}
+
+/// The prefix `pack` adds to the lifecycle output of the build phase when running verbosely.
+const BUILDER_PHASE_PREFIX: &str = "[builder] ";
+
+/// Extracts the lines between the lifecycle's start and end messages of the given buildpack's
+/// build.
+fn buildpack_build_output(pack_stdout: &str, buildpack_id: &BuildpackId) -> Option<String> {
+    let start_message = format!("Running build for buildpack {buildpack_id}@");
+    let end_message = format!("Finished running build for buildpack {buildpack_id}@");
+
+    let mut lines = pack_stdout
+        .lines()
+        .map(|line| line.strip_prefix(BUILDER_PHASE_PREFIX).unwrap_or(line));
+
+    lines.find(|line| line.starts_with(&start_message))?;
+
+    Some(
+        lines
+            .take_while(|line| {
+                !line.starts_with(&end_message)
+                    // Guards against output that ends abruptly, i.e. due to a failed build.
+                    && !line.starts_with("Running build for buildpack ")
+                    && !line.starts_with("===> ")
+            })
+            .fold(String::new(), |mut output, line| {
+                output.push_str(line);
+                output.push('\n');
+                output
+            }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERBOSE_PACK_OUTPUT: &str = "===> BUILDING
+[builder] Starting build
+[builder] Running build for buildpack heroku/nodejs@3.0.0
+[builder] Looking up buildpack
+[builder] [Installing Node.js]
+[builder] Done (1.234s)
+[builder] Finished running build for buildpack heroku/nodejs@3.0.0
+[builder] Running build for buildpack my-project/buildpack@0.1.0
+[builder] [Installing dependencies]
+[builder] Finished running build for buildpack my-project/buildpack@0.1.0
+[builder] Running build for buildpack heroku/procfile@3.0.0
+[builder] Failed to parse Procfile
+===> EXPORTING
+";
+
+    #[test]
+    fn buildpack_build_output_sections() {
+        assert_eq!(
+            buildpack_build_output(
+                VERBOSE_PACK_OUTPUT,
+                &libcnb_data::buildpack_id!("heroku/nodejs")
+            ),
+            Some(String::from(
+                "Looking up buildpack\n[Installing Node.js]\nDone (1.234s)\n"
+            ))
+        );
+        assert_eq!(
+            buildpack_build_output(
+                VERBOSE_PACK_OUTPUT,
+                &libcnb_data::buildpack_id!("my-project/buildpack")
+            ),
+            Some(String::from("[Installing dependencies]\n"))
+        );
+        assert_eq!(
+            buildpack_build_output(
+                VERBOSE_PACK_OUTPUT,
+                &libcnb_data::buildpack_id!("heroku/procfile")
+            ),
+            Some(String::from("Failed to parse Procfile\n"))
+        );
+        assert_eq!(
+            buildpack_build_output(
+                VERBOSE_PACK_OUTPUT,
+                &libcnb_data::buildpack_id!("heroku/ruby")
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn sbom_files_read() {
+        let temp_dir = tempdir().unwrap();
+        let sbom_files = SbomFiles {
+            sbom_files_directory: temp_dir.path().into(),
+        };
+
+        let path = sbom_files.path_for(
+            libcnb_data::buildpack_id!("heroku/ruby"),
+            SbomType::Launch,
+            SbomFormat::SpdxJson,
+        );
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(
+            &path,
+            r#"{
+                "SPDXID": "SPDXRef-DOCUMENT",
+                "spdxVersion": "SPDX-2.3",
+                "dataLicense": "CC0-1.0",
+                "name": "app",
+                "documentNamespace": "https://example.com/app",
+                "creationInfo": {"created": "2024-01-01T00:00:00Z", "creators": ["Tool: test"]},
+                "packages": [{
+                    "SPDXID": "SPDXRef-ruby",
+                    "name": "ruby",
+                    "versionInfo": "3.3.0",
+                    "downloadLocation": "NOASSERTION"
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        let sbom = sbom_files.read(
+            libcnb_data::buildpack_id!("heroku/ruby"),
+            SbomType::Launch,
+            SbomFormat::SpdxJson,
+        );
+        crate::assert_sbom_contains_package!(sbom, "ruby", "3.3");
+    }
 }
@@ -1,11 +1,20 @@
 use crate::docker::{DockerRemoveImageCommand, DockerRemoveVolumeCommand};
+use crate::docker_host::DockerEndpoint;
 use crate::pack::PackBuildCommand;
 use crate::util::CommandError;
-use crate::{app, build, util, BuildConfig, BuildpackReference, PackResult, TestContext};
+use crate::{
+    app, build, cache_modification, github_actions, util, BuildConfig, BuildpackReference,
+    HermeticConfig, PackResult, PackageContext, PackagedBuildpack, RegistryCredentials,
+    TestContext,
+};
+use libcnb_common::generated_dockerfile::PRINT_GENERATED_DOCKERFILES_ENV_VAR_NAME;
+use libcnb_common::toml_file::read_toml_file;
+use std::any::Any;
 use std::borrow::Borrow;
 use std::env;
-use std::path::PathBuf;
-use tempfile::tempdir;
+use std::panic::{self, AssertUnwindSafe, Location};
+use std::path::{Path, PathBuf};
+use tempfile::{tempdir, TempDir};
 
 /// Runner for libcnb integration tests.
 ///
@@ -22,9 +31,51 @@ use tempfile::tempdir;
 /// )
 /// ```
 #[derive(Default)]
-pub struct TestRunner {}
+pub struct TestRunner {
+    hermetic_config: Option<HermeticConfig>,
+}
 
 impl TestRunner {
+    /// Creates a runner that pins the versions of the external tools and images used by tests.
+    ///
+    /// The configuration is read from the given TOML file, relative paths are resolved against
+    /// the directory of the crate's `Cargo.toml`. See [`HermeticConfig`] for the file format.
+    ///
+    /// Before each build, the runner verifies that the installed `pack` CLI matches the pinned
+    /// version. Builders and run images are referenced by their pinned digests, builds with
+    /// builders that aren't pinned fail. The pinned versions are included in the failure output of
+    /// failed builds.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, TestRunner};
+    ///
+    /// TestRunner::hermetic("tests/libcnb-test.toml").build(
+    ///     BuildConfig::new("heroku/builder:24", "tests/fixtures/app"),
+    ///     |context| {
+    ///         // ...
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the configuration can't be read.
+    pub fn hermetic(config_path: impl AsRef<Path>) -> Self {
+        let config_path = cargo_manifest_dir().join(config_path);
+
+        let hermetic_config = HermeticConfig::from_path(&config_path).unwrap_or_else(|error| {
+            panic!(
+                "Error reading hermetic test configuration from {}: {error}",
+                config_path.display()
+            )
+        });
+
+        Self {
+            hermetic_config: Some(hermetic_config),
+        }
+    }
+
     /// Starts a new integration test build.
     ///
     /// This function copies the application to a temporary directory (if necessary), cross-compiles the current
@@ -49,6 +100,7 @@ impl TestRunner {
     ///     },
     /// )
     /// ```
+    #[track_caller]
     pub fn build<C: Borrow<BuildConfig>, F: FnOnce(TestContext)>(&self, config: C, f: F) {
         let image_name = util::random_docker_identifier();
         let docker_resources = TemporaryDockerResources {
@@ -56,7 +108,127 @@ impl TestRunner {
             launch_cache_volume_name: format!("{image_name}.launch-cache"),
             image_name,
         };
-        self.build_internal(docker_resources, config, f);
+        self.build_internal(docker_resources, config, f, Location::caller());
+    }
+
+    /// Runs the same test function for multiple build configurations, i.e. to test a buildpack
+    /// with multiple builders or target architectures.
+    ///
+    /// Each configuration is built like with [`build`](Self::build), one after another. A failing
+    /// build or test function doesn't stop the remaining builds. After all builds finished, this
+    /// function panics with a summary of the failed configurations, identified by their builder
+    /// and target triple, if any failed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{assert_contains, BuildConfig, TestRunner};
+    ///
+    /// TestRunner::default().build_matrix(
+    ///     ["heroku/builder:22", "heroku/builder:24"]
+    ///         .map(|builder| BuildConfig::new(builder, "tests/fixtures/app")),
+    ///     |context| {
+    ///         assert_contains!(context.pack_stdout, "Expected build output");
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the build or test function failed for any of the configurations.
+    #[track_caller]
+    pub fn build_matrix<C: Borrow<BuildConfig>, F: Fn(TestContext)>(
+        &self,
+        configs: impl IntoIterator<Item = C>,
+        f: F,
+    ) {
+        let caller = Location::caller();
+        let mut failures = Vec::new();
+
+        for config in configs {
+            let config = config.borrow();
+            let image_name = util::random_docker_identifier();
+            let docker_resources = TemporaryDockerResources {
+                build_cache_volume_name: format!("{image_name}.build-cache"),
+                launch_cache_volume_name: format!("{image_name}.launch-cache"),
+                image_name,
+            };
+
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                self.build_internal(docker_resources, config, &f, caller);
+            }));
+
+            if let Err(payload) = result {
+                failures.push(format!(
+                    "- {} ({}): {}",
+                    config.builder_name,
+                    config.target_triple,
+                    panic_message(payload.as_ref())
+                ));
+            }
+        }
+
+        assert!(
+            failures.is_empty(),
+            "Build matrix failed for {} configuration(s):\n{}",
+            failures.len(),
+            failures.join("\n")
+        );
+    }
+
+    /// Packages the buildpacks of the given [`BuildConfig`] without building an image.
+    ///
+    /// This runs only the packaging half of [`build`](Self::build): the current crate and
+    /// workspace buildpacks are cross-compiled and assembled into buildpack directories, including
+    /// composite buildpacks and their [`component_overrides`](BuildConfig::component_overrides).
+    /// Neither `pack` nor Docker are invoked, so packaging can be tested in environments without a
+    /// Docker daemon. [`BuildpackReference::Other`] references, the builder and the app directory
+    /// are ignored.
+    ///
+    /// The passed test function receives a [`PackageContext`] with the paths and descriptors of
+    /// the packaged buildpacks.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, BuildpackReference, TestRunner};
+    ///
+    /// TestRunner::default().package_only(
+    ///     BuildConfig::new("heroku/builder:24", "tests/fixtures/app")
+    ///         .buildpacks([BuildpackReference::CurrentCrate]),
+    ///     |context| {
+    ///         let buildpack = &context.buildpacks[0];
+    ///         assert!(buildpack.path.join("bin/build").exists());
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Like [`build`](Self::build), this function panics if packaging fails.
+    pub fn package_only<C: Borrow<BuildConfig>, F: FnOnce(PackageContext)>(&self, config: C, f: F) {
+        let config = config.borrow();
+        let cargo_manifest_dir = cargo_manifest_dir();
+
+        let buildpacks_target_dir =
+            tempdir().expect("Error creating temporary directory for compiled buildpacks");
+
+        let buildpacks = config
+            .buildpacks
+            .iter()
+            .filter_map(|buildpack| {
+                package_buildpack_reference(
+                    buildpack,
+                    config,
+                    &cargo_manifest_dir,
+                    buildpacks_target_dir.path(),
+                )
+                .map(|path| read_packaged_buildpack(buildpack, path))
+            })
+            .collect();
+
+        f(PackageContext {
+            buildpacks,
+            _buildpacks_target_dir: buildpacks_target_dir,
+        });
     }
 
     pub(crate) fn build_internal<C: Borrow<BuildConfig>, F: FnOnce(TestContext)>(
@@ -64,13 +236,13 @@ impl TestRunner {
         docker_resources: TemporaryDockerResources,
         config: C,
         f: F,
+        caller: &'static Location<'static>,
     ) {
         let config = config.borrow();
 
-        let cargo_manifest_dir = env::var("CARGO_MANIFEST_DIR").map_or_else(
-            |error| panic!("Error determining Cargo manifest directory: {error}"),
-            PathBuf::from,
-        );
+        let _test_run = github_actions::start_test_run(caller, &docker_resources.image_name);
+
+        let cargo_manifest_dir = cargo_manifest_dir();
 
         let app_dir = {
             let normalized_app_dir_path = if config.app_dir.is_relative() {
@@ -110,46 +282,54 @@ impl TestRunner {
             &docker_resources.launch_cache_volume_name,
         );
 
+        // Makes the Dockerfiles generated by libcnb.rs image extensions available via
+        // `TestContext::generated_dockerfiles`.
+        if config.print_generated_dockerfiles {
+            pack_command.env(PRINT_GENERATED_DOCKERFILES_ENV_VAR_NAME, "true");
+        }
+
         config.env.iter().for_each(|(key, value)| {
             pack_command.env(key, value);
         });
 
-        for buildpack in &config.buildpacks {
-            match buildpack {
-                BuildpackReference::CurrentCrate => {
-                    let crate_buildpack_dir = build::package_crate_buildpack(
-                        config.cargo_profile,
-                        &config.target_triple,
-                        &cargo_manifest_dir,
-                        buildpacks_target_dir.path(),
-                    )
-                    .unwrap_or_else(|error| {
-                        panic!("Error packaging current crate as buildpack: {error}")
-                    });
-                    pack_command.buildpack(crate_buildpack_dir);
-                }
-
-                BuildpackReference::WorkspaceBuildpack(buildpack_id) => {
-                    let buildpack_dir = build::package_buildpack(
-                        buildpack_id,
-                        config.cargo_profile,
-                        &config.target_triple,
-                        &cargo_manifest_dir,
-                        buildpacks_target_dir.path(),
-                    )
-                    .unwrap_or_else(|error| {
-                        panic!("Error packaging buildpack '{buildpack_id}': {error}")
-                    });
-                    pack_command.buildpack(buildpack_dir);
-                }
-
-                BuildpackReference::Other(id) => {
-                    pack_command.buildpack(id.clone());
-                }
-            };
+        if config.verbose_pack_output {
+            pack_command.verbose();
         }
 
+        pack_command.trust_builder(config.trust_builder);
+
+        config.pack_args.iter().for_each(|arg| {
+            pack_command.additional_arg(arg);
+        });
+
+        // Older Pack versions don't support Docker contexts.
+        let docker_endpoint = DockerEndpoint::current();
+        if let Some(url) = docker_endpoint
+            .url
+            .as_ref()
+            .filter(|_| docker_endpoint.from_context)
+        {
+            pack_command.docker_host(url);
+        }
+
+        self.apply_hermetic_config(&mut pack_command, &config.builder_name);
+
+        // Must outlive the pack command, since pack reads the Docker config from this directory.
+        let temporary_docker_config_dir =
+            configure_registry_credentials(&mut pack_command, config.registry_credentials.as_ref());
+
+        let packaged_buildpacks = add_buildpacks(
+            &mut pack_command,
+            config,
+            &cargo_manifest_dir,
+            buildpacks_target_dir.path(),
+        );
+
+        let network_isolation_summary =
+            configure_network(&mut pack_command, config, &packaged_buildpacks);
+
         let pack_result = util::run_command(pack_command);
+        drop(temporary_docker_config_dir);
 
         let output = match (&config.expected_pack_result, pack_result) {
             (PackResult::Success, Ok(output)) => output,
@@ -157,13 +337,21 @@ impl TestRunner {
                 log_output
             }
             (PackResult::Failure, Ok(log_output)) => {
-                panic!("The pack build was expected to fail, but did not:\n\n{log_output}");
+                panic!(
+                    "The pack build was expected to fail, but did not:\n\n{log_output}{network_isolation_summary}{}",
+                    self.hermetic_config_summary()
+                );
             }
             (_, Err(command_err)) => {
-                panic!("Error performing pack build:\n\n{command_err}");
+                panic!(
+                    "Error performing pack build:\n\n{command_err}{network_isolation_summary}{}",
+                    self.hermetic_config_summary()
+                );
             }
         };
 
+        github_actions::record_pack_output(&output);
+
         let test_context = TestContext {
             pack_stdout: output.stdout,
             pack_stderr: output.stderr,
@@ -174,6 +362,228 @@ impl TestRunner {
 
         f(test_context);
     }
+
+    /// Verifies the test environment against the hermetic configuration (if any) and pins the
+    /// builder and run image of the given pack command to their configured digests.
+    fn apply_hermetic_config(&self, pack_command: &mut PackBuildCommand, builder_name: &str) {
+        if let Some(hermetic_config) = &self.hermetic_config {
+            let pinned_builder = hermetic_config
+                .verify_environment()
+                .and_then(|()| hermetic_config.pinned_builder(builder_name))
+                .unwrap_or_else(|error| panic!("Error in hermetic test environment: {error}"));
+
+            pack_command.builder(pinned_builder.builder_reference(builder_name));
+
+            if let Some(run_image) = pinned_builder.run_image_reference() {
+                pack_command.run_image(run_image);
+            }
+        }
+    }
+
+    fn hermetic_config_summary(&self) -> String {
+        self.hermetic_config
+            .as_ref()
+            .map(|hermetic_config| format!("\n\nHermetic test configuration:\n{hermetic_config}"))
+            .unwrap_or_default()
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Unknown panic")
+}
+
+pub(crate) fn cargo_manifest_dir() -> PathBuf {
+    env::var("CARGO_MANIFEST_DIR").map_or_else(
+        |error| panic!("Error determining Cargo manifest directory: {error}"),
+        PathBuf::from,
+    )
+}
+
+/// Packages the buildpacks of the given config if necessary and adds them to the pack command.
+///
+/// Returns the packaged buildpacks of the Cargo workspace.
+fn add_buildpacks(
+    pack_command: &mut PackBuildCommand,
+    config: &BuildConfig,
+    cargo_manifest_dir: &Path,
+    buildpacks_target_dir: &Path,
+) -> Vec<PackagedBuildpack> {
+    let mut packaged_buildpacks = Vec::new();
+
+    if !config.cached_layer_modifications.is_empty() {
+        let modification_buildpack_dir = buildpacks_target_dir.join("cached-layer-modifications");
+        cache_modification::write_modification_buildpack(
+            &modification_buildpack_dir,
+            &config.cached_layer_modifications,
+        )
+        .unwrap_or_else(|io_error| {
+            panic!("Error writing cached layer modification buildpack: {io_error}")
+        });
+        pack_command.buildpack(modification_buildpack_dir);
+    }
+
+    for buildpack in &config.buildpacks {
+        if let BuildpackReference::Other(id) = buildpack {
+            pack_command.buildpack(id.clone());
+        } else if let Some(buildpack_dir) = package_buildpack_reference(
+            buildpack,
+            config,
+            cargo_manifest_dir,
+            buildpacks_target_dir,
+        ) {
+            pack_command.buildpack(buildpack_dir.clone());
+            packaged_buildpacks.push(read_packaged_buildpack(buildpack, buildpack_dir));
+        }
+    }
+
+    packaged_buildpacks
+}
+
+/// Sets the network mode of the pack command, see [`BuildConfig::network`].
+///
+/// Returns a summary for the output of failed builds that explains why the build ran without
+/// network access, if applicable.
+fn configure_network(
+    pack_command: &mut PackBuildCommand,
+    config: &BuildConfig,
+    packaged_buildpacks: &[PackagedBuildpack],
+) -> &'static str {
+    if let Some(network) = &config.network {
+        pack_command.network(network);
+        ""
+    } else if declares_network_isolation(&config.buildpacks, packaged_buildpacks) {
+        pack_command.network("none");
+        "\n\nThe build ran without network access, since all buildpacks declare `network-isolation = true` in the `[metadata.libcnb]` table of their buildpack.toml."
+    } else {
+        ""
+    }
+}
+
+/// Whether all buildpacks of a build are packaged buildpacks of the Cargo workspace that declare
+/// `network-isolation = true` in the `[metadata.libcnb]` table of their `buildpack.toml`.
+///
+/// Buildpacks that aren't part of the workspace, i.e. from a builder, might need network access.
+fn declares_network_isolation(
+    buildpacks: &[BuildpackReference],
+    packaged_buildpacks: &[PackagedBuildpack],
+) -> bool {
+    !buildpacks.is_empty()
+        && buildpacks
+            .iter()
+            .all(|buildpack| !matches!(buildpack, BuildpackReference::Other(_)))
+        && packaged_buildpacks.iter().all(|packaged_buildpack| {
+            packaged_buildpack
+                .descriptor
+                .libcnb_metadata()
+                .unwrap_or_else(|error| {
+                    panic!(
+                        "Error reading libcnb metadata of packaged buildpack {}: {error}",
+                        packaged_buildpack.path.display()
+                    )
+                })
+                .network_isolation
+        })
+}
+
+/// Packages the given buildpack reference if it references a buildpack of the Cargo workspace.
+///
+/// Returns the path to the packaged buildpack, or `None` for [`BuildpackReference::Other`].
+fn package_buildpack_reference(
+    buildpack: &BuildpackReference,
+    config: &BuildConfig,
+    cargo_manifest_dir: &Path,
+    buildpacks_target_dir: &Path,
+) -> Option<PathBuf> {
+    match buildpack {
+        BuildpackReference::CurrentCrate => Some(
+            build::package_crate_buildpack(
+                config.cargo_profile,
+                &config.target_triple,
+                cargo_manifest_dir,
+                buildpacks_target_dir,
+                &config.component_overrides,
+            )
+            .unwrap_or_else(|error| panic!("Error packaging current crate as buildpack: {error}")),
+        ),
+
+        BuildpackReference::WorkspaceBuildpack(buildpack_id) => Some(
+            build::package_buildpack(
+                buildpack_id,
+                config.cargo_profile,
+                &config.target_triple,
+                cargo_manifest_dir,
+                buildpacks_target_dir,
+                &config.component_overrides,
+            )
+            .unwrap_or_else(|error| panic!("Error packaging buildpack '{buildpack_id}': {error}")),
+        ),
+
+        BuildpackReference::Other(_) => None,
+    }
+}
+
+/// Reads the descriptors of a packaged buildpack.
+fn read_packaged_buildpack(buildpack: &BuildpackReference, path: PathBuf) -> PackagedBuildpack {
+    let descriptor = read_toml_file(path.join("buildpack.toml")).unwrap_or_else(|error| {
+        panic!(
+            "Error reading buildpack.toml of packaged buildpack {}: {error}",
+            path.display()
+        )
+    });
+
+    let package_descriptor_path = path.join("package.toml");
+    let package_descriptor = package_descriptor_path.exists().then(|| {
+        read_toml_file(&package_descriptor_path).unwrap_or_else(|error| {
+            panic!(
+                "Error reading package.toml of packaged buildpack {}: {error}",
+                path.display()
+            )
+        })
+    });
+
+    PackagedBuildpack {
+        reference: buildpack.clone(),
+        path,
+        descriptor,
+        package_descriptor,
+    }
+}
+
+/// Configures the given pack command to use the given registry credentials.
+///
+/// Returns the temporary Docker config directory that was created for token credentials, if any.
+fn configure_registry_credentials(
+    pack_command: &mut PackBuildCommand,
+    registry_credentials: Option<&RegistryCredentials>,
+) -> Option<TempDir> {
+    match registry_credentials? {
+        RegistryCredentials::DockerConfig(path) => {
+            pack_command.docker_config_dir(path);
+            None
+        }
+        RegistryCredentials::Token {
+            registry,
+            username,
+            token,
+        } => {
+            let docker_config_dir =
+                tempdir().expect("Error creating temporary directory for Docker config");
+            util::write_docker_config(
+                docker_config_dir.path(),
+                util::user_docker_config_dir().as_deref(),
+                registry,
+                username,
+                token,
+            )
+            .unwrap_or_else(|error| panic!("Error writing Docker config: {error}"));
+            pack_command.docker_config_dir(docker_config_dir.path());
+            Some(docker_config_dir)
+        }
+    }
 }
 
 #[allow(clippy::struct_field_names)]
@@ -188,11 +598,69 @@ impl Drop for TemporaryDockerResources {
         // Ignoring errors here since we don't want to panic inside Drop.
         // We don't emit a warning to stderr since that gets too noisy in some common
         // cases (such as running a test suite when Docker isn't started) where the tests
-        // themselves will also report the same error message.
-        let _ = util::run_command(DockerRemoveImageCommand::new(&self.image_name));
-        let _ = util::run_command(DockerRemoveVolumeCommand::new([
+        // themselves will also report the same error message. On GitHub Actions, leaked
+        // resources of otherwise passing tests are reported as warning annotations, since they
+        // eventually exhaust the disk space of the runner.
+        let remove_image_result =
+            util::run_command(DockerRemoveImageCommand::new(&self.image_name));
+        let remove_volumes_result = util::run_command(DockerRemoveVolumeCommand::new([
             &self.build_cache_volume_name,
             &self.launch_cache_volume_name,
         ]));
+
+        if !std::thread::panicking() {
+            if let Err(command_err) = remove_image_result.and(remove_volumes_result) {
+                github_actions::warning(&format!(
+                    "Couldn't remove Docker resources of image {}:\n\n{command_err}",
+                    self.image_name
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packaged_buildpack(metadata: &str) -> PackagedBuildpack {
+        PackagedBuildpack {
+            reference: BuildpackReference::CurrentCrate,
+            path: PathBuf::from("/buildpack"),
+            descriptor: toml::from_str(&format!(
+                "api = \"0.10\"\n[buildpack]\nid = \"foo/bar\"\nversion = \"0.0.1\"\n{metadata}"
+            ))
+            .unwrap(),
+            package_descriptor: None,
+        }
+    }
+
+    #[test]
+    fn network_isolation_declarations() {
+        let isolated = || packaged_buildpack("[metadata.libcnb]\nnetwork-isolation = true");
+
+        assert!(declares_network_isolation(
+            &[BuildpackReference::CurrentCrate],
+            &[isolated()]
+        ));
+        assert!(!declares_network_isolation(
+            &[BuildpackReference::CurrentCrate],
+            &[packaged_buildpack("")]
+        ));
+        assert!(!declares_network_isolation(
+            &[
+                BuildpackReference::CurrentCrate,
+                BuildpackReference::WorkspaceBuildpack(libcnb_data::buildpack_id!("foo/baz"))
+            ],
+            &[isolated(), packaged_buildpack("")]
+        ));
+        assert!(!declares_network_isolation(
+            &[
+                BuildpackReference::Other(String::from("heroku/procfile")),
+                BuildpackReference::CurrentCrate
+            ],
+            &[isolated()]
+        ));
+        assert!(!declares_network_isolation(&[], &[]));
     }
 }
@@ -1,8 +1,12 @@
 use crate::LogOutput;
+use base64::Engine;
 use std::fmt::Display;
 use std::io;
 use std::iter::repeat_with;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
 
 /// Generate a random Docker identifier.
 ///
@@ -20,6 +24,138 @@ pub(crate) fn random_docker_identifier() -> String {
 
 pub(crate) const CNB_LAUNCHER_BINARY: &str = "launcher";
 
+/// Writes a Docker `config.json` with credentials for a single registry to the given directory.
+///
+/// The config is based on the user's Docker config in `user_docker_config_dir` (see
+/// [`user_docker_config_dir`]), if there is one. Its settings, such as `credsStore`,
+/// `credHelpers`, the `auths` of other registries and the current context, are kept and its
+/// `contexts` directory is copied. Credential helpers take precedence over `auths`, so the given
+/// registry is mapped to no credential helper to make sure the given credentials are used.
+///
+/// The directory can then be used as `DOCKER_CONFIG` for tools that pull images, such as `pack`.
+pub(crate) fn write_docker_config(
+    dir: &Path,
+    user_docker_config_dir: Option<&Path>,
+    registry: &str,
+    username: &str,
+    token: &str,
+) -> io::Result<()> {
+    let mut config = match user_docker_config_dir
+        .map(|user_docker_config_dir| std::fs::read(user_docker_config_dir.join("config.json")))
+    {
+        Some(Ok(contents)) => serde_json::from_slice::<serde_json::Value>(&contents)?,
+        Some(Err(error)) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+        Some(Err(_)) | None => serde_json::json!({}),
+    };
+
+    let config_object = config.as_object_mut().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Docker config.json doesn't contain a JSON object",
+        )
+    })?;
+
+    let auth = base64::engine::general_purpose::STANDARD.encode(format!("{username}:{token}"));
+    json_object_entry(config_object, "auths")?
+        .insert(registry.to_string(), serde_json::json!({ "auth": auth }));
+
+    if config_object.contains_key("credsStore") || config_object.contains_key("credHelpers") {
+        json_object_entry(config_object, "credHelpers")?
+            .insert(registry.to_string(), serde_json::json!(""));
+    }
+
+    if let Some(user_docker_config_dir) = user_docker_config_dir {
+        let contexts_dir = user_docker_config_dir.join("contexts");
+        if contexts_dir.is_dir() {
+            copy_dir_recursively(&contexts_dir, &dir.join("contexts"))?;
+        }
+    }
+
+    std::fs::write(dir.join("config.json"), config.to_string())
+}
+
+/// The Docker config directory of the user, i.e. `$DOCKER_CONFIG` or `~/.docker`.
+pub(crate) fn user_docker_config_dir() -> Option<PathBuf> {
+    std::env::var_os("DOCKER_CONFIG")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".docker")))
+}
+
+/// Returns the JSON object stored under the given key, inserting an empty one if there is none.
+fn json_object_entry<'a>(
+    object: &'a mut serde_json::Map<String, serde_json::Value>,
+    key: &str,
+) -> io::Result<&'a mut serde_json::Map<String, serde_json::Value>> {
+    object
+        .entry(key)
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Docker config.json contains an invalid {key} entry"),
+            )
+        })
+}
+
+fn copy_dir_recursively(source: &Path, destination: &Path) -> io::Result<()> {
+    std::fs::create_dir_all(destination)?;
+
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let destination = destination.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            copy_dir_recursively(&entry.path(), &destination)?;
+        } else {
+            std::fs::copy(entry.path(), destination)?;
+        }
+    }
+
+    Ok(())
+}
+
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(100);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// The smallest per-attempt timeout passed to operations by [`retry_with_backoff`].
+///
+/// Some APIs (such as [`std::net::TcpStream::connect_timeout`]) reject a zero timeout.
+const MIN_ATTEMPT_TIMEOUT: Duration = Duration::from_millis(1);
+
+/// Retries the given operation with exponential backoff until it succeeds or the timeout elapsed.
+///
+/// The operation is passed the time remaining until the overall deadline, which it should use as
+/// the timeout for any blocking calls it makes, so that the total time spent (including time spent
+/// within the operation itself) doesn't exceed the given timeout.
+///
+/// Returns the error of the last attempt if the operation didn't succeed in time. The operation
+/// is always attempted at least once.
+pub(crate) fn retry_with_backoff<T, E>(
+    timeout: Duration,
+    mut operation: impl FnMut(Duration) -> Result<T, E>,
+) -> Result<T, E> {
+    let deadline = Instant::now() + timeout;
+    let mut delay = INITIAL_RETRY_DELAY;
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+
+        match operation(remaining.max(MIN_ATTEMPT_TIMEOUT)) {
+            Ok(value) => return Ok(value),
+            Err(error) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Err(error);
+                }
+
+                thread::sleep(delay.min(remaining));
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+            }
+        }
+    }
+}
+
 /// A helper for running an external process using [`Command`].
 pub(crate) fn run_command(command: impl Into<Command>) -> Result<LogOutput, CommandError> {
     let mut command = command.into();
@@ -104,6 +240,175 @@ mod tests {
     use super::*;
     use indoc::indoc;
 
+    #[test]
+    fn write_docker_config_auth() {
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        write_docker_config(
+            temp_dir.path(),
+            None,
+            "registry.example.com",
+            "user",
+            "secret",
+        )
+        .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("config.json")).unwrap(),
+            r#"{"auths":{"registry.example.com":{"auth":"dXNlcjpzZWNyZXQ="}}}"#
+        );
+    }
+
+    #[test]
+    fn write_docker_config_merges_user_config() {
+        let user_docker_config_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            user_docker_config_dir.path().join("config.json"),
+            r#"{
+                "auths": {"other.example.com": {"auth": "b3RoZXI="}},
+                "credsStore": "desktop",
+                "credHelpers": {"gcr.io": "gcloud"},
+                "currentContext": "remote"
+            }"#,
+        )
+        .unwrap();
+        let context_meta_dir = user_docker_config_dir.path().join("contexts/meta/0123");
+        std::fs::create_dir_all(&context_meta_dir).unwrap();
+        std::fs::write(context_meta_dir.join("meta.json"), "{}").unwrap();
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        write_docker_config(
+            temp_dir.path(),
+            Some(user_docker_config_dir.path()),
+            "registry.example.com",
+            "user",
+            "secret",
+        )
+        .unwrap();
+
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(
+                &std::fs::read(temp_dir.path().join("config.json")).unwrap()
+            )
+            .unwrap(),
+            serde_json::json!({
+                "auths": {
+                    "other.example.com": {"auth": "b3RoZXI="},
+                    "registry.example.com": {"auth": "dXNlcjpzZWNyZXQ="}
+                },
+                "credsStore": "desktop",
+                "credHelpers": {"gcr.io": "gcloud", "registry.example.com": ""},
+                "currentContext": "remote"
+            })
+        );
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("contexts/meta/0123/meta.json")).unwrap(),
+            "{}"
+        );
+    }
+
+    #[test]
+    fn write_docker_config_without_user_config() {
+        let user_docker_config_dir = tempfile::tempdir().unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        write_docker_config(
+            temp_dir.path(),
+            Some(user_docker_config_dir.path()),
+            "registry.example.com",
+            "user",
+            "secret",
+        )
+        .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("config.json")).unwrap(),
+            r#"{"auths":{"registry.example.com":{"auth":"dXNlcjpzZWNyZXQ="}}}"#
+        );
+        assert!(!temp_dir.path().join("contexts").exists());
+    }
+
+    #[test]
+    fn retry_with_backoff_eventually_succeeds() {
+        let mut attempts = 0;
+
+        let result: Result<u32, ()> = retry_with_backoff(Duration::from_secs(10), |_| {
+            attempts += 1;
+            if attempts < 3 {
+                Err(())
+            } else {
+                Ok(attempts)
+            }
+        });
+
+        assert_eq!(result, Ok(3));
+    }
+
+    #[test]
+    fn retry_with_backoff_timeout() {
+        let start = Instant::now();
+        let mut attempts = 0;
+
+        let result: Result<(), u32> = retry_with_backoff(Duration::from_millis(250), |_| {
+            attempts += 1;
+            Err(attempts)
+        });
+
+        // Attempts at 0ms, 100ms (after 100ms delay) and 250ms (after a delay capped to the timeout).
+        assert_eq!(result, Err(3));
+        assert!(start.elapsed() >= Duration::from_millis(250));
+    }
+
+    #[test]
+    fn retry_with_backoff_passes_remaining_time() {
+        let timeout = Duration::from_secs(10);
+        let mut remaining_per_attempt = Vec::new();
+
+        let result: Result<(), ()> = retry_with_backoff(timeout, |remaining| {
+            remaining_per_attempt.push(remaining);
+            if remaining_per_attempt.len() < 3 {
+                Err(())
+            } else {
+                Ok(())
+            }
+        });
+
+        assert_eq!(result, Ok(()));
+        assert!(remaining_per_attempt[0] <= timeout);
+        assert!(remaining_per_attempt
+            .windows(2)
+            .all(|pair| pair[1] < pair[0]));
+    }
+
+    #[test]
+    fn retry_with_backoff_slow_operation_respects_overall_timeout() {
+        let timeout = Duration::from_millis(300);
+        let start = Instant::now();
+
+        // Simulates an operation (such as a connection attempt) that blocks until its own
+        // timeout elapses. The overall timeout must not be exceeded by such operations.
+        let result: Result<(), ()> = retry_with_backoff(timeout, |remaining| {
+            thread::sleep(remaining);
+            Err(())
+        });
+
+        assert_eq!(result, Err(()));
+        assert!(start.elapsed() < timeout + Duration::from_millis(200));
+    }
+
+    #[test]
+    fn retry_with_backoff_zero_timeout() {
+        let mut remaining_per_attempt = Vec::new();
+
+        let result: Result<(), ()> = retry_with_backoff(Duration::ZERO, |remaining| {
+            remaining_per_attempt.push(remaining);
+            Err(())
+        });
+
+        assert_eq!(result, Err(()));
+        assert_eq!(remaining_per_attempt, [MIN_ATTEMPT_TIMEOUT]);
+    }
+
     #[test]
     fn run_command_succeeded() {
         let mut command = Command::new("bash");
@@ -11,8 +11,9 @@
 use indoc::{formatdoc, indoc};
 use libcnb_data::buildpack_id;
 use libcnb_test::{
-    assert_contains, assert_empty, assert_not_contains, BuildConfig, BuildpackReference,
-    ContainerConfig, PackResult, TestRunner,
+    assert_contains, assert_empty, assert_not_contains, AssertionFailure, BuildConfig,
+    BuildpackReference, ContainerConfig, ExecStream, PackResult, ServiceConfig, TestContext,
+    TestRunner,
 };
 use std::path::PathBuf;
 use std::time::Duration;
@@ -44,6 +45,99 @@ fn build_other_buildpack() {
     );
 }
 
+#[test]
+#[ignore = "integration test"]
+fn assert_that_passing() {
+    TestRunner::default().build(
+        BuildConfig::new("heroku/builder:22", "tests/fixtures/procfile")
+            .buildpacks([BuildpackReference::Other(String::from(PROCFILE_URL))]),
+        |context| {
+            context.assert_that(&|context: &TestContext| {
+                if context.pack_stdout.contains("Procfile declares types") {
+                    Ok(())
+                } else {
+                    Err(AssertionFailure::new("Process types weren't discovered"))
+                }
+            });
+        },
+    );
+}
+
+#[test]
+#[ignore = "integration test"]
+#[should_panic(expected = "Assertion failed: Dependencies weren't cached")]
+fn assert_that_failing() {
+    TestRunner::default().build(
+        BuildConfig::new("heroku/builder:22", "tests/fixtures/procfile")
+            .buildpacks([BuildpackReference::Other(String::from(PROCFILE_URL))]),
+        |context| {
+            context.assert_that(&|_: &TestContext| {
+                Err(AssertionFailure::new("Dependencies weren't cached"))
+            });
+        },
+    );
+}
+
+#[test]
+#[ignore = "integration test"]
+fn logs_for_buildpack() {
+    TestRunner::default().build(
+        BuildConfig::new("heroku/builder:22", "tests/fixtures/procfile")
+            .buildpacks([BuildpackReference::Other(String::from(PROCFILE_URL))])
+            .verbose_pack_output(true),
+        |context| {
+            assert_contains!(
+                context.logs_for_buildpack(buildpack_id!("heroku/procfile")),
+                "Procfile declares types -> web, worker"
+            );
+        },
+    );
+}
+
+#[test]
+#[ignore = "integration test"]
+#[should_panic(expected = "Couldn't find the build output of buildpack heroku/ruby")]
+fn logs_for_missing_buildpack() {
+    TestRunner::default().build(
+        BuildConfig::new("heroku/builder:22", "tests/fixtures/procfile")
+            .buildpacks([BuildpackReference::Other(String::from(PROCFILE_URL))])
+            .verbose_pack_output(true),
+        |context| {
+            let _ = context.logs_for_buildpack(buildpack_id!("heroku/ruby"));
+        },
+    );
+}
+
+#[test]
+#[ignore = "integration test"]
+fn run_image() {
+    TestRunner::default().build(
+        BuildConfig::new("heroku/builder:22", "tests/fixtures/procfile")
+            .buildpacks([BuildpackReference::Other(String::from(PROCFILE_URL))]),
+        |context| {
+            assert_contains!(
+                context.run_image().image.unwrap_or_default(),
+                "heroku/heroku:22"
+            );
+        },
+    );
+}
+
+#[test]
+#[ignore = "integration test"]
+#[should_panic(
+    expected = "Generated Dockerfiles are only available if BuildConfig::print_generated_dockerfiles is enabled."
+)]
+fn generated_dockerfiles_not_enabled() {
+    TestRunner::default().build(
+        BuildConfig::new("heroku/builder:22", "tests/fixtures/procfile")
+            .buildpacks([BuildpackReference::Other(String::from(PROCFILE_URL))]),
+        |context| {
+            let _ = context.generated_dockerfiles();
+        },
+    );
+}
+
 #[test]
 #[ignore = "integration test"]
 fn build_workspace_component_buildpack() {
@@ -111,6 +205,33 @@ fn build_multiple_buildpacks() {
     );
 }
 
+#[test]
+#[ignore = "integration test"]
+fn package_only_workspace_composite_buildpack() {
+    TestRunner::default().package_only(
+        BuildConfig::new("heroku/builder:22", "tests/fixtures/empty").buildpacks([
+            BuildpackReference::WorkspaceBuildpack(buildpack_id!("libcnb-test/composite")),
+            BuildpackReference::Other(String::from(PROCFILE_URL)),
+        ]),
+        |context| {
+            assert_eq!(context.buildpacks.len(), 1);
+
+            let composite = context
+                .buildpack(&buildpack_id!("libcnb-test/composite"))
+                .unwrap();
+            assert!(composite.path.join("buildpack.toml").is_file());
+
+            let package_descriptor = composite.package_descriptor.as_ref().unwrap();
+            assert_eq!(package_descriptor.dependencies.len(), 3);
+            // The `libcnb:` dependencies are replaced with the paths of the packaged components.
+            assert!(package_descriptor
+                .dependencies
+                .iter()
+                .all(|dependency| !dependency.uri.to_string().starts_with("libcnb:")));
+        },
+    );
+}
+
 #[test]
 #[ignore = "integration test"]
 fn rebuild() {
@@ -130,6 +251,61 @@ fn rebuild() {
     );
 }
 
+#[test]
+#[ignore = "integration test"]
+fn rebuild_with_app_edit() {
+    TestRunner::default().build(
+        BuildConfig::new("heroku/builder:22", "tests/fixtures/procfile")
+            .buildpacks([BuildpackReference::Other(String::from(PROCFILE_URL))])
+            .app_dir_preprocessor(|app_dir| {
+                fs::write(app_dir.join("preprocessed.txt"), "").unwrap();
+            }),
+        |context| {
+            let log_output = context.run_shell_command("ls *.txt");
+            assert_eq!(log_output.stdout, "preprocessed.txt\n");
+
+            context.rebuild_with_app_edit(
+                |app_dir| {
+                    assert!(app_dir.join("preprocessed.txt").exists());
+                    fs::write(app_dir.join("edited.txt"), "").unwrap();
+                },
+                |rebuild_context| {
+                    assert_contains!(rebuild_context.pack_stdout, "Reusing layer");
+                    let log_output = rebuild_context.run_shell_command("ls *.txt");
+                    assert_eq!(log_output.stdout, "edited.txt\npreprocessed.txt\n");
+                },
+            );
+        },
+    );
+
+    // Check that the original fixture was left untouched.
+    let fixture_dir = env::var("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap()
+        .join("tests/fixtures/procfile");
+    assert!(!fixture_dir.join("edited.txt").exists());
+}
+
+#[test]
+#[ignore = "integration test"]
+fn build_without_network() {
+    TestRunner::default().build(
+        BuildConfig::new("heroku/builder:22", "tests/fixtures/procfile")
+            .buildpacks([BuildpackReference::Other(String::from(PROCFILE_URL))])
+            .network("none"),
+        |context| {
+            assert_empty!(context.pack_stderr);
+            assert_contains!(
+                context.pack_stdout,
+                indoc! {"
+                    [Discovering process types]
+                    Procfile declares types -> web, worker
+                "}
+            );
+        },
+    );
+}
+
 #[test]
 #[ignore = "integration test"]
 fn packaging_failure_missing_buildpack_toml() {
@@ -191,6 +367,28 @@ fn packaging_failure_composite_buildpack_missing_package_toml() {
     );
 }
 
+#[test]
+#[ignore = "integration test"]
+#[should_panic(
+    expected = "Build matrix failed for 2 configuration(s):\n- invalid! (x86_64-unknown-linux-musl): Error packaging buildpack 'libcnb-test/composite-missing-package-toml'"
+)]
+fn build_matrix_reports_failures_per_config() {
+    TestRunner::default().build_matrix(
+        ["x86_64-unknown-linux-musl", "aarch64-unknown-linux-musl"].map(|target_triple| {
+            let mut config = BuildConfig::new("invalid!", "tests/fixtures/empty");
+            config.target_triple(target_triple).buildpacks([
+                BuildpackReference::WorkspaceBuildpack(buildpack_id!(
+                    "libcnb-test/composite-missing-package-toml"
+                )),
+            ]);
+            config
+        }),
+        |_| {
+            unreachable!("The test should panic prior to the TestContext being invoked.");
+        },
+    );
+}
+
 #[test]
 #[ignore = "integration test"]
 #[should_panic(
@@ -530,6 +728,113 @@ fn starting_containers() {
     );
 }
 
+#[test]
+#[ignore = "integration test"]
+fn waiting_for_container_ports() {
+    TestRunner::default().build(
+        BuildConfig::new("heroku/builder:22", "tests/fixtures/procfile")
+            .buildpacks([BuildpackReference::Other(String::from(PROCFILE_URL))]),
+        |context| {
+            context.start_container(
+                ContainerConfig::new()
+                    .env("PORT", TEST_PORT.to_string())
+                    .expose_port(TEST_PORT),
+                |container| {
+                    let address_on_host =
+                        container.wait_for_port(TEST_PORT, Duration::from_secs(10));
+                    assert_eq!(address_on_host, container.address_for_port(TEST_PORT));
+
+                    let response = container.wait_for_http(
+                        &format!("http://{address_on_host}"),
+                        |response| response.status == 200,
+                        Duration::from_secs(10),
+                    );
+                    assert_contains!(response.body, "Directory listing for /");
+                },
+            );
+        },
+    );
+}
+
+#[test]
+#[ignore = "integration test"]
+#[should_panic(expected = "Timed out after 2s waiting for HTTP response from http://")]
+fn waiting_for_http_timeout() {
+    TestRunner::default().build(
+        BuildConfig::new("heroku/builder:22", "tests/fixtures/procfile")
+            .buildpacks([BuildpackReference::Other(String::from(PROCFILE_URL))]),
+        |context| {
+            context.start_container(
+                ContainerConfig::new()
+                    .env("PORT", TEST_PORT.to_string())
+                    .expose_port(TEST_PORT),
+                |container| {
+                    let address_on_host = container.address_for_port(TEST_PORT);
+                    let _ = container.wait_for_http(
+                        &format!("http://{address_on_host}"),
+                        |response| response.status == 418,
+                        Duration::from_secs(2),
+                    );
+                },
+            );
+        },
+    );
+}
+
+#[test]
+#[ignore = "integration test"]
+fn starting_containers_with_services() {
+    TestRunner::default().build(
+        BuildConfig::new("heroku/builder:22", "tests/fixtures/procfile")
+            .buildpacks([BuildpackReference::Other(String::from(PROCFILE_URL))]),
+        |context| {
+            context.start_container(
+                ContainerConfig::new()
+                    .entrypoint("launcher")
+                    .command(["echo PING | timeout 5 bash -c 'exec 3<>/dev/tcp/redis/6379; cat >&3; head -c 7 <&3'; echo \" $REDIS_URL\""])
+                    .service(
+                        ServiceConfig::new("redis", "redis:7-alpine")
+                            .ready_log_message("Ready to accept connections")
+                            .app_env("REDIS_URL", "redis://redis:6379"),
+                    ),
+                |container| {
+                    let all_log_output = container.logs_wait();
+                    assert_empty!(all_log_output.stderr);
+                    assert_eq!(all_log_output.stdout, "+PONG\r\n redis://redis:6379\n");
+                },
+            );
+        },
+    );
+}
+
+#[test]
+#[ignore = "integration test"]
+fn starting_containers_with_docker_run_options() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    fs::write(temp_dir.path().join("greeting.txt"), "Hello").unwrap();
+
+    TestRunner::default().build(
+        BuildConfig::new("heroku/builder:22", "tests/fixtures/procfile")
+            .buildpacks([BuildpackReference::Other(String::from(PROCFILE_URL))]),
+        |context| {
+            context.start_container(
+                ContainerConfig::new()
+                    .entrypoint("launcher")
+                    .command(["echo \"$(cat /mounted/greeting.txt) from $HOSTNAME\""])
+                    .bind_mount(temp_dir.path(), "/mounted")
+                    .memory_limit("256m")
+                    .network("none")
+                    .docker_run_arg("--hostname=libcnb-test"),
+                |container| {
+                    let all_log_output = container.logs_wait();
+                    assert_empty!(all_log_output.stderr);
+                    assert_eq!(all_log_output.stdout, "Hello from libcnb-test\n");
+                },
+            );
+        },
+    );
+}
+
 #[test]
 #[ignore = "integration test"]
 #[should_panic(expected = "Error starting container:
@@ -608,6 +913,43 @@ fn shell_exec_nonzero_exit_status() {
     );
 }
 
+#[test]
+#[ignore = "integration test"]
+fn shell_exec_unchecked_and_streaming() {
+    TestRunner::default().build(
+        BuildConfig::new("heroku/builder:22", "tests/fixtures/procfile")
+            .buildpacks([BuildpackReference::Other(String::from(PROCFILE_URL))]),
+        |context| {
+            context.start_container(ContainerConfig::new(), |container| {
+                thread::sleep(Duration::from_secs(1));
+
+                let exec_output = container
+                    .shell_exec_unchecked("echo 'some stdout'; echo 'some stderr' >&2; exit 3");
+                assert_eq!(exec_output.stdout, "some stdout\n");
+                assert_eq!(exec_output.stderr, "some stderr\n");
+                assert_eq!(exec_output.exit_code, Some(3));
+                assert!(!exec_output.success());
+
+                let mut lines = Vec::new();
+                let exec_output = container.shell_exec_streaming(
+                    "echo 'line 1'; sleep 1; echo 'line 2' >&2",
+                    |stream, line| lines.push((stream, line.to_string())),
+                );
+                assert_eq!(
+                    lines,
+                    [
+                        (ExecStream::Stdout, String::from("line 1")),
+                        (ExecStream::Stderr, String::from("line 2"))
+                    ]
+                );
+                assert_eq!(exec_output.stdout, "line 1\n");
+                assert_eq!(exec_output.stderr, "line 2\n");
+                assert!(exec_output.success());
+            });
+        },
+    );
+}
+
 #[test]
 #[ignore = "integration test"]
 #[should_panic(expected = "Error running container:
@@ -632,6 +974,25 @@ fn run_shell_command_nonzero_exit_status() {
     );
 }
 
+#[test]
+#[ignore = "integration test"]
+fn run_process() {
+    TestRunner::default().build(
+        BuildConfig::new("heroku/builder:22", "tests/fixtures/procfile")
+            .buildpacks([BuildpackReference::Other(String::from(PROCFILE_URL))]),
+        |context| {
+            assert_eq!(context.process_types(), ["web", "worker"]);
+            assert_eq!(
+                context.default_process().map(|process| process.r#type),
+                Some(String::from("web"))
+            );
+
+            let log_output = context.run_process("worker");
+            assert_eq!(log_output.stdout, "this is the worker process!\n");
+        },
+    );
+}
+
 #[test]
 #[ignore = "integration test"]
 fn logs_work_after_container_crashed() {
@@ -0,0 +1,94 @@
+use crate::build::{BuildContext, BuildResult};
+use crate::detect::{DetectContext, DetectResult};
+use crate::error::Error;
+use crate::Buildpack;
+use std::future::Future;
+
+/// Represents a buildpack written with the libcnb framework that uses `async` code.
+///
+/// This is an extension of the [`Buildpack`] trait with `async` detect and build methods. It allows
+/// buildpacks to run multiple operations, such as downloading artifacts, concurrently without
+/// setting up their own async runtime. The associated types and constants are declared in the
+/// [`Buildpack`] implementation, which forwards its detect and build methods to
+/// [`detect_blocking`](Self::detect_blocking) and [`build_blocking`](Self::build_blocking). These
+/// drive the `async` methods on a multi-threaded [tokio](https://docs.rs/tokio) runtime.
+///
+/// # Example:
+/// ```
+/// use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
+/// use libcnb::detect::{DetectContext, DetectResult, DetectResultBuilder};
+/// use libcnb::generic::{GenericError, GenericMetadata, GenericPlatform};
+/// use libcnb::{buildpack_main, AsyncBuildpack, Buildpack};
+///
+/// pub(crate) struct MyBuildpack;
+///
+/// impl Buildpack for MyBuildpack {
+///     type Platform = GenericPlatform;
+///     type Metadata = GenericMetadata;
+///     type Error = GenericError;
+///
+///     fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+///         self.detect_blocking(context)
+///     }
+///
+///     fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
+///         self.build_blocking(context)
+///     }
+/// }
+///
+/// impl AsyncBuildpack for MyBuildpack {
+///     async fn detect(
+///         &self,
+///         context: DetectContext<Self>,
+///     ) -> libcnb::Result<DetectResult, Self::Error> {
+///         DetectResultBuilder::pass().build()
+///     }
+///
+///     async fn build(
+///         &self,
+///         context: BuildContext<Self>,
+///     ) -> libcnb::Result<BuildResult, Self::Error> {
+///         BuildResultBuilder::new().build()
+///     }
+/// }
+///
+/// buildpack_main!(MyBuildpack);
+/// ```
+pub trait AsyncBuildpack: Buildpack {
+    /// Async detect logic for this buildpack. See [`Buildpack::detect`].
+    fn detect(
+        &self,
+        context: DetectContext<Self>,
+    ) -> impl Future<Output = crate::Result<DetectResult, Self::Error>>;
+
+    /// Async build logic for this buildpack. See [`Buildpack::build`].
+    fn build(
+        &self,
+        context: BuildContext<Self>,
+    ) -> impl Future<Output = crate::Result<BuildResult, Self::Error>>;
+
+    /// Runs [`AsyncBuildpack::detect`] to completion on a new async runtime. Intended to be called
+    /// from [`Buildpack::detect`].
+    fn detect_blocking(
+        &self,
+        context: DetectContext<Self>,
+    ) -> crate::Result<DetectResult, Self::Error> {
+        async_runtime()?.block_on(AsyncBuildpack::detect(self, context))
+    }
+
+    /// Runs [`AsyncBuildpack::build`] to completion on a new async runtime. Intended to be called
+    /// from [`Buildpack::build`].
+    fn build_blocking(
+        &self,
+        context: BuildContext<Self>,
+    ) -> crate::Result<BuildResult, Self::Error> {
+        async_runtime()?.block_on(AsyncBuildpack::build(self, context))
+    }
+}
+
+fn async_runtime<E>() -> crate::Result<tokio::runtime::Runtime, E> {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .map_err(Error::CannotCreateAsyncRuntime)
+}
@@ -1,26 +1,43 @@
 //! Provides build phase specific types and helpers.
 
 use crate::buildpack::Buildpack;
+use crate::data::build::Unmet;
+use crate::data::buildpack::BuildpackId;
 use crate::data::layer::LayerName;
 use crate::data::store::Store;
 use crate::data::{
     buildpack::ComponentBuildpackDescriptor, buildpack_plan::BuildpackPlan, launch::Launch,
 };
+use crate::env::user_tools_env;
+use crate::layer::artifact_store::{artifact_store_layer_name, UsedArtifacts};
+use crate::layer::buildpack_layers::{read_buildpack_layer, read_buildpack_layers};
+use crate::layer::timestamp_skew::{detect_timestamp_skew, TIMESTAMP_SKEW_TOLERANCE};
 use crate::layer::trait_api::handling::LayerErrorOrBuildpackError;
 use crate::layer::{
-    CachedLayerDefinition, IntoAction, InvalidMetadataAction, LayerRef, RestoredLayerAction,
-    UncachedLayerDefinition,
+    ArtifactStore, BuildpackLayer, CachedLayerDefinition, IntoAction, InvalidMetadataAction,
+    LayerError, LayerRef, RestoredLayerAction, TimestampSkew, UncachedLayerDefinition,
 };
+use crate::log::Logger;
 use crate::sbom::Sbom;
-use crate::Target;
+use crate::util::lock;
+use crate::{BuildpackTracer, Env, Platform, Target, TargetSpec};
 use libcnb_data::generic::GenericMetadata;
 use libcnb_data::layer_content_metadata::LayerTypes;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::borrow::Borrow;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
 /// Context for the build phase execution.
+///
+/// Helpers that are shared with the detect phase can accept [`CommonContext`](crate::CommonContext)
+/// instead.
 pub struct BuildContext<B: Buildpack + ?Sized> {
     pub layers_dir: PathBuf,
     pub app_dir: PathBuf,
@@ -28,8 +45,75 @@ pub struct BuildContext<B: Buildpack + ?Sized> {
     pub target: Target,
     pub platform: B::Platform,
     pub buildpack_plan: BuildpackPlan,
+    /// The path of the buildpack plan file (`CNB_BP_PLAN_PATH`) that
+    /// [`buildpack_plan`](Self::buildpack_plan) was read from.
+    pub buildpack_plan_path: PathBuf,
+    /// The platform directory as passed by the CNB lifecycle. Prefer [`platform`](Self::platform)
+    /// to read platform environment variables.
+    pub platform_dir: PathBuf,
     pub buildpack_descriptor: ComponentBuildpackDescriptor<B::Metadata>,
     pub store: Option<Store>,
+    /// Logger for buildpack output. See [`Logger`] for details.
+    pub log: Logger,
+    /// State of the build that is managed by libcnb. See [`BuildContextState`] for details.
+    pub state: BuildContextState,
+}
+
+/// State of a build that is managed by libcnb, i.e. temporary layers or the SBOMs attached from
+/// layer code.
+///
+/// The libcnb runtime initializes the state before calling the build function of a buildpack. Use
+/// [`BuildContextState::default`] when constructing a [`BuildContext`] outside of the runtime, for
+/// example in unit tests.
+#[derive(Debug, Default)]
+pub struct BuildContextState {
+    pub(crate) tracer: BuildpackTracer,
+    pub(crate) temp_layers: Arc<Mutex<Vec<LayerName>>>,
+    pub(crate) mutable_store: Arc<Mutex<Option<Store>>>,
+    pub(crate) store_mut_borrowed: AtomicBool,
+    pub(crate) collected_sboms: Arc<Mutex<CollectedSboms>>,
+    pub(crate) timestamp_skews: Mutex<HashMap<LayerName, Option<TimestampSkew>>>,
+    pub(crate) used_artifacts: UsedArtifacts,
+    pub(crate) handled_layers: Mutex<HashMap<LayerName, LayerTypes>>,
+}
+
+/// SBOMs attached from layer code via [`LayerRef::add_launch_sbom`] and
+/// [`LayerRef::add_build_sbom`], written by the runtime after the build finished.
+#[derive(Debug, Default)]
+pub(crate) struct CollectedSboms {
+    pub(crate) build: Vec<Sbom>,
+    pub(crate) launch: Vec<Sbom>,
+}
+
+/// Mutable access to the buildpack store, returned by [`BuildContext::store_mut`].
+///
+/// Changes are made to a copy of the store, which replaces the store of the build when this value
+/// is dropped. Until then, [`BuildContext::store_or_default`] returns the store without them.
+pub struct StoreMut<'a> {
+    store: Store,
+    mutable_store: &'a Mutex<Option<Store>>,
+    borrowed: &'a AtomicBool,
+}
+
+impl Deref for StoreMut<'_> {
+    type Target = Store;
+
+    fn deref(&self) -> &Self::Target {
+        &self.store
+    }
+}
+
+impl DerefMut for StoreMut<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.store
+    }
+}
+
+impl Drop for StoreMut<'_> {
+    fn drop(&mut self) {
+        *lock(self.mutable_store) = Some(std::mem::take(&mut self.store));
+        self.borrowed.store(false, Ordering::Release);
+    }
 }
 
 impl<B: Buildpack + ?Sized> BuildContext<B> {
@@ -171,8 +255,8 @@ impl<B: Buildpack + ?Sized> BuildContext<B> {
     ///         build: false,
     ///         launch: false,
     ///         // Will be called if a cached version of the layer was found, but the metadata
-    ///         // could not be parsed. In this example, we instruct libcnb to always delete the
-    ///         // existing layer in such a case. But we can implement any logic here if we want.
+    ///         // could not be parsed. In this example, we instruct libcnb to always
+    ///         // delete the existing layer in such a case. But we can implement any logic here.
     ///         invalid_metadata_action: &|_| InvalidMetadataAction::DeleteLayer,
     ///         // Will be called if a cached version of the layer was found. This allows us to
     ///         // inspect the contents and metadata to decide if we want to keep the existing
@@ -358,23 +442,104 @@ impl<B: Buildpack + ?Sized> BuildContext<B> {
         layer_name: impl Borrow<LayerName>,
         layer_definition: impl Borrow<CachedLayerDefinition<'a, M, MA, RA>>,
     ) -> crate::Result<LayerRef<B, MAC, RAC>, B::Error>
+    where
+        M: 'a + Serialize + DeserializeOwned,
+        MA: 'a + IntoAction<InvalidMetadataAction<M>, MAC, B::Error>,
+        RA: 'a + IntoAction<RestoredLayerAction, RAC, B::Error>,
+    {
+        self.cached_layer_with_metadata_migration(layer_name, layer_definition, &|_| None)
+    }
+
+    /// Creates a cached layer, migrating the metadata of a restored layer if necessary.
+    ///
+    /// Works like [`cached_layer`](Self::cached_layer), but calls `migrate_metadata` first if the
+    /// metadata of a restored layer cannot be parsed as `M`. The `invalid_metadata_action` of the
+    /// layer definition is only called if the migration returns `None`. Use
+    /// [`migrate_versioned_metadata`](crate::layer::migrate_versioned_metadata) to migrate
+    /// stepwise along the previous versions declared by a
+    /// [`MetadataVersioned`](crate::layer::MetadataVersioned) implementation.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
+    /// # use libcnb::detect::{DetectContext, DetectResult};
+    /// # use libcnb::generic::{GenericError, GenericMetadata, GenericPlatform};
+    /// # use libcnb::layer::{
+    /// #     migrate_versioned_metadata, CachedLayerDefinition, InvalidMetadataAction,
+    /// #     MetadataVersioned, NoPreviousMetadataVersion, RestoredLayerAction,
+    /// # };
+    /// # use libcnb::Buildpack;
+    /// # use libcnb_data::layer_name;
+    /// # use serde::{Deserialize, Serialize};
+    /// #
+    /// # struct ExampleBuildpack;
+    /// #
+    /// # impl Buildpack for ExampleBuildpack {
+    /// #    type Platform = GenericPlatform;
+    /// #    type Metadata = GenericMetadata;
+    /// #    type Error = GenericError;
+    /// #
+    /// #    fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+    /// #        unimplemented!()
+    /// #    }
+    /// #
+    /// #    fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
+    /// let layer_ref = context.cached_layer_with_metadata_migration(
+    ///     layer_name!("runtime"),
+    ///     CachedLayerDefinition {
+    ///         build: false,
+    ///         launch: true,
+    ///         invalid_metadata_action: &|_| InvalidMetadataAction::DeleteLayer,
+    ///         restored_layer_action: &|_: &RuntimeLayerMetadata, _| RestoredLayerAction::KeepLayer,
+    ///     },
+    ///     &migrate_versioned_metadata::<RuntimeLayerMetadata>,
+    /// )?;
+    /// #
+    /// #        BuildResultBuilder::new().build()
+    /// #    }
+    /// # }
+    /// #
+    /// # #[derive(Deserialize, Serialize)]
+    /// # struct RuntimeLayerMetadata {
+    /// #     version: String,
+    /// # }
+    /// #
+    /// # impl MetadataVersioned for RuntimeLayerMetadata {
+    /// #     type Previous = NoPreviousMetadataVersion;
+    /// #
+    /// #     fn migrate_from_previous(previous: Self::Previous) -> Option<Self> {
+    /// #         match previous {}
+    /// #     }
+    /// # }
+    /// ```
+    pub fn cached_layer_with_metadata_migration<'a, M, MA, RA, MAC, RAC>(
+        &self,
+        layer_name: impl Borrow<LayerName>,
+        layer_definition: impl Borrow<CachedLayerDefinition<'a, M, MA, RA>>,
+        migrate_metadata: &'a dyn Fn(&GenericMetadata) -> Option<M>,
+    ) -> crate::Result<LayerRef<B, MAC, RAC>, B::Error>
     where
         M: 'a + Serialize + DeserializeOwned,
         MA: 'a + IntoAction<InvalidMetadataAction<M>, MAC, B::Error>,
         RA: 'a + IntoAction<RestoredLayerAction, RAC, B::Error>,
     {
         let layer_definition = layer_definition.borrow();
+        let layer_types = LayerTypes {
+            launch: layer_definition.launch,
+            build: layer_definition.build,
+            cache: true,
+        };
+
+        self.register_layer(layer_name.borrow(), layer_types)?;
 
         crate::layer::struct_api::handling::handle_layer(
-            LayerTypes {
-                launch: layer_definition.launch,
-                build: layer_definition.build,
-                cache: true,
-            },
+            layer_types,
+            migrate_metadata,
             layer_definition.invalid_metadata_action,
             layer_definition.restored_layer_action,
             layer_name.borrow(),
             &self.layers_dir,
+            &self.state.collected_sboms,
         )
     }
 
@@ -458,19 +623,542 @@ impl<B: Buildpack + ?Sized> BuildContext<B> {
         layer_definition: impl Borrow<UncachedLayerDefinition>,
     ) -> crate::Result<LayerRef<B, (), ()>, B::Error> {
         let layer_definition = layer_definition.borrow();
+        let layer_types = LayerTypes {
+            launch: layer_definition.launch,
+            build: layer_definition.build,
+            cache: false,
+        };
+
+        self.register_layer(layer_name.borrow(), layer_types)?;
 
         crate::layer::struct_api::handling::handle_layer(
-            LayerTypes {
-                launch: layer_definition.launch,
-                build: layer_definition.build,
-                cache: false,
-            },
+            layer_types,
+            &|_| None,
+            &|_| InvalidMetadataAction::DeleteLayer,
+            &|_: &GenericMetadata, _| RestoredLayerAction::DeleteLayer,
+            layer_name.borrow(),
+            &self.layers_dir,
+            &self.state.collected_sboms,
+        )
+    }
+
+    /// Returns a mutable reference to the store of this buildpack.
+    ///
+    /// On first access, the store is initialized with a copy of [`BuildContext::store`] (or an empty
+    /// store if there is none). Any changes made through the returned reference are automatically
+    /// persisted to `store.toml` after the build function of the buildpack returned successfully.
+    /// There is no need to pass the store to [`BuildResultBuilder::store`]. If a store is passed
+    /// anyway, it is merged with the changes made through this method, with its values taking
+    /// precedence for keys that are in both stores.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a reference returned by a previous call is still alive, on any thread.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
+    /// # use libcnb::detect::{DetectContext, DetectResult};
+    /// # use libcnb::generic::{GenericError, GenericMetadata, GenericPlatform};
+    /// # use libcnb::Buildpack;
+    /// #
+    /// # struct ExampleBuildpack;
+    /// #
+    /// # impl Buildpack for ExampleBuildpack {
+    /// #    type Platform = GenericPlatform;
+    /// #    type Metadata = GenericMetadata;
+    /// #    type Error = GenericError;
+    /// #
+    /// #    fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+    /// #        unimplemented!()
+    /// #    }
+    /// #
+    /// #    fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
+    /// context.store_mut().metadata.insert(
+    ///     String::from("last_build_version"),
+    ///     toml::Value::from("1.2.3"),
+    /// );
+    ///
+    /// BuildResultBuilder::new().build()
+    /// #    }
+    /// # }
+    /// ```
+    pub fn store_mut(&self) -> StoreMut<'_> {
+        assert!(
+            !self.state.store_mut_borrowed.swap(true, Ordering::Acquire),
+            "BuildContext::store_mut called while a previously returned StoreMut is still alive"
+        );
+
+        StoreMut {
+            store: self.store_or_default(),
+            mutable_store: &self.state.mutable_store,
+            borrowed: &self.state.store_mut_borrowed,
+        }
+    }
+
+    /// Returns a copy of the current buildpack store, or an empty store if there is none.
+    ///
+    /// The copy includes changes made via [`store_mut`](Self::store_mut), once the returned
+    /// [`StoreMut`] was dropped. Use [`Store::get`] to read typed values from it.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
+    /// # use libcnb::detect::{DetectContext, DetectResult};
+    /// # use libcnb::generic::{GenericError, GenericMetadata, GenericPlatform};
+    /// # use libcnb::Buildpack;
+    /// #
+    /// # struct ExampleBuildpack;
+    /// #
+    /// # impl Buildpack for ExampleBuildpack {
+    /// #    type Platform = GenericPlatform;
+    /// #    type Metadata = GenericMetadata;
+    /// #    type Error = GenericError;
+    /// #
+    /// #    fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+    /// #        unimplemented!()
+    /// #    }
+    /// #
+    /// #    fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
+    /// let last_build_version = context
+    ///     .store_or_default()
+    ///     .get::<String>("last_build_version")
+    ///     .unwrap_or_default();
+    ///
+    /// context
+    ///     .store_mut()
+    ///     .set("last_build_version", "1.2.3")
+    ///     .expect("string values to be serializable");
+    /// #
+    /// #        BuildResultBuilder::new().build()
+    /// #    }
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn store_or_default(&self) -> Store {
+        lock(&self.state.mutable_store)
+            .clone()
+            .or_else(|| self.store.clone())
+            .unwrap_or_default()
+    }
+
+    /// Deserializes the metadata of all buildpack plan entries with the given name.
+    ///
+    /// The metadata is the `[requires.metadata]` table of the requirements that were resolved to
+    /// this buildpack during detection. The result contains one element per matching entry. See
+    /// [`merged_buildpack_plan_entry`](Self::merged_buildpack_plan_entry) to combine them into a
+    /// single value.
+    ///
+    /// # Errors
+    /// Returns an error if the metadata of any matching entry can't be deserialized into a `T`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
+    /// # use libcnb::detect::{DetectContext, DetectResult};
+    /// # use libcnb::generic::{GenericError, GenericMetadata, GenericPlatform};
+    /// # use libcnb::Buildpack;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct JdkRequirement {
+    ///     version: String,
+    /// }
+    /// #
+    /// # struct ExampleBuildpack;
+    /// #
+    /// # impl Buildpack for ExampleBuildpack {
+    /// #    type Platform = GenericPlatform;
+    /// #    type Metadata = GenericMetadata;
+    /// #    type Error = GenericError;
+    /// #
+    /// #    fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+    /// #        unimplemented!()
+    /// #    }
+    /// #
+    /// #    fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
+    /// let jdk_requirements = context
+    ///     .buildpack_plan_entries::<JdkRequirement>("jdk")
+    ///     .expect("JDK requirements to be valid");
+    /// #
+    /// #        BuildResultBuilder::new().build()
+    /// #    }
+    /// # }
+    /// ```
+    pub fn buildpack_plan_entries<T: DeserializeOwned>(
+        &self,
+        name: &str,
+    ) -> Result<Vec<T>, toml::de::Error> {
+        self.buildpack_plan.entries_metadata(name)
+    }
+
+    /// Merges the metadata of all buildpack plan entries with the given name and deserializes it.
+    ///
+    /// Entries are merged in plan order, top-level keys of later entries override the keys of
+    /// earlier ones. Returns `None` if the buildpack plan has no entry with the given name.
+    ///
+    /// # Errors
+    /// Returns an error if the merged metadata can't be deserialized into a `T`.
+    pub fn merged_buildpack_plan_entry<T: DeserializeOwned>(
+        &self,
+        name: &str,
+    ) -> Result<Option<T>, toml::de::Error> {
+        self.buildpack_plan.merged_entry_metadata(name)
+    }
+
+    /// Returns the files of the given restored layer that have modification times in the future.
+    ///
+    /// The layer is checked the first time this is called for it, the result is reused for later
+    /// calls. Use this in the `restored_layer_action` of a [`CachedLayerDefinition`], before the
+    /// layer is modified, to delete layers whose contents are used by incremental tools that rely
+    /// on modification times. Returns `None` for layers that weren't restored.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
+    /// # use libcnb::detect::{DetectContext, DetectResult};
+    /// # use libcnb::generic::{GenericError, GenericMetadata, GenericPlatform};
+    /// # use libcnb::layer::{CachedLayerDefinition, InvalidMetadataAction, RestoredLayerAction};
+    /// # use libcnb::Buildpack;
+    /// # use libcnb_data::layer_name;
+    /// #
+    /// # struct ExampleBuildpack;
+    /// #
+    /// # impl Buildpack for ExampleBuildpack {
+    /// #    type Platform = GenericPlatform;
+    /// #    type Metadata = GenericMetadata;
+    /// #    type Error = GenericError;
+    /// #
+    /// #    fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+    /// #        unimplemented!()
+    /// #    }
+    /// #
+    /// #    fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
+    /// let build_cache_layer = context.cached_layer(
+    ///     layer_name!("build-cache"),
+    ///     CachedLayerDefinition {
+    ///         build: false,
+    ///         launch: false,
+    ///         invalid_metadata_action: &|_| InvalidMetadataAction::DeleteLayer,
+    ///         restored_layer_action: &|_: &GenericMetadata, _| {
+    ///             if context
+    ///                 .restored_layer_timestamp_skew(&layer_name!("build-cache"))
+    ///                 .is_some()
+    ///             {
+    ///                 RestoredLayerAction::DeleteLayer
+    ///             } else {
+    ///                 RestoredLayerAction::KeepLayer
+    ///             }
+    ///         },
+    ///     },
+    /// )?;
+    /// #
+    /// #        BuildResultBuilder::new().build()
+    /// #    }
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn restored_layer_timestamp_skew(&self, layer_name: &LayerName) -> Option<TimestampSkew> {
+        lock(&self.state.timestamp_skews)
+            .entry(layer_name.clone())
+            .or_insert_with(|| {
+                detect_timestamp_skew(
+                    &self.layers_dir,
+                    layer_name,
+                    SystemTime::now(),
+                    TIMESTAMP_SKEW_TOLERANCE,
+                )
+            })
+            .clone()
+    }
+
+    /// Creates a temporary layer to be used as scratch space during the build.
+    ///
+    /// The layer is neither available during build of subsequent buildpacks, nor at launch, nor
+    /// is it cached. libcnb deletes temporary layers after the build function of the buildpack
+    /// has returned, regardless of whether the build succeeded or not. Use temporary layers for
+    /// transient files (i.e. downloaded archives that are extracted to another layer) instead of
+    /// writing to directories outside of the layers directory.
+    ///
+    /// If the layer already exists, the existing data will be deleted.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
+    /// # use libcnb::detect::{DetectContext, DetectResult};
+    /// # use libcnb::generic::{GenericError, GenericMetadata, GenericPlatform};
+    /// # use libcnb::Buildpack;
+    /// # use libcnb_data::layer_name;
+    /// #
+    /// # struct ExampleBuildpack;
+    /// #
+    /// # impl Buildpack for ExampleBuildpack {
+    /// #    type Platform = GenericPlatform;
+    /// #    type Metadata = GenericMetadata;
+    /// #    type Error = GenericError;
+    /// #
+    /// #    fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+    /// #        unimplemented!()
+    /// #    }
+    /// #
+    /// #    fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
+    /// let scratch_layer = context.temp_layer(layer_name!("scratch"))?;
+    /// let archive_path = scratch_layer.path().join("runtime.tgz");
+    ///
+    /// // Code to download and extract the archive to another layer would go here
+    /// #
+    /// #        BuildResultBuilder::new().build()
+    /// #    }
+    /// # }
+    /// ```
+    pub fn temp_layer(
+        &self,
+        layer_name: impl Borrow<LayerName>,
+    ) -> crate::Result<LayerRef<B, (), ()>, B::Error> {
+        let layer_types = LayerTypes {
+            launch: false,
+            build: false,
+            cache: false,
+        };
+
+        self.register_layer(layer_name.borrow(), layer_types)?;
+
+        let layer_ref = crate::layer::struct_api::handling::handle_layer(
+            layer_types,
+            &|_| None,
             &|_| InvalidMetadataAction::DeleteLayer,
             &|_: &GenericMetadata, _| RestoredLayerAction::DeleteLayer,
             layer_name.borrow(),
             &self.layers_dir,
+            &self.state.collected_sboms,
+        )?;
+
+        lock(&self.state.temp_layers).push(layer_name.borrow().clone());
+
+        Ok(layer_ref)
+    }
+
+    /// Returns the content-addressed [`ArtifactStore`] of this build.
+    ///
+    /// The store is backed by a cache-only layer managed by libcnb, so artifacts are restored in
+    /// subsequent builds. Use it to download artifacts that are used by multiple layers only once.
+    ///
+    /// # Example:
+    /// ```
+    /// # use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
+    /// # use libcnb::data::layer_name;
+    /// # use libcnb::detect::{DetectContext, DetectResult};
+    /// # use libcnb::generic::{GenericError, GenericMetadata, GenericPlatform};
+    /// # use libcnb::layer::{ArtifactDigest, UncachedLayerDefinition};
+    /// # use libcnb::Buildpack;
+    /// #
+    /// # struct ExampleBuildpack;
+    /// #
+    /// # impl Buildpack for ExampleBuildpack {
+    /// #    type Platform = GenericPlatform;
+    /// #    type Metadata = GenericMetadata;
+    /// #    type Error = GenericError;
+    /// #
+    /// #    fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+    /// #        unimplemented!()
+    /// #    }
+    /// #
+    /// #    fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
+    /// # let jdk_digest = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824".parse::<ArtifactDigest>().unwrap();
+    /// # fn download(_: &std::path::Path) {}
+    /// let artifact_store = context.artifact_store()?;
+    ///
+    /// if !artifact_store.contains(&jdk_digest) {
+    ///     let scratch_layer = context.temp_layer(layer_name!("scratch"))?;
+    ///     let download_path = scratch_layer.path().join("jdk.tar.gz");
+    ///     download(&download_path);
+    ///     artifact_store
+    ///         .insert_file_with_digest(&download_path, &jdk_digest)
+    ///         .unwrap();
+    /// }
+    ///
+    /// for tool in [layer_name!("maven"), layer_name!("gradle")] {
+    ///     let tool_layer = context.uncached_layer(
+    ///         tool,
+    ///         UncachedLayerDefinition {
+    ///             build: true,
+    ///             launch: false,
+    ///         },
+    ///     )?;
+    ///
+    ///     artifact_store
+    ///         .materialize(&jdk_digest, tool_layer.path().join("jdk.tar.gz"))
+    ///         .unwrap();
+    /// }
+    /// #
+    /// #        BuildResultBuilder::new().build()
+    /// #    }
+    /// # }
+    /// ```
+    pub fn artifact_store(&self) -> crate::Result<ArtifactStore, B::Error> {
+        let layer_name = artifact_store_layer_name();
+        let layer_types = LayerTypes {
+            launch: false,
+            build: false,
+            cache: true,
+        };
+
+        self.register_layer(&layer_name, layer_types)?;
+
+        let _: LayerRef<B, (), ()> = crate::layer::struct_api::handling::handle_layer(
+            layer_types,
+            &|_| None,
+            &|_| InvalidMetadataAction::DeleteLayer,
+            &|_: &GenericMetadata, _| RestoredLayerAction::KeepLayer,
+            &layer_name,
+            &self.layers_dir,
+            &self.state.collected_sboms,
+        )?;
+
+        Ok(ArtifactStore::new(
+            self.layers_dir.join(layer_name.as_str()),
+            Arc::clone(&self.state.used_artifacts),
+        ))
+    }
+
+    /// Returns read-only views of all layers contributed by the given buildpack, sorted by name.
+    ///
+    /// Use this to read layers of buildpacks that ran earlier in the group, i.e. to locate the
+    /// Node.js installation of a Node.js engine buildpack in a buildpack that installs
+    /// `node_modules`. Only layers with `build = true` are returned, since the lifecycle only
+    /// makes those available to later buildpacks. Returns an empty list if the buildpack didn't
+    /// run or didn't contribute `build` layers.
+    ///
+    /// # Example:
+    /// ```
+    /// # use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
+    /// # use libcnb::data::{buildpack_id, layer_name};
+    /// # use libcnb::detect::{DetectContext, DetectResult};
+    /// # use libcnb::generic::{GenericError, GenericMetadata, GenericPlatform};
+    /// # use libcnb::Buildpack;
+    /// # use serde::Deserialize;
+    /// #
+    /// # struct ExampleBuildpack;
+    /// #
+    /// # impl Buildpack for ExampleBuildpack {
+    /// #    type Platform = GenericPlatform;
+    /// #    type Metadata = GenericMetadata;
+    /// #    type Error = GenericError;
+    /// #
+    /// #    fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+    /// #        unimplemented!()
+    /// #    }
+    /// #
+    /// #    fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
+    /// #[derive(Deserialize)]
+    /// struct NodeLayerMetadata {
+    ///     version: String,
+    /// }
+    ///
+    /// if let Some(node_layer) =
+    ///     context.buildpack_layer(&buildpack_id!("heroku/nodejs-engine"), &layer_name!("dist"))?
+    /// {
+    ///     let node_version = node_layer
+    ///         .metadata::<NodeLayerMetadata>()
+    ///         .map(|metadata| metadata.version)
+    ///         .ok();
+    ///     let node_binary = node_layer.path().join("bin/node");
+    /// }
+    /// #
+    /// #        BuildResultBuilder::new().build()
+    /// #    }
+    /// # }
+    /// ```
+    pub fn buildpack_layers(
+        &self,
+        buildpack_id: &BuildpackId,
+    ) -> crate::Result<Vec<BuildpackLayer>, B::Error> {
+        read_buildpack_layers(&self.layers_dir, buildpack_id)
+            .map_err(|error| crate::Error::LayerError(LayerError::ReadLayerError(error)))
+    }
+
+    /// Returns a read-only view of a single layer contributed by the given buildpack.
+    ///
+    /// Returns `None` if the layer doesn't exist or isn't a `build` layer. See
+    /// [`buildpack_layers`](Self::buildpack_layers) for details.
+    pub fn buildpack_layer(
+        &self,
+        buildpack_id: &BuildpackId,
+        layer_name: &LayerName,
+    ) -> crate::Result<Option<BuildpackLayer>, B::Error> {
+        read_buildpack_layer(&self.layers_dir, buildpack_id, layer_name)
+            .map_err(|error| crate::Error::LayerError(LayerError::ReadLayerError(error)))
+    }
+
+    /// Returns whether the buildpack is declared with `clear-env = true` in its `buildpack.toml`.
+    ///
+    /// The lifecycle doesn't set the platform environment variables for the processes of such
+    /// buildpacks. See [`env_for_user_tools`](Self::env_for_user_tools) for an environment that
+    /// takes this into account.
+    #[must_use]
+    pub fn clear_env(&self) -> bool {
+        self.buildpack_descriptor.buildpack.clear_env
+    }
+
+    /// Returns the environment for commands the buildpack runs on behalf of the user, such as
+    /// package managers or build tools of the app.
+    ///
+    /// The CNB spec requires buildpacks declared with `clear-env = true` to set the platform
+    /// environment variables for such commands themselves. This is the environment of the
+    /// buildpack process, with the platform environment variables added if the buildpack is
+    /// declared with `clear-env = true`. Otherwise, the lifecycle already set them for the
+    /// buildpack process.
+    #[must_use]
+    pub fn env_for_user_tools(&self) -> Env {
+        user_tools_env(
+            Env::from_current(),
+            self.platform.env(),
+            self.buildpack_descriptor.buildpack.clear_env,
         )
     }
+
+    /// Returns whether the target the buildpack runs on matches the given [`TargetSpec`].
+    ///
+    /// See [`Target::matches`] for details.
+    #[must_use]
+    pub fn target_matches(&self, spec: &TargetSpec) -> bool {
+        self.target.matches(spec)
+    }
+
+    /// Records that a layer is handled with the given types during this build.
+    ///
+    /// Handling the same layer more than once is allowed, as long as the types don't change.
+    /// Otherwise, the later definition would silently change the layer created by the earlier one,
+    /// i.e. discard a layer that is expected to be cached.
+    pub(crate) fn register_layer(
+        &self,
+        layer_name: &LayerName,
+        layer_types: LayerTypes,
+    ) -> Result<(), LayerError> {
+        match lock(&self.state.handled_layers).entry(layer_name.clone()) {
+            Entry::Occupied(entry) if *entry.get() != layer_types => {
+                Err(LayerError::ConflictingLayerDefinition {
+                    layer_name: layer_name.clone(),
+                    previous: *entry.get(),
+                    current: layer_types,
+                })
+            }
+            Entry::Occupied(_) => Ok(()),
+            Entry::Vacant(entry) => {
+                entry.insert(layer_types);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns a handle for adding spans and events to the trace of the build phase.
+    ///
+    /// Spans are only recorded when the `trace` feature is enabled. See [`BuildpackTracer`] for
+    /// details.
+    #[must_use]
+    pub fn tracer(&self) -> &BuildpackTracer {
+        &self.state.tracer
+    }
 }
 
 /// Describes the result of the build phase.
@@ -493,6 +1181,7 @@ pub(crate) enum InnerBuildResult {
         store: Option<Store>,
         build_sboms: Vec<Sbom>,
         launch_sboms: Vec<Sbom>,
+        unmet: Vec<Unmet>,
     },
 }
 
@@ -526,6 +1215,7 @@ pub struct BuildResultBuilder {
     store: Option<Store>,
     build_sboms: Vec<Sbom>,
     launch_sboms: Vec<Sbom>,
+    unmet: Vec<Unmet>,
 }
 
 impl BuildResultBuilder {
@@ -539,17 +1229,37 @@ impl BuildResultBuilder {
     /// not fallible. This is done to simplify using this method in the context it's most often used
     /// in: a buildpack's [build method](crate::Buildpack::build).
     ///
-    /// See [`build_unwrapped`](Self::build_unwrapped) for an unwrapped version of this method.
+    /// See [`build_unwrapped`](Self::build_unwrapped) for an unwrapped version of this method and
+    /// [`try_build`](Self::try_build) for a version that validates the launch configuration.
     pub fn build<E>(self) -> Result<BuildResult, E> {
         Ok(self.build_unwrapped())
     }
 
+    /// Builds the final [`BuildResult`] after validating its launch configuration.
+    ///
+    /// Fails if process types aren't unique, more than one process is the default process or a
+    /// process has an empty command. See [`Launch::validate`] for details. libcnb validates the
+    /// launch configuration again before writing `launch.toml`, this method allows buildpacks to
+    /// handle the error themselves.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error::InvalidLaunch`] if the launch configuration is invalid.
+    pub fn try_build<E>(self) -> crate::Result<BuildResult, E> {
+        if let Some(launch) = &self.launch {
+            launch.validate().map_err(crate::Error::InvalidLaunch)?;
+        }
+
+        Ok(self.build_unwrapped())
+    }
+
     pub fn build_unwrapped(self) -> BuildResult {
         BuildResult(InnerBuildResult::Pass {
             launch: self.launch,
             store: self.store,
             build_sboms: self.build_sboms,
             launch_sboms: self.launch_sboms,
+            unmet: self.unmet,
         })
     }
 
@@ -583,4 +1293,15 @@ impl BuildResultBuilder {
         self.launch_sboms.push(sbom);
         self
     }
+
+    /// Marks a buildpack plan entry as unmet.
+    ///
+    /// Use this if the buildpack decided not to provide a dependency it was asked for in the
+    /// buildpack plan. The entry is written to `build.toml` and the lifecycle passes it on to
+    /// subsequent buildpacks that require it. This function can be called multiple times to mark
+    /// multiple entries as unmet.
+    pub fn unmet_requirement(mut self, name: impl Into<String>) -> Self {
+        self.unmet.push(Unmet::new(name));
+        self
+    }
 }
@@ -1,6 +1,9 @@
 use crate::build::{BuildContext, BuildResult};
 use crate::detect::{DetectContext, DetectResult};
-use crate::Platform;
+use crate::layer::LayerIoRetryPolicy;
+#[cfg(feature = "trace")]
+use crate::TraceExporter;
+use crate::{ErrorReport, Platform, PlatformEnvVar};
 use serde::de::DeserializeOwned;
 use std::fmt::Debug;
 
@@ -26,6 +29,42 @@ pub trait Buildpack {
     /// during buildpack execution.
     type Error: Debug;
 
+    /// The retry policy for layer I/O operations that failed with a transient error. See
+    /// [`LayerIoRetryPolicy`] for details.
+    ///
+    /// Defaults to [`LayerIoRetryPolicy::DISABLED`].
+    const LAYER_IO_RETRY_POLICY: LayerIoRetryPolicy = LayerIoRetryPolicy::DISABLED;
+
+    /// Controls how malformed content metadata of restored layers is handled, i.e. TOML written by
+    /// older buildpack versions or edited manually.
+    ///
+    /// By default, restored layer content metadata that isn't valid TOML or has an invalid
+    /// `[types]` table fails the build. If enabled, the layer types are salvaged if possible and
+    /// the metadata is treated as invalid instead, which triggers the `invalid_metadata_action`
+    /// (or [`Layer::migrate_incompatible_metadata`](crate::layer::Layer::migrate_incompatible_metadata))
+    /// of the layer with empty metadata.
+    ///
+    /// Defaults to `false`.
+    const LENIENT_LAYER_METADATA_PARSING: bool = false;
+
+    /// The platform environment variables used by this buildpack. See [`PlatformEnvVar`] for
+    /// details.
+    ///
+    /// The framework validates the platform environment against these declarations before
+    /// [`detect`](Self::detect) and [`build`](Self::build) are called and fails with
+    /// [`Error::InvalidPlatformEnv`](crate::Error::InvalidPlatformEnv) if validation fails.
+    ///
+    /// Defaults to no declarations.
+    const PLATFORM_ENV_VARS: &'static [PlatformEnvVar] = &[];
+
+    /// The exporter for the traces of the detect and build phases. See [`TraceExporter`] for
+    /// details.
+    ///
+    /// Defaults to [`TraceExporter::FromEnv`], which lets platform operators configure the export
+    /// with the standard OpenTelemetry environment variables.
+    #[cfg(feature = "trace")]
+    const TRACE_EXPORTER: TraceExporter = TraceExporter::FromEnv;
+
     /// Detect logic for this buildpack. Directly corresponds to
     /// [detect in the CNB buildpack interface](https://github.com/buildpacks/spec/blob/platform/v0.10/buildpack.md#detection).
     fn detect(&self, context: DetectContext<Self>) -> crate::Result<DetectResult, Self::Error>;
@@ -36,16 +75,16 @@ pub trait Buildpack {
 
     /// If an unhandled error occurred within the framework or the buildpack, this method will be
     /// called by the framework to allow custom, buildpack specific, code to run before exiting.
-    /// Usually, this method is implemented by logging the error in a user friendly manner.
+    /// Usually, this method is implemented by describing the error in a user friendly manner.
     ///
-    /// Implementations are not limited to just logging, for example, buildpacks might want to
-    /// collect and send metrics about occurring errors to a central system.
+    /// The returned [`ErrorReport`] controls the message written to stderr, whether internal
+    /// details are included and the exit code of the buildpack. Implementations are not limited to
+    /// reporting, for example, buildpacks might want to collect and send metrics about occurring
+    /// errors to a central system.
     ///
-    /// The default implementation will simply print the error
-    /// (using its [`Debug`] implementation) to stderr.
-    fn on_error(&self, error: crate::Error<Self::Error>) {
-        eprintln!("Unhandled error:");
-        eprintln!("> {error:?}");
-        eprintln!("Buildpack will exit!");
+    /// The default implementation will simply report the error
+    /// (using its [`Debug`] implementation).
+    fn on_error(&self, error: crate::Error<Self::Error>) -> ErrorReport {
+        ErrorReport::unhandled_error(&error, "Buildpack")
     }
 }
@@ -0,0 +1,105 @@
+use crate::build::BuildContext;
+use crate::buildpack::Buildpack;
+use crate::data::buildpack::ComponentBuildpackDescriptor;
+use crate::detect::DetectContext;
+use crate::log::Logger;
+use crate::Target;
+use std::path::Path;
+
+/// Access to the parts of the context that are shared between the detect and build phase.
+///
+/// Implemented by [`DetectContext`] and [`BuildContext`], so that helpers used in both phases can
+/// accept either context instead of duplicating their signatures or taking each field as a
+/// separate argument.
+///
+/// # Example
+/// ```
+/// use libcnb::{CommonContext, Platform};
+///
+/// fn node_env(context: &impl CommonContext) -> String {
+///     context
+///         .platform()
+///         .env()
+///         .get_string_lossy("NODE_ENV")
+///         .unwrap_or_else(|| String::from("production"))
+/// }
+///
+/// fn has_package_json(context: &impl CommonContext) -> bool {
+///     context.app_dir().join("package.json").is_file()
+/// }
+/// ```
+pub trait CommonContext {
+    /// The buildpack the context belongs to.
+    type Buildpack: Buildpack + ?Sized;
+
+    fn app_dir(&self) -> &Path;
+
+    fn buildpack_dir(&self) -> &Path;
+
+    fn target(&self) -> &Target;
+
+    fn platform(&self) -> &<Self::Buildpack as Buildpack>::Platform;
+
+    fn buildpack_descriptor(
+        &self,
+    ) -> &ComponentBuildpackDescriptor<<Self::Buildpack as Buildpack>::Metadata>;
+
+    /// Logger for buildpack output. See [`Logger`] for details.
+    fn log(&self) -> &Logger;
+}
+
+impl<B: Buildpack + ?Sized> CommonContext for DetectContext<B> {
+    type Buildpack = B;
+
+    fn app_dir(&self) -> &Path {
+        &self.app_dir
+    }
+
+    fn buildpack_dir(&self) -> &Path {
+        &self.buildpack_dir
+    }
+
+    fn target(&self) -> &Target {
+        &self.target
+    }
+
+    fn platform(&self) -> &B::Platform {
+        &self.platform
+    }
+
+    fn buildpack_descriptor(&self) -> &ComponentBuildpackDescriptor<B::Metadata> {
+        &self.buildpack_descriptor
+    }
+
+    fn log(&self) -> &Logger {
+        &self.log
+    }
+}
+
+impl<B: Buildpack + ?Sized> CommonContext for BuildContext<B> {
+    type Buildpack = B;
+
+    fn app_dir(&self) -> &Path {
+        &self.app_dir
+    }
+
+    fn buildpack_dir(&self) -> &Path {
+        &self.buildpack_dir
+    }
+
+    fn target(&self) -> &Target {
+        &self.target
+    }
+
+    fn platform(&self) -> &B::Platform {
+        &self.platform
+    }
+
+    fn buildpack_descriptor(&self) -> &ComponentBuildpackDescriptor<B::Metadata> {
+        &self.buildpack_descriptor
+    }
+
+    fn log(&self) -> &Logger {
+        &self.log
+    }
+}
@@ -0,0 +1,159 @@
+//! Validation of the buildpack descriptor (`buildpack.toml`) for constructs that are deprecated by
+//! the CNB spec or likely cause lifecycle failures, run before the buildpack's detect and build
+//! code.
+
+use crate::data::buildpack::ComponentBuildpackDescriptor;
+use crate::log::Logger;
+
+/// A construct of the buildpack descriptor that the buildpack author should change.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) enum DescriptorWarning {
+    /// `[[stacks]]` are declared although the Buildpack API replaced them with `[[targets]]`.
+    DeprecatedStacks,
+    /// No `[[targets]]` are declared, so the lifecycle has to infer them.
+    MissingTargets,
+    /// A target declares an architecture variant without an architecture.
+    TargetVariantWithoutArch(String),
+    /// A target declares distributions without an operating system.
+    TargetDistrosWithoutOs,
+}
+
+impl DescriptorWarning {
+    fn header(&self) -> &'static str {
+        match self {
+            Self::DeprecatedStacks => "Deprecated [[stacks]] in buildpack.toml",
+            Self::MissingTargets => "No [[targets]] in buildpack.toml",
+            Self::TargetVariantWithoutArch(_) | Self::TargetDistrosWithoutOs => {
+                "Incomplete target in buildpack.toml"
+            }
+        }
+    }
+
+    fn body(&self) -> String {
+        match self {
+            Self::DeprecatedStacks => String::from(
+                "Stacks are deprecated since Buildpack API 0.10 and ignored by lifecycles that support it.\nDeclare the supported operating systems and architectures with [[targets]] instead and remove [[stacks]].",
+            ),
+            Self::MissingTargets => String::from(
+                "The lifecycle infers the targets of buildpacks without [[targets]] from the buildpack's files, which might not match the platforms the buildpack supports.\nDeclare the supported operating systems and architectures with [[targets]].",
+            ),
+            Self::TargetVariantWithoutArch(variant) => format!(
+                "A target declares the architecture variant '{variant}' without an architecture, so the variant can't be matched.\nAdd the 'arch' of the target."
+            ),
+            Self::TargetDistrosWithoutOs => String::from(
+                "A target declares distributions without an operating system.\nAdd the 'os' of the target, i.e. 'linux'.",
+            ),
+        }
+    }
+}
+
+/// Determines the warnings for the given buildpack descriptor.
+pub(crate) fn buildpack_descriptor_warnings<BM>(
+    descriptor: &ComponentBuildpackDescriptor<BM>,
+) -> Vec<DescriptorWarning> {
+    let mut warnings = Vec::new();
+
+    if !descriptor.stacks.is_empty() && (descriptor.api.major, descriptor.api.minor) >= (0, 10) {
+        warnings.push(DescriptorWarning::DeprecatedStacks);
+    }
+
+    if descriptor.targets.is_empty() {
+        warnings.push(DescriptorWarning::MissingTargets);
+    }
+
+    for target in &descriptor.targets {
+        if let (Some(variant), None) = (&target.variant, &target.arch) {
+            warnings.push(DescriptorWarning::TargetVariantWithoutArch(variant.clone()));
+        }
+
+        if !target.distros.is_empty() && target.os.is_none() {
+            warnings.push(DescriptorWarning::TargetDistrosWithoutOs);
+        }
+    }
+
+    warnings
+}
+
+/// Logs the warnings for the given buildpack descriptor.
+pub(crate) fn log_buildpack_descriptor_warnings<BM>(
+    log: &Logger,
+    descriptor: &ComponentBuildpackDescriptor<BM>,
+) {
+    for warning in buildpack_descriptor_warnings(descriptor) {
+        log.warning(warning.header(), warning.body());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::buildpack::ComponentBuildpackDescriptor;
+    use crate::generic::GenericMetadata;
+
+    fn descriptor(tables: &str) -> ComponentBuildpackDescriptor<GenericMetadata> {
+        toml::from_str(&format!(
+            r#"
+api = "0.10"
+
+[buildpack]
+id = "foo/bar"
+version = "0.0.1"
+
+{tables}
+"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn descriptor_without_warnings() {
+        assert_eq!(
+            buildpack_descriptor_warnings(&descriptor(
+                r#"
+[[targets]]
+os = "linux"
+arch = "arm64"
+variant = "v8"
+
+[[targets.distros]]
+name = "ubuntu"
+version = "24.04"
+"#
+            )),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn descriptor_with_warnings() {
+        assert_eq!(
+            buildpack_descriptor_warnings(&descriptor(
+                r#"
+[[stacks]]
+id = "*"
+"#
+            )),
+            vec![
+                DescriptorWarning::DeprecatedStacks,
+                DescriptorWarning::MissingTargets
+            ]
+        );
+
+        assert_eq!(
+            buildpack_descriptor_warnings(&descriptor(
+                r#"
+[[targets]]
+variant = "v8"
+
+[[targets.distros]]
+name = "ubuntu"
+version = "24.04"
+"#
+            )),
+            vec![
+                DescriptorWarning::TargetVariantWithoutArch(String::from("v8")),
+                DescriptorWarning::TargetDistrosWithoutOs
+            ]
+        );
+    }
+}
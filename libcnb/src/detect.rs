@@ -1,18 +1,117 @@
 //! Provides detect phase specific types and helpers.
 
 use crate::buildpack::Buildpack;
-use crate::Target;
-use crate::{data::build_plan::BuildPlan, data::buildpack::ComponentBuildpackDescriptor};
+use crate::data::build_plan::{BuildPlan, BuildPlanBuilder, Require};
+use crate::data::buildpack::ComponentBuildpackDescriptor;
+use crate::env::user_tools_env;
+use crate::error::Error;
+use crate::log::Logger;
+use crate::{BuildpackTracer, Env, Platform, Target, TargetSpec};
+use serde::Serialize;
 use std::fmt::Debug;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tempfile::TempDir;
 
 /// Context for the detect phase execution.
+///
+/// Helpers that are shared with the build phase can accept [`CommonContext`](crate::CommonContext)
+/// instead.
 pub struct DetectContext<B: Buildpack + ?Sized> {
     pub app_dir: PathBuf,
     pub buildpack_dir: PathBuf,
+    /// The platform directory as passed by the CNB lifecycle. Prefer [`platform`](Self::platform)
+    /// to read platform environment variables.
+    pub platform_dir: PathBuf,
+    /// The path of the build plan file (`CNB_BUILD_PLAN_PATH`). libcnb overwrites this file with
+    /// the build plan of the [`DetectResult`], if there is one.
+    pub build_plan_path: PathBuf,
     pub target: Target,
     pub platform: B::Platform,
     pub buildpack_descriptor: ComponentBuildpackDescriptor<B::Metadata>,
+    /// Logger for buildpack output. See [`Logger`] for details.
+    pub log: Logger,
+    /// State of the detection that is managed by libcnb. See [`DetectContextState`] for details.
+    pub state: DetectContextState,
+}
+
+/// State of a detection that is managed by libcnb, i.e. the scratch directory.
+///
+/// The libcnb runtime initializes the state before calling the detect function of a buildpack. Use
+/// [`DetectContextState::default`] when constructing a [`DetectContext`] outside of the runtime,
+/// for example in unit tests.
+#[derive(Debug, Default)]
+pub struct DetectContextState {
+    pub(crate) tracer: BuildpackTracer,
+    pub(crate) scratch_dir: OnceLock<TempDir>,
+}
+
+impl<B: Buildpack + ?Sized> DetectContext<B> {
+    /// Returns the path to a scratch directory for detection logic that needs to run commands or
+    /// unpack files, without writing to the app directory.
+    ///
+    /// The directory is created on first use and removed, including its contents, after the detect
+    /// phase.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the scratch directory couldn't be created.
+    pub fn scratch_dir(&self) -> crate::Result<&Path, B::Error> {
+        if let Some(scratch_dir) = self.state.scratch_dir.get() {
+            return Ok(scratch_dir.path());
+        }
+
+        let scratch_dir = tempfile::Builder::new()
+            .prefix("libcnb-detect-")
+            .tempdir()
+            .map_err(Error::CannotCreateScratchDirectory)?;
+
+        Ok(self.state.scratch_dir.get_or_init(|| scratch_dir).path())
+    }
+
+    /// Returns whether the buildpack is declared with `clear-env = true` in its `buildpack.toml`.
+    ///
+    /// The lifecycle doesn't set the platform environment variables for the processes of such
+    /// buildpacks. See [`env_for_user_tools`](Self::env_for_user_tools) for an environment that
+    /// takes this into account.
+    #[must_use]
+    pub fn clear_env(&self) -> bool {
+        self.buildpack_descriptor.buildpack.clear_env
+    }
+
+    /// Returns the environment for commands the buildpack runs on behalf of the user, such as
+    /// package managers or build tools of the app.
+    ///
+    /// The CNB spec requires buildpacks declared with `clear-env = true` to set the platform
+    /// environment variables for such commands themselves. This is the environment of the
+    /// buildpack process, with the platform environment variables added if the buildpack is
+    /// declared with `clear-env = true`. Otherwise, the lifecycle already set them for the
+    /// buildpack process.
+    #[must_use]
+    pub fn env_for_user_tools(&self) -> Env {
+        user_tools_env(
+            Env::from_current(),
+            self.platform.env(),
+            self.buildpack_descriptor.buildpack.clear_env,
+        )
+    }
+
+    /// Returns whether the target the buildpack runs on matches the given [`TargetSpec`].
+    ///
+    /// See [`Target::matches`] for details.
+    #[must_use]
+    pub fn target_matches(&self, spec: &TargetSpec) -> bool {
+        self.target.matches(spec)
+    }
+
+    /// Returns a handle for adding spans and events to the trace of the detect phase.
+    ///
+    /// Spans are only recorded when the `trace` feature is enabled. See [`BuildpackTracer`] for
+    /// details.
+    #[must_use]
+    pub fn tracer(&self) -> &BuildpackTracer {
+        &self.state.tracer
+    }
 }
 
 /// Describes the result of the detect phase.
@@ -26,7 +125,10 @@ pub struct DetectResult(pub(crate) InnerDetectResult);
 #[derive(Debug)]
 pub(crate) enum InnerDetectResult {
     Fail,
-    Pass { build_plan: Option<BuildPlan> },
+    Pass {
+        build_plan: Option<BuildPlan>,
+        build_plan_toml: Option<toml::Table>,
+    },
 }
 
 /// Constructs [`DetectResult`] values.
@@ -42,13 +144,24 @@ pub(crate) enum InnerDetectResult {
 /// let with_build_plan: Result<DetectResult, ()> = DetectResultBuilder::pass()
 ///     .build_plan(BuildPlanBuilder::new().provides("something").build())
 ///     .build();
+///
+/// let with_inline_build_plan: Result<DetectResult, ()> = DetectResultBuilder::pass()
+///     .provides("something")
+///     .requires("something")
+///     .or()
+///     .provides("something")
+///     .build();
 /// ```
 #[must_use]
 pub struct DetectResultBuilder;
 
 impl DetectResultBuilder {
     pub fn pass() -> PassDetectResultBuilder {
-        PassDetectResultBuilder { build_plan: None }
+        PassDetectResultBuilder {
+            build_plan: None,
+            build_plan_builder: None,
+            toml_build_plan: None,
+        }
     }
 
     pub fn fail() -> FailDetectResultBuilder {
@@ -61,6 +174,8 @@ impl DetectResultBuilder {
 #[must_use]
 pub struct PassDetectResultBuilder {
     build_plan: Option<BuildPlan>,
+    build_plan_builder: Option<BuildPlanBuilder>,
+    toml_build_plan: Option<toml::Table>,
 }
 
 impl PassDetectResultBuilder {
@@ -77,14 +192,110 @@ impl PassDetectResultBuilder {
 
     pub fn build_unwrapped(self) -> DetectResult {
         DetectResult(InnerDetectResult::Pass {
-            build_plan: self.build_plan,
+            build_plan: self
+                .build_plan
+                .or_else(|| self.build_plan_builder.map(BuildPlanBuilder::build)),
+            build_plan_toml: self.toml_build_plan,
         })
     }
 
+    /// Sets the build plan of the detect result.
+    ///
+    /// A build plan set with this method takes precedence over any entries added with
+    /// [`provides`](Self::provides), [`requires`](Self::requires),
+    /// [`requires_with_metadata`](Self::requires_with_metadata) and [`or`](Self::or).
     pub fn build_plan(mut self, build_plan: BuildPlan) -> Self {
         self.build_plan = Some(build_plan);
         self
     }
+
+    /// Sets the build plan of the detect result from already serialized TOML, i.e. generated by
+    /// an external tool.
+    ///
+    /// The TOML is only checked for syntax errors and written to the build plan file as is. A
+    /// build plan set with this method takes precedence over a build plan set with
+    /// [`build_plan`](Self::build_plan) and any inline entries.
+    ///
+    /// # Errors
+    /// Returns an error if the given string isn't a valid TOML document.
+    ///
+    /// # Example:
+    /// ```
+    /// use libcnb::detect::{DetectResult, DetectResultBuilder};
+    ///
+    /// let detect_result: DetectResult = DetectResultBuilder::pass()
+    ///     .build_plan_toml("[[provides]]\nname = \"jdk\"\n")
+    ///     .unwrap()
+    ///     .build_unwrapped();
+    /// ```
+    pub fn build_plan_toml(mut self, toml: &str) -> Result<Self, toml::de::Error> {
+        self.toml_build_plan = Some(toml::from_str(toml)?);
+        Ok(self)
+    }
+
+    /// Adds a provision to the current alternative of the build plan.
+    pub fn provides(self, name: impl AsRef<str>) -> Self {
+        self.map_build_plan_builder(|builder| builder.provides(name))
+    }
+
+    /// Adds a requirement to the current alternative of the build plan.
+    pub fn requires(self, require: impl Into<Require>) -> Self {
+        self.map_build_plan_builder(|builder| builder.requires(require))
+    }
+
+    /// Adds a requirement with metadata to the current alternative of the build plan.
+    ///
+    /// The metadata can be any type that serializes to a TOML table, i.e. a struct deriving
+    /// [`Serialize`]. Buildpacks can read it back during build with
+    /// [`Entry::metadata`](crate::data::buildpack_plan::Entry::metadata).
+    ///
+    /// # Errors
+    /// Returns an error if the metadata can't be serialized as a TOML table.
+    ///
+    /// # Example:
+    /// ```
+    /// use libcnb::detect::{DetectResult, DetectResultBuilder};
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct JdkMetadata {
+    ///     version: String,
+    /// }
+    ///
+    /// let detect_result: DetectResult = DetectResultBuilder::pass()
+    ///     .provides("jdk")
+    ///     .requires_with_metadata(
+    ///         "jdk",
+    ///         JdkMetadata {
+    ///             version: String::from("21"),
+    ///         },
+    ///     )
+    ///     .unwrap()
+    ///     .build_unwrapped();
+    /// ```
+    pub fn requires_with_metadata<T: Serialize>(
+        self,
+        name: impl Into<String>,
+        metadata: T,
+    ) -> Result<Self, toml::ser::Error> {
+        Require::with_metadata(name, metadata).map(|require| self.requires(require))
+    }
+
+    /// Starts a new alternative of the build plan.
+    ///
+    /// All provisions and requirements added before the first call to this method form the
+    /// primary alternative, each subsequent call starts an `[[or]]` alternative.
+    pub fn or(self) -> Self {
+        self.map_build_plan_builder(BuildPlanBuilder::or)
+    }
+
+    fn map_build_plan_builder(
+        mut self,
+        f: impl FnOnce(BuildPlanBuilder) -> BuildPlanBuilder,
+    ) -> Self {
+        self.build_plan_builder = Some(f(self.build_plan_builder.unwrap_or_default()));
+        self
+    }
 }
 
 /// Constructs [`DetectResult`] values for a failed detection. Can't be used directly, use
@@ -109,3 +320,103 @@ impl FailDetectResultBuilder {
         DetectResult(InnerDetectResult::Fail)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pass_with_inline_build_plan() {
+        #[derive(Serialize)]
+        struct Metadata {
+            version: String,
+        }
+
+        let detect_result = DetectResultBuilder::pass()
+            .provides("jdk")
+            .requires_with_metadata(
+                "jdk",
+                Metadata {
+                    version: String::from("21"),
+                },
+            )
+            .unwrap()
+            .or()
+            .provides("jre")
+            .build_unwrapped();
+
+        let DetectResult(InnerDetectResult::Pass {
+            build_plan: Some(build_plan),
+            ..
+        }) = detect_result
+        else {
+            panic!("Expected passing detect result with build plan");
+        };
+
+        assert_eq!(build_plan.provides.len(), 1);
+        assert_eq!(build_plan.requires[0].name, "jdk");
+        assert_eq!(
+            build_plan.requires[0].metadata.get("version"),
+            Some(&toml::Value::String(String::from("21")))
+        );
+        assert_eq!(build_plan.or.len(), 1);
+    }
+
+    #[test]
+    fn pass_without_build_plan() {
+        assert!(matches!(
+            DetectResultBuilder::pass().build_unwrapped(),
+            DetectResult(InnerDetectResult::Pass {
+                build_plan: None,
+                build_plan_toml: None
+            })
+        ));
+    }
+
+    #[test]
+    fn explicit_build_plan_takes_precedence() {
+        let detect_result = DetectResultBuilder::pass()
+            .provides("jdk")
+            .build_plan(BuildPlanBuilder::new().provides("jre").build())
+            .build_unwrapped();
+
+        let DetectResult(InnerDetectResult::Pass {
+            build_plan: Some(build_plan),
+            ..
+        }) = detect_result
+        else {
+            panic!("Expected passing detect result with build plan");
+        };
+
+        assert!(build_plan.requires.is_empty());
+        assert_eq!(
+            toml::to_string(&build_plan).unwrap(),
+            "[[provides]]\nname = \"jre\"\n"
+        );
+    }
+
+    #[test]
+    fn build_plan_toml() {
+        let detect_result = DetectResultBuilder::pass()
+            .build_plan_toml("[[provides]]\nname = \"jdk\"\n")
+            .unwrap()
+            .build_unwrapped();
+
+        let DetectResult(InnerDetectResult::Pass {
+            build_plan_toml: Some(build_plan_toml),
+            ..
+        }) = detect_result
+        else {
+            panic!("Expected passing detect result with build plan TOML");
+        };
+
+        assert_eq!(
+            toml::to_string(&build_plan_toml).unwrap(),
+            "[[provides]]\nname = \"jdk\"\n"
+        );
+
+        assert!(DetectResultBuilder::pass()
+            .build_plan_toml("[[provides]")
+            .is_err());
+    }
+}
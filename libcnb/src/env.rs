@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::env;
 use std::env::VarsOs;
 use std::ffi::{OsStr, OsString};
+use std::fmt::{Display, Formatter};
 
 /// Generic collection of environment variables.
 ///
@@ -51,11 +52,136 @@ impl Env {
 
     /// Inserts a key-value pair into the environment, overriding the value if `key` was already
     /// present.
+    ///
+    /// The key and value are inserted as-is, without validation. Use [`try_insert`](Self::try_insert)
+    /// to reject keys and values that can't be passed to processes.
     pub fn insert(&mut self, key: impl Into<OsString>, value: impl Into<OsString>) -> &mut Self {
         self.inner.insert(key.into(), value.into());
         self
     }
 
+    /// Inserts a key-value pair into the environment after validating it, overriding the value if
+    /// `key` was already present.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is empty or contains `=` or NUL bytes, or if the value
+    /// contains NUL bytes. See [`validate_env_var`].
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb::{Env, EnvVarError};
+    ///
+    /// let mut env = Env::new();
+    /// assert!(env.try_insert("JAVA_OPTS", "-Xmx2g").is_ok());
+    /// assert_eq!(
+    ///     env.try_insert("JAVA_OPTS=", "-Xmx2g"),
+    ///     Err(EnvVarError::NameContainsEquals("JAVA_OPTS=".into()))
+    /// );
+    /// ```
+    pub fn try_insert(
+        &mut self,
+        key: impl Into<OsString>,
+        value: impl Into<OsString>,
+    ) -> Result<&mut Self, EnvVarError> {
+        let key = key.into();
+        let value = value.into();
+        validate_env_var(&key, &value)?;
+
+        Ok(self.insert(key, value))
+    }
+
+    /// Appends the value to the value of the given key, separated by the delimiter.
+    ///
+    /// The delimiter is omitted if the key has no or an empty value. This mirrors the `append`
+    /// modification behavior of `LayerEnv`, but for a static
+    /// environment.
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb::Env;
+    ///
+    /// let mut env = Env::new();
+    /// env.append_with_delimiter("JAVA_OPTS", "-Xmx2g", " ")
+    ///     .append_with_delimiter("JAVA_OPTS", "-XX:+UseG1GC", " ");
+    ///
+    /// assert_eq!(
+    ///     env.get_string_lossy("JAVA_OPTS"),
+    ///     Some(String::from("-Xmx2g -XX:+UseG1GC"))
+    /// );
+    /// ```
+    pub fn append_with_delimiter(
+        &mut self,
+        key: impl Into<OsString>,
+        value: impl AsRef<OsStr>,
+        delimiter: impl AsRef<OsStr>,
+    ) -> &mut Self {
+        let key = key.into();
+        let mut new_value = self.inner.remove(&key).unwrap_or_default();
+
+        if !new_value.is_empty() {
+            new_value.push(delimiter);
+        }
+
+        new_value.push(value);
+        self.insert(key, new_value)
+    }
+
+    /// Prepends the value to the value of the given key, separated by the delimiter.
+    ///
+    /// The delimiter is omitted if the key has no or an empty value. This mirrors the `prepend`
+    /// modification behavior of `LayerEnv`, but for a static
+    /// environment.
+    pub fn prepend_with_delimiter(
+        &mut self,
+        key: impl Into<OsString>,
+        value: impl AsRef<OsStr>,
+        delimiter: impl AsRef<OsStr>,
+    ) -> &mut Self {
+        let key = key.into();
+        let previous_value = self.inner.remove(&key).unwrap_or_default();
+
+        let mut new_value = value.as_ref().to_owned();
+
+        if !previous_value.is_empty() {
+            new_value.push(delimiter);
+            new_value.push(previous_value);
+        }
+
+        self.insert(key, new_value)
+    }
+
+    /// Appends the path to the path list of the given key, i.e. `PATH`, using the path list
+    /// separator of the operating system.
+    ///
+    /// See [`append_with_delimiter`](Self::append_with_delimiter) for details.
+    pub fn append_path(&mut self, key: impl Into<OsString>, path: impl AsRef<OsStr>) -> &mut Self {
+        self.append_with_delimiter(key, path, PATH_LIST_SEPARATOR)
+    }
+
+    /// Prepends the path to the path list of the given key, i.e. `PATH`, using the path list
+    /// separator of the operating system.
+    ///
+    /// See [`prepend_with_delimiter`](Self::prepend_with_delimiter) for details.
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb::Env;
+    /// use std::path::Path;
+    /// use std::process::Command;
+    ///
+    /// let layer_dir = Path::new("/layers/example/runtime");
+    ///
+    /// let mut env = Env::from_current();
+    /// env.prepend_path("PATH", layer_dir.join("bin"))
+    ///     .prepend_path("LD_LIBRARY_PATH", layer_dir.join("lib"));
+    ///
+    /// let command = Command::new("runtime").envs(&env);
+    /// ```
+    pub fn prepend_path(&mut self, key: impl Into<OsString>, path: impl AsRef<OsStr>) -> &mut Self {
+        self.prepend_with_delimiter(key, path, PATH_LIST_SEPARATOR)
+    }
+
     /// Returns the value corresponding to the given key.
     #[must_use]
     pub fn get(&self, key: impl AsRef<OsStr>) -> Option<&OsString> {
@@ -82,12 +208,100 @@ impl Env {
         self.inner.contains_key(key.as_ref())
     }
 
+    /// Returns a new `Env` that only contains the variables with names matching the predicate.
+    ///
+    /// Variables with names that aren't valid Unicode are never included.
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb::Env;
+    ///
+    /// let mut env = Env::new();
+    /// env.insert("NODE_ENV", "production");
+    /// env.insert("NODE_OPTIONS", "--max-old-space-size=2048");
+    /// env.insert("PATH", "/usr/bin");
+    ///
+    /// let node_env = env.filtered(|name| name.starts_with("NODE_"));
+    /// assert_eq!(node_env.iter().count(), 2);
+    /// assert!(!node_env.contains_key("PATH"));
+    /// ```
+    #[must_use]
+    pub fn filtered(&self, predicate: impl Fn(&str) -> bool) -> Self {
+        Self {
+            inner: self
+                .inner
+                .iter()
+                .filter(|(key, _)| key.to_str().is_some_and(&predicate))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        }
+    }
+
     #[must_use]
     pub fn iter(&self) -> std::collections::hash_map::Iter<'_, OsString, OsString> {
         self.inner.iter()
     }
 }
 
+/// Validates an environment variable name and value.
+///
+/// Names must not be empty and must not contain `=` or NUL bytes. Values must not contain NUL
+/// bytes. Such variables can't be passed to processes and result in corrupt environment files
+/// when written to a layer.
+///
+/// # Errors
+///
+/// Returns an error describing the first problem with the given name or value.
+pub fn validate_env_var(name: &OsStr, value: &OsStr) -> Result<(), EnvVarError> {
+    let name_bytes = name.as_encoded_bytes();
+
+    if name_bytes.is_empty() {
+        Err(EnvVarError::EmptyName)
+    } else if name_bytes.contains(&b'=') {
+        Err(EnvVarError::NameContainsEquals(name.to_owned()))
+    } else if name_bytes.contains(&0) {
+        Err(EnvVarError::NameContainsNul(name.to_owned()))
+    } else if value.as_encoded_bytes().contains(&0) {
+        Err(EnvVarError::ValueContainsNul(name.to_owned()))
+    } else {
+        Ok(())
+    }
+}
+
+/// An invalid environment variable name or value. See [`validate_env_var`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum EnvVarError {
+    /// The name is empty.
+    EmptyName,
+    /// The name contains `=`.
+    NameContainsEquals(OsString),
+    /// The name contains a NUL byte.
+    NameContainsNul(OsString),
+    /// The value of the variable with the given name contains a NUL byte.
+    ValueContainsNul(OsString),
+}
+
+impl Display for EnvVarError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyName => write!(f, "Environment variable name is empty"),
+            Self::NameContainsEquals(name) => {
+                write!(f, "Environment variable name {name:?} must not contain '='")
+            }
+            Self::NameContainsNul(name) => write!(
+                f,
+                "Environment variable name {name:?} must not contain NUL bytes"
+            ),
+            Self::ValueContainsNul(name) => write!(
+                f,
+                "Value of environment variable {name:?} must not contain NUL bytes"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EnvVarError {}
+
 impl From<VarsOs> for Env {
     fn from(vars_os: VarsOs) -> Self {
         Self {
@@ -96,6 +310,23 @@ impl From<VarsOs> for Env {
     }
 }
 
+impl<K: Into<OsString>, V: Into<OsString>> Extend<(K, V)> for Env {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.inner.extend(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.into())),
+        );
+    }
+}
+
+impl<K: Into<OsString>, V: Into<OsString>> FromIterator<(K, V)> for Env {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut env = Self::new();
+        env.extend(iter);
+        env
+    }
+}
+
 impl<'a> IntoIterator for &'a Env {
     type Item = (&'a OsString, &'a OsString);
     type IntoIter = std::collections::hash_map::Iter<'a, OsString, OsString>;
@@ -105,8 +336,90 @@ impl<'a> IntoIterator for &'a Env {
     }
 }
 
+/// Composes the environment for tools run by the buildpack on behalf of the user from the
+/// environment of the buildpack process and the platform environment variables.
+///
+/// The lifecycle only sets the platform environment variables for the buildpack process if the
+/// buildpack isn't declared with `clear-env = true`. Buildpacks declared with `clear-env = true`
+/// are responsible for setting them for user-provided tools, so they're only added in that case.
+#[cfg(feature = "buildpack")]
+pub(crate) fn user_tools_env(mut process_env: Env, platform_env: &Env, clear_env: bool) -> Env {
+    if clear_env {
+        process_env.extend(platform_env);
+    }
+
+    process_env
+}
+
+#[cfg(target_family = "unix")]
+pub(crate) const PATH_LIST_SEPARATOR: &str = ":";
+
+#[cfg(target_family = "windows")]
+pub(crate) const PATH_LIST_SEPARATOR: &str = ";";
+
 #[cfg(test)]
 mod tests {
+    #[test]
+    fn try_insert() {
+        use crate::{Env, EnvVarError};
+        use std::ffi::OsString;
+
+        let mut env = Env::new();
+        env.try_insert("FOO", "BAR").unwrap();
+        assert_eq!(env.get("FOO"), Some(&OsString::from("BAR")));
+
+        assert_eq!(env.try_insert("", "BAR"), Err(EnvVarError::EmptyName));
+        assert_eq!(
+            env.try_insert("FOO=BAR", "BAZ"),
+            Err(EnvVarError::NameContainsEquals(OsString::from("FOO=BAR")))
+        );
+        assert_eq!(
+            env.try_insert("FOO\0", "BAR"),
+            Err(EnvVarError::NameContainsNul(OsString::from("FOO\0")))
+        );
+        assert_eq!(
+            env.try_insert("FOO", "BAR\0"),
+            Err(EnvVarError::ValueContainsNul(OsString::from("FOO")))
+        );
+        assert_eq!(env.get("FOO"), Some(&OsString::from("BAR")));
+        assert_eq!(env.iter().count(), 1);
+    }
+
+    #[test]
+    #[cfg(target_family = "unix")]
+    fn path_list_manipulation() {
+        use crate::Env;
+
+        let mut env = Env::from_iter([("PATH", "/usr/bin"), ("EMPTY", "")]);
+        env.prepend_path("PATH", "/layers/a/bin")
+            .append_path("PATH", "/layers/b/bin")
+            .prepend_path("EMPTY", "/layers/a/lib")
+            .append_path("LD_LIBRARY_PATH", "/layers/b/lib")
+            .prepend_with_delimiter("JAVA_OPTS", "-Xmx2g", " ")
+            .prepend_with_delimiter("JAVA_OPTS", "-Xss512k", " ");
+
+        assert_eq!(
+            env.get_string_lossy("PATH"),
+            Some(String::from("/layers/a/bin:/usr/bin:/layers/b/bin"))
+        );
+        assert_eq!(
+            env.get_string_lossy("EMPTY"),
+            Some(String::from("/layers/a/lib"))
+        );
+        assert_eq!(
+            env.get_string_lossy("LD_LIBRARY_PATH"),
+            Some(String::from("/layers/b/lib"))
+        );
+        assert_eq!(
+            env.get_string_lossy("JAVA_OPTS"),
+            Some(String::from("-Xss512k -Xmx2g"))
+        );
+
+        env.extend([("FOO", "BAR"), ("PATH", "/bin")]);
+        assert_eq!(env.get_string_lossy("FOO"), Some(String::from("BAR")));
+        assert_eq!(env.get_string_lossy("PATH"), Some(String::from("/bin")));
+    }
+
     #[test]
     #[cfg(target_family = "unix")]
     fn into_iterator() {
@@ -129,4 +442,47 @@ mod tests {
             String::from_utf8_lossy(&output.stdout)
         );
     }
+
+    #[test]
+    #[cfg(target_family = "unix")]
+    fn filtered_skips_non_unicode_names() {
+        use crate::Env;
+        use std::ffi::OsString;
+        use std::os::unix::ffi::OsStringExt;
+
+        let mut env = Env::new();
+        env.insert("NODE_ENV", "production");
+        env.insert(OsString::from_vec(b"NODE_\xFF".to_vec()), "invalid");
+
+        assert_eq!(
+            env.filtered(|_| true).iter().collect::<Vec<_>>(),
+            [(&OsString::from("NODE_ENV"), &OsString::from("production"))]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "buildpack")]
+    fn user_tools_env_respects_clear_env() {
+        use super::user_tools_env;
+        use crate::Env;
+
+        let process_env = Env::from_iter([("PATH", "/usr/bin"), ("NODE_ENV", "development")]);
+        let platform_env = Env::from_iter([("NODE_ENV", "production")]);
+
+        // Without `clear-env`, the lifecycle already set the platform environment variables for
+        // the buildpack process, so the process environment is used as-is.
+        let env = user_tools_env(process_env.clone(), &platform_env, false);
+        assert_eq!(env.get_string_lossy("PATH"), Some(String::from("/usr/bin")));
+        assert_eq!(
+            env.get_string_lossy("NODE_ENV"),
+            Some(String::from("development"))
+        );
+
+        let env = user_tools_env(process_env, &platform_env, true);
+        assert_eq!(env.get_string_lossy("PATH"), Some(String::from("/usr/bin")));
+        assert_eq!(
+            env.get_string_lossy("NODE_ENV"),
+            Some(String::from("production"))
+        );
+    }
 }
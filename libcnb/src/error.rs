@@ -1,7 +1,10 @@
-use crate::data::launch::ProcessTypeError;
-use crate::layer::LayerError;
+use crate::data::launch::{LaunchValidationError, ProcessTypeError};
+use crate::layer::{LayerError, WriteLayerError};
+use crate::sbom::SbomMergeError;
+use crate::PlatformEnvVarError;
 use libcnb_common::toml_file::TomlFileError;
 use std::fmt::Debug;
+use std::path::PathBuf;
 
 /// A specialized Result type for libcnb.
 ///
@@ -23,6 +26,9 @@ pub enum Error<E> {
     #[error("Couldn't determine buildpack directory: {0}")]
     CannotDetermineBuildpackDirectory(std::env::VarError),
 
+    #[error("Couldn't determine extension directory: {0}")]
+    CannotDetermineExtensionDirectory(std::env::VarError),
+
     #[error("Couldn't determine target os: {0}")]
     CannotDetermineTargetOs(std::env::VarError),
 
@@ -38,30 +44,69 @@ pub enum Error<E> {
     #[error("Couldn't create platform from platform path: {0}")]
     CannotCreatePlatformFromPath(std::io::Error),
 
+    #[error("Invalid platform environment: {0}")]
+    InvalidPlatformEnv(PlatformEnvVarError),
+
     #[error("Couldn't read buildpack plan: {0}")]
     CannotReadBuildpackPlan(TomlFileError),
 
     #[error("Couldn't read buildpack.toml: {0}")]
     CannotReadBuildpackDescriptor(TomlFileError),
 
+    #[error("Couldn't read extension.toml: {0}")]
+    CannotReadExtensionDescriptor(TomlFileError),
+
     #[error("Couldn't read store.toml: {0}")]
     CannotReadStore(TomlFileError),
 
     #[error("Couldn't write build plan: {0}")]
     CannotWriteBuildPlan(TomlFileError),
 
+    #[error("Invalid launch configuration: {0}")]
+    InvalidLaunch(LaunchValidationError),
+
     #[error("Couldn't write launch.toml: {0}")]
     CannotWriteLaunch(TomlFileError),
 
+    #[error("Couldn't write process environment layer: {0}")]
+    CannotWriteProcessEnvLayer(WriteLayerError),
+
     #[error("Couldn't write store.toml: {0}")]
     CannotWriteStore(TomlFileError),
 
+    #[error("Couldn't write build.toml: {0}")]
+    CannotWriteBuild(TomlFileError),
+
+    #[error("Legacy BOM entries ([[bom]]) in {0} are not supported by the buildpack's Buildpack API, use SBOMs instead (see `LayerRef::write_sboms` and `BuildResultBuilder::launch_sbom`)")]
+    LegacyBom(PathBuf),
+
+    #[error("Couldn't merge SBOMs: {0}")]
+    CannotMergeSboms(SbomMergeError),
+
+    #[error("Invalid SBOM: {0}")]
+    InvalidSbom(crate::data::sbom::SbomValidationError),
+
     #[error("Couldn't write build SBOM files: {0}")]
     CannotWriteBuildSbom(std::io::Error),
 
     #[error("Couldn't write launch SBOM files: {0}")]
     CannotWriteLaunchSbom(std::io::Error),
 
+    #[error("Couldn't write build.Dockerfile: {0}")]
+    CannotWriteBuildDockerfile(std::io::Error),
+
+    #[error("Couldn't write run.Dockerfile: {0}")]
+    CannotWriteRunDockerfile(std::io::Error),
+
+    #[error("Couldn't write extend-config.toml: {0}")]
+    CannotWriteExtendConfig(TomlFileError),
+
+    #[error("Couldn't create detect scratch directory: {0}")]
+    CannotCreateScratchDirectory(std::io::Error),
+
+    #[error("Couldn't create async runtime: {0}")]
+    CannotCreateAsyncRuntime(std::io::Error),
+
     #[error("Buildpack error: {0:?}")]
     BuildpackError(E),
 }
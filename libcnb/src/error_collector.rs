@@ -0,0 +1,274 @@
+use crate::log::Logger;
+use crate::{ErrorMessageStyle, ErrorReport};
+use std::fmt::{Debug, Display, Formatter};
+
+/// Collects non-fatal errors and warnings, to report all of them at once instead of failing on
+/// the first error.
+///
+/// Useful for buildpacks that validate many user inputs, such as configuration files. Errors are
+/// recorded with [`push`](Self::push) or [`collect`](Self::collect) and the collector is turned
+/// into a result with [`finish`](Self::finish) once all inputs have been validated.
+///
+/// # Example
+/// ```
+/// use libcnb::log::{LogLevel, Logger};
+/// use libcnb::{CollectedErrors, ErrorCollector};
+///
+/// #[derive(Debug)]
+/// enum ConfigError {
+///     InvalidPort(String),
+///     InvalidTimeout(String),
+/// }
+///
+/// impl std::fmt::Display for ConfigError {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         match self {
+///             Self::InvalidPort(value) => write!(f, "Invalid port: {value}"),
+///             Self::InvalidTimeout(value) => write!(f, "Invalid timeout: {value}"),
+///         }
+///     }
+/// }
+///
+/// let logger = Logger::new(LogLevel::Info);
+/// let mut errors = ErrorCollector::new();
+///
+/// let port = errors.collect(
+///     "80a"
+///         .parse::<u16>()
+///         .map_err(|_| ConfigError::InvalidPort(String::from("80a"))),
+/// );
+/// let timeout = errors.collect(
+///     "ten"
+///         .parse::<u32>()
+///         .map_err(|_| ConfigError::InvalidTimeout(String::from("ten"))),
+/// );
+/// errors.warning("Deprecated setting", "The `workers` setting is ignored.");
+///
+/// let collected_errors: CollectedErrors<ConfigError> = errors.finish(&logger).unwrap_err();
+/// assert_eq!(collected_errors.len(), 2);
+/// assert_eq!(port, None);
+/// assert_eq!(timeout, None);
+///
+/// // I.e. in `Buildpack::on_error`:
+/// let error_report = collected_errors.error_report("Invalid configuration");
+/// ```
+#[derive(Debug)]
+#[must_use]
+pub struct ErrorCollector<E> {
+    errors: Vec<E>,
+    warnings: Vec<(String, String)>,
+}
+
+impl<E> ErrorCollector<E> {
+    /// Creates an empty collector.
+    pub fn new() -> Self {
+        Self {
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Records an error.
+    pub fn push(&mut self, error: E) {
+        self.errors.push(error);
+    }
+
+    /// Records the error of the given result, if any, and returns its value otherwise.
+    pub fn collect<T>(&mut self, result: Result<T, E>) -> Option<T> {
+        result.map_err(|error| self.push(error)).ok()
+    }
+
+    /// Records a warning with a header and a body, to be logged by [`finish`](Self::finish).
+    pub fn warning(&mut self, header: impl Into<String>, body: impl Into<String>) {
+        self.warnings.push((header.into(), body.into()));
+    }
+
+    /// Returns the errors recorded so far.
+    #[must_use]
+    pub fn errors(&self) -> &[E] {
+        &self.errors
+    }
+
+    /// Returns whether any errors have been recorded.
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// Logs all recorded warnings and returns the recorded errors, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns all recorded errors if at least one error was recorded.
+    pub fn finish(self, logger: &Logger) -> Result<(), CollectedErrors<E>> {
+        for (header, body) in &self.warnings {
+            logger.warning(header, body);
+        }
+
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(CollectedErrors(self.errors))
+        }
+    }
+}
+
+impl<E> Default for ErrorCollector<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The errors recorded by an [`ErrorCollector`]. Contains at least one error.
+///
+/// Wrap it in the buildpack's error type and use [`error_report`](Self::error_report) to render
+/// all errors in [`Buildpack::on_error`](crate::Buildpack::on_error).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CollectedErrors<E>(Vec<E>);
+
+impl<E> CollectedErrors<E> {
+    /// Returns the collected errors.
+    #[must_use]
+    pub fn errors(&self) -> &[E] {
+        &self.0
+    }
+
+    /// Returns the collected errors.
+    #[must_use]
+    pub fn into_errors(self) -> Vec<E> {
+        self.0
+    }
+
+    /// Returns the number of collected errors.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns whether there are no collected errors, which is never the case when returned by
+    /// [`ErrorCollector::finish`].
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Creates an [`ErrorReport`] with the given header that lists all collected errors.
+    pub fn error_report(&self, header: impl Into<String>) -> ErrorReport
+    where
+        E: Display,
+    {
+        ErrorReport::new()
+            .message(self.to_string())
+            .style(ErrorMessageStyle::Header(header.into()))
+    }
+}
+
+impl<E: Display> Display for CollectedErrors<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.0.as_slice() {
+            [error] => write!(f, "{error}"),
+            errors => {
+                write!(f, "Found {} problems:", errors.len())?;
+                for error in errors {
+                    write!(f, "\n- {error}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<E: Display + Debug> std::error::Error for CollectedErrors<E> {}
+
+impl<E> IntoIterator for CollectedErrors<E> {
+    type Item = E;
+    type IntoIter = std::vec::IntoIter<E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::LogLevel;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn finish_without_errors() {
+        let stderr = SharedBuffer::default();
+        let logger = Logger::with_writers(LogLevel::Info, std::io::sink(), stderr.clone());
+
+        let mut collector = ErrorCollector::<String>::new();
+        assert_eq!(collector.collect(Ok::<_, String>(42)), Some(42));
+        collector.warning("Deprecated", "Use something else.");
+
+        assert!(!collector.has_errors());
+        assert_eq!(collector.finish(&logger), Ok(()));
+        assert_eq!(
+            String::from_utf8_lossy(&stderr.0.lock().unwrap()),
+            "\n[Warning: Deprecated]\nUse something else.\n"
+        );
+    }
+
+    #[test]
+    fn finish_with_errors() {
+        let mut collector = ErrorCollector::new();
+        assert_eq!(
+            collector.collect(Err::<(), _>(String::from("Invalid port"))),
+            None
+        );
+        collector.push(String::from("Invalid timeout"));
+
+        let collected_errors = collector
+            .finish(&Logger::with_writers(
+                LogLevel::Info,
+                std::io::sink(),
+                std::io::sink(),
+            ))
+            .unwrap_err();
+
+        assert_eq!(
+            collected_errors.to_string(),
+            "Found 2 problems:\n- Invalid port\n- Invalid timeout"
+        );
+        assert_eq!(
+            collected_errors.error_report("Invalid configuration"),
+            ErrorReport::new()
+                .message("Found 2 problems:\n- Invalid port\n- Invalid timeout")
+                .style(ErrorMessageStyle::Header(String::from(
+                    "Invalid configuration"
+                )))
+        );
+        assert_eq!(
+            collected_errors.into_errors(),
+            [
+                String::from("Invalid port"),
+                String::from("Invalid timeout")
+            ]
+        );
+    }
+
+    #[test]
+    fn display_single_error() {
+        assert_eq!(
+            CollectedErrors(vec![String::from("Invalid port")]).to_string(),
+            "Invalid port"
+        );
+    }
+}
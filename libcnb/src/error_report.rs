@@ -0,0 +1,184 @@
+use crate::exit_code;
+use std::fmt::Debug;
+use std::io::Write;
+
+/// Describes how an unhandled error is reported to the user before the buildpack exits.
+///
+/// Returned by [`Buildpack::on_error`](crate::Buildpack::on_error) and
+/// [`Extension::on_error`](crate::Extension::on_error). The framework writes the report to stderr
+/// and exits with the report's exit code.
+///
+/// # Example
+/// ```
+/// use libcnb::{ErrorMessageStyle, ErrorReport};
+///
+/// # let error = "";
+/// let report = ErrorReport::new()
+///     .message("The `engines.node` version in package.json is invalid.")
+///     .style(ErrorMessageStyle::Header(String::from("Invalid Node.js version")))
+///     .details(format!("{error:?}"))
+///     .print_details(false)
+///     .exit_code(2);
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[must_use]
+pub struct ErrorReport {
+    exit_code: i32,
+    message: Option<String>,
+    style: ErrorMessageStyle,
+    details: Option<String>,
+    print_details: bool,
+}
+
+/// The style of the user-facing message of an [`ErrorReport`].
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub enum ErrorMessageStyle {
+    /// Writes the message as-is.
+    #[default]
+    Plain,
+    /// Writes the message below a `[Error: <header>]` line, consistent with
+    /// [`Logger::error`](crate::log::Logger::error).
+    Header(String),
+}
+
+impl ErrorReport {
+    /// Creates a report that exits with a generic error exit code and writes no output.
+    ///
+    /// Use this if the error was already logged, i.e. with a custom logger.
+    pub fn new() -> Self {
+        Self {
+            exit_code: exit_code::GENERIC_UNSPECIFIED_ERROR,
+            message: None,
+            style: ErrorMessageStyle::Plain,
+            details: None,
+            print_details: true,
+        }
+    }
+
+    /// Creates the report used by the default `on_error` implementations, which writes the
+    /// [`Debug`] representation of the error.
+    pub(crate) fn unhandled_error(error: &impl Debug, component_kind: &str) -> Self {
+        Self::new().message(format!(
+            "Unhandled error:\n> {error:?}\n{component_kind} will exit!"
+        ))
+    }
+
+    /// Sets the exit code. Defaults to `1`.
+    ///
+    /// An exit code of `0` would signal success to the CNB lifecycle and is replaced with `1`.
+    pub fn exit_code(mut self, exit_code: i32) -> Self {
+        self.exit_code = if exit_code == exit_code::GENERIC_SUCCESS {
+            exit_code::GENERIC_UNSPECIFIED_ERROR
+        } else {
+            exit_code
+        };
+        self
+    }
+
+    /// Sets the user-facing message.
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Sets the style of the user-facing message. Defaults to [`ErrorMessageStyle::Plain`].
+    pub fn style(mut self, style: ErrorMessageStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets internal details, such as the debug representation of the error, that are written
+    /// after the user-facing message.
+    pub fn details(mut self, details: impl Into<String>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+
+    /// Controls whether internal details are written. Defaults to `true`.
+    ///
+    /// Buildpacks can use this to only show details when debugging, i.e. depending on the log level.
+    pub fn print_details(mut self, print_details: bool) -> Self {
+        self.print_details = print_details;
+        self
+    }
+
+    /// Returns the exit code the buildpack exits with.
+    #[must_use]
+    pub fn get_exit_code(&self) -> i32 {
+        self.exit_code
+    }
+
+    pub(crate) fn write_to(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        if let Some(message) = &self.message {
+            match &self.style {
+                ErrorMessageStyle::Plain => writeln!(writer, "{message}")?,
+                ErrorMessageStyle::Header(header) => {
+                    writeln!(writer, "\n[Error: {header}]\n{message}")?;
+                }
+            }
+        }
+
+        if let Some(details) = self.details.as_ref().filter(|_| self.print_details) {
+            writeln!(writer, "{details}")?;
+        }
+
+        writer.flush()
+    }
+}
+
+impl Default for ErrorReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(report: &ErrorReport) -> String {
+        let mut buffer = Vec::new();
+        report.write_to(&mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn write_plain_report() {
+        let report = ErrorReport::new()
+            .message("Something failed")
+            .details("Debug info");
+
+        assert_eq!(output(&report), "Something failed\nDebug info\n");
+        assert_eq!(output(&report.print_details(false)), "Something failed\n");
+        assert_eq!(output(&ErrorReport::new()), "");
+    }
+
+    #[test]
+    fn write_header_report() {
+        let report = ErrorReport::new()
+            .message("Your package.json is invalid.")
+            .style(ErrorMessageStyle::Header(String::from(
+                "Invalid package.json",
+            )));
+
+        assert_eq!(
+            output(&report),
+            "\n[Error: Invalid package.json]\nYour package.json is invalid.\n"
+        );
+    }
+
+    #[test]
+    fn unhandled_error_report() {
+        assert_eq!(
+            output(&ErrorReport::unhandled_error(&"boom", "Buildpack")),
+            "Unhandled error:\n> \"boom\"\nBuildpack will exit!\n"
+        );
+    }
+
+    #[test]
+    fn exit_code() {
+        assert_eq!(ErrorReport::new().get_exit_code(), 1);
+        assert_eq!(ErrorReport::new().exit_code(42).get_exit_code(), 42);
+        assert_eq!(ErrorReport::new().exit_code(0).get_exit_code(), 1);
+    }
+}
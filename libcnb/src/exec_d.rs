@@ -1,22 +1,50 @@
-use libcnb_data::exec_d::ExecDProgramOutput;
+//! Support for writing [exec.d](https://github.com/buildpacks/spec/blob/main/buildpack.md#execd) programs.
+//!
+//! This module is also available when libcnb is compiled with `default-features = false`, which
+//! excludes the buildpack framework for small and fast compiling exec.d binaries.
+//!
+//! Most exec.d programs only need [`write_exec_d_program_output`]. Programs that need more control,
+//! i.e. to handle errors themselves or to be runnable outside the CNB launcher, can use the
+//! lower-level [`exec_d_output_file`], [`serialize_exec_d_program_output`] and
+//! [`write_exec_d_program_output_to`] functions instead.
+//!
+//! To append or prepend to existing environment variables, i.e. `PATH`, build the output with
+//! [`ExecDProgramOutputBuilder`].
+
+use crate::{phase, Env, Phase};
+use libcnb_data::exec_d::{ExecDProgramOutput, ExecDProgramOutputKey, ExecDProgramOutputKeyError};
+use libcnb_data::launch::ProcessType;
+use std::collections::BTreeMap;
+use std::env;
 use std::fs::File;
 use std::io::BufWriter;
 use std::io::Write;
+#[cfg(target_family = "unix")]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The file descriptor exec.d programs write their output to, as required by the CNB spec.
+pub const EXEC_D_OUTPUT_FD: i32 = 3;
+
+/// The environment variable the CNB launcher uses to expose the type of the launched process.
+pub const PROCESS_TYPE_ENV_VAR: &str = "CNB_PROCESS_TYPE";
 
 /// Writes the output of a CNB exec.d program in a spec compliant way.
 ///
 /// # Panics
 ///
-/// Panics if there was an error serializing the TOML output or writing to FD 3.
+/// Panics if there was an error serializing the TOML output or writing to FD 3, or if called from
+/// the detect, build or generate phase of a buildpack or extension.
 pub fn write_exec_d_program_output<O: Into<ExecDProgramOutput>>(o: O) {
+    if let Some(phase @ (Phase::Detect | Phase::Build | Phase::Generate)) = phase() {
+        panic!("exec.d program output can't be written during the {phase} phase");
+    }
+
     // Allow compilation of exec.d programs under windows, but fail at runtime:
     #[cfg(target_family = "windows")]
     unimplemented!("libcnb.rs does not support running in Windows containers yet!");
 
     #[cfg(target_family = "unix")]
     {
-        use std::os::unix::io::FromRawFd;
-
         // The spec requires writing the TOML to fd3:
         // https://github.com/buildpacks/spec/blob/main/buildpack.md#execd
         //
@@ -24,10 +52,11 @@ pub fn write_exec_d_program_output<O: Into<ExecDProgramOutput>>(o: O) {
         // actually mapped to something. Since we're implementing the CNB spec and it explicitly
         // tells us to write to that file descriptor, this is safe to do without additional
         // validation in this context.
+        EXEC_D_OUTPUT_FILE_TAKEN.store(true, Ordering::SeqCst);
         #[allow(unsafe_code)]
-        let output_file = unsafe { File::from_raw_fd(3) };
+        let output_file = unsafe { raw_exec_d_output_file() };
 
-        let serialized_output = toml::to_string(&o.into())
+        let serialized_output = serialize_exec_d_program_output(&o.into())
             .expect("Couldn't TOML serialize the exec.d program output: ");
 
         BufWriter::new(output_file)
@@ -35,3 +64,281 @@ pub fn write_exec_d_program_output<O: Into<ExecDProgramOutput>>(o: O) {
             .expect("Couldn't write exec.d program output: ");
     }
 }
+
+/// Returns the file exec.d programs write their output to, if it is open.
+///
+/// Returns `None` if file descriptor 3 isn't open, which is the case when the program wasn't
+/// started by the CNB launcher, i.e. when running it manually for debugging.
+///
+/// The returned [`File`] owns the file descriptor and closes it when dropped. To ensure the file
+/// descriptor is only closed once, only the first call returns the file, all subsequent calls
+/// (and calls after [`write_exec_d_program_output`]) return `None`.
+#[cfg(target_family = "unix")]
+#[must_use]
+pub fn exec_d_output_file() -> Option<File> {
+    if exec_d_output_fd_open() && !EXEC_D_OUTPUT_FILE_TAKEN.swap(true, Ordering::SeqCst) {
+        // The file descriptor is open and no other `File` owns it, so taking ownership is safe.
+        #[allow(unsafe_code)]
+        Some(unsafe { raw_exec_d_output_file() })
+    } else {
+        None
+    }
+}
+
+/// Whether ownership of the exec.d output file descriptor has been taken by a [`File`].
+#[cfg(target_family = "unix")]
+static EXEC_D_OUTPUT_FILE_TAKEN: AtomicBool = AtomicBool::new(false);
+
+#[cfg(target_family = "unix")]
+pub(crate) fn exec_d_output_fd_open() -> bool {
+    // `/dev/fd` lists the open file descriptors of the current process on both Linux and macOS.
+    std::path::Path::new(&format!("/dev/fd/{EXEC_D_OUTPUT_FD}")).exists()
+}
+
+#[cfg(not(target_family = "unix"))]
+pub(crate) fn exec_d_output_fd_open() -> bool {
+    false
+}
+
+#[cfg(target_family = "unix")]
+#[allow(unsafe_code)]
+unsafe fn raw_exec_d_output_file() -> File {
+    use std::os::unix::io::FromRawFd;
+    File::from_raw_fd(EXEC_D_OUTPUT_FD)
+}
+
+/// Serializes the output of a CNB exec.d program to TOML.
+///
+/// # Errors
+///
+/// Returns an error if the output couldn't be serialized.
+pub fn serialize_exec_d_program_output(
+    output: &ExecDProgramOutput,
+) -> Result<String, toml::ser::Error> {
+    toml::to_string(output)
+}
+
+/// Writes the output of a CNB exec.d program to the given writer.
+///
+/// Use together with [`exec_d_output_file`] to handle errors instead of panicking like
+/// [`write_exec_d_program_output`] does.
+///
+/// # Errors
+///
+/// Returns an error if the output couldn't be serialized or written. Serialization errors are
+/// reported with [`std::io::ErrorKind::InvalidData`].
+pub fn write_exec_d_program_output_to<O: Into<ExecDProgramOutput>, W: Write>(
+    output: O,
+    mut writer: W,
+) -> std::io::Result<()> {
+    let serialized_output = serialize_exec_d_program_output(&output.into())
+        .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+
+    writer.write_all(serialized_output.as_bytes())?;
+    writer.flush()
+}
+
+/// Returns the type of the process the exec.d program is run for.
+///
+/// Reads the [`PROCESS_TYPE_ENV_VAR`] environment variable. Returns `None` if it isn't set or
+/// isn't a valid process type, i.e. when the launcher runs a custom command instead of a process
+/// type.
+#[must_use]
+pub fn exec_d_process_type() -> Option<ProcessType> {
+    env::var(PROCESS_TYPE_ENV_VAR)
+        .ok()
+        .and_then(|process_type| process_type.parse().ok())
+}
+
+/// Builds [`ExecDProgramOutput`] from a series of operations on environment variables.
+///
+/// The CNB launcher overrides environment variables with the values an exec.d program outputs.
+/// To append or prepend to a variable, the builder combines the given value with the variable's
+/// current value: the value of a previous operation on the same variable, or otherwise its value
+/// in the environment the builder was created with.
+///
+/// Keys are validated when building. The output is serialized in sorted key order, which makes it
+/// stable across runs.
+///
+/// # Example
+/// ```no_run
+/// use libcnb::exec_d::{write_exec_d_program_output, ExecDProgramOutputBuilder};
+/// use libcnb::Env;
+///
+/// let mut env = Env::new();
+/// env.insert("PATH", "/usr/bin");
+///
+/// let output = ExecDProgramOutputBuilder::with_env(env)
+///     .prepend("PATH", "/layers/example/tools/bin", ":")
+///     .set("TOOLS_HOME", "/layers/example/tools")
+///     .build()
+///     .unwrap();
+///
+/// write_exec_d_program_output(output);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExecDProgramOutputBuilder {
+    env: Env,
+    output: BTreeMap<String, String>,
+}
+
+impl ExecDProgramOutputBuilder {
+    /// Constructs a new builder that resolves current values from the environment of the current
+    /// process.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_env(Env::from_current())
+    }
+
+    /// Constructs a new builder that resolves current values from the given environment.
+    #[must_use]
+    pub fn with_env(env: Env) -> Self {
+        Self {
+            env,
+            output: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the variable to the given value.
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.output.insert(key.into(), value.into());
+        self
+    }
+
+    /// Appends the given value to the current value of the variable, separated by the delimiter.
+    ///
+    /// The delimiter is omitted if the variable isn't set or is empty.
+    pub fn append(
+        &mut self,
+        key: impl Into<String>,
+        value: impl AsRef<str>,
+        delimiter: impl AsRef<str>,
+    ) -> &mut Self {
+        let key = key.into();
+        let value = match self.current_value(&key) {
+            Some(current_value) => {
+                format!("{current_value}{}{}", delimiter.as_ref(), value.as_ref())
+            }
+            None => String::from(value.as_ref()),
+        };
+
+        self.output.insert(key, value);
+        self
+    }
+
+    /// Prepends the given value to the current value of the variable, separated by the delimiter.
+    ///
+    /// The delimiter is omitted if the variable isn't set or is empty.
+    pub fn prepend(
+        &mut self,
+        key: impl Into<String>,
+        value: impl AsRef<str>,
+        delimiter: impl AsRef<str>,
+    ) -> &mut Self {
+        let key = key.into();
+        let value = match self.current_value(&key) {
+            Some(current_value) => {
+                format!("{}{}{current_value}", value.as_ref(), delimiter.as_ref())
+            }
+            None => String::from(value.as_ref()),
+        };
+
+        self.output.insert(key, value);
+        self
+    }
+
+    /// Builds the output.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for the first key that isn't a valid [`ExecDProgramOutputKey`].
+    pub fn build(&self) -> Result<ExecDProgramOutput, ExecDProgramOutputKeyError> {
+        self.output
+            .iter()
+            .map(|(key, value)| Ok((key.parse::<ExecDProgramOutputKey>()?, value.clone())))
+            .collect::<Result<Vec<_>, _>>()
+            .map(ExecDProgramOutput::from)
+    }
+
+    fn current_value(&self, key: &str) -> Option<String> {
+        self.output
+            .get(key)
+            .cloned()
+            .or_else(|| self.env.get_string_lossy(key))
+            .filter(|value| !value.is_empty())
+    }
+}
+
+impl Default for ExecDProgramOutputBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libcnb_data::exec_d_program_output_key;
+
+    #[test]
+    fn write_to_writer() {
+        let mut buffer = Vec::new();
+
+        write_exec_d_program_output_to(
+            [(exec_d_program_output_key!("PATH"), "/layers/bin:/usr/bin")],
+            &mut buffer,
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "PATH = \"/layers/bin:/usr/bin\"\n"
+        );
+    }
+
+    #[test]
+    fn serialize() {
+        assert_eq!(
+            serialize_exec_d_program_output(&ExecDProgramOutput::from([(
+                exec_d_program_output_key!("FOO"),
+                "bar"
+            )]))
+            .unwrap(),
+            "FOO = \"bar\"\n"
+        );
+    }
+
+    #[test]
+    fn builder() {
+        let mut env = Env::new();
+        env.insert("PATH", "/usr/bin");
+        env.insert("EMPTY", "");
+
+        let output = ExecDProgramOutputBuilder::with_env(env)
+            .set("ZED", "last")
+            .prepend("PATH", "/layers/tools/bin", ":")
+            .append("PATH", "/layers/fallback/bin", ":")
+            .append("EMPTY", "value", ":")
+            .prepend("UNSET", "value", ":")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            serialize_exec_d_program_output(&output).unwrap(),
+            "EMPTY = \"value\"\nPATH = \"/layers/tools/bin:/usr/bin:/layers/fallback/bin\"\nUNSET = \"value\"\nZED = \"last\"\n"
+        );
+    }
+
+    #[test]
+    fn builder_invalid_key() {
+        assert_eq!(
+            ExecDProgramOutputBuilder::with_env(Env::new())
+                .set("VALID", "value")
+                .set("IN VALID", "value")
+                .build(),
+            Err(ExecDProgramOutputKeyError::InvalidValue(String::from(
+                "IN VALID"
+            )))
+        );
+    }
+}
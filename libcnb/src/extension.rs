@@ -0,0 +1,208 @@
+//! Provides types and helpers for writing CNB image extensions.
+//!
+//! Image extensions are similar to buildpacks, but instead of contributing layers they generate
+//! Dockerfiles that are used to extend the build and run images. See the
+//! [CNB image extension specification](https://github.com/buildpacks/spec/blob/main/image_extension.md)
+//! for details.
+
+use crate::detect::{DetectResult, DetectResultBuilder};
+use crate::log::Logger;
+use crate::{ErrorReport, Platform, Target};
+use libcnb_data::buildpack_plan::BuildpackPlan;
+use libcnb_data::extension::{DockerfileArg, ExtendConfig, ExtensionDescriptor};
+use serde::de::DeserializeOwned;
+use std::fmt::Debug;
+use std::path::PathBuf;
+
+/// Represents an image extension written with the libcnb framework.
+///
+/// To implement an image extension with this framework, start by implementing this trait. Then,
+/// use the [`extension_main`](crate::extension_main) macro to wire it up to the framework.
+///
+/// # Example:
+/// ```
+/// use libcnb::extension::{Extension, GenerateContext, GenerateResult, GenerateResultBuilder};
+/// use libcnb::extension_main;
+/// use libcnb::generic::{GenericError, GenericMetadata, GenericPlatform};
+///
+/// pub(crate) struct MyExtension;
+///
+/// impl Extension for MyExtension {
+///     type Platform = GenericPlatform;
+///     type Metadata = GenericMetadata;
+///     type Error = GenericError;
+///
+///     fn generate(
+///         &self,
+///         context: GenerateContext<Self>,
+///     ) -> libcnb::Result<GenerateResult, Self::Error> {
+///         GenerateResultBuilder::new()
+///             .run_dockerfile("ARG base_image\nFROM ${base_image}\nRUN apt-get update\n")
+///             .build()
+///     }
+/// }
+///
+/// extension_main!(MyExtension);
+/// ```
+pub trait Extension {
+    /// The platform targeted by this extension. If no specific platform is targeted, consider
+    /// using [`GenericPlatform`](crate::generic::GenericPlatform) as the type.
+    type Platform: Platform;
+
+    /// The metadata type for this extension. This is the data within `[metadata]` of the extension's
+    /// `extension.toml`. The framework will attempt to parse the data and will only continue if
+    /// parsing succeeded. If you wish to use raw, untyped, TOML data instead, use
+    /// [`GenericMetadata`](crate::generic::GenericMetadata).
+    type Metadata: DeserializeOwned;
+
+    /// The error type for extension specific errors, usually an enum. Examples of values inside the
+    /// enum are: `MavenExecutionFailed`, `InvalidGemfileLock`, `IncompatiblePythonVersion`. The
+    /// framework itself has its [own error type](crate::Error) that contains more low-level errors
+    /// that can occur during extension execution.
+    type Error: Debug;
+
+    /// Extension detect logic.
+    ///
+    /// Like with buildpacks, this method determines if this extension is able to operate on the
+    /// given application. Extensions pass detection by default.
+    fn detect(&self, context: DetectContext<Self>) -> crate::Result<DetectResult, Self::Error> {
+        let _ = context;
+        DetectResultBuilder::pass().build()
+    }
+
+    /// Extension generate logic.
+    ///
+    /// This method generates the Dockerfiles (and their build arguments) that are used to extend
+    /// the build and run images.
+    fn generate(
+        &self,
+        context: GenerateContext<Self>,
+    ) -> crate::Result<GenerateResult, Self::Error>;
+
+    /// If an unhandled error occurred within the framework or the extension, this method will be
+    /// called by the framework to allow custom, extension specific, code to run before exiting.
+    /// Usually, this method is implemented by describing the error in a user friendly manner.
+    ///
+    /// The returned [`ErrorReport`] controls the message written to stderr, whether internal
+    /// details are included and the exit code of the extension. Implementations are not limited to
+    /// reporting, for example, extensions might want to collect and send metrics about occurring
+    /// errors to a central system.
+    ///
+    /// The default implementation will simply report the error
+    /// (using its [`Debug`] implementation).
+    fn on_error(&self, error: crate::Error<Self::Error>) -> ErrorReport {
+        ErrorReport::unhandled_error(&error, "Extension")
+    }
+}
+
+/// Context for the detect phase execution of an extension.
+pub struct DetectContext<E: Extension + ?Sized> {
+    pub app_dir: PathBuf,
+    pub extension_dir: PathBuf,
+    pub target: Target,
+    pub platform: E::Platform,
+    pub extension_descriptor: ExtensionDescriptor<E::Metadata>,
+    /// Logger for extension output. See [`Logger`] for details.
+    pub log: Logger,
+}
+
+/// Context for the generate phase execution of an extension.
+pub struct GenerateContext<E: Extension + ?Sized> {
+    pub app_dir: PathBuf,
+    pub extension_dir: PathBuf,
+    pub target: Target,
+    pub platform: E::Platform,
+    pub buildpack_plan: BuildpackPlan,
+    pub extension_descriptor: ExtensionDescriptor<E::Metadata>,
+    /// Logger for extension output. See [`Logger`] for details.
+    pub log: Logger,
+}
+
+/// Describes the result of the generate phase.
+///
+/// Contains the generated Dockerfiles and their build arguments. To construct values of this type,
+/// use a [`GenerateResultBuilder`].
+#[derive(Debug)]
+#[must_use]
+pub struct GenerateResult {
+    pub(crate) build_dockerfile: Option<String>,
+    pub(crate) run_dockerfile: Option<String>,
+    pub(crate) extend_config: ExtendConfig,
+}
+
+/// Constructs [`GenerateResult`] values.
+///
+/// # Examples:
+/// ```
+/// use libcnb::extension::{GenerateResult, GenerateResultBuilder};
+///
+/// let empty: Result<GenerateResult, ()> = GenerateResultBuilder::new().build();
+///
+/// let with_dockerfiles: Result<GenerateResult, ()> = GenerateResultBuilder::new()
+///     .build_dockerfile("ARG base_image\nFROM ${base_image}\n")
+///     .run_dockerfile("ARG base_image\nFROM ${base_image}\nARG version\n")
+///     .run_arg("version", "1.2.3")
+///     .build();
+/// ```
+#[derive(Default)]
+#[must_use]
+pub struct GenerateResultBuilder {
+    build_dockerfile: Option<String>,
+    run_dockerfile: Option<String>,
+    extend_config: ExtendConfig,
+}
+
+impl GenerateResultBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds the final [`GenerateResult`].
+    ///
+    /// This method returns the [`GenerateResult`] wrapped in a [`Result`] even though its
+    /// technically not fallible. This is done to simplify using this method in the context it's
+    /// most often used in: an extension's [generate method](Extension::generate).
+    ///
+    /// See [`build_unwrapped`](Self::build_unwrapped) for an unwrapped version of this method.
+    pub fn build<E>(self) -> Result<GenerateResult, E> {
+        Ok(self.build_unwrapped())
+    }
+
+    pub fn build_unwrapped(self) -> GenerateResult {
+        GenerateResult {
+            build_dockerfile: self.build_dockerfile,
+            run_dockerfile: self.run_dockerfile,
+            extend_config: self.extend_config,
+        }
+    }
+
+    /// Sets the contents of the Dockerfile used to extend the build image.
+    pub fn build_dockerfile(mut self, dockerfile: impl Into<String>) -> Self {
+        self.build_dockerfile = Some(dockerfile.into());
+        self
+    }
+
+    /// Sets the contents of the Dockerfile used to extend the run image.
+    pub fn run_dockerfile(mut self, dockerfile: impl Into<String>) -> Self {
+        self.run_dockerfile = Some(dockerfile.into());
+        self
+    }
+
+    /// Adds a build argument for the build image Dockerfile.
+    pub fn build_arg(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extend_config.build.args.push(DockerfileArg {
+            name: name.into(),
+            value: value.into(),
+        });
+        self
+    }
+
+    /// Adds a build argument for the run image Dockerfile.
+    pub fn run_arg(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extend_config.run.args.push(DockerfileArg {
+            name: name.into(),
+            value: value.into(),
+        });
+        self
+    }
+}
@@ -1,7 +1,7 @@
 //! Generic implementations for some libcnb types.
 
-use crate::platform::Platform;
-use crate::{read_platform_env, Env};
+use crate::platform::{Platform, PlatformDir};
+use crate::Env;
 use std::fmt::{Debug, Display, Formatter};
 use std::path::Path;
 
@@ -16,15 +16,19 @@ impl Display for GenericError {
     }
 }
 
-/// A generic platform that only provides access to environment variables.
+/// A generic platform that only provides access to environment variables and the platform directory.
 pub struct GenericPlatform {
     env: Env,
+    platform_dir: Option<PlatformDir>,
 }
 
 impl GenericPlatform {
     #[must_use]
     pub fn new(env: Env) -> Self {
-        Self { env }
+        Self {
+            env,
+            platform_dir: None,
+        }
     }
 }
 
@@ -34,6 +38,15 @@ impl Platform for GenericPlatform {
     }
 
     fn from_path(platform_dir: impl AsRef<Path>) -> std::io::Result<Self> {
-        read_platform_env(platform_dir.as_ref()).map(|env| Self { env })
+        let platform_dir = PlatformDir::new(platform_dir.as_ref());
+
+        platform_dir.read_env().map(|env| Self {
+            env,
+            platform_dir: Some(platform_dir),
+        })
+    }
+
+    fn platform_dir(&self) -> Option<&PlatformDir> {
+        self.platform_dir.as_ref()
     }
 }
@@ -3,4 +3,4 @@
 // We cannot use `::libcnb_proc_macros::verify_bin_target_exists` in our macros directly as this
 // would require every crate to explicitly import the `libcnb_proc_macros` crate as crates can't
 // use code from transitive dependencies.
-pub use libcnb_proc_macros::verify_bin_target_exists;
+pub use libcnb_proc_macros::{additional_binaries_dir, verify_bin_target_exists};
@@ -0,0 +1,409 @@
+use crate::util::lock;
+use libcnb_data::layer::LayerName;
+use libcnb_data::layer_name;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+/// Name of the cache-only layer that backs the [`ArtifactStore`].
+pub(crate) fn artifact_store_layer_name() -> LayerName {
+    layer_name!("libcnb-artifact-store")
+}
+
+/// Digests of the artifacts used during the current build, `None` if the store wasn't accessed.
+pub(crate) type UsedArtifacts = Arc<Mutex<Option<HashSet<ArtifactDigest>>>>;
+
+/// A content-addressed store for artifacts, such as downloaded archives, that is shared across
+/// the layers of a build.
+///
+/// Artifacts are stored by their SHA-256 digest in a cache-only layer managed by libcnb. Layers
+/// materialize artifacts as copies, so an artifact used by multiple layers is only downloaded and
+/// cached once.
+///
+/// After a successful build, artifacts that weren't looked up, inserted or materialized during the
+/// build are removed from the store, so that outdated artifacts don't accumulate in the cache.
+///
+/// Obtain the store with [`BuildContext::artifact_store`](crate::build::BuildContext::artifact_store).
+///
+/// Materialized files share their contents with the store. Don't modify them in place, replace
+/// them instead.
+#[derive(Debug, Clone)]
+pub struct ArtifactStore {
+    dir: PathBuf,
+    used_artifacts: UsedArtifacts,
+}
+
+impl ArtifactStore {
+    pub(crate) fn new(dir: PathBuf, used_artifacts: UsedArtifacts) -> Self {
+        lock(&used_artifacts).get_or_insert_with(HashSet::new);
+        Self {
+            dir,
+            used_artifacts,
+        }
+    }
+
+    /// Returns whether the store contains the artifact with the given digest.
+    ///
+    /// Like all other lookups, this keeps the artifact in the store for subsequent builds.
+    #[must_use]
+    pub fn contains(&self, digest: &ArtifactDigest) -> bool {
+        self.artifact_path(digest).is_some()
+    }
+
+    /// Returns the path of the artifact with the given digest, if the store contains it.
+    ///
+    /// The file at the returned path must not be modified.
+    #[must_use]
+    pub fn artifact_path(&self, digest: &ArtifactDigest) -> Option<PathBuf> {
+        let path = self.stored_path(digest);
+        path.is_file().then(|| {
+            self.mark_used(digest);
+            path
+        })
+    }
+
+    /// Moves the file at the given path into the store and returns its digest.
+    ///
+    /// If the store already contains an artifact with the same contents, the file is removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file couldn't be read or moved.
+    pub fn insert_file(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<ArtifactDigest, ArtifactStoreError> {
+        let path = path.as_ref();
+        let digest = sha256_file_digest(path)?;
+        self.store_file(path, &digest)?;
+        Ok(digest)
+    }
+
+    /// Moves the file at the given path into the store after verifying its digest.
+    ///
+    /// Use this for downloads with a known checksum. If the digest doesn't match, the file is left
+    /// in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the digest doesn't match or the file couldn't be read or moved.
+    pub fn insert_file_with_digest(
+        &self,
+        path: impl AsRef<Path>,
+        expected_digest: &ArtifactDigest,
+    ) -> Result<(), ArtifactStoreError> {
+        let path = path.as_ref();
+        let digest = sha256_file_digest(path)?;
+
+        if &digest != expected_digest {
+            return Err(ArtifactStoreError::DigestMismatch {
+                expected: expected_digest.clone(),
+                actual: digest,
+            });
+        }
+
+        self.store_file(path, &digest)
+    }
+
+    /// Makes the artifact with the given digest available at the given destination path.
+    ///
+    /// The stored artifact is copied to the destination. Unlike a hard link, the copy can be
+    /// modified or removed without affecting the stored artifact. Missing parent directories are
+    /// created and an existing file at the destination is replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store doesn't contain the artifact or it couldn't be materialized.
+    pub fn materialize(
+        &self,
+        digest: &ArtifactDigest,
+        destination: impl AsRef<Path>,
+    ) -> Result<(), ArtifactStoreError> {
+        let destination = destination.as_ref();
+        let stored_path = self
+            .artifact_path(digest)
+            .ok_or_else(|| ArtifactStoreError::MissingArtifact(digest.clone()))?;
+
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        match fs::remove_file(destination) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error.into()),
+            _ => {}
+        }
+
+        fs::copy(&stored_path, destination)?;
+
+        Ok(())
+    }
+
+    fn store_file(&self, path: &Path, digest: &ArtifactDigest) -> Result<(), ArtifactStoreError> {
+        let stored_path = self.stored_path(digest);
+
+        if stored_path.is_file() {
+            fs::remove_file(path)?;
+        } else {
+            fs::create_dir_all(self.dir.join(ARTIFACTS_DIR_NAME))?;
+
+            if fs::rename(path, &stored_path).is_err() {
+                // Renaming fails across file systems. Copy to a temporary file first, so that an
+                // interrupted copy never leaves a partial artifact in the store.
+                let temp_path = self.dir.join(format!(".{}.tmp", digest.as_str()));
+                fs::copy(path, &temp_path)?;
+                fs::rename(&temp_path, &stored_path)?;
+                fs::remove_file(path)?;
+            }
+        }
+
+        self.mark_used(digest);
+        Ok(())
+    }
+
+    fn stored_path(&self, digest: &ArtifactDigest) -> PathBuf {
+        self.dir.join(ARTIFACTS_DIR_NAME).join(digest.as_str())
+    }
+
+    fn mark_used(&self, digest: &ArtifactDigest) {
+        lock(&self.used_artifacts)
+            .get_or_insert_with(HashSet::new)
+            .insert(digest.clone());
+    }
+}
+
+const ARTIFACTS_DIR_NAME: &str = "sha256";
+
+/// Removes all artifacts that weren't used during the build, as well as leftover temporary files.
+pub(crate) fn prune_artifact_store(
+    store_dir: &Path,
+    used_artifacts: &HashSet<ArtifactDigest>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(store_dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+
+        if file_name == ARTIFACTS_DIR_NAME {
+            for artifact_entry in fs::read_dir(entry.path())? {
+                let artifact_entry = artifact_entry?;
+                let is_used = artifact_entry
+                    .file_name()
+                    .to_str()
+                    .and_then(|file_name| file_name.parse::<ArtifactDigest>().ok())
+                    .is_some_and(|digest| used_artifacts.contains(&digest));
+
+                if !is_used {
+                    fs::remove_file(artifact_entry.path())?;
+                }
+            }
+        } else if file_name.to_string_lossy().ends_with(".tmp") {
+            fs::remove_file(entry.path())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn sha256_file_digest(path: &Path) -> io::Result<ArtifactDigest> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(ArtifactDigest(format!("{:x}", hasher.finalize())))
+}
+
+/// The SHA-256 digest of an artifact in an [`ArtifactStore`].
+///
+/// Parsed from 64 hexadecimal characters, optionally prefixed with `sha256:`.
+///
+/// # Examples
+/// ```
+/// use libcnb::layer::ArtifactDigest;
+///
+/// let digest = "sha256:2CF24DBA5FB0A30E26E83B2AC5B9E29E1B161E5C1FA7425E73043362938B9824"
+///     .parse::<ArtifactDigest>()
+///     .unwrap();
+///
+/// assert_eq!(
+///     digest.as_str(),
+///     "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+/// );
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ArtifactDigest(String);
+
+impl ArtifactDigest {
+    /// Returns the digest as lowercase hexadecimal characters.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for ArtifactDigest {
+    type Err = ArtifactDigestError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let hex = value.strip_prefix("sha256:").unwrap_or(value);
+
+        if hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            Ok(Self(hex.to_ascii_lowercase()))
+        } else {
+            Err(ArtifactDigestError::InvalidValue(String::from(value)))
+        }
+    }
+}
+
+impl Display for ArtifactDigest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "sha256:{}", self.0)
+    }
+}
+
+#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+pub enum ArtifactDigestError {
+    #[error("Invalid SHA-256 artifact digest: {0}")]
+    InvalidValue(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ArtifactStoreError {
+    #[error("Artifact store doesn't contain artifact {0}")]
+    MissingArtifact(ArtifactDigest),
+
+    #[error("Artifact digest mismatch, expected {expected} but got {actual}")]
+    DigestMismatch {
+        expected: ArtifactDigest,
+        actual: ArtifactDigest,
+    },
+
+    #[error("I/O error in artifact store: {0}")]
+    IoError(#[from] io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const HELLO_DIGEST: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    #[test]
+    fn insert_and_materialize() {
+        let temp_dir = tempdir().unwrap();
+        let used_artifacts = UsedArtifacts::default();
+        let store = ArtifactStore::new(temp_dir.path().join("store"), Arc::clone(&used_artifacts));
+
+        let download_path = temp_dir.path().join("download");
+        fs::write(&download_path, "hello").unwrap();
+
+        let digest = store.insert_file(&download_path).unwrap();
+        assert_eq!(digest.as_str(), HELLO_DIGEST);
+        assert!(!download_path.exists());
+        assert!(store.contains(&digest));
+
+        // Inserting identical contents again is deduplicated.
+        fs::write(&download_path, "hello").unwrap();
+        store
+            .insert_file_with_digest(&download_path, &digest)
+            .unwrap();
+        assert!(!download_path.exists());
+
+        for layer in ["jdk-tools", "maven"] {
+            let destination = temp_dir.path().join(layer).join("jdk.tar.gz");
+            store.materialize(&digest, &destination).unwrap();
+            assert_eq!(fs::read_to_string(destination).unwrap(), "hello");
+        }
+
+        assert_eq!(
+            used_artifacts.lock().unwrap().clone(),
+            Some(HashSet::from([digest]))
+        );
+    }
+
+    #[test]
+    fn insert_with_mismatching_digest() {
+        let temp_dir = tempdir().unwrap();
+        let store = ArtifactStore::new(temp_dir.path().join("store"), UsedArtifacts::default());
+
+        let download_path = temp_dir.path().join("download");
+        fs::write(&download_path, "tampered").unwrap();
+
+        let result = store.insert_file_with_digest(&download_path, &HELLO_DIGEST.parse().unwrap());
+
+        assert!(matches!(
+            result,
+            Err(ArtifactStoreError::DigestMismatch { .. })
+        ));
+        assert!(download_path.exists());
+    }
+
+    #[test]
+    fn materialize_copies_artifact() {
+        let temp_dir = tempdir().unwrap();
+        let store = ArtifactStore::new(temp_dir.path().join("store"), UsedArtifacts::default());
+
+        let download_path = temp_dir.path().join("download");
+        fs::write(&download_path, "hello").unwrap();
+        let digest = store.insert_file(&download_path).unwrap();
+
+        let destination = temp_dir.path().join("layer").join("file");
+        store.materialize(&digest, &destination).unwrap();
+        fs::write(&destination, "modified").unwrap();
+
+        let other_destination = temp_dir.path().join("other-layer").join("file");
+        store.materialize(&digest, &other_destination).unwrap();
+        assert_eq!(fs::read_to_string(other_destination).unwrap(), "hello");
+    }
+
+    #[test]
+    fn materialize_missing_artifact() {
+        let temp_dir = tempdir().unwrap();
+        let store = ArtifactStore::new(temp_dir.path().join("store"), UsedArtifacts::default());
+
+        assert!(matches!(
+            store.materialize(
+                &HELLO_DIGEST.parse().unwrap(),
+                temp_dir.path().join("destination")
+            ),
+            Err(ArtifactStoreError::MissingArtifact(_))
+        ));
+    }
+
+    #[test]
+    fn prune_unused_artifacts() {
+        let temp_dir = tempdir().unwrap();
+        let store_dir = temp_dir.path();
+        let artifacts_dir = store_dir.join(ARTIFACTS_DIR_NAME);
+        fs::create_dir_all(&artifacts_dir).unwrap();
+
+        let unused_digest = "a".repeat(64);
+        fs::write(artifacts_dir.join(HELLO_DIGEST), "hello").unwrap();
+        fs::write(artifacts_dir.join(&unused_digest), "unused").unwrap();
+        fs::write(store_dir.join(format!(".{unused_digest}.tmp")), "partial").unwrap();
+
+        prune_artifact_store(store_dir, &HashSet::from([HELLO_DIGEST.parse().unwrap()])).unwrap();
+
+        assert!(artifacts_dir.join(HELLO_DIGEST).exists());
+        assert!(!artifacts_dir.join(&unused_digest).exists());
+        assert!(!store_dir.join(format!(".{unused_digest}.tmp")).exists());
+    }
+
+    #[test]
+    fn parse_digest() {
+        assert_eq!(
+            format!("sha256:{HELLO_DIGEST}")
+                .parse::<ArtifactDigest>()
+                .unwrap()
+                .as_str(),
+            HELLO_DIGEST
+        );
+        assert_eq!(
+            "abc".parse::<ArtifactDigest>(),
+            Err(ArtifactDigestError::InvalidValue(String::from("abc")))
+        );
+        assert!("g".repeat(64).parse::<ArtifactDigest>().is_err());
+    }
+}
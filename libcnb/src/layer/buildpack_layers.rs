@@ -0,0 +1,295 @@
+use crate::layer::ReadLayerError;
+use crate::layer_env::LayerEnv;
+use libcnb_data::buildpack::BuildpackId;
+use libcnb_data::generic::GenericMetadata;
+use libcnb_data::layer::LayerName;
+use libcnb_data::layer_content_metadata::{LayerContentMetadata, LayerTypes};
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A read-only view of a layer contributed by another buildpack of the build.
+///
+/// Allows buildpacks to hand off layers to later buildpacks in the group, i.e. a buildpack that
+/// installs Node.js exposing its layer to a buildpack that installs `node_modules`, without
+/// passing paths via environment variables. Returned by
+/// [`BuildContext::buildpack_layers`](crate::build::BuildContext::buildpack_layers) and
+/// [`BuildContext::buildpack_layer`](crate::build::BuildContext::buildpack_layer).
+///
+/// The CNB lifecycle only makes layers of previous buildpacks available if they are `build`
+/// layers. Buildpacks must not modify the contents of layers of other buildpacks.
+#[derive(Debug, Clone)]
+pub struct BuildpackLayer {
+    buildpack_id: BuildpackId,
+    name: LayerName,
+    path: PathBuf,
+    types: LayerTypes,
+    metadata: GenericMetadata,
+}
+
+impl BuildpackLayer {
+    /// The ID of the buildpack that contributed this layer.
+    #[must_use]
+    pub fn buildpack_id(&self) -> &BuildpackId {
+        &self.buildpack_id
+    }
+
+    /// The name of the layer.
+    #[must_use]
+    pub fn name(&self) -> &LayerName {
+        &self.name
+    }
+
+    /// The path to the layer directory.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The types of the layer, as written by the contributing buildpack.
+    #[must_use]
+    pub fn types(&self) -> &LayerTypes {
+        &self.types
+    }
+
+    /// The untyped metadata of the layer.
+    #[must_use]
+    pub fn generic_metadata(&self) -> &GenericMetadata {
+        &self.metadata
+    }
+
+    /// Parses the metadata of the layer into the given type.
+    ///
+    /// A layer without metadata is parsed as an empty table.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the metadata can't be deserialized into the given type.
+    pub fn metadata<M: DeserializeOwned>(&self) -> Result<M, toml::de::Error> {
+        toml::Value::Table(self.metadata.clone().unwrap_or_default()).try_into()
+    }
+
+    /// Reads the environment variables of the layer, including the implicit ones such as `PATH`
+    /// for the `bin` directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the environment files of the layer couldn't be read.
+    pub fn env(&self) -> Result<LayerEnv, std::io::Error> {
+        LayerEnv::read_from_layer_dir(&self.path)
+    }
+}
+
+/// Returns the directory that contains the layers of the given buildpack, as a sibling of the
+/// layers directory of the current buildpack.
+pub(crate) fn buildpack_layers_dir(layers_dir: &Path, buildpack_id: &BuildpackId) -> PathBuf {
+    let escaped_buildpack_id = buildpack_id.replace('/', "_");
+
+    layers_dir.parent().map_or_else(
+        || PathBuf::from(&escaped_buildpack_id),
+        |parent| parent.join(&escaped_buildpack_id),
+    )
+}
+
+/// Reads all layers of the given buildpack, sorted by name.
+///
+/// Only `build` layers are included. Layers without a directory (i.e. launch-only layers of which
+/// the lifecycle only restored the metadata) are skipped as well. Returns an empty list if the layers of the buildpack aren't available.
+/// In contrast to the layer handling of the current buildpack, this never modifies the layers.
+pub(crate) fn read_buildpack_layers(
+    layers_dir: &Path,
+    buildpack_id: &BuildpackId,
+) -> Result<Vec<BuildpackLayer>, ReadLayerError> {
+    let buildpack_layers_dir = buildpack_layers_dir(layers_dir, buildpack_id);
+
+    if !buildpack_layers_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut layers = Vec::new();
+    for entry in fs::read_dir(&buildpack_layers_dir)? {
+        let path = entry?.path();
+
+        let Some(layer_name) = path
+            .file_name()
+            .filter(|_| path.is_dir())
+            .and_then(|file_name| file_name.to_str())
+            .and_then(|file_name| file_name.parse::<LayerName>().ok())
+        else {
+            continue;
+        };
+
+        if let Some(layer) =
+            read_buildpack_layer_from_dir(&buildpack_layers_dir, buildpack_id, &layer_name)?
+        {
+            layers.push(layer);
+        }
+    }
+
+    layers.sort_by(|a, b| a.name.as_str().cmp(b.name.as_str()));
+    Ok(layers)
+}
+
+/// Reads a single layer of the given buildpack.
+///
+/// Returns `None` if the layer doesn't exist or isn't a `build` layer.
+pub(crate) fn read_buildpack_layer(
+    layers_dir: &Path,
+    buildpack_id: &BuildpackId,
+    layer_name: &LayerName,
+) -> Result<Option<BuildpackLayer>, ReadLayerError> {
+    read_buildpack_layer_from_dir(
+        &buildpack_layers_dir(layers_dir, buildpack_id),
+        buildpack_id,
+        layer_name,
+    )
+}
+
+fn read_buildpack_layer_from_dir(
+    buildpack_layers_dir: &Path,
+    buildpack_id: &BuildpackId,
+    layer_name: &LayerName,
+) -> Result<Option<BuildpackLayer>, ReadLayerError> {
+    let layer_dir_path = buildpack_layers_dir.join(layer_name.as_str());
+    let layer_toml_path = buildpack_layers_dir.join(format!("{layer_name}.toml"));
+
+    if !layer_dir_path.is_dir() {
+        return Ok(None);
+    }
+
+    let content_metadata: LayerContentMetadata<GenericMetadata> =
+        match fs::read_to_string(&layer_toml_path) {
+            Ok(layer_toml_contents) => toml::from_str(&layer_toml_contents)
+                .map_err(ReadLayerError::LayerContentMetadataParseError)?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(ReadLayerError::IoError(error)),
+        };
+
+    // The lifecycle only guarantees access to `build` layers of other buildpacks. Other layers
+    // might be left over on disk, but their contents aren't meant to be used by later buildpacks.
+    let Some(types) = content_metadata.types.filter(|types| types.build) else {
+        return Ok(None);
+    };
+
+    Ok(Some(BuildpackLayer {
+        buildpack_id: buildpack_id.clone(),
+        name: layer_name.clone(),
+        path: layer_dir_path,
+        types,
+        metadata: content_metadata.metadata,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libcnb_data::{buildpack_id, layer_name};
+    use serde::Deserialize;
+    use tempfile::tempdir;
+
+    #[test]
+    fn read_layers_of_other_buildpack() {
+        #[derive(Deserialize, Debug, Eq, PartialEq)]
+        struct NodeMetadata {
+            version: String,
+        }
+
+        let temp_dir = tempdir().unwrap();
+        let layers_dir = temp_dir.path().join("heroku_npm");
+        let nodejs_layers_dir = temp_dir.path().join("heroku_nodejs");
+        fs::create_dir_all(&layers_dir).unwrap();
+        fs::create_dir_all(nodejs_layers_dir.join("dist/bin")).unwrap();
+        fs::create_dir_all(nodejs_layers_dir.join("corepack")).unwrap();
+        fs::create_dir_all(nodejs_layers_dir.join("cache")).unwrap();
+        fs::create_dir_all(nodejs_layers_dir.join("untyped")).unwrap();
+        fs::write(
+            nodejs_layers_dir.join("dist.toml"),
+            "[types]\nbuild = true\nlaunch = true\n\n[metadata]\nversion = \"22.12.0\"\n",
+        )
+        .unwrap();
+        fs::write(
+            nodejs_layers_dir.join("corepack.toml"),
+            "[types]\nbuild = true\n",
+        )
+        .unwrap();
+        // Layers that aren't build layers are ignored.
+        fs::write(
+            nodejs_layers_dir.join("cache.toml"),
+            "[types]\ncache = true\n",
+        )
+        .unwrap();
+        // Launch-only layers without a directory and other files are ignored.
+        fs::write(nodejs_layers_dir.join("launch-only.toml"), "").unwrap();
+        fs::write(nodejs_layers_dir.join("store.toml"), "").unwrap();
+
+        let layers = read_buildpack_layers(&layers_dir, &buildpack_id!("heroku/nodejs")).unwrap();
+        assert_eq!(
+            layers
+                .iter()
+                .map(|layer| layer.name().as_str())
+                .collect::<Vec<_>>(),
+            ["corepack", "dist"]
+        );
+
+        let dist_layer = &layers[1];
+        assert_eq!(dist_layer.buildpack_id(), &buildpack_id!("heroku/nodejs"));
+        assert_eq!(dist_layer.path(), nodejs_layers_dir.join("dist"));
+        assert_eq!(
+            dist_layer.types(),
+            &LayerTypes {
+                build: true,
+                launch: true,
+                cache: false
+            }
+        );
+        assert_eq!(
+            dist_layer.metadata::<NodeMetadata>().unwrap(),
+            NodeMetadata {
+                version: String::from("22.12.0")
+            }
+        );
+        assert_eq!(
+            dist_layer
+                .env()
+                .unwrap()
+                .apply_to_empty(crate::layer_env::Scope::Build)
+                .get("PATH"),
+            Some(&std::ffi::OsString::from(
+                nodejs_layers_dir.join("dist/bin")
+            ))
+        );
+
+        let corepack_layer = &layers[0];
+        assert!(corepack_layer.metadata::<NodeMetadata>().is_err());
+
+        for layer_name in [layer_name!("cache"), layer_name!("untyped")] {
+            assert!(read_buildpack_layer(
+                &layers_dir,
+                &buildpack_id!("heroku/nodejs"),
+                &layer_name
+            )
+            .unwrap()
+            .is_none());
+        }
+
+        assert!(read_buildpack_layer(
+            &layers_dir,
+            &buildpack_id!("heroku/nodejs"),
+            &layer_name!("launch-only")
+        )
+        .unwrap()
+        .is_none());
+    }
+
+    #[test]
+    fn read_layers_of_missing_buildpack() {
+        let temp_dir = tempdir().unwrap();
+
+        assert!(read_buildpack_layers(
+            &temp_dir.path().join("heroku_npm"),
+            &buildpack_id!("heroku/nodejs")
+        )
+        .unwrap()
+        .is_empty());
+    }
+}
@@ -1,13 +1,23 @@
 //! Provides types and helpers to work with layers.
 
+pub(crate) mod artifact_store;
+pub(crate) mod buildpack_layers;
+pub(crate) mod retry;
 pub(crate) mod shared;
 pub(crate) mod struct_api;
+pub(crate) mod timestamp_skew;
 pub(crate) mod trait_api;
 
+pub use artifact_store::{ArtifactDigest, ArtifactDigestError, ArtifactStore, ArtifactStoreError};
+pub use buildpack_layers::BuildpackLayer;
+pub use retry::LayerIoRetryPolicy;
+pub use shared::CreateLayerSymlinkError;
 pub use shared::DeleteLayerError;
 pub use shared::LayerError;
 pub use shared::ReadLayerError;
 pub use shared::WriteLayerError;
+pub use timestamp_skew::TimestampSkew;
 
+pub use libcnb_proc_macros::LayerMetadata;
 pub use struct_api::*;
 pub use trait_api::*;
@@ -0,0 +1,258 @@
+use crate::layer::shared::{
+    CreateLayerSymlinkError, DeleteLayerError, ReadLayerError, ReplaceLayerExecdProgramsError,
+    ReplaceLayerSbomsError, WriteLayerError, WriteLayerMetadataError,
+};
+use libcnb_common::toml_file::TomlFileError;
+use std::io;
+use std::time::Duration;
+
+/// Controls automatic retries of layer I/O operations that failed with a transient error.
+///
+/// Some platforms back the layers directory with network or overlay filesystems that
+/// occasionally fail operations with errors such as `EBUSY` or `ETIMEDOUT`. With retries enabled,
+/// libcnb retries layer operations (i.e. creating, restoring, deleting and modifying layers via
+/// [`BuildContext::cached_layer`](crate::build::BuildContext::cached_layer),
+/// [`BuildContext::uncached_layer`](crate::build::BuildContext::uncached_layer) and
+/// [`LayerRef`](crate::layer::LayerRef)) that failed with such an error. All other errors are
+/// reported immediately.
+///
+/// Retries are disabled by default. Set [`Buildpack::LAYER_IO_RETRY_POLICY`](crate::Buildpack::LAYER_IO_RETRY_POLICY)
+/// to enable them for a buildpack.
+///
+/// # Example
+/// ```
+/// use libcnb::layer::LayerIoRetryPolicy;
+/// use std::time::Duration;
+///
+/// const LAYER_IO_RETRY_POLICY: LayerIoRetryPolicy = LayerIoRetryPolicy {
+///     max_retries: 3,
+///     delay: Duration::from_millis(250),
+/// };
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct LayerIoRetryPolicy {
+    /// The maximum number of retries after the initial attempt.
+    pub max_retries: u32,
+    /// The delay between two attempts.
+    pub delay: Duration,
+}
+
+impl LayerIoRetryPolicy {
+    /// A policy that never retries.
+    pub const DISABLED: Self = Self {
+        max_retries: 0,
+        delay: Duration::ZERO,
+    };
+}
+
+impl Default for LayerIoRetryPolicy {
+    fn default() -> Self {
+        Self::DISABLED
+    }
+}
+
+/// Runs the given layer operation, retrying it according to the given policy if it failed with
+/// a transient I/O error.
+///
+/// The operation must be idempotent, which is the case for all layer operations in libcnb.
+pub(crate) fn with_retries<T, E: LayerIoError>(
+    policy: LayerIoRetryPolicy,
+    mut operation: impl FnMut() -> Result<T, E>,
+) -> Result<T, E> {
+    let mut retries = 0;
+
+    loop {
+        match operation() {
+            Err(error)
+                if retries < policy.max_retries
+                    && error.io_error().is_some_and(is_transient_io_error) =>
+            {
+                retries += 1;
+                std::thread::sleep(policy.delay);
+            }
+            result => return result,
+        }
+    }
+}
+
+fn is_transient_io_error(error: &io::Error) -> bool {
+    // EBUSY has the same value on Linux and macOS. `io::ErrorKind::ResourceBusy` cannot be used
+    // since it requires a newer Rust version than our MSRV.
+    const EBUSY: i32 = 16;
+
+    error.kind() == io::ErrorKind::TimedOut || error.raw_os_error() == Some(EBUSY)
+}
+
+/// Errors of layer operations that might have been caused by an I/O error.
+pub(crate) trait LayerIoError {
+    fn io_error(&self) -> Option<&io::Error>;
+}
+
+impl LayerIoError for io::Error {
+    fn io_error(&self) -> Option<&io::Error> {
+        Some(self)
+    }
+}
+
+impl LayerIoError for TomlFileError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            TomlFileError::IoError(error) => Some(error),
+            TomlFileError::TomlDeserializationError(_)
+            | TomlFileError::TomlSerializationError(_) => None,
+        }
+    }
+}
+
+impl LayerIoError for ReadLayerError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            ReadLayerError::IoError(error) => Some(error),
+            ReadLayerError::LayerContentMetadataParseError(_) => None,
+        }
+    }
+}
+
+impl LayerIoError for WriteLayerError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            WriteLayerError::WriteLayerMetadataError(error) => error.io_error(),
+            WriteLayerError::ReplaceLayerSbomsError(error) => error.io_error(),
+            WriteLayerError::ReplaceLayerExecdProgramsError(error) => error.io_error(),
+            WriteLayerError::CreateLayerSymlinkError(error) => error.io_error(),
+            WriteLayerError::IoError(error) => Some(error),
+        }
+    }
+}
+
+impl LayerIoError for WriteLayerMetadataError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            WriteLayerMetadataError::IoError(error) => Some(error),
+            WriteLayerMetadataError::TomlFileError(error) => error.io_error(),
+        }
+    }
+}
+
+impl LayerIoError for DeleteLayerError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            DeleteLayerError::IoError(error) => Some(error),
+        }
+    }
+}
+
+impl LayerIoError for ReplaceLayerSbomsError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            ReplaceLayerSbomsError::IoError(error) => Some(error),
+            ReplaceLayerSbomsError::MissingLayer(_) => None,
+        }
+    }
+}
+
+impl LayerIoError for ReplaceLayerExecdProgramsError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            ReplaceLayerExecdProgramsError::IoError(error) => Some(error),
+            ReplaceLayerExecdProgramsError::MissingExecDFile(_)
+            | ReplaceLayerExecdProgramsError::MissingLayer(_) => None,
+        }
+    }
+}
+
+impl LayerIoError for CreateLayerSymlinkError {
+    fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            CreateLayerSymlinkError::IoError(error) => Some(error),
+            CreateLayerSymlinkError::InvalidLinkPath(_)
+            | CreateLayerSymlinkError::AbsoluteTarget(_)
+            | CreateLayerSymlinkError::TargetEscapesLayer { .. }
+            | CreateLayerSymlinkError::MissingTarget { .. }
+            | CreateLayerSymlinkError::LinkPathOccupied(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: LayerIoRetryPolicy = LayerIoRetryPolicy {
+        max_retries: 2,
+        delay: Duration::ZERO,
+    };
+
+    #[test]
+    fn with_retries_transient_error() {
+        let mut attempts = 0;
+
+        let result = with_retries(POLICY, || {
+            attempts += 1;
+
+            if attempts < 3 {
+                Err(io::Error::from(io::ErrorKind::TimedOut))
+            } else {
+                Ok(attempts)
+            }
+        });
+
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[test]
+    fn with_retries_exhausted() {
+        let mut attempts = 0;
+
+        let result: Result<(), io::Error> = with_retries(POLICY, || {
+            attempts += 1;
+            Err(io::Error::from_raw_os_error(16))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn with_retries_non_transient_error() {
+        let mut attempts = 0;
+
+        let result: Result<(), io::Error> = with_retries(POLICY, || {
+            attempts += 1;
+            Err(io::Error::from(io::ErrorKind::NotFound))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn with_retries_disabled() {
+        let mut attempts = 0;
+
+        let result: Result<(), io::Error> = with_retries(LayerIoRetryPolicy::default(), || {
+            attempts += 1;
+            Err(io::Error::from(io::ErrorKind::TimedOut))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn with_retries_nested_io_error() {
+        let mut attempts = 0;
+
+        let result: Result<(), WriteLayerError> = with_retries(POLICY, || {
+            attempts += 1;
+            Err(WriteLayerError::WriteLayerMetadataError(
+                WriteLayerMetadataError::TomlFileError(TomlFileError::IoError(io::Error::from(
+                    io::ErrorKind::TimedOut,
+                ))),
+            ))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 3);
+    }
+}
@@ -11,7 +11,7 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::collections::HashMap;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 pub(in crate::layer) fn read_layer<M: DeserializeOwned, P: AsRef<Path>>(
     layers_dir: P,
@@ -72,7 +72,7 @@ pub enum ReadLayerError {
     IoError(#[from] std::io::Error),
 }
 
-pub(in crate::layer) fn write_layer<M: Serialize, P: AsRef<Path>>(
+pub(crate) fn write_layer<M: Serialize, P: AsRef<Path>>(
     layers_dir: P,
     layer_name: &LayerName,
     layer_content_metadata: &LayerContentMetadata<M>,
@@ -101,12 +101,15 @@ pub enum WriteLayerError {
     #[error("{0}")]
     ReplaceLayerExecdProgramsError(ReplaceLayerExecdProgramsError),
 
+    #[error("{0}")]
+    CreateLayerSymlinkError(CreateLayerSymlinkError),
+
     #[error("Unexpected I/O error while writing layer: {0}")]
     IoError(#[from] std::io::Error),
 }
 
 /// Does not error if the layer doesn't exist.
-pub(in crate::layer) fn delete_layer<P: AsRef<Path>>(
+pub(crate) fn delete_layer<P: AsRef<Path>>(
     layers_dir: P,
     layer_name: &LayerName,
 ) -> Result<(), DeleteLayerError> {
@@ -159,6 +162,72 @@ pub(in crate::layer) fn replace_layer_types<P: AsRef<Path>>(
         .map_err(WriteLayerMetadataError::TomlFileError)
 }
 
+/// Rewrites the content metadata file of the given layer if it isn't valid layer content metadata,
+/// keeping only the layer types that can be salvaged. See [`parse_layer_content_metadata_lenient`].
+///
+/// Used by the lenient layer metadata parsing mode to treat malformed metadata as invalid, instead
+/// of failing the build.
+pub(in crate::layer) fn salvage_layer_content_metadata<P: AsRef<Path>>(
+    layers_dir: P,
+    layer_name: &LayerName,
+) -> Result<(), WriteLayerMetadataError> {
+    let layer_content_metadata_path = layers_dir.as_ref().join(format!("{layer_name}.toml"));
+
+    let contents = fs::read(&layer_content_metadata_path)?;
+
+    let is_valid = std::str::from_utf8(&contents)
+        .is_ok_and(|contents| toml::from_str::<LayerContentMetadata>(contents).is_ok());
+
+    if !is_valid {
+        write_toml_file(
+            &parse_layer_content_metadata_lenient(&String::from_utf8_lossy(&contents)),
+            &layer_content_metadata_path,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Parses layer content metadata, salvaging as much as possible from malformed documents.
+///
+/// If the document isn't valid layer content metadata, the metadata is dropped and only the layer
+/// types are kept, provided the `[types]` table can still be parsed. For documents that aren't
+/// valid TOML, the lines of the `[types]` table are parsed on their own.
+pub(in crate::layer) fn parse_layer_content_metadata_lenient(
+    contents: &str,
+) -> LayerContentMetadata {
+    if let Ok(layer_content_metadata) = toml::from_str::<LayerContentMetadata>(contents) {
+        return layer_content_metadata;
+    }
+
+    let types = toml::from_str::<toml::Table>(contents)
+        .ok()
+        .and_then(|mut table| table.remove("types"))
+        .or_else(|| {
+            types_table_source(contents)
+                .and_then(|source| toml::from_str::<toml::Table>(&source).ok())
+                .map(toml::Value::Table)
+        })
+        .and_then(|value| value.try_into::<LayerTypes>().ok());
+
+    LayerContentMetadata {
+        types,
+        metadata: None,
+    }
+}
+
+// Extracts the key/value lines of the `[types]` table, up to the next table header.
+fn types_table_source(contents: &str) -> Option<String> {
+    let mut lines = contents.lines().skip_while(|line| line.trim() != "[types]");
+
+    lines.next().map(|_| {
+        lines
+            .take_while(|line| !line.trim_start().starts_with('['))
+            .collect::<Vec<_>>()
+            .join("\n")
+    })
+}
+
 pub(crate) fn replace_layer_sboms<P: AsRef<Path>>(
     layers_dir: P,
     layer_name: &LayerName,
@@ -257,6 +326,147 @@ pub enum WriteLayerMetadataError {
     TomlFileError(#[from] TomlFileError),
 }
 
+/// Creates a symlink inside a layer, pointing to another path inside the same layer.
+///
+/// Both paths are relative: `link` to the layer directory and `target` to the directory that
+/// contains the link, just like the target of a relative symlink. Existing symlinks at `link` are
+/// replaced, which makes it safe to call this function for layers restored from cache.
+///
+/// Both the directory containing the link and the target are resolved on the filesystem, following
+/// existing symlinks, and must be inside the layer directory.
+pub(crate) fn create_layer_symlink(
+    layer_dir: &Path,
+    link: &Path,
+    target: &Path,
+) -> Result<(), CreateLayerSymlinkError> {
+    let invalid_link_path = || CreateLayerSymlinkError::InvalidLinkPath(PathBuf::from(link));
+    let target_escapes_layer = || CreateLayerSymlinkError::TargetEscapesLayer {
+        link: PathBuf::from(link),
+        target: PathBuf::from(target),
+    };
+
+    if target.is_absolute() {
+        return Err(CreateLayerSymlinkError::AbsoluteTarget(PathBuf::from(
+            target,
+        )));
+    }
+
+    let link = normalize_relative_path(link).ok_or_else(invalid_link_path)?;
+
+    let canonical_layer_dir = fs::canonicalize(layer_dir)?;
+    let link_path = layer_dir.join(&link);
+    let link_parent = link_path.parent().ok_or_else(invalid_link_path)?;
+
+    // Directories of the link path might be symlinks, so the link's parent directory must be
+    // checked before creating missing directories in it, and again after creating them.
+    if !canonicalize_existing_ancestor(link_parent)?.starts_with(&canonical_layer_dir) {
+        return Err(invalid_link_path());
+    }
+
+    fs::create_dir_all(link_parent)?;
+    let canonical_link_parent = fs::canonicalize(link_parent)?;
+    if !canonical_link_parent.starts_with(&canonical_layer_dir) {
+        return Err(invalid_link_path());
+    }
+
+    // The target is resolved like the symlink will be, following symlinks in its path.
+    let canonical_target = match fs::canonicalize(canonical_link_parent.join(target)) {
+        Ok(canonical_target) => canonical_target,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            return Err(CreateLayerSymlinkError::MissingTarget {
+                link,
+                target: PathBuf::from(target),
+            });
+        }
+        Err(error) => return Err(CreateLayerSymlinkError::IoError(error)),
+    };
+
+    if !canonical_target.starts_with(&canonical_layer_dir) {
+        return Err(target_escapes_layer());
+    }
+
+    match fs::symlink_metadata(&link_path) {
+        Ok(metadata) if metadata.is_symlink() => fs::remove_file(&link_path)?,
+        Ok(_) => return Err(CreateLayerSymlinkError::LinkPathOccupied(link)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+        Err(error) => return Err(CreateLayerSymlinkError::IoError(error)),
+    }
+
+    symlink(target, &link_path)?;
+
+    Ok(())
+}
+
+/// Canonicalizes the path, or its closest existing ancestor if the path doesn't exist.
+fn canonicalize_existing_ancestor(path: &Path) -> std::io::Result<PathBuf> {
+    let mut ancestor = path;
+
+    loop {
+        match fs::canonicalize(ancestor) {
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                ancestor = ancestor.parent().ok_or(error)?;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Normalizes a relative path lexically, returning `None` if it is absolute, empty or escapes
+/// the directory it is relative to.
+fn normalize_relative_path(path: &Path) -> Option<PathBuf> {
+    let mut normalized_path = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::Normal(name) => normalized_path.push(name),
+            Component::ParentDir => {
+                if !normalized_path.pop() {
+                    return None;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+
+    (normalized_path.components().next().is_some()).then_some(normalized_path)
+}
+
+#[cfg(target_family = "unix")]
+fn symlink(target: &Path, link_path: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link_path)
+}
+
+#[cfg(not(target_family = "unix"))]
+fn symlink(_target: &Path, _link_path: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Creating layer symlinks is only supported on Unix",
+    ))
+}
+
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum CreateLayerSymlinkError {
+    #[error("Symlink path {0} is not a relative path inside the layer")]
+    InvalidLinkPath(PathBuf),
+
+    #[error("Symlink target {0} must be relative to the symlink")]
+    AbsoluteTarget(PathBuf),
+
+    #[error("Target {target} of symlink {link} is outside of the layer")]
+    TargetEscapesLayer { link: PathBuf, target: PathBuf },
+
+    #[error("Target {target} of symlink {link} doesn't exist")]
+    MissingTarget { link: PathBuf, target: PathBuf },
+
+    #[error("Cannot create symlink {0}, the path already exists and is not a symlink")]
+    LinkPathOccupied(PathBuf),
+
+    #[error("Unexpected I/O error while creating layer symlink: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum LayerError {
     #[error("{0}")]
@@ -273,16 +483,24 @@ pub enum LayerError {
     IoError(#[from] std::io::Error),
     #[error("Unexpected missing layer")]
     UnexpectedMissingLayer,
+    #[error("Layer {layer_name} was already handled in this build with different types ({previous:?}), now requested with {current:?}")]
+    ConflictingLayerDefinition {
+        layer_name: LayerName,
+        previous: LayerTypes,
+        current: LayerTypes,
+    },
 }
 
 #[cfg(test)]
 mod test {
-    use crate::layer::ReadLayerError;
+    use crate::layer::{CreateLayerSymlinkError, ReadLayerError};
+    use libcnb_common::toml_file::read_toml_file;
     use libcnb_data::generic::GenericMetadata;
     use libcnb_data::layer_content_metadata::{LayerContentMetadata, LayerTypes};
     use libcnb_data::layer_name;
     use serde::Deserialize;
     use std::fs;
+    use std::path::Path;
     use tempfile::tempdir;
 
     #[test]
@@ -453,4 +671,263 @@ mod test {
             _ => panic!("Expected Ok(None)!"),
         }
     }
+
+    #[test]
+    fn create_layer_symlink() {
+        let temp_dir = tempdir().unwrap();
+        let layer_dir = temp_dir.path();
+        fs::create_dir_all(layer_dir.join("node-v22.0.0/bin")).unwrap();
+        fs::write(layer_dir.join("node-v22.0.0/bin/node"), "").unwrap();
+
+        super::create_layer_symlink(
+            layer_dir,
+            Path::new("bin/node"),
+            Path::new("../node-v22.0.0/bin/node"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_link(layer_dir.join("bin/node")).unwrap(),
+            Path::new("../node-v22.0.0/bin/node")
+        );
+        assert!(layer_dir.join("bin/node").is_file());
+
+        // Existing symlinks are replaced, i.e. for layers restored from cache.
+        fs::create_dir_all(layer_dir.join("node-v22.1.0/bin")).unwrap();
+        fs::write(layer_dir.join("node-v22.1.0/bin/node"), "").unwrap();
+
+        super::create_layer_symlink(
+            layer_dir,
+            Path::new("bin/node"),
+            Path::new("../node-v22.1.0/bin/node"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_link(layer_dir.join("bin/node")).unwrap(),
+            Path::new("../node-v22.1.0/bin/node")
+        );
+    }
+
+    #[test]
+    fn create_layer_symlink_invalid_paths() {
+        let temp_dir = tempdir().unwrap();
+        let layer_dir = temp_dir.path().join("layer");
+        fs::create_dir_all(layer_dir.join("bin")).unwrap();
+        fs::write(layer_dir.join("bin/node"), "").unwrap();
+        fs::write(temp_dir.path().join("outside"), "").unwrap();
+
+        assert!(matches!(
+            super::create_layer_symlink(
+                &layer_dir,
+                Path::new("../node"),
+                Path::new("layer/bin/node")
+            ),
+            Err(CreateLayerSymlinkError::InvalidLinkPath(_))
+        ));
+        assert!(matches!(
+            super::create_layer_symlink(&layer_dir, Path::new("/node"), Path::new("bin/node")),
+            Err(CreateLayerSymlinkError::InvalidLinkPath(_))
+        ));
+        assert!(matches!(
+            super::create_layer_symlink(&layer_dir, Path::new("node"), &layer_dir.join("bin/node")),
+            Err(CreateLayerSymlinkError::AbsoluteTarget(_))
+        ));
+        assert!(matches!(
+            super::create_layer_symlink(&layer_dir, Path::new("node"), Path::new("../outside")),
+            Err(CreateLayerSymlinkError::TargetEscapesLayer { .. })
+        ));
+        assert!(matches!(
+            super::create_layer_symlink(&layer_dir, Path::new("node"), Path::new("bin/missing")),
+            Err(CreateLayerSymlinkError::MissingTarget { .. })
+        ));
+        assert!(matches!(
+            super::create_layer_symlink(&layer_dir, Path::new("bin/node"), Path::new("node")),
+            Err(CreateLayerSymlinkError::LinkPathOccupied(_))
+        ));
+        assert!(!layer_dir.join("node").exists());
+    }
+
+    #[test]
+    fn create_layer_symlink_target_through_escaping_symlink() {
+        let temp_dir = tempdir().unwrap();
+        let layer_dir = temp_dir.path().join("layer");
+        fs::create_dir_all(&layer_dir).unwrap();
+        fs::create_dir_all(temp_dir.path().join("outside")).unwrap();
+        fs::write(temp_dir.path().join("outside/node"), "").unwrap();
+        std::os::unix::fs::symlink("../outside", layer_dir.join("escape")).unwrap();
+
+        assert!(matches!(
+            super::create_layer_symlink(&layer_dir, Path::new("node"), Path::new("escape/node")),
+            Err(CreateLayerSymlinkError::TargetEscapesLayer { .. })
+        ));
+    }
+
+    #[test]
+    fn create_layer_symlink_link_through_escaping_symlink() {
+        let temp_dir = tempdir().unwrap();
+        let layer_dir = temp_dir.path().join("layer");
+        fs::create_dir_all(layer_dir.join("bin")).unwrap();
+        fs::write(layer_dir.join("bin/node"), "").unwrap();
+        fs::create_dir_all(temp_dir.path().join("outside")).unwrap();
+        std::os::unix::fs::symlink("../outside", layer_dir.join("escape")).unwrap();
+
+        assert!(matches!(
+            super::create_layer_symlink(
+                &layer_dir,
+                Path::new("escape/node"),
+                Path::new("bin/node")
+            ),
+            Err(CreateLayerSymlinkError::InvalidLinkPath(_))
+        ));
+        assert!(matches!(
+            super::create_layer_symlink(
+                &layer_dir,
+                Path::new("escape/sub/node"),
+                Path::new("../../bin/node")
+            ),
+            Err(CreateLayerSymlinkError::InvalidLinkPath(_))
+        ));
+        assert!(!temp_dir.path().join("outside/node").exists());
+        assert!(!temp_dir.path().join("outside/sub").exists());
+    }
+
+    #[test]
+    fn parse_layer_content_metadata_lenient() {
+        let types = Some(LayerTypes {
+            build: true,
+            launch: false,
+            cache: true,
+        });
+
+        // Invalid TOML after the types table.
+        assert_eq!(
+            super::parse_layer_content_metadata_lenient(
+                "[types]\nbuild = true\ncache = true\n\n[metadata]\nversion = \"1.0\n"
+            ),
+            LayerContentMetadata {
+                types,
+                metadata: None
+            }
+        );
+
+        // Valid TOML with unknown top-level keys.
+        assert_eq!(
+            super::parse_layer_content_metadata_lenient(
+                "launch = true\n\n[types]\nbuild = true\ncache = true\n"
+            ),
+            LayerContentMetadata {
+                types,
+                metadata: None
+            }
+        );
+
+        // Invalid types table.
+        assert_eq!(
+            super::parse_layer_content_metadata_lenient(
+                "[types]\nbuild = \"yes\"\n\n[metadata]\nversion = \"1.0\"\n"
+            ),
+            LayerContentMetadata {
+                types: None,
+                metadata: None
+            }
+        );
+
+        // Valid layer content metadata is returned as-is.
+        assert_eq!(
+            super::parse_layer_content_metadata_lenient("[metadata]\nversion = \"1.0\"\n"),
+            toml::from_str::<LayerContentMetadata>("[metadata]\nversion = \"1.0\"\n").unwrap()
+        );
+    }
+
+    #[test]
+    fn salvage_layer_content_metadata() {
+        let layer_name = layer_name!("foo");
+        let temp_dir = tempdir().unwrap();
+        let layers_dir = temp_dir.path();
+        let layer_toml_path = layers_dir.join(format!("{layer_name}.toml"));
+
+        fs::write(&layer_toml_path, "[types]\nbuild = true\n\n[metadata\n").unwrap();
+        super::salvage_layer_content_metadata(layers_dir, &layer_name).unwrap();
+        assert_eq!(
+            fs::read_to_string(&layer_toml_path).unwrap(),
+            "[types]\nlaunch = false\nbuild = true\ncache = false\n"
+        );
+
+        // Valid layer content metadata is left untouched.
+        fs::write(&layer_toml_path, "[metadata]\nanswer = 42\n").unwrap();
+        super::salvage_layer_content_metadata(layers_dir, &layer_name).unwrap();
+        assert_eq!(
+            fs::read_to_string(&layer_toml_path).unwrap(),
+            "[metadata]\nanswer = 42\n"
+        );
+    }
+
+    // Mutates valid layer content metadata documents at random (with a fixed seed, so failures are
+    // reproducible) and verifies that lenient parsing never panics, never changes the result for
+    // documents that are still valid and always produces a file that can be parsed again.
+    #[test]
+    fn fuzz_lenient_layer_content_metadata_parsing() {
+        const SEED_DOCUMENTS: [&str; 3] = [
+            "[types]\nbuild = true\nlaunch = true\ncache = false\n\n[metadata]\nversion = \"22.12.0\"\nurl = \"https://example.com/node.tgz\"\n",
+            "[metadata]\nanswer = 42\n\n[metadata.nested]\nlist = [1, 2, 3]\ntable = { key = \"value\" }\n",
+            "types = { build = true, cache = true }\nmetadata = { checksum = \"sha256:abc\" }\n",
+        ];
+        const INTERESTING_BYTES: &[u8] = b"[]{}=\"'#.,\n \\tx0\xff";
+
+        let layer_name = layer_name!("foo");
+        let temp_dir = tempdir().unwrap();
+        let layers_dir = temp_dir.path();
+        let layer_toml_path = layers_dir.join(format!("{layer_name}.toml"));
+
+        // xorshift64, to avoid a dependency on a random number generator crate.
+        let mut state = 0x2545_F491_4F6C_DD1D_u64;
+        let mut next_random = |bound: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            usize::try_from(state % bound as u64).unwrap()
+        };
+
+        for iteration in 0..2000 {
+            let mut document = SEED_DOCUMENTS[iteration % SEED_DOCUMENTS.len()]
+                .as_bytes()
+                .to_vec();
+
+            for _ in 0..=next_random(4) {
+                let position = next_random(document.len() + 1);
+                match next_random(4) {
+                    0 => document.truncate(position),
+                    1 if position < document.len() => {
+                        document[position] =
+                            INTERESTING_BYTES[next_random(INTERESTING_BYTES.len())];
+                    }
+                    2 => document.insert(
+                        position,
+                        INTERESTING_BYTES[next_random(INTERESTING_BYTES.len())],
+                    ),
+                    _ if position < document.len() => {
+                        document.remove(position);
+                    }
+                    _ => {}
+                }
+            }
+
+            let contents = String::from_utf8_lossy(&document);
+            let lenient_result = super::parse_layer_content_metadata_lenient(&contents);
+
+            if let Ok(strict_result) = toml::from_str::<LayerContentMetadata>(&contents) {
+                assert_eq!(lenient_result, strict_result, "Document: {contents:?}");
+            } else {
+                assert_eq!(lenient_result.metadata, None, "Document: {contents:?}");
+            }
+
+            fs::write(&layer_toml_path, &document).unwrap();
+            super::salvage_layer_content_metadata(layers_dir, &layer_name).unwrap();
+            assert!(
+                read_toml_file::<LayerContentMetadata>(&layer_toml_path).is_ok(),
+                "Document: {contents:?}"
+            );
+        }
+    }
 }
@@ -1,6 +1,8 @@
+use crate::build::CollectedSboms;
+use crate::layer::retry::with_retries;
 use crate::layer::shared::{
-    delete_layer, read_layer, replace_layer_metadata, replace_layer_types, ReadLayerError,
-    WriteLayerError,
+    delete_layer, read_layer, replace_layer_metadata, replace_layer_types,
+    salvage_layer_content_metadata, ReadLayerError, WriteLayerError,
 };
 use crate::layer::{
     EmptyLayerCause, IntoAction, InvalidMetadataAction, LayerError, LayerRef, LayerState,
@@ -15,13 +17,16 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 pub(crate) fn handle_layer<B, M, MA, RA, MAC, RAC>(
     layer_types: LayerTypes,
+    migrate_metadata_fn: &dyn Fn(&GenericMetadata) -> Option<M>,
     invalid_metadata_action_fn: &dyn Fn(&GenericMetadata) -> MA,
     restored_layer_action_fn: &dyn Fn(&M, &Path) -> RA,
     layer_name: &LayerName,
     layers_dir: &Path,
+    collected_sboms: &Arc<Mutex<CollectedSboms>>,
 ) -> crate::Result<LayerRef<B, MAC, RAC>, B::Error>
 where
     B: Buildpack + ?Sized,
@@ -29,11 +34,14 @@ where
     MA: IntoAction<InvalidMetadataAction<M>, MAC, B::Error>,
     RA: IntoAction<RestoredLayerAction, RAC, B::Error>,
 {
-    match read_layer::<M, _>(layers_dir, layer_name) {
+    let retry_policy = B::LAYER_IO_RETRY_POLICY;
+
+    match with_retries(retry_policy, || read_layer::<M, _>(layers_dir, layer_name)) {
         Ok(None) => create_layer(
             layer_types,
             layer_name,
             layers_dir,
+            collected_sboms,
             EmptyLayerCause::NewlyCreated,
         ),
         Ok(Some(layer_data)) => {
@@ -44,12 +52,14 @@ where
 
             match restored_layer_action {
                 (RestoredLayerAction::DeleteLayer, cause) => {
-                    delete_layer(layers_dir, layer_name).map_err(LayerError::DeleteLayerError)?;
+                    with_retries(retry_policy, || delete_layer(layers_dir, layer_name))
+                        .map_err(LayerError::DeleteLayerError)?;
 
                     create_layer(
                         layer_types,
                         layer_name,
                         layers_dir,
+                        collected_sboms,
                         EmptyLayerCause::RestoredLayerAction { cause },
                     )
                 }
@@ -57,13 +67,17 @@ where
                     // Always write the layer types as:
                     // a) they might be different from what is currently on disk
                     // b) the cache field will be removed by CNB lifecycle on cache restore
-                    replace_layer_types(layers_dir, layer_name, layer_types).map_err(|error| {
+                    with_retries(retry_policy, || {
+                        replace_layer_types(layers_dir, layer_name, layer_types)
+                    })
+                    .map_err(|error| {
                         LayerError::WriteLayerError(WriteLayerError::WriteLayerMetadataError(error))
                     })?;
 
                     Ok(LayerRef {
                         name: layer_data.name,
                         layers_dir: PathBuf::from(layers_dir),
+                        collected_sboms: Arc::clone(collected_sboms),
                         buildpack: PhantomData,
                         state: LayerState::Restored { cause },
                     })
@@ -71,41 +85,60 @@ where
             }
         }
         Err(ReadLayerError::LayerContentMetadataParseError(_)) => {
-            let layer_content_metadata = read_toml_file::<LayerContentMetadata>(
-                layers_dir.join(format!("{layer_name}.toml")),
-            )
+            if B::LENIENT_LAYER_METADATA_PARSING {
+                with_retries(retry_policy, || {
+                    salvage_layer_content_metadata(layers_dir, layer_name)
+                })
+                .map_err(|error| {
+                    LayerError::WriteLayerError(WriteLayerError::WriteLayerMetadataError(error))
+                })?;
+            }
+
+            let layer_content_metadata = with_retries(retry_policy, || {
+                read_toml_file::<LayerContentMetadata>(
+                    layers_dir.join(format!("{layer_name}.toml")),
+                )
+            })
             .map_err(LayerError::CouldNotReadGenericLayerMetadata)?;
 
-            let invalid_metadata_action =
-                invalid_metadata_action_fn(&layer_content_metadata.metadata)
+            let replacement_metadata = match migrate_metadata_fn(&layer_content_metadata.metadata) {
+                Some(metadata) => metadata,
+                None => match invalid_metadata_action_fn(&layer_content_metadata.metadata)
                     .into_action()
-                    .map_err(crate::Error::BuildpackError)?;
-
-            match invalid_metadata_action {
-                (InvalidMetadataAction::DeleteLayer, cause) => {
-                    delete_layer(layers_dir, layer_name).map_err(LayerError::DeleteLayerError)?;
-
-                    create_layer(
-                        layer_types,
-                        layer_name,
-                        layers_dir,
-                        EmptyLayerCause::InvalidMetadataAction { cause },
-                    )
-                }
-                (InvalidMetadataAction::ReplaceMetadata(metadata), _) => {
-                    replace_layer_metadata(layers_dir, layer_name, metadata).map_err(|error| {
-                        LayerError::WriteLayerError(WriteLayerError::WriteLayerMetadataError(error))
-                    })?;
-
-                    handle_layer(
-                        layer_types,
-                        invalid_metadata_action_fn,
-                        restored_layer_action_fn,
-                        layer_name,
-                        layers_dir,
-                    )
-                }
-            }
+                    .map_err(crate::Error::BuildpackError)?
+                {
+                    (InvalidMetadataAction::DeleteLayer, cause) => {
+                        with_retries(retry_policy, || delete_layer(layers_dir, layer_name))
+                            .map_err(LayerError::DeleteLayerError)?;
+
+                        return create_layer(
+                            layer_types,
+                            layer_name,
+                            layers_dir,
+                            collected_sboms,
+                            EmptyLayerCause::InvalidMetadataAction { cause },
+                        );
+                    }
+                    (InvalidMetadataAction::ReplaceMetadata(metadata), _) => metadata,
+                },
+            };
+
+            with_retries(retry_policy, || {
+                replace_layer_metadata(layers_dir, layer_name, &replacement_metadata)
+            })
+            .map_err(|error| {
+                LayerError::WriteLayerError(WriteLayerError::WriteLayerMetadataError(error))
+            })?;
+
+            handle_layer(
+                layer_types,
+                migrate_metadata_fn,
+                invalid_metadata_action_fn,
+                restored_layer_action_fn,
+                layer_name,
+                layers_dir,
+                collected_sboms,
+            )
         }
         Err(read_layer_error) => Err(LayerError::ReadLayerError(read_layer_error))?,
     }
@@ -115,28 +148,36 @@ fn create_layer<B, MAC, RAC>(
     layer_types: LayerTypes,
     layer_name: &LayerName,
     layers_dir: &Path,
+    collected_sboms: &Arc<Mutex<CollectedSboms>>,
     empty_layer_cause: EmptyLayerCause<MAC, RAC>,
 ) -> Result<LayerRef<B, MAC, RAC>, crate::Error<B::Error>>
 where
     B: Buildpack + ?Sized,
 {
-    crate::layer::shared::write_layer(
-        layers_dir,
-        layer_name,
-        &LayerContentMetadata {
-            types: Some(layer_types),
-            metadata: GenericMetadata::default(),
-        },
-    )
+    let retry_policy = B::LAYER_IO_RETRY_POLICY;
+
+    with_retries(retry_policy, || {
+        crate::layer::shared::write_layer(
+            layers_dir,
+            layer_name,
+            &LayerContentMetadata {
+                types: Some(layer_types),
+                metadata: GenericMetadata::default(),
+            },
+        )
+    })
     .map_err(LayerError::WriteLayerError)?;
 
-    let layer_data = read_layer::<GenericMetadata, _>(layers_dir, layer_name)
-        .map_err(LayerError::ReadLayerError)?
-        .ok_or(LayerError::CouldNotReadLayerAfterCreate(layer_name.clone()))?;
+    let layer_data = with_retries(retry_policy, || {
+        read_layer::<GenericMetadata, _>(layers_dir, layer_name)
+    })
+    .map_err(LayerError::ReadLayerError)?
+    .ok_or(LayerError::CouldNotReadLayerAfterCreate(layer_name.clone()))?;
 
     Ok(LayerRef {
         name: layer_data.name,
         layers_dir: PathBuf::from(layers_dir),
+        collected_sboms: Arc::clone(collected_sboms),
         buildpack: PhantomData,
         state: LayerState::Empty {
             cause: empty_layer_cause,
@@ -147,16 +188,23 @@ where
 #[cfg(test)]
 mod tests {
     use super::handle_layer;
-    use crate::build::{BuildContext, BuildResult};
+    use crate::build::{BuildContext, BuildResult, CollectedSboms};
     use crate::detect::{DetectContext, DetectResult};
     use crate::generic::{GenericError, GenericPlatform};
-    use crate::layer::{EmptyLayerCause, InvalidMetadataAction, LayerState, RestoredLayerAction};
+    use crate::layer::{
+        migrate_versioned_metadata, EmptyLayerCause, InvalidMetadataAction, LayerError, LayerState,
+        MetadataVersioned, NoPreviousMetadataVersion, ReadLayerError, RestoredLayerAction,
+    };
+    use crate::layer_env::{LayerEnv, ModificationBehavior, Scope};
+    use crate::sbom::Sbom;
     use crate::Buildpack;
     use libcnb_common::toml_file::read_toml_file;
     use libcnb_data::generic::GenericMetadata;
     use libcnb_data::layer_content_metadata::{LayerContentMetadata, LayerTypes};
     use libcnb_data::layer_name;
+    use libcnb_data::sbom::SbomFormat;
     use serde::{Deserialize, Serialize};
+    use std::sync::{Arc, Mutex};
     use tempfile::tempdir;
     use toml::toml;
 
@@ -174,6 +222,7 @@ mod tests {
             },
             &layer_name,
             temp_dir.path(),
+            &Arc::default(),
             cause,
         )
         .unwrap();
@@ -197,6 +246,122 @@ mod tests {
         );
     }
 
+    #[test]
+    fn layer_ref_collects_sboms() {
+        let temp_dir = tempdir().unwrap();
+        let collected_sboms = Arc::new(Mutex::new(CollectedSboms::default()));
+
+        let layer_ref = super::create_layer::<TestBuildpack, (), ()>(
+            LayerTypes {
+                launch: true,
+                build: true,
+                cache: false,
+            },
+            &layer_name!("test_layer"),
+            temp_dir.path(),
+            &collected_sboms,
+            EmptyLayerCause::NewlyCreated,
+        )
+        .unwrap();
+
+        layer_ref.add_launch_sbom(Sbom::from_bytes(SbomFormat::CycloneDxJson, "{}"));
+        layer_ref.add_build_sbom(Sbom::from_bytes(SbomFormat::SyftJson, "{}"));
+        layer_ref.add_launch_sbom(Sbom::from_bytes(SbomFormat::SpdxJson, "{}"));
+
+        let collected_sboms = std::mem::take(&mut *collected_sboms.lock().unwrap());
+        assert_eq!(
+            collected_sboms
+                .launch
+                .iter()
+                .map(|sbom| sbom.format.clone())
+                .collect::<Vec<_>>(),
+            [SbomFormat::CycloneDxJson, SbomFormat::SpdxJson]
+        );
+        assert_eq!(collected_sboms.build.len(), 1);
+    }
+
+    #[test]
+    fn layer_ref_modify_env() {
+        let temp_dir = tempdir().unwrap();
+
+        let layer_ref = super::create_layer::<TestBuildpack, (), ()>(
+            LayerTypes {
+                launch: true,
+                build: true,
+                cache: false,
+            },
+            &layer_name!("test_layer"),
+            temp_dir.path(),
+            &Arc::default(),
+            EmptyLayerCause::NewlyCreated,
+        )
+        .unwrap();
+        std::fs::create_dir(layer_ref.path().join("bin")).unwrap();
+
+        layer_ref
+            .write_env(LayerEnv::new().chainable_insert(
+                Scope::All,
+                ModificationBehavior::Override,
+                "FOO",
+                "foo",
+            ))
+            .unwrap();
+
+        layer_ref
+            .modify_env(|layer_env| {
+                layer_env.chainable_insert(
+                    Scope::Process(String::from("web")),
+                    ModificationBehavior::Override,
+                    "BAR",
+                    "bar",
+                )
+            })
+            .unwrap();
+
+        let env = layer_ref
+            .read_env()
+            .unwrap()
+            .apply_to_empty(Scope::Process(String::from("web")));
+
+        assert_eq!(env.get_string_lossy("FOO"), Some(String::from("foo")));
+        assert_eq!(env.get_string_lossy("BAR"), Some(String::from("bar")));
+        assert!(!temp_dir.path().join("test_layer/env/PATH.prepend").exists());
+    }
+
+    #[test]
+    fn layer_ref_write_and_read_metadata() {
+        let temp_dir = tempdir().unwrap();
+
+        let layer_ref = super::create_layer::<TestBuildpack, (), ()>(
+            LayerTypes {
+                launch: true,
+                build: true,
+                cache: true,
+            },
+            &layer_name!("test_layer"),
+            temp_dir.path(),
+            &Arc::default(),
+            EmptyLayerCause::NewlyCreated,
+        )
+        .unwrap();
+
+        let metadata = layer_ref
+            .write_metadata(TestLayerMetadataV1 { answer: 42 })
+            .unwrap();
+
+        assert_eq!(metadata, TestLayerMetadataV1 { answer: 42 });
+        assert_eq!(
+            layer_ref.read_metadata::<TestLayerMetadataV1>().unwrap(),
+            metadata
+        );
+        assert!(matches!(
+            layer_ref.read_metadata::<TestLayerMetadataV2>(),
+            Err(crate::Error::LayerError(LayerError::ReadLayerError(
+                ReadLayerError::LayerContentMetadataParseError(_)
+            )))
+        ));
+    }
+
     #[test]
     fn handle_layer_uncached() {
         let temp_dir = tempdir().unwrap();
@@ -215,10 +380,12 @@ mod tests {
                 launch: true,
                 cache: true,
             },
+            &|_| None,
             &|_| panic!("invalid_metadata_action callback should not be called!"),
             &|_, _| panic!("restored_layer_action callback should not be called!"),
             &layer_name,
             temp_dir.path(),
+            &Arc::default(),
         )
         .unwrap();
 
@@ -269,6 +436,7 @@ mod tests {
                     launch: true,
                     cache: true,
                 },
+                &|_| None,
                 &|_| panic!("invalid_metadata_action callback should not be called!"),
                 &|metadata, path| {
                     assert_eq!(metadata, &Some(toml! { answer = 42 }));
@@ -277,6 +445,7 @@ mod tests {
                 },
                 &layer_name,
                 temp_dir.path(),
+                &Arc::default(),
             )
             .unwrap();
 
@@ -322,6 +491,7 @@ mod tests {
                     launch: true,
                     cache: true,
                 },
+                &|_| None,
                 &|_| panic!("invalid_metadata_action callback should not be called!"),
                 &|metadata, path| {
                     assert_eq!(metadata, &Some(toml! { answer = 42 }));
@@ -330,6 +500,7 @@ mod tests {
                 },
                 &layer_name,
                 temp_dir.path(),
+                &Arc::default(),
             )
             .unwrap();
 
@@ -393,6 +564,7 @@ mod tests {
                 launch: true,
                 cache: true,
             },
+            &|_| None,
             &|metadata| {
                 assert_eq!(metadata, &Some(toml! { answer = 42 }));
                 (InvalidMetadataAction::DeleteLayer, DELETE_CAUSE)
@@ -400,6 +572,7 @@ mod tests {
             &|_, _| panic!("restored_layer_action callback should not be called!"),
             &layer_name,
             temp_dir.path(),
+            &Arc::default(),
         )
         .unwrap();
 
@@ -461,6 +634,7 @@ mod tests {
                 launch: true,
                 cache: true,
             },
+            &|_| None,
             &|metadata| {
                 assert_eq!(metadata, &Some(toml! { answer = 42 }));
 
@@ -480,6 +654,7 @@ mod tests {
             },
             &layer_name,
             temp_dir.path(),
+            &Arc::default(),
         )
         .unwrap();
 
@@ -505,6 +680,203 @@ mod tests {
         assert_eq!(layer_ref.state, LayerState::Restored { cause: KEEP_CAUSE });
     }
 
+    #[test]
+    fn handle_layer_cached_invalid_metadata_migrate() {
+        const KEEP_CAUSE: &str = "cause";
+
+        let temp_dir = tempdir().unwrap();
+        let layer_name = layer_name!("test_layer");
+
+        std::fs::create_dir_all(temp_dir.path().join(&*layer_name)).unwrap();
+        std::fs::write(
+            temp_dir.path().join(format!("{layer_name}.toml")),
+            "[metadata]\nanswer=42",
+        )
+        .unwrap();
+
+        let layer_ref = handle_layer::<
+            TestBuildpack,
+            TestLayerMetadataV2,
+            InvalidMetadataAction<TestLayerMetadataV2>,
+            _,
+            (),
+            _,
+        >(
+            LayerTypes {
+                build: true,
+                launch: true,
+                cache: true,
+            },
+            &migrate_versioned_metadata,
+            &|_| panic!("invalid_metadata_action callback should not be called!"),
+            &|metadata, _| {
+                assert_eq!(
+                    metadata,
+                    &TestLayerMetadataV2 {
+                        answer: String::from("42")
+                    }
+                );
+
+                (RestoredLayerAction::KeepLayer, KEEP_CAUSE)
+            },
+            &layer_name,
+            temp_dir.path(),
+            &Arc::default(),
+        )
+        .unwrap();
+
+        assert_eq!(layer_ref.state, LayerState::Restored { cause: KEEP_CAUSE });
+        assert_eq!(
+            read_toml_file::<LayerContentMetadata<TestLayerMetadataV2>>(
+                temp_dir.path().join(format!("{layer_name}.toml"))
+            )
+            .unwrap()
+            .metadata,
+            TestLayerMetadataV2 {
+                answer: String::from("42")
+            }
+        );
+    }
+
+    #[test]
+    fn handle_layer_cached_invalid_metadata_migrate_fallback() {
+        const DELETE_CAUSE: &str = "cause";
+
+        let temp_dir = tempdir().unwrap();
+        let layer_name = layer_name!("test_layer");
+
+        // Metadata that doesn't match any known version falls back to the invalid metadata action.
+        std::fs::create_dir_all(temp_dir.path().join(&*layer_name)).unwrap();
+        std::fs::write(
+            temp_dir.path().join(format!("{layer_name}.toml")),
+            "[metadata]\nquestion=42",
+        )
+        .unwrap();
+
+        let layer_ref =
+            handle_layer::<TestBuildpack, TestLayerMetadataV2, _, RestoredLayerAction, _, ()>(
+                LayerTypes {
+                    build: true,
+                    launch: true,
+                    cache: true,
+                },
+                &migrate_versioned_metadata,
+                &|_| (InvalidMetadataAction::DeleteLayer, DELETE_CAUSE),
+                &|_, _| panic!("restored_layer_action callback should not be called!"),
+                &layer_name,
+                temp_dir.path(),
+                &Arc::default(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            layer_ref.state,
+            LayerState::Empty {
+                cause: EmptyLayerCause::InvalidMetadataAction {
+                    cause: DELETE_CAUSE
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn handle_layer_cached_malformed_metadata() {
+        const DELETE_CAUSE: &str = "cause";
+
+        let temp_dir = tempdir().unwrap();
+        let layer_name = layer_name!("test_layer");
+        let layer_toml_path = temp_dir.path().join(format!("{layer_name}.toml"));
+
+        std::fs::create_dir_all(temp_dir.path().join(&*layer_name)).unwrap();
+        std::fs::write(&layer_toml_path, "[metadata]\nanswer = \"42").unwrap();
+
+        // Malformed metadata fails the build by default.
+        assert!(matches!(
+            handle_layer::<TestBuildpack, GenericMetadata, _, RestoredLayerAction, _, ()>(
+                LayerTypes {
+                    build: true,
+                    launch: true,
+                    cache: true,
+                },
+                &|_| None,
+                &|_| InvalidMetadataAction::DeleteLayer,
+                &|_, _| panic!("restored_layer_action callback should not be called!"),
+                &layer_name,
+                temp_dir.path(),
+                &Arc::default(),
+            ),
+            Err(crate::Error::LayerError(
+                LayerError::CouldNotReadGenericLayerMetadata(_)
+            ))
+        ));
+
+        // With lenient parsing, malformed metadata triggers the invalid metadata action instead.
+        let layer_ref = handle_layer::<
+            LenientTestBuildpack,
+            TestLayerMetadataV1,
+            _,
+            RestoredLayerAction,
+            _,
+            (),
+        >(
+            LayerTypes {
+                build: true,
+                launch: true,
+                cache: true,
+            },
+            &|_| None,
+            &|metadata| {
+                assert_eq!(metadata, &None);
+                (InvalidMetadataAction::DeleteLayer, DELETE_CAUSE)
+            },
+            &|_, _| panic!("restored_layer_action callback should not be called!"),
+            &layer_name,
+            temp_dir.path(),
+            &Arc::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            layer_ref.state,
+            LayerState::Empty {
+                cause: EmptyLayerCause::InvalidMetadataAction {
+                    cause: DELETE_CAUSE
+                }
+            }
+        );
+        assert!(read_toml_file::<LayerContentMetadata>(&layer_toml_path).is_ok());
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    struct TestLayerMetadataV1 {
+        answer: i64,
+    }
+
+    impl MetadataVersioned for TestLayerMetadataV1 {
+        type Previous = NoPreviousMetadataVersion;
+
+        fn migrate_from_previous(previous: Self::Previous) -> Option<Self> {
+            match previous {}
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    struct TestLayerMetadataV2 {
+        answer: String,
+    }
+
+    impl MetadataVersioned for TestLayerMetadataV2 {
+        type Previous = TestLayerMetadataV1;
+
+        fn migrate_from_previous(previous: Self::Previous) -> Option<Self> {
+            Some(Self {
+                answer: previous.answer.to_string(),
+            })
+        }
+    }
+
     struct TestBuildpack;
     impl Buildpack for TestBuildpack {
         type Platform = GenericPlatform;
@@ -519,4 +891,21 @@ mod tests {
             unimplemented!()
         }
     }
+
+    struct LenientTestBuildpack;
+    impl Buildpack for LenientTestBuildpack {
+        type Platform = GenericPlatform;
+        type Metadata = GenericMetadata;
+        type Error = GenericError;
+
+        const LENIENT_LAYER_METADATA_PARSING: bool = true;
+
+        fn detect(&self, _: DetectContext<Self>) -> crate::Result<DetectResult, Self::Error> {
+            unimplemented!()
+        }
+
+        fn build(&self, _: BuildContext<Self>) -> crate::Result<BuildResult, Self::Error> {
+            unimplemented!()
+        }
+    }
 }
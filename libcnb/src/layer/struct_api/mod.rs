@@ -2,19 +2,26 @@ pub(crate) mod handling;
 
 // BuildContext is only used in RustDoc (https://github.com/rust-lang/rust/issues/79542)
 #[allow(unused)]
-use crate::build::BuildContext;
-use crate::layer::shared::{replace_layer_exec_d_programs, replace_layer_sboms, WriteLayerError};
+use crate::build::{BuildContext, CollectedSboms};
+use crate::layer::retry::with_retries;
+use crate::layer::shared::{
+    create_layer_symlink, read_layer, replace_layer_exec_d_programs, replace_layer_sboms,
+    WriteLayerError,
+};
 use crate::layer::{LayerError, ReadLayerError};
 use crate::layer_env::LayerEnv;
 use crate::sbom::Sbom;
+use crate::util::lock;
 use crate::Buildpack;
 use libcnb_data::generic::GenericMetadata;
 use libcnb_data::layer::LayerName;
-use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use std::borrow::Borrow;
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 /// A definition for a cached layer.
 ///
@@ -54,6 +61,120 @@ pub enum InvalidMetadataAction<M> {
     ReplaceMetadata(M),
 }
 
+/// Layer metadata that declares its previous version, allowing stepwise migrations.
+///
+/// Each version of the metadata is a separate type that declares the type of the version before
+/// it, forming a chain of versions that ends with [`NoPreviousMetadataVersion`]. When the
+/// metadata of a restored layer cannot be parsed as the current version,
+/// [`migrate_versioned_metadata`] walks back the chain until a version parses and then applies the
+/// migrations up to the current version.
+///
+/// Since versions are detected by parsing, make sure that metadata of one version never parses as
+/// another, i.e. with `#[serde(deny_unknown_fields)]` or a dedicated version field.
+///
+/// # Example
+/// ```
+/// use libcnb::data::generic::GenericMetadata;
+/// use libcnb::layer::{migrate_versioned_metadata, MetadataVersioned, NoPreviousMetadataVersion};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// #[serde(deny_unknown_fields)]
+/// struct MetadataV1 {
+///     version: String,
+/// }
+///
+/// impl MetadataVersioned for MetadataV1 {
+///     type Previous = NoPreviousMetadataVersion;
+///
+///     fn migrate_from_previous(previous: Self::Previous) -> Option<Self> {
+///         match previous {}
+///     }
+/// }
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// #[serde(deny_unknown_fields)]
+/// struct MetadataV2 {
+///     runtime_version: String,
+///     arch: String,
+/// }
+///
+/// impl MetadataVersioned for MetadataV2 {
+///     type Previous = MetadataV1;
+///
+///     fn migrate_from_previous(previous: Self::Previous) -> Option<Self> {
+///         Some(Self {
+///             runtime_version: previous.version,
+///             // Version 1 only supported amd64.
+///             arch: String::from("amd64"),
+///         })
+///     }
+/// }
+///
+/// let metadata: GenericMetadata = toml::from_str(r#"version = "1.2.3""#).unwrap();
+///
+/// assert_eq!(
+///     migrate_versioned_metadata::<MetadataV2>(&metadata),
+///     Some(MetadataV2 {
+///         runtime_version: String::from("1.2.3"),
+///         arch: String::from("amd64"),
+///     })
+/// );
+/// ```
+pub trait MetadataVersioned: DeserializeOwned {
+    /// The previous version of this metadata, or [`NoPreviousMetadataVersion`] if this is the
+    /// first version.
+    type Previous: MetadataVersioned;
+
+    /// Migrates metadata of the previous version to this version.
+    ///
+    /// Returns `None` if the metadata cannot be migrated, in which case the layer's
+    /// `invalid_metadata_action` is called.
+    fn migrate_from_previous(previous: Self::Previous) -> Option<Self>;
+
+    #[doc(hidden)]
+    #[must_use]
+    fn parse_or_migrate(metadata: &GenericMetadata) -> Option<Self> {
+        parse_metadata(metadata).or_else(|| {
+            Self::Previous::parse_or_migrate(metadata).and_then(Self::migrate_from_previous)
+        })
+    }
+}
+
+/// Marks the end of a [`MetadataVersioned`] version chain.
+///
+/// This type has no values and never parses, so it can only be used as the previous version of
+/// the first metadata version.
+#[derive(Deserialize, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NoPreviousMetadataVersion {}
+
+impl MetadataVersioned for NoPreviousMetadataVersion {
+    type Previous = Self;
+
+    fn migrate_from_previous(previous: Self::Previous) -> Option<Self> {
+        Some(previous)
+    }
+
+    fn parse_or_migrate(_metadata: &GenericMetadata) -> Option<Self> {
+        None
+    }
+}
+
+/// Parses the given metadata as `M`, migrating it from previous versions if necessary.
+///
+/// Intended to be used as the `migrate_metadata` callback of
+/// [`BuildContext::cached_layer_with_metadata_migration`]. See [`MetadataVersioned`] for details.
+#[must_use]
+pub fn migrate_versioned_metadata<M: MetadataVersioned>(metadata: &GenericMetadata) -> Option<M> {
+    M::parse_or_migrate(metadata)
+}
+
+fn parse_metadata<M: DeserializeOwned>(metadata: &GenericMetadata) -> Option<M> {
+    toml::Value::Table(metadata.clone().unwrap_or_default())
+        .try_into()
+        .ok()
+}
+
 /// The action to take when a previously cached layer was restored.
 #[derive(Copy, Clone, Debug)]
 pub enum RestoredLayerAction {
@@ -63,6 +184,67 @@ pub enum RestoredLayerAction {
     KeepLayer,
 }
 
+/// Layer metadata that can be compared with the metadata of a restored layer, to decide whether
+/// the restored layer can be kept.
+///
+/// Usually derived with `#[derive(LayerMetadata)]`, which keeps the restored layer if all fields
+/// are equal and deletes it otherwise. The comparison can be narrowed down with field attributes:
+/// - `#[layer_metadata(invalidate_on_change)]`: If any fields have this attribute, only changes to
+///   these fields invalidate the layer.
+/// - `#[layer_metadata(ignore)]`: Changes to this field never invalidate the layer.
+///
+/// All compared fields must implement [`PartialEq`]. The derived implementation refers to the
+/// trait as `::libcnb::layer::LayerMetadata`, so the `libcnb` crate can't be renamed.
+///
+/// # Example
+/// ```
+/// use libcnb::layer::{LayerMetadata, RestoredLayerAction};
+///
+/// #[derive(LayerMetadata)]
+/// struct RuntimeLayerMetadata {
+///     #[layer_metadata(invalidate_on_change)]
+///     version: String,
+///     #[layer_metadata(invalidate_on_change)]
+///     arch: String,
+///     installed_at: String,
+/// }
+///
+/// let restored = RuntimeLayerMetadata {
+///     version: String::from("1.0.0"),
+///     arch: String::from("amd64"),
+///     installed_at: String::from("2024-12-10"),
+/// };
+///
+/// let current = RuntimeLayerMetadata {
+///     version: String::from("1.0.0"),
+///     arch: String::from("amd64"),
+///     installed_at: String::from("2025-01-01"),
+/// };
+///
+/// assert!(matches!(
+///     restored.eq_or_invalidate(&current),
+///     RestoredLayerAction::KeepLayer
+/// ));
+///
+/// let updated = RuntimeLayerMetadata {
+///     version: String::from("1.1.0"),
+///     arch: String::from("amd64"),
+///     installed_at: String::from("2025-01-01"),
+/// };
+///
+/// assert!(matches!(
+///     restored.eq_or_invalidate(&updated),
+///     RestoredLayerAction::DeleteLayer
+/// ));
+/// ```
+///
+/// In a [`CachedLayerDefinition`], use it as the `restored_layer_action`, i.e.
+/// `&|restored_metadata: &RuntimeLayerMetadata, _| restored_metadata.eq_or_invalidate(&metadata)`.
+pub trait LayerMetadata {
+    /// Compares this metadata of a restored layer with the given metadata of the current build.
+    fn eq_or_invalidate(&self, other: &Self) -> RestoredLayerAction;
+}
+
 /// Framework metadata about the layer state.
 ///
 /// See: [`BuildContext::cached_layer`] and [`BuildContext::uncached_layer`]
@@ -153,6 +335,8 @@ where
     // actual layer directory. To make LayerRef nice to use, we bite the bullet and include
     // the layers_dir here.
     layers_dir: PathBuf,
+    // Shared with the BuildContext, to allow attaching build and launch SBOMs from layer code.
+    collected_sboms: Arc<Mutex<CollectedSboms>>,
     buildpack: PhantomData<B>,
     pub state: LayerState<MAC, RAC>,
 }
@@ -166,33 +350,53 @@ where
         self.layers_dir.join(self.name.as_str())
     }
 
-    /// Writes the given layer metadata to disk.
+    /// Writes the given layer metadata to disk and returns it.
     ///
     /// Any existing layer metadata will be overwritten. The new value does not have to be of the
     /// same type as the existing metadata.
-    pub fn write_metadata<M>(&self, metadata: M) -> crate::Result<(), B::Error>
+    pub fn write_metadata<M>(&self, metadata: M) -> crate::Result<M, B::Error>
     where
         M: Serialize,
     {
-        crate::layer::shared::replace_layer_metadata(&self.layers_dir, &self.name, metadata)
-            .map_err(|error| {
-                crate::Error::LayerError(LayerError::WriteLayerError(
-                    WriteLayerError::WriteLayerMetadataError(error),
-                ))
-            })
+        with_retries(B::LAYER_IO_RETRY_POLICY, || {
+            crate::layer::shared::replace_layer_metadata(&self.layers_dir, &self.name, &metadata)
+        })
+        .map_err(|error| {
+            crate::Error::LayerError(LayerError::WriteLayerError(
+                WriteLayerError::WriteLayerMetadataError(error),
+            ))
+        })?;
+
+        Ok(metadata)
+    }
+
+    /// Reads the current layer metadata from disk.
+    ///
+    /// Unlike the metadata passed to the `restored_layer_action` of a [`CachedLayerDefinition`],
+    /// this reflects changes made with [`write_metadata`](Self::write_metadata). The metadata is
+    /// not migrated, and parsing it fails if it's not of the given type.
+    pub fn read_metadata<M>(&self) -> crate::Result<M, B::Error>
+    where
+        M: DeserializeOwned,
+    {
+        with_retries(B::LAYER_IO_RETRY_POLICY, || {
+            read_layer::<M, _>(&self.layers_dir, &self.name)
+        })
+        .map_err(|error| crate::Error::LayerError(LayerError::ReadLayerError(error)))?
+        .map(|layer| layer.metadata.metadata)
+        .ok_or(crate::Error::LayerError(LayerError::UnexpectedMissingLayer))
     }
 
     /// Writes the given layer environment to disk.
     ///
     /// Any existing layer environment will be overwritten.
     pub fn write_env(&self, env: impl Borrow<LayerEnv>) -> crate::Result<(), B::Error> {
-        env.borrow()
-            .write_to_layer_dir(self.path())
-            .map_err(|error| {
-                crate::Error::LayerError(LayerError::WriteLayerError(WriteLayerError::IoError(
-                    error,
-                )))
-            })
+        with_retries(B::LAYER_IO_RETRY_POLICY, || {
+            env.borrow().write_to_layer_dir(self.path())
+        })
+        .map_err(|error| {
+            crate::Error::LayerError(LayerError::WriteLayerError(WriteLayerError::IoError(error)))
+        })
     }
 
     /// Reads the current layer environment from disk.
@@ -200,22 +404,123 @@ where
     /// Note that this includes implicit entries such as adding `bin/` to `PATH`. See [`LayerEnv`]
     /// docs for details on implicit entries.
     pub fn read_env(&self) -> crate::Result<LayerEnv, B::Error> {
-        LayerEnv::read_from_layer_dir(self.path()).map_err(|error| {
+        with_retries(B::LAYER_IO_RETRY_POLICY, || {
+            LayerEnv::read_from_layer_dir(self.path())
+        })
+        .map_err(|error| {
             crate::Error::LayerError(LayerError::ReadLayerError(ReadLayerError::IoError(error)))
         })
     }
 
+    /// Modifies the layer environment on disk with the given function.
+    ///
+    /// The function is passed the current layer environment, and the layer environment it returns
+    /// replaces it. In contrast to [`write_env`](Self::write_env), this allows adding to the layer
+    /// environment from multiple places in the buildpack without tracking its entire state. The
+    /// implicit entries described in the [`LayerEnv`] docs are not written to disk.
+    ///
+    /// # Example
+    /// ```
+    /// # use libcnb::layer::LayerRef;
+    /// # use libcnb::layer_env::{ModificationBehavior, Scope};
+    /// # use libcnb::Buildpack;
+    /// fn add_java_home<B: Buildpack>(layer_ref: &LayerRef<B, (), ()>) -> libcnb::Result<(), B::Error> {
+    ///     layer_ref.modify_env(|layer_env| {
+    ///         layer_env.chainable_insert(
+    ///             Scope::All,
+    ///             ModificationBehavior::Override,
+    ///             "JAVA_HOME",
+    ///             layer_ref.path(),
+    ///         )
+    ///     })
+    /// }
+    /// ```
+    pub fn modify_env(&self, f: impl FnOnce(LayerEnv) -> LayerEnv) -> crate::Result<(), B::Error> {
+        let layer_env = with_retries(B::LAYER_IO_RETRY_POLICY, || {
+            LayerEnv::read_from_layer_dir(self.path())
+        })
+        .map_err(|error| {
+            crate::Error::LayerError(LayerError::ReadLayerError(ReadLayerError::IoError(error)))
+        })?;
+
+        self.write_env(f(layer_env))
+    }
+
     /// Writes the given SBOMs to disk.
     ///
-    /// Any existing SBOMs will be overwritten.
+    /// Any existing SBOMs will be overwritten. With the `sbom-validate` feature, the SBOMs are
+    /// validated with `Sbom::validate` first.
     pub fn write_sboms(&self, sboms: &[Sbom]) -> crate::Result<(), B::Error> {
-        replace_layer_sboms(&self.layers_dir, &self.name, sboms).map_err(|error| {
+        #[cfg(feature = "sbom-validate")]
+        for sbom in sboms {
+            sbom.validate().map_err(crate::Error::InvalidSbom)?;
+        }
+
+        with_retries(B::LAYER_IO_RETRY_POLICY, || {
+            replace_layer_sboms(&self.layers_dir, &self.name, sboms)
+        })
+        .map_err(|error| {
             crate::Error::LayerError(LayerError::WriteLayerError(
                 WriteLayerError::ReplaceLayerSbomsError(error),
             ))
         })
     }
 
+    /// Adds the given SBOM to the launch SBOMs of the build.
+    ///
+    /// In contrast to [`write_sboms`](Self::write_sboms), which writes SBOMs for this layer only,
+    /// this attaches an SBOM to the launch image as a whole, the same way as
+    /// [`BuildResultBuilder::launch_sbom`](crate::build::BuildResultBuilder::launch_sbom) does.
+    /// This allows keeping SBOM handling next to the code that installs the artifacts.
+    ///
+    /// The SBOM isn't written immediately, but queued and written once the build finished
+    /// successfully. SBOMs of the same format, from multiple layers or the
+    /// [`BuildResult`](crate::build::BuildResult), are combined with [`Sbom::merge`].
+    pub fn add_launch_sbom(&self, sbom: Sbom) {
+        lock(&self.collected_sboms).launch.push(sbom);
+    }
+
+    /// Adds the given SBOM to the build SBOMs of the build.
+    ///
+    /// See [`add_launch_sbom`](Self::add_launch_sbom) for details, this is the equivalent of
+    /// [`BuildResultBuilder::build_sbom`](crate::build::BuildResultBuilder::build_sbom).
+    pub fn add_build_sbom(&self, sbom: Sbom) {
+        lock(&self.collected_sboms).build.push(sbom);
+    }
+
+    /// Creates a symlink inside the layer that points to another path inside the layer.
+    ///
+    /// The path of the symlink is relative to the layer directory, the target is relative to the
+    /// directory that contains the symlink. Symlinks with absolute targets or targets outside of
+    /// the layer break when the layer is exported or restored, so they are rejected, as are
+    /// targets that don't exist. Missing parent directories of the symlink are created and an
+    /// existing symlink at the same path is replaced.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use libcnb::layer::LayerRef;
+    /// # use libcnb::Buildpack;
+    /// # fn example<B: Buildpack>(layer_ref: &LayerRef<B, (), ()>) -> libcnb::Result<(), B::Error> {
+    /// // Creates `bin/node` pointing to `<layer>/node-v22.0.0/bin/node`.
+    /// layer_ref.create_symlink("bin/node", "../node-v22.0.0/bin/node")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn create_symlink(
+        &self,
+        link: impl AsRef<Path>,
+        target: impl AsRef<Path>,
+    ) -> crate::Result<(), B::Error> {
+        with_retries(B::LAYER_IO_RETRY_POLICY, || {
+            create_layer_symlink(&self.path(), link.as_ref(), target.as_ref())
+        })
+        .map_err(|error| {
+            crate::Error::LayerError(LayerError::WriteLayerError(
+                WriteLayerError::CreateLayerSymlinkError(error),
+            ))
+        })
+    }
+
     /// Writes the given exec.d programs to disk.
     ///
     /// Any existing exec.d programs will be overwritten.
@@ -229,7 +534,10 @@ where
             .map(|(k, v)| (k.into(), v))
             .collect::<HashMap<_, _>>();
 
-        replace_layer_exec_d_programs(&self.layers_dir, &self.name, &programs).map_err(|error| {
+        with_retries(B::LAYER_IO_RETRY_POLICY, || {
+            replace_layer_exec_d_programs(&self.layers_dir, &self.name, &programs)
+        })
+        .map_err(|error| {
             crate::Error::LayerError(LayerError::WriteLayerError(
                 WriteLayerError::ReplaceLayerExecdProgramsError(error),
             ))
@@ -0,0 +1,167 @@
+use libcnb_data::layer::LayerName;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Files with modification times further in the future than this are considered skewed. Small
+/// differences are common between hosts and don't affect incremental tools in practice.
+pub(crate) const TIMESTAMP_SKEW_TOLERANCE: Duration = Duration::from_secs(10 * 60);
+
+/// Files of a restored layer with modification times in the future.
+///
+/// Usually caused by a clock skew between the host that built and cached the layer and the
+/// current one. Incremental build tools that compare modification times (i.e. `make` or compilers
+/// with build caches) might then consider outdated files to be up-to-date or rebuild everything.
+///
+/// Layers are only checked on request, see
+/// [`BuildContext::restored_layer_timestamp_skew`](crate::build::BuildContext::restored_layer_timestamp_skew).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TimestampSkew {
+    /// The number of files and directories with modification times in the future.
+    pub affected_paths: usize,
+    /// The path with the modification time furthest in the future, relative to the layer.
+    pub most_skewed_path: PathBuf,
+    /// How far the modification time of `most_skewed_path` is in the future.
+    pub max_skew: Duration,
+}
+
+/// Checks the restored layer with the given name for modification times that are more than
+/// `tolerance` after `now`. Returns `None` for layers that weren't restored by the CNB lifecycle.
+///
+/// Errors while reading the layer are ignored, since they will surface when the layer is used.
+pub(crate) fn detect_timestamp_skew(
+    layers_dir: &Path,
+    layer_name: &LayerName,
+    now: SystemTime,
+    tolerance: Duration,
+) -> Option<TimestampSkew> {
+    // Only layers with a metadata file were restored by the CNB lifecycle.
+    if !layers_dir.join(format!("{layer_name}.toml")).is_file() {
+        return None;
+    }
+
+    let layer_path = layers_dir.join(layer_name.as_str());
+    let threshold = now + tolerance;
+    let mut skew: Option<TimestampSkew> = None;
+
+    walk_modification_times(&layer_path, &mut |path, modified| {
+        if let Ok(path_skew) = modified.duration_since(threshold) {
+            let path_skew = path_skew + tolerance;
+            let skew = skew.get_or_insert_with(|| TimestampSkew {
+                affected_paths: 0,
+                most_skewed_path: PathBuf::new(),
+                max_skew: Duration::ZERO,
+            });
+
+            skew.affected_paths += 1;
+            if path_skew > skew.max_skew {
+                skew.max_skew = path_skew;
+                skew.most_skewed_path =
+                    PathBuf::from(path.strip_prefix(&layer_path).unwrap_or(path));
+            }
+        }
+    });
+
+    skew
+}
+
+/// Calls the given function with the modification time of every file and directory in the given
+/// directory, recursively. Symlinks are not followed.
+fn walk_modification_times(dir: &Path, f: &mut dyn FnMut(&Path, SystemTime)) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+
+        if let Ok(modified) = metadata.modified() {
+            f(&path, modified);
+        }
+
+        if metadata.is_dir() {
+            walk_modification_times(&path, f);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libcnb_data::layer_name;
+    use tempfile::tempdir;
+
+    #[test]
+    fn detect_future_modification_times() {
+        let temp_dir = tempdir().unwrap();
+        let layers_dir = temp_dir.path();
+        let now = SystemTime::now();
+
+        for layer in ["skewed", "fine", "not-restored"] {
+            fs::create_dir_all(layers_dir.join(layer).join("lib")).unwrap();
+            fs::write(layers_dir.join(layer).join("lib/a.o"), "").unwrap();
+            fs::write(layers_dir.join(layer).join("lib/b.o"), "").unwrap();
+        }
+        fs::write(layers_dir.join("skewed.toml"), "").unwrap();
+        fs::write(layers_dir.join("fine.toml"), "").unwrap();
+
+        let set_modified = |path: PathBuf, modified: SystemTime| {
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        };
+
+        set_modified(
+            layers_dir.join("skewed/lib/a.o"),
+            now + Duration::from_secs(3600),
+        );
+        set_modified(
+            layers_dir.join("skewed/lib/b.o"),
+            now + Duration::from_secs(7200),
+        );
+        set_modified(
+            layers_dir.join("fine/lib/a.o"),
+            now + Duration::from_secs(60),
+        );
+        set_modified(
+            layers_dir.join("not-restored/lib/a.o"),
+            now + Duration::from_secs(7200),
+        );
+
+        let detect = |layer_name: LayerName| {
+            detect_timestamp_skew(layers_dir, &layer_name, now, TIMESTAMP_SKEW_TOLERANCE)
+        };
+
+        assert_eq!(
+            detect(layer_name!("skewed")),
+            Some(TimestampSkew {
+                affected_paths: 2,
+                most_skewed_path: PathBuf::from("lib/b.o"),
+                max_skew: Duration::from_secs(7200),
+            })
+        );
+        assert_eq!(detect(layer_name!("fine")), None);
+        assert_eq!(detect(layer_name!("not-restored")), None);
+    }
+
+    #[test]
+    fn detect_in_missing_layers_dir() {
+        let temp_dir = tempdir().unwrap();
+
+        assert_eq!(
+            detect_timestamp_skew(
+                &temp_dir.path().join("missing"),
+                &layer_name!("layer"),
+                SystemTime::now(),
+                TIMESTAMP_SKEW_TOLERANCE
+            ),
+            None
+        );
+    }
+}
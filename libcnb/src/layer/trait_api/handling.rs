@@ -7,8 +7,8 @@ use crate::data::layer::LayerName;
 use crate::data::layer_content_metadata::LayerContentMetadata;
 use crate::generic::GenericMetadata;
 use crate::layer::shared::{
-    delete_layer, replace_layer_exec_d_programs, replace_layer_sboms, ReadLayerError,
-    WriteLayerError,
+    delete_layer, replace_layer_exec_d_programs, replace_layer_sboms,
+    salvage_layer_content_metadata, ReadLayerError, WriteLayerError,
 };
 use crate::layer::{ExistingLayerStrategy, LayerData, LayerError, MetadataMigration};
 use crate::layer_env::LayerEnv;
@@ -25,6 +25,10 @@ pub(crate) fn handle_layer<B: Buildpack + ?Sized, L: Layer<Buildpack = B>>(
     layer_name: LayerName,
     mut layer: L,
 ) -> Result<LayerData<L::Metadata>, LayerErrorOrBuildpackError<B::Error>> {
+    context
+        .register_layer(&layer_name, layer.types())
+        .map_err(LayerErrorOrBuildpackError::LayerError)?;
+
     match read_layer(&context.layers_dir, &layer_name) {
         Ok(None) => handle_create_layer(context, &layer_name, &mut layer),
         Ok(Some(layer_data)) => {
@@ -78,6 +82,12 @@ pub(crate) fn handle_layer<B: Buildpack + ?Sized, L: Layer<Buildpack = B>>(
             }
         }
         Err(ReadLayerError::LayerContentMetadataParseError(_)) => {
+            if B::LENIENT_LAYER_METADATA_PARSING {
+                salvage_layer_content_metadata(&context.layers_dir, &layer_name)
+                    .map_err(WriteLayerError::WriteLayerMetadataError)
+                    .map_err(|error| LayerErrorOrBuildpackError::LayerError(error.into()))?;
+            }
+
             match read_layer::<GenericMetadata, _>(&context.layers_dir, &layer_name) {
                 Ok(Some(generic_layer_data)) => {
                     let metadata_migration_strategy = layer
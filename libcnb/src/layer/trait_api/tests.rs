@@ -9,16 +9,18 @@
 //! All tests in this module assume a specific [`Layer`] implementation that is also in this file.
 //! Look for the `TestLayer` type and it's [`Layer`] implementation.
 
-use crate::build::{BuildContext, BuildResult, BuildResultBuilder};
+use crate::build::{BuildContext, BuildContextState, BuildResult, BuildResultBuilder};
 use crate::data::buildpack_id;
 use crate::data::layer_content_metadata::LayerTypes;
 use crate::detect::{DetectContext, DetectResult, DetectResultBuilder};
 use crate::generic::{GenericMetadata, GenericPlatform};
 use crate::layer::trait_api::handling::handle_layer;
 use crate::layer::{
-    ExistingLayerStrategy, Layer, LayerData, LayerResult, LayerResultBuilder, MetadataMigration,
+    ExistingLayerStrategy, Layer, LayerData, LayerError, LayerResult, LayerResultBuilder,
+    MetadataMigration, UncachedLayerDefinition,
 };
 use crate::layer_env::{LayerEnv, ModificationBehavior, Scope};
+use crate::log::{LogLevel, Logger};
 use crate::{read_toml_file, Buildpack, Env, Target, LIBCNB_SUPPORTED_BUILDPACK_API};
 use libcnb_data::buildpack::{BuildpackTarget, BuildpackVersion, ComponentBuildpackDescriptor};
 use libcnb_data::buildpack_plan::BuildpackPlan;
@@ -730,6 +732,46 @@ fn write_layer_env() {
     assert_eq!(layer_env_from_disk, layer_env);
 }
 
+#[test]
+fn conflicting_layer_definitions() {
+    let temp_dir = tempdir().unwrap();
+    let context = build_context(&temp_dir);
+    let layer_name = layer_name!("my-layer");
+
+    let test_layer = || TestLayer {
+        existing_layer_strategy: ExistingLayerStrategy::Keep,
+        write_version: String::from("1.0.0"),
+        write_layer_env: None,
+    };
+
+    handle_layer(&context, layer_name.clone(), test_layer()).unwrap();
+    handle_layer(&context, layer_name.clone(), test_layer()).unwrap();
+
+    let uncached_layer_result = context.uncached_layer(
+        &layer_name,
+        UncachedLayerDefinition {
+            build: TEST_LAYER_BUILD,
+            launch: TEST_LAYER_LAUNCH,
+        },
+    );
+
+    match uncached_layer_result {
+        Err(crate::Error::LayerError(LayerError::ConflictingLayerDefinition {
+            layer_name: conflicting_layer_name,
+            previous,
+            current,
+        })) => {
+            assert_eq!(conflicting_layer_name, layer_name);
+            assert!(previous.cache);
+            assert!(!current.cache);
+        }
+        _ => panic!("Expected ConflictingLayerDefinition error"),
+    }
+
+    assert!(context.temp_layer(layer_name!("scratch")).is_ok());
+    assert!(context.temp_layer(layer_name!("scratch")).is_ok());
+}
+
 #[test]
 fn default_layer_method_implementations() {
     struct SimpleLayer;
@@ -887,6 +929,14 @@ fn layer_env_read_write() {
     // See the Layer implementation for more asserts
 }
 
+#[test]
+fn contexts_are_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<BuildContext<TestBuildpack>>();
+    assert_send_sync::<DetectContext<TestBuildpack>>();
+}
+
 fn build_context(temp_dir: &TempDir) -> BuildContext<TestBuildpack> {
     let layers_dir = temp_dir.path().join("layers");
     let app_dir = temp_dir.path().join("app");
@@ -911,6 +961,8 @@ fn build_context(temp_dir: &TempDir) -> BuildContext<TestBuildpack> {
         buildpack_plan: BuildpackPlan {
             entries: Vec::new(),
         },
+        buildpack_plan_path: temp_dir.path().join("plan.toml"),
+        platform_dir: temp_dir.path().join("platform"),
         buildpack_descriptor: ComponentBuildpackDescriptor {
             api: LIBCNB_SUPPORTED_BUILDPACK_API,
             buildpack: crate::data::buildpack::Buildpack {
@@ -934,6 +986,8 @@ fn build_context(temp_dir: &TempDir) -> BuildContext<TestBuildpack> {
             metadata: GenericMetadata::default(),
         },
         store: None,
+        log: Logger::new(LogLevel::Info),
+        state: BuildContextState::default(),
     }
 }
 
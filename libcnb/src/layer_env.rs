@@ -1,10 +1,13 @@
 //! Type-safe, in-memory, layer environment variables.
 
-use crate::Env;
+use crate::env::PATH_LIST_SEPARATOR;
+use crate::{validate_env_var, Env, EnvVarError};
+use libcnb_data::layer::LayerName;
+use std::borrow::Borrow;
 use std::cmp::Ordering;
 use std::collections::hash_map::Entry;
 use std::collections::{BTreeMap, HashMap};
-use std::ffi::OsString;
+use std::ffi::{OsStr, OsString};
 use std::fs;
 use std::path::Path;
 
@@ -136,24 +139,40 @@ impl LayerEnv {
     /// assert_eq!(modified_env.get("VAR2").unwrap(), "previous-value");
     /// ```
     #[must_use]
+    // Taking the scope by value is part of the public API.
+    #[allow(clippy::needless_pass_by_value)]
     pub fn apply(&self, scope: Scope, env: &Env) -> Env {
-        let deltas = match scope {
+        // Like the lifecycle, apply the implicit layer path entries before the env directories.
+        self.layer_paths_delta(&scope)
+            .into_iter()
+            .chain(self.env_dir_deltas(&scope))
+            .fold(env.clone(), |env, delta| delta.apply(&env))
+    }
+
+    /// The delta for the implicit layer path entries of the given scope, if there are any.
+    fn layer_paths_delta(&self, scope: &Scope) -> Option<&LayerEnvDelta> {
+        match scope {
+            Scope::Build => Some(&self.layer_paths_build),
+            Scope::Launch => Some(&self.layer_paths_launch),
+            Scope::All | Scope::Process(_) => None,
+        }
+    }
+
+    /// The deltas of the env directories for the given scope, in the order they're applied.
+    fn env_dir_deltas(&self, scope: &Scope) -> Vec<&LayerEnvDelta> {
+        match scope {
             Scope::All => vec![&self.all],
-            Scope::Build => vec![&self.all, &self.build, &self.layer_paths_build],
-            Scope::Launch => vec![&self.all, &self.launch, &self.layer_paths_launch],
+            Scope::Build => vec![&self.all, &self.build],
+            Scope::Launch => vec![&self.all, &self.launch],
             Scope::Process(process) => {
                 let mut process_deltas = vec![&self.all];
-                if let Some(process_specific_delta) = self.process.get(&process) {
+                if let Some(process_specific_delta) = self.process.get(process) {
                     process_deltas.push(process_specific_delta);
                 }
 
                 process_deltas
             }
-        };
-
-        deltas
-            .iter()
-            .fold(env.clone(), |env, delta| delta.apply(&env))
+        }
     }
 
     /// Applies this [`LayerEnv`] to an empty [`Env`] for the given [`Scope`].
@@ -185,6 +204,10 @@ impl LayerEnv {
     /// assert_eq!(env.get("VAR2").unwrap(), "bar");
     /// ```
     ///
+    /// The name and value are inserted as-is, without validation. Use
+    /// [`LayerEnv::try_insert`] to reject names and values that would result in corrupt
+    /// environment files.
+    ///
     /// See [`LayerEnv::chainable_insert`] that allows easy creation of inline `LayerEnv` values
     /// without intermediate variables.
     pub fn insert(
@@ -207,6 +230,44 @@ impl LayerEnv {
         target_delta.insert(modification_behavior, name, value);
     }
 
+    /// Validates the given name and value and inserts a new entry into this `LayerEnv`.
+    ///
+    /// Behaves like [`LayerEnv::insert`] for valid names and values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is empty or contains `=` or NUL bytes, or if the value
+    /// contains NUL bytes. See [`validate_env_var`].
+    ///
+    /// # Example:
+    /// ```
+    /// use libcnb::layer_env::{LayerEnv, ModificationBehavior, Scope};
+    /// use libcnb::EnvVarError;
+    ///
+    /// let mut layer_env = LayerEnv::new();
+    /// assert!(layer_env
+    ///     .try_insert(Scope::All, ModificationBehavior::Override, "JAVA_HOME", "/layers/jdk")
+    ///     .is_ok());
+    /// assert_eq!(
+    ///     layer_env.try_insert(Scope::All, ModificationBehavior::Override, "", "/layers/jdk"),
+    ///     Err(EnvVarError::EmptyName)
+    /// );
+    /// ```
+    pub fn try_insert(
+        &mut self,
+        scope: Scope,
+        modification_behavior: ModificationBehavior,
+        name: impl Into<OsString>,
+        value: impl Into<OsString>,
+    ) -> Result<(), EnvVarError> {
+        let name = name.into();
+        let value = value.into();
+        validate_env_var(&name, &value)?;
+
+        self.insert(scope, modification_behavior, name, value);
+        Ok(())
+    }
+
     /// Chainable version of [`LayerEnv::insert`].
     ///
     /// Allows easy creation of inline `LayerEnv` values without intermediate variables. See
@@ -240,6 +301,112 @@ impl LayerEnv {
         self
     }
 
+    /// Merges the entries of another `LayerEnv` into this one.
+    ///
+    /// The result is equivalent to applying this `LayerEnv` and then `other`, as long as each
+    /// environment variable is only modified with one of the behaviors (and an optional delimiter)
+    /// per scope:
+    ///
+    /// - [`Append`](ModificationBehavior::Append) and [`Prepend`](ModificationBehavior::Prepend)
+    ///   values are combined using the delimiter of `other`, or of this `LayerEnv` if `other`
+    ///   doesn't specify one.
+    /// - [`Override`](ModificationBehavior::Override) and
+    ///   [`Delimiter`](ModificationBehavior::Delimiter) values of `other` replace existing values.
+    /// - Existing [`Default`](ModificationBehavior::Default) values are kept.
+    ///
+    /// # Example:
+    /// ```
+    /// use libcnb::layer_env::{LayerEnv, ModificationBehavior, Scope};
+    ///
+    /// let mut layer_env = LayerEnv::new()
+    ///     .chainable_insert(Scope::All, ModificationBehavior::Prepend, "PATH", "/layers/jdk/bin")
+    ///     .chainable_insert(Scope::All, ModificationBehavior::Delimiter, "PATH", ":");
+    ///
+    /// layer_env.merge(
+    ///     &LayerEnv::new()
+    ///         .chainable_insert(Scope::All, ModificationBehavior::Prepend, "PATH", "/layers/maven/bin")
+    ///         .chainable_insert(Scope::Build, ModificationBehavior::Override, "MAVEN_OPTS", "-Xmx1g"),
+    /// );
+    ///
+    /// let env = layer_env.apply_to_empty(Scope::Build);
+    /// assert_eq!(env.get("PATH").unwrap(), "/layers/maven/bin:/layers/jdk/bin");
+    /// assert_eq!(env.get("MAVEN_OPTS").unwrap(), "-Xmx1g");
+    /// ```
+    pub fn merge(&mut self, other: &Self) {
+        self.all.merge(&other.all);
+        self.build.merge(&other.build);
+        self.launch.merge(&other.launch);
+        self.layer_paths_build.merge(&other.layer_paths_build);
+        self.layer_paths_launch.merge(&other.layer_paths_launch);
+
+        for (process_type_name, other_delta) in &other.process {
+            self.process
+                .entry(process_type_name.clone())
+                .or_default()
+                .merge(other_delta);
+        }
+    }
+
+    /// Returns an iterator over the entries of this `LayerEnv`.
+    ///
+    /// Entries are yielded as `(scope, modification behavior, name, value)` tuples, ordered by
+    /// scope ([`All`](Scope::All), [`Build`](Scope::Build), [`Launch`](Scope::Launch), then
+    /// [`Process`](Scope::Process) sorted by process type), modification behavior and name.
+    ///
+    /// The implicit entries for layer paths such as `bin`, that [`read_from_layer_dir`](Self::read_from_layer_dir)
+    /// adds, are not included.
+    ///
+    /// # Example:
+    /// ```
+    /// use libcnb::layer_env::{LayerEnv, ModificationBehavior, Scope};
+    /// use std::ffi::OsStr;
+    ///
+    /// let layer_env = LayerEnv::new()
+    ///     .chainable_insert(Scope::Launch, ModificationBehavior::Default, "PORT", "8080")
+    ///     .chainable_insert(Scope::All, ModificationBehavior::Append, "PATH", "/bin");
+    ///
+    /// let entries = layer_env.iter().collect::<Vec<_>>();
+    ///
+    /// assert_eq!(
+    ///     entries,
+    ///     [
+    ///         (Scope::All, &ModificationBehavior::Append, OsStr::new("PATH"), OsStr::new("/bin")),
+    ///         (Scope::Launch, &ModificationBehavior::Default, OsStr::new("PORT"), OsStr::new("8080")),
+    ///     ]
+    /// );
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (Scope, &ModificationBehavior, &OsStr, &OsStr)> {
+        let mut process_deltas = self.process.iter().collect::<Vec<_>>();
+        process_deltas.sort_by_key(|(process_type_name, _)| *process_type_name);
+
+        [
+            (Scope::All, &self.all),
+            (Scope::Build, &self.build),
+            (Scope::Launch, &self.launch),
+        ]
+        .into_iter()
+        .chain(
+            process_deltas
+                .into_iter()
+                .map(|(process_type_name, delta)| {
+                    (Scope::Process(process_type_name.clone()), delta)
+                }),
+        )
+        .flat_map(|(scope, delta)| {
+            delta
+                .entries
+                .iter()
+                .map(move |((modification_behavior, name), value)| {
+                    (
+                        scope.clone(),
+                        modification_behavior,
+                        name.as_os_str(),
+                        value.as_os_str(),
+                    )
+                })
+        })
+    }
+
     /// Constructs a `LayerEnv` based on the given layer directory.
     ///
     /// Follows the rules described in the Cloud Native Buildpacks specification and adds implicit
@@ -319,7 +486,24 @@ impl LayerEnv {
 
         let env_launch_path = layer_dir.as_ref().join("env.launch");
         if env_launch_path.is_dir() {
-            result_layer_env.launch = LayerEnvDelta::read_from_env_dir(env_launch_path)?;
+            result_layer_env.launch = LayerEnvDelta::read_from_env_dir(&env_launch_path)?;
+
+            // Process-specific environment is stored in subdirectories of `env.launch`, named
+            // after the process type.
+            for dir_entry in fs::read_dir(&env_launch_path)? {
+                let path = dir_entry?.path();
+
+                if !path.is_dir() {
+                    continue;
+                }
+
+                if let Some(process_type_name) = path.file_name().and_then(OsStr::to_str) {
+                    result_layer_env.process.insert(
+                        process_type_name.to_string(),
+                        LayerEnvDelta::read_from_env_dir(&path)?,
+                    );
+                }
+            }
         }
 
         Ok(result_layer_env)
@@ -378,6 +562,77 @@ impl LayerEnv {
     }
 }
 
+/// Applies the [`LayerEnv`]s of multiple layers to the given [`Env`] for the given [`Scope`].
+///
+/// The layers are applied in the same order the CNB lifecycle applies them for subsequent
+/// buildpacks: sorted by layer name, regardless of the order they were created in. Like the
+/// lifecycle, this is done in two passes: first, the implicit layer path entries (i.e. `<layer>/bin`
+/// for `PATH`) of all layers are applied, then the env directories of all layers. Since both
+/// prepend to variables such as `PATH` by default, the entries of layers with names that sort
+/// later take precedence, and entries from env directories take precedence over the implicit layer
+/// path entries of all layers.
+///
+/// This is useful when a buildpack needs to invoke tools from layers it created earlier in the
+/// same build, with the same environment subsequent buildpacks will see. Only pass layers that are
+/// available in the given scope, i.e. layers with `build = true` for [`Scope::Build`].
+///
+/// # Example:
+/// ```
+/// use libcnb::data::layer_name;
+/// use libcnb::layer_env::{apply_layer_envs, LayerEnv, ModificationBehavior, Scope};
+/// use libcnb::Env;
+///
+/// let jdk_layer_env = LayerEnv::new()
+///     .chainable_insert(Scope::All, ModificationBehavior::Prepend, "PATH", "/layers/jdk/bin")
+///     .chainable_insert(Scope::All, ModificationBehavior::Delimiter, "PATH", ":");
+///
+/// let maven_layer_env = LayerEnv::new()
+///     .chainable_insert(Scope::All, ModificationBehavior::Prepend, "PATH", "/layers/maven/bin")
+///     .chainable_insert(Scope::All, ModificationBehavior::Delimiter, "PATH", ":");
+///
+/// let mut env = Env::new();
+/// env.insert("PATH", "/usr/bin");
+///
+/// let env = apply_layer_envs(
+///     [
+///         (layer_name!("maven"), &maven_layer_env),
+///         (layer_name!("jdk"), &jdk_layer_env),
+///     ],
+///     &Scope::Build,
+///     &env,
+/// );
+///
+/// assert_eq!(
+///     env.get("PATH").unwrap(),
+///     "/layers/maven/bin:/layers/jdk/bin:/usr/bin"
+/// );
+/// ```
+pub fn apply_layer_envs<N, E>(
+    layer_envs: impl IntoIterator<Item = (N, E)>,
+    scope: &Scope,
+    env: &Env,
+) -> Env
+where
+    N: Borrow<LayerName>,
+    E: Borrow<LayerEnv>,
+{
+    let mut layer_envs = layer_envs.into_iter().collect::<Vec<_>>();
+
+    // The lifecycle iterates over the layer directories sorted by their file name.
+    layer_envs.sort_by(|(a, _), (b, _)| a.borrow().as_str().cmp(b.borrow().as_str()));
+
+    let layer_paths_deltas = layer_envs
+        .iter()
+        .filter_map(|(_, layer_env)| layer_env.borrow().layer_paths_delta(scope));
+
+    let env = layer_paths_deltas.fold(env.clone(), |env, delta| delta.apply(&env));
+
+    layer_envs
+        .iter()
+        .flat_map(|(_, layer_env)| layer_env.borrow().env_dir_deltas(scope))
+        .fold(env, |env, delta| delta.apply(&env))
+}
+
 /// Environment variable modification behavior.
 /// ([CNB spec: Environment Variable Modification Rules](https://github.com/buildpacks/spec/blob/main/buildpack.md#environment-variable-modification-rules))
 #[derive(Eq, PartialEq, Debug, Clone)]
@@ -500,6 +755,11 @@ impl LayerEnvDelta {
             // See: https://github.com/buildpacks/lifecycle/blob/a7428a55c2a14d8a37e84285b95dc63192e3264e/env/env.go#L73-L106
             let path = dir_entry?.path();
 
+            // Subdirectories contain process-specific environment and are read separately.
+            if path.is_dir() {
+                continue;
+            }
+
             #[cfg(target_family = "unix")]
             let file_contents = {
                 use std::os::unix::ffi::OsStringExt;
@@ -592,23 +852,66 @@ impl LayerEnvDelta {
 
         self
     }
-}
 
-#[cfg(target_family = "unix")]
-const PATH_LIST_SEPARATOR: &str = ":";
+    fn merge(&mut self, other: &Self) {
+        for ((modification_behavior, name), value) in &other.entries {
+            let merged_value = match modification_behavior {
+                ModificationBehavior::Append | ModificationBehavior::Prepend => {
+                    match self
+                        .entries
+                        .get(&(modification_behavior.clone(), name.clone()))
+                    {
+                        Some(existing_value) => {
+                            let delimiter = other
+                                .entries
+                                .get(&(ModificationBehavior::Delimiter, name.clone()))
+                                .cloned()
+                                .unwrap_or_else(|| self.delimiter_for(name));
+
+                            let (first, second) =
+                                if modification_behavior == &ModificationBehavior::Append {
+                                    (existing_value, value)
+                                } else {
+                                    (value, existing_value)
+                                };
+
+                            let mut merged_value = first.clone();
+                            merged_value.push(delimiter);
+                            merged_value.push(second);
+                            merged_value
+                        }
+                        None => value.clone(),
+                    }
+                }
+                ModificationBehavior::Default => {
+                    if self
+                        .entries
+                        .contains_key(&(ModificationBehavior::Default, name.clone()))
+                    {
+                        continue;
+                    }
+                    value.clone()
+                }
+                ModificationBehavior::Delimiter | ModificationBehavior::Override => value.clone(),
+            };
 
-#[cfg(target_family = "windows")]
-const PATH_LIST_SEPARATOR: &str = ";";
+            self.insert(modification_behavior.clone(), name.clone(), merged_value);
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use std::cmp::Ordering;
     use std::collections::HashMap;
+    use std::ffi::OsString;
     use std::fs;
 
     use tempfile::tempdir;
 
-    use crate::layer_env::{Env, LayerEnv, ModificationBehavior, Scope};
+    use crate::layer_env::{apply_layer_envs, Env, LayerEnv, ModificationBehavior, Scope};
+    use crate::EnvVarError;
+    use libcnb_data::layer::LayerName;
 
     use super::LayerEnvDelta;
 
@@ -921,6 +1224,283 @@ mod tests {
         );
     }
 
+    #[test]
+    fn read_from_layer_dir_process_env() {
+        let temp_dir = tempdir().unwrap();
+        let layer_dir = temp_dir.path();
+
+        let layer_env = LayerEnv::new()
+            .chainable_insert(Scope::Launch, ModificationBehavior::Override, "FOO", "bar")
+            .chainable_insert(
+                Scope::Process(String::from("web")),
+                ModificationBehavior::Override,
+                "PORT",
+                "5000",
+            );
+
+        layer_env.write_to_layer_dir(layer_dir).unwrap();
+
+        assert_eq!(LayerEnv::read_from_layer_dir(layer_dir).unwrap(), layer_env);
+    }
+
+    #[test]
+    fn layer_paths_are_applied_before_env_dirs() {
+        let temp_dir = tempdir().unwrap();
+        let layer_dir = temp_dir.path();
+        fs::create_dir_all(layer_dir.join("bin")).unwrap();
+        fs::create_dir_all(layer_dir.join("env")).unwrap();
+        fs::write(layer_dir.join("env").join("PATH.prepend"), "custom").unwrap();
+        fs::write(layer_dir.join("env").join("PATH.delim"), ":").unwrap();
+
+        let mut original_env = Env::new();
+        original_env.insert("PATH", "some");
+
+        let layer_env = LayerEnv::read_from_layer_dir(layer_dir).unwrap();
+
+        for scope in [Scope::Build, Scope::Launch] {
+            assert_eq!(
+                layer_env
+                    .apply(scope, &original_env)
+                    .get_string_lossy("PATH"),
+                Some(format!(
+                    "custom:{}:some",
+                    layer_dir.join("bin").to_str().unwrap()
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn apply_layer_envs_in_layer_name_order() {
+        let temp_dir = tempdir().unwrap();
+        let layers_dir = temp_dir.path();
+
+        let layer_envs = ["python", "pip", "venv"].map(|layer_name| {
+            let bin_dir = layers_dir.join(layer_name).join("bin");
+            fs::create_dir_all(&bin_dir).unwrap();
+            (
+                layer_name.parse::<LayerName>().unwrap(),
+                LayerEnv::read_from_layer_dir(layers_dir.join(layer_name)).unwrap(),
+            )
+        });
+
+        let mut original_env = Env::new();
+        original_env.insert("PATH", "/usr/bin");
+        original_env.insert("VAR", "original");
+
+        let mut venv_layer_env = layer_envs[2].1.clone();
+        venv_layer_env.insert(Scope::Build, ModificationBehavior::Override, "VAR", "venv");
+        let pip_layer_env = layer_envs[1].1.clone().chainable_insert(
+            Scope::Build,
+            ModificationBehavior::Override,
+            "VAR",
+            "pip",
+        );
+
+        let env = apply_layer_envs(
+            [
+                (&layer_envs[2].0, &venv_layer_env),
+                (&layer_envs[0].0, &layer_envs[0].1),
+                (&layer_envs[1].0, &pip_layer_env),
+            ],
+            &Scope::Build,
+            &original_env,
+        );
+
+        assert_eq!(
+            env.get_string_lossy("PATH"),
+            Some(format!(
+                "{}:{}:{}:/usr/bin",
+                layers_dir.join("venv/bin").to_str().unwrap(),
+                layers_dir.join("python/bin").to_str().unwrap(),
+                layers_dir.join("pip/bin").to_str().unwrap()
+            ))
+        );
+        assert_eq!(env.get("VAR").unwrap(), "venv");
+
+        let env = apply_layer_envs(
+            Vec::<(LayerName, LayerEnv)>::new(),
+            &Scope::Launch,
+            &original_env,
+        );
+        assert_eq!(env, original_env);
+    }
+
+    #[test]
+    fn apply_layer_envs_applies_layer_paths_before_env_dirs() {
+        let temp_dir = tempdir().unwrap();
+        let layers_dir = temp_dir.path();
+
+        let layer_envs = ["a", "b"].map(|layer_name| {
+            let dir = layers_dir.join(layer_name);
+            fs::create_dir_all(dir.join("bin")).unwrap();
+            fs::create_dir_all(dir.join("env")).unwrap();
+            fs::write(dir.join("env/PATH.prepend"), format!("{layer_name}prep")).unwrap();
+            fs::write(dir.join("env/PATH.delim"), ":").unwrap();
+
+            (
+                layer_name.parse::<LayerName>().unwrap(),
+                LayerEnv::read_from_layer_dir(&dir).unwrap(),
+            )
+        });
+
+        let mut original_env = Env::new();
+        original_env.insert("PATH", "/usr/bin");
+
+        for scope in [Scope::Build, Scope::Launch] {
+            assert_eq!(
+                apply_layer_envs(layer_envs.iter().rev().cloned(), &scope, &original_env)
+                    .get_string_lossy("PATH"),
+                Some(format!(
+                    "bprep:aprep:{}:{}:/usr/bin",
+                    layers_dir.join("b/bin").to_str().unwrap(),
+                    layers_dir.join("a/bin").to_str().unwrap()
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn merge_matches_sequential_application() {
+        let first = LayerEnv::new()
+            .chainable_insert(
+                Scope::All,
+                ModificationBehavior::Prepend,
+                "PATH",
+                "/first/bin",
+            )
+            .chainable_insert(Scope::All, ModificationBehavior::Delimiter, "PATH", ":")
+            .chainable_insert(Scope::All, ModificationBehavior::Append, "FLAGS", "-a")
+            .chainable_insert(Scope::All, ModificationBehavior::Delimiter, "FLAGS", " ")
+            .chainable_insert(Scope::Build, ModificationBehavior::Default, "MODE", "first")
+            .chainable_insert(Scope::Launch, ModificationBehavior::Override, "PORT", "80");
+
+        let second = LayerEnv::new()
+            .chainable_insert(
+                Scope::All,
+                ModificationBehavior::Prepend,
+                "PATH",
+                "/second/bin",
+            )
+            .chainable_insert(Scope::All, ModificationBehavior::Delimiter, "PATH", ":")
+            .chainable_insert(Scope::All, ModificationBehavior::Append, "FLAGS", "-b")
+            .chainable_insert(Scope::All, ModificationBehavior::Delimiter, "FLAGS", " ")
+            .chainable_insert(
+                Scope::Build,
+                ModificationBehavior::Default,
+                "MODE",
+                "second",
+            )
+            .chainable_insert(
+                Scope::Launch,
+                ModificationBehavior::Override,
+                "PORT",
+                "8080",
+            )
+            .chainable_insert(
+                Scope::Process(String::from("web")),
+                ModificationBehavior::Override,
+                "WEB",
+                "true",
+            );
+
+        let mut merged = first.clone();
+        merged.merge(&second);
+
+        let mut original_env = Env::new();
+        original_env.insert("PATH", "/usr/bin");
+        original_env.insert("FLAGS", "-o");
+
+        for scope in [
+            Scope::All,
+            Scope::Build,
+            Scope::Launch,
+            Scope::Process(String::from("web")),
+        ] {
+            assert_eq!(
+                merged.apply(scope.clone(), &original_env),
+                second.apply(scope.clone(), &first.apply(scope, &original_env))
+            );
+        }
+    }
+
+    #[test]
+    fn iter_yields_entries_in_scope_order() {
+        let layer_env = LayerEnv::new()
+            .chainable_insert(
+                Scope::Process(String::from("worker")),
+                ModificationBehavior::Default,
+                "QUEUE",
+                "default",
+            )
+            .chainable_insert(
+                Scope::Process(String::from("web")),
+                ModificationBehavior::Override,
+                "WEB",
+                "true",
+            )
+            .chainable_insert(Scope::Build, ModificationBehavior::Prepend, "PATH", "/bin")
+            .chainable_insert(Scope::All, ModificationBehavior::Override, "B", "b")
+            .chainable_insert(Scope::All, ModificationBehavior::Append, "A", "a");
+
+        let entries = layer_env
+            .iter()
+            .map(|(scope, modification_behavior, name, value)| {
+                (
+                    scope,
+                    modification_behavior.clone(),
+                    name.to_str().unwrap(),
+                    value.to_str().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            entries,
+            [
+                (Scope::All, ModificationBehavior::Append, "A", "a"),
+                (Scope::All, ModificationBehavior::Override, "B", "b"),
+                (Scope::Build, ModificationBehavior::Prepend, "PATH", "/bin"),
+                (
+                    Scope::Process(String::from("web")),
+                    ModificationBehavior::Override,
+                    "WEB",
+                    "true"
+                ),
+                (
+                    Scope::Process(String::from("worker")),
+                    ModificationBehavior::Default,
+                    "QUEUE",
+                    "default"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn try_insert_rejects_invalid_entries() {
+        let mut layer_env = LayerEnv::new();
+
+        layer_env
+            .try_insert(
+                Scope::Process(String::from("web")),
+                ModificationBehavior::Override,
+                "PORT",
+                "8080",
+            )
+            .unwrap();
+        assert_eq!(
+            layer_env.try_insert(Scope::All, ModificationBehavior::Append, "PATH=", "/bin"),
+            Err(EnvVarError::NameContainsEquals(OsString::from("PATH=")))
+        );
+        assert_eq!(
+            layer_env.try_insert(Scope::Build, ModificationBehavior::Default, "FOO", "\0"),
+            Err(EnvVarError::ValueContainsNul(OsString::from("FOO")))
+        );
+
+        assert_eq!(layer_env.iter().count(), 1);
+    }
+
     fn environment_as_sorted_vector(environment: &Env) -> Vec<(&str, &str)> {
         let mut result: Vec<(&str, &str)> = environment
             .iter()
@@ -1,43 +1,109 @@
-#![doc = include_str!("../README.md")]
+#![cfg_attr(feature = "buildpack", doc = include_str!("../README.md"))]
 
+#[cfg(feature = "buildpack")]
 pub mod build;
+#[cfg(feature = "buildpack")]
 pub mod detect;
 pub mod exec_d;
+#[cfg(feature = "buildpack")]
+pub mod extension;
+#[cfg(feature = "buildpack")]
 pub mod generic;
+#[cfg(feature = "buildpack")]
 pub mod layer;
+#[cfg(feature = "buildpack")]
 pub mod layer_env;
+#[cfg(feature = "buildpack")]
+pub mod log;
+#[cfg(feature = "buildpack")]
 pub mod sbom;
 
 // Internals that need to be public for macros
+#[cfg(feature = "buildpack")]
 #[doc(hidden)]
 pub mod internals;
 
+#[cfg(feature = "async")]
+mod async_buildpack;
+#[cfg(feature = "buildpack")]
 mod buildpack;
+#[cfg(feature = "buildpack")]
+mod context;
+#[cfg(feature = "buildpack")]
+mod descriptor_warnings;
 mod env;
+#[cfg(feature = "buildpack")]
 mod error;
+#[cfg(feature = "buildpack")]
+mod error_collector;
+#[cfg(feature = "buildpack")]
+mod error_report;
+#[cfg(feature = "buildpack")]
 mod exit_code;
+#[cfg(feature = "buildpack")]
+mod output_capture;
+mod phase;
+#[cfg(feature = "buildpack")]
 mod platform;
+#[cfg(feature = "buildpack")]
+mod platform_env;
+#[cfg(feature = "buildpack")]
 mod runtime;
+#[cfg(feature = "buildpack")]
 mod target;
+#[cfg(feature = "buildpack")]
+mod tracer;
 #[cfg(feature = "trace")]
 mod tracing;
+#[cfg(feature = "buildpack")]
 mod util;
 
+#[cfg(feature = "async")]
+pub use async_buildpack::AsyncBuildpack;
+#[cfg(feature = "buildpack")]
 pub use buildpack::Buildpack;
+#[cfg(feature = "buildpack")]
+pub use context::CommonContext;
 pub use env::*;
+#[cfg(feature = "buildpack")]
 pub use error::*;
+#[cfg(feature = "buildpack")]
+pub use error_collector::*;
+#[cfg(feature = "buildpack")]
+pub use error_report::*;
+#[cfg(feature = "buildpack")]
+pub use extension::Extension;
+#[cfg(feature = "buildpack")]
+pub use libcnb_common::color;
+#[cfg(feature = "buildpack")]
 pub use libcnb_common::toml_file::*;
+pub use phase::{phase, Phase};
+#[cfg(feature = "buildpack")]
 pub use platform::*;
+#[cfg(feature = "buildpack")]
+pub use platform_env::*;
+#[cfg(feature = "buildpack")]
 pub use runtime::*;
+#[cfg(feature = "buildpack")]
 pub use target::*;
+#[cfg(feature = "buildpack")]
+pub use tracer::*;
+#[cfg(feature = "trace-otlp")]
+pub use tracing::OtlpProtocol;
+#[cfg(feature = "trace")]
+pub use tracing::TraceExporter;
 
-#[cfg(all(test, not(feature = "trace")))]
+// Only used by the tests of the buildpack framework.
+#[cfg(all(test, not(feature = "buildpack")))]
 use serde_json as _;
+#[cfg(all(test, not(feature = "buildpack")))]
+use tempfile as _;
 
 /// Provides types for CNB data formats. Is a re-export of the `libcnb-data` crate.
 #[doc(inline)]
 pub use libcnb_data as data;
 
+#[cfg(feature = "buildpack")]
 const LIBCNB_SUPPORTED_BUILDPACK_API: data::buildpack::BuildpackApi =
     data::buildpack::BuildpackApi {
         major: 0,
@@ -76,6 +142,7 @@ const LIBCNB_SUPPORTED_BUILDPACK_API: data::buildpack::BuildpackApi =
 ///
 /// buildpack_main!(MyBuildpack);
 /// ```
+#[cfg(feature = "buildpack")]
 #[macro_export]
 macro_rules! buildpack_main {
     ($buildpack:expr) => {
@@ -85,6 +152,43 @@ macro_rules! buildpack_main {
     };
 }
 
+/// Generates a main function for the given image extension.
+///
+/// It will create the main function and wires up the extension to the framework.
+///
+/// # Example:
+/// ```
+/// use libcnb::extension::{Extension, GenerateContext, GenerateResult, GenerateResultBuilder};
+/// use libcnb::extension_main;
+/// use libcnb::generic::{GenericError, GenericMetadata, GenericPlatform};
+///
+/// pub(crate) struct MyExtension;
+///
+/// impl Extension for MyExtension {
+///     type Platform = GenericPlatform;
+///     type Metadata = GenericMetadata;
+///     type Error = GenericError;
+///
+///     fn generate(
+///         &self,
+///         context: GenerateContext<Self>,
+///     ) -> libcnb::Result<GenerateResult, Self::Error> {
+///         GenerateResultBuilder::new().build()
+///     }
+/// }
+///
+/// extension_main!(MyExtension);
+/// ```
+#[cfg(feature = "buildpack")]
+#[macro_export]
+macro_rules! extension_main {
+    ($extension:expr) => {
+        fn main() {
+            ::libcnb::libcnb_extension_runtime(&$extension);
+        }
+    };
+}
+
 /// Resolves the path to an additional buildpack binary by Cargo target name.
 ///
 /// This can be used to copy additional binaries to layers or use them for exec.d.
@@ -93,6 +197,15 @@ macro_rules! buildpack_main {
 /// Cargo will [automatically configure it as a binary target](https://doc.rust-lang.org/cargo/reference/cargo-targets.html#target-auto-discovery)
 /// with the name of file.
 ///
+/// By default, additional binaries are packaged into the `.libcnb-cargo/additional-bin` directory
+/// of the buildpack. To use a different directory, i.e. to make the binaries easier to find for
+/// other tooling, configure it in the buildpack's `Cargo.toml`:
+///
+/// ```toml
+/// [package.metadata.libcnb]
+/// additional-binaries-dir = "bin/helpers"
+/// ```
+///
 /// **Note**: This only works properly if the buildpack is packaged with `libcnb-cargo`/`libcnb-test`.
 ///
 /// ```no_run,compile_fail
@@ -106,6 +219,7 @@ macro_rules! buildpack_main {
 /// )
 /// .unwrap();
 /// ```
+#[cfg(feature = "buildpack")]
 #[macro_export]
 macro_rules! additional_buildpack_binary_path {
     ($target_name:expr) => {
@@ -115,8 +229,7 @@ macro_rules! additional_buildpack_binary_path {
                 ::std::env::var("CNB_BUILDPACK_DIR")
                     .map(::std::path::PathBuf::from)
                     .expect("Couldn't read CNB_BUILDPACK_DIR environment variable")
-                    .join(".libcnb-cargo")
-                    .join("additional-bin")
+                    .join(::libcnb::internals::additional_binaries_dir!())
                     .join($target_name)
             },
             {
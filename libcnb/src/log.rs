@@ -0,0 +1,280 @@
+//! Provides a structured logger for buildpack output.
+//!
+//! libcnb passes a [`Logger`] to buildpacks via [`BuildContext::log`](crate::build::BuildContext)
+//! and [`DetectContext::log`](crate::detect::DetectContext). Using it ensures consistent output
+//! across buildpacks and allows users to control the verbosity of the output with the
+//! `BP_LOG_LEVEL` platform environment variable.
+
+use crate::util::lock;
+use crate::Env;
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The name of the platform environment variable used to configure the log level.
+pub const LOG_LEVEL_ENV_VAR_NAME: &str = "BP_LOG_LEVEL";
+
+/// The severity of a log message.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Default)]
+pub enum LogLevel {
+    Debug,
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
+
+impl FromStr for LogLevel {
+    type Err = ParseLogLevelError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "debug" => Ok(Self::Debug),
+            "info" => Ok(Self::Info),
+            "warn" | "warning" => Ok(Self::Warning),
+            "error" => Ok(Self::Error),
+            _ => Err(ParseLogLevelError::InvalidValue(String::from(value))),
+        }
+    }
+}
+
+impl Display for LogLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
+#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+pub enum ParseLogLevelError {
+    #[error("Invalid log level: {0}")]
+    InvalidValue(String),
+}
+
+/// A structured logger for buildpack output.
+///
+/// Regular messages are written to stdout, warnings and errors are written to stderr. Messages
+/// below the configured [`LogLevel`] are discarded.
+///
+/// # Example
+/// ```
+/// use libcnb::log::{LogLevel, Logger};
+///
+/// let logger = Logger::new(LogLevel::Info);
+///
+/// logger.section("Installing Node.js", || {
+///     logger.info("Downloading Node.js 22.12.0");
+///     logger.debug("Download URL: https://nodejs.org/dist/v22.12.0/node-v22.12.0.tar.gz");
+/// });
+///
+/// logger.warning("Deprecated configuration", "The `engines` key will be ignored.");
+/// ```
+pub struct Logger {
+    level: LogLevel,
+    stdout: Mutex<Box<dyn Write + Send>>,
+    stderr: Mutex<Box<dyn Write + Send>>,
+}
+
+impl Logger {
+    /// Creates a logger that writes to stdout and stderr.
+    #[must_use]
+    pub fn new(level: LogLevel) -> Self {
+        Self::with_writers(level, std::io::stdout(), std::io::stderr())
+    }
+
+    /// Creates a logger that writes to the given writers instead of stdout and stderr.
+    ///
+    /// Primarily useful for testing code that uses a logger.
+    pub fn with_writers(
+        level: LogLevel,
+        stdout: impl Write + Send + 'static,
+        stderr: impl Write + Send + 'static,
+    ) -> Self {
+        Self {
+            level,
+            stdout: Mutex::new(Box::new(stdout)),
+            stderr: Mutex::new(Box::new(stderr)),
+        }
+    }
+
+    /// Creates a logger that writes to stdout and stderr, with the log level read from the
+    /// `BP_LOG_LEVEL` variable of the given environment.
+    ///
+    /// Defaults to [`LogLevel::Info`] if the variable is not set or contains an invalid value.
+    #[must_use]
+    pub fn from_env(env: &Env) -> Self {
+        Self::new(
+            env.get_string_lossy(LOG_LEVEL_ENV_VAR_NAME)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Returns the level of this logger.
+    #[must_use]
+    pub fn level(&self) -> LogLevel {
+        self.level
+    }
+
+    /// Logs a debug message.
+    pub fn debug(&self, message: impl AsRef<str>) {
+        self.write(LogLevel::Debug, message.as_ref());
+    }
+
+    /// Logs an informational message.
+    pub fn info(&self, message: impl AsRef<str>) {
+        self.write(LogLevel::Info, message.as_ref());
+    }
+
+    /// Logs a warning with a header and a body.
+    pub fn warning(&self, header: impl AsRef<str>, body: impl AsRef<str>) {
+        self.write(
+            LogLevel::Warning,
+            &format!("\n[Warning: {}]\n{}", header.as_ref(), body.as_ref()),
+        );
+    }
+
+    /// Logs an error with a header and a body.
+    pub fn error(&self, header: impl AsRef<str>, body: impl AsRef<str>) {
+        self.write(
+            LogLevel::Error,
+            &format!("\n[Error: {}]\n{}", header.as_ref(), body.as_ref()),
+        );
+    }
+
+    /// Logs a section header, runs the given function and logs how long it took.
+    ///
+    /// Returns the value returned by the given function.
+    pub fn section<T>(&self, title: impl AsRef<str>, f: impl FnOnce() -> T) -> T {
+        self.write(LogLevel::Info, &format!("\n[{}]", title.as_ref()));
+
+        let start = Instant::now();
+        let value = f();
+        self.write(
+            LogLevel::Info,
+            &format!("Done ({})", format_duration(start.elapsed())),
+        );
+
+        value
+    }
+
+    fn write(&self, level: LogLevel, message: &str) {
+        if level < self.level {
+            return;
+        }
+
+        let mut writer = lock(match level {
+            LogLevel::Debug | LogLevel::Info => &self.stdout,
+            LogLevel::Warning | LogLevel::Error => &self.stderr,
+        });
+
+        // Failing to write log output must not fail the buildpack, errors are ignored.
+        let _ = writeln!(writer, "{message}").and_then(|()| writer.flush());
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+
+    if seconds >= 60 {
+        format!("{}m {}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{}.{:03}s", seconds, duration.subsec_millis())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedBuffer {
+        fn contents(&self) -> String {
+            String::from_utf8_lossy(&self.0.lock().unwrap()).to_string()
+        }
+    }
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn log_level_from_str() {
+        assert_eq!("DEBUG".parse(), Ok(LogLevel::Debug));
+        assert_eq!("info".parse(), Ok(LogLevel::Info));
+        assert_eq!("warn".parse(), Ok(LogLevel::Warning));
+        assert_eq!("Warning".parse(), Ok(LogLevel::Warning));
+        assert_eq!("error".parse(), Ok(LogLevel::Error));
+        assert_eq!(
+            "verbose".parse::<LogLevel>(),
+            Err(ParseLogLevelError::InvalidValue(String::from("verbose")))
+        );
+    }
+
+    #[test]
+    fn logger_streams_and_levels() {
+        let stdout = SharedBuffer::default();
+        let stderr = SharedBuffer::default();
+        let logger = Logger::with_writers(LogLevel::Info, stdout.clone(), stderr.clone());
+
+        logger.debug("debug message");
+        logger.info("info message");
+        logger.warning("Warning header", "warning body");
+        logger.error("Error header", "error body");
+
+        assert_eq!(stdout.contents(), "info message\n");
+        assert_eq!(
+            stderr.contents(),
+            "\n[Warning: Warning header]\nwarning body\n\n[Error: Error header]\nerror body\n"
+        );
+    }
+
+    #[test]
+    fn logger_section() {
+        let stdout = SharedBuffer::default();
+        let logger = Logger::with_writers(LogLevel::Debug, stdout.clone(), std::io::sink());
+
+        let value = logger.section("Section", || {
+            logger.debug("inside");
+            42
+        });
+
+        assert_eq!(value, 42);
+
+        let contents = stdout.contents();
+        assert!(contents.starts_with("\n[Section]\ninside\nDone ("));
+    }
+
+    #[test]
+    fn logger_from_env() {
+        let mut env = Env::new();
+        assert_eq!(Logger::from_env(&env).level(), LogLevel::Info);
+
+        env.insert(LOG_LEVEL_ENV_VAR_NAME, "debug");
+        assert_eq!(Logger::from_env(&env).level(), LogLevel::Debug);
+
+        env.insert(LOG_LEVEL_ENV_VAR_NAME, "invalid");
+        assert_eq!(Logger::from_env(&env).level(), LogLevel::Info);
+    }
+
+    #[test]
+    fn duration_formatting() {
+        assert_eq!(format_duration(Duration::from_millis(1234)), "1.234s");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2m 5s");
+    }
+}
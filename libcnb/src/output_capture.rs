@@ -0,0 +1,466 @@
+//! Opt-in capturing of the buildpack's output for debugging.
+//!
+//! When enabled, everything the buildpack process and its child processes write to stdout and
+//! stderr is additionally written to timestamped log files. Platforms that truncate streamed build
+//! output can then retrieve the complete output.
+//!
+//! With `LIBCNB_CAPTURE_OUTPUT=true`, the output of the build phase is written to the
+//! `libcnb-diagnostics` layer. The layer is cached, but not part of the app image. Like any other
+//! layer, it's only exported for successful builds, so the logs of the last successful build can be
+//! retrieved from the build cache. The detect phase has no layers directory, its output isn't
+//! captured in this mode.
+//!
+//! With `LIBCNB_CAPTURE_OUTPUT_DIR` set to a directory, i.e. a volume mounted by the platform, the
+//! output of both the detect and the build phase is written to a subdirectory per buildpack of it
+//! instead. Since the logs are written outside of any layer, they're available for failed builds,
+//! too.
+//!
+//! Capturing requires the `capture-output` feature and is only supported on Unix.
+
+use crate::data::buildpack::BuildpackId;
+use crate::data::generic::GenericMetadata;
+use crate::data::layer::LayerName;
+use crate::data::layer_content_metadata::{LayerContentMetadata, LayerTypes};
+use crate::data::layer_name;
+use crate::layer::shared::write_layer;
+use crate::Env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Enables capturing the output of the build phase into a layer when set to `true`, either in the
+/// platform or process environment.
+pub(crate) const CAPTURE_OUTPUT_ENV_VAR_NAME: &str = "LIBCNB_CAPTURE_OUTPUT";
+
+/// Enables capturing the output of the detect and build phases into the given directory, either in
+/// the platform or process environment.
+pub(crate) const CAPTURE_OUTPUT_DIR_ENV_VAR_NAME: &str = "LIBCNB_CAPTURE_OUTPUT_DIR";
+
+/// The name of the cached layer the captured output is written to, unless a directory is set with
+/// [`CAPTURE_OUTPUT_DIR_ENV_VAR_NAME`].
+fn diagnostics_layer_name() -> LayerName {
+    layer_name!("libcnb-diagnostics")
+}
+
+// The capture needs to outlive the build entry point, so that the output of the error handler that
+// runs afterwards is captured as well.
+static ACTIVE_CAPTURE: Mutex<Option<OutputCapture>> = Mutex::new(None);
+
+fn env_value(platform_env: &Env, name: &str) -> Option<String> {
+    platform_env
+        .get_string_lossy(name)
+        .or_else(|| std::env::var(name).ok())
+        .filter(|value| !value.is_empty())
+}
+
+fn capture_output_enabled(platform_env: &Env) -> bool {
+    env_value(platform_env, CAPTURE_OUTPUT_ENV_VAR_NAME).is_some_and(|value| value == "true")
+}
+
+fn capture_output_dir(platform_env: &Env) -> Option<PathBuf> {
+    env_value(platform_env, CAPTURE_OUTPUT_DIR_ENV_VAR_NAME).map(PathBuf::from)
+}
+
+/// Starts capturing stdout and stderr of the detect phase into a new log file in the directory
+/// set with [`CAPTURE_OUTPUT_DIR_ENV_VAR_NAME`], if any.
+///
+/// Failing to start capturing is not fatal, detection continues without it.
+pub(crate) fn start_detect_output_capture(platform_env: &Env, buildpack_id: &BuildpackId) {
+    if let Some(capture_output_dir) = capture_output_dir(platform_env) {
+        start_output_capture(
+            &buildpack_logs_dir(&capture_output_dir, buildpack_id).join(log_file_name("detect")),
+        );
+    }
+}
+
+/// Starts capturing stdout and stderr of the build phase into a new log file, if enabled.
+///
+/// The log file is written to the directory set with [`CAPTURE_OUTPUT_DIR_ENV_VAR_NAME`] or, with
+/// `LIBCNB_CAPTURE_OUTPUT=true`, to the cached diagnostics layer. Logs of previous builds in that
+/// layer are removed.
+///
+/// Failing to start capturing is not fatal, the build continues without it.
+pub(crate) fn start_build_output_capture(
+    platform_env: &Env,
+    layers_dir: &Path,
+    buildpack_id: &BuildpackId,
+) {
+    if let Some(capture_output_dir) = capture_output_dir(platform_env) {
+        start_output_capture(
+            &buildpack_logs_dir(&capture_output_dir, buildpack_id).join(log_file_name("build")),
+        );
+    } else if capture_output_enabled(platform_env) {
+        let layer_name = diagnostics_layer_name();
+        let layer_content_metadata = LayerContentMetadata::<GenericMetadata> {
+            types: Some(LayerTypes {
+                launch: false,
+                build: false,
+                cache: true,
+            }),
+            metadata: None,
+        };
+
+        if let Err(error) = write_layer(layers_dir, &layer_name, &layer_content_metadata) {
+            eprintln!("Warning: Couldn't create the {layer_name} layer to capture output: {error}");
+            return;
+        }
+
+        let diagnostics_layer_dir = layers_dir.join(layer_name.as_str());
+        remove_log_files(&diagnostics_layer_dir);
+        start_output_capture(&diagnostics_layer_dir.join(log_file_name("build")));
+    }
+}
+
+fn start_output_capture(log_file_path: &Path) {
+    match OutputCapture::start(log_file_path) {
+        Ok(capture) => {
+            if let Ok(mut active_capture) = ACTIVE_CAPTURE.lock() {
+                *active_capture = Some(capture);
+            }
+        }
+        Err(io_error) => {
+            eprintln!(
+                "Warning: Couldn't capture output to {}: {io_error}",
+                log_file_path.display()
+            );
+        }
+    }
+}
+
+/// Stops capturing output, making sure all output written so far reached the log file.
+pub(crate) fn finish_output_capture() {
+    if let Some(capture) = ACTIVE_CAPTURE
+        .lock()
+        .ok()
+        .and_then(|mut active_capture| active_capture.take())
+    {
+        capture.finish();
+    }
+}
+
+fn log_file_name(phase: &str) -> String {
+    format!(
+        "{phase}-{}.log",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default()
+    )
+}
+
+// Buildpack ids can contain slashes, which aren't allowed in file names.
+fn buildpack_logs_dir(capture_output_dir: &Path, buildpack_id: &BuildpackId) -> PathBuf {
+    capture_output_dir.join(buildpack_id.replace('/', "_"))
+}
+
+/// Removes the log files in the given directory. Failing to remove a log file is not fatal.
+fn remove_log_files(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        if path.extension().is_some_and(|extension| extension == "log") {
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
+
+#[cfg(all(target_family = "unix", feature = "capture-output"))]
+struct OutputCapture(unix::Tee);
+
+#[cfg(all(target_family = "unix", feature = "capture-output"))]
+impl OutputCapture {
+    fn start(log_file_path: &Path) -> std::io::Result<Self> {
+        fs::create_dir_all(log_file_path.parent().unwrap_or(log_file_path))?;
+        let log_file = fs::File::create(log_file_path)?;
+
+        unix::Tee::start(&[libc::STDOUT_FILENO, libc::STDERR_FILENO], log_file).map(Self)
+    }
+
+    fn finish(self) {
+        use std::io::Write;
+
+        let _ = std::io::stdout().flush();
+        let _ = std::io::stderr().flush();
+        self.0.finish();
+    }
+}
+
+#[cfg(not(all(target_family = "unix", feature = "capture-output")))]
+enum OutputCapture {}
+
+#[cfg(not(all(target_family = "unix", feature = "capture-output")))]
+impl OutputCapture {
+    fn start(_log_file_path: &Path) -> std::io::Result<Self> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "Output capturing requires the `capture-output` feature of libcnb and is only supported on Unix",
+        ))
+    }
+
+    fn finish(self) {
+        match self {}
+    }
+}
+
+#[cfg(all(target_family = "unix", feature = "capture-output"))]
+#[allow(unsafe_code)]
+mod unix {
+    use std::fs::File;
+    use std::io::{ErrorKind, Read, Write};
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread::{self, JoinHandle};
+    use std::time::{Duration, Instant};
+
+    // Child processes that outlive the build keep the pipe open, so don't wait for them forever.
+    const FINISH_TIMEOUT: Duration = Duration::from_secs(5);
+
+    // How often the copying thread checks whether it should stop while the pipe is idle.
+    const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Duplicates everything written to the given file descriptors into a log file.
+    ///
+    /// Each file descriptor is replaced with the write end of a pipe. A thread per file descriptor
+    /// reads from the pipe and writes the data to both the original file descriptor and the log
+    /// file.
+    pub(super) struct Tee {
+        streams: Vec<TeedStream>,
+    }
+
+    struct TeedStream {
+        fd: RawFd,
+        original_fd: OwnedFd,
+        finishing: Arc<AtomicBool>,
+        thread: JoinHandle<()>,
+    }
+
+    impl Tee {
+        pub(super) fn start(fds: &[RawFd], log_file: File) -> std::io::Result<Self> {
+            let log_file = Arc::new(Mutex::new(log_file));
+            let mut tee = Self {
+                streams: Vec::new(),
+            };
+
+            for fd in fds {
+                match TeedStream::start(*fd, Arc::clone(&log_file)) {
+                    Ok(stream) => tee.streams.push(stream),
+                    Err(io_error) => {
+                        tee.finish();
+                        return Err(io_error);
+                    }
+                }
+            }
+
+            Ok(tee)
+        }
+
+        /// Restores the original file descriptors and waits until the copying threads wrote all
+        /// remaining data.
+        pub(super) fn finish(self) {
+            for stream in &self.streams {
+                // Restoring the original file descriptor closes the last write end of the pipe,
+                // unless a child process still holds it.
+                // SAFETY: Both file descriptors are valid for the duration of the call.
+                unsafe { libc::dup2(stream.original_fd.as_raw_fd(), stream.fd) };
+                stream.finishing.store(true, Ordering::SeqCst);
+            }
+
+            for stream in self.streams {
+                let _ = stream.thread.join();
+            }
+        }
+    }
+
+    impl TeedStream {
+        fn start(fd: RawFd, log_file: Arc<Mutex<File>>) -> std::io::Result<Self> {
+            let (pipe_read, pipe_write) = pipe()?;
+
+            // SAFETY: The file descriptor is borrowed for the duration of the call only.
+            let original_fd =
+                unsafe { std::os::fd::BorrowedFd::borrow_raw(fd) }.try_clone_to_owned()?;
+            let original = File::from(original_fd.try_clone()?);
+
+            // SAFETY: Both file descriptors are valid for the duration of the call.
+            if unsafe { libc::dup2(pipe_write.as_raw_fd(), fd) } == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            drop(pipe_write);
+
+            let finishing = Arc::new(AtomicBool::new(false));
+            let thread = thread::spawn({
+                let finishing = Arc::clone(&finishing);
+                move || copy_pipe(File::from(pipe_read), original, &log_file, &finishing)
+            });
+
+            Ok(Self {
+                fd,
+                original_fd,
+                finishing,
+                thread,
+            })
+        }
+    }
+
+    /// Copies data from the pipe to the original file descriptor and the log file until all write
+    /// ends of the pipe are closed. Once finishing, stops after [`FINISH_TIMEOUT`] at the latest.
+    fn copy_pipe(
+        mut pipe_read: File,
+        mut original: File,
+        log_file: &Mutex<File>,
+        finishing: &AtomicBool,
+    ) {
+        let mut buffer = [0; 8192];
+        let mut finish_deadline = None;
+
+        loop {
+            if finishing.load(Ordering::SeqCst) {
+                let deadline =
+                    *finish_deadline.get_or_insert_with(|| Instant::now() + FINISH_TIMEOUT);
+
+                if Instant::now() >= deadline {
+                    break;
+                }
+            }
+
+            match poll_readable(&pipe_read, POLL_INTERVAL) {
+                Ok(false) => continue,
+                Ok(true) => {}
+                Err(io_error) if io_error.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+
+            match pipe_read.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(length) => {
+                    let _ = original.write_all(&buffer[..length]);
+                    if let Ok(mut log_file) = log_file.lock() {
+                        let _ = log_file.write_all(&buffer[..length]);
+                    }
+                }
+                Err(io_error) if io_error.kind() == ErrorKind::Interrupted => {}
+                Err(_) => break,
+            }
+        }
+    }
+
+    /// Waits until the given file is readable, i.e. has data or reached EOF, or the timeout
+    /// elapsed.
+    fn poll_readable(file: &File, timeout: Duration) -> std::io::Result<bool> {
+        let mut poll_fd = libc::pollfd {
+            fd: file.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+
+        let timeout = libc::c_int::try_from(timeout.as_millis()).unwrap_or(libc::c_int::MAX);
+
+        // SAFETY: `poll_fd` is a single valid `pollfd` for the duration of the call.
+        match unsafe { libc::poll(&mut poll_fd, 1, timeout) } {
+            -1 => Err(std::io::Error::last_os_error()),
+            0 => Ok(false),
+            _ => Ok(true),
+        }
+    }
+
+    fn pipe() -> std::io::Result<(OwnedFd, OwnedFd)> {
+        let mut fds: [RawFd; 2] = [-1; 2];
+
+        // SAFETY: `fds` has space for the two file descriptors written by `pipe`.
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } == -1 {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        for fd in fds {
+            // Child processes must only inherit the pipe via the replaced file descriptor.
+            // SAFETY: `pipe` succeeded, so the file descriptor is open.
+            unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) };
+        }
+
+        // SAFETY: `pipe` succeeded, so both file descriptors are open and owned by us.
+        Ok(unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::fs;
+        use tempfile::tempdir;
+
+        #[test]
+        fn tee_into_log_file() {
+            let temp_dir = tempdir().unwrap();
+            let stream_path = temp_dir.path().join("stream.txt");
+            let log_path = temp_dir.path().join("output.log");
+
+            let stream_file = File::create(&stream_path).unwrap();
+            let tee =
+                Tee::start(&[stream_file.as_raw_fd()], File::create(&log_path).unwrap()).unwrap();
+
+            // Writes to the original file descriptor now go through the pipe.
+            (&stream_file).write_all(b"Hello World!\n").unwrap();
+            tee.finish();
+            (&stream_file).write_all(b"Not captured\n").unwrap();
+
+            assert_eq!(
+                fs::read_to_string(&stream_path).unwrap(),
+                "Hello World!\nNot captured\n"
+            );
+            assert_eq!(fs::read_to_string(&log_path).unwrap(), "Hello World!\n");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::buildpack_id;
+    use tempfile::tempdir;
+
+    #[test]
+    fn remove_log_files_keeps_other_files() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(temp_dir.path().join("build-1.log"), "build").unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "notes").unwrap();
+
+        remove_log_files(temp_dir.path());
+
+        assert!(!temp_dir.path().join("build-1.log").exists());
+        assert!(temp_dir.path().join("notes.txt").exists());
+    }
+
+    #[test]
+    fn buildpack_logs_dir_of_buildpack() {
+        assert_eq!(
+            buildpack_logs_dir(Path::new("/logs"), &buildpack_id!("heroku/java")),
+            Path::new("/logs/heroku_java")
+        );
+    }
+
+    #[test]
+    fn capture_output_enabled_from_platform_env() {
+        let mut env = Env::new();
+        assert!(!capture_output_enabled(&env));
+
+        env.insert(CAPTURE_OUTPUT_ENV_VAR_NAME, "false");
+        assert!(!capture_output_enabled(&env));
+
+        env.insert(CAPTURE_OUTPUT_ENV_VAR_NAME, "true");
+        assert!(capture_output_enabled(&env));
+    }
+
+    #[test]
+    fn capture_output_dir_from_platform_env() {
+        let mut env = Env::new();
+        assert_eq!(capture_output_dir(&env), None);
+
+        env.insert(CAPTURE_OUTPUT_DIR_ENV_VAR_NAME, "");
+        assert_eq!(capture_output_dir(&env), None);
+
+        env.insert(CAPTURE_OUTPUT_DIR_ENV_VAR_NAME, "/logs");
+        assert_eq!(capture_output_dir(&env), Some(PathBuf::from("/logs")));
+    }
+}
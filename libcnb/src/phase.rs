@@ -0,0 +1,80 @@
+use crate::exec_d::exec_d_output_fd_open;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// A phase of the CNB lifecycle that buildpack or extension code runs in.
+///
+/// Use [`phase`] to determine the current phase.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Phase {
+    /// The detect phase of a buildpack or image extension.
+    Detect,
+    /// The build phase of a buildpack.
+    Build,
+    /// The generate phase of an image extension.
+    Generate,
+    /// An [exec.d](crate::exec_d) program run by the CNB launcher.
+    ExecD,
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Detect => "detect",
+            Self::Build => "build",
+            Self::Generate => "generate",
+            Self::ExecD => "exec.d",
+        })
+    }
+}
+
+const NO_PHASE: u8 = 0;
+
+static CURRENT_PHASE: AtomicU8 = AtomicU8::new(NO_PHASE);
+
+/// Returns the lifecycle phase the current process runs in.
+///
+/// This allows shared library code to adjust its behavior to the phase, i.e. to refuse writing
+/// layers during detect, without passing the detect or build context around.
+///
+/// The detect, build and generate phases are set by the libcnb runtime before the buildpack or
+/// extension code is called. Outside of those, [`Phase::ExecD`] is returned if the exec.d output
+/// file descriptor is open, as it is when the CNB launcher runs an exec.d program. Returns `None`
+/// in all other cases, i.e. in unit tests.
+#[must_use]
+pub fn phase() -> Option<Phase> {
+    match CURRENT_PHASE.load(Ordering::Relaxed) {
+        1 => Some(Phase::Detect),
+        2 => Some(Phase::Build),
+        3 => Some(Phase::Generate),
+        _ if exec_d_output_fd_open() => Some(Phase::ExecD),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "buildpack")]
+pub(crate) fn set_phase(phase: Phase) {
+    let value = match phase {
+        Phase::Detect => 1,
+        Phase::Build => 2,
+        Phase::Generate => 3,
+        Phase::ExecD => NO_PHASE,
+    };
+
+    CURRENT_PHASE.store(value, Ordering::Relaxed);
+}
+
+#[cfg(all(test, feature = "buildpack"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_read_phase() {
+        for phase in [Phase::Detect, Phase::Build, Phase::Generate] {
+            set_phase(phase);
+            assert_eq!(super::phase(), Some(phase));
+        }
+
+        assert_eq!(Phase::ExecD.to_string(), "exec.d");
+    }
+}
@@ -1,7 +1,7 @@
 use crate::Env;
 use std::fs;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Represents a Cloud Native Buildpack platform.
 ///
@@ -27,6 +27,80 @@ where
     /// let platform = GenericPlatform::from_path("/platform").unwrap();
     /// ```
     fn from_path(platform_dir: impl AsRef<Path>) -> io::Result<Self>;
+
+    /// Returns the platform environment variables with names matching the given predicate.
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb::generic::GenericPlatform;
+    /// use libcnb::{Env, Platform};
+    ///
+    /// let mut env = Env::new();
+    /// env.insert("NODE_ENV", "production");
+    /// env.insert("NPM_CONFIG_PRODUCTION", "false");
+    ///
+    /// let platform = GenericPlatform::new(env);
+    /// let node_env = platform.env_filtered(|name| name.starts_with("NODE_"));
+    ///
+    /// assert_eq!(node_env.get_string_lossy("NODE_ENV"), Some(String::from("production")));
+    /// assert!(!node_env.contains_key("NPM_CONFIG_PRODUCTION"));
+    /// ```
+    fn env_filtered(&self, predicate: impl Fn(&str) -> bool) -> Env {
+        self.env().filtered(predicate)
+    }
+
+    /// Returns the platform directory this platform was initialized from, if any.
+    ///
+    /// Platforms that aren't backed by a platform directory, i.e. platforms created in tests,
+    /// return `None`.
+    fn platform_dir(&self) -> Option<&PlatformDir> {
+        None
+    }
+}
+
+/// The well-known contents of a CNB platform directory.
+///
+/// See [the platform directory](https://github.com/buildpacks/spec/blob/main/buildpack.md#platform-directory)
+/// in the buildpack specification for details.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlatformDir {
+    path: PathBuf,
+}
+
+impl PlatformDir {
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The path of the platform directory itself.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The directory containing the user-provided environment variables, one file per variable.
+    #[must_use]
+    pub fn env_dir(&self) -> PathBuf {
+        self.path.join("env")
+    }
+
+    /// The directory containing the [service bindings](https://github.com/buildpacks/spec/blob/main/extensions/bindings.md)
+    /// provided by the platform.
+    #[must_use]
+    pub fn bindings_dir(&self) -> PathBuf {
+        self.path.join("bindings")
+    }
+
+    /// Reads the user-provided environment variables from [`env_dir`](Self::env_dir).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the environment variables couldn't be read. A missing `env` directory
+    /// is not an error and results in an empty [`Env`].
+    pub fn read_env(&self) -> io::Result<Env> {
+        read_platform_env(&self.path)
+    }
 }
 
 /// Initializes a new `Env` based on the given platform directory.
@@ -110,6 +184,22 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn platform_dir_paths() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        fs::create_dir(tmpdir.path().join("env")).unwrap();
+        fs::write(tmpdir.path().join("env").join("FOO"), "BAR").unwrap();
+
+        let platform_dir = PlatformDir::new(tmpdir.path());
+        assert_eq!(platform_dir.path(), tmpdir.path());
+        assert_eq!(platform_dir.env_dir(), tmpdir.path().join("env"));
+        assert_eq!(platform_dir.bindings_dir(), tmpdir.path().join("bindings"));
+        assert_eq!(
+            platform_dir.read_env().unwrap().get("FOO"),
+            Some(&OsString::from("BAR"))
+        );
+    }
+
     #[test]
     fn read_platform_env_does_not_blow_up_if_platform_env_is_missing() {
         let tmpdir = tempfile::tempdir().unwrap();
@@ -0,0 +1,308 @@
+use crate::Env;
+use std::fmt::Write;
+
+/// Declares a platform environment variable used by a buildpack.
+///
+/// Buildpacks declare the platform environment variables they use via
+/// [`Buildpack::PLATFORM_ENV_VARS`](crate::Buildpack::PLATFORM_ENV_VARS). The framework validates
+/// the platform environment against these declarations before `detect` and `build` run, resulting
+/// in consistent errors for missing or malformed values in both phases.
+///
+/// # Example
+/// ```
+/// use libcnb::{Env, PlatformEnvVar, PlatformEnvVarKind};
+///
+/// const MAVEN_VERSION: PlatformEnvVar =
+///     PlatformEnvVar::optional("MAVEN_VERSION", "The Maven version to install.")
+///         .with_default("3.9.6");
+///
+/// const MAVEN_SKIP_TESTS: PlatformEnvVar =
+///     PlatformEnvVar::optional("MAVEN_SKIP_TESTS", "Skips running tests during the build.")
+///         .with_kind(PlatformEnvVarKind::Boolean)
+///         .with_default("false");
+///
+/// let mut env = Env::new();
+/// env.insert("MAVEN_SKIP_TESTS", "true");
+///
+/// assert_eq!(MAVEN_VERSION.value(&env), Some(String::from("3.9.6")));
+/// assert_eq!(MAVEN_SKIP_TESTS.value(&env), Some(String::from("true")));
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct PlatformEnvVar {
+    /// The name of the environment variable.
+    pub name: &'static str,
+    /// A human-readable description of the environment variable.
+    pub description: &'static str,
+    /// The kind of values the environment variable accepts.
+    pub kind: PlatformEnvVarKind,
+    /// Whether the environment variable must be set by the platform.
+    pub required: bool,
+    /// The value to use if the environment variable isn't set.
+    pub default: Option<&'static str>,
+}
+
+impl PlatformEnvVar {
+    /// Declares a platform environment variable that must be set.
+    #[must_use]
+    pub const fn required(name: &'static str, description: &'static str) -> Self {
+        Self {
+            name,
+            description,
+            kind: PlatformEnvVarKind::String,
+            required: true,
+            default: None,
+        }
+    }
+
+    /// Declares a platform environment variable that may be set.
+    #[must_use]
+    pub const fn optional(name: &'static str, description: &'static str) -> Self {
+        Self {
+            name,
+            description,
+            kind: PlatformEnvVarKind::String,
+            required: false,
+            default: None,
+        }
+    }
+
+    /// Sets the kind of values the environment variable accepts.
+    #[must_use]
+    pub const fn with_kind(mut self, kind: PlatformEnvVarKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Sets the value to use if the environment variable isn't set.
+    #[must_use]
+    pub const fn with_default(mut self, default: &'static str) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    /// Returns the value of the environment variable from the given platform environment, falling
+    /// back to the default value if it isn't set.
+    ///
+    /// Non-Unicode values are converted lossily. Values are not validated, the framework takes
+    /// care of that before `detect` and `build` run.
+    #[must_use]
+    pub fn value(&self, env: &Env) -> Option<String> {
+        env.get_string_lossy(self.name)
+            .or_else(|| self.default.map(String::from))
+    }
+
+    /// Validates the value of the environment variable in the given platform environment.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the environment variable is required but not set, isn't valid Unicode
+    /// or its value doesn't match the declared [`PlatformEnvVarKind`].
+    pub fn validate(&self, env: &Env) -> Result<(), PlatformEnvVarError> {
+        match env.get(self.name) {
+            None if self.required => Err(PlatformEnvVarError::Missing(self.name)),
+            None => Ok(()),
+            Some(value) => {
+                let value = value
+                    .to_str()
+                    .ok_or(PlatformEnvVarError::NotUnicode(self.name))?;
+
+                if self.kind.accepts(value) {
+                    Ok(())
+                } else {
+                    Err(PlatformEnvVarError::InvalidValue {
+                        name: self.name,
+                        value: String::from(value),
+                        expected: self.kind.to_string(),
+                    })
+                }
+            }
+        }
+    }
+}
+
+/// The kind of values a [`PlatformEnvVar`] accepts.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PlatformEnvVarKind {
+    /// Any string.
+    String,
+    /// `true` or `false`.
+    Boolean,
+    /// A signed integer.
+    Integer,
+    /// One of the given strings.
+    OneOf(&'static [&'static str]),
+}
+
+impl PlatformEnvVarKind {
+    fn accepts(&self, value: &str) -> bool {
+        match self {
+            Self::String => true,
+            Self::Boolean => matches!(value, "true" | "false"),
+            Self::Integer => value.parse::<i64>().is_ok(),
+            Self::OneOf(values) => values.contains(&value),
+        }
+    }
+}
+
+impl std::fmt::Display for PlatformEnvVarKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::String => f.write_str("string"),
+            Self::Boolean => f.write_str("`true` or `false`"),
+            Self::Integer => f.write_str("integer"),
+            Self::OneOf(values) => f.write_str(
+                &values
+                    .iter()
+                    .map(|value| format!("`{value}`"))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+pub enum PlatformEnvVarError {
+    #[error("Required platform environment variable {0} is not set")]
+    Missing(&'static str),
+
+    #[error("Platform environment variable {0} is not valid Unicode")]
+    NotUnicode(&'static str),
+
+    #[error(
+        "Platform environment variable {name} has invalid value '{value}', expected: {expected}"
+    )]
+    InvalidValue {
+        name: &'static str,
+        value: String,
+        expected: String,
+    },
+}
+
+/// Validates the given platform environment against the given declarations.
+///
+/// # Errors
+///
+/// Returns the error of the first declaration that failed validation, see
+/// [`PlatformEnvVar::validate`].
+pub fn validate_platform_env(
+    platform_env_vars: &[PlatformEnvVar],
+    env: &Env,
+) -> Result<(), PlatformEnvVarError> {
+    platform_env_vars
+        .iter()
+        .try_for_each(|platform_env_var| platform_env_var.validate(env))
+}
+
+/// Renders the given declarations as a Markdown table, i.e. for inclusion in a buildpack's README.
+#[must_use]
+pub fn platform_env_vars_markdown(platform_env_vars: &[PlatformEnvVar]) -> String {
+    let mut markdown = String::from(
+        "| Name | Description | Required | Values | Default |\n|------|-------------|----------|--------|---------|\n",
+    );
+
+    for platform_env_var in platform_env_vars {
+        let _ = writeln!(
+            markdown,
+            "| `{}` | {} | {} | {} | {} |",
+            platform_env_var.name,
+            platform_env_var.description,
+            if platform_env_var.required {
+                "yes"
+            } else {
+                "no"
+            },
+            platform_env_var.kind,
+            platform_env_var
+                .default
+                .map_or(String::from("-"), |default| format!("`{default}`"))
+        );
+    }
+
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STACK: PlatformEnvVar = PlatformEnvVar::required("STACK", "The stack.")
+        .with_kind(PlatformEnvVarKind::OneOf(&["heroku-22", "heroku-24"]));
+
+    const DEBUG: PlatformEnvVar = PlatformEnvVar::optional("DEBUG", "Enables debug output.")
+        .with_kind(PlatformEnvVarKind::Boolean)
+        .with_default("false");
+
+    const RETRIES: PlatformEnvVar = PlatformEnvVar::optional("RETRIES", "Number of retries.")
+        .with_kind(PlatformEnvVarKind::Integer);
+
+    #[test]
+    fn validate_valid_env() {
+        let mut env = Env::new();
+        env.insert("STACK", "heroku-24");
+        env.insert("RETRIES", "-3");
+
+        assert_eq!(
+            validate_platform_env(&[STACK, DEBUG, RETRIES], &env),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn validate_missing_required() {
+        assert_eq!(
+            validate_platform_env(&[DEBUG, STACK], &Env::new()),
+            Err(PlatformEnvVarError::Missing("STACK"))
+        );
+    }
+
+    #[test]
+    fn validate_invalid_values() {
+        let mut env = Env::new();
+        env.insert("STACK", "heroku-20");
+        env.insert("DEBUG", "yes");
+        env.insert("RETRIES", "many");
+
+        assert_eq!(
+            STACK.validate(&env),
+            Err(PlatformEnvVarError::InvalidValue {
+                name: "STACK",
+                value: String::from("heroku-20"),
+                expected: String::from("`heroku-22`, `heroku-24`"),
+            })
+        );
+        assert!(matches!(
+            DEBUG.validate(&env),
+            Err(PlatformEnvVarError::InvalidValue { name: "DEBUG", .. })
+        ));
+        assert!(matches!(
+            RETRIES.validate(&env),
+            Err(PlatformEnvVarError::InvalidValue {
+                name: "RETRIES",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn value_with_default() {
+        let mut env = Env::new();
+        assert_eq!(DEBUG.value(&env), Some(String::from("false")));
+        assert_eq!(RETRIES.value(&env), None);
+
+        env.insert("DEBUG", "true");
+        assert_eq!(DEBUG.value(&env), Some(String::from("true")));
+    }
+
+    #[test]
+    fn markdown() {
+        assert_eq!(
+            platform_env_vars_markdown(&[STACK, DEBUG]),
+            r"| Name | Description | Required | Values | Default |
+|------|-------------|----------|--------|---------|
+| `STACK` | The stack. | yes | `heroku-22`, `heroku-24` | - |
+| `DEBUG` | Enables debug output. | no | `true` or `false` | `false` |
+"
+        );
+    }
+}
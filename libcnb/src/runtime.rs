@@ -1,16 +1,42 @@
-use crate::build::{BuildContext, InnerBuildResult};
+use crate::build::{BuildContext, BuildContextState, InnerBuildResult};
 use crate::buildpack::Buildpack;
 use crate::data::buildpack::BuildpackApi;
-use crate::detect::{DetectContext, InnerDetectResult};
+use crate::descriptor_warnings::log_buildpack_descriptor_warnings;
+use crate::detect::{DetectContext, DetectContextState, InnerDetectResult};
 use crate::error::Error;
+use crate::extension::{self, Extension, GenerateContext, GenerateResult};
+use crate::layer::artifact_store::{artifact_store_layer_name, prune_artifact_store};
+use crate::layer::retry::with_retries;
+use crate::layer::shared::{delete_layer, write_layer};
+use crate::layer::{LayerError, WriteLayerError};
+use crate::layer_env::{LayerEnv, ModificationBehavior, Scope};
+use crate::log::Logger;
+use crate::output_capture::{
+    finish_output_capture, start_build_output_capture, start_detect_output_capture,
+};
+use crate::phase::set_phase;
 use crate::platform::Platform;
-use crate::sbom::cnb_sbom_path;
+use crate::platform_env::validate_platform_env;
+use crate::sbom::{cnb_sbom_path, merge_sboms_by_format};
 #[cfg(feature = "trace")]
 use crate::tracing::start_trace;
-use crate::util::is_not_found_error_kind;
-use crate::{exit_code, Target, TomlFileError, LIBCNB_SUPPORTED_BUILDPACK_API};
+use crate::util::{is_not_found_error_kind, lock};
+use crate::{exit_code, Env, Phase, Target, TomlFileError, LIBCNB_SUPPORTED_BUILDPACK_API};
+use libcnb_common::color::{
+    color_output_from_env_vars, set_color_output, CLICOLOR_FORCE_ENV_VAR_NAME,
+    NO_COLOR_ENV_VAR_NAME,
+};
+use libcnb_common::generated_dockerfile::{
+    format_generated_dockerfile, PRINT_GENERATED_DOCKERFILES_ENV_VAR_NAME,
+};
 use libcnb_common::toml_file::{read_toml_file, write_toml_file};
+use libcnb_data::build::Build;
 use libcnb_data::buildpack::ComponentBuildpackDescriptor;
+use libcnb_data::extension::ExtensionDescriptor;
+use libcnb_data::launch::Launch;
+use libcnb_data::layer_content_metadata::{LayerContentMetadata, LayerTypes};
+use libcnb_data::layer_name;
+use libcnb_data::sbom::legacy_bom::{find_legacy_bom_entries, legacy_bom_supported};
 use libcnb_data::store::Store;
 use serde::de::DeserializeOwned;
 use serde::Deserialize;
@@ -18,6 +44,7 @@ use std::ffi::OsStr;
 use std::fmt::Debug;
 use std::path::{Path, PathBuf};
 use std::process::exit;
+use std::sync::Arc;
 use std::{env, fs};
 
 /// Main entry point for this framework.
@@ -106,10 +133,15 @@ pub fn libcnb_runtime<B: Buildpack>(buildpack: &B) {
     };
 
     match result {
-        Ok(code) => exit(code),
+        Ok(code) => {
+            finish_output_capture();
+            exit(code)
+        }
         Err(libcnb_error) => {
-            buildpack.on_error(libcnb_error);
-            exit(exit_code::GENERIC_UNSPECIFIED_ERROR);
+            let error_report = buildpack.on_error(libcnb_error);
+            let _ = error_report.write_to(&mut std::io::stderr());
+            finish_output_capture();
+            exit(error_report.get_exit_code());
         }
     }
 }
@@ -122,6 +154,8 @@ pub fn libcnb_runtime_detect<B: Buildpack>(
     buildpack: &B,
     args: DetectArgs,
 ) -> crate::Result<i32, B::Error> {
+    set_phase(Phase::Detect);
+
     let app_dir = env::current_dir().map_err(Error::CannotDetermineAppDirectory)?;
 
     let buildpack_dir = read_buildpack_dir()?;
@@ -130,7 +164,13 @@ pub fn libcnb_runtime_detect<B: Buildpack>(
         read_buildpack_descriptor()?;
 
     #[cfg(feature = "trace")]
-    let mut trace = start_trace(&buildpack_descriptor.buildpack, "detect");
+    let mut trace = start_trace(&buildpack_descriptor.buildpack, "detect", B::TRACE_EXPORTER);
+
+    #[cfg(feature = "trace")]
+    let tracer = trace.tracer();
+
+    #[cfg(not(feature = "trace"))]
+    let tracer = crate::BuildpackTracer::default();
 
     #[cfg(feature = "trace")]
     let mut trace_error = |err: &dyn std::error::Error| {
@@ -144,16 +184,34 @@ pub fn libcnb_runtime_detect<B: Buildpack>(
         .map_err(Error::CannotCreatePlatformFromPath)
         .inspect_err(|err| trace_error(err))?;
 
+    start_detect_output_capture(platform.env(), &buildpack_descriptor.buildpack.id);
+
+    validate_platform_env(B::PLATFORM_ENV_VARS, platform.env())
+        .map_err(Error::InvalidPlatformEnv)
+        .inspect_err(|err| trace_error(err))?;
+
     let build_plan_path = args.build_plan_path;
 
     let target = context_target().inspect_err(|err| trace_error(err))?;
 
+    configure_color_output(platform.env());
+    let log = Logger::from_env(platform.env());
+
+    log_buildpack_descriptor_warnings(&log, &buildpack_descriptor);
+
     let detect_context = DetectContext {
         app_dir,
         buildpack_dir,
+        platform_dir: args.platform_dir_path,
+        build_plan_path: build_plan_path.clone(),
         target,
         platform,
         buildpack_descriptor,
+        log,
+        state: DetectContextState {
+            tracer,
+            ..DetectContextState::default()
+        },
     };
 
     let detect_result = buildpack
@@ -166,8 +224,15 @@ pub fn libcnb_runtime_detect<B: Buildpack>(
             trace.add_event("detect-failed");
             Ok(exit_code::DETECT_DETECTION_FAILED)
         }
-        InnerDetectResult::Pass { build_plan } => {
-            if let Some(build_plan) = build_plan {
+        InnerDetectResult::Pass {
+            build_plan,
+            build_plan_toml,
+        } => {
+            if let Some(build_plan_toml) = build_plan_toml {
+                write_toml_file(&build_plan_toml, build_plan_path)
+                    .map_err(Error::CannotWriteBuildPlan)
+                    .inspect_err(|err| trace_error(err))?;
+            } else if let Some(build_plan) = build_plan {
                 write_toml_file(&build_plan, build_plan_path)
                     .map_err(Error::CannotWriteBuildPlan)
                     .inspect_err(|err| trace_error(err))?;
@@ -188,6 +253,8 @@ pub fn libcnb_runtime_build<B: Buildpack>(
     buildpack: &B,
     args: BuildArgs,
 ) -> crate::Result<i32, B::Error> {
+    set_phase(Phase::Build);
+
     let layers_dir = args.layers_dir_path;
 
     let app_dir = env::current_dir().map_err(Error::CannotDetermineAppDirectory)?;
@@ -198,7 +265,13 @@ pub fn libcnb_runtime_build<B: Buildpack>(
         read_buildpack_descriptor()?;
 
     #[cfg(feature = "trace")]
-    let mut trace = start_trace(&buildpack_descriptor.buildpack, "build");
+    let mut trace = start_trace(&buildpack_descriptor.buildpack, "build", B::TRACE_EXPORTER);
+
+    #[cfg(feature = "trace")]
+    let tracer = trace.tracer();
+
+    #[cfg(not(feature = "trace"))]
+    let tracer = crate::BuildpackTracer::default();
 
     #[cfg(feature = "trace")]
     let mut trace_error = |err: &dyn std::error::Error| {
@@ -208,10 +281,20 @@ pub fn libcnb_runtime_build<B: Buildpack>(
     #[cfg(not(feature = "trace"))]
     let mut trace_error = |_: &dyn std::error::Error| {};
 
-    let platform = Platform::from_path(&args.platform_dir_path)
+    let platform = B::Platform::from_path(&args.platform_dir_path)
         .map_err(Error::CannotCreatePlatformFromPath)
         .inspect_err(|err| trace_error(err))?;
 
+    start_build_output_capture(
+        platform.env(),
+        &layers_dir,
+        &buildpack_descriptor.buildpack.id,
+    );
+
+    validate_platform_env(B::PLATFORM_ENV_VARS, platform.env())
+        .map_err(Error::InvalidPlatformEnv)
+        .inspect_err(|err| trace_error(err))?;
+
     let buildpack_plan = read_toml_file(&args.buildpack_plan_path)
         .map_err(Error::CannotReadBuildpackPlan)
         .inspect_err(|err| trace_error(err))?;
@@ -225,41 +308,130 @@ pub fn libcnb_runtime_build<B: Buildpack>(
 
     let target = context_target().inspect_err(|err| trace_error(err))?;
 
+    configure_color_output(platform.env());
+    let log = Logger::from_env(platform.env());
+
+    log_buildpack_descriptor_warnings(&log, &buildpack_descriptor);
+
+    let state = BuildContextState {
+        tracer,
+        ..BuildContextState::default()
+    };
+
+    let temp_layers = Arc::clone(&state.temp_layers);
+    let mutable_store = Arc::clone(&state.mutable_store);
+    let collected_sboms = Arc::clone(&state.collected_sboms);
+    let used_artifacts = Arc::clone(&state.used_artifacts);
+
+    let legacy_bom_supported = legacy_bom_supported(&buildpack_descriptor.api);
+
     let build_context = BuildContext {
         layers_dir: layers_dir.clone(),
         app_dir,
         platform,
         target,
         buildpack_plan,
+        buildpack_plan_path: args.buildpack_plan_path,
+        platform_dir: args.platform_dir_path,
         buildpack_dir,
         buildpack_descriptor,
         store,
+        log,
+        state,
     };
 
-    let build_result = buildpack
-        .build(build_context)
+    let build_result = buildpack.build(build_context);
+
+    // Temporary layers are deleted regardless of the build result. All of them are deleted, even
+    // if deleting one fails, and only the first error is kept. Errors while deleting them are only
+    // reported if the build itself succeeded to not mask the original error.
+    let delete_temp_layers_result = lock(&temp_layers)
+        .iter()
+        .map(|layer_name| {
+            with_retries(B::LAYER_IO_RETRY_POLICY, || {
+                delete_layer(&layers_dir, layer_name)
+            })
+        })
+        .fold(Ok(()), Result::and)
+        .map_err(|error| Error::LayerError(LayerError::DeleteLayerError(error)));
+
+    let build_result = build_result
+        .and_then(|build_result| delete_temp_layers_result.map(|()| build_result))
         .inspect_err(|err| trace_error(err))?;
 
+    // Pruning is best-effort, leftover artifacts only take up space in the cache.
+    if let Some(used_artifacts) = lock(&used_artifacts).take() {
+        let _ = prune_artifact_store(
+            &layers_dir.join(artifact_store_layer_name().as_str()),
+            &used_artifacts,
+        );
+    }
+
     match build_result.0 {
         InnerBuildResult::Pass {
             launch,
             store,
             build_sboms,
             launch_sboms,
+            unmet,
         } => {
+            // Checked before libcnb writes `launch.toml` and `build.toml` itself, since both files
+            // might have been written by buildpack code and would be overwritten otherwise.
+            if !legacy_bom_supported {
+                if let Some(path) = find_legacy_bom(&layers_dir) {
+                    let err = Error::LegacyBom(path);
+                    trace_error(&err);
+                    return Err(err);
+                }
+            }
+
             if let Some(launch) = launch {
+                launch
+                    .validate()
+                    .map_err(Error::InvalidLaunch)
+                    .inspect_err(|err| trace_error(err))?;
+
                 write_toml_file(&launch, layers_dir.join("launch.toml"))
                     .map_err(Error::CannotWriteLaunch)
                     .inspect_err(|err| trace_error(err))?;
+
+                write_process_env_layer(&layers_dir, &launch)
+                    .map_err(Error::CannotWriteProcessEnvLayer)
+                    .inspect_err(|err| trace_error(err))?;
             };
 
-            if let Some(store) = store {
+            if let Some(store) = merge_stores(lock(&mutable_store).take(), store) {
                 write_toml_file(&store, layers_dir.join("store.toml"))
                     .map_err(Error::CannotWriteStore)
                     .inspect_err(|err| trace_error(err))?;
             };
 
+            if !unmet.is_empty() {
+                write_toml_file(&Build { unmet }, layers_dir.join("build.toml"))
+                    .map_err(Error::CannotWriteBuild)
+                    .inspect_err(|err| trace_error(err))?;
+            }
+
+            // SBOMs attached from layer code are written alongside the ones from the build result.
+            let collected_sboms = std::mem::take(&mut *lock(&collected_sboms));
+
+            let build_sboms =
+                merge_sboms_by_format(build_sboms.into_iter().chain(collected_sboms.build))
+                    .map_err(Error::CannotMergeSboms)
+                    .inspect_err(|err| trace_error(err))?;
+
+            let launch_sboms =
+                merge_sboms_by_format(launch_sboms.into_iter().chain(collected_sboms.launch))
+                    .map_err(Error::CannotMergeSboms)
+                    .inspect_err(|err| trace_error(err))?;
+
             for build_sbom in build_sboms {
+                #[cfg(feature = "sbom-validate")]
+                build_sbom
+                    .validate()
+                    .map_err(Error::InvalidSbom)
+                    .inspect_err(|err| trace_error(err))?;
+
                 fs::write(
                     cnb_sbom_path(&build_sbom.format, &layers_dir, "build"),
                     &build_sbom.data,
@@ -269,6 +441,12 @@ pub fn libcnb_runtime_build<B: Buildpack>(
             }
 
             for launch_sbom in launch_sboms {
+                #[cfg(feature = "sbom-validate")]
+                launch_sbom
+                    .validate()
+                    .map_err(Error::InvalidSbom)
+                    .inspect_err(|err| trace_error(err))?;
+
                 fs::write(
                     cnb_sbom_path(&launch_sbom.format, &layers_dir, "launch"),
                     &launch_sbom.data,
@@ -284,6 +462,301 @@ pub fn libcnb_runtime_build<B: Buildpack>(
     }
 }
 
+/// Returns the path of the first `launch.toml` or `build.toml` in the given layers directory that
+/// contains legacy BOM entries.
+///
+/// Both files might have been written by buildpack code directly, bypassing [`Launch`]. Files that
+/// can't be read or parsed are skipped, since the lifecycle reports these itself.
+fn find_legacy_bom(layers_dir: &Path) -> Option<PathBuf> {
+    ["launch.toml", "build.toml"]
+        .into_iter()
+        .map(|file_name| layers_dir.join(file_name))
+        .find(|path| {
+            read_toml_file::<toml::Table>(path)
+                .is_ok_and(|document| !find_legacy_bom_entries(&document).is_empty())
+        })
+}
+
+/// Writes the default environment variables of all processes in the given [`Launch`] to a
+/// dedicated launch layer, using process-specific environment variables as defined by the CNB spec.
+///
+/// No layer is written if none of the processes declare environment variables.
+fn write_process_env_layer(layers_dir: &Path, launch: &Launch) -> Result<(), WriteLayerError> {
+    if launch
+        .processes
+        .iter()
+        .all(|process| process.env.is_empty())
+    {
+        return Ok(());
+    }
+
+    let layer_name = layer_name!("libcnb-process-env");
+
+    let layer_env = launch
+        .processes
+        .iter()
+        .flat_map(|process| {
+            process
+                .env
+                .iter()
+                .map(|(key, value)| (process.r#type.to_string(), key, value))
+        })
+        .fold(LayerEnv::new(), |layer_env, (process_type, key, value)| {
+            layer_env.chainable_insert(
+                Scope::Process(process_type),
+                ModificationBehavior::Default,
+                key,
+                value,
+            )
+        });
+
+    write_layer(
+        layers_dir,
+        &layer_name,
+        &LayerContentMetadata {
+            types: Some(LayerTypes {
+                launch: true,
+                build: false,
+                cache: false,
+            }),
+            metadata: None::<toml::Table>,
+        },
+    )?;
+
+    layer_env.write_to_layer_dir(layers_dir.join(layer_name.as_str()))?;
+
+    Ok(())
+}
+
+/// Main entry point for image extensions written with this framework.
+///
+/// Like [`libcnb_runtime`], a single binary is built and the filename by which it is invoked
+/// (`detect` or `generate`) determines the mode in which it is being run.
+///
+/// Don't implement this directly and use the [`extension_main`] macro instead!
+#[doc(hidden)]
+pub fn libcnb_extension_runtime<E: Extension>(extension: &E) {
+    // See `libcnb_runtime` for why the API version is checked before anything else.
+    match read_extension_descriptor::<BuildpackDescriptorApiOnly, E::Error>() {
+        Ok(extension_descriptor) => {
+            if extension_descriptor.api != LIBCNB_SUPPORTED_BUILDPACK_API {
+                eprintln!("Error: Cloud Native Buildpack API mismatch");
+                eprintln!(
+                    "This extension uses Cloud Native Buildpacks API version {} (specified in extension.toml).",
+                    &extension_descriptor.api,
+                );
+                eprintln!("However, the underlying libcnb.rs library only supports CNB API {LIBCNB_SUPPORTED_BUILDPACK_API}.");
+                exit(exit_code::GENERIC_CNB_API_VERSION_ERROR)
+            }
+        }
+        Err(libcnb_error) => {
+            eprintln!("Error: Unable to determine Buildpack API version");
+            eprintln!("Cause: {libcnb_error}");
+            exit(exit_code::GENERIC_CNB_API_VERSION_ERROR);
+        }
+    }
+
+    let args: Vec<String> = env::args().collect();
+
+    let current_exe_file_name = args
+        .first()
+        .map(Path::new)
+        .and_then(Path::file_name)
+        .and_then(OsStr::to_str);
+
+    let result = match current_exe_file_name {
+        Some("detect") => libcnb_extension_runtime_detect(
+            extension,
+            ExtensionDetectArgs::from_env().unwrap_or_else(|parse_error| {
+                eprintln!("Error: {parse_error}");
+                eprintln!(
+                    "https://github.com/buildpacks/spec/blob/main/image_extension.md#detection"
+                );
+                exit(exit_code::GENERIC_UNSPECIFIED_ERROR);
+            }),
+        ),
+        Some("generate") => libcnb_extension_runtime_generate(
+            extension,
+            GenerateArgs::from_env().unwrap_or_else(|parse_error| {
+                eprintln!("Error: {parse_error}");
+                eprintln!(
+                    "https://github.com/buildpacks/spec/blob/main/image_extension.md#generation"
+                );
+                exit(exit_code::GENERIC_UNSPECIFIED_ERROR);
+            }),
+        ),
+        other => {
+            eprintln!(
+                "Error: Expected the name of this executable to be 'detect' or 'generate', but it was '{}'",
+                other.unwrap_or("<unknown>")
+            );
+            eprintln!("The executable name is used to determine the current extension phase.");
+            eprintln!("You might want to create 'detect' and 'generate' links to this executable and run those instead.");
+            exit(exit_code::GENERIC_UNEXPECTED_EXECUTABLE_NAME_ERROR)
+        }
+    };
+
+    match result {
+        Ok(code) => exit(code),
+        Err(libcnb_error) => {
+            let error_report = extension.on_error(libcnb_error);
+            let _ = error_report.write_to(&mut std::io::stderr());
+            exit(error_report.get_exit_code());
+        }
+    }
+}
+
+/// Extension detect entry point for this framework.
+///
+/// Exposed only to allow for advanced use-cases where detect is programmatically invoked.
+#[doc(hidden)]
+pub fn libcnb_extension_runtime_detect<E: Extension>(
+    extension: &E,
+    args: ExtensionDetectArgs,
+) -> crate::Result<i32, E::Error> {
+    set_phase(Phase::Detect);
+
+    let app_dir = env::current_dir().map_err(Error::CannotDetermineAppDirectory)?;
+
+    let extension_dir = read_extension_dir()?;
+
+    let extension_descriptor: ExtensionDescriptor<E::Metadata> = read_extension_descriptor()?;
+
+    let platform = E::Platform::from_path(&args.platform_dir_path)
+        .map_err(Error::CannotCreatePlatformFromPath)?;
+
+    let target = context_target()?;
+
+    configure_color_output(platform.env());
+    let log = Logger::from_env(platform.env());
+
+    let detect_context = extension::DetectContext {
+        app_dir,
+        extension_dir,
+        target,
+        platform,
+        extension_descriptor,
+        log,
+    };
+
+    match extension.detect(detect_context)?.0 {
+        InnerDetectResult::Fail => Ok(exit_code::DETECT_DETECTION_FAILED),
+        InnerDetectResult::Pass {
+            build_plan,
+            build_plan_toml,
+        } => {
+            if let Some(build_plan_toml) = build_plan_toml {
+                write_toml_file(&build_plan_toml, args.build_plan_path)
+                    .map_err(Error::CannotWriteBuildPlan)?;
+            } else if let Some(build_plan) = build_plan {
+                write_toml_file(&build_plan, args.build_plan_path)
+                    .map_err(Error::CannotWriteBuildPlan)?;
+            }
+            Ok(exit_code::DETECT_DETECTION_PASSED)
+        }
+    }
+}
+
+/// Extension generate entry point for this framework.
+///
+/// Exposed only to allow for advanced use-cases where generate is programmatically invoked.
+#[doc(hidden)]
+pub fn libcnb_extension_runtime_generate<E: Extension>(
+    extension: &E,
+    args: GenerateArgs,
+) -> crate::Result<i32, E::Error> {
+    set_phase(Phase::Generate);
+
+    let app_dir = env::current_dir().map_err(Error::CannotDetermineAppDirectory)?;
+
+    let extension_dir = read_extension_dir()?;
+
+    let extension_descriptor: ExtensionDescriptor<E::Metadata> = read_extension_descriptor()?;
+
+    let platform = E::Platform::from_path(&args.platform_dir_path)
+        .map_err(Error::CannotCreatePlatformFromPath)?;
+
+    let buildpack_plan =
+        read_toml_file(args.buildpack_plan_path).map_err(Error::CannotReadBuildpackPlan)?;
+
+    let target = context_target()?;
+
+    configure_color_output(platform.env());
+    let log = Logger::from_env(platform.env());
+
+    let print_dockerfiles = print_generated_dockerfiles_enabled(platform.env());
+    let extension_id = extension_descriptor.extension.id.to_string();
+
+    let generate_context = GenerateContext {
+        app_dir,
+        extension_dir,
+        target,
+        platform,
+        buildpack_plan,
+        extension_descriptor,
+        log,
+    };
+
+    let generate_result = extension.generate(generate_context)?;
+    write_generate_result(&args.output_dir_path, &generate_result)?;
+
+    if print_dockerfiles {
+        print_generated_dockerfiles(&extension_id, &generate_result);
+    }
+
+    Ok(exit_code::GENERIC_SUCCESS)
+}
+
+fn print_generated_dockerfiles_enabled(platform_env: &Env) -> bool {
+    platform_env
+        .get_string_lossy(PRINT_GENERATED_DOCKERFILES_ENV_VAR_NAME)
+        .or_else(|| env::var(PRINT_GENERATED_DOCKERFILES_ENV_VAR_NAME).ok())
+        .is_some_and(|value| value == "true")
+}
+
+/// Prints the generated Dockerfiles to stdout, so that `libcnb-test` can parse them from the `pack`
+/// output.
+fn print_generated_dockerfiles(extension_id: &str, generate_result: &GenerateResult) {
+    for (file_name, dockerfile) in [
+        ("build.Dockerfile", &generate_result.build_dockerfile),
+        ("run.Dockerfile", &generate_result.run_dockerfile),
+    ] {
+        if let Some(dockerfile) = dockerfile {
+            print!(
+                "{}",
+                format_generated_dockerfile(extension_id, file_name, dockerfile)
+            );
+        }
+    }
+}
+
+/// Writes the Dockerfiles and `extend-config.toml` of the given [`GenerateResult`] to the given
+/// output directory, as defined by the CNB image extension spec.
+fn write_generate_result<E: Debug>(
+    output_dir: &Path,
+    generate_result: &GenerateResult,
+) -> crate::Result<(), E> {
+    if let Some(build_dockerfile) = &generate_result.build_dockerfile {
+        fs::write(output_dir.join("build.Dockerfile"), build_dockerfile)
+            .map_err(Error::CannotWriteBuildDockerfile)?;
+    }
+
+    if let Some(run_dockerfile) = &generate_result.run_dockerfile {
+        fs::write(output_dir.join("run.Dockerfile"), run_dockerfile)
+            .map_err(Error::CannotWriteRunDockerfile)?;
+    }
+
+    if !generate_result.extend_config.is_empty() {
+        write_toml_file(
+            &generate_result.extend_config,
+            output_dir.join("extend-config.toml"),
+        )
+        .map_err(Error::CannotWriteExtendConfig)?;
+    }
+
+    Ok(())
+}
+
 // A partial representation of buildpack.toml that contains only the Buildpack API version,
 // so that the version can still be read when the buildpack descriptor doesn't match the
 // supported spec version.
@@ -344,6 +817,77 @@ pub enum BuildArgsParseError {
     InvalidArguments,
 }
 
+#[doc(hidden)]
+pub struct ExtensionDetectArgs {
+    pub platform_dir_path: PathBuf,
+    pub build_plan_path: PathBuf,
+}
+
+impl ExtensionDetectArgs {
+    pub fn from_env() -> Result<Self, ExtensionArgsParseError> {
+        Ok(Self {
+            platform_dir_path: read_path_env_var("CNB_PLATFORM_DIR")?,
+            build_plan_path: read_path_env_var("CNB_BUILD_PLAN_PATH")?,
+        })
+    }
+}
+
+#[doc(hidden)]
+pub struct GenerateArgs {
+    pub platform_dir_path: PathBuf,
+    pub buildpack_plan_path: PathBuf,
+    pub output_dir_path: PathBuf,
+}
+
+impl GenerateArgs {
+    pub fn from_env() -> Result<Self, ExtensionArgsParseError> {
+        Ok(Self {
+            platform_dir_path: read_path_env_var("CNB_PLATFORM_DIR")?,
+            buildpack_plan_path: read_path_env_var("CNB_BP_PLAN_PATH")?,
+            output_dir_path: read_path_env_var("CNB_OUTPUT_DIR")?,
+        })
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[doc(hidden)]
+pub enum ExtensionArgsParseError {
+    #[error("Missing environment variable {0}")]
+    MissingEnvironmentVariable(&'static str),
+}
+
+/// Applies `NO_COLOR` and `CLICOLOR_FORCE` from the platform environment to the global color
+/// switch, since platforms can't set them in the environment of the buildpack process.
+fn configure_color_output(platform_env: &Env) {
+    if let Some(enabled) = color_output_from_env_vars(
+        platform_env.get(NO_COLOR_ENV_VAR_NAME).map(AsRef::as_ref),
+        platform_env
+            .get(CLICOLOR_FORCE_ENV_VAR_NAME)
+            .map(AsRef::as_ref),
+    ) {
+        set_color_output(enabled);
+    }
+}
+
+fn read_path_env_var(name: &'static str) -> Result<PathBuf, ExtensionArgsParseError> {
+    env::var_os(name)
+        .map(PathBuf::from)
+        .ok_or(ExtensionArgsParseError::MissingEnvironmentVariable(name))
+}
+
+fn read_extension_dir<E: Debug>() -> crate::Result<PathBuf, E> {
+    env::var("CNB_EXTENSION_DIR")
+        .map_err(Error::CannotDetermineExtensionDirectory)
+        .map(PathBuf::from)
+}
+
+fn read_extension_descriptor<ED: DeserializeOwned, E: Debug>() -> crate::Result<ED, E> {
+    read_extension_dir().and_then(|extension_dir| {
+        read_toml_file(extension_dir.join("extension.toml"))
+            .map_err(Error::CannotReadExtensionDescriptor)
+    })
+}
+
 fn read_buildpack_dir<E: Debug>() -> crate::Result<PathBuf, E> {
     env::var("CNB_BUILDPACK_DIR")
         .map_err(Error::CannotDetermineBuildpackDirectory)
@@ -357,6 +901,18 @@ fn read_buildpack_descriptor<BD: DeserializeOwned, E: Debug>() -> crate::Result<
     })
 }
 
+/// Merges the store modified via `BuildContext::store_mut` with the store passed to
+/// `BuildResultBuilder::store`. Values of the passed store take precedence.
+fn merge_stores(mutable_store: Option<Store>, store: Option<Store>) -> Option<Store> {
+    match (mutable_store, store) {
+        (Some(mut mutable_store), Some(store)) => {
+            mutable_store.metadata.extend(store.metadata);
+            Some(mutable_store)
+        }
+        (mutable_store, store) => store.or(mutable_store),
+    }
+}
+
 fn context_target<E>() -> crate::Result<Target, E>
 where
     E: Debug,
@@ -380,3 +936,158 @@ where
         distro_version,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extension::GenerateResultBuilder;
+    use libcnb_data::launch::{LaunchBuilder, ProcessBuilder};
+    use libcnb_data::process_type;
+    use tempfile::tempdir;
+
+    #[test]
+    fn merge_stores_prefers_passed_store() {
+        let mut mutable_store = Store::default();
+        mutable_store.set("java_version", "21").unwrap();
+        mutable_store.set("maven_version", "3.9.9").unwrap();
+
+        let mut store = Store::default();
+        store.set("java_version", "17").unwrap();
+
+        let merged_store = merge_stores(Some(mutable_store), Some(store)).unwrap();
+        assert_eq!(
+            merged_store
+                .get::<String>("java_version")
+                .unwrap()
+                .as_deref(),
+            Some("17")
+        );
+        assert_eq!(
+            merged_store
+                .get::<String>("maven_version")
+                .unwrap()
+                .as_deref(),
+            Some("3.9.9")
+        );
+
+        assert!(merge_stores(None, None).is_none());
+        assert!(merge_stores(Some(Store::default()), None).is_some());
+        assert!(merge_stores(None, Some(Store::default())).is_some());
+    }
+
+    #[test]
+    fn write_process_env_layer_with_env() {
+        let temp_dir = tempdir().unwrap();
+        let layers_dir = temp_dir.path();
+
+        let launch = LaunchBuilder::new()
+            .process(
+                ProcessBuilder::new(process_type!("web"), ["web"])
+                    .env("PORT", "5000")
+                    .build(),
+            )
+            .process(ProcessBuilder::new(process_type!("worker"), ["worker"]).build())
+            .build();
+
+        write_process_env_layer(layers_dir, &launch).unwrap();
+
+        let launch_env_dir = layers_dir.join("libcnb-process-env").join("env.launch");
+
+        assert_eq!(
+            fs::read_to_string(launch_env_dir.join("web").join("PORT.default")).unwrap(),
+            "5000"
+        );
+        assert!(!launch_env_dir.join("worker").exists());
+
+        let layer_content_metadata =
+            read_toml_file::<LayerContentMetadata>(layers_dir.join("libcnb-process-env.toml"))
+                .unwrap();
+
+        assert_eq!(
+            layer_content_metadata.types,
+            Some(LayerTypes {
+                launch: true,
+                build: false,
+                cache: false
+            })
+        );
+    }
+
+    #[test]
+    fn write_process_env_layer_without_env() {
+        let temp_dir = tempdir().unwrap();
+        let layers_dir = temp_dir.path();
+
+        let launch = LaunchBuilder::new()
+            .process(ProcessBuilder::new(process_type!("web"), ["web"]).build())
+            .build();
+
+        write_process_env_layer(layers_dir, &launch).unwrap();
+
+        assert!(!layers_dir.join("libcnb-process-env").exists());
+        assert!(!layers_dir.join("libcnb-process-env.toml").exists());
+    }
+
+    #[test]
+    fn find_legacy_bom_in_buildpack_written_files() {
+        let temp_dir = tempdir().unwrap();
+        let layers_dir = temp_dir.path();
+
+        assert_eq!(find_legacy_bom(layers_dir), None);
+
+        fs::write(
+            layers_dir.join("launch.toml"),
+            "[[processes]]\ntype = \"web\"\n",
+        )
+        .unwrap();
+        fs::write(layers_dir.join("build.toml"), "[[bom]]\nname = \"ruby\"\n").unwrap();
+        assert_eq!(
+            find_legacy_bom(layers_dir),
+            Some(layers_dir.join("build.toml"))
+        );
+
+        fs::write(layers_dir.join("launch.toml"), "[[bom]]\nname = \"ruby\"\n").unwrap();
+        assert_eq!(
+            find_legacy_bom(layers_dir),
+            Some(layers_dir.join("launch.toml"))
+        );
+    }
+
+    #[test]
+    fn write_generate_result_with_dockerfiles() {
+        let temp_dir = tempdir().unwrap();
+        let output_dir = temp_dir.path();
+
+        let generate_result = GenerateResultBuilder::new()
+            .build_dockerfile("ARG base_image\nFROM ${base_image}\n")
+            .run_dockerfile("ARG base_image\nFROM ${base_image}\nARG version\n")
+            .run_arg("version", "1.2.3")
+            .build_unwrapped();
+
+        write_generate_result::<()>(output_dir, &generate_result).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(output_dir.join("build.Dockerfile")).unwrap(),
+            "ARG base_image\nFROM ${base_image}\n"
+        );
+        assert_eq!(
+            fs::read_to_string(output_dir.join("run.Dockerfile")).unwrap(),
+            "ARG base_image\nFROM ${base_image}\nARG version\n"
+        );
+        assert_eq!(
+            fs::read_to_string(output_dir.join("extend-config.toml")).unwrap(),
+            "[[run.args]]\nname = \"version\"\nvalue = \"1.2.3\"\n"
+        );
+    }
+
+    #[test]
+    fn write_generate_result_empty() {
+        let temp_dir = tempdir().unwrap();
+        let output_dir = temp_dir.path();
+
+        write_generate_result::<()>(output_dir, &GenerateResultBuilder::new().build_unwrapped())
+            .unwrap();
+
+        assert_eq!(fs::read_dir(output_dir).unwrap().count(), 0);
+    }
+}
@@ -1,6 +1,8 @@
 //! Software Bill of Materials (SBOM) support.
 
 use libcnb_data::sbom::SbomFormat;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -22,16 +24,16 @@ pub struct Sbom {
 impl Sbom {
     /// Constructs an `Sbom` from the given path, treating it as the SBOM format specified.
     ///
-    /// Note that there is no validation performed by libcnb.rs, the CNB lifecycle will error at
-    /// runtime should the SBOM be invalid.
+    /// Note that the SBOM isn't validated here. Without the `sbom-validate` feature, the CNB
+    /// lifecycle will error at export time should the SBOM be invalid.
     pub fn from_path<P: AsRef<Path>>(format: SbomFormat, path: P) -> std::io::Result<Self> {
         fs::read(path.as_ref()).map(|data| Self { format, data })
     }
 
     /// Constructs an `Sbom` from the given bytes, treating it as the SBOM format specified.
     ///
-    /// Note that there is no validation performed by libcnb.rs, the CNB lifecycle will error at
-    /// runtime should the SBOM be invalid.
+    /// Note that the SBOM isn't validated here. Without the `sbom-validate` feature, the CNB
+    /// lifecycle will error at export time should the SBOM be invalid.
     pub fn from_bytes<D: Into<Vec<u8>>>(format: SbomFormat, data: D) -> Self {
         Self {
             format,
@@ -40,6 +42,331 @@ impl Sbom {
     }
 }
 
+#[cfg(feature = "sbom-validate")]
+impl Sbom {
+    /// Validates that the data of this SBOM is a document of its format.
+    ///
+    /// See [`SbomFormat::validate`] for the checks that are performed. With the `sbom-validate`
+    /// feature, libcnb validates all SBOMs before writing them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the SBOM data isn't a valid document of its format.
+    pub fn validate(&self) -> Result<(), libcnb_data::sbom::SbomValidationError> {
+        self.format.validate(&self.data)
+    }
+}
+
+impl Sbom {
+    /// Merges multiple SBOMs of the same format into a single SBOM.
+    ///
+    /// This is useful to emit a consolidated launch SBOM for a buildpack that also attaches
+    /// SBOMs to individual layers. The first SBOM is used as the base document; the elements of
+    /// all other SBOMs are appended to it:
+    ///
+    /// - `CycloneDX`: `components` (by `bom-ref`) and `dependencies`. The `dependsOn` of
+    ///   dependencies with the same `ref` are combined. The `serialNumber` of the base document is
+    ///   removed since the merged SBOM is a new document.
+    /// - SPDX: `packages` and `files` (by `SPDXID`), `relationships` and `documentDescribes`.
+    /// - Syft: `artifacts` and `files` (by `id`), as well as `artifactRelationships`.
+    ///
+    /// Identical elements are only included once. Since ids are only unique within a document,
+    /// an element whose id is already used by a different element of the merged SBOM gets a new
+    /// id, suffixed with the position of its SBOM, and all references to it within its SBOM are
+    /// updated accordingly.
+    ///
+    /// All other top-level data, such as document metadata, is taken from the first SBOM.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no SBOMs were given, the SBOMs have different formats or any SBOM
+    /// isn't a valid JSON document of its format.
+    ///
+    /// # Example
+    /// ```
+    /// use libcnb::data::sbom::SbomFormat;
+    /// use libcnb::sbom::Sbom;
+    ///
+    /// let runtime_sbom = Sbom::from_bytes(
+    ///     SbomFormat::CycloneDxJson,
+    ///     r#"{"bomFormat": "CycloneDX", "specVersion": "1.4", "components": [{"bom-ref": "pkg:generic/ruby@3.3.0", "type": "application", "name": "ruby"}]}"#,
+    /// );
+    ///
+    /// let gems_sbom = Sbom::from_bytes(
+    ///     SbomFormat::CycloneDxJson,
+    ///     r#"{"bomFormat": "CycloneDX", "specVersion": "1.4", "components": [{"bom-ref": "pkg:gem/rails@7.1.0", "type": "library", "name": "rails"}]}"#,
+    /// );
+    ///
+    /// let launch_sbom = Sbom::merge([runtime_sbom, gems_sbom]).unwrap();
+    /// ```
+    pub fn merge<I: IntoIterator<Item = Self>>(sboms: I) -> Result<Self, SbomMergeError> {
+        let mut sboms = sboms.into_iter();
+        let base_sbom = sboms.next().ok_or(SbomMergeError::NoSboms)?;
+        let format = base_sbom.format;
+
+        let mut merged_document = parse_sbom_document(&base_sbom.data)?;
+
+        if format == SbomFormat::CycloneDxJson {
+            merged_document.remove("serialNumber");
+        }
+
+        for (index, sbom) in sboms.enumerate() {
+            if sbom.format != format {
+                return Err(SbomMergeError::FormatMismatch {
+                    expected: format,
+                    actual: sbom.format,
+                });
+            }
+
+            let mut document = parse_sbom_document(&sbom.data)?;
+
+            // The base document is the first SBOM, so this document is the one at `index + 2`.
+            rename_clashing_ids(&format, &merged_document, &mut document, index + 2)?;
+
+            for (key, array_merge) in mergeable_arrays(&format) {
+                merge_array(&mut merged_document, &document, key, array_merge)?;
+            }
+        }
+
+        serde_json::to_vec(&merged_document)
+            .map(|data| Self { format, data })
+            .map_err(SbomMergeError::InvalidJson)
+    }
+}
+
+/// An error that occurred while merging SBOMs with [`Sbom::merge`].
+#[derive(thiserror::Error, Debug)]
+pub enum SbomMergeError {
+    #[error("No SBOMs to merge")]
+    NoSboms,
+    #[error("Cannot merge SBOMs of different formats: expected {expected:?}, got {actual:?}")]
+    FormatMismatch {
+        expected: SbomFormat,
+        actual: SbomFormat,
+    },
+    #[error("SBOM is not valid JSON: {0}")]
+    InvalidJson(serde_json::Error),
+    #[error("SBOM is not a JSON object")]
+    NotAnObject,
+    #[error("Expected {0} in SBOM to be an array")]
+    NotAnArray(String),
+}
+
+/// How the elements of a top-level array of an SBOM are merged.
+enum ArrayMerge {
+    /// Elements are identified by the id in the given key.
+    Identified(&'static str),
+    /// Elements with the same value in the first key are combined into one element, merging the
+    /// arrays in the second key.
+    Combined(&'static str, &'static str),
+    /// Elements are compared by their whole value.
+    Unidentified,
+}
+
+// The top-level arrays that are merged for each format.
+fn mergeable_arrays(format: &SbomFormat) -> &'static [(&'static str, ArrayMerge)] {
+    match format {
+        SbomFormat::CycloneDxJson => &[
+            ("components", ArrayMerge::Identified("bom-ref")),
+            ("dependencies", ArrayMerge::Combined("ref", "dependsOn")),
+        ],
+        SbomFormat::SpdxJson => &[
+            ("packages", ArrayMerge::Identified("SPDXID")),
+            ("files", ArrayMerge::Identified("SPDXID")),
+            ("relationships", ArrayMerge::Unidentified),
+            ("documentDescribes", ArrayMerge::Unidentified),
+        ],
+        SbomFormat::SyftJson => &[
+            ("artifacts", ArrayMerge::Identified("id")),
+            ("files", ArrayMerge::Identified("id")),
+            ("artifactRelationships", ArrayMerge::Unidentified),
+        ],
+    }
+}
+
+// The fields that refer to ids of elements for each format, as pairs of a top-level array and the
+// key of the field in its elements. A field is either a single id or an array of ids. Without a
+// key, the top-level array itself contains ids.
+fn id_references(format: &SbomFormat) -> &'static [(&'static str, Option<&'static str>)] {
+    match format {
+        SbomFormat::CycloneDxJson => &[
+            ("dependencies", Some("ref")),
+            ("dependencies", Some("dependsOn")),
+        ],
+        SbomFormat::SpdxJson => &[
+            ("relationships", Some("spdxElementId")),
+            ("relationships", Some("relatedSpdxElement")),
+            ("packages", Some("hasFiles")),
+            ("documentDescribes", None),
+        ],
+        SbomFormat::SyftJson => &[
+            ("artifactRelationships", Some("parent")),
+            ("artifactRelationships", Some("child")),
+        ],
+    }
+}
+
+fn parse_sbom_document(data: &[u8]) -> Result<Map<String, Value>, SbomMergeError> {
+    match serde_json::from_slice(data).map_err(SbomMergeError::InvalidJson)? {
+        Value::Object(document) => Ok(document),
+        _ => Err(SbomMergeError::NotAnObject),
+    }
+}
+
+fn array<'a>(document: &'a Map<String, Value>, key: &str) -> Result<&'a [Value], SbomMergeError> {
+    document.get(key).map_or(Ok(&[]), |value| {
+        value
+            .as_array()
+            .map(Vec::as_slice)
+            .ok_or_else(|| SbomMergeError::NotAnArray(String::from(key)))
+    })
+}
+
+/// Renames the ids of elements in `document` that are already used by a different element of
+/// `merged_document`, including all references to them within `document`.
+fn rename_clashing_ids(
+    format: &SbomFormat,
+    merged_document: &Map<String, Value>,
+    document: &mut Map<String, Value>,
+    document_number: usize,
+) -> Result<(), SbomMergeError> {
+    let mut renames = HashMap::new();
+
+    for (key, array_merge) in mergeable_arrays(format) {
+        let ArrayMerge::Identified(id_key) = array_merge else {
+            continue;
+        };
+
+        let merged_elements = array(merged_document, key)?;
+        let is_used = |id: &str| {
+            merged_elements
+                .iter()
+                .any(|element| element.get(id_key).and_then(Value::as_str) == Some(id))
+        };
+
+        for element in array(document, key)? {
+            let Some(id) = element.get(id_key).and_then(Value::as_str) else {
+                continue;
+            };
+
+            if is_used(id) && !merged_elements.contains(element) {
+                let mut new_id = format!("{id}-{document_number}");
+                while is_used(&new_id) {
+                    new_id = format!("{new_id}-{document_number}");
+                }
+                renames.insert(String::from(id), new_id);
+            }
+        }
+    }
+
+    if renames.is_empty() {
+        return Ok(());
+    }
+
+    let rename = |value: &mut Value| match value {
+        Value::String(id) => {
+            if let Some(new_id) = renames.get(id) {
+                new_id.clone_into(id);
+            }
+        }
+        Value::Array(ids) => {
+            for id in ids.iter_mut().filter_map(|id| match id {
+                Value::String(id) => Some(id),
+                _ => None,
+            }) {
+                if let Some(new_id) = renames.get(id) {
+                    new_id.clone_into(id);
+                }
+            }
+        }
+        _ => {}
+    };
+
+    let id_keys =
+        mergeable_arrays(format)
+            .iter()
+            .filter_map(|(key, array_merge)| match array_merge {
+                ArrayMerge::Identified(id_key) => Some((*key, Some(*id_key))),
+                _ => None,
+            });
+
+    for (key, field_key) in id_keys.chain(id_references(format).iter().copied()) {
+        let Some(value) = document.get_mut(key) else {
+            continue;
+        };
+
+        match field_key {
+            None => rename(value),
+            Some(field_key) => {
+                for element in value.as_array_mut().into_iter().flatten() {
+                    if let Some(field) = element.get_mut(field_key) {
+                        rename(field);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn merge_array(
+    target: &mut Map<String, Value>,
+    source: &Map<String, Value>,
+    key: &str,
+    array_merge: &ArrayMerge,
+) -> Result<(), SbomMergeError> {
+    let source_elements = array(source, key)?;
+    if source_elements.is_empty() {
+        return Ok(());
+    }
+
+    let target_elements = target
+        .entry(key)
+        .or_insert_with(|| Value::Array(Vec::new()))
+        .as_array_mut()
+        .ok_or_else(|| SbomMergeError::NotAnArray(String::from(key)))?;
+
+    for source_element in source_elements {
+        let target_element = target_elements
+            .iter_mut()
+            .find(|target_element| match array_merge {
+                ArrayMerge::Identified(id_key) | ArrayMerge::Combined(id_key, _) => source_element
+                    .get(id_key)
+                    .is_some_and(|source_id| target_element.get(id_key) == Some(source_id)),
+                ArrayMerge::Unidentified => *target_element == source_element,
+            });
+
+        match (target_element, array_merge) {
+            (None, _) => target_elements.push(source_element.clone()),
+            (Some(target_element), ArrayMerge::Combined(_, combined_key)) => {
+                let source_values = source_element
+                    .get(combined_key)
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten();
+
+                if let Some(Value::Array(target_values)) =
+                    target_element.as_object_mut().map(|target_element| {
+                        target_element
+                            .entry(*combined_key)
+                            .or_insert_with(|| Value::Array(Vec::new()))
+                    })
+                {
+                    for source_value in source_values {
+                        if !target_values.contains(source_value) {
+                            target_values.push(source_value.clone());
+                        }
+                    }
+                }
+            }
+            (Some(_), _) => {}
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(feature = "cyclonedx-bom")]
 impl TryFrom<cyclonedx_bom::models::bom::Bom> for Sbom {
     type Error = cyclonedx_bom::errors::JsonWriteError;
@@ -56,6 +383,44 @@ impl TryFrom<cyclonedx_bom::models::bom::Bom> for Sbom {
     }
 }
 
+/// Merges all given SBOMs of the same format with [`Sbom::merge`], keeping the order in which the
+/// formats first appeared. SBOMs that are the only ones of their format are kept as-is.
+///
+/// SBOMs that aren't JSON documents can't be merged. If any SBOM of a format isn't one, the last
+/// SBOM of that format is kept, the same way as SBOM files overwrote each other before merging
+/// was supported.
+pub(crate) fn merge_sboms_by_format<I: IntoIterator<Item = Sbom>>(
+    sboms: I,
+) -> Result<Vec<Sbom>, SbomMergeError> {
+    let mut sboms_by_format: Vec<(SbomFormat, Vec<Sbom>)> = Vec::new();
+
+    for sbom in sboms {
+        match sboms_by_format
+            .iter_mut()
+            .find(|(format, _)| *format == sbom.format)
+        {
+            Some((_, format_sboms)) => format_sboms.push(sbom),
+            None => sboms_by_format.push((sbom.format.clone(), vec![sbom])),
+        }
+    }
+
+    sboms_by_format
+        .into_iter()
+        .map(|(_, mut format_sboms)| {
+            let mergeable = format_sboms.len() > 1
+                && format_sboms
+                    .iter()
+                    .all(|sbom| parse_sbom_document(&sbom.data).is_ok());
+
+            if mergeable {
+                Sbom::merge(format_sboms)
+            } else {
+                Ok(format_sboms.remove(format_sboms.len() - 1))
+            }
+        })
+        .collect()
+}
+
 pub(crate) fn cnb_sbom_path<P: AsRef<Path>>(
     sbom_format: &SbomFormat,
     base_directory: P,
@@ -71,3 +436,266 @@ pub(crate) fn cnb_sbom_path<P: AsRef<Path>>(
         .as_ref()
         .join(format!("{base_name}.sbom.{suffix}"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sbom(format: SbomFormat, value: &Value) -> Sbom {
+        Sbom::from_bytes(format, serde_json::to_vec(value).unwrap())
+    }
+
+    fn parse(sbom: &Sbom) -> Value {
+        serde_json::from_slice(&sbom.data).unwrap()
+    }
+
+    #[test]
+    fn merge_cyclonedx() {
+        let merged = Sbom::merge([
+            sbom(
+                SbomFormat::CycloneDxJson,
+                &json!({
+                    "bomFormat": "CycloneDX",
+                    "serialNumber": "urn:uuid:3e671687-395b-41f5-a30f-a58921a69b79",
+                    "components": [{ "bom-ref": "ruby", "name": "ruby" }]
+                }),
+            ),
+            sbom(
+                SbomFormat::CycloneDxJson,
+                &json!({
+                    "bomFormat": "CycloneDX",
+                    "components": [
+                        { "bom-ref": "ruby", "name": "ruby" },
+                        { "bom-ref": "rails", "name": "rails" }
+                    ],
+                    "dependencies": [{ "ref": "rails", "dependsOn": ["ruby"] }]
+                }),
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(merged.format, SbomFormat::CycloneDxJson);
+        assert_eq!(
+            parse(&merged),
+            json!({
+                "bomFormat": "CycloneDX",
+                "components": [
+                    { "bom-ref": "ruby", "name": "ruby" },
+                    { "bom-ref": "rails", "name": "rails" }
+                ],
+                "dependencies": [{ "ref": "rails", "dependsOn": ["ruby"] }]
+            })
+        );
+    }
+
+    #[test]
+    fn merge_spdx() {
+        let relationship = json!({
+            "spdxElementId": "SPDXRef-DOCUMENT",
+            "relationshipType": "DESCRIBES",
+            "relatedSpdxElement": "SPDXRef-ruby"
+        });
+
+        let merged = Sbom::merge([
+            sbom(
+                SbomFormat::SpdxJson,
+                &json!({
+                    "spdxVersion": "SPDX-2.3",
+                    "packages": [{ "SPDXID": "SPDXRef-ruby" }],
+                    "relationships": [relationship]
+                }),
+            ),
+            sbom(
+                SbomFormat::SpdxJson,
+                &json!({
+                    "spdxVersion": "SPDX-2.3",
+                    "packages": [{ "SPDXID": "SPDXRef-rails" }],
+                    "relationships": [relationship]
+                }),
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            parse(&merged),
+            json!({
+                "spdxVersion": "SPDX-2.3",
+                "packages": [{ "SPDXID": "SPDXRef-ruby" }, { "SPDXID": "SPDXRef-rails" }],
+                "relationships": [relationship]
+            })
+        );
+    }
+
+    #[test]
+    fn merge_cyclonedx_combines_dependencies() {
+        let merged = Sbom::merge([
+            sbom(
+                SbomFormat::CycloneDxJson,
+                &json!({ "dependencies": [{ "ref": "app", "dependsOn": ["ruby"] }] }),
+            ),
+            sbom(
+                SbomFormat::CycloneDxJson,
+                &json!({ "dependencies": [
+                    { "ref": "app", "dependsOn": ["ruby", "rails"] },
+                    { "ref": "rails" }
+                ] }),
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            parse(&merged),
+            json!({ "dependencies": [
+                { "ref": "app", "dependsOn": ["ruby", "rails"] },
+                { "ref": "rails" }
+            ] })
+        );
+    }
+
+    #[test]
+    fn merge_renames_clashing_ids() {
+        let spdx = |name: &str| {
+            sbom(
+                SbomFormat::SpdxJson,
+                &json!({
+                    "packages": [{ "SPDXID": "SPDXRef-Package-1", "name": name }],
+                    "documentDescribes": ["SPDXRef-Package-1"],
+                    "relationships": [{
+                        "spdxElementId": "SPDXRef-DOCUMENT",
+                        "relationshipType": "DESCRIBES",
+                        "relatedSpdxElement": "SPDXRef-Package-1"
+                    }]
+                }),
+            )
+        };
+
+        let merged = Sbom::merge([spdx("ruby"), spdx("rails"), spdx("ruby")]).unwrap();
+
+        assert_eq!(
+            parse(&merged),
+            json!({
+                "packages": [
+                    { "SPDXID": "SPDXRef-Package-1", "name": "ruby" },
+                    { "SPDXID": "SPDXRef-Package-1-2", "name": "rails" }
+                ],
+                "documentDescribes": ["SPDXRef-Package-1", "SPDXRef-Package-1-2"],
+                "relationships": [
+                    {
+                        "spdxElementId": "SPDXRef-DOCUMENT",
+                        "relationshipType": "DESCRIBES",
+                        "relatedSpdxElement": "SPDXRef-Package-1"
+                    },
+                    {
+                        "spdxElementId": "SPDXRef-DOCUMENT",
+                        "relationshipType": "DESCRIBES",
+                        "relatedSpdxElement": "SPDXRef-Package-1-2"
+                    }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn merge_syft() {
+        let merged = Sbom::merge([
+            sbom(SbomFormat::SyftJson, &json!({ "schema": {} })),
+            sbom(
+                SbomFormat::SyftJson,
+                &json!({ "artifacts": [{ "id": "a" }], "files": [{ "id": "f" }] }),
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            parse(&merged),
+            json!({ "schema": {}, "artifacts": [{ "id": "a" }], "files": [{ "id": "f" }] })
+        );
+    }
+
+    #[test]
+    fn merge_single() {
+        let merged = Sbom::merge([sbom(
+            SbomFormat::SyftJson,
+            &json!({ "artifacts": [{ "id": "a" }] }),
+        )])
+        .unwrap();
+
+        assert_eq!(parse(&merged), json!({ "artifacts": [{ "id": "a" }] }));
+    }
+
+    #[test]
+    fn merge_errors() {
+        assert!(matches!(
+            Sbom::merge(Vec::new()),
+            Err(SbomMergeError::NoSboms)
+        ));
+
+        assert!(matches!(
+            Sbom::merge([
+                sbom(SbomFormat::SyftJson, &json!({})),
+                sbom(SbomFormat::SpdxJson, &json!({}))
+            ]),
+            Err(SbomMergeError::FormatMismatch { .. })
+        ));
+
+        assert!(matches!(
+            Sbom::merge([Sbom::from_bytes(SbomFormat::SyftJson, "not json")]),
+            Err(SbomMergeError::InvalidJson(_))
+        ));
+
+        assert!(matches!(
+            Sbom::merge([sbom(SbomFormat::SyftJson, &json!([]))]),
+            Err(SbomMergeError::NotAnObject)
+        ));
+
+        assert!(matches!(
+            Sbom::merge([
+                sbom(SbomFormat::SyftJson, &json!({ "artifacts": {} })),
+                sbom(SbomFormat::SyftJson, &json!({ "artifacts": [] }))
+            ]),
+            Err(SbomMergeError::NotAnArray(_))
+        ));
+    }
+
+    #[test]
+    fn merge_sboms_by_format_groups_formats() {
+        let cyclonedx_a = sbom(
+            SbomFormat::CycloneDxJson,
+            &json!({"bomFormat": "CycloneDX", "components": [{"bom-ref": "a"}]}),
+        );
+        let syft = sbom(SbomFormat::SyftJson, &json!({"artifacts": [{"id": "x"}]}));
+        let cyclonedx_b = sbom(
+            SbomFormat::CycloneDxJson,
+            &json!({"bomFormat": "CycloneDX", "components": [{"bom-ref": "b"}]}),
+        );
+
+        let merged = merge_sboms_by_format([cyclonedx_a, syft.clone(), cyclonedx_b]).unwrap();
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].format, SbomFormat::CycloneDxJson);
+        assert_eq!(
+            parse(&merged[0])["components"],
+            json!([{"bom-ref": "a"}, {"bom-ref": "b"}])
+        );
+        assert_eq!(merged[1].data, syft.data);
+    }
+
+    #[test]
+    fn merge_sboms_by_format_keeps_last_non_json_sbom() {
+        let spdx = sbom(
+            SbomFormat::SpdxJson,
+            &json!({"packages": [{"SPDXID": "a"}]}),
+        );
+
+        let merged = merge_sboms_by_format([
+            spdx,
+            Sbom::from_bytes(SbomFormat::SpdxJson, "not json"),
+            Sbom::from_bytes(SbomFormat::SpdxJson, "also not json"),
+        ])
+        .unwrap();
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].data, b"also not json");
+    }
+}
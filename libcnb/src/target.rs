@@ -1,3 +1,8 @@
+/// The target the buildpack runs on, as provided by the CNB lifecycle via the `CNB_TARGET_*`
+/// environment variables.
+///
+/// Available via [`BuildContext::target`](crate::build::BuildContext::target) and
+/// [`DetectContext::target`](crate::detect::DetectContext::target).
 #[derive(Clone, Debug)]
 pub struct Target {
     /// The name of the target operating system.
@@ -38,3 +43,158 @@ pub struct Target {
     /// 2. Or else, the `VERSION_ID` field of the `/etc/os-release` file in the build image.
     pub distro_version: String,
 }
+
+impl Target {
+    /// Returns whether this target matches the given [`TargetSpec`].
+    ///
+    /// Follows the matching rules the CNB lifecycle applies to the `[[targets]]` of buildpacks:
+    /// fields of the spec that are unset, empty or `*` match any value. Likewise, fields the target
+    /// doesn't provide, i.e. an absent architecture variant or an empty distribution, match any
+    /// value of the spec. All other fields have to be equal.
+    ///
+    /// ```
+    /// use libcnb::{Target, TargetSpec};
+    ///
+    /// let target = Target {
+    ///     os: String::from("linux"),
+    ///     arch: String::from("arm64"),
+    ///     arch_variant: Some(String::from("v8")),
+    ///     distro_name: String::from("ubuntu"),
+    ///     distro_version: String::from("24.04"),
+    /// };
+    ///
+    /// assert!(target.matches(&TargetSpec {
+    ///     arch: Some(String::from("arm64")),
+    ///     distro_name: Some(String::from("*")),
+    ///     ..TargetSpec::default()
+    /// }));
+    /// ```
+    #[must_use]
+    pub fn matches(&self, spec: &TargetSpec) -> bool {
+        fn field_matches(spec_value: Option<&String>, value: Option<&String>) -> bool {
+            match (spec_value.map(String::as_str), value.map(String::as_str)) {
+                (None | Some("" | "*"), _) | (_, None | Some("")) => true,
+                (Some(spec_value), Some(value)) => value == spec_value,
+            }
+        }
+
+        field_matches(spec.os.as_ref(), Some(&self.os))
+            && field_matches(spec.arch.as_ref(), Some(&self.arch))
+            && field_matches(spec.arch_variant.as_ref(), self.arch_variant.as_ref())
+            && field_matches(spec.distro_name.as_ref(), Some(&self.distro_name))
+            && field_matches(spec.distro_version.as_ref(), Some(&self.distro_version))
+    }
+}
+
+/// A specification of targets, as used by [`Target::matches`] and the `target_matches` methods of
+/// [`BuildContext`](crate::build::BuildContext::target_matches) and
+/// [`DetectContext`](crate::detect::DetectContext::target_matches).
+///
+/// Fields that are `None`, empty or `*` match any value, like the fields of `[[targets]]` in
+/// `buildpack.toml`. Fields the target doesn't provide match any value as well.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TargetSpec {
+    pub os: Option<String>,
+    pub arch: Option<String>,
+    pub arch_variant: Option<String>,
+    pub distro_name: Option<String>,
+    pub distro_version: Option<String>,
+}
+
+#[cfg(feature = "inventory")]
+impl TryFrom<&Target> for libcnb_data::inventory::artifact::Os {
+    type Error = libcnb_data::inventory::artifact::UnsupportedOsError;
+
+    fn try_from(target: &Target) -> Result<Self, Self::Error> {
+        target.os.parse()
+    }
+}
+
+#[cfg(feature = "inventory")]
+impl TryFrom<&Target> for libcnb_data::inventory::artifact::Arch {
+    type Error = libcnb_data::inventory::artifact::UnsupportedArchError;
+
+    fn try_from(target: &Target) -> Result<Self, Self::Error> {
+        target.arch.parse()
+    }
+}
+
+#[cfg(feature = "inventory")]
+impl From<&Target> for libcnb_data::inventory::artifact::Distro {
+    fn from(target: &Target) -> Self {
+        Self::new(&target.distro_name, &target.distro_version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(arch: &str, arch_variant: Option<&str>) -> Target {
+        Target {
+            os: String::from("linux"),
+            arch: String::from(arch),
+            arch_variant: arch_variant.map(String::from),
+            distro_name: String::from("ubuntu"),
+            distro_version: String::from("24.04"),
+        }
+    }
+
+    #[test]
+    fn matches() {
+        fn spec(fields: [Option<&str>; 5]) -> TargetSpec {
+            let [os, arch, arch_variant, distro_name, distro_version] =
+                fields.map(|field| field.map(String::from));
+            TargetSpec {
+                os,
+                arch,
+                arch_variant,
+                distro_name,
+                distro_version,
+            }
+        }
+
+        let amd64 = target("amd64", None);
+        let arm64 = target("arm64", Some("v8"));
+
+        assert!(amd64.matches(&TargetSpec::default()));
+        assert!(arm64.matches(&spec([
+            Some("linux"),
+            Some("arm64"),
+            Some("v8"),
+            Some("ubuntu"),
+            Some("24.04")
+        ])));
+        assert!(arm64.matches(&spec([Some("*"), Some("arm64"), Some(""), Some("*"), None])));
+        assert!(amd64.matches(&spec([None, Some("amd64"), Some("*"), None, None])));
+
+        assert!(!amd64.matches(&spec([None, Some("arm64"), None, None, None])));
+        assert!(amd64.matches(&spec([None, None, Some("v8"), None, None])));
+        assert!(Target {
+            distro_name: String::new(),
+            distro_version: String::new(),
+            ..arm64.clone()
+        }
+        .matches(&spec([None, None, None, Some("ubuntu"), Some("22.04")])));
+
+        assert!(!arm64.matches(&spec([Some("windows"), None, None, None, None])));
+        assert!(!arm64.matches(&spec([None, None, None, Some("ubuntu"), Some("22.04")])));
+    }
+
+    #[cfg(feature = "inventory")]
+    #[test]
+    fn inventory_conversions() {
+        use libcnb_data::inventory::artifact::{Arch, Distro, Os};
+
+        let target = target("arm64", Some("v8"));
+        assert_eq!(Os::try_from(&target).unwrap(), Os::Linux);
+        assert_eq!(Arch::try_from(&target).unwrap(), Arch::Arm64);
+        assert_eq!(Distro::from(&target), Distro::new("ubuntu", "24.04"));
+
+        assert!(Arch::try_from(&Target {
+            arch: String::from("riscv64"),
+            ..target
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,238 @@
+use std::borrow::Cow;
+use std::fmt::{Debug, Formatter};
+
+/// Handle for adding user-defined spans and events to the trace libcnb records for the detect
+/// and build phases.
+///
+/// Spans created with this handle are children of libcnb's root span for the current phase and
+/// are exported together with it. Without the `trace` feature, all methods are no-ops, so
+/// buildpacks can instrument their code unconditionally.
+///
+/// Use [`DetectContext::tracer`](crate::detect::DetectContext::tracer) or
+/// [`BuildContext::tracer`](crate::build::BuildContext::tracer) to get the handle.
+///
+/// # Example
+/// ```
+/// use libcnb::BuildpackTracer;
+///
+/// fn install_dependencies(tracer: &BuildpackTracer) {
+///     let span = tracer.start_span("install-dependencies");
+///     span.set_attribute("package_manager", "npm");
+///     // ...
+///     span.add_event("dependencies-installed");
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct BuildpackTracer {
+    #[cfg(feature = "trace")]
+    parent: Option<SpanHandle>,
+}
+
+impl BuildpackTracer {
+    #[cfg(feature = "trace")]
+    pub(crate) fn new(root: SpanHandle) -> Self {
+        Self { parent: Some(root) }
+    }
+
+    /// Starts a new span as a child of the root span of the current phase.
+    ///
+    /// The span ends when the returned [`TraceSpan`] is dropped or [`TraceSpan::end`] is called.
+    pub fn start_span(&self, name: impl Into<Cow<'static, str>>) -> TraceSpan {
+        #[cfg(feature = "trace")]
+        return TraceSpan {
+            span: self.parent.as_ref().map(|parent| parent.start_child(name)),
+        };
+
+        #[cfg(not(feature = "trace"))]
+        {
+            let _ = name.into();
+            TraceSpan {}
+        }
+    }
+
+    /// Runs the given function in a new span that is a child of the root span of the current
+    /// phase. The span ends when the function returns.
+    pub fn in_span<T>(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        f: impl FnOnce(&TraceSpan) -> T,
+    ) -> T {
+        let span = self.start_span(name);
+        f(&span)
+    }
+
+    /// Adds a named event to the root span of the current phase.
+    pub fn add_event(&self, name: impl Into<Cow<'static, str>>) {
+        #[cfg(feature = "trace")]
+        if let Some(parent) = &self.parent {
+            parent.add_event(name);
+        }
+
+        #[cfg(not(feature = "trace"))]
+        let _ = name.into();
+    }
+}
+
+impl Debug for BuildpackTracer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BuildpackTracer").finish_non_exhaustive()
+    }
+}
+
+/// A user-defined span, created with [`BuildpackTracer::start_span`].
+///
+/// The span ends when it is dropped. Without the `trace` feature, all methods are no-ops.
+#[must_use]
+pub struct TraceSpan {
+    #[cfg(feature = "trace")]
+    span: Option<SpanHandle>,
+}
+
+impl TraceSpan {
+    /// Starts a new span as a child of this span.
+    pub fn start_span(&self, name: impl Into<Cow<'static, str>>) -> TraceSpan {
+        #[cfg(feature = "trace")]
+        return TraceSpan {
+            span: self.span.as_ref().map(|span| span.start_child(name)),
+        };
+
+        #[cfg(not(feature = "trace"))]
+        {
+            let _ = name.into();
+            TraceSpan {}
+        }
+    }
+
+    /// Adds a named event to this span.
+    pub fn add_event(&self, name: impl Into<Cow<'static, str>>) {
+        #[cfg(feature = "trace")]
+        if let Some(span) = &self.span {
+            span.add_event(name);
+        }
+
+        #[cfg(not(feature = "trace"))]
+        let _ = name.into();
+    }
+
+    /// Sets an attribute on this span, replacing an existing attribute with the same key.
+    pub fn set_attribute(
+        &self,
+        key: impl Into<Cow<'static, str>>,
+        value: impl Into<Cow<'static, str>>,
+    ) {
+        #[cfg(feature = "trace")]
+        if let Some(span) = &self.span {
+            use opentelemetry::trace::TraceContextExt;
+
+            span.context
+                .span()
+                .set_attribute(opentelemetry::KeyValue::new(key.into(), value.into()));
+        }
+
+        #[cfg(not(feature = "trace"))]
+        let _ = (key.into(), value.into());
+    }
+
+    /// Sets the status of this span to error and records the error as an exception event.
+    pub fn record_error(&self, error: &dyn std::error::Error) {
+        #[cfg(feature = "trace")]
+        if let Some(span) = &self.span {
+            span.set_error(error);
+        }
+
+        #[cfg(not(feature = "trace"))]
+        let _ = error;
+    }
+
+    /// Ends this span. Equivalent to dropping it.
+    pub fn end(self) {}
+}
+
+impl Drop for TraceSpan {
+    fn drop(&mut self) {
+        #[cfg(feature = "trace")]
+        if let Some(span) = &self.span {
+            span.end();
+        }
+    }
+}
+
+impl Debug for TraceSpan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TraceSpan").finish_non_exhaustive()
+    }
+}
+
+/// A span and the tracer to start child spans with.
+#[cfg(feature = "trace")]
+#[derive(Clone)]
+pub(crate) struct SpanHandle {
+    tracer: opentelemetry_sdk::trace::Tracer,
+    context: opentelemetry::Context,
+}
+
+#[cfg(feature = "trace")]
+impl SpanHandle {
+    pub(crate) fn new(
+        tracer: opentelemetry_sdk::trace::Tracer,
+        span: opentelemetry_sdk::trace::Span,
+    ) -> Self {
+        use opentelemetry::trace::TraceContextExt;
+
+        Self {
+            tracer,
+            context: opentelemetry::Context::new().with_span(span),
+        }
+    }
+
+    fn start_child(&self, name: impl Into<Cow<'static, str>>) -> SpanHandle {
+        use opentelemetry::trace::{TraceContextExt, Tracer};
+
+        let span = self.tracer.start_with_context(name, &self.context);
+        SpanHandle {
+            tracer: self.tracer.clone(),
+            context: self.context.with_span(span),
+        }
+    }
+
+    pub(crate) fn add_event(&self, name: impl Into<Cow<'static, str>>) {
+        use opentelemetry::trace::TraceContextExt;
+
+        self.context.span().add_event(name, Vec::new());
+    }
+
+    pub(crate) fn set_error(&self, error: &dyn std::error::Error) {
+        use opentelemetry::trace::{Status, TraceContextExt};
+
+        let span = self.context.span();
+        span.set_status(Status::error(format!("{error:?}")));
+        span.record_error(error);
+    }
+
+    pub(crate) fn end(&self) {
+        use opentelemetry::trace::TraceContextExt;
+
+        self.context.span().end();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BuildpackTracer;
+    use std::io::Error;
+
+    #[test]
+    fn default_tracer_is_noop() {
+        let tracer = BuildpackTracer::default();
+        tracer.add_event("event");
+
+        let result = tracer.in_span("span", |span| {
+            span.set_attribute("key", String::from("value"));
+            span.add_event("event");
+            span.start_span("nested")
+                .record_error(&Error::other("error"));
+            42
+        });
+        assert_eq!(result, 42);
+    }
+}
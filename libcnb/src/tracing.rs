@@ -1,11 +1,12 @@
+use crate::tracer::{BuildpackTracer, SpanHandle};
 use libcnb_data::buildpack::Buildpack;
 use opentelemetry::{
     global,
-    trace::{Span as SpanTrait, Status, Tracer, TracerProvider as TracerProviderTrait},
+    trace::{Span as SpanTrait, Tracer, TracerProvider as TracerProviderTrait},
     KeyValue,
 };
 use opentelemetry_sdk::{
-    trace::{Config, Span, TracerProvider},
+    trace::{Config, TracerProvider},
     Resource,
 };
 use std::{io::BufWriter, path::Path};
@@ -19,55 +20,146 @@ use std::{io::BufWriter, path::Path};
 #[cfg(target_family = "unix")]
 const TELEMETRY_EXPORT_ROOT: &str = "/tmp/libcnb-telemetry";
 
+/// The exporter for the traces libcnb records for the detect and build phases.
+///
+/// Configure it with [`Buildpack::TRACE_EXPORTER`](crate::Buildpack::TRACE_EXPORTER). Requires the
+/// `trace` feature, the OTLP exporters additionally require the `trace-otlp` feature.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[non_exhaustive]
+pub enum TraceExporter {
+    /// Selects the exporter with the standard OpenTelemetry environment variables, which allows
+    /// platform operators to configure the export for all buildpacks of a build:
+    ///
+    /// - `OTEL_TRACES_EXPORTER=none` disables the export.
+    /// - `OTEL_TRACES_EXPORTER=otlp`, `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` or
+    ///   `OTEL_EXPORTER_OTLP_ENDPOINT` select the OTLP exporter, if the `trace-otlp` feature is
+    ///   enabled. The protocol is read from `OTEL_EXPORTER_OTLP_TRACES_PROTOCOL` or
+    ///   `OTEL_EXPORTER_OTLP_PROTOCOL` (`grpc` or `http/protobuf`, the default).
+    /// - Otherwise, traces are written to a file, see [`TraceExporter::File`].
+    #[default]
+    FromEnv,
+    /// Writes traces as JSON lines to a file in `/tmp/libcnb-telemetry`, from where services
+    /// such as <https://github.com/heroku/cnb-otel-collector> can export them.
+    File,
+    /// Exports traces to an OpenTelemetry collector via OTLP.
+    ///
+    /// The `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` and `OTEL_EXPORTER_OTLP_ENDPOINT` environment
+    /// variables take precedence over the given endpoint. Without an endpoint, the default
+    /// endpoint of the protocol on `localhost` is used.
+    #[cfg(feature = "trace-otlp")]
+    Otlp {
+        protocol: OtlpProtocol,
+        endpoint: Option<&'static str>,
+    },
+    /// Disables the export of traces.
+    None,
+}
+
+/// The protocol of the OTLP trace exporter.
+#[cfg(feature = "trace-otlp")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum OtlpProtocol {
+    /// OTLP/gRPC.
+    Grpc,
+    /// OTLP/HTTP with binary protobuf payloads.
+    HttpProtobuf,
+}
+
+/// Resolves [`TraceExporter::FromEnv`] with the given environment variable lookup function.
+fn resolve_trace_exporter(
+    exporter: TraceExporter,
+    env_var: impl Fn(&str) -> Option<String>,
+) -> TraceExporter {
+    if exporter != TraceExporter::FromEnv {
+        return exporter;
+    }
+
+    let traces_exporter = env_var("OTEL_TRACES_EXPORTER");
+    if traces_exporter.as_deref() == Some("none") {
+        return TraceExporter::None;
+    }
+
+    #[cfg(feature = "trace-otlp")]
+    if traces_exporter.as_deref() == Some("otlp")
+        || env_var("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT").is_some()
+        || env_var("OTEL_EXPORTER_OTLP_ENDPOINT").is_some()
+    {
+        let protocol = env_var("OTEL_EXPORTER_OTLP_TRACES_PROTOCOL")
+            .or_else(|| env_var("OTEL_EXPORTER_OTLP_PROTOCOL"));
+
+        return TraceExporter::Otlp {
+            protocol: if protocol.as_deref() == Some("grpc") {
+                OtlpProtocol::Grpc
+            } else {
+                OtlpProtocol::HttpProtobuf
+            },
+            endpoint: None,
+        };
+    }
+
+    TraceExporter::File
+}
+
 /// Represents an OpenTelemetry tracer provider and single span tracing
 /// a single CNB build or detect phase.
 pub(crate) struct BuildpackTrace {
     provider: TracerProvider,
-    span: Span,
+    root: SpanHandle,
+    // The runtime of the batch exporter used for OTLP. Must be dropped after the provider has
+    // been shut down.
+    #[cfg(feature = "trace-otlp")]
+    _runtime: Option<tokio::runtime::Runtime>,
 }
 
-/// Start an OpenTelemetry trace and span that exports to an
-/// OpenTelemetry file export. The resulting trace provider and span are
-/// enriched with data from the buildpack and the rust environment.
-pub(crate) fn start_trace(buildpack: &Buildpack, phase_name: &'static str) -> BuildpackTrace {
+/// Start an OpenTelemetry trace and span that exports with the given
+/// exporter. The resulting trace provider and span are enriched with data
+/// from the buildpack and the rust environment.
+pub(crate) fn start_trace(
+    buildpack: &Buildpack,
+    phase_name: &'static str,
+    exporter: TraceExporter,
+) -> BuildpackTrace {
     let trace_name = format!(
         "{}-{phase_name}",
         buildpack.id.replace(['/', '.', '-'], "_")
     );
-    let tracing_file_path = Path::new(TELEMETRY_EXPORT_ROOT).join(format!("{trace_name}.jsonl"));
 
-    // Ensure tracing file path parent exists by creating it.
-    if let Some(parent_dir) = tracing_file_path.parent() {
-        let _ = std::fs::create_dir_all(parent_dir);
-    }
-    let exporter = match std::fs::File::options()
-        .create(true)
-        .append(true)
-        .open(&tracing_file_path)
-    {
-        // Write tracing data to a file, which may be read by other
-        // services. Wrap with a BufWriter to prevent serde from sending each
-        // JSON token to IO, and instead send entire JSON objects to IO.
-        Ok(file) => opentelemetry_stdout::SpanExporter::builder()
-            .with_writer(BufWriter::new(file))
-            .build(),
-        // Failed tracing shouldn't fail a build, and any logging here would
-        // likely confuse the user, so send telemetry to /dev/null on errors.
-        Err(_) => opentelemetry_stdout::SpanExporter::builder()
-            .with_writer(std::io::sink())
-            .build(),
-    };
-
-    let provider = TracerProvider::builder()
-        .with_simple_exporter(exporter)
-        .with_config(Config::default().with_resource(Resource::new([
+    let builder =
+        TracerProvider::builder().with_config(Config::default().with_resource(Resource::new([
             // Associate the tracer provider with service attributes. The buildpack
             // name/version seems to map well to the suggestion here
             // https://opentelemetry.io/docs/specs/semconv/resource/#service.
             KeyValue::new("service.name", buildpack.id.to_string()),
             KeyValue::new("service.version", buildpack.version.to_string()),
-        ])))
-        .build();
+        ])));
+
+    #[cfg(feature = "trace-otlp")]
+    let mut runtime = None;
+
+    let provider = match resolve_trace_exporter(exporter, |name| std::env::var(name).ok()) {
+        TraceExporter::FromEnv | TraceExporter::File => builder
+            .with_simple_exporter(file_exporter(&trace_name))
+            .build(),
+        #[cfg(feature = "trace-otlp")]
+        TraceExporter::Otlp { protocol, endpoint } => {
+            match otlp::start_runtime_and_exporter(protocol, endpoint) {
+                Some((otlp_runtime, exporter)) => {
+                    // The batch exporter spawns its export task on the runtime.
+                    let provider = {
+                        let _guard = otlp_runtime.enter();
+                        builder
+                            .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+                            .build()
+                    };
+                    runtime = Some(otlp_runtime);
+                    provider
+                }
+                // Failed tracing shouldn't fail a build, see `file_exporter`.
+                None => builder.with_simple_exporter(sink_exporter()).build(),
+            }
+        }
+        TraceExporter::None => builder.with_simple_exporter(sink_exporter()).build(),
+    };
 
     // Set the global tracer provider so that buildpacks may use it.
     global::set_tracer_provider(provider.clone());
@@ -91,26 +183,108 @@ pub(crate) fn start_trace(buildpack: &Buildpack, phase_name: &'static str) -> Bu
             "buildpack_homepage",
             buildpack.homepage.clone().unwrap_or_default(),
         ),
+        KeyValue::new("buildpack_phase", phase_name),
     ]);
-    BuildpackTrace { provider, span }
+    BuildpackTrace {
+        provider,
+        root: SpanHandle::new(tracer, span),
+        #[cfg(feature = "trace-otlp")]
+        _runtime: runtime,
+    }
+}
+
+fn file_exporter(trace_name: &str) -> opentelemetry_stdout::SpanExporter {
+    let tracing_file_path = Path::new(TELEMETRY_EXPORT_ROOT).join(format!("{trace_name}.jsonl"));
+
+    // Ensure tracing file path parent exists by creating it.
+    if let Some(parent_dir) = tracing_file_path.parent() {
+        let _ = std::fs::create_dir_all(parent_dir);
+    }
+    match std::fs::File::options()
+        .create(true)
+        .append(true)
+        .open(&tracing_file_path)
+    {
+        // Write tracing data to a file, which may be read by other
+        // services. Wrap with a BufWriter to prevent serde from sending each
+        // JSON token to IO, and instead send entire JSON objects to IO.
+        Ok(file) => opentelemetry_stdout::SpanExporter::builder()
+            .with_writer(BufWriter::new(file))
+            .build(),
+        // Failed tracing shouldn't fail a build, and any logging here would
+        // likely confuse the user, so send telemetry to /dev/null on errors.
+        Err(_) => sink_exporter(),
+    }
+}
+
+fn sink_exporter() -> opentelemetry_stdout::SpanExporter {
+    opentelemetry_stdout::SpanExporter::builder()
+        .with_writer(std::io::sink())
+        .build()
+}
+
+#[cfg(feature = "trace-otlp")]
+mod otlp {
+    use super::OtlpProtocol;
+    use opentelemetry_otlp::WithExportConfig;
+
+    /// Starts the runtime for the OTLP exporter and creates the exporter on it. Returns `None` if
+    /// either failed, i.e. because of an invalid endpoint.
+    pub(super) fn start_runtime_and_exporter(
+        protocol: OtlpProtocol,
+        endpoint: Option<&'static str>,
+    ) -> Option<(tokio::runtime::Runtime, opentelemetry_otlp::SpanExporter)> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .ok()?;
+
+        // The gRPC exporter must be created on the runtime.
+        let exporter = {
+            let _guard = runtime.enter();
+            match protocol {
+                OtlpProtocol::Grpc => {
+                    let mut builder = opentelemetry_otlp::new_exporter().tonic();
+                    if let Some(endpoint) = endpoint {
+                        builder = builder.with_endpoint(endpoint);
+                    }
+                    builder.build_span_exporter()
+                }
+                OtlpProtocol::HttpProtobuf => {
+                    let mut builder = opentelemetry_otlp::new_exporter().http();
+                    if let Some(endpoint) = endpoint {
+                        builder = builder.with_endpoint(endpoint);
+                    }
+                    builder.build_span_exporter()
+                }
+            }
+        }
+        .ok()?;
+
+        Some((runtime, exporter))
+    }
 }
 
 impl BuildpackTrace {
     /// Set the status for the underlying span to error, and record
     /// an exception on the span.
     pub(crate) fn set_error(&mut self, err: &dyn std::error::Error) {
-        self.span.set_status(Status::error(format!("{err:?}")));
-        self.span.record_error(err);
+        self.root.set_error(err);
     }
     /// Add a named event to the underlying span.
     pub(crate) fn add_event(&mut self, name: &'static str) {
-        self.span.add_event(name, Vec::new());
+        self.root.add_event(name);
+    }
+    /// Returns a handle for creating user-defined spans and events under the underlying span.
+    pub(crate) fn tracer(&self) -> BuildpackTracer {
+        BuildpackTracer::new(self.root.clone())
     }
 }
 
 impl Drop for BuildpackTrace {
     fn drop(&mut self) {
-        self.span.end();
+        self.root.end();
         self.provider.force_flush();
         global::shutdown_tracer_provider();
     }
@@ -118,17 +292,13 @@ impl Drop for BuildpackTrace {
 
 #[cfg(test)]
 mod tests {
-    use super::start_trace;
+    use super::{resolve_trace_exporter, start_trace, TraceExporter};
     use libcnb_data::{
         buildpack::{Buildpack, BuildpackVersion},
         buildpack_id,
     };
     use serde_json::Value;
-    use std::{
-        collections::HashSet,
-        fs,
-        io::{Error, ErrorKind},
-    };
+    use std::{collections::HashSet, fs, io::Error};
 
     #[test]
     fn test_tracing() {
@@ -147,9 +317,9 @@ mod tests {
         _ = fs::remove_file(telemetry_path);
 
         {
-            let mut trace = start_trace(&buildpack, "bar");
+            let mut trace = start_trace(&buildpack, "bar", TraceExporter::File);
             trace.add_event("baz-event");
-            trace.set_error(&Error::new(ErrorKind::Other, "it's broken"));
+            trace.set_error(&Error::other("it's broken"));
         }
         let tracing_contents = fs::read_to_string(telemetry_path)
             .expect("Expected telemetry file to exist, but couldn't read it");
@@ -178,6 +348,9 @@ mod tests {
                 "{\"key\":\"buildpack_name\",\"value\":{\"stringValue\":\"Foo buildpack for company.com\"}}"
         ));
 
+        assert!(tracing_contents
+            .contains("{\"key\":\"buildpack_phase\",\"value\":{\"stringValue\":\"bar\"}}"));
+
         // Check event name
         assert!(tracing_contents.contains("\"name\":\"baz-event\""));
 
@@ -192,4 +365,192 @@ mod tests {
             .contains("\"message\":\"Custom { kind: Other, error: \\\"it's broken\\\" }"));
         assert!(tracing_contents.contains("\"code\":1"));
     }
+
+    #[test]
+    fn test_user_defined_spans() {
+        let buildpack = Buildpack {
+            id: buildpack_id!("company.com/foo"),
+            version: BuildpackVersion::new(0, 0, 99),
+            name: None,
+            homepage: None,
+            clear_env: false,
+            description: None,
+            keywords: Vec::new(),
+            licenses: Vec::new(),
+            sbom_formats: HashSet::new(),
+        };
+        let telemetry_path = "/tmp/libcnb-telemetry/company_com_foo-user.jsonl";
+        _ = fs::remove_file(telemetry_path);
+
+        {
+            let trace = start_trace(&buildpack, "user", TraceExporter::File);
+            let tracer = trace.tracer();
+            tracer.add_event("root-event");
+            tracer.in_span("user-span", |span| {
+                span.set_attribute("user_key", "user_value");
+                span.add_event("user-event");
+                span.start_span("nested-user-span")
+                    .record_error(&Error::other("nested error"));
+            });
+        }
+        let tracing_contents = fs::read_to_string(telemetry_path)
+            .expect("Expected telemetry file to exist, but couldn't read it");
+
+        let spans = tracing_contents
+            .lines()
+            .flat_map(|line| {
+                let data: Value = serde_json::from_str(line)
+                    .expect("Expected tracing export file contents to be valid json");
+                data["resourceSpans"][0]["scopeSpans"][0]["spans"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        let span_named = |name: &str| {
+            spans
+                .iter()
+                .find(|span| span["name"] == name)
+                .unwrap_or_else(|| panic!("Expected span {name} to be exported"))
+        };
+
+        let root_span = span_named("company_com_foo-user");
+        let user_span = span_named("user-span");
+        let nested_span = span_named("nested-user-span");
+
+        assert_eq!(user_span["parentSpanId"], root_span["spanId"]);
+        assert_eq!(user_span["traceId"], root_span["traceId"]);
+        assert_eq!(nested_span["parentSpanId"], user_span["spanId"]);
+
+        assert!(tracing_contents.contains("\"name\":\"root-event\""));
+        assert!(tracing_contents.contains("\"name\":\"user-event\""));
+        assert!(tracing_contents
+            .contains("{\"key\":\"user_key\",\"value\":{\"stringValue\":\"user_value\"}}"));
+        assert!(tracing_contents.contains(
+            "{\"key\":\"exception.message\",\"value\":{\"stringValue\":\"nested error\"}}"
+        ));
+    }
+
+    #[test]
+    fn resolve_trace_exporter_from_env() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| (*value).to_string())
+            }
+        };
+
+        assert_eq!(
+            resolve_trace_exporter(TraceExporter::FromEnv, env(&[])),
+            TraceExporter::File
+        );
+        assert_eq!(
+            resolve_trace_exporter(
+                TraceExporter::FromEnv,
+                env(&[("OTEL_TRACES_EXPORTER", "none")])
+            ),
+            TraceExporter::None
+        );
+        assert_eq!(
+            resolve_trace_exporter(
+                TraceExporter::File,
+                env(&[("OTEL_TRACES_EXPORTER", "none")])
+            ),
+            TraceExporter::File
+        );
+
+        #[cfg(not(feature = "trace-otlp"))]
+        assert_eq!(
+            resolve_trace_exporter(
+                TraceExporter::FromEnv,
+                env(&[("OTEL_TRACES_EXPORTER", "otlp")])
+            ),
+            TraceExporter::File
+        );
+
+        #[cfg(feature = "trace-otlp")]
+        {
+            use super::OtlpProtocol;
+
+            assert_eq!(
+                resolve_trace_exporter(
+                    TraceExporter::FromEnv,
+                    env(&[("OTEL_TRACES_EXPORTER", "otlp")])
+                ),
+                TraceExporter::Otlp {
+                    protocol: OtlpProtocol::HttpProtobuf,
+                    endpoint: None
+                }
+            );
+            assert_eq!(
+                resolve_trace_exporter(
+                    TraceExporter::FromEnv,
+                    env(&[
+                        ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://collector:4317"),
+                        ("OTEL_EXPORTER_OTLP_PROTOCOL", "grpc")
+                    ])
+                ),
+                TraceExporter::Otlp {
+                    protocol: OtlpProtocol::Grpc,
+                    endpoint: None
+                }
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "trace-otlp")]
+    fn test_otlp_http_tracing() {
+        use super::OtlpProtocol;
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint: &'static str = format!("http://{}", listener.local_addr().unwrap()).leak();
+
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 4096];
+            // The request is complete once the protobuf payload, which contains the span name,
+            // has been received.
+            while !String::from_utf8_lossy(&request).contains("company_com_foo-otlp") {
+                let read = stream.read(&mut buffer).unwrap();
+                assert_ne!(read, 0);
+                request.extend_from_slice(&buffer[..read]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            String::from_utf8_lossy(&request).into_owned()
+        });
+
+        {
+            let mut trace = start_trace(
+                &Buildpack {
+                    id: buildpack_id!("company.com/foo"),
+                    version: BuildpackVersion::new(0, 0, 99),
+                    name: None,
+                    homepage: None,
+                    clear_env: false,
+                    description: None,
+                    keywords: Vec::new(),
+                    licenses: Vec::new(),
+                    sbom_formats: HashSet::new(),
+                },
+                "otlp",
+                TraceExporter::Otlp {
+                    protocol: OtlpProtocol::HttpProtobuf,
+                    endpoint: Some(endpoint),
+                },
+            );
+            trace.add_event("baz-event");
+        }
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /v1/traces HTTP/1.1"));
+        assert!(request.contains("application/x-protobuf"));
+        assert!(request.contains("buildpack_phase"));
+    }
 }
@@ -1,6 +1,7 @@
 use std::fs;
 use std::fs::Permissions;
 use std::path::Path;
+use std::sync::{Mutex, MutexGuard, PoisonError};
 
 /// Removes [`std::io::Error`] values from a [`Result`] that have the
 /// [`std::io::ErrorKind::NotFound`] error kind by replacing them with the default value for `T`.
@@ -18,6 +19,14 @@ pub(crate) fn is_not_found_error_kind(error: &std::io::Error) -> bool {
     matches!(error.kind(), std::io::ErrorKind::NotFound)
 }
 
+/// Locks the given [`Mutex`], ignoring poisoning.
+///
+/// libcnb only holds these locks for short sections that don't leave the data in an inconsistent
+/// state when panicking, so the data of a poisoned mutex can still be used.
+pub(crate) fn lock<T: ?Sized>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
 /// Recursively removes the given path, similar to [`std::fs::remove_dir_all`].
 ///
 /// Compared to `remove_dir_all`, this function behaves more like `rm -rf` on UNIX systems.
@@ -0,0 +1,697 @@
+use flate2::read::MultiGzDecoder;
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+use xz2::read::XzDecoder;
+use zip::ZipArchive;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ArchiveError {
+    #[error("I/O error while extracting archive: {0}")]
+    IoError(#[from] io::Error),
+
+    #[error("Zip error while extracting archive: {0}")]
+    ZipError(#[from] zip::result::ZipError),
+
+    #[error(
+        "Unsupported archive format, expected a .tar, .tar.gz, .tar.xz, .tar.zst or .zip file"
+    )]
+    UnsupportedFormat,
+
+    #[error("Archive entry has a path outside of the destination directory: {}", .0.display())]
+    InvalidEntryPath(PathBuf),
+}
+
+/// The formats supported by [`extract`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ArchiveFormat {
+    Tar,
+    TarGz,
+    TarXz,
+    TarZst,
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// Detects the format of the given archive file by its magic bytes, independent of its file
+    /// extension.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ArchiveError::UnsupportedFormat`] if the file isn't in one of the supported
+    /// formats, or an [`ArchiveError::IoError`] if it couldn't be read.
+    pub fn detect(archive: impl AsRef<Path>) -> Result<Self, ArchiveError> {
+        let mut header = Vec::new();
+        File::open(archive.as_ref())?
+            .take(262)
+            .read_to_end(&mut header)?;
+
+        if header.starts_with(&[0x1F, 0x8B]) {
+            Ok(Self::TarGz)
+        } else if header.starts_with(&[0xFD, b'7', b'z', b'X', b'Z', 0x00]) {
+            Ok(Self::TarXz)
+        } else if header.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+            Ok(Self::TarZst)
+        } else if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+            Ok(Self::Zip)
+        } else if header.get(257..262) == Some(b"ustar") {
+            Ok(Self::Tar)
+        } else {
+            Err(ArchiveError::UnsupportedFormat)
+        }
+    }
+}
+
+/// Extracts a `.tar`, `.tar.gz`, `.tar.xz`, `.tar.zst` or `.zip` archive to the given directory.
+///
+/// The format is detected with [`ArchiveFormat::detect`]. Like `tar --strip-components`, the
+/// first `strip_components` components are removed from the path of each entry, and entries with
+/// fewer components are skipped. Use this for archives that contain a single top-level directory,
+/// such as `node-v22.12.0-linux-x64/`.
+///
+/// The destination directory is created if it doesn't exist. Entries that would be written outside
+/// of it, i.e. via `..` components or symlinks, fail the extraction. This includes symlink entries
+/// that point outside of the destination directory. Existing files and symlinks at the path of an
+/// entry are replaced instead of being written through.
+///
+/// # Examples
+/// ```no_run
+/// use libherokubuildpack::archive::extract;
+///
+/// extract("node-v22.12.0-linux-x64.tar.xz", "/layers/node/runtime", 1).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the archive format isn't supported, the archive is corrupt or an entry
+/// couldn't be written to the destination directory.
+pub fn extract(
+    archive: impl AsRef<Path>,
+    destination: impl AsRef<Path>,
+    strip_components: usize,
+) -> Result<(), ArchiveError> {
+    let format = ArchiveFormat::detect(archive.as_ref())?;
+    let file = File::open(archive.as_ref())?;
+
+    fs::create_dir_all(destination.as_ref())?;
+    let destination = fs::canonicalize(destination.as_ref())?;
+
+    match format {
+        ArchiveFormat::Tar => extract_tar(BufReader::new(file), &destination, strip_components),
+        ArchiveFormat::TarGz => {
+            extract_tar(MultiGzDecoder::new(file), &destination, strip_components)
+        }
+        ArchiveFormat::TarXz => extract_tar(
+            XzDecoder::new_multi_decoder(file),
+            &destination,
+            strip_components,
+        ),
+        ArchiveFormat::TarZst => {
+            extract_tar(zstd::Decoder::new(file)?, &destination, strip_components)
+        }
+        ArchiveFormat::Zip => extract_zip(file, &destination, strip_components),
+    }
+}
+
+fn extract_tar(
+    reader: impl Read,
+    destination: &Path,
+    strip_components: usize,
+) -> Result<(), ArchiveError> {
+    let mut archive = tar::Archive::new(reader);
+    archive.set_preserve_permissions(true);
+
+    let mut symlinks = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let Some(path) = entry_destination(&entry.path()?, destination, strip_components)? else {
+            continue;
+        };
+
+        if entry.header().entry_type().is_hard_link() {
+            let link_name = entry
+                .link_name()?
+                .ok_or_else(|| ArchiveError::InvalidEntryPath(path.clone()))?;
+
+            // Hard links refer to other entries of the archive, so their paths are stripped, too.
+            let link_path = entry_destination(&link_name, destination, strip_components)?
+                .ok_or_else(|| ArchiveError::InvalidEntryPath(link_name.to_path_buf()))?;
+
+            fs::hard_link(link_path, path)?;
+        } else {
+            if entry.header().entry_type().is_symlink() {
+                let link_name = entry
+                    .link_name()?
+                    .ok_or_else(|| ArchiveError::InvalidEntryPath(path.clone()))?;
+
+                validate_symlink_target(&path, &link_name, destination)?;
+                symlinks.push(path.clone());
+            }
+
+            entry.unpack(path)?;
+        }
+    }
+
+    validate_extracted_symlinks(&symlinks, destination)
+}
+
+fn extract_zip(
+    file: File,
+    destination: &Path,
+    strip_components: usize,
+) -> Result<(), ArchiveError> {
+    let mut archive = ZipArchive::new(BufReader::new(file))?;
+    let mut symlinks = Vec::new();
+
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        let Some(path) = entry_destination(Path::new(entry.name()), destination, strip_components)?
+        else {
+            continue;
+        };
+
+        if entry.is_dir() {
+            fs::create_dir_all(&path)?;
+            continue;
+        }
+
+        // Replace existing files and symlinks instead of writing through them. A symlink entry
+        // followed by a regular entry of the same name would otherwise write to the symlink's target.
+        remove_existing_file(&path)?;
+
+        #[cfg(target_family = "unix")]
+        if entry.is_symlink() {
+            let mut link_target = String::new();
+            entry.read_to_string(&mut link_target)?;
+            validate_symlink_target(&path, Path::new(&link_target), destination)?;
+            std::os::unix::fs::symlink(link_target, &path)?;
+            symlinks.push(path);
+            continue;
+        }
+
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        io::copy(&mut entry, &mut file)?;
+
+        #[cfg(target_family = "unix")]
+        if let Some(mode) = entry.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode))?;
+        }
+    }
+
+    validate_extracted_symlinks(&symlinks, destination)
+}
+
+/// Returns the path an archive entry is extracted to, or `None` if the entry is skipped because
+/// its path has no more than `strip_components` components.
+///
+/// Creates the parent directories of the returned path and verifies that they're inside the
+/// destination directory, so that entries can't be written to other locations via symlinks.
+fn entry_destination(
+    entry_path: &Path,
+    destination: &Path,
+    strip_components: usize,
+) -> Result<Option<PathBuf>, ArchiveError> {
+    let mut components = Vec::new();
+    for component in entry_path.components() {
+        match component {
+            Component::Normal(component) => components.push(component),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(ArchiveError::InvalidEntryPath(entry_path.to_path_buf()));
+            }
+        }
+    }
+
+    if components.len() <= strip_components {
+        return Ok(None);
+    }
+
+    let path = components[strip_components..]
+        .iter()
+        .fold(destination.to_path_buf(), |path, component| {
+            path.join(component)
+        });
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+
+        if !fs::canonicalize(parent)?.starts_with(destination) {
+            return Err(ArchiveError::InvalidEntryPath(entry_path.to_path_buf()));
+        }
+    }
+
+    Ok(Some(path))
+}
+
+/// Verifies that a symlink at the given path with the given target points to a location inside the
+/// destination directory.
+///
+/// The target is resolved against the file system, starting at the symlink's (canonicalized)
+/// parent directory. Symlinks that already exist along the way are followed, so that chains of
+/// symlinks, such as `a -> ..` and `b -> a/../..`, can't escape the destination directory. Since a
+/// later entry could turn a component that doesn't exist yet into a symlink, `..` components after
+/// such a component are rejected.
+fn validate_symlink_target(
+    path: &Path,
+    target: &Path,
+    destination: &Path,
+) -> Result<(), ArchiveError> {
+    let invalid_target = || ArchiveError::InvalidEntryPath(target.to_path_buf());
+
+    let mut resolved = match path.parent() {
+        Some(parent) => fs::canonicalize(parent)?,
+        None => destination.to_path_buf(),
+    };
+
+    if target.has_root() {
+        return Err(invalid_target());
+    }
+
+    let mut remaining = VecDeque::new();
+    push_front_components(&mut remaining, target);
+
+    let mut followed_symlinks = 0;
+    let mut missing_component = false;
+
+    while let Some(component) = remaining.pop_front() {
+        if component == ".." {
+            if missing_component {
+                return Err(invalid_target());
+            }
+
+            resolved.pop();
+            continue;
+        }
+
+        let candidate = resolved.join(&component);
+        match fs::symlink_metadata(&candidate) {
+            Ok(metadata) if metadata.is_symlink() => {
+                followed_symlinks += 1;
+                if followed_symlinks > MAX_FOLLOWED_SYMLINKS {
+                    return Err(invalid_target());
+                }
+
+                let link_target = fs::read_link(&candidate)?;
+                if link_target.has_root() {
+                    resolved = PathBuf::from("/");
+                }
+                push_front_components(&mut remaining, &link_target);
+            }
+            Ok(_) => resolved = candidate,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                missing_component = true;
+                resolved = candidate;
+            }
+            Err(error) => return Err(error.into()),
+        }
+    }
+
+    if resolved.starts_with(destination) {
+        Ok(())
+    } else {
+        Err(invalid_target())
+    }
+}
+
+/// The maximum number of symlinks followed while resolving a symlink target, like the limit of
+/// Linux that fails path resolution with `ELOOP`.
+const MAX_FOLLOWED_SYMLINKS: usize = 40;
+
+/// Adds the `..` and normal components of the given path to the front of the queue, keeping their
+/// order.
+fn push_front_components(queue: &mut VecDeque<OsString>, path: &Path) {
+    for component in path.components().rev() {
+        match component {
+            Component::Normal(component) => queue.push_front(component.to_os_string()),
+            Component::ParentDir => queue.push_front(OsString::from("..")),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+        }
+    }
+}
+
+/// Verifies the targets of all extracted symlinks again, once all entries have been extracted.
+///
+/// Entries extracted after a symlink can replace other symlinks it points through, which changes
+/// where it points to. Symlinks that point outside of the destination directory are removed.
+fn validate_extracted_symlinks(
+    symlinks: &[PathBuf],
+    destination: &Path,
+) -> Result<(), ArchiveError> {
+    for path in symlinks {
+        let Ok(target) = fs::read_link(path) else {
+            // The symlink was replaced by a later entry.
+            continue;
+        };
+
+        if let Err(error) = validate_symlink_target(path, &target, destination) {
+            remove_existing_file(path)?;
+            return Err(error);
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes the file or symlink at the given path, if there is one. Directories are kept.
+fn remove_existing_file(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if !metadata.is_dir() => fs::remove_file(path),
+        Ok(_) => Ok(()),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use xz2::write::XzEncoder;
+    use zip::write::SimpleFileOptions;
+
+    fn tar_archive() -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+
+        for (path, contents, mode) in [
+            ("runtime-1.0.0/bin/runtime", "#!/usr/bin/env bash\n", 0o755),
+            ("runtime-1.0.0/README", "Runtime\n", 0o644),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(mode);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, contents.as_bytes())
+                .unwrap();
+        }
+
+        builder.into_inner().unwrap()
+    }
+
+    fn zip_archive() -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+
+        for (path, contents, mode) in [
+            ("runtime-1.0.0/bin/runtime", "#!/usr/bin/env bash\n", 0o755),
+            ("runtime-1.0.0/README", "Runtime\n", 0o644),
+        ] {
+            writer
+                .start_file(path, SimpleFileOptions::default().unix_permissions(mode))
+                .unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn compress(format: ArchiveFormat, data: &[u8]) -> Vec<u8> {
+        match format {
+            ArchiveFormat::Tar | ArchiveFormat::Zip => data.to_vec(),
+            ArchiveFormat::TarGz => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+            ArchiveFormat::TarXz => {
+                let mut encoder = XzEncoder::new(Vec::new(), 6);
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+            ArchiveFormat::TarZst => zstd::encode_all(data, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn extract_supported_formats() {
+        for format in [
+            ArchiveFormat::Tar,
+            ArchiveFormat::TarGz,
+            ArchiveFormat::TarXz,
+            ArchiveFormat::TarZst,
+            ArchiveFormat::Zip,
+        ] {
+            let temp_dir = tempdir().unwrap();
+            let archive_path = temp_dir.path().join("archive");
+            let data = if format == ArchiveFormat::Zip {
+                zip_archive()
+            } else {
+                tar_archive()
+            };
+            fs::write(&archive_path, compress(format, &data)).unwrap();
+
+            assert_eq!(ArchiveFormat::detect(&archive_path).unwrap(), format);
+
+            let destination = temp_dir.path().join("runtime");
+            extract(&archive_path, &destination, 1).unwrap();
+
+            assert_eq!(
+                fs::read_to_string(destination.join("bin/runtime")).unwrap(),
+                "#!/usr/bin/env bash\n"
+            );
+            assert_eq!(
+                fs::read_to_string(destination.join("README")).unwrap(),
+                "Runtime\n"
+            );
+
+            #[cfg(target_family = "unix")]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                assert_eq!(
+                    fs::metadata(destination.join("bin/runtime"))
+                        .unwrap()
+                        .permissions()
+                        .mode()
+                        & 0o777,
+                    0o755
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn extract_without_strip_components() {
+        let temp_dir = tempdir().unwrap();
+        let archive_path = temp_dir.path().join("archive.tar.gz");
+        fs::write(
+            &archive_path,
+            compress(ArchiveFormat::TarGz, &tar_archive()),
+        )
+        .unwrap();
+
+        extract(&archive_path, temp_dir.path().join("out"), 0).unwrap();
+        assert!(temp_dir
+            .path()
+            .join("out/runtime-1.0.0/bin/runtime")
+            .is_file());
+
+        extract(&archive_path, temp_dir.path().join("empty"), 3).unwrap();
+        assert_eq!(
+            fs::read_dir(temp_dir.path().join("empty")).unwrap().count(),
+            0
+        );
+    }
+
+    #[test]
+    fn extract_unsupported_format() {
+        let temp_dir = tempdir().unwrap();
+        let archive_path = temp_dir.path().join("archive.rar");
+        fs::write(&archive_path, "Rar!").unwrap();
+
+        assert!(matches!(
+            extract(&archive_path, temp_dir.path().join("out"), 0),
+            Err(ArchiveError::UnsupportedFormat)
+        ));
+    }
+
+    #[test]
+    fn extract_entry_outside_of_destination() {
+        let temp_dir = tempdir().unwrap();
+        let archive_path = temp_dir.path().join("archive.zip");
+        let mut writer = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+        writer
+            .start_file("../evil", SimpleFileOptions::default())
+            .unwrap();
+        fs::write(&archive_path, writer.finish().unwrap().into_inner()).unwrap();
+
+        assert!(matches!(
+            extract(&archive_path, temp_dir.path().join("out"), 0),
+            Err(ArchiveError::InvalidEntryPath(path)) if path == Path::new("../evil")
+        ));
+        assert!(!temp_dir.path().join("evil").exists());
+    }
+
+    #[test]
+    #[cfg(target_family = "unix")]
+    fn extract_symlink_outside_of_destination() {
+        let temp_dir = tempdir().unwrap();
+        let outside_file = temp_dir.path().join("outside");
+        fs::write(&outside_file, "original").unwrap();
+
+        // A symlink pointing outside of the destination, followed by a regular entry with the same
+        // name that would be written through the symlink.
+        let archive_path = temp_dir.path().join("archive.zip");
+        let mut writer = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+        writer
+            .add_symlink(
+                "x",
+                outside_file.to_string_lossy(),
+                SimpleFileOptions::default(),
+            )
+            .unwrap();
+        // Zip archives can't contain duplicate names, but `./x` is extracted to the same path as `x`.
+        writer
+            .start_file("./x", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(b"overwritten").unwrap();
+        fs::write(&archive_path, writer.finish().unwrap().into_inner()).unwrap();
+
+        assert!(matches!(
+            extract(&archive_path, temp_dir.path().join("out"), 0),
+            Err(ArchiveError::InvalidEntryPath(path)) if path == outside_file
+        ));
+        assert_eq!(fs::read_to_string(&outside_file).unwrap(), "original");
+
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_size(0);
+        builder
+            .append_link(&mut header, "x", "../../outside")
+            .unwrap();
+        fs::write(&archive_path, builder.into_inner().unwrap()).unwrap();
+
+        assert!(matches!(
+            extract(&archive_path, temp_dir.path().join("out"), 0),
+            Err(ArchiveError::InvalidEntryPath(path)) if path == Path::new("../../outside")
+        ));
+    }
+
+    #[test]
+    #[cfg(target_family = "unix")]
+    fn extract_chained_symlinks_outside_of_destination() {
+        let temp_dir = tempdir().unwrap();
+        let archive_path = temp_dir.path().join("archive.tar");
+
+        // Each target stays inside of the destination when resolved on paper, but `b` escapes it
+        // when `sub/a` is followed.
+        let mut builder = tar::Builder::new(Vec::new());
+        append_symlink(&mut builder, "sub/a", "..");
+        append_symlink(&mut builder, "b", "sub/a/../..");
+        fs::write(&archive_path, builder.into_inner().unwrap()).unwrap();
+
+        let destination = temp_dir.path().join("out");
+        assert!(matches!(
+            extract(&archive_path, &destination, 0),
+            Err(ArchiveError::InvalidEntryPath(path)) if path == Path::new("sub/a/../..")
+        ));
+        assert!(fs::symlink_metadata(destination.join("b")).is_err());
+
+        // `b` stays inside of the destination until a later entry replaces `sub/a`.
+        let mut builder = tar::Builder::new(Vec::new());
+        append_symlink(&mut builder, "sub/a", "../x/y/z");
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_mode(0o755);
+        header.set_size(0);
+        builder
+            .append_data(&mut header, "x/y/z", io::empty())
+            .unwrap();
+        append_symlink(&mut builder, "b", "sub/a/../../..");
+        append_symlink(&mut builder, "sub/a", "..");
+        fs::write(&archive_path, builder.into_inner().unwrap()).unwrap();
+
+        let destination = temp_dir.path().join("out2");
+        assert!(matches!(
+            extract(&archive_path, &destination, 0),
+            Err(ArchiveError::InvalidEntryPath(path)) if path == Path::new("sub/a/../../..")
+        ));
+        assert!(fs::symlink_metadata(destination.join("b")).is_err());
+
+        // `..` after a component that doesn't exist yet is rejected, since a later entry could
+        // turn that component into a symlink.
+        let mut builder = tar::Builder::new(Vec::new());
+        append_symlink(&mut builder, "b", "sub/a/..");
+        fs::write(&archive_path, builder.into_inner().unwrap()).unwrap();
+
+        assert!(matches!(
+            extract(&archive_path, temp_dir.path().join("out3"), 0),
+            Err(ArchiveError::InvalidEntryPath(path)) if path == Path::new("sub/a/..")
+        ));
+    }
+
+    #[test]
+    #[cfg(target_family = "unix")]
+    fn extract_chained_symlinks_inside_of_destination() {
+        let temp_dir = tempdir().unwrap();
+        let archive_path = temp_dir.path().join("archive.tar");
+
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_mode(0o644);
+        header.set_size(4);
+        builder
+            .append_data(&mut header, "lib/file", &b"file"[..])
+            .unwrap();
+        append_symlink(&mut builder, "bin/lib", "../lib");
+        append_symlink(&mut builder, "bin/file", "lib/../lib/file");
+        fs::write(&archive_path, builder.into_inner().unwrap()).unwrap();
+
+        let destination = temp_dir.path().join("out");
+        extract(&archive_path, &destination, 0).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(destination.join("bin/file")).unwrap(),
+            "file"
+        );
+    }
+
+    #[cfg(target_family = "unix")]
+    fn append_symlink(builder: &mut tar::Builder<Vec<u8>>, path: &str, target: &str) {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_size(0);
+        builder.append_link(&mut header, path, target).unwrap();
+    }
+
+    #[test]
+    #[cfg(target_family = "unix")]
+    fn extract_entry_replaces_existing_symlink() {
+        let temp_dir = tempdir().unwrap();
+        let archive_path = temp_dir.path().join("archive.zip");
+        let mut writer = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+        writer
+            .start_file("target", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(b"target").unwrap();
+        writer
+            .add_symlink("x", "target", SimpleFileOptions::default())
+            .unwrap();
+        writer
+            .start_file("./x", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(b"x").unwrap();
+        fs::write(&archive_path, writer.finish().unwrap().into_inner()).unwrap();
+
+        let destination = temp_dir.path().join("out");
+        extract(&archive_path, &destination, 0).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(destination.join("target")).unwrap(),
+            "target"
+        );
+        assert!(!fs::symlink_metadata(destination.join("x"))
+            .unwrap()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(destination.join("x")).unwrap(), "x");
+    }
+}
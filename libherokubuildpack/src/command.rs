@@ -1,9 +1,14 @@
 use crate::write::tee;
 use crossbeam_utils::thread::ScopedJoinHandle;
 use std::io::Write;
+use std::ops::{Deref, DerefMut};
+use std::time::{Duration, Instant};
 use std::{io, process, thread};
 use std::{mem, panic};
 
+/// The interval in which the status of a child process is polled while waiting with a timeout.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
 /// Extension trait for [`process::Command`] that adds functions for use within buildpacks.
 pub trait CommandExt {
     /// Spawns the command process and sends the output of stdout and stderr to the given writers.
@@ -67,6 +72,48 @@ pub trait CommandExt {
         stdout_write: OW,
         stderr_write: EW,
     ) -> io::Result<process::Output>;
+
+    /// Like [`output_and_write_streams`](Self::output_and_write_streams), but kills the process if
+    /// it doesn't finish within the given timeout.
+    ///
+    /// Use this for tool invocations that might hang, such as package managers waiting on the
+    /// network. The output of the process is written to the given writers until it is killed. The
+    /// process is also killed if the calling thread panics while waiting.
+    ///
+    /// On Unix, the process is spawned in a new process group and the whole process group is
+    /// killed, including processes it spawned, such as the actual tool started by a wrapper
+    /// script. As a consequence, the process doesn't receive signals sent to the process group of
+    /// the buildpack, such as `SIGINT` when pressing Ctrl+C in a terminal. Processes that leave the
+    /// process group and keep stdout or stderr open will block this function until they exit or
+    /// close these streams, as will processes that keep them open after the process exited in time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`io::ErrorKind::TimedOut`] if the process was killed because it
+    /// didn't finish within the timeout, or any I/O error that occurred while spawning the process,
+    /// waiting for it or writing its output.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// use libherokubuildpack::command::CommandExt;
+    /// use std::process::Command;
+    /// use std::time::Duration;
+    ///
+    /// let output = Command::new("npm")
+    ///     .arg("install")
+    ///     .output_and_write_streams_with_timeout(
+    ///         std::io::stdout(),
+    ///         std::io::stderr(),
+    ///         Duration::from_secs(600),
+    ///     )
+    ///     .unwrap();
+    /// ```
+    fn output_and_write_streams_with_timeout<OW: Write + Send, EW: Write + Send>(
+        &mut self,
+        stdout_write: OW,
+        stderr_write: EW,
+        timeout: Duration,
+    ) -> io::Result<process::Output>;
 }
 
 impl CommandExt for process::Command {
@@ -100,13 +147,183 @@ impl CommandExt for process::Command {
             stderr: stderr_buffer,
         })
     }
+
+    fn output_and_write_streams_with_timeout<OW: Write + Send, EW: Write + Send>(
+        &mut self,
+        stdout_write: OW,
+        stderr_write: EW,
+        timeout: Duration,
+    ) -> io::Result<process::Output> {
+        let mut stdout_buffer = Vec::new();
+        let mut stderr_buffer = Vec::new();
+
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(self, 0);
+
+        let mut child = self
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::piped())
+            .spawn()
+            .map(|child| KillOnDropChild {
+                child,
+                kill_process_group: cfg!(unix),
+            })?;
+
+        let status = copy_child_process_output_while(
+            &mut child,
+            tee(&mut stdout_buffer, stdout_write),
+            tee(&mut stderr_buffer, stderr_write),
+            |child| wait_with_timeout(child, timeout, cfg!(unix)),
+        )?;
+
+        Ok(process::Output {
+            status,
+            stdout: stdout_buffer,
+            stderr: stderr_buffer,
+        })
+    }
+}
+
+/// A child process that is killed when this wrapper is dropped while the process is still running.
+///
+/// [`process::Child`] lets the process keep running when it's dropped. Wrap a child with this
+/// type to make sure a process doesn't outlive the buildpack step that started it, i.e. when the
+/// step returns early with an error or panics. Dereferences to the wrapped [`process::Child`].
+///
+/// # Example:
+/// ```no_run
+/// use libherokubuildpack::command::{CommandExt, KillOnDropChild};
+/// use std::process::Command;
+///
+/// let child = Command::new("sleep")
+///     .arg("600")
+///     .spawn_and_write_streams(std::io::stdout(), std::io::stderr())
+///     .map(KillOnDropChild::new)
+///     .unwrap();
+///
+/// // The process is killed here if it's still running.
+/// drop(child);
+/// ```
+#[derive(Debug)]
+pub struct KillOnDropChild {
+    child: process::Child,
+    // Only set for children spawned as process group leaders by this module.
+    kill_process_group: bool,
+}
+
+impl KillOnDropChild {
+    /// Wraps the given child process.
+    #[must_use]
+    pub fn new(child: process::Child) -> Self {
+        Self {
+            child,
+            kill_process_group: false,
+        }
+    }
+}
+
+impl From<process::Child> for KillOnDropChild {
+    fn from(child: process::Child) -> Self {
+        Self::new(child)
+    }
+}
+
+impl Deref for KillOnDropChild {
+    type Target = process::Child;
+
+    fn deref(&self) -> &Self::Target {
+        &self.child
+    }
+}
+
+impl DerefMut for KillOnDropChild {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.child
+    }
+}
+
+impl Drop for KillOnDropChild {
+    fn drop(&mut self) {
+        if let Ok(None) = self.child.try_wait() {
+            let _ = kill(&mut self.child, self.kill_process_group);
+            let _ = self.child.wait();
+        }
+    }
+}
+
+/// Kills the child process, or the process group it leads if `kill_process_group` is set.
+///
+/// Must only be called while the child process hasn't been waited for yet, since its process ID
+/// (and with it the process group ID) might be reused afterwards.
+fn kill(child: &mut process::Child, kill_process_group: bool) -> io::Result<()> {
+    #[cfg(unix)]
+    if kill_process_group {
+        // The ID of the process group is the process ID of its leader.
+        let process_group_id = libc::pid_t::try_from(child.id()).map_err(io::Error::other)?;
+
+        // SAFETY: `kill` has no memory safety preconditions. The negative ID addresses the
+        // process group instead of a single process.
+        #[allow(unsafe_code)]
+        return if unsafe { libc::kill(-process_group_id, libc::SIGKILL) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        };
+    }
+
+    #[cfg(not(unix))]
+    let _ = kill_process_group;
+
+    child.kill()
+}
+
+/// Waits for the child process to exit, killing it if it doesn't exit within the given timeout.
+///
+/// If `kill_process_group` is set, the whole process group the child leads is killed instead.
+fn wait_with_timeout(
+    child: &mut process::Child,
+    timeout: Duration,
+    kill_process_group: bool,
+) -> io::Result<process::ExitStatus> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            kill(child, kill_process_group)?;
+            child.wait()?;
+
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("Process didn't finish within {timeout:?} and was killed"),
+            ));
+        }
+
+        thread::sleep(WAIT_POLL_INTERVAL.min(deadline - now));
+    }
 }
 
 fn write_child_process_output<OW: Write + Send, EW: Write + Send>(
     mut child: process::Child,
+    stdout_writer: OW,
+    stderr_writer: EW,
+) -> io::Result<process::Child> {
+    copy_child_process_output_while(&mut child, stdout_writer, stderr_writer, |_| Ok(()))
+        .map(|()| child)
+}
+
+/// Copies stdout and stderr of the child process to the given writers while running `f` with the
+/// child process. Returns after `f` has returned and both streams have been closed.
+fn copy_child_process_output_while<T, OW: Write + Send, EW: Write + Send>(
+    child: &mut process::Child,
     mut stdout_writer: OW,
     mut stderr_writer: EW,
-) -> io::Result<process::Child> {
+    f: impl FnOnce(&mut process::Child) -> io::Result<T>,
+) -> io::Result<T> {
     // Copying the data to the writers happens in separate threads for stdout and stderr to ensure
     // they're processed in parallel. Example: imagine the caller uses io::stdout() and io::stderr()
     // as the writers so that the user can follow along with the command's output. If we copy stdout
@@ -124,11 +341,13 @@ fn write_child_process_output<OW: Write + Send, EW: Write + Send>(
         let stderr_copy_thread = mem::take(&mut child.stderr)
             .map(|mut stderr| scope.spawn(move |_| std::io::copy(&mut stderr, &mut stderr_writer)));
 
+        let result = f(child);
+
         let stdout_copy_result = stdout_copy_thread.map_or_else(|| Ok(0), join_and_unwind_panic);
         let stderr_copy_result = stderr_copy_thread.map_or_else(|| Ok(0), join_and_unwind_panic);
 
-        // Return the first error from either Result, or the child process value
-        stdout_copy_result.and(stderr_copy_result).map(|_| child)
+        // Return the first error from either Result, or the value returned by `f`
+        result.and_then(|value| stdout_copy_result.and(stderr_copy_result).map(|_| value))
     }))
 }
 
@@ -145,8 +364,10 @@ fn unwind_panic<T>(t: thread::Result<T>) -> T {
 
 #[cfg(test)]
 mod test {
-    use crate::command::CommandExt;
-    use std::process::Command;
+    use crate::command::{CommandExt, KillOnDropChild};
+    use std::io;
+    use std::process::{Command, Stdio};
+    use std::time::{Duration, Instant};
 
     #[test]
     #[cfg(unix)]
@@ -182,4 +403,82 @@ mod test {
         assert_eq!(output.stdout, "Hello World!".as_bytes());
         assert_eq!(output.stderr, Vec::<u8>::new());
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_output_and_write_streams_with_timeout() {
+        let mut stdout_buf = Vec::new();
+
+        let output = Command::new("echo")
+            .args(["-n", "Hello World!"])
+            .output_and_write_streams_with_timeout(
+                &mut stdout_buf,
+                io::sink(),
+                Duration::from_secs(60),
+            )
+            .unwrap();
+
+        assert_eq!(stdout_buf, "Hello World!".as_bytes());
+        assert_eq!(output.status.code(), Some(0));
+        assert_eq!(output.stdout, "Hello World!".as_bytes());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_output_and_write_streams_with_timeout_exceeded() {
+        let mut stdout_buf = Vec::new();
+        let start = Instant::now();
+
+        let error = Command::new("sh")
+            .args(["-c", "echo Started; exec sleep 60"])
+            .output_and_write_streams_with_timeout(
+                &mut stdout_buf,
+                io::sink(),
+                Duration::from_millis(500),
+            )
+            .unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert_eq!(stdout_buf, "Started\n".as_bytes());
+        assert!(start.elapsed() < Duration::from_secs(30));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_output_and_write_streams_with_timeout_kills_process_group() {
+        let start = Instant::now();
+
+        // The background process inherits stdout and stderr. If it weren't killed as well, it
+        // would keep the streams open and block until it exits.
+        let error = Command::new("sh")
+            .args(["-c", "sleep 60 & wait"])
+            .output_and_write_streams_with_timeout(
+                io::sink(),
+                io::sink(),
+                Duration::from_millis(500),
+            )
+            .unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert!(start.elapsed() < Duration::from_secs(30));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_kill_on_drop_child() {
+        let child = KillOnDropChild::new(Command::new("sleep").arg("60").spawn().unwrap());
+        let pid = child.id().to_string();
+        drop(child);
+
+        assert!(!Command::new("kill")
+            .args(["-0", &pid])
+            .stderr(Stdio::null())
+            .status()
+            .unwrap()
+            .success());
+
+        // Processes that already exited are left alone.
+        let mut child = KillOnDropChild::new(Command::new("true").spawn().unwrap());
+        assert!(child.wait().unwrap().success());
+    }
 }
@@ -1,4 +1,7 @@
-use std::{fs, io};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::Duration;
 
 #[derive(thiserror::Error, Debug)]
 pub enum DownloadError {
@@ -12,6 +15,9 @@ pub enum DownloadError {
 
 /// Downloads a file via HTTP(S) to a local path
 ///
+/// Uses a [`Downloader`] with its default configuration, i.e. failed downloads are retried and
+/// resumed. Use [`Downloader`] directly to customize retries, timeouts or to report progress.
+///
 /// # Examples
 /// ```
 /// use libherokubuildpack::digest::sha256;
@@ -31,10 +37,571 @@ pub fn download_file(
     uri: impl AsRef<str>,
     destination: impl AsRef<std::path::Path>,
 ) -> Result<(), DownloadError> {
-    let response = ureq::get(uri.as_ref()).call().map_err(Box::new)?;
+    Downloader::new().download(uri, destination)
+}
+
+/// Downloads files via HTTP(S), retrying and resuming failed downloads.
+///
+/// Failed requests are retried with exponential backoff. Transport errors, I/O errors while
+/// reading the response body as well as `429` and `5xx` HTTP responses are considered retryable.
+/// I/O errors while writing the file, i.e. because the disk is full, are not.
+///
+/// If a download fails after parts of the file have already been written, the next attempt
+/// requests only the remaining bytes using an HTTP `Range` header. The `If-Range` header makes
+/// sure the remaining bytes belong to the same version of the file, based on the `ETag` or
+/// `Last-Modified` header of the previous response. If the server sent neither, doesn't support
+/// range requests or the file changed, the download starts from scratch.
+///
+/// # Examples
+/// ```no_run
+/// use libherokubuildpack::download::Downloader;
+/// use std::time::Duration;
+///
+/// Downloader::new()
+///     .retries(5)
+///     .timeout(Duration::from_secs(600))
+///     .download_with_progress(
+///         "https://example.com/runtime.tar.gz",
+///         "/tmp/runtime.tar.gz",
+///         |progress| {
+///             if let Some(total) = progress.total {
+///                 eprintln!("{}/{total} bytes", progress.downloaded);
+///             }
+///         },
+///     )
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Downloader {
+    retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    connect_timeout: Duration,
+    timeout: Option<Duration>,
+}
+
+impl Default for Downloader {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(30),
+            timeout: None,
+        }
+    }
+}
+
+impl Downloader {
+    /// Creates a new downloader with the default configuration: three retries, starting with a
+    /// backoff of one second, a connect timeout of 30 seconds and no overall timeout.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of retries after the initial attempt.
+    #[must_use]
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Sets the delay before the first retry. The delay doubles with every subsequent retry, up to
+    /// the [maximum backoff](Self::max_backoff).
+    #[must_use]
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Sets the maximum delay between two attempts.
+    #[must_use]
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Sets the timeout for establishing a connection.
+    #[must_use]
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// Sets the timeout for a single attempt, including reading the whole response body.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Downloads a file via HTTP(S) to a local path.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the last attempt if all attempts failed or the error of the first
+    /// attempt that failed with a non-retryable error.
+    pub fn download(
+        &self,
+        uri: impl AsRef<str>,
+        destination: impl AsRef<Path>,
+    ) -> Result<(), DownloadError> {
+        self.download_with_progress(uri, destination, |_| {})
+    }
+
+    /// Downloads a file via HTTP(S) to a local path, calling the given function whenever data was
+    /// written to the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the last attempt if all attempts failed or the error of the first
+    /// attempt that failed with a non-retryable error.
+    pub fn download_with_progress(
+        &self,
+        uri: impl AsRef<str>,
+        destination: impl AsRef<Path>,
+        mut on_progress: impl FnMut(DownloadProgress),
+    ) -> Result<(), DownloadError> {
+        let mut agent_builder = ureq::AgentBuilder::new().timeout_connect(self.connect_timeout);
+        if let Some(timeout) = self.timeout {
+            agent_builder = agent_builder.timeout(timeout);
+        }
+        let agent = agent_builder.build();
+
+        let mut file = fs::File::create(destination.as_ref())?;
+        let mut partial_download = PartialDownload::default();
+        let mut attempt = 0;
+
+        loop {
+            match download_attempt(
+                &agent,
+                uri.as_ref(),
+                &mut file,
+                &mut partial_download,
+                &mut on_progress,
+            ) {
+                Ok(()) => return Ok(()),
+                Err(error) if attempt < self.retries && error.retryable => {
+                    std::thread::sleep(self.backoff(attempt));
+                    attempt += 1;
+                }
+                Err(error) => return Err(error.error),
+            }
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2_u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+/// Progress of a download, passed to the callback of [`Downloader::download_with_progress`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DownloadProgress {
+    /// The number of bytes written to the destination so far.
+    pub downloaded: u64,
+    /// The total size of the file in bytes, if known.
+    pub total: Option<u64>,
+}
+
+/// The part of the file that was downloaded by previous attempts.
+#[derive(Debug, Default)]
+struct PartialDownload {
+    /// The number of bytes written to the file.
+    downloaded: u64,
+    /// The `ETag` or `Last-Modified` value of the response the bytes were read from, used to
+    /// resume the download only if the file didn't change in the meantime.
+    validator: Option<String>,
+}
+
+/// The error of a single download attempt.
+#[derive(Debug)]
+struct AttemptError {
+    error: DownloadError,
+    /// Whether another attempt might succeed, i.e. for transport errors. Errors that occur when
+    /// writing the file, such as a full disk, are not retryable.
+    retryable: bool,
+}
+
+impl AttemptError {
+    fn transport(error: impl Into<DownloadError>) -> Self {
+        let error = error.into();
+        let retryable = match &error {
+            DownloadError::HttpError(error) => match error.as_ref() {
+                ureq::Error::Status(status, _) => *status == 429 || *status >= 500,
+                ureq::Error::Transport(_) => true,
+            },
+            DownloadError::IoError(_) => true,
+        };
+
+        Self { error, retryable }
+    }
+
+    fn local(error: std::io::Error) -> Self {
+        Self {
+            error: DownloadError::IoError(error),
+            retryable: false,
+        }
+    }
+}
+
+fn download_attempt(
+    agent: &ureq::Agent,
+    uri: &str,
+    file: &mut fs::File,
+    partial_download: &mut PartialDownload,
+    on_progress: &mut impl FnMut(DownloadProgress),
+) -> Result<(), AttemptError> {
+    let mut request = agent.get(uri);
+    // Without a validator, there is no way to tell whether the remaining bytes belong to the same
+    // version of the file, so the download starts from scratch.
+    if let (1.., Some(validator)) = (partial_download.downloaded, &partial_download.validator) {
+        request = request
+            .set("Range", &format!("bytes={}-", partial_download.downloaded))
+            .set("If-Range", validator);
+    }
+
+    let response = request
+        .call()
+        .map_err(|error| AttemptError::transport(Box::new(error)))?;
+
+    if response.status() == 206 {
+        let range_start = response
+            .header("Content-Range")
+            .and_then(content_range_start);
+
+        if range_start != Some(partial_download.downloaded) {
+            // The server sent a different part of the file than requested, start from scratch
+            // with the next attempt.
+            *partial_download = PartialDownload::default();
+            file.set_len(0).map_err(AttemptError::local)?;
+
+            return Err(AttemptError::transport(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Server responded with an unexpected Content-Range",
+            )));
+        }
+
+        file.seek(SeekFrom::Start(partial_download.downloaded))
+            .map_err(AttemptError::local)?;
+    } else {
+        // The server sent the whole file, either because this is the first attempt, the file
+        // changed or the server doesn't support range requests.
+        *partial_download = PartialDownload {
+            downloaded: 0,
+            validator: response
+                .header("ETag")
+                // Weak entity tags can't be used for range requests.
+                .filter(|etag| !etag.starts_with("W/"))
+                .or_else(|| response.header("Last-Modified"))
+                .map(String::from),
+        };
+        file.set_len(0).map_err(AttemptError::local)?;
+        file.rewind().map_err(AttemptError::local)?;
+    }
+
+    let total = response
+        .header("Content-Length")
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(|content_length| content_length + partial_download.downloaded);
+
     let mut reader = response.into_reader();
-    let mut file = fs::File::create(destination.as_ref())?;
-    io::copy(&mut reader, &mut file)?;
+    let mut buffer = vec![0; 64 * 1024];
+
+    loop {
+        let read = reader.read(&mut buffer).map_err(AttemptError::transport)?;
+        if read == 0 {
+            break;
+        }
+
+        file.write_all(&buffer[..read])
+            .map_err(AttemptError::local)?;
+        partial_download.downloaded += read as u64;
+
+        on_progress(DownloadProgress {
+            downloaded: partial_download.downloaded,
+            total,
+        });
+    }
+
+    if total.is_some_and(|total| partial_download.downloaded < total) {
+        return Err(AttemptError::transport(std::io::Error::from(
+            std::io::ErrorKind::UnexpectedEof,
+        )));
+    }
+
+    file.flush().map_err(AttemptError::local)
+}
+
+/// Returns the first byte position of a `Content-Range` header value, i.e. `10` for
+/// `bytes 10-35/36`.
+fn content_range_start(content_range: &str) -> Option<u64> {
+    content_range
+        .strip_prefix("bytes ")?
+        .split_once('-')?
+        .0
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::io::BufReader;
+    use std::net::TcpListener;
+    use tempfile::tempdir;
+
+    const CONTENT: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+    // Serves the given responses, one per connection, and returns the received request headers.
+    fn serve(responses: Vec<Vec<u8>>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let uri = format!("http://{}/file", listener.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|response| {
+                    let (mut stream, _) = listener.accept().unwrap();
+
+                    let mut request = String::new();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        if line.trim().is_empty() {
+                            break;
+                        }
+                        request.push_str(&line);
+                    }
+
+                    stream.write_all(&response).unwrap();
+                    request
+                })
+                .collect()
+        });
+
+        (uri, handle)
+    }
+
+    fn response(status: &str, headers: &[&str], body: &[u8]) -> Vec<u8> {
+        let mut response = format!("HTTP/1.1 {status}\r\nConnection: close\r\n");
+        for header in headers {
+            response.push_str(header);
+            response.push_str("\r\n");
+        }
+        response.push_str("\r\n");
+
+        let mut response = response.into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    fn downloader() -> Downloader {
+        Downloader::new()
+            .retries(2)
+            .initial_backoff(Duration::from_millis(1))
+    }
+
+    #[test]
+    fn resumes_interrupted_download() {
+        let (uri, server) = serve(vec![
+            // Announces the full length, but closes the connection after ten bytes.
+            response(
+                "200 OK",
+                &[
+                    &format!("Content-Length: {}", CONTENT.len()),
+                    "ETag: \"abc\"",
+                ],
+                &CONTENT[..10],
+            ),
+            response(
+                "206 Partial Content",
+                &[
+                    &format!("Content-Length: {}", CONTENT.len() - 10),
+                    &format!(
+                        "Content-Range: bytes 10-{}/{}",
+                        CONTENT.len() - 1,
+                        CONTENT.len()
+                    ),
+                ],
+                &CONTENT[10..],
+            ),
+        ]);
+
+        let temp_dir = tempdir().unwrap();
+        let destination = temp_dir.path().join("file");
+        let mut progress = Vec::new();
+
+        downloader()
+            .download_with_progress(&uri, &destination, |p| progress.push(p))
+            .unwrap();
+
+        assert_eq!(fs::read(&destination).unwrap(), CONTENT);
+        assert_eq!(
+            progress.last(),
+            Some(&DownloadProgress {
+                downloaded: CONTENT.len() as u64,
+                total: Some(CONTENT.len() as u64)
+            })
+        );
+
+        let requests = server.join().unwrap();
+        assert!(!requests[0].to_lowercase().contains("range:"));
+        assert!(requests[1].to_lowercase().contains("range: bytes=10-"));
+        assert!(requests[1].to_lowercase().contains("if-range: \"abc\""));
+    }
+
+    #[test]
+    fn restarts_download_without_validator() {
+        let (uri, server) = serve(vec![
+            response(
+                "200 OK",
+                &[&format!("Content-Length: {}", CONTENT.len())],
+                &CONTENT[..10],
+            ),
+            response(
+                "200 OK",
+                &[&format!("Content-Length: {}", CONTENT.len())],
+                CONTENT,
+            ),
+        ]);
+
+        let temp_dir = tempdir().unwrap();
+        let destination = temp_dir.path().join("file");
+
+        downloader().download(&uri, &destination).unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(!requests[1].to_lowercase().contains("range:"));
+        assert_eq!(fs::read(&destination).unwrap(), CONTENT);
+    }
+
+    #[test]
+    fn restarts_download_on_unexpected_content_range() {
+        let (uri, server) = serve(vec![
+            response(
+                "200 OK",
+                &[
+                    &format!("Content-Length: {}", CONTENT.len()),
+                    "Last-Modified: Wed, 21 Oct 2015 07:28:00 GMT",
+                ],
+                &CONTENT[..10],
+            ),
+            response(
+                "206 Partial Content",
+                &[
+                    &format!("Content-Length: {}", CONTENT.len() - 5),
+                    &format!(
+                        "Content-Range: bytes 5-{}/{}",
+                        CONTENT.len() - 1,
+                        CONTENT.len()
+                    ),
+                ],
+                &CONTENT[5..],
+            ),
+            response(
+                "200 OK",
+                &[&format!("Content-Length: {}", CONTENT.len())],
+                CONTENT,
+            ),
+        ]);
+
+        let temp_dir = tempdir().unwrap();
+        let destination = temp_dir.path().join("file");
+
+        downloader().download(&uri, &destination).unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(requests[1]
+            .to_lowercase()
+            .contains("if-range: wed, 21 oct 2015 07:28:00 gmt"));
+        assert!(!requests[2].to_lowercase().contains("range:"));
+        assert_eq!(fs::read(&destination).unwrap(), CONTENT);
+    }
+
+    #[test]
+    fn content_range_start_of_header() {
+        assert_eq!(content_range_start("bytes 10-35/36"), Some(10));
+        assert_eq!(content_range_start("bytes 0-35/*"), Some(0));
+        assert_eq!(content_range_start("bytes */36"), None);
+        assert_eq!(content_range_start("items 10-35/36"), None);
+    }
+
+    #[test]
+    fn restarts_download_if_range_is_not_supported() {
+        let (uri, server) = serve(vec![
+            response(
+                "200 OK",
+                &[&format!("Content-Length: {}", CONTENT.len())],
+                &CONTENT[..10],
+            ),
+            response(
+                "200 OK",
+                &[&format!("Content-Length: {}", CONTENT.len())],
+                CONTENT,
+            ),
+        ]);
+
+        let temp_dir = tempdir().unwrap();
+        let destination = temp_dir.path().join("file");
+
+        downloader().download(&uri, &destination).unwrap();
+        server.join().unwrap();
+
+        assert_eq!(fs::read(&destination).unwrap(), CONTENT);
+    }
+
+    #[test]
+    fn retries_server_errors() {
+        let (uri, server) = serve(vec![
+            response("503 Service Unavailable", &["Content-Length: 0"], b""),
+            response(
+                "200 OK",
+                &[&format!("Content-Length: {}", CONTENT.len())],
+                CONTENT,
+            ),
+        ]);
+
+        let temp_dir = tempdir().unwrap();
+        let destination = temp_dir.path().join("file");
+
+        downloader().download(&uri, &destination).unwrap();
+        server.join().unwrap();
+
+        assert_eq!(fs::read(&destination).unwrap(), CONTENT);
+    }
+
+    #[test]
+    fn does_not_retry_client_errors() {
+        let (uri, server) = serve(vec![response("404 Not Found", &["Content-Length: 0"], b"")]);
+
+        let temp_dir = tempdir().unwrap();
+
+        let result = downloader().download(&uri, temp_dir.path().join("file"));
+        server.join().unwrap();
+
+        assert!(matches!(
+            result,
+            Err(DownloadError::HttpError(error)) if matches!(*error, ureq::Error::Status(404, _))
+        ));
+    }
+
+    #[test]
+    fn backoff() {
+        let downloader = Downloader::new()
+            .initial_backoff(Duration::from_secs(1))
+            .max_backoff(Duration::from_secs(5));
 
-    Ok(())
+        assert_eq!(downloader.backoff(0), Duration::from_secs(1));
+        assert_eq!(downloader.backoff(1), Duration::from_secs(2));
+        assert_eq!(downloader.backoff(2), Duration::from_secs(4));
+        assert_eq!(downloader.backoff(3), Duration::from_secs(5));
+        assert_eq!(downloader.backoff(40), Duration::from_secs(5));
+    }
 }
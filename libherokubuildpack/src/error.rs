@@ -1,4 +1,5 @@
 use crate::log::log_error;
+use libcnb::ErrorReport;
 use std::fmt::Debug;
 
 /// Handles a given [`libcnb::Error`] in a consistent style.
@@ -6,12 +7,13 @@ use std::fmt::Debug;
 /// This function is intended to be used inside [`libcnb::Buildpack::on_error`].
 ///
 /// It outputs generic libcnb errors in a consistent style using the [logging functions](log_error) from this
-/// crate. Buildpack specific errors are handled by the passed custom handler.
+/// crate. Buildpack specific errors are handled by the passed custom handler. Since the error is
+/// already logged, the returned [`ErrorReport`] only exits with a generic error exit code.
 ///
 /// # Example:
 /// ```
 /// use libcnb::build::{BuildContext, BuildResult};
-/// use libcnb::Buildpack;
+/// use libcnb::{Buildpack, ErrorReport};
 /// use libcnb::detect::{DetectContext, DetectResult};
 /// use libcnb::generic::{GenericMetadata, GenericPlatform};
 /// use libherokubuildpack::log::log_error;
@@ -50,12 +52,12 @@ use std::fmt::Debug;
 ///     #     unimplemented!()
 ///     # }
 ///
-///     fn on_error(&self, error: libcnb::Error<Self::Error>) {
+///     fn on_error(&self, error: libcnb::Error<Self::Error>) -> ErrorReport {
 ///         on_error(on_foo_buildpack_error, error)
 ///     }
 /// }
 /// ```
-pub fn on_error<F, E>(f: F, error: libcnb::Error<E>)
+pub fn on_error<F, E>(f: F, error: libcnb::Error<E>) -> ErrorReport
 where
     E: Debug,
     F: Fn(E),
@@ -66,4 +68,6 @@ where
             log_error("Internal Buildpack Error", libcnb_error.to_string());
         }
     }
+
+    ErrorReport::new()
 }
@@ -0,0 +1,460 @@
+//! Helpers for installing git repositories into layers.
+//!
+//! This module shells out to the `git` CLI, which must be available on the `PATH` of the build
+//! image. Repositories are fetched shallowly at the requested ref (a branch, tag or full commit
+//! SHA) and checked out as a detached `HEAD`. Interactive credential prompts are disabled, so
+//! fetching a private repository without configured credentials fails instead of hanging the build.
+
+use libcnb::build::BuildContext;
+use libcnb::data::layer::LayerName;
+use libcnb::layer::{CachedLayerDefinition, InvalidMetadataAction, LayerRef, RestoredLayerAction};
+use libcnb::Buildpack;
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::process::Command;
+
+/// Checks out the given ref of a git repository into the given directory.
+///
+/// If the directory already contains a checkout of the same repository, only the missing objects
+/// are fetched and the working tree is reset to the requested ref, discarding all local changes.
+/// If the ref is a full commit SHA that is already present in the checkout, nothing is fetched.
+/// Otherwise, a new repository is initialized in the directory. Returns the full SHA of the
+/// checked out commit.
+///
+/// # Errors
+///
+/// Returns an error if the URL or ref start with `-`, the `git` CLI is not available or one of the
+/// `git` commands failed.
+pub fn checkout(
+    url: &str,
+    reference: &str,
+    destination: impl AsRef<Path>,
+) -> Result<String, GitError> {
+    let destination = destination.as_ref();
+
+    // Values starting with `-` would be parsed as options by git.
+    if url.starts_with('-') {
+        return Err(GitError::InvalidUrl(String::from(url)));
+    }
+    if reference.starts_with('-') {
+        return Err(GitError::InvalidReference(String::from(reference)));
+    }
+
+    if !destination.join(".git").is_dir() {
+        fs::create_dir_all(destination).map_err(GitError::IoError)?;
+        run_git(destination, ["init", "--quiet"])?;
+    }
+
+    // Setting the URL on every checkout keeps it in sync when a repository moved.
+    let remotes = run_git(destination, ["remote"])?;
+    let remote_command = if remotes.lines().any(|remote| remote == "origin") {
+        "set-url"
+    } else {
+        "add"
+    };
+    run_git(
+        destination,
+        ["remote", remote_command, "--end-of-options", "origin", url],
+    )?;
+
+    let revision = if is_commit_sha(reference) && commit_exists(destination, reference) {
+        reference
+    } else {
+        run_git(
+            destination,
+            [
+                "fetch",
+                "--quiet",
+                "--depth",
+                "1",
+                "--end-of-options",
+                "origin",
+                reference,
+            ],
+        )?;
+        "FETCH_HEAD"
+    };
+
+    // The revision is either `FETCH_HEAD` or a validated commit SHA, so it can't be parsed as an
+    // option. `git checkout` doesn't support `--end-of-options` together with `--detach`.
+    run_git(
+        destination,
+        ["checkout", "--quiet", "--force", "--detach", revision],
+    )?;
+    run_git(destination, ["clean", "--quiet", "--force", "-d", "-x"])?;
+
+    run_git(destination, ["rev-parse", "HEAD"]).map(|stdout| stdout.trim().to_string())
+}
+
+fn is_commit_sha(reference: &str) -> bool {
+    matches!(reference.len(), 40 | 64) && reference.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn commit_exists(repository: &Path, commit: &str) -> bool {
+    run_git(
+        repository,
+        [
+            "cat-file",
+            "-e",
+            "--end-of-options",
+            &format!("{commit}^{{commit}}"),
+        ],
+    )
+    .is_ok()
+}
+
+fn run_git<I, S>(working_dir: &Path, args: I) -> Result<String, GitError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut command = Command::new("git");
+    command
+        .args(args)
+        .current_dir(working_dir)
+        .env("GIT_TERMINAL_PROMPT", "0");
+
+    // Keep the tests independent of the git configuration of the host.
+    #[cfg(test)]
+    command
+        .env("GIT_CONFIG_GLOBAL", "/dev/null")
+        .env("GIT_CONFIG_NOSYSTEM", "1");
+
+    let output = command.output().map_err(|io_error| {
+        if io_error.kind() == ErrorKind::NotFound {
+            GitError::GitNotFound
+        } else {
+            GitError::IoError(io_error)
+        }
+    })?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(GitError::CommandFailed {
+            command: command
+                .get_args()
+                .fold(String::from("git"), |command, arg| {
+                    command + " " + &arg.to_string_lossy()
+                }),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        })
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GitError {
+    #[error("The git CLI could not be found, make sure git is installed and on the PATH")]
+    GitNotFound,
+
+    #[error("Command `{command}` failed: {stderr}")]
+    CommandFailed { command: String, stderr: String },
+
+    #[error("I/O error while running git: {0}")]
+    IoError(std::io::Error),
+
+    #[error("Invalid git repository URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("Invalid git ref: {0}")]
+    InvalidReference(String),
+}
+
+/// A definition for a cached layer that contains a checkout of a git repository.
+///
+/// Refer to the docs of [`CachedGitLayerExt::cached_git_layer`] for usage examples.
+#[derive(Debug, Clone, Copy)]
+pub struct CachedGitLayerDefinition<'a> {
+    /// Whether the layer is intended for build.
+    pub build: bool,
+    /// Whether the layer is intended for launch.
+    pub launch: bool,
+    /// The URL of the git repository.
+    pub url: &'a str,
+    /// The ref to check out, i.e. a branch, tag or full commit SHA.
+    pub reference: &'a str,
+}
+
+/// The layer metadata written by [`CachedGitLayerExt::cached_git_layer`].
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CachedGitLayerMetadata {
+    pub url: String,
+    pub reference: String,
+    /// The full SHA of the checked out commit.
+    pub commit: String,
+}
+
+/// A cached layer containing a checkout of a git repository.
+pub struct CachedGitLayer<B: Buildpack> {
+    pub layer_ref: LayerRef<B, (), ()>,
+    /// The full SHA of the checked out commit.
+    pub commit: String,
+}
+
+/// Extends [`BuildContext`] with cached layers for git repositories.
+pub trait CachedGitLayerExt<B: Buildpack> {
+    /// Creates a cached layer that contains a checkout of the given git repository ref.
+    ///
+    /// If the layer was cached from a previous build of the same repository URL, the existing
+    /// checkout is updated incrementally. Otherwise, the cached layer is discarded and the
+    /// repository is fetched from scratch. Checkouts are done with [`checkout`], the layer
+    /// contains the `.git` directory of the checkout. The URL, ref and resolved commit are stored
+    /// as layer metadata ([`CachedGitLayerMetadata`]).
+    ///
+    /// The buildpack's error type must be convertible from [`GitError`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
+    /// # use libcnb::detect::{DetectContext, DetectResult};
+    /// # use libcnb::generic::{GenericMetadata, GenericPlatform};
+    /// # use libcnb::Buildpack;
+    /// # use libcnb::data::layer_name;
+    /// use libherokubuildpack::git::{CachedGitLayerDefinition, CachedGitLayerExt, GitError};
+    /// #
+    /// # struct ExampleBuildpack;
+    ///
+    /// #[derive(Debug)]
+    /// enum ExampleBuildpackError {
+    ///     PluginCheckoutError(GitError),
+    /// }
+    ///
+    /// impl From<GitError> for ExampleBuildpackError {
+    ///     fn from(value: GitError) -> Self {
+    ///         Self::PluginCheckoutError(value)
+    ///     }
+    /// }
+    /// #
+    /// # impl Buildpack for ExampleBuildpack {
+    /// #    type Platform = GenericPlatform;
+    /// #    type Metadata = GenericMetadata;
+    /// #    type Error = ExampleBuildpackError;
+    /// #
+    /// #    fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+    /// #        unimplemented!()
+    /// #    }
+    /// #
+    /// #    fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
+    /// let plugin = context.cached_git_layer(
+    ///     layer_name!("plugin"),
+    ///     CachedGitLayerDefinition {
+    ///         build: true,
+    ///         launch: false,
+    ///         url: "https://github.com/example/plugin.git",
+    ///         reference: "v1.2.3",
+    ///     },
+    /// )?;
+    ///
+    /// println!("Installed plugin at commit {}", plugin.commit);
+    /// #
+    /// #        BuildResultBuilder::new().build()
+    /// #    }
+    /// # }
+    /// ```
+    fn cached_git_layer(
+        &self,
+        layer_name: impl Borrow<LayerName>,
+        layer_definition: CachedGitLayerDefinition,
+    ) -> libcnb::Result<CachedGitLayer<B>, B::Error>
+    where
+        B::Error: From<GitError>;
+}
+
+impl<B: Buildpack> CachedGitLayerExt<B> for BuildContext<B> {
+    fn cached_git_layer(
+        &self,
+        layer_name: impl Borrow<LayerName>,
+        layer_definition: CachedGitLayerDefinition,
+    ) -> libcnb::Result<CachedGitLayer<B>, B::Error>
+    where
+        B::Error: From<GitError>,
+    {
+        let layer_ref = self.cached_layer(
+            layer_name,
+            CachedLayerDefinition {
+                build: layer_definition.build,
+                launch: layer_definition.launch,
+                invalid_metadata_action: &|_| InvalidMetadataAction::DeleteLayer,
+                restored_layer_action: &|metadata: &CachedGitLayerMetadata, _| {
+                    restored_layer_action(metadata, layer_definition.url)
+                },
+            },
+        )?;
+
+        let commit = checkout(
+            layer_definition.url,
+            layer_definition.reference,
+            layer_ref.path(),
+        )
+        .map_err(|error| libcnb::Error::BuildpackError(B::Error::from(error)))?;
+
+        layer_ref.write_metadata(CachedGitLayerMetadata {
+            url: String::from(layer_definition.url),
+            reference: String::from(layer_definition.reference),
+            commit: commit.clone(),
+        })?;
+
+        Ok(CachedGitLayer { layer_ref, commit })
+    }
+}
+
+fn restored_layer_action(
+    restored_metadata: &CachedGitLayerMetadata,
+    expected_url: &str,
+) -> RestoredLayerAction {
+    if restored_metadata.url == expected_url {
+        RestoredLayerAction::KeepLayer
+    } else {
+        RestoredLayerAction::DeleteLayer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn create_repository(path: &Path) -> String {
+        run_git(path, ["init", "--quiet", "--initial-branch", "main"]).unwrap();
+        run_git(path, ["config", "user.name", "Test"]).unwrap();
+        run_git(path, ["config", "user.email", "test@example.com"]).unwrap();
+        commit_file(path, "README.md", "v1");
+        run_git(path, ["tag", "v1"]).unwrap();
+        commit_file(path, "README.md", "v2")
+    }
+
+    fn commit_file(repository: &Path, name: &str, contents: &str) -> String {
+        fs::write(repository.join(name), contents).unwrap();
+        run_git(repository, ["add", name]).unwrap();
+        run_git(repository, ["commit", "--quiet", "--message", contents]).unwrap();
+        run_git(repository, ["rev-parse", "HEAD"])
+            .unwrap()
+            .trim()
+            .to_string()
+    }
+
+    #[test]
+    fn checkout_refs() {
+        let temp_dir = tempdir().unwrap();
+        let repository = temp_dir.path().join("repository");
+        fs::create_dir(&repository).unwrap();
+        let head = create_repository(&repository);
+        let url = format!("file://{}", repository.display());
+        let destination = temp_dir.path().join("checkout");
+
+        assert_eq!(checkout(&url, "main", &destination).unwrap(), head);
+        assert_eq!(
+            fs::read_to_string(destination.join("README.md")).unwrap(),
+            "v2"
+        );
+
+        // Local changes are discarded when updating an existing checkout.
+        fs::write(destination.join("README.md"), "modified").unwrap();
+        fs::write(destination.join("untracked.txt"), "untracked").unwrap();
+
+        let v1 = checkout(&url, "v1", &destination).unwrap();
+        assert_ne!(v1, head);
+        assert_eq!(
+            fs::read_to_string(destination.join("README.md")).unwrap(),
+            "v1"
+        );
+        assert!(!destination.join("untracked.txt").exists());
+
+        let v3 = commit_file(&repository, "README.md", "v3");
+        assert_eq!(checkout(&url, &v3, &destination).unwrap(), v3);
+        assert_eq!(
+            fs::read_to_string(destination.join("README.md")).unwrap(),
+            "v3"
+        );
+    }
+
+    #[test]
+    fn checkout_present_commit_without_fetching() {
+        let temp_dir = tempdir().unwrap();
+        let repository = temp_dir.path().join("repository");
+        fs::create_dir(&repository).unwrap();
+        let head = create_repository(&repository);
+        let destination = temp_dir.path().join("checkout");
+
+        checkout(
+            &format!("file://{}", repository.display()),
+            &head,
+            &destination,
+        )
+        .unwrap();
+        fs::write(destination.join("README.md"), "modified").unwrap();
+
+        // The repository is gone, so this would fail if the commit was fetched again.
+        fs::remove_dir_all(&repository).unwrap();
+        assert_eq!(
+            checkout(
+                &format!("file://{}", repository.display()),
+                &head,
+                &destination
+            )
+            .unwrap(),
+            head
+        );
+        assert_eq!(
+            fs::read_to_string(destination.join("README.md")).unwrap(),
+            "v2"
+        );
+    }
+
+    #[test]
+    fn checkout_option_like_arguments() {
+        let temp_dir = tempdir().unwrap();
+
+        assert!(matches!(
+            checkout("--upload-pack=touch /tmp/pwned", "main", temp_dir.path()),
+            Err(GitError::InvalidUrl(_))
+        ));
+        assert!(matches!(
+            checkout(
+                "https://github.com/example/plugin.git",
+                "--upload-pack=touch /tmp/pwned",
+                temp_dir.path()
+            ),
+            Err(GitError::InvalidReference(_))
+        ));
+    }
+
+    #[test]
+    fn checkout_unknown_ref() {
+        let temp_dir = tempdir().unwrap();
+        let repository = temp_dir.path().join("repository");
+        fs::create_dir(&repository).unwrap();
+        create_repository(&repository);
+
+        assert!(matches!(
+            checkout(
+                &format!("file://{}", repository.display()),
+                "does-not-exist",
+                temp_dir.path().join("checkout")
+            ),
+            Err(GitError::CommandFailed { ref command, .. }) if command.starts_with("git fetch")
+        ));
+    }
+
+    #[test]
+    fn restored_layer_action_invalidation() {
+        let metadata = CachedGitLayerMetadata {
+            url: String::from("https://github.com/example/plugin.git"),
+            reference: String::from("v1.2.3"),
+            commit: String::from("9fceb02d0ae598e95dc970b74767f19372d61af8"),
+        };
+
+        assert!(matches!(
+            restored_layer_action(&metadata, "https://github.com/example/plugin.git"),
+            RestoredLayerAction::KeepLayer
+        ));
+        assert!(matches!(
+            restored_layer_action(&metadata, "https://github.com/example/fork.git"),
+            RestoredLayerAction::DeleteLayer
+        ));
+    }
+}
@@ -0,0 +1,295 @@
+//! Ed25519 signature verification for inventory files.
+//!
+//! Inventories are often fetched over the network at build time. To be able to trust such an
+//! inventory, its publisher can sign the serialized inventory file with an Ed25519 key. The
+//! buildpack then verifies the signature with the corresponding public key (usually provided via
+//! the buildpack's metadata in `buildpack.toml`) before resolving any artifacts from it.
+//!
+//! Keys and signatures are represented as hex strings. Use [`parse_inventory_with_signature`] to
+//! parse an inventory after verifying its signature.
+use crate::inventory::checksum::Digest;
+use crate::inventory::{Inventory, ParseInventoryError};
+use ed25519_dalek::Verifier;
+use hex::FromHexError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// An Ed25519 public key used to verify inventory signatures.
+///
+/// Can be parsed from and serialized to a hex string. This allows it to be used in buildpack
+/// metadata types directly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyingKey(ed25519_dalek::VerifyingKey);
+
+/// An Ed25519 signature of a serialized inventory.
+///
+/// Can be parsed from and serialized to a hex string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature(ed25519_dalek::Signature);
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum ParseVerifyingKeyError {
+    #[error("Verifying key cannot be parsed as hex string: {0}")]
+    InvalidHex(FromHexError),
+    #[error("Verifying key length {0} is invalid")]
+    InvalidLength(usize),
+    #[error("Verifying key is not a valid Ed25519 public key")]
+    InvalidKey,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum ParseSignatureError {
+    #[error("Signature cannot be parsed as hex string: {0}")]
+    InvalidHex(FromHexError),
+    #[error("Signature length {0} is invalid")]
+    InvalidLength(usize),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ParseSignedInventoryError {
+    #[error("Inventory signature is invalid")]
+    InvalidSignature,
+    #[error("{0}")]
+    ParseInventoryError(ParseInventoryError),
+}
+
+impl FromStr for VerifyingKey {
+    type Err = ParseVerifyingKeyError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(value.trim()).map_err(ParseVerifyingKeyError::InvalidHex)?;
+
+        let bytes = <[u8; ed25519_dalek::PUBLIC_KEY_LENGTH]>::try_from(bytes.as_slice())
+            .map_err(|_| ParseVerifyingKeyError::InvalidLength(bytes.len()))?;
+
+        ed25519_dalek::VerifyingKey::from_bytes(&bytes)
+            .map(Self)
+            .map_err(|_| ParseVerifyingKeyError::InvalidKey)
+    }
+}
+
+impl FromStr for Signature {
+    type Err = ParseSignatureError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(value.trim()).map_err(ParseSignatureError::InvalidHex)?;
+
+        ed25519_dalek::Signature::from_slice(&bytes)
+            .map(Self)
+            .map_err(|_| ParseSignatureError::InvalidLength(bytes.len()))
+    }
+}
+
+impl From<ed25519_dalek::VerifyingKey> for VerifyingKey {
+    fn from(value: ed25519_dalek::VerifyingKey) -> Self {
+        Self(value)
+    }
+}
+
+impl From<ed25519_dalek::Signature> for Signature {
+    fn from(value: ed25519_dalek::Signature) -> Self {
+        Self(value)
+    }
+}
+
+impl VerifyingKey {
+    /// Verifies that the given signature is a valid signature of the given data for this key.
+    #[must_use]
+    pub fn verify(&self, data: &[u8], signature: &Signature) -> bool {
+        self.0.verify(data, &signature.0).is_ok()
+    }
+}
+
+impl Serialize for VerifyingKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&hex::encode(self.0.as_bytes()))
+    }
+}
+
+impl<'de> Deserialize<'de> for VerifyingKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse::<Self>()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for Signature {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&hex::encode(self.0.to_bytes()))
+    }
+}
+
+impl<'de> Deserialize<'de> for Signature {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse::<Self>()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Parses an inventory from TOML after verifying its signature.
+///
+/// The signature must have been created over the exact bytes of the given string. The inventory
+/// is only parsed if the signature is valid for the given key.
+///
+/// # Example
+/// ```rust
+/// use ed25519_dalek::{Signer, SigningKey};
+/// use libherokubuildpack::inventory::signature::{
+///     parse_inventory_with_signature, Signature, VerifyingKey,
+/// };
+/// use libherokubuildpack::inventory::Inventory;
+/// use semver::Version;
+/// use sha2::Sha256;
+///
+/// let inventory_toml = r#"[[artifacts]]
+/// version = "1.0.0"
+/// os = "linux"
+/// arch = "arm64"
+/// url = "https://example.com/foo.txt"
+/// checksum = "sha256:2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae"
+/// "#;
+///
+/// // The publisher of the inventory signs it with their private key...
+/// let signing_key = SigningKey::from_bytes(&[1; 32]);
+/// let signature_hex = hex::encode(signing_key.sign(inventory_toml.as_bytes()).to_bytes());
+/// let verifying_key_hex = hex::encode(signing_key.verifying_key().as_bytes());
+///
+/// // ...and the buildpack verifies it with the public key before using the inventory.
+/// let inventory: Inventory<Version, Sha256, Option<()>> = parse_inventory_with_signature(
+///     inventory_toml,
+///     &signature_hex.parse::<Signature>().unwrap(),
+///     &verifying_key_hex.parse::<VerifyingKey>().unwrap(),
+/// )
+/// .unwrap();
+///
+/// assert_eq!(inventory.artifacts.len(), 1);
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the signature is invalid or the inventory couldn't be parsed.
+pub fn parse_inventory_with_signature<V, D, M>(
+    s: &str,
+    signature: &Signature,
+    verifying_key: &VerifyingKey,
+) -> Result<Inventory<V, D, M>, ParseSignedInventoryError>
+where
+    V: Serialize + DeserializeOwned,
+    D: Digest,
+    M: Serialize + DeserializeOwned,
+{
+    if verifying_key.verify(s.as_bytes(), signature) {
+        s.parse::<Inventory<V, D, M>>()
+            .map_err(ParseSignedInventoryError::ParseInventoryError)
+    } else {
+        Err(ParseSignedInventoryError::InvalidSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    #[derive(Debug, Clone)]
+    struct BogusDigest;
+
+    impl Digest for BogusDigest {
+        fn name_compatible(name: &str) -> bool {
+            name == "bogus"
+        }
+
+        fn length_compatible(len: usize) -> bool {
+            len == 4
+        }
+    }
+
+    const INVENTORY_TOML: &str = r#"[[artifacts]]
+version = "1.0.0"
+os = "linux"
+arch = "amd64"
+url = "https://example.com/foo.tgz"
+checksum = "bogus:cafebabe"
+"#;
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_bytes(&[42; ed25519_dalek::SECRET_KEY_LENGTH])
+    }
+
+    fn verifying_key() -> VerifyingKey {
+        VerifyingKey::from(signing_key().verifying_key())
+    }
+
+    #[test]
+    fn test_valid_signature() {
+        let signature = Signature::from(signing_key().sign(INVENTORY_TOML.as_bytes()));
+
+        let inventory: Inventory<String, BogusDigest, Option<()>> =
+            parse_inventory_with_signature(INVENTORY_TOML, &signature, &verifying_key()).unwrap();
+
+        assert_eq!(inventory.artifacts.len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_signature() {
+        let signature = Signature::from(signing_key().sign(b"something else"));
+
+        let result = parse_inventory_with_signature::<String, BogusDigest, Option<()>>(
+            INVENTORY_TOML,
+            &signature,
+            &verifying_key(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(ParseSignedInventoryError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_key_and_signature_hex_round_trip() {
+        let key = verifying_key();
+        let key_hex = toml::Value::try_from(&key).unwrap();
+        assert_eq!(
+            key_hex.as_str().unwrap().parse::<VerifyingKey>().unwrap(),
+            key
+        );
+
+        let signature = Signature::from(signing_key().sign(INVENTORY_TOML.as_bytes()));
+        let signature_hex = hex::encode(signature.0.to_bytes());
+        assert_eq!(signature_hex.parse::<Signature>().unwrap(), signature);
+    }
+
+    #[test]
+    fn test_invalid_key_and_signature_strings() {
+        assert_eq!(
+            "zz".parse::<VerifyingKey>(),
+            Err(ParseVerifyingKeyError::InvalidHex(
+                FromHexError::InvalidHexCharacter { c: 'z', index: 0 }
+            ))
+        );
+        assert_eq!(
+            "cafebabe".parse::<VerifyingKey>(),
+            Err(ParseVerifyingKeyError::InvalidLength(4))
+        );
+        assert_eq!(
+            "cafebabe".parse::<Signature>(),
+            Err(ParseSignatureError::InvalidLength(4))
+        );
+    }
+}
@@ -0,0 +1,419 @@
+//! Higher-level layer helpers built on top of libcnb's layer API.
+
+use crate::digest::sha256;
+use crate::download::{download_file, DownloadError};
+use crate::tar::decompress_tarball;
+use libcnb::build::BuildContext;
+use libcnb::data::layer::LayerName;
+use libcnb::layer::{
+    CachedLayerDefinition, InvalidMetadataAction, LayerRef, LayerState, RestoredLayerAction,
+};
+use libcnb::Buildpack;
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::fs;
+use std::path::Path;
+
+/// A definition for a cached layer that contains a downloaded and unpacked artifact.
+///
+/// Refer to the docs of [`CachedDownloadLayerExt::cached_download_layer`] for usage examples.
+#[derive(Debug, Clone, Copy)]
+pub struct CachedDownloadLayerDefinition<'a> {
+    /// Whether the layer is intended for build.
+    pub build: bool,
+    /// Whether the layer is intended for launch.
+    pub launch: bool,
+    /// The URL of the gzipped tarball to download.
+    pub url: &'a str,
+    /// The expected SHA-256 checksum of the downloaded tarball, as a hex string.
+    pub sha256: &'a str,
+}
+
+/// The layer metadata written by [`CachedDownloadLayerExt::cached_download_layer`].
+///
+/// Used to invalidate the cached layer when the URL or checksum of the artifact changes.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CachedDownloadLayerMetadata {
+    pub url: String,
+    pub sha256: String,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CachedDownloadLayerError {
+    #[error("Couldn't download artifact: {0}")]
+    DownloadError(DownloadError),
+
+    #[error(
+        "Checksum of downloaded artifact doesn't match. Expected: {expected}, actual: {actual}"
+    )]
+    ChecksumMismatch { expected: String, actual: String },
+
+    #[error("I/O error while unpacking downloaded artifact: {0}")]
+    IoError(std::io::Error),
+}
+
+/// Extends [`BuildContext`] with cached layers for downloaded artifacts.
+pub trait CachedDownloadLayerExt<B: Buildpack> {
+    /// Creates a cached layer that contains the contents of the gzipped tarball at the given URL.
+    ///
+    /// If the layer was cached from a previous build with the same URL and checksum, the cached
+    /// layer is kept as-is. Otherwise, the tarball is downloaded, its SHA-256 checksum is verified
+    /// and its contents are unpacked into the layer. The URL and checksum are stored as layer
+    /// metadata ([`CachedDownloadLayerMetadata`]) to automatically invalidate the cached layer when
+    /// either of them changes.
+    ///
+    /// The buildpack's error type must be convertible from [`CachedDownloadLayerError`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
+    /// # use libcnb::detect::{DetectContext, DetectResult};
+    /// # use libcnb::generic::{GenericMetadata, GenericPlatform};
+    /// # use libcnb::layer::LayerState;
+    /// # use libcnb::Buildpack;
+    /// # use libcnb::data::layer_name;
+    /// use libherokubuildpack::layer::{
+    ///     CachedDownloadLayerDefinition, CachedDownloadLayerError, CachedDownloadLayerExt,
+    /// };
+    /// #
+    /// # struct ExampleBuildpack;
+    ///
+    /// #[derive(Debug)]
+    /// enum ExampleBuildpackError {
+    ///     RuntimeLayerError(CachedDownloadLayerError),
+    /// }
+    ///
+    /// impl From<CachedDownloadLayerError> for ExampleBuildpackError {
+    ///     fn from(value: CachedDownloadLayerError) -> Self {
+    ///         Self::RuntimeLayerError(value)
+    ///     }
+    /// }
+    /// #
+    /// # impl Buildpack for ExampleBuildpack {
+    /// #    type Platform = GenericPlatform;
+    /// #    type Metadata = GenericMetadata;
+    /// #    type Error = ExampleBuildpackError;
+    /// #
+    /// #    fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+    /// #        unimplemented!()
+    /// #    }
+    /// #
+    /// #    fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
+    /// let layer_ref = context.cached_download_layer(
+    ///     layer_name!("runtime"),
+    ///     CachedDownloadLayerDefinition {
+    ///         build: true,
+    ///         launch: true,
+    ///         url: "https://example.com/runtime-1.2.3.tar.gz",
+    ///         sha256: "ea8fac7c65fb589b0d53560f5251f74f9e9b243478dcb6b3ea79b5e36449c8d9",
+    ///     },
+    /// )?;
+    ///
+    /// if let LayerState::Restored { .. } = layer_ref.state {
+    ///     println!("Reusing cached runtime");
+    /// }
+    /// #
+    /// #        BuildResultBuilder::new().build()
+    /// #    }
+    /// # }
+    /// ```
+    fn cached_download_layer(
+        &self,
+        layer_name: impl Borrow<LayerName>,
+        layer_definition: CachedDownloadLayerDefinition,
+    ) -> libcnb::Result<LayerRef<B, (), ()>, B::Error>
+    where
+        B::Error: From<CachedDownloadLayerError>;
+}
+
+impl<B: Buildpack> CachedDownloadLayerExt<B> for BuildContext<B> {
+    fn cached_download_layer(
+        &self,
+        layer_name: impl Borrow<LayerName>,
+        layer_definition: CachedDownloadLayerDefinition,
+    ) -> libcnb::Result<LayerRef<B, (), ()>, B::Error>
+    where
+        B::Error: From<CachedDownloadLayerError>,
+    {
+        let layer_metadata = CachedDownloadLayerMetadata {
+            url: String::from(layer_definition.url),
+            sha256: String::from(layer_definition.sha256),
+        };
+
+        let layer_ref = self.cached_layer(
+            layer_name,
+            CachedLayerDefinition {
+                build: layer_definition.build,
+                launch: layer_definition.launch,
+                invalid_metadata_action: &|_| InvalidMetadataAction::DeleteLayer,
+                restored_layer_action: &|metadata: &CachedDownloadLayerMetadata, _| {
+                    restored_layer_action(metadata, &layer_metadata)
+                },
+            },
+        )?;
+
+        if let LayerState::Empty { .. } = layer_ref.state {
+            download_and_unpack(
+                layer_definition.url,
+                layer_definition.sha256,
+                &layer_ref.path(),
+            )
+            .map_err(|error| libcnb::Error::BuildpackError(B::Error::from(error)))?;
+
+            layer_ref.write_metadata(layer_metadata)?;
+        }
+
+        Ok(layer_ref)
+    }
+}
+
+fn restored_layer_action(
+    restored_metadata: &CachedDownloadLayerMetadata,
+    expected_metadata: &CachedDownloadLayerMetadata,
+) -> RestoredLayerAction {
+    if restored_metadata == expected_metadata {
+        RestoredLayerAction::KeepLayer
+    } else {
+        RestoredLayerAction::DeleteLayer
+    }
+}
+
+fn download_and_unpack(
+    url: &str,
+    expected_sha256: &str,
+    destination: &Path,
+) -> Result<(), CachedDownloadLayerError> {
+    fetch_and_unpack(
+        |archive_path| {
+            download_file(url, archive_path).map_err(CachedDownloadLayerError::DownloadError)
+        },
+        expected_sha256,
+        destination,
+    )
+}
+
+/// Fetches the archive with the given function, then verifies and unpacks it.
+///
+/// The archive is always removed afterwards, even if fetching, verifying or unpacking it failed.
+/// Errors from fetching, verifying or unpacking take precedence over errors from the removal.
+fn fetch_and_unpack(
+    fetch: impl FnOnce(&Path) -> Result<(), CachedDownloadLayerError>,
+    expected_sha256: &str,
+    destination: &Path,
+) -> Result<(), CachedDownloadLayerError> {
+    let archive_path = destination.join(".libherokubuildpack-download.tar.gz");
+
+    let result = fetch(&archive_path)
+        .and_then(|()| verify_and_unpack(&archive_path, expected_sha256, destination));
+
+    let cleanup_result = match fs::remove_file(&archive_path) {
+        Err(io_error) if io_error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        other => other.map_err(CachedDownloadLayerError::IoError),
+    };
+
+    result.and(cleanup_result)
+}
+
+fn verify_and_unpack(
+    archive_path: &Path,
+    expected_sha256: &str,
+    destination: &Path,
+) -> Result<(), CachedDownloadLayerError> {
+    let actual_sha256 = sha256(archive_path).map_err(CachedDownloadLayerError::IoError)?;
+
+    if !actual_sha256.eq_ignore_ascii_case(expected_sha256) {
+        return Err(CachedDownloadLayerError::ChecksumMismatch {
+            expected: String::from(expected_sha256),
+            actual: actual_sha256,
+        });
+    }
+
+    fs::File::open(archive_path)
+        .and_then(|mut archive| decompress_tarball(&mut archive, destination))
+        .map_err(CachedDownloadLayerError::IoError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use tempfile::tempdir;
+
+    fn write_tarball(path: &Path) {
+        let mut builder = tar::Builder::new(GzEncoder::new(
+            fs::File::create(path).unwrap(),
+            Compression::default(),
+        ));
+
+        let contents = b"Hello World!";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "bin/hello.txt", &contents[..])
+            .unwrap();
+
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn verify_and_unpack_valid_checksum() {
+        let temp_dir = tempdir().unwrap();
+        let archive_path = temp_dir.path().join("archive.tar.gz");
+        let destination = temp_dir.path().join("layer");
+        fs::create_dir(&destination).unwrap();
+        write_tarball(&archive_path);
+
+        let checksum = sha256(&archive_path).unwrap();
+        verify_and_unpack(&archive_path, &checksum.to_uppercase(), &destination).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(destination.join("bin/hello.txt")).unwrap(),
+            "Hello World!"
+        );
+    }
+
+    #[test]
+    fn verify_and_unpack_checksum_mismatch() {
+        let temp_dir = tempdir().unwrap();
+        let archive_path = temp_dir.path().join("archive.tar.gz");
+        let destination = temp_dir.path().join("layer");
+        fs::create_dir(&destination).unwrap();
+        write_tarball(&archive_path);
+
+        let result = verify_and_unpack(&archive_path, "cafebabe", &destination);
+
+        assert!(matches!(
+            result,
+            Err(CachedDownloadLayerError::ChecksumMismatch { ref expected, .. }) if expected == "cafebabe"
+        ));
+        assert!(!destination.join("bin").exists());
+    }
+
+    #[test]
+    fn fetch_and_unpack_removes_archive() {
+        let temp_dir = tempdir().unwrap();
+        let source_path = temp_dir.path().join("archive.tar.gz");
+        let destination = temp_dir.path().join("layer");
+        fs::create_dir(&destination).unwrap();
+        write_tarball(&source_path);
+        let checksum = sha256(&source_path).unwrap();
+
+        fetch_and_unpack(
+            |archive_path| {
+                fs::copy(&source_path, archive_path)
+                    .map(|_| ())
+                    .map_err(CachedDownloadLayerError::IoError)
+            },
+            &checksum,
+            &destination,
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_dir(&destination)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name())
+                .collect::<Vec<_>>(),
+            ["bin"]
+        );
+    }
+
+    #[test]
+    fn fetch_and_unpack_removes_partial_download() {
+        let temp_dir = tempdir().unwrap();
+
+        let result = fetch_and_unpack(
+            |archive_path| {
+                fs::write(archive_path, "partial").unwrap();
+                Err(CachedDownloadLayerError::DownloadError(
+                    DownloadError::IoError(std::io::Error::other("connection reset")),
+                ))
+            },
+            "cafebabe",
+            temp_dir.path(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(CachedDownloadLayerError::DownloadError(_))
+        ));
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn fetch_and_unpack_removes_archive_on_checksum_mismatch() {
+        let temp_dir = tempdir().unwrap();
+
+        let result = fetch_and_unpack(
+            |archive_path| {
+                fs::write(archive_path, "not a tarball").map_err(CachedDownloadLayerError::IoError)
+            },
+            "cafebabe",
+            temp_dir.path(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(CachedDownloadLayerError::ChecksumMismatch { .. })
+        ));
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn fetch_and_unpack_fetch_error_without_archive() {
+        let temp_dir = tempdir().unwrap();
+
+        let result = fetch_and_unpack(
+            |_| {
+                Err(CachedDownloadLayerError::DownloadError(
+                    DownloadError::IoError(std::io::Error::other("connection refused")),
+                ))
+            },
+            "cafebabe",
+            temp_dir.path(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(CachedDownloadLayerError::DownloadError(_))
+        ));
+    }
+
+    #[test]
+    fn restored_layer_action_invalidation() {
+        let metadata = CachedDownloadLayerMetadata {
+            url: String::from("https://example.com/foo-1.0.0.tar.gz"),
+            sha256: String::from("cafebabe"),
+        };
+
+        assert!(matches!(
+            restored_layer_action(&metadata, &metadata.clone()),
+            RestoredLayerAction::KeepLayer
+        ));
+
+        assert!(matches!(
+            restored_layer_action(
+                &metadata,
+                &CachedDownloadLayerMetadata {
+                    url: String::from("https://example.com/foo-1.0.1.tar.gz"),
+                    ..metadata.clone()
+                }
+            ),
+            RestoredLayerAction::DeleteLayer
+        ));
+
+        assert!(matches!(
+            restored_layer_action(
+                &metadata,
+                &CachedDownloadLayerMetadata {
+                    sha256: String::from("deadbeef"),
+                    ..metadata.clone()
+                }
+            ),
+            RestoredLayerAction::DeleteLayer
+        ));
+    }
+}
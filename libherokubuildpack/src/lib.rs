@@ -1,5 +1,7 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "archive")]
+pub mod archive;
 #[cfg(feature = "command")]
 pub mod command;
 #[cfg(feature = "digest")]
@@ -10,13 +12,31 @@ pub mod download;
 pub mod error;
 #[cfg(feature = "fs")]
 pub mod fs;
+#[cfg(feature = "git")]
+pub mod git;
 #[cfg(feature = "inventory")]
 pub mod inventory;
+#[cfg(feature = "layer")]
+pub mod layer;
 #[cfg(feature = "log")]
 pub mod log;
+#[cfg(feature = "procfile")]
+pub mod procfile;
 #[cfg(feature = "tar")]
 pub mod tar;
 #[cfg(feature = "toml")]
 pub mod toml;
+#[cfg(feature = "version-requirement")]
+pub mod version_requirement;
+#[cfg(feature = "wrapper")]
+pub mod wrapper;
 #[cfg(feature = "write")]
 pub mod write;
+
+// Suppress warnings due to the `unused_crate_dependencies` lint not handling doctests well.
+#[cfg(test)]
+use hex as _;
+#[cfg(test)]
+use semver as _;
+#[cfg(test)]
+use sha2 as _;
@@ -1,3 +1,5 @@
+use libcnb_common::color::color_output_enabled;
+use std::env;
 use std::io::{self, Write};
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 
@@ -5,14 +7,50 @@ use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 ///
 /// Will panic if there was a problem setting the color settings, or all bytes could
 /// not be written due to either I/O errors or EOF being reached.
+pub fn log_error(header: impl AsRef<str>, body: impl AsRef<str>) {
+    write_error(header.as_ref(), body.as_ref());
+}
+
+/// Like [`log_error`], but wraps the body to the width of the terminal (from the `COLUMNS`
+/// environment variable, falling back to 80 columns). Words longer than a line, i.e. URLs, are
+/// never split.
+///
+/// # Panics
+///
+/// Will panic if there was a problem setting the color settings, or all bytes could
+/// not be written due to either I/O errors or EOF being reached.
+pub fn log_error_wrapped(header: impl AsRef<str>, body: impl AsRef<str>) {
+    write_error(header.as_ref(), &wrap_text(body.as_ref(), output_width()));
+}
+
+/// # Panics
+///
+/// Will panic if there was a problem setting the color settings, or all bytes could
+/// not be written due to either I/O errors or EOF being reached.
+pub fn log_warning(header: impl AsRef<str>, body: impl AsRef<str>) {
+    write_warning(header.as_ref(), body.as_ref());
+}
+
+/// Like [`log_warning`], but wraps the body to the width of the terminal (from the `COLUMNS`
+/// environment variable, falling back to 80 columns). Words longer than a line, i.e. URLs, are
+/// never split.
+///
+/// # Panics
+///
+/// Will panic if there was a problem setting the color settings, or all bytes could
+/// not be written due to either I/O errors or EOF being reached.
+pub fn log_warning_wrapped(header: impl AsRef<str>, body: impl AsRef<str>) {
+    write_warning(header.as_ref(), &wrap_text(body.as_ref(), output_width()));
+}
+
 // TODO: Replace `.unwrap()` usages with `.expect()` to give a clearer error message:
 // https://github.com/heroku/libcnb.rs/issues/712
 #[allow(clippy::unwrap_used)]
-pub fn log_error(header: impl AsRef<str>, body: impl AsRef<str>) {
-    let mut stream = StandardStream::stderr(ColorChoice::Always);
+fn write_error(header: &str, body: &str) {
+    let mut stream = StandardStream::stderr(color_choice());
     write_styled_message(
         &mut stream,
-        format!("\n[Error: {}]", header.as_ref()),
+        format!("\n[Error: {header}]"),
         ColorSpec::new().set_fg(Some(Color::Red)).set_bold(true),
     )
     .unwrap();
@@ -21,18 +59,14 @@ pub fn log_error(header: impl AsRef<str>, body: impl AsRef<str>) {
     stream.flush().unwrap();
 }
 
-/// # Panics
-///
-/// Will panic if there was a problem setting the color settings, or all bytes could
-/// not be written due to either I/O errors or EOF being reached.
 // TODO: Replace `.unwrap()` usages with `.expect()` to give a clearer error message:
 // https://github.com/heroku/libcnb.rs/issues/712
 #[allow(clippy::unwrap_used)]
-pub fn log_warning(header: impl AsRef<str>, body: impl AsRef<str>) {
-    let mut stream = StandardStream::stderr(ColorChoice::Always);
+fn write_warning(header: &str, body: &str) {
+    let mut stream = StandardStream::stderr(color_choice());
     write_styled_message(
         &mut stream,
-        format!("\n[Warning: {}]", header.as_ref()),
+        format!("\n[Warning: {header}]"),
         ColorSpec::new().set_fg(Some(Color::Yellow)).set_bold(true),
     )
     .unwrap();
@@ -54,7 +88,7 @@ pub fn log_warning(header: impl AsRef<str>, body: impl AsRef<str>) {
 // https://github.com/heroku/libcnb.rs/issues/712
 #[allow(clippy::unwrap_used)]
 pub fn log_header(title: impl AsRef<str>) {
-    let mut stream = StandardStream::stdout(ColorChoice::Always);
+    let mut stream = StandardStream::stdout(color_choice());
     write_styled_message(
         &mut stream,
         format!("\n[{}]", title.as_ref()),
@@ -93,3 +127,101 @@ fn write_styled_message(
     }
     Ok(())
 }
+
+/// The output width used when the width of the terminal cannot be determined, i.e. on CI.
+const FALLBACK_OUTPUT_WIDTH: usize = 80;
+
+// See `libcnb_common::color` for how colors can be disabled.
+fn color_choice() -> ColorChoice {
+    if color_output_enabled() {
+        ColorChoice::Always
+    } else {
+        ColorChoice::Never
+    }
+}
+
+// Uses the `COLUMNS` environment variable that shells export for the terminal width.
+fn output_width() -> usize {
+    output_width_from_env(env::var("COLUMNS").ok())
+}
+
+fn output_width_from_env(columns: Option<String>) -> usize {
+    columns
+        .and_then(|columns| columns.trim().parse().ok())
+        .filter(|width| *width > 0)
+        .unwrap_or(FALLBACK_OUTPUT_WIDTH)
+}
+
+// Wraps each line of the given text at whitespace so that it fits the given width. Words longer
+// than the width (i.e. URLs) are never split. Continuation lines keep the indentation of the
+// original line.
+fn wrap_text(text: &str, width: usize) -> String {
+    text.split('\n')
+        .map(|line| {
+            if line.chars().count() <= width {
+                return String::from(line);
+            }
+
+            let indentation = &line[..line.len() - line.trim_start().len()];
+            let mut wrapped_lines = vec![String::from(indentation)];
+
+            for word in line.split_whitespace() {
+                let current_line = wrapped_lines
+                    .last_mut()
+                    .expect("wrapped lines are never empty");
+
+                if current_line.trim().is_empty() {
+                    current_line.push_str(word);
+                } else if current_line.chars().count() + 1 + word.chars().count() <= width {
+                    current_line.push(' ');
+                    current_line.push_str(word);
+                } else {
+                    wrapped_lines.push(format!("{indentation}{word}"));
+                }
+            }
+
+            wrapped_lines.join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_width() {
+        assert_eq!(output_width_from_env(None), FALLBACK_OUTPUT_WIDTH);
+        assert_eq!(output_width_from_env(Some(String::from("120"))), 120);
+        assert_eq!(
+            output_width_from_env(Some(String::from("0"))),
+            FALLBACK_OUTPUT_WIDTH
+        );
+        assert_eq!(
+            output_width_from_env(Some(String::from("wide"))),
+            FALLBACK_OUTPUT_WIDTH
+        );
+    }
+
+    #[test]
+    fn wrap_text() {
+        assert_eq!(super::wrap_text("short line", 20), "short line");
+        assert_eq!(
+            super::wrap_text("the quick brown fox jumps over the lazy dog", 15),
+            "the quick brown\nfox jumps over\nthe lazy dog"
+        );
+        assert_eq!(
+            super::wrap_text("  indented text that wraps", 15),
+            "  indented text\n  that wraps"
+        );
+        assert_eq!(
+            super::wrap_text("see https://example.com/a/very/long/url", 10),
+            "see\nhttps://example.com/a/very/long/url"
+        );
+        assert_eq!(
+            super::wrap_text("first paragraph\n\nsecond\n", 10),
+            "first\nparagraph\n\nsecond\n"
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//! Parsing of `Procfile` files, which declare the process types of an app, and their conversion
+//! into [`Process`] values for `launch.toml`.
+//!
+//! Uses the same semantics as the Heroku Procfile buildpack:
+//!
+//! - Each line declares a process type and its command, separated by a colon (`web: bundle exec
+//!   puma`). Whitespace around the process type and command is ignored.
+//! - Empty lines, comments (`# ...`) and lines without a colon are ignored.
+//! - If a process type is declared more than once, the last command wins, but the process type
+//!   keeps the position of its first declaration.
+//! - Commands are run with `bash -c`, so they can use shell syntax such as `$PORT`.
+//! - The `web` process is the default process. If there's no `web` process but only a single
+//!   process, that process is the default process.
+
+use libcnb::data::launch::{Process, ProcessBuilder, ProcessType, ProcessTypeError};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// The process types and commands of a `Procfile`, in order of declaration.
+///
+/// # Example
+/// ```
+/// use libcnb::data::process_type;
+/// use libherokubuildpack::procfile::Procfile;
+///
+/// let procfile: Procfile = "web: bundle exec puma -p $PORT\nworker: bundle exec sidekiq\n"
+///     .parse()
+///     .unwrap();
+///
+/// assert_eq!(
+///     procfile.get(&process_type!("web")),
+///     Some("bundle exec puma -p $PORT")
+/// );
+///
+/// let processes = procfile.processes();
+/// assert_eq!(processes[0].command, ["bash", "-c", "bundle exec puma -p $PORT"]);
+/// assert!(processes[0].default);
+/// assert!(!processes[1].default);
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Procfile {
+    entries: Vec<(ProcessType, String)>,
+}
+
+impl Procfile {
+    /// Creates a `Procfile` without any process types.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads and parses the `Procfile` at the given path.
+    ///
+    /// Returns `Ok(None)` if the file doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file couldn't be read or parsed.
+    pub fn read(path: impl AsRef<Path>) -> Result<Option<Self>, ReadProcfileError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => contents
+                .parse()
+                .map(Some)
+                .map_err(ReadProcfileError::ParseError),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(ReadProcfileError::IoError(error)),
+        }
+    }
+
+    /// Declares a process type, replacing the command of an already declared process type.
+    pub fn insert(&mut self, process_type: ProcessType, command: impl Into<String>) {
+        let command = command.into();
+
+        match self
+            .entries
+            .iter_mut()
+            .find(|(existing, _)| existing == &process_type)
+        {
+            Some((_, existing_command)) => *existing_command = command,
+            None => self.entries.push((process_type, command)),
+        }
+    }
+
+    /// Returns the command of the given process type.
+    #[must_use]
+    pub fn get(&self, process_type: &ProcessType) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(existing, _)| existing == process_type)
+            .map(|(_, command)| command.as_str())
+    }
+
+    /// Returns an iterator over the process types and commands, in order of declaration.
+    pub fn iter(&self) -> impl Iterator<Item = (&ProcessType, &str)> {
+        self.entries
+            .iter()
+            .map(|(process_type, command)| (process_type, command.as_str()))
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Converts the process types into [`Process`] values, in order of declaration.
+    ///
+    /// Commands are run with `bash -c`. See the [module documentation](self) for how the default
+    /// process is selected.
+    #[must_use]
+    pub fn processes(&self) -> Vec<Process> {
+        let default_process_type = match self.entries.as_slice() {
+            [(process_type, _)] => Some(process_type),
+            entries => entries
+                .iter()
+                .map(|(process_type, _)| process_type)
+                .find(|process_type| process_type.as_str() == "web"),
+        };
+
+        self.entries
+            .iter()
+            .map(|(process_type, command)| {
+                ProcessBuilder::new(process_type.clone(), ["bash", "-c", command])
+                    .default(Some(process_type) == default_process_type)
+                    .build()
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Procfile {
+    type Err = ProcfileParseError;
+
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        let mut procfile = Self::new();
+
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((name, command)) = line.split_once(':') else {
+                continue;
+            };
+
+            let process_type = name.trim().parse::<ProcessType>().map_err(|error| {
+                ProcfileParseError::InvalidProcessType {
+                    line_number: index + 1,
+                    error,
+                }
+            })?;
+
+            let command = command.trim();
+            if command.is_empty() {
+                return Err(ProcfileParseError::EmptyCommand {
+                    line_number: index + 1,
+                    process_type,
+                });
+            }
+
+            procfile.insert(process_type, command);
+        }
+
+        Ok(procfile)
+    }
+}
+
+/// An error while parsing a `Procfile`.
+#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+pub enum ProcfileParseError {
+    #[error("Invalid process type on line {line_number} of Procfile: {error}")]
+    InvalidProcessType {
+        line_number: usize,
+        error: ProcessTypeError,
+    },
+
+    #[error("Process type {process_type} on line {line_number} of Procfile has an empty command")]
+    EmptyCommand {
+        line_number: usize,
+        process_type: ProcessType,
+    },
+}
+
+/// An error while reading a `Procfile`, see [`Procfile::read`].
+#[derive(thiserror::Error, Debug)]
+pub enum ReadProcfileError {
+    #[error("Couldn't read Procfile: {0}")]
+    IoError(std::io::Error),
+
+    #[error(transparent)]
+    ParseError(ProcfileParseError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libcnb::data::process_type;
+
+    #[test]
+    fn parse() {
+        let procfile: Procfile = "\
+# Processes of the app
+web: bundle exec puma -C config/puma.rb\r
+  worker :   bundle exec sidekiq
+
+release: rake db:migrate
+this line is ignored
+web: bundle exec puma -p $PORT
+"
+        .parse()
+        .unwrap();
+
+        assert_eq!(
+            procfile.iter().collect::<Vec<_>>(),
+            [
+                (&process_type!("web"), "bundle exec puma -p $PORT"),
+                (&process_type!("worker"), "bundle exec sidekiq"),
+                (&process_type!("release"), "rake db:migrate"),
+            ]
+        );
+        assert_eq!(procfile.len(), 3);
+        assert_eq!(procfile.get(&process_type!("console")), None);
+
+        assert_eq!("".parse(), Ok(Procfile::new()));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "web: puma\nweb worker: sidekiq".parse::<Procfile>(),
+            Err(ProcfileParseError::InvalidProcessType {
+                line_number: 2,
+                error: ProcessTypeError::InvalidValue(String::from("web worker"))
+            })
+        );
+
+        assert_eq!(
+            "web:   ".parse::<Procfile>(),
+            Err(ProcfileParseError::EmptyCommand {
+                line_number: 1,
+                process_type: process_type!("web")
+            })
+        );
+    }
+
+    #[test]
+    fn processes() {
+        let processes = |procfile: &str| {
+            procfile
+                .parse::<Procfile>()
+                .unwrap()
+                .processes()
+                .into_iter()
+                .map(|process| (process.r#type.to_string(), process.default))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            processes("worker: sidekiq\nweb: puma"),
+            [(String::from("worker"), false), (String::from("web"), true)]
+        );
+        assert_eq!(
+            processes("worker: sidekiq"),
+            [(String::from("worker"), true)]
+        );
+        assert_eq!(
+            processes("worker: sidekiq\nrelease: rake db:migrate"),
+            [
+                (String::from("worker"), false),
+                (String::from("release"), false)
+            ]
+        );
+
+        let process = &Procfile::from_str("web: puma -p $PORT")
+            .unwrap()
+            .processes()[0];
+        assert_eq!(process.command, ["bash", "-c", "puma -p $PORT"]);
+        assert!(process.args.is_empty());
+    }
+
+    #[test]
+    fn read() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("Procfile");
+
+        assert!(Procfile::read(&path).unwrap().is_none());
+
+        fs::write(&path, "web: puma").unwrap();
+        assert_eq!(
+            Procfile::read(&path)
+                .unwrap()
+                .unwrap()
+                .get(&process_type!("web")),
+            Some("puma")
+        );
+    }
+}
@@ -0,0 +1,367 @@
+//! Resolves the version requirement of a dependency, such as the Node.js or Python version, from
+//! the places users and other buildpacks can specify it.
+//!
+//! The sources are considered in the following order, the first source that specifies a
+//! requirement wins:
+//!
+//! 1. A `BP_*` environment variable of the platform, i.e. `BP_NODE_VERSION`. These are usually
+//!    set for a single build (`pack build --env`) and therefore override everything else.
+//! 2. A key in the app's `project.toml`, i.e. `com.heroku.buildpacks.nodejs.version`.
+//! 3. The metadata of a buildpack plan entry, i.e. the `version` of a `node` requirement that
+//!    another buildpack added to the build plan.
+//! 4. The default requirement of the buildpack.
+//!
+//! Lower precedence sources are not validated if a higher precedence source specifies a
+//! requirement. The source of the effective requirement is returned along with it, so buildpacks
+//! can tell users where a version came from.
+
+use libcnb::data::buildpack_plan::BuildpackPlan;
+use libcnb::Env;
+use std::ffi::OsString;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Collects the sources of a version requirement and resolves the effective requirement.
+///
+/// # Example
+/// ```
+/// use libcnb::data::buildpack_plan::BuildpackPlan;
+/// use libcnb::Env;
+/// use libherokubuildpack::version_requirement::{
+///     VersionRequirementResolver, VersionRequirementSource,
+/// };
+///
+/// let mut env = Env::new();
+/// env.insert("BP_NODE_VERSION", "22.x");
+///
+/// let project_descriptor: toml::Table = toml::from_str(
+///     r#"
+///     [com.heroku.buildpacks.nodejs]
+///     version = "20.x"
+///     "#,
+/// )
+/// .unwrap();
+///
+/// let resolved = VersionRequirementResolver::new()
+///     .env_var(&env, "BP_NODE_VERSION")
+///     .project_descriptor(&project_descriptor, &["com", "heroku", "buildpacks", "nodejs", "version"])
+///     .buildpack_plan_entry(&BuildpackPlan { entries: Vec::new() }, "node", "version")
+///     .default_requirement("*")
+///     .resolve::<String>()
+///     .unwrap()
+///     .unwrap();
+///
+/// assert_eq!(resolved.requirement, "22.x");
+/// assert_eq!(
+///     resolved.source,
+///     VersionRequirementSource::EnvVar(String::from("BP_NODE_VERSION"))
+/// );
+/// assert_eq!(
+///     resolved.source.to_string(),
+///     "the `BP_NODE_VERSION` environment variable"
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+#[must_use]
+pub struct VersionRequirementResolver {
+    env_var: Option<(String, OsString)>,
+    project_descriptor: Option<(String, Option<toml::Value>)>,
+    buildpack_plan_entry: Option<(String, String, Option<toml::Value>)>,
+    default_requirement: Option<String>,
+}
+
+impl VersionRequirementResolver {
+    /// Creates a resolver without any sources.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses the value of the given environment variable, if it's set to a non-empty value.
+    pub fn env_var(mut self, env: &Env, name: impl Into<String>) -> Self {
+        let name = name.into();
+        self.env_var = env
+            .get(&name)
+            .filter(|value| !value.is_empty())
+            .cloned()
+            .map(|value| (name, value));
+        self
+    }
+
+    /// Uses the value at the given key path of the parsed `project.toml`, if present.
+    pub fn project_descriptor(
+        mut self,
+        project_descriptor: &toml::Table,
+        key_path: &[&str],
+    ) -> Self {
+        let value = key_path
+            .split_first()
+            .and_then(|(first, rest)| {
+                rest.iter()
+                    .try_fold(project_descriptor.get(*first)?, |value, key| value.get(key))
+            })
+            .cloned();
+
+        self.project_descriptor = Some((key_path.join("."), value));
+        self
+    }
+
+    /// Uses the given metadata key of the first buildpack plan entry with the given name that has
+    /// this key.
+    pub fn buildpack_plan_entry(
+        mut self,
+        buildpack_plan: &BuildpackPlan,
+        entry_name: impl Into<String>,
+        metadata_key: impl Into<String>,
+    ) -> Self {
+        let entry_name = entry_name.into();
+        let metadata_key = metadata_key.into();
+
+        let value = buildpack_plan
+            .entries
+            .iter()
+            .filter(|entry| entry.name == entry_name)
+            .find_map(|entry| entry.metadata.get(&metadata_key))
+            .cloned();
+
+        self.buildpack_plan_entry = Some((entry_name, metadata_key, value));
+        self
+    }
+
+    /// Uses the given requirement if no other source specifies one.
+    pub fn default_requirement(mut self, requirement: impl Into<String>) -> Self {
+        self.default_requirement = Some(requirement.into());
+        self
+    }
+
+    /// Parses the requirement of the highest precedence source that specifies one.
+    ///
+    /// Returns `None` if no source specifies a requirement.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the requirement of the highest precedence source is not a string or
+    /// couldn't be parsed.
+    pub fn resolve<R: FromStr>(
+        self,
+    ) -> Result<Option<ResolvedVersionRequirement<R>>, VersionRequirementError<R::Err>> {
+        let (source, value) = if let Some((name, value)) = self.env_var {
+            let source = VersionRequirementSource::EnvVar(name);
+            match value.into_string() {
+                Ok(value) => (source, value),
+                Err(_) => return Err(VersionRequirementError::NotAString(source)),
+            }
+        } else if let Some((key, Some(value))) = self.project_descriptor {
+            string_value(VersionRequirementSource::ProjectDescriptor(key), value)?
+        } else if let Some((entry_name, metadata_key, Some(value))) = self.buildpack_plan_entry {
+            string_value(
+                VersionRequirementSource::BuildpackPlanEntry {
+                    entry_name,
+                    metadata_key,
+                },
+                value,
+            )?
+        } else if let Some(value) = self.default_requirement {
+            (VersionRequirementSource::Default, value)
+        } else {
+            return Ok(None);
+        };
+
+        match value.trim().parse() {
+            Ok(requirement) => Ok(Some(ResolvedVersionRequirement {
+                requirement,
+                source,
+            })),
+            Err(error) => Err(VersionRequirementError::InvalidRequirement {
+                requirement_source: source,
+                value,
+                error,
+            }),
+        }
+    }
+}
+
+fn string_value<E>(
+    source: VersionRequirementSource,
+    value: toml::Value,
+) -> Result<(VersionRequirementSource, String), VersionRequirementError<E>> {
+    match value {
+        toml::Value::String(value) => Ok((source, value)),
+        _ => Err(VersionRequirementError::NotAString(source)),
+    }
+}
+
+/// The effective version requirement and the source that specified it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ResolvedVersionRequirement<R> {
+    pub requirement: R,
+    pub source: VersionRequirementSource,
+}
+
+/// The source of a version requirement.
+///
+/// The [`Display`] implementation is meant to be used in build output, i.e.
+/// `format!("Using Node.js version requirement {requirement} from {source}.")`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum VersionRequirementSource {
+    /// An environment variable of the platform, with the given name.
+    EnvVar(String),
+    /// A key of `project.toml`, as a dotted key path.
+    ProjectDescriptor(String),
+    /// The metadata of a buildpack plan entry.
+    BuildpackPlanEntry {
+        entry_name: String,
+        metadata_key: String,
+    },
+    /// The default requirement of the buildpack.
+    Default,
+}
+
+impl Display for VersionRequirementSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EnvVar(name) => write!(f, "the `{name}` environment variable"),
+            Self::ProjectDescriptor(key) => write!(f, "`{key}` in `project.toml`"),
+            Self::BuildpackPlanEntry {
+                entry_name,
+                metadata_key,
+            } => write!(
+                f,
+                "the `{metadata_key}` of the `{entry_name}` buildpack plan entry"
+            ),
+            Self::Default => write!(f, "the buildpack default"),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum VersionRequirementError<E> {
+    #[error("The version requirement from {0} is not a string")]
+    NotAString(VersionRequirementSource),
+    #[error("Invalid version requirement `{value}` from {requirement_source}: {error}")]
+    InvalidRequirement {
+        requirement_source: VersionRequirementSource,
+        value: String,
+        error: E,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libcnb::data::buildpack_plan::Entry;
+
+    fn buildpack_plan(metadata: &str) -> BuildpackPlan {
+        BuildpackPlan {
+            entries: vec![
+                Entry {
+                    name: String::from("node"),
+                    metadata: toml::Table::new(),
+                },
+                Entry {
+                    name: String::from("node"),
+                    metadata: toml::from_str(metadata).unwrap(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn resolve_precedence() {
+        let mut env = Env::new();
+        env.insert("BP_NODE_VERSION", "22");
+        let project_descriptor = toml::from_str("[nodejs]\nversion = \"20\"").unwrap();
+        let plan = buildpack_plan("version = \"18\"");
+
+        let resolver = VersionRequirementResolver::new()
+            .project_descriptor(&project_descriptor, &["nodejs", "version"])
+            .buildpack_plan_entry(&plan, "node", "version")
+            .default_requirement("16");
+
+        assert_eq!(
+            resolver
+                .clone()
+                .env_var(&env, "BP_NODE_VERSION")
+                .resolve::<u32>()
+                .unwrap(),
+            Some(ResolvedVersionRequirement {
+                requirement: 22,
+                source: VersionRequirementSource::EnvVar(String::from("BP_NODE_VERSION"))
+            })
+        );
+
+        env.insert("BP_NODE_VERSION", "");
+        assert_eq!(
+            resolver
+                .clone()
+                .env_var(&env, "BP_NODE_VERSION")
+                .resolve::<u32>()
+                .unwrap(),
+            Some(ResolvedVersionRequirement {
+                requirement: 20,
+                source: VersionRequirementSource::ProjectDescriptor(String::from("nodejs.version"))
+            })
+        );
+
+        assert_eq!(
+            resolver
+                .clone()
+                .project_descriptor(&toml::Table::new(), &["nodejs", "version"])
+                .resolve::<u32>()
+                .unwrap(),
+            Some(ResolvedVersionRequirement {
+                requirement: 18,
+                source: VersionRequirementSource::BuildpackPlanEntry {
+                    entry_name: String::from("node"),
+                    metadata_key: String::from("version")
+                }
+            })
+        );
+
+        assert_eq!(
+            resolver
+                .project_descriptor(&toml::Table::new(), &["nodejs", "version"])
+                .buildpack_plan_entry(&buildpack_plan(""), "node", "version")
+                .resolve::<u32>()
+                .unwrap(),
+            Some(ResolvedVersionRequirement {
+                requirement: 16,
+                source: VersionRequirementSource::Default
+            })
+        );
+
+        assert_eq!(
+            VersionRequirementResolver::new().resolve::<u32>().unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn resolve_errors() {
+        let project_descriptor = toml::from_str("[nodejs]\nversion = 20").unwrap();
+        let error = VersionRequirementResolver::new()
+            .project_descriptor(&project_descriptor, &["nodejs", "version"])
+            .resolve::<u32>()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The version requirement from `nodejs.version` in `project.toml` is not a string"
+        );
+
+        let error = VersionRequirementResolver::new()
+            .buildpack_plan_entry(&buildpack_plan("version = \"latest\""), "node", "version")
+            .default_requirement("16")
+            .resolve::<u32>()
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            VersionRequirementError::InvalidRequirement { ref value, .. } if value == "latest"
+        ));
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Invalid version requirement `latest` from the `version` of the `node` buildpack plan entry: {}",
+                "latest".parse::<u32>().unwrap_err()
+            )
+        );
+    }
+}
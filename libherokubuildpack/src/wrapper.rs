@@ -0,0 +1,283 @@
+//! Helpers for writing wrapper scripts into layer `bin` directories.
+//!
+//! Wrapper scripts are small shell scripts that set up the environment for a program before
+//! executing it, i.e. to pass additional arguments or to source an environment file. Hand-written
+//! wrappers commonly get quoting wrong or fork the wrapped program instead of replacing the shell
+//! process, which breaks signal forwarding. The scripts generated by this module always quote all
+//! values and `exec` the wrapped program, passing through all arguments with `"$@"`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A wrapper script that executes another program.
+///
+/// # Example
+/// ```no_run
+/// use libherokubuildpack::wrapper::WrapperScript;
+///
+/// WrapperScript::new("ruby", "/layers/heroku_ruby/ruby/bin/ruby")
+///     .arg("--disable-gems")
+///     .env("RUBYOPT", "-W0")
+///     .source("/layers/heroku_ruby/ruby/env.sh")
+///     .write_to("/layers/heroku_ruby/wrappers/bin")
+///     .unwrap();
+/// ```
+///
+/// The generated script looks like this:
+///
+/// ```bash
+/// #!/usr/bin/env bash
+/// . '/layers/heroku_ruby/ruby/env.sh'
+/// export RUBYOPT='-W0'
+/// exec '/layers/heroku_ruby/ruby/bin/ruby' '--disable-gems' "$@"
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct WrapperScript {
+    name: String,
+    program: PathBuf,
+    args: Vec<String>,
+    env: BTreeMap<String, String>,
+    source_files: Vec<PathBuf>,
+}
+
+impl WrapperScript {
+    /// Creates a new wrapper script with the given file name that executes the given program.
+    pub fn new(name: impl Into<String>, program: impl Into<PathBuf>) -> Self {
+        Self {
+            name: name.into(),
+            program: program.into(),
+            args: Vec::new(),
+            env: BTreeMap::new(),
+            source_files: Vec::new(),
+        }
+    }
+
+    /// Adds an argument that is passed to the program before the arguments of the wrapper script.
+    #[must_use]
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Exports an environment variable before executing the program.
+    #[must_use]
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
+
+    /// Sources the given shell file before the environment variables are exported and the program
+    /// is executed.
+    #[must_use]
+    pub fn source(mut self, path: impl Into<PathBuf>) -> Self {
+        self.source_files.push(path.into());
+        self
+    }
+
+    /// Renders the contents of the wrapper script.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the wrapper script is invalid, see [`WrapperScriptError`].
+    pub fn render(&self) -> Result<String, WrapperScriptError> {
+        self.validate()?;
+
+        let mut lines = vec![String::from("#!/usr/bin/env bash")];
+
+        for source_file in &self.source_files {
+            lines.push(format!(". {}", shell_quote(&path_to_string(source_file)?)));
+        }
+
+        for (key, value) in &self.env {
+            lines.push(format!("export {key}={}", shell_quote(value)));
+        }
+
+        let exec_words = std::iter::once(path_to_string(&self.program)?)
+            .chain(self.args.iter().cloned())
+            .map(|word| shell_quote(&word))
+            .collect::<Vec<_>>();
+
+        lines.push(format!("exec {} \"$@\"", exec_words.join(" ")));
+
+        Ok(lines.join("\n") + "\n")
+    }
+
+    /// Writes the wrapper script into the given directory and makes it executable.
+    ///
+    /// The directory is created if it doesn't exist. An existing file with the same name is
+    /// overwritten. Returns the path of the written script.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the wrapper script is invalid or couldn't be written.
+    pub fn write_to(&self, dir: impl AsRef<Path>) -> Result<PathBuf, WrapperScriptError> {
+        let script = self.render()?;
+        let path = dir.as_ref().join(&self.name);
+
+        fs::create_dir_all(dir.as_ref()).map_err(WrapperScriptError::IoError)?;
+        fs::write(&path, script).map_err(WrapperScriptError::IoError)?;
+
+        #[cfg(target_family = "unix")]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+                .map_err(WrapperScriptError::IoError)?;
+        }
+
+        Ok(path)
+    }
+
+    fn validate(&self) -> Result<(), WrapperScriptError> {
+        if self.name.is_empty()
+            || self.name == "."
+            || self.name == ".."
+            || self.name.contains(['/', '\0'])
+        {
+            return Err(WrapperScriptError::InvalidName(self.name.clone()));
+        }
+
+        if let Some(key) = self.env.keys().find(|key| !is_valid_env_var_name(key)) {
+            return Err(WrapperScriptError::InvalidEnvironmentVariableName(
+                key.clone(),
+            ));
+        }
+
+        let contains_nul = self
+            .args
+            .iter()
+            .chain(self.env.values())
+            .any(|value| value.contains('\0'));
+
+        if contains_nul {
+            return Err(WrapperScriptError::NulByte);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum WrapperScriptError {
+    #[error("Invalid wrapper script name: {0}")]
+    InvalidName(String),
+
+    #[error("Invalid environment variable name: {0}")]
+    InvalidEnvironmentVariableName(String),
+
+    #[error("Path is not valid UTF-8: {}", .0.display())]
+    NonUtf8Path(PathBuf),
+
+    #[error("Wrapper script values must not contain NUL bytes")]
+    NulByte,
+
+    #[error("I/O error while writing wrapper script: {0}")]
+    IoError(std::io::Error),
+}
+
+// Quotes the given value with single quotes, so that the shell doesn't perform any expansion.
+// Single quotes within the value are escaped by closing the quoted string, adding an escaped
+// single quote and reopening the quoted string.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn path_to_string(path: &Path) -> Result<String, WrapperScriptError> {
+    path.to_str()
+        .filter(|path| !path.contains('\0'))
+        .map(String::from)
+        .ok_or_else(|| WrapperScriptError::NonUtf8Path(path.to_path_buf()))
+}
+
+fn is_valid_env_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars
+        .next()
+        .is_some_and(|char| char.is_ascii_alphabetic() || char == '_')
+        && chars.all(|char| char.is_ascii_alphanumeric() || char == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::tempdir;
+
+    #[test]
+    fn render() {
+        let script = WrapperScript::new("ruby", "/layers/ruby/bin/ruby")
+            .arg("--disable-gems")
+            .arg("it's")
+            .env("RUBYOPT", "-W0 $HOME")
+            .source("/layers/ruby/env.sh")
+            .render()
+            .unwrap();
+
+        assert_eq!(
+            script,
+            r#"#!/usr/bin/env bash
+. '/layers/ruby/env.sh'
+export RUBYOPT='-W0 $HOME'
+exec '/layers/ruby/bin/ruby' '--disable-gems' 'it'\''s' "$@"
+"#
+        );
+    }
+
+    #[test]
+    fn render_invalid() {
+        assert!(matches!(
+            WrapperScript::new("bin/ruby", "ruby").render(),
+            Err(WrapperScriptError::InvalidName(_))
+        ));
+        assert!(matches!(
+            WrapperScript::new("..", "ruby").render(),
+            Err(WrapperScriptError::InvalidName(_))
+        ));
+        assert!(matches!(
+            WrapperScript::new("ruby", "ruby")
+                .env("1FOO", "bar")
+                .render(),
+            Err(WrapperScriptError::InvalidEnvironmentVariableName(_))
+        ));
+        assert!(matches!(
+            WrapperScript::new("ruby", "ruby")
+                .env("FOO; rm", "bar")
+                .render(),
+            Err(WrapperScriptError::InvalidEnvironmentVariableName(_))
+        ));
+        assert!(matches!(
+            WrapperScript::new("ruby", "ruby").arg("a\0b").render(),
+            Err(WrapperScriptError::NulByte)
+        ));
+    }
+
+    #[test]
+    #[cfg(target_family = "unix")]
+    fn write_to_and_execute() {
+        let temp_dir = tempdir().unwrap();
+        let env_file = temp_dir.path().join("env.sh");
+        fs::write(&env_file, "export FROM_ENV_FILE='sourced'\n").unwrap();
+
+        let script_path = WrapperScript::new("wrapper", "/usr/bin/env")
+            .arg("bash")
+            .arg("-c")
+            .arg(r#"printf '%s|' "$FROM_ENV_FILE" "$QUOTED" "$@""#)
+            .arg("bash")
+            .env("QUOTED", r#"it's "$HOME" `id`"#)
+            .source(&env_file)
+            .write_to(temp_dir.path().join("bin"))
+            .unwrap();
+
+        let output = Command::new(&script_path)
+            .args(["first arg", "'second'"])
+            .output()
+            .unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            r#"sourced|it's "$HOME" `id`|first arg|'second'|"#
+        );
+    }
+}